@@ -0,0 +1,80 @@
+//! 裁定取引の試行を損益台帳に記録し、入力量の統計を `History` に反映する
+//!
+//! 台帳への書き込みに失敗しても取引自体は済んでいるので、ここでのエラーは警告に留める。
+
+use blockchain::ref_finance::history::get_history;
+use blockchain::ref_finance::history::statistics::Statistics;
+use common::types::TokenAccount;
+use dex::{TokenPairLike, TokenPath};
+use logging::*;
+use persistence::arbitrage_attempt::{
+    ArbitrageAttempt, ArbitrageHop, ArbitrageOutcome, NewArbitrageAttempt,
+};
+
+/// 入力量の統計に使う直近の成功件数
+const HISTORY_WINDOW: i64 = 100;
+
+fn hops(path: &TokenPath) -> Vec<ArbitrageHop> {
+    path.0
+        .iter()
+        .map(|pair| ArbitrageHop {
+            pool_id: pair.pool_id(),
+            token_in: pair.token_in_id().into(),
+            token_out: pair.token_out_id().into(),
+        })
+        .collect()
+}
+
+/// 試行を台帳に記録し、`History` を更新する
+pub(crate) async fn record(
+    start: &TokenAccount,
+    path: &TokenPath,
+    input_amount: u128,
+    estimated_output: u128,
+    outcome: ArbitrageOutcome,
+) {
+    let log = DEFAULT.new(o!("function" => "ledger::record"));
+
+    let inserted =
+        match NewArbitrageAttempt::new(start, &hops(path), input_amount, estimated_output, outcome)
+        {
+            Ok(attempt) => attempt.insert_async().await,
+            Err(e) => Err(e),
+        };
+    match inserted {
+        Ok(attempt) => debug!(log, "recorded";
+            "id" => attempt.id,
+            "status" => &attempt.status,
+        ),
+        Err(e) => {
+            warn!(log, "failed to record arbitrage attempt"; "error" => %e);
+            return;
+        }
+    }
+    refresh_history().await;
+}
+
+/// 直近の成功した試行の入力量から `History` の統計を作り直す
+///
+/// 成功した試行がまだ無ければ既定値のままにする。
+pub(crate) async fn refresh_history() {
+    let log = DEFAULT.new(o!("function" => "ledger::refresh_history"));
+
+    match ArbitrageAttempt::input_statistics_async(HISTORY_WINDOW).await {
+        Ok(Some(stats)) => {
+            get_history()
+                .write()
+                .expect("history lock is only held for plain field access; poisoning is impossible")
+                .inputs = Statistics::new(stats.max, stats.average);
+            debug!(log, "history updated";
+                "inputs.max" => stats.max,
+                "inputs.average" => stats.average,
+            );
+        }
+        Ok(None) => trace!(log, "no successful attempts yet"),
+        Err(e) => warn!(log, "failed to load arbitrage statistics"; "error" => %e),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,35 @@
+use super::*;
+use dex::pool_info::PoolInfoList;
+use dex::test_helpers::simple_pool;
+
+#[test]
+fn test_hops_follow_path() {
+    let list = PoolInfoList::new(vec![
+        simple_pool(7, ["wrap.near", "a.near"], [1_000, 1_000]),
+        simple_pool(9, ["wrap.near", "a.near"], [1_000, 1_000]),
+    ]);
+    let path = TokenPath(vec![
+        list.get(7).unwrap().get_pair(0.into(), 1.into()).unwrap(),
+        list.get(9).unwrap().get_pair(1.into(), 0.into()).unwrap(),
+    ]);
+
+    let hops = hops(&path);
+
+    let wnear: TokenAccount = "wrap.near".parse().unwrap();
+    let a: TokenAccount = "a.near".parse().unwrap();
+    assert_eq!(
+        hops,
+        vec![
+            ArbitrageHop {
+                pool_id: 7,
+                token_in: wnear.clone(),
+                token_out: a.clone(),
+            },
+            ArbitrageHop {
+                pool_id: 9,
+                token_in: a,
+                token_out: wnear,
+            },
+        ]
+    );
+}
@@ -8,12 +8,19 @@ use blockchain::ref_finance::token_account::WNEAR_TOKEN;
 use blockchain::types::MicroNear;
 use blockchain::wallet;
 use common::config::ConfigAccess;
+use common::types::YoctoAmount;
 use dex::TokenPath;
 use dex::errors::Error;
 use logging::*;
 
 use std::time::Duration;
 
+mod ledger;
+mod pool_cache;
+
+use persistence::arbitrage_attempt::ArbitrageOutcome;
+use pool_cache::PoolCache;
+
 type Result<T> = anyhow::Result<T>;
 
 fn token_not_found_wait(cfg: &impl ConfigAccess) -> Duration {
@@ -40,8 +47,25 @@ pub async fn run(cfg: common::config::ConfigResolver) {
     }
     let client = jsonrpc::new_client();
     let wallet = wallet::new_wallet();
+    let access_keys = access_key_pool(&client, &wallet, &cfg).await;
+    ledger::refresh_history().await;
+    let mut pools = loop {
+        match PoolCache::load().await {
+            Ok(pools) => break pools,
+            Err(err) => {
+                let wait = other_error_wait(&cfg);
+                warn!(log, "failed to load pools, retrying after {:?}", wait; "error" => %err);
+                tokio::time::sleep(wait).await;
+            }
+        }
+    };
     loop {
-        match single_loop(&client, &wallet, &cfg).await {
+        // function-call access key があればスワップはそちらで署名し、trade と nonce を分ける
+        let result = match access_keys.acquire() {
+            Some(key) => single_loop(&client, &wallet, key, &mut pools, &cfg).await,
+            None => single_loop(&client, &wallet, &wallet, &mut pools, &cfg).await,
+        };
+        match result {
             Ok(_) => info!(log, "success, go next"),
             Err(err) => {
                 warn!(log, "failure: {:?}", err);
@@ -64,11 +88,56 @@ pub async fn run(cfg: common::config::ConfigResolver) {
     }
 }
 
-async fn single_loop<C, W>(client: &C, wallet: &W, cfg: &impl ConfigAccess) -> crate::Result<()>
+/// スワップ用の function-call access key を用意する
+///
+/// 登録に失敗した場合は空のプールを返し、root の鍵で署名する。
+async fn access_key_pool<C>(
+    client: &C,
+    root: &wallet::StandardWallet,
+    cfg: &impl ConfigAccess,
+) -> wallet::AccessKeyPool
+where
+    C: jsonrpc::SendTx + jsonrpc::AccessKeyInfo,
+{
+    let log = DEFAULT.new(o!("function" => "access_key_pool"));
+    let size = cfg.access_key_pool_size();
+    let pool = match wallet::AccessKeyPool::derive(root, size) {
+        Ok(pool) => pool,
+        Err(err) => {
+            warn!(log, "failed to derive access keys, signing with root key"; "error" => %err);
+            return wallet::AccessKeyPool::empty(root);
+        }
+    };
+    if pool.is_empty() {
+        return pool;
+    }
+    match pool.provision(client).await {
+        Ok(added) => {
+            info!(log, "swaps will be signed with function-call access keys";
+                "size" => size,
+                "added" => added,
+            );
+            pool
+        }
+        Err(err) => {
+            warn!(log, "failed to provision access keys, signing with root key"; "error" => %err);
+            wallet::AccessKeyPool::empty(root)
+        }
+    }
+}
+
+async fn single_loop<C, W, S>(
+    client: &C,
+    wallet: &W,
+    swap_wallet: &S,
+    pools: &mut PoolCache,
+    cfg: &impl ConfigAccess,
+) -> crate::Result<()>
 where
     C: jsonrpc::AccountInfo + jsonrpc::SendTx + jsonrpc::ViewContract + jsonrpc::GasInfo,
     <C as jsonrpc::SendTx>::Output: std::fmt::Display,
     W: wallet::Wallet,
+    S: wallet::Wallet,
 {
     let log = DEFAULT.new(o!("function" => "single_loop"));
 
@@ -82,56 +151,124 @@ where
         "start.balance_in_micro" => ?start_balance,
     );
 
-    let pools = persistence::pool_info::read_from_db(None).await?;
-
-    let graph = ref_finance::path::graph::TokenGraph::new(pools);
+    let graph = ref_finance::path::graph::TokenGraph::new(pools.snapshot().await?);
     let gas_price = client.get_gas_price(None).await?;
     let previews = ref_finance::path::pick_previews(&graph, &start, start_balance, gas_price)?;
 
-    if let Some(previews) = previews {
-        let (pre_path, tokens) = previews.into_with_path(&graph, &start).await?;
-        for (_, path) in &pre_path {
-            path.validate_length()?;
-        }
+    let mut pre_path = match previews {
+        Some(previews) => previews.into_with_path(&graph, &start).await?.0,
+        None => Vec::new(),
+    };
+    // 往復では拾えない三角形以上の循環も候補に加える
+    pre_path.extend(ref_finance::path::pick_cycle_previews(
+        &graph,
+        &start,
+        start_balance,
+        gas_price,
+    )?);
+    if pre_path.is_empty() {
+        info!(log, "previews not found");
+        tokio::time::sleep(preview_not_found_wait(cfg)).await;
+        return Ok(());
+    }
+    pre_path.sort_by_key(|(preview, _)| std::cmp::Reverse(preview.gain));
 
-        let max_top_up = ref_finance::storage::max_top_up_from_config(cfg);
-        // keep: 裁定取引は毎回異なるパスを使うため、基軸通貨の WNEAR のみ保持
-        let keep = ref_finance::storage::keep_wnear_only();
-        ref_finance::storage::ensure_ref_storage_setup(client, wallet, &tokens, &keep, max_top_up)
-            .await?;
-
-        // スワップを順次実行（nonce衝突を回避）
-        let mut success_count = 0;
-        let total_count = pre_path.len();
-
-        for (preview, path) in pre_path {
-            match swap_each(client, wallet, preview, path).await {
-                Ok(_) => {
-                    success_count += 1;
-                    // Arbitrageの場合は1つ成功したら終了
-                    info!(log, "arbitrage swap successful, stopping further attempts");
-                    break;
-                }
-                Err(e) => {
-                    warn!(log, "swap attempt failed, trying next path if available"; "error" => %e);
-                }
+    let mut tokens = Vec::new();
+    for (_, path) in &pre_path {
+        path.validate_length()?;
+        tokens.extend(path.all_tokens());
+    }
+    tokens.sort();
+    tokens.dedup();
+
+    let max_top_up = ref_finance::storage::max_top_up_from_config(cfg);
+    // keep: 裁定取引は毎回異なるパスを使うため、基軸通貨の WNEAR のみ保持
+    let keep = ref_finance::storage::keep_wnear_only();
+    ref_finance::storage::ensure_ref_storage_setup(client, wallet, &tokens, &keep, max_top_up)
+        .await?;
+
+    // 候補を利益順に試し、最初に成功した時点で打ち切る
+    let mut success_count = 0;
+    let total_count = pre_path.len();
+
+    for (preview, path) in pre_path {
+        // 探索に使ったプール状態は古い可能性があるので、実行直前に取り直して見積もり直す
+        let (preview, path) = match reverify(client, pools, preview, &path).await {
+            Ok(Some(verified)) => verified,
+            Ok(None) => {
+                metrics::arbitrage::record_stale();
+                info!(
+                    log,
+                    "preview is no longer profitable on fresh pools, skipping"
+                );
+                continue;
+            }
+            Err(e) => {
+                warn!(log, "failed to re-verify preview, trying next path if available"; "error" => %e);
+                continue;
+            }
+        };
+        match swap_each(client, swap_wallet, preview, path).await {
+            Ok(_) => {
+                success_count += 1;
+                // Arbitrageの場合は1つ成功したら終了
+                info!(log, "arbitrage swap successful, stopping further attempts");
+                break;
+            }
+            Err(e) => {
+                warn!(log, "swap attempt failed, trying next path if available"; "error" => %e);
             }
         }
-
-        info!(log, "swaps completed";
-            "success" => format!("{}/{}", success_count, total_count),
-        );
-    } else {
-        info!(log, "previews not found");
-        tokio::time::sleep(preview_not_found_wait(cfg)).await;
     }
 
+    info!(log, "swaps completed";
+        "success" => format!("{}/{}", success_count, total_count),
+    );
+
     Ok(())
 }
 
 #[cfg(test)]
 mod tests;
 
+/// 経路上のプールを最新化して `preview` を見積もり直す
+///
+/// ガス代を差し引いた利益が残らなければ `None` を返す。
+async fn reverify<A, C>(
+    client: &C,
+    pools: &mut PoolCache,
+    preview: Preview<A>,
+    path: &TokenPath,
+) -> crate::Result<Option<(Preview<A>, TokenPath)>>
+where
+    A: Into<u128> + Copy,
+    C: jsonrpc::ViewContract,
+{
+    let log = DEFAULT.new(o!(
+        "function" => "reverify",
+        "preview.output_value" => preview.output_value,
+        "preview.gain" => preview.gain,
+    ));
+
+    let fresh_path = pools.refresh_path(client, path).await?;
+    let output = fresh_path.calc_value(preview.input_value.into())?;
+    let verified = Preview::new(
+        preview.gas_price,
+        preview.input_value,
+        preview.token,
+        fresh_path.len(),
+        output,
+    );
+    debug!(log, "re-verified";
+        "fresh.output_value" => verified.output_value,
+        "fresh.gain" => verified.gain,
+    );
+    if verified.gain == 0 {
+        return Ok(None);
+    }
+    Ok(Some((verified, fresh_path)))
+}
+
 async fn swap_each<A, C, W>(
     client: &C,
     wallet: &W,
@@ -140,7 +277,7 @@ async fn swap_each<A, C, W>(
 ) -> crate::Result<()>
 where
     A: Into<u128> + Copy,
-    C: jsonrpc::SendTx,
+    C: jsonrpc::SendTx + jsonrpc::ViewContract,
     <C as jsonrpc::SendTx>::Output: std::fmt::Display,
     W: wallet::Wallet,
 {
@@ -155,23 +292,85 @@ where
         initial_in: preview.input_value.into(),
         min_out: preview.output_value.saturating_sub(preview.gain),
     };
+    let input_amount = arg.initial_in;
     let swap_result = ref_finance::swap::run_swap(client, wallet, &path.0, arg).await;
 
     let (sent_tx, out) = match swap_result {
         Ok(result) => result,
         Err(e) => {
             error!(log, "swap operation failed"; "error" => ?e);
+            metrics::arbitrage::record_failure();
+            let outcome = ArbitrageOutcome::Failure {
+                tx_hash: None,
+                gas_burnt: None,
+                gas_cost: None,
+                error: format!("{e:#}"),
+            };
+            ledger::record(
+                &WNEAR_TOKEN,
+                &path,
+                input_amount,
+                preview.output_value,
+                outcome,
+            )
+            .await;
             return Err(e);
         }
     };
+    let tx_hash = sent_tx.tx_hash().to_string();
 
-    if let Err(e) = sent_tx.wait_for_success().await {
-        error!(log, "transaction failed"; "tx" => %sent_tx, "error" => %e);
-        return Err(e);
-    }
+    let view = match sent_tx.wait_for_success().await {
+        Ok(view) => view,
+        Err(e) => {
+            // on-chain で失敗してもガスは燃えるので、実行結果があれば消費量を残す
+            let burnt = e
+                .downcast_ref::<jsonrpc::TxFailed>()
+                .map(|failed| ref_finance::swap::extract_burnt(&failed.view));
+            error!(log, "transaction failed";
+                "tx" => %sent_tx,
+                "error" => %e,
+                "gas_burnt" => ?burnt.map(|(gas, _)| gas),
+            );
+            metrics::arbitrage::record_failure();
+            let outcome = ArbitrageOutcome::Failure {
+                tx_hash: Some(tx_hash),
+                gas_burnt: burnt.map(|(gas, _)| gas),
+                gas_cost: burnt.map(|(_, cost)| cost),
+                error: format!("{e:#}"),
+            };
+            ledger::record(
+                &WNEAR_TOKEN,
+                &path,
+                input_amount,
+                preview.output_value,
+                outcome,
+            )
+            .await;
+            return Err(e);
+        }
+    };
 
+    let actual_output = ref_finance::swap::extract_actual_output(&view).ok();
+    let (gas_burnt, gas_cost) = ref_finance::swap::extract_burnt(&view);
     info!(log, "swap done";
         "estimated_output" => out,
+        "actual_output" => ?actual_output,
+        "gas_burnt" => gas_burnt,
     );
+    metrics::arbitrage::record_success(&YoctoAmount::from_u128(preview.gain));
+    let outcome = ArbitrageOutcome::Success {
+        tx_hash,
+        actual_output,
+        gas_burnt,
+        gas_cost,
+    };
+    ledger::record(
+        &WNEAR_TOKEN,
+        &path,
+        input_amount,
+        preview.output_value,
+        outcome,
+    )
+    .await;
     Ok(())
 }
@@ -0,0 +1,76 @@
+//! 裁定取引用のプールキャッシュ
+//!
+//! DB のプール情報は `record_rates` が書き込むため最大で記録間隔ぶん古く、
+//! そこで見つけた循環経路は on-chain ではすでに消えていることが多い。
+//! 経路探索は手元のキャッシュで行い、実行直前に候補経路が通るプールだけを
+//! `get_pool` で取り直して見積もりを検証する。
+
+use crate::Result;
+use blockchain::jsonrpc::ViewContract;
+use dex::{PoolInfoList, TokenPath};
+use logging::*;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// DB から全体を読み直す間隔（`record_rates` の記録間隔に合わせる）
+const RELOAD_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+pub struct PoolCache {
+    pools: Arc<PoolInfoList>,
+    loaded_at: Instant,
+}
+
+impl PoolCache {
+    pub fn new(pools: Arc<PoolInfoList>) -> Self {
+        Self {
+            pools,
+            loaded_at: Instant::now(),
+        }
+    }
+
+    /// DB の最新スナップショットから作る
+    pub async fn load() -> Result<Self> {
+        let pools = persistence::pool_info::read_from_db(None).await?;
+        Ok(Self::new(pools))
+    }
+
+    /// 経路探索に使う現在のプール一覧
+    ///
+    /// 前回の読み込みから `RELOAD_INTERVAL` が過ぎていれば DB から読み直す。
+    /// 個別に取り直したプールもそこで DB の値に戻るが、使う直前に再取得するので問題ない。
+    pub async fn snapshot(&mut self) -> Result<Arc<PoolInfoList>> {
+        if self.is_expired() {
+            *self = Self::load().await?;
+        }
+        Ok(Arc::clone(&self.pools))
+    }
+
+    fn is_expired(&self) -> bool {
+        self.loaded_at.elapsed() >= RELOAD_INTERVAL
+    }
+
+    /// 指定したプールをノードから取り直してキャッシュを更新する
+    pub async fn refresh<C: ViewContract>(&mut self, client: &C, pool_ids: &[u32]) -> Result<()> {
+        let log = DEFAULT.new(o!(
+            "function" => "PoolCache::refresh",
+            "count" => pool_ids.len(),
+        ));
+        let fresh = blockchain::ref_finance::pool_info::get_pools_by_id(client, pool_ids).await?;
+        self.pools = Arc::new(self.pools.with_replaced(fresh));
+        debug!(log, "refreshed"; "pool_ids" => ?pool_ids);
+        Ok(())
+    }
+
+    /// 経路を最新のプールで取り直し、キャッシュ上の状態に組み直したものを返す
+    pub async fn refresh_path<C: ViewContract>(
+        &mut self,
+        client: &C,
+        path: &TokenPath,
+    ) -> Result<TokenPath> {
+        self.refresh(client, &path.pool_ids()).await?;
+        path.rebind(&self.pools)
+    }
+}
+
+#[cfg(test)]
+mod tests;
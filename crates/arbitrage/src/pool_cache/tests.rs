@@ -0,0 +1,162 @@
+use super::*;
+use anyhow::anyhow;
+use blockchain::ref_finance::path::preview::Preview;
+use blockchain::types::gas_price::GasPrice;
+use dex::pool_info::PoolInfoBared;
+use dex::test_helpers::{simple_pool, simple_pool_bare};
+use near_primitives::views::CallResult;
+use near_sdk::{AccountId, NearToken};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+const GAS_PRICE: GasPrice = GasPrice::from_balance(NearToken::from_yoctonear(100_000_000));
+const ONE_NEAR: u128 = NearToken::from_near(1).as_yoctonear();
+const RESERVE: u128 = 1_000 * ONE_NEAR;
+
+/// `get_pool` に指定したプールを返すクライアント
+struct PoolsClient {
+    pools: HashMap<u32, PoolInfoBared>,
+    requested: Mutex<Vec<u32>>,
+}
+
+impl PoolsClient {
+    fn new(pools: Vec<(u32, PoolInfoBared)>) -> Self {
+        Self {
+            pools: pools.into_iter().collect(),
+            requested: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn requested(&self) -> Vec<u32> {
+        let mut ids = self.requested.lock().unwrap().clone();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+impl ViewContract for PoolsClient {
+    async fn view_contract<T>(
+        &self,
+        _receiver: &AccountId,
+        method_name: &str,
+        args: &T,
+    ) -> Result<CallResult>
+    where
+        T: ?Sized + serde::Serialize + Sync,
+    {
+        assert_eq!(method_name, "get_pool");
+        let args = serde_json::to_value(args)?;
+        let pool_id = args["pool_id"].as_u64().unwrap() as u32;
+        self.requested.lock().unwrap().push(pool_id);
+        let bare = self
+            .pools
+            .get(&pool_id)
+            .ok_or_else(|| anyhow!("pool {pool_id} not found"))?;
+        Ok(CallResult {
+            result: serde_json::to_vec(bare)?,
+            logs: vec![],
+        })
+    }
+}
+
+/// wrap.near -> a.near -> wrap.near の循環（DB 上では a.near が割安）
+fn stale_cycle() -> (PoolCache, TokenPath) {
+    let list = PoolInfoList::new(vec![
+        simple_pool(1, ["wrap.near", "a.near"], [RESERVE, 2 * RESERVE]),
+        simple_pool(2, ["a.near", "wrap.near"], [RESERVE, RESERVE]),
+        simple_pool(3, ["b.near", "c.near"], [RESERVE, RESERVE]),
+    ]);
+    let path = TokenPath(vec![
+        list.get(1).unwrap().get_pair(0.into(), 1.into()).unwrap(),
+        list.get(2).unwrap().get_pair(0.into(), 1.into()).unwrap(),
+    ]);
+    (PoolCache::new(Arc::new(list)), path)
+}
+
+#[tokio::test]
+async fn test_refresh_path_fetches_only_touched_pools() {
+    let (mut cache, path) = stale_cycle();
+    let client = PoolsClient::new(vec![
+        (
+            1,
+            simple_pool_bare(["wrap.near", "a.near"], [RESERVE, RESERVE]),
+        ),
+        (
+            2,
+            simple_pool_bare(["a.near", "wrap.near"], [RESERVE, RESERVE]),
+        ),
+    ]);
+
+    let fresh = cache.refresh_path(&client, &path).await.unwrap();
+
+    assert_eq!(client.requested(), vec![1, 2]);
+    assert_eq!(fresh.pool_ids(), vec![1, 2]);
+    assert!(fresh.calc_value(ONE_NEAR).unwrap() < path.calc_value(ONE_NEAR).unwrap());
+
+    // キャッシュも更新され、触れていないプールはそのまま
+    let snapshot = cache.snapshot().await.unwrap();
+    assert_eq!(snapshot.get(1).unwrap().amount(1.into()).unwrap(), RESERVE);
+    assert_eq!(snapshot.get(3).unwrap().amount(0.into()).unwrap(), RESERVE);
+}
+
+#[tokio::test]
+async fn test_refresh_fails_when_pool_is_unavailable() {
+    let (mut cache, path) = stale_cycle();
+    let client = PoolsClient::new(vec![(
+        1,
+        simple_pool_bare(["wrap.near", "a.near"], [RESERVE, RESERVE]),
+    )]);
+
+    assert!(cache.refresh_path(&client, &path).await.is_err());
+}
+
+#[tokio::test]
+async fn test_reverify_keeps_profitable_preview() {
+    let (mut cache, path) = stale_cycle();
+    let client = PoolsClient::new(vec![
+        (
+            1,
+            simple_pool_bare(["wrap.near", "a.near"], [RESERVE, 2 * RESERVE]),
+        ),
+        (
+            2,
+            simple_pool_bare(["a.near", "wrap.near"], [RESERVE, RESERVE]),
+        ),
+    ]);
+    let input = ONE_NEAR;
+    let output = path.calc_value(input).unwrap();
+    let preview = Preview::new(GAS_PRICE, input, "wrap.near".parse().unwrap(), 2, output);
+
+    let verified = crate::reverify(&client, &mut cache, preview.clone(), &path)
+        .await
+        .unwrap();
+
+    let (verified, fresh) = verified.expect("still profitable");
+    assert_eq!(verified.output_value, preview.output_value);
+    assert_eq!(fresh.len(), 2);
+}
+
+#[tokio::test]
+async fn test_reverify_drops_preview_gone_on_chain() {
+    let (mut cache, path) = stale_cycle();
+    // on-chain では価格差が解消している
+    let client = PoolsClient::new(vec![
+        (
+            1,
+            simple_pool_bare(["wrap.near", "a.near"], [RESERVE, RESERVE]),
+        ),
+        (
+            2,
+            simple_pool_bare(["a.near", "wrap.near"], [RESERVE, RESERVE]),
+        ),
+    ]);
+    let input = ONE_NEAR;
+    let output = path.calc_value(input).unwrap();
+    let preview = Preview::new(GAS_PRICE, input, "wrap.near".parse().unwrap(), 2, output);
+
+    let verified = crate::reverify(&client, &mut cache, preview, &path)
+        .await
+        .unwrap();
+
+    assert!(verified.is_none());
+}
@@ -7,6 +7,7 @@ use common::config;
 use humantime::parse_duration;
 use near_crypto::InMemorySigner;
 use near_primitives::action::Action;
+use near_primitives::hash::CryptoHash;
 use near_primitives::types::BlockId;
 use near_primitives::views::{
     CallResult, FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum,
@@ -222,6 +223,10 @@ impl std::fmt::Display for MockSentTx {
 }
 
 impl jsonrpc::SentTx for MockSentTx {
+    fn tx_hash(&self) -> CryptoHash {
+        CryptoHash::hash_bytes(self.id.as_bytes())
+    }
+
     async fn wait_for_executed(&self) -> crate::Result<FinalExecutionOutcomeViewEnum> {
         unimplemented!()
     }
@@ -11,14 +11,27 @@
 
 #![deny(warnings)]
 
-use common::config::ConfigResolver;
+use common::config::{ConfigAccess, ConfigResolver};
 use logging::*;
+use scheduler::{CatchUpPolicy, Job};
 
 #[tokio::main]
 async fn main() {
     let log = DEFAULT.new(o!("function" => "main"));
     info!(log, "Starting up");
 
+    // OTLP へのトレース出力（endpoint 未設定なら無効）。guard は終了時の flush 用に保持する。
+    let _tracing = match logging::otel::init() {
+        Ok(guard) => {
+            info!(log, "tracing initialized"; "enabled" => guard.is_some());
+            guard
+        }
+        Err(e) => {
+            warn!(log, "failed to initialize tracing, continuing without it"; "error" => %e);
+            None
+        }
+    };
+
     let cfg = ConfigResolver;
     let startup = common::config::startup::get();
 
@@ -31,9 +44,16 @@ async fn main() {
     let account_zero = base.derive(0).unwrap();
     info!(log, "Account 0 created"; "pubkey" => %account_zero.pub_base58());
 
-    tokio::spawn(trade::run(cfg));
+    let scheduler = scheduler::global();
+    if let Err(e) = trade::register_jobs(scheduler, cfg).await {
+        error!(log, "failed to register trade jobs"; "error" => %e);
+    }
+    if let Err(e) = scheduler.register(maintenance_job(cfg)) {
+        error!(log, "failed to register db maintenance job"; "error" => %e);
+    }
+    tokio::spawn(scheduler.run(cfg));
     tokio::spawn(arbitrage::run(cfg));
-    tokio::spawn(persistence::maintenance::run(cfg));
+    tokio::spawn(blockchain::jsonrpc::run_head_probe());
     {
         let log = log.clone();
         tokio::spawn(async move {
@@ -42,5 +62,26 @@ async fn main() {
             }
         });
     }
+    if startup.metrics_port != 0 {
+        let log = log.clone();
+        let port = startup.metrics_port;
+        tokio::spawn(async move {
+            if let Err(e) = web::serve_metrics(port).await {
+                error!(log, "metrics endpoint exited"; "error" => %e);
+            }
+        });
+    }
     tokio::signal::ctrl_c().await.ok();
 }
+
+/// DB メンテナンス（REINDEX）ジョブ。停止中に予定時刻を過ぎていたら起動時に1回だけ実行する。
+fn maintenance_job(cfg: ConfigResolver) -> Job {
+    let schedule = scheduler::parse_schedule(
+        &cfg.db_maintenance_cron_schedule(),
+        persistence::maintenance::DEFAULT_CRON_SCHEDULE,
+    );
+    Job::new("db_maintenance", schedule, move || async move {
+        persistence::maintenance::execute(&cfg).await
+    })
+    .with_catch_up(CatchUpPolicy::Latest)
+}
@@ -0,0 +1,171 @@
+//! 同一 receiver への複数の FunctionCall を 1 トランザクションにまとめるビルダー
+//!
+//! 1 つのトランザクション内の action は同じレシートで順に実行され、どれかが失敗すれば
+//! そのレシートの状態変更はすべて巻き戻る。storage の top-up と `register_tokens` のように
+//! 途中で止まると半端な状態が残る呼び出しの組は、まとめて送ることで往復と nonce も減らせる。
+//!
+//! まとめられるのは receiver が同じ呼び出しだけ。`ft_transfer_call` のような
+//! cross-contract 呼び出しの先で起きる処理は別レシートになるため、原子性は呼び出し元の
+//! レシートに限られる。
+
+use crate::Result;
+use crate::jsonrpc::SendTx;
+use logging::*;
+use near_crypto::InMemorySigner;
+use near_primitives::action::{Action, FunctionCallAction};
+use near_primitives::types::Gas;
+use near_sdk::{AccountId, NearToken};
+use thiserror::Error;
+
+/// 1 トランザクションに添付できるガスの上限
+pub const MAX_TX_GAS: Gas = Gas::from_teragas(300);
+
+/// バッチに積む 1 つの FunctionCall
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchCall {
+    pub method_name: String,
+    pub args: Vec<u8>,
+    pub deposit: NearToken,
+    pub gas: Gas,
+}
+
+impl BatchCall {
+    pub fn new<T>(method_name: &str, args: &T, deposit: NearToken, gas: Gas) -> Result<Self>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        Ok(Self {
+            method_name: method_name.to_string(),
+            args: serde_json::to_vec(args)?,
+            deposit,
+            gas,
+        })
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BatchError {
+    #[error("{method} needs {requested} gas but only {remaining} gas remains in the batch")]
+    GasBudgetExceeded {
+        method: String,
+        requested: u64,
+        remaining: u64,
+    },
+    #[error("cannot send an empty batch to {0}")]
+    Empty(AccountId),
+}
+
+/// 同一 receiver 宛ての FunctionCall 列
+///
+/// 添付ガスの合計がガス予算（既定は [`MAX_TX_GAS`]）を超える呼び出しは積めない。
+#[derive(Debug, Clone)]
+pub struct TxBatch {
+    receiver: AccountId,
+    calls: Vec<BatchCall>,
+    gas_budget: Gas,
+}
+
+impl TxBatch {
+    pub fn new(receiver: AccountId) -> Self {
+        Self {
+            receiver,
+            calls: Vec::new(),
+            gas_budget: MAX_TX_GAS,
+        }
+    }
+
+    /// ガス予算を [`MAX_TX_GAS`] 以下に絞る
+    pub fn with_gas_budget(mut self, budget: Gas) -> Self {
+        self.gas_budget = Gas::from_gas(budget.as_gas().min(MAX_TX_GAS.as_gas()));
+        self
+    }
+
+    /// 呼び出しを末尾に積む。ガス予算を超える場合は積まずにエラーを返す
+    pub fn push(&mut self, call: BatchCall) -> std::result::Result<&mut Self, BatchError> {
+        let remaining = self.remaining_gas().as_gas();
+        if call.gas.as_gas() > remaining {
+            return Err(BatchError::GasBudgetExceeded {
+                method: call.method_name,
+                requested: call.gas.as_gas(),
+                remaining,
+            });
+        }
+        self.calls.push(call);
+        Ok(self)
+    }
+
+    pub fn receiver(&self) -> &AccountId {
+        &self.receiver
+    }
+
+    pub fn calls(&self) -> &[BatchCall] {
+        &self.calls
+    }
+
+    pub fn len(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    pub fn total_gas(&self) -> Gas {
+        Gas::from_gas(self.calls.iter().map(|c| c.gas.as_gas()).sum())
+    }
+
+    pub fn remaining_gas(&self) -> Gas {
+        Gas::from_gas(
+            self.gas_budget
+                .as_gas()
+                .saturating_sub(self.total_gas().as_gas()),
+        )
+    }
+
+    pub fn total_deposit(&self) -> NearToken {
+        self.calls
+            .iter()
+            .fold(NearToken::from_yoctonear(0), |acc, c| {
+                acc.saturating_add(c.deposit)
+            })
+    }
+
+    pub fn into_actions(self) -> Vec<Action> {
+        self.calls
+            .into_iter()
+            .map(|call| {
+                Action::FunctionCall(
+                    FunctionCallAction {
+                        method_name: call.method_name,
+                        args: call.args,
+                        gas: call.gas,
+                        deposit: call.deposit,
+                    }
+                    .into(),
+                )
+            })
+            .collect()
+    }
+
+    /// 1 つの署名済みトランザクションとして送信する
+    pub async fn send<C: SendTx>(self, client: &C, signer: &InMemorySigner) -> Result<C::Output> {
+        let log = DEFAULT.new(o!(
+            "function" => "TxBatch::send",
+            "receiver" => format!("{}", self.receiver),
+            "calls" => self.len(),
+        ));
+        if self.is_empty() {
+            return Err(BatchError::Empty(self.receiver).into());
+        }
+        info!(log, "sending batched transaction";
+            "methods" => ?self.calls.iter().map(|c| c.method_name.as_str()).collect::<Vec<_>>(),
+            "total_gas" => self.total_gas().as_gas(),
+            "total_deposit" => self.total_deposit().as_yoctonear(),
+        );
+        let receiver = self.receiver.clone();
+        client.send_tx(signer, &receiver, self.into_actions()).await
+    }
+}
+
+#[cfg(test)]
+mod tests;
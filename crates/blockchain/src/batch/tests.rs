@@ -0,0 +1,167 @@
+use super::*;
+use crate::jsonrpc::SentTx;
+use near_primitives::hash::CryptoHash;
+use near_primitives::views::{FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum};
+use serde_json::json;
+use std::sync::Mutex;
+
+struct RecordedTx;
+
+impl SentTx for RecordedTx {
+    fn tx_hash(&self) -> CryptoHash {
+        CryptoHash::default()
+    }
+
+    async fn wait_for_executed(&self) -> Result<FinalExecutionOutcomeViewEnum> {
+        unimplemented!()
+    }
+
+    async fn wait_for_success(&self) -> Result<FinalExecutionOutcomeView> {
+        Ok(crate::mock::dummy_final_outcome(vec![]))
+    }
+}
+
+#[derive(Default)]
+struct RecordingClient {
+    sent: Mutex<Vec<(AccountId, Vec<Action>)>>,
+}
+
+impl SendTx for RecordingClient {
+    type Output = RecordedTx;
+
+    async fn transfer_native_token(
+        &self,
+        _signer: &InMemorySigner,
+        _receiver: &AccountId,
+        _amount: NearToken,
+    ) -> Result<Self::Output> {
+        unimplemented!()
+    }
+
+    async fn exec_contract<T>(
+        &self,
+        _signer: &InMemorySigner,
+        _receiver: &AccountId,
+        _method_name: &str,
+        _args: T,
+        _deposit: NearToken,
+    ) -> Result<Self::Output>
+    where
+        T: Sized + serde::Serialize,
+    {
+        unimplemented!()
+    }
+
+    async fn send_tx(
+        &self,
+        _signer: &InMemorySigner,
+        receiver: &AccountId,
+        actions: Vec<Action>,
+    ) -> Result<Self::Output> {
+        self.sent.lock().unwrap().push((receiver.clone(), actions));
+        Ok(RecordedTx)
+    }
+}
+
+fn receiver() -> AccountId {
+    "v2.ref-finance.near".parse().unwrap()
+}
+
+fn signer() -> InMemorySigner {
+    match InMemorySigner::from_seed(
+        "test.near".parse().unwrap(),
+        near_crypto::KeyType::ED25519,
+        "test.near",
+    ) {
+        near_crypto::Signer::InMemory(signer) => signer,
+        _ => panic!("Expected InMemorySigner"),
+    }
+}
+
+fn call(method: &str, tgas: u64, deposit: u128) -> BatchCall {
+    BatchCall::new(
+        method,
+        &json!({}),
+        NearToken::from_yoctonear(deposit),
+        Gas::from_teragas(tgas),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_push_within_budget() {
+    let mut batch = TxBatch::new(receiver());
+    batch
+        .push(call("storage_deposit", 30, 100))
+        .unwrap()
+        .push(call("register_tokens", 200, 1))
+        .unwrap();
+
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch.total_gas(), Gas::from_teragas(230));
+    assert_eq!(batch.remaining_gas(), Gas::from_teragas(70));
+    assert_eq!(batch.total_deposit(), NearToken::from_yoctonear(101));
+}
+
+#[test]
+fn test_push_over_budget_is_rejected() {
+    let mut batch = TxBatch::new(receiver()).with_gas_budget(Gas::from_teragas(100));
+    batch.push(call("a", 60, 0)).unwrap();
+
+    let err = batch.push(call("b", 50, 0)).unwrap_err();
+    assert_eq!(
+        err,
+        BatchError::GasBudgetExceeded {
+            method: "b".to_string(),
+            requested: Gas::from_teragas(50).as_gas(),
+            remaining: Gas::from_teragas(40).as_gas(),
+        }
+    );
+    assert_eq!(batch.len(), 1);
+}
+
+#[test]
+fn test_gas_budget_is_capped_at_tx_limit() {
+    let batch = TxBatch::new(receiver()).with_gas_budget(Gas::from_teragas(1_000));
+    assert_eq!(batch.remaining_gas(), MAX_TX_GAS);
+}
+
+#[tokio::test]
+async fn test_send_builds_one_transaction() {
+    let client = RecordingClient::default();
+    let mut batch = TxBatch::new(receiver());
+    batch
+        .push(call("storage_deposit", 30, 100))
+        .unwrap()
+        .push(call("register_tokens", 200, 1))
+        .unwrap();
+
+    batch.send(&client, &signer()).await.unwrap();
+
+    let sent = client.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    let (to, actions) = &sent[0];
+    assert_eq!(to, &receiver());
+    let methods: Vec<(&str, u128)> = actions
+        .iter()
+        .map(|action| match action {
+            Action::FunctionCall(fc) => (fc.method_name.as_str(), fc.deposit.as_yoctonear()),
+            other => panic!("unexpected action {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        methods,
+        vec![("storage_deposit", 100), ("register_tokens", 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_send_empty_batch_fails() {
+    let client = RecordingClient::default();
+    let err = TxBatch::new(receiver())
+        .send(&client, &signer())
+        .await
+        .unwrap_err();
+    assert!(err.downcast_ref::<BatchError>().is_some());
+    assert!(client.sent.lock().unwrap().is_empty());
+}
@@ -1,11 +1,16 @@
 mod endpoint_pool;
 mod near_client;
+mod nonce;
 mod rpc;
 mod sent_tx;
 
 #[cfg(test)]
 mod near_compat_tests;
 
+pub use endpoint_pool::{EndpointHealthSnapshot, MAX_HEAD_LAG};
+pub use nonce::NonceManager;
+pub use sent_tx::{TxFailed, TxMaybeSent};
+
 use crate::Result;
 use crate::jsonrpc::near_client::StandardNearClient;
 use crate::jsonrpc::rpc::StandardRpcClient;
@@ -32,14 +37,44 @@ static SHARED_ENDPOINT_POOL: LazyLock<Arc<endpoint_pool::EndpointPool>> = LazyLo
     ))
 });
 
-pub fn new_client() -> StandardNearClient<StandardRpcClient> {
+/// 全呼び出し元で共有される NonceManager
+/// trade と arbitrage が同じ鍵で並行に送っても nonce が衝突しないようにする
+static SHARED_NONCE_MANAGER: LazyLock<Arc<NonceManager>> =
+    LazyLock::new(|| Arc::new(NonceManager::default()));
+
+/// head 取得の間隔（NEAR のブロック間隔は約 1 秒）
+const HEAD_PROBE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+fn new_rpc_client() -> StandardRpcClient {
     let retry_limit = common::config::typed().rpc_max_attempts();
-    StandardNearClient::new(&Arc::new(StandardRpcClient::new(
+    StandardRpcClient::new(
         Arc::clone(&SHARED_ENDPOINT_POOL),
         retry_limit,
         std::time::Duration::from_secs(60),
         0.1,
-    )))
+    )
+}
+
+pub fn new_client() -> StandardNearClient<StandardRpcClient> {
+    StandardNearClient::new(&Arc::new(new_rpc_client()), &SHARED_NONCE_MANAGER)
+}
+
+/// 全 endpoint の head を定期的に取得し続ける
+///
+/// 最良 head から遅れた endpoint は `EndpointPool` の
+/// 選択対象から外れる。プロセス起動時に 1 度だけ spawn すること。
+pub async fn run_head_probe() {
+    let client = new_rpc_client();
+    let mut interval = tokio::time::interval(HEAD_PROBE_INTERVAL);
+    loop {
+        interval.tick().await;
+        client.probe_heads().await;
+    }
+}
+
+/// 共有 EndpointPool の各 endpoint の健全性
+pub fn endpoint_health() -> Vec<EndpointHealthSnapshot> {
+    SHARED_ENDPOINT_POOL.snapshot()
 }
 
 pub trait RpcClient {
@@ -51,6 +86,19 @@ pub trait RpcClient {
         M: methods::RpcMethod + std::marker::Send + std::marker::Sync,
         <M as methods::RpcMethod>::Response: std::marker::Send,
         <M as methods::RpcMethod>::Error: std::marker::Send;
+
+    /// 参照系（冪等）の呼び出し。応答が遅い場合に別 endpoint へも投げる実装がある
+    fn call_hedged<M>(
+        &self,
+        method: M,
+    ) -> impl std::future::Future<Output = MethodCallResult<M::Response, M::Error>> + Send
+    where
+        M: methods::RpcMethod + std::marker::Send + std::marker::Sync,
+        <M as methods::RpcMethod>::Response: std::marker::Send,
+        <M as methods::RpcMethod>::Error: std::marker::Send,
+    {
+        self.call(method)
+    }
 }
 
 pub trait BlockInfo {
@@ -110,6 +158,10 @@ pub trait SendTx {
     where
         T: Sized + serde::Serialize;
 
+    /// 署名して送る
+    ///
+    /// ブロードキャストされたか分からないエラーは [`TxMaybeSent`] で返す。
+    /// それ以外のエラーはトランザクションが送られていない。
     async fn send_tx(
         &self,
         signer: &InMemorySigner,
@@ -119,6 +171,11 @@ pub trait SendTx {
 }
 
 pub trait SentTx {
+    /// ブロードキャストしたトランザクションの hash
+    fn tx_hash(&self) -> CryptoHash;
     async fn wait_for_executed(&self) -> Result<FinalExecutionOutcomeViewEnum>;
+    /// 成功して確定するまで待つ
+    ///
+    /// on-chain で失敗した場合は実行結果ごと [`TxFailed`] で返す。
     async fn wait_for_success(&self) -> Result<FinalExecutionOutcomeView>;
 }
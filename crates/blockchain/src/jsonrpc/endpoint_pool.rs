@@ -1,8 +1,22 @@
 use common::config::startup::StartupConfig;
 use rand::Rng;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
+/// レイテンシ・成否を保持する直近サンプル数
+const HEALTH_WINDOW: usize = 100;
+/// パーセンタイルを信用するのに必要な最小サンプル数
+const MIN_LATENCY_SAMPLES: usize = 10;
+/// 最良 head からこのブロック数以上遅れた endpoint は選択対象から外す
+pub const MAX_HEAD_LAG: u64 = 10;
+/// 劣化した endpoint にも回復の機会を残すための最低重み（静的重みに対する比率）
+const MIN_WEIGHT_RATIO: f64 = 0.05;
+/// サンプル不足時の hedge 待ち時間
+const HEDGE_DELAY_DEFAULT: Duration = Duration::from_millis(500);
+const HEDGE_DELAY_MIN: Duration = Duration::from_millis(50);
+const HEDGE_DELAY_MAX: Duration = Duration::from_secs(2);
+
 /// RPC endpoint configuration
 #[derive(Debug, Clone)]
 pub struct RpcEndpoint {
@@ -12,17 +26,82 @@ pub struct RpcEndpoint {
     pub max_retries: u32,
 }
 
-/// Manages multiple RPC endpoints with weighted random selection and failure tracking
+/// Manages multiple RPC endpoints with health-scored weighted random selection
+///
+/// 静的な weight に、直近のレイテンシ・エラー率を掛け合わせた適応重みで選択する。
+/// 最良 head から [`MAX_HEAD_LAG`] ブロック以上遅れた endpoint は選択対象から外す。
 #[derive(Debug)]
 pub struct EndpointPool {
     endpoints: Vec<RpcEndpoint>,
-    failed_endpoints: Arc<Mutex<FailedEndpoints>>,
+    state: Arc<Mutex<PoolState>>,
     failure_reset_seconds: u64,
 }
 
-#[derive(Debug)]
-struct FailedEndpoints {
-    failures: std::collections::HashMap<String, SystemTime>,
+#[derive(Debug, Default)]
+struct PoolState {
+    /// 明示的に失敗扱いにした endpoint と、その解除時刻
+    failures: HashMap<String, SystemTime>,
+    health: HashMap<String, EndpointHealth>,
+}
+
+/// endpoint ごとの直近の振る舞い
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    latencies: VecDeque<Duration>,
+    /// true = endpoint 起因のエラー
+    errors: VecDeque<bool>,
+    head: Option<u64>,
+}
+
+impl EndpointHealth {
+    fn record(&mut self, latency: Duration, ok: bool) {
+        if self.latencies.len() == HEALTH_WINDOW {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+        if self.errors.len() == HEALTH_WINDOW {
+            self.errors.pop_front();
+        }
+        self.errors.push_back(!ok);
+    }
+
+    fn error_rate(&self) -> f64 {
+        if self.errors.is_empty() {
+            return 0.0;
+        }
+        let errors = self.errors.iter().filter(|e| **e).count();
+        errors as f64 / self.errors.len() as f64
+    }
+
+    /// 直近レイテンシの q 分位点（サンプル不足なら None）
+    fn latency_quantile(&self, q: f64) -> Option<Duration> {
+        if self.latencies.len() < MIN_LATENCY_SAMPLES {
+            return None;
+        }
+        let mut sorted: Vec<Duration> = self.latencies.iter().copied().collect();
+        sorted.sort();
+        let idx = ((sorted.len() - 1) as f64 * q).round() as usize;
+        sorted.get(idx).copied()
+    }
+}
+
+/// gRPC やメトリクスに公開する endpoint の健全性
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointHealthSnapshot {
+    pub url: String,
+    pub weight: u32,
+    /// レイテンシ・エラー率を反映した現在の重み
+    pub effective_weight: f64,
+    pub latency_p50: Option<Duration>,
+    pub latency_p95: Option<Duration>,
+    pub error_rate: f64,
+    pub head: Option<u64>,
+    /// 最良 head からの遅れ（ブロック数）
+    pub head_lag: Option<u64>,
+    /// 明示的な失敗扱い中
+    pub failed: bool,
+    /// head の遅れにより選択対象外
+    pub lagging: bool,
 }
 
 impl EndpointPool {
@@ -52,15 +131,22 @@ impl EndpointPool {
             });
         }
 
+        Self::with_endpoints(endpoints, startup.rpc_failure_reset_seconds)
+    }
+
+    fn with_endpoints(endpoints: Vec<RpcEndpoint>, failure_reset_seconds: u64) -> Self {
         Self {
             endpoints,
-            failed_endpoints: Arc::new(Mutex::new(FailedEndpoints {
-                failures: std::collections::HashMap::new(),
-            })),
-            failure_reset_seconds: startup.rpc_failure_reset_seconds,
+            state: Arc::new(Mutex::new(PoolState::default())),
+            failure_reset_seconds,
         }
     }
 
+    /// 設定されている全 endpoint
+    pub fn endpoints(&self) -> &[RpcEndpoint] {
+        &self.endpoints
+    }
+
     /// Select next available endpoint using weighted random selection
     pub fn next_endpoint(&self) -> Option<&RpcEndpoint> {
         use logging::*;
@@ -72,8 +158,8 @@ impl EndpointPool {
         if available.is_empty() {
             warn!(log, "no available endpoints, resetting all failures");
             // Reset all failures if no endpoints available
-            if let Ok(mut failed) = self.failed_endpoints.lock() {
-                failed.failures.clear();
+            if let Ok(mut state) = self.state.lock() {
+                state.failures.clear();
             }
             // Try again with first endpoint
             return self.endpoints.first();
@@ -90,6 +176,80 @@ impl EndpointPool {
         selected
     }
 
+    /// `exclude` 以外から 1 つ選ぶ（hedge 用。候補が無ければ None）
+    pub fn next_endpoint_excluding(&self, exclude: &str) -> Option<&RpcEndpoint> {
+        let available: Vec<&RpcEndpoint> = self
+            .available_endpoints()
+            .into_iter()
+            .filter(|ep| ep.url != exclude)
+            .collect();
+        self.weighted_random_select(&available)
+    }
+
+    /// 1 回のリクエストの所要時間と、endpoint 起因で失敗したかを記録する
+    pub fn record_outcome(&self, url: &str, latency: Duration, ok: bool) {
+        if let Ok(mut state) = self.state.lock() {
+            state
+                .health
+                .entry(url.to_string())
+                .or_default()
+                .record(latency, ok);
+        }
+    }
+
+    /// endpoint が返した最新ブロック高を記録する
+    pub fn record_head(&self, url: &str, height: u64) {
+        if let Ok(mut state) = self.state.lock() {
+            let health = state.health.entry(url.to_string()).or_default();
+            health.head = Some(health.head.map_or(height, |h| h.max(height)));
+        }
+    }
+
+    /// hedge を発火するまでの待ち時間（その endpoint の p95 レイテンシ）
+    pub fn hedge_delay(&self, url: &str) -> Duration {
+        let p95 = self
+            .state
+            .lock()
+            .ok()
+            .and_then(|state| state.health.get(url)?.latency_quantile(0.95));
+        p95.map_or(HEDGE_DELAY_DEFAULT, |d| {
+            d.clamp(HEDGE_DELAY_MIN, HEDGE_DELAY_MAX)
+        })
+    }
+
+    /// 全 endpoint の健全性
+    pub fn snapshot(&self) -> Vec<EndpointHealthSnapshot> {
+        let now = SystemTime::now();
+        let Ok(state) = self.state.lock() else {
+            return vec![];
+        };
+        let best_head = state.best_head();
+        let fastest = state.fastest_median();
+        self.endpoints
+            .iter()
+            .map(|ep| {
+                let health = state.health.get(&ep.url);
+                let head = health.and_then(|h| h.head);
+                let head_lag = best_head.zip(head).map(|(best, h)| best.saturating_sub(h));
+                EndpointHealthSnapshot {
+                    url: ep.url.clone(),
+                    weight: ep.weight,
+                    effective_weight: effective_weight(ep, health, fastest),
+                    latency_p50: health.and_then(|h| h.latency_quantile(0.5)),
+                    latency_p95: health.and_then(|h| h.latency_quantile(0.95)),
+                    error_rate: health.map_or(0.0, EndpointHealth::error_rate),
+                    head,
+                    head_lag,
+                    failed: state
+                        .failures
+                        .get(&ep.url)
+                        .is_some_and(|reset| now < *reset),
+                    lagging: head_lag.is_some_and(|lag| lag >= MAX_HEAD_LAG),
+                }
+            })
+            .collect()
+    }
+
     /// Mark an endpoint as failed
     pub fn mark_failed(&self, url: &str) {
         use logging::*;
@@ -99,37 +259,59 @@ impl EndpointPool {
             "failure_reset_seconds" => self.failure_reset_seconds,
         ));
 
-        if let Ok(mut failed) = self.failed_endpoints.lock() {
-            failed.failures.insert(
+        metrics::rpc::record_endpoint_failed(url);
+        if let Ok(mut state) = self.state.lock() {
+            state.failures.insert(
                 url.to_string(),
                 SystemTime::now() + Duration::from_secs(self.failure_reset_seconds),
             );
             warn!(log, "endpoint marked as failed";
                 "reset_after_seconds" => self.failure_reset_seconds,
-                "total_failed" => failed.failures.len()
+                "total_failed" => state.failures.len()
             );
         }
     }
 
-    /// Get list of currently available (non-failed) endpoints
+    /// Get list of currently available endpoints
+    ///
+    /// 失敗扱い中のものを除き、さらに head が遅れているものを除く。
+    /// 遅れていない endpoint が残らない場合は遅れているものも候補に戻す。
     fn available_endpoints(&self) -> Vec<&RpcEndpoint> {
         let now = SystemTime::now();
-        let failed = self.failed_endpoints.lock().ok();
+        let Ok(state) = self.state.lock() else {
+            return self.endpoints.iter().collect();
+        };
 
-        self.endpoints
+        let not_failed: Vec<&RpcEndpoint> = self
+            .endpoints
             .iter()
             .filter(|ep| {
-                if let Some(ref failed) = failed
-                    && let Some(reset_time) = failed.failures.get(&ep.url)
-                {
-                    return now >= *reset_time;
-                }
-                true
+                state
+                    .failures
+                    .get(&ep.url)
+                    .is_none_or(|reset_time| now >= *reset_time)
             })
-            .collect()
+            .collect();
+
+        let Some(best_head) = state.best_head() else {
+            return not_failed;
+        };
+        let in_sync: Vec<&RpcEndpoint> = not_failed
+            .iter()
+            .copied()
+            .filter(|ep| {
+                let head = state.health.get(&ep.url).and_then(|h| h.head);
+                head.is_none_or(|h| best_head.saturating_sub(h) < MAX_HEAD_LAG)
+            })
+            .collect();
+        if in_sync.is_empty() {
+            not_failed
+        } else {
+            in_sync
+        }
     }
 
-    /// Weighted random selection from available endpoints
+    /// Weighted random selection from available endpoints using adaptive weights
     fn weighted_random_select<'a>(
         &'a self,
         endpoints: &[&'a RpcEndpoint],
@@ -138,24 +320,69 @@ impl EndpointPool {
             return None;
         }
 
-        let total_weight: u32 = endpoints.iter().map(|ep| ep.weight).sum();
-        if total_weight == 0 {
+        let weights: Vec<f64> = match self.state.lock() {
+            Ok(state) => {
+                let fastest = state.fastest_median();
+                endpoints
+                    .iter()
+                    .map(|ep| effective_weight(ep, state.health.get(&ep.url), fastest))
+                    .collect()
+            }
+            Err(_) => endpoints.iter().map(|ep| f64::from(ep.weight)).collect(),
+        };
+        let total_weight: f64 = weights.iter().sum();
+        if total_weight <= 0.0 {
             return endpoints.first().copied();
         }
 
         let mut rng = rand::rng();
-        let mut random = rng.random_range(0..total_weight);
+        let mut random = rng.random_range(0.0..total_weight);
 
-        for ep in endpoints {
-            if random < ep.weight {
+        for (ep, weight) in endpoints.iter().zip(&weights) {
+            if random < *weight {
                 return Some(ep);
             }
-            random -= ep.weight;
+            random -= weight;
         }
 
         endpoints.last().copied()
     }
 }
 
+impl PoolState {
+    /// 全 endpoint が返した中で最も新しい head
+    fn best_head(&self) -> Option<u64> {
+        self.health.values().filter_map(|h| h.head).max()
+    }
+
+    /// 最も速い endpoint の中央レイテンシ（レイテンシ係数の基準）
+    fn fastest_median(&self) -> Option<Duration> {
+        self.health
+            .values()
+            .filter_map(|h| h.latency_quantile(0.5))
+            .min()
+    }
+}
+
+/// 静的 weight × 成功率 × 相対レイテンシ。サンプル不足の要素は 1 として扱う
+fn effective_weight(
+    endpoint: &RpcEndpoint,
+    health: Option<&EndpointHealth>,
+    fastest_median: Option<Duration>,
+) -> f64 {
+    let base = f64::from(endpoint.weight);
+    let Some(health) = health else {
+        return base;
+    };
+    let success = 1.0 - health.error_rate();
+    let latency = match (fastest_median, health.latency_quantile(0.5)) {
+        (Some(fastest), Some(median)) if !median.is_zero() => {
+            (fastest.as_secs_f64() / median.as_secs_f64()).min(1.0)
+        }
+        _ => 1.0,
+    };
+    (base * success * latency).max(base * MIN_WEIGHT_RATIO)
+}
+
 #[cfg(test)]
 mod tests;
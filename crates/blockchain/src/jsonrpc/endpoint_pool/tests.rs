@@ -14,6 +14,10 @@ fn test_startup() -> StartupConfig {
         root_hdpath: "m/44'/397'/0'".to_string(),
         instance_id: "*".to_string(),
         google_client_id: String::new(),
+        metrics_port: 0,
+        otel_exporter_otlp_endpoint: String::new(),
+        otel_service_name: "zaciraci".to_string(),
+        otel_traces_sample_ratio: 1.0,
     }
 }
 
@@ -46,13 +50,7 @@ fn test_weighted_random_select() {
         },
     ];
 
-    let pool = EndpointPool {
-        endpoints: endpoints.clone(),
-        failed_endpoints: Arc::new(Mutex::new(FailedEndpoints {
-            failures: std::collections::HashMap::new(),
-        })),
-        failure_reset_seconds: 300,
-    };
+    let pool = EndpointPool::with_endpoints(endpoints.clone(), 300);
 
     let refs: Vec<&RpcEndpoint> = endpoints.iter().collect();
 
@@ -80,7 +78,7 @@ fn test_mark_failed() {
     pool.mark_failed(&url);
 
     // Check it's in failed list
-    if let Ok(failed) = pool.failed_endpoints.lock() {
+    if let Ok(failed) = pool.state.lock() {
         assert!(failed.failures.contains_key(&url));
     }
 }
@@ -100,13 +98,7 @@ fn test_available_endpoints_excludes_failed() {
         },
     ];
 
-    let pool = EndpointPool {
-        endpoints,
-        failed_endpoints: Arc::new(Mutex::new(FailedEndpoints {
-            failures: std::collections::HashMap::new(),
-        })),
-        failure_reset_seconds: 300,
-    };
+    let pool = EndpointPool::with_endpoints(endpoints, 300);
 
     // Initially all available
     assert_eq!(pool.available_endpoints().len(), 2);
@@ -134,13 +126,7 @@ fn test_rate_limit_triggers_endpoint_switch() {
         },
     ];
 
-    let pool = EndpointPool {
-        endpoints: endpoints.clone(),
-        failed_endpoints: Arc::new(Mutex::new(FailedEndpoints {
-            failures: std::collections::HashMap::new(),
-        })),
-        failure_reset_seconds: 300,
-    };
+    let pool = EndpointPool::with_endpoints(endpoints.clone(), 300);
 
     // Simulate rate limit on endpoint1
     pool.mark_failed("http://endpoint1");
@@ -151,7 +137,7 @@ fn test_rate_limit_triggers_endpoint_switch() {
     assert_eq!(selected.unwrap().url, "http://endpoint2");
 
     // Verify endpoint1 is in failed list
-    if let Ok(failed) = pool.failed_endpoints.lock() {
+    if let Ok(failed) = pool.state.lock() {
         assert!(failed.failures.contains_key("http://endpoint1"));
         assert!(!failed.failures.contains_key("http://endpoint2"));
     }
@@ -172,13 +158,7 @@ fn test_all_endpoints_failed_resets() {
         },
     ];
 
-    let pool = EndpointPool {
-        endpoints: endpoints.clone(),
-        failed_endpoints: Arc::new(Mutex::new(FailedEndpoints {
-            failures: std::collections::HashMap::new(),
-        })),
-        failure_reset_seconds: 300,
-    };
+    let pool = EndpointPool::with_endpoints(endpoints.clone(), 300);
 
     // Mark all endpoints as failed
     pool.mark_failed("http://endpoint1");
@@ -190,7 +170,7 @@ fn test_all_endpoints_failed_resets() {
     assert_eq!(selected.unwrap().url, "http://endpoint1");
 
     // Verify failures were cleared
-    if let Ok(failed) = pool.failed_endpoints.lock() {
+    if let Ok(failed) = pool.state.lock() {
         assert!(failed.failures.is_empty());
     }
 }
@@ -222,3 +202,150 @@ fn test_rpc_endpoints_json_roundtrip() {
     assert_eq!(reparsed[0].url, parsed[0].url);
     assert_eq!(reparsed[1].url, parsed[1].url);
 }
+
+fn two_endpoints() -> Vec<RpcEndpoint> {
+    vec![
+        RpcEndpoint {
+            url: "http://fast".to_string(),
+            weight: 50,
+            max_retries: 3,
+        },
+        RpcEndpoint {
+            url: "http://slow".to_string(),
+            weight: 50,
+            max_retries: 3,
+        },
+    ]
+}
+
+fn find<'a>(snapshot: &'a [EndpointHealthSnapshot], url: &str) -> &'a EndpointHealthSnapshot {
+    snapshot.iter().find(|s| s.url == url).unwrap()
+}
+
+#[test]
+fn test_effective_weight_without_samples_is_static() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    let snapshot = pool.snapshot();
+    assert_eq!(find(&snapshot, "http://fast").effective_weight, 50.0);
+    assert_eq!(find(&snapshot, "http://fast").latency_p95, None);
+    assert_eq!(find(&snapshot, "http://fast").head_lag, None);
+}
+
+#[test]
+fn test_slow_endpoint_gets_lower_weight() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    for _ in 0..20 {
+        pool.record_outcome("http://fast", Duration::from_millis(100), true);
+        pool.record_outcome("http://slow", Duration::from_millis(400), true);
+    }
+
+    let snapshot = pool.snapshot();
+    assert_eq!(find(&snapshot, "http://fast").effective_weight, 50.0);
+    assert_eq!(find(&snapshot, "http://slow").effective_weight, 12.5);
+    assert_eq!(
+        find(&snapshot, "http://slow").latency_p50,
+        Some(Duration::from_millis(400))
+    );
+}
+
+#[test]
+fn test_error_rate_reduces_weight_with_floor() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    for i in 0..20 {
+        pool.record_outcome("http://fast", Duration::from_millis(100), i % 2 == 0);
+        pool.record_outcome("http://slow", Duration::from_millis(100), false);
+    }
+
+    let snapshot = pool.snapshot();
+    let flaky = find(&snapshot, "http://fast");
+    assert_eq!(flaky.error_rate, 0.5);
+    assert_eq!(flaky.effective_weight, 25.0);
+    // 全滅していても回復を観測できるよう最低重みは残す
+    let dead = find(&snapshot, "http://slow");
+    assert_eq!(dead.error_rate, 1.0);
+    assert_eq!(dead.effective_weight, 50.0 * MIN_WEIGHT_RATIO);
+}
+
+#[test]
+fn test_health_window_is_rolling() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    for _ in 0..HEALTH_WINDOW {
+        pool.record_outcome("http://fast", Duration::from_millis(100), false);
+    }
+    for _ in 0..HEALTH_WINDOW {
+        pool.record_outcome("http://fast", Duration::from_millis(100), true);
+    }
+    assert_eq!(find(&pool.snapshot(), "http://fast").error_rate, 0.0);
+}
+
+#[test]
+fn test_lagging_endpoint_is_ejected() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    pool.record_head("http://fast", 1_000);
+    pool.record_head("http://slow", 1_000 - MAX_HEAD_LAG);
+
+    let available = pool.available_endpoints();
+    assert_eq!(available.len(), 1);
+    assert_eq!(available[0].url, "http://fast");
+    for _ in 0..20 {
+        assert_eq!(pool.next_endpoint().unwrap().url, "http://fast");
+    }
+
+    let snapshot = pool.snapshot();
+    assert!(find(&snapshot, "http://slow").lagging);
+    assert_eq!(find(&snapshot, "http://slow").head_lag, Some(MAX_HEAD_LAG));
+    assert!(!find(&snapshot, "http://fast").lagging);
+
+    // 追いつけば選択対象に戻る
+    pool.record_head("http://slow", 1_000);
+    assert_eq!(pool.available_endpoints().len(), 2);
+}
+
+#[test]
+fn test_lagging_endpoint_used_when_leader_failed() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    pool.record_head("http://fast", 1_000);
+    pool.record_head("http://slow", 900);
+    pool.mark_failed("http://fast");
+
+    let available = pool.available_endpoints();
+    assert_eq!(available.len(), 1);
+    assert_eq!(available[0].url, "http://slow");
+}
+
+#[test]
+fn test_record_head_keeps_highest() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    pool.record_head("http://fast", 1_000);
+    pool.record_head("http://fast", 990);
+    assert_eq!(find(&pool.snapshot(), "http://fast").head, Some(1_000));
+}
+
+#[test]
+fn test_hedge_delay_uses_p95() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    assert_eq!(pool.hedge_delay("http://fast"), HEDGE_DELAY_DEFAULT);
+
+    for ms in 1..=100 {
+        pool.record_outcome("http://fast", Duration::from_millis(ms * 10), true);
+    }
+    assert_eq!(pool.hedge_delay("http://fast"), Duration::from_millis(950));
+
+    for _ in 0..HEALTH_WINDOW {
+        pool.record_outcome("http://slow", Duration::from_secs(10), true);
+    }
+    assert_eq!(pool.hedge_delay("http://slow"), HEDGE_DELAY_MAX);
+}
+
+#[test]
+fn test_next_endpoint_excluding() {
+    let pool = EndpointPool::with_endpoints(two_endpoints(), 300);
+    for _ in 0..20 {
+        assert_eq!(
+            pool.next_endpoint_excluding("http://fast").unwrap().url,
+            "http://slow"
+        );
+    }
+    pool.mark_failed("http://slow");
+    assert!(pool.next_endpoint_excluding("http://fast").is_none());
+}
@@ -1,13 +1,17 @@
 use super::{AccessKeyInfo, BlockInfo, RpcClient, SendTx, TxInfo, ViewContract};
 use crate::Result;
-use crate::jsonrpc::sent_tx::StandardSentTx;
+use crate::jsonrpc::nonce::{NonceManager, NonceRecovery};
+use crate::jsonrpc::sent_tx::{StandardSentTx, TxMaybeSent};
 use crate::types::gas_price::GasPrice;
 use anyhow::anyhow;
 use logging::*;
 use near_crypto::InMemorySigner;
+use near_jsonrpc_client::errors::{
+    JsonRpcError, JsonRpcServerError, JsonRpcTransportSendError, RpcTransportError,
+};
 use near_jsonrpc_client::methods;
 use near_jsonrpc_primitives::types::query::QueryResponseKind;
-use near_jsonrpc_primitives::types::transactions::RpcTransactionResponse;
+use near_jsonrpc_primitives::types::transactions::{RpcTransactionError, RpcTransactionResponse};
 use near_primitives::action::{Action, FunctionCallAction, TransferAction};
 use near_primitives::hash::CryptoHash;
 use near_primitives::transaction::{SignedTransaction, Transaction, TransactionV0};
@@ -18,15 +22,36 @@ use near_primitives::views::{
 use near_sdk::{AccountId, NearToken};
 use std::sync::Arc;
 
+/// nonce 起因で拒否された送信を立て直して再送する回数の上限
+const MAX_NONCE_ATTEMPTS: usize = 3;
+
+/// ブロードキャストされていないことが確かなエラーか
+///
+/// 検証での拒否と、リクエストを組み立てられなかった場合だけが該当する。それ以外
+/// （タイムアウト、通信エラー、ノード内部のエラーなど）はブロードキャスト後にも起こりうるため、
+/// 送信済みかどうかは分からない。
+fn rejected_before_broadcast(err: &JsonRpcError<RpcTransactionError>) -> bool {
+    matches!(
+        err,
+        JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcTransactionError::InvalidTransaction { .. }
+        )) | JsonRpcError::TransportError(RpcTransportError::SendError(
+            JsonRpcTransportSendError::PayloadSerializeError(_)
+        ))
+    )
+}
+
 #[derive(Debug)]
 pub struct StandardNearClient<A> {
     rpc: Arc<A>,
+    nonces: Arc<NonceManager>,
 }
 
 impl<A> StandardNearClient<A> {
-    pub fn new(rpc: &Arc<A>) -> Self {
+    pub fn new(rpc: &Arc<A>, nonces: &Arc<NonceManager>) -> Self {
         Self {
             rpc: Arc::clone(rpc),
+            nonces: Arc::clone(nonces),
         }
     }
 }
@@ -35,6 +60,7 @@ impl<A> Clone for StandardNearClient<A> {
     fn clone(&self) -> Self {
         Self {
             rpc: Arc::clone(&self.rpc),
+            nonces: Arc::clone(&self.nonces),
         }
     }
 }
@@ -65,7 +91,7 @@ impl<A: RpcClient> super::AccountInfo for StandardNearClient<A> {
                 account_id: account.clone(),
             },
         };
-        let res = self.rpc.call(req).await?;
+        let res = self.rpc.call_hedged(req).await?;
         if let QueryResponseKind::ViewAccount(am) = res.kind {
             Ok(am.amount)
         } else {
@@ -118,7 +144,7 @@ where
                 args: serde_json::to_vec(args)?.into(),
             },
         };
-        let res = self.rpc.call(req).await?;
+        let res = self.rpc.call_hedged(req).await?;
         match res.kind {
             QueryResponseKind::CallResult(r) => Ok(r),
             other => Err(anyhow!(
@@ -224,36 +250,90 @@ impl<A: RpcClient> SendTx for StandardNearClient<A> {
             "receiver" => format!("{}", receiver),
         ));
 
-        let access_key = self.get_access_key_info(signer).await?;
         let block = self.get_recent_block().await?;
-        let nonce = access_key.nonce + 1;
         let block_hash = block.header.hash;
 
-        let transaction = Transaction::V0(TransactionV0 {
-            signer_id: signer.account_id.clone(),
-            public_key: signer.public_key(),
-            nonce,
-            receiver_id: receiver.clone(),
-            block_hash,
-            actions,
-        });
+        let mut attempt = 1;
+        loop {
+            let nonce = self
+                .nonces
+                .next(signer, || async {
+                    Ok(self.get_access_key_info(signer).await?.nonce)
+                })
+                .await?;
 
-        let (hash, _) = transaction.get_hash_and_size();
-        let signature = signer.sign(hash.as_bytes());
-        let signed_tx = SignedTransaction::new(signature, transaction);
+            let transaction = Transaction::V0(TransactionV0 {
+                signer_id: signer.account_id.clone(),
+                public_key: signer.public_key(),
+                nonce,
+                receiver_id: receiver.clone(),
+                block_hash,
+                actions: actions.clone(),
+            });
 
-        let req = methods::broadcast_tx_async::RpcBroadcastTxAsyncRequest {
-            signed_transaction: signed_tx,
-        };
+            let (hash, _) = transaction.get_hash_and_size();
+            let signature = signer.sign(hash.as_bytes());
+            let signed_tx = SignedTransaction::new(signature, transaction);
 
-        let res = self.rpc.call(req).await?;
-        info!(log, "broadcasted";
-            "response" => %res,
-            "nonce" => nonce,
-            "block_hash" => %block_hash,
-            "public_key" => %signer.public_key(),
-        );
-        let sent_tx = StandardSentTx::new(self.clone(), signer.account_id.clone(), res);
-        Ok(sent_tx)
+            // broadcast_tx_async は検証結果を返さないため、nonce の拒否を受け取れるよう
+            // 取り込み待ちをしない send_tx で送る
+            let req = methods::send_tx::RpcSendTransactionRequest {
+                signed_transaction: signed_tx,
+                wait_until: TxExecutionStatus::None,
+            };
+
+            let err = match self.rpc.call(req).await {
+                Ok(_) => {
+                    info!(log, "broadcasted";
+                        "tx_hash" => %hash,
+                        "nonce" => nonce,
+                        "block_hash" => %block_hash,
+                        "public_key" => %signer.public_key(),
+                    );
+                    let sent_tx =
+                        StandardSentTx::new(self.clone(), signer.account_id.clone(), hash);
+                    return Ok(sent_tx);
+                }
+                Err(err) => err,
+            };
+
+            match NonceRecovery::from_error(&err) {
+                Some(recovery) if attempt < MAX_NONCE_ATTEMPTS => {
+                    warn!(log, "transaction rejected by nonce, resyncing";
+                        "nonce" => nonce,
+                        "recovery" => ?recovery,
+                        "attempt" => attempt,
+                    );
+                    match recovery {
+                        NonceRecovery::Resync { ak_nonce } => {
+                            self.nonces.resync(signer, ak_nonce).await
+                        }
+                        NonceRecovery::Refetch => self.nonces.invalidate(signer).await,
+                    }
+                    attempt += 1;
+                }
+                _ => {
+                    // 取り込まれたか分からない nonce を使い回さないよう、次回はチェーンから取り直す
+                    self.nonces.invalidate(signer).await;
+                    if rejected_before_broadcast(&err) {
+                        return Err(err.into());
+                    }
+                    warn!(log, "transaction may have been broadcast";
+                        "tx_hash" => %hash,
+                        "nonce" => nonce,
+                        "error" => %err,
+                    );
+                    return Err(TxMaybeSent {
+                        account: signer.account_id.clone(),
+                        tx_hash: hash,
+                        reason: err.to_string(),
+                    }
+                    .into());
+                }
+            }
+        }
     }
 }
+
+#[cfg(test)]
+mod tests;
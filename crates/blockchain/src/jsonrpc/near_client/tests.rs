@@ -0,0 +1,36 @@
+use super::*;
+use near_primitives::errors::InvalidTxError;
+
+fn handler_error(err: RpcTransactionError) -> JsonRpcError<RpcTransactionError> {
+    JsonRpcError::ServerError(JsonRpcServerError::HandlerError(err))
+}
+
+#[test]
+fn test_rejections_before_broadcast() {
+    let invalid = handler_error(RpcTransactionError::InvalidTransaction {
+        context: InvalidTxError::InvalidSignature,
+    });
+    assert!(rejected_before_broadcast(&invalid));
+
+    let unserializable = JsonRpcError::TransportError(RpcTransportError::SendError(
+        JsonRpcTransportSendError::PayloadSerializeError(std::io::Error::other("bad payload")),
+    ));
+    assert!(rejected_before_broadcast(&unserializable));
+}
+
+#[test]
+fn test_timeout_may_have_been_broadcast() {
+    let timeout = handler_error(RpcTransactionError::TimeoutError);
+    assert!(!rejected_before_broadcast(&timeout));
+}
+
+#[test]
+fn test_maybe_sent_tx_id_matches_sent_tx() {
+    let err = TxMaybeSent {
+        account: "alice.near".parse().unwrap(),
+        tx_hash: CryptoHash::default(),
+        reason: "timeout".to_string(),
+    };
+    let sent = StandardSentTx::new((), err.account.clone(), err.tx_hash);
+    assert_eq!(err.tx_id(), sent.to_string());
+}
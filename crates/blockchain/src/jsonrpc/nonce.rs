@@ -0,0 +1,97 @@
+//! access key ごとの nonce 払い出し
+//!
+//! トランザクションごとに `view_access_key` で nonce を取り直すと、未取り込みの
+//! トランザクションと同じ nonce を使ってしまい、同じ鍵で並行に送ると衝突する。
+//! ここでは (account, public key) ごとに最後に払い出した nonce を保持し、
+//! 排他的に +1 して渡す。チェーンから取り直すのは初回と、送信が nonce 起因で
+//! 拒否されたときだけ。
+
+use crate::Result;
+use near_crypto::{InMemorySigner, PublicKey};
+use near_jsonrpc_client::errors::{JsonRpcError, JsonRpcServerError};
+use near_jsonrpc_primitives::types::transactions::RpcTransactionError;
+use near_primitives::errors::InvalidTxError;
+use near_sdk::AccountId;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+type NonceSlot = Arc<tokio::sync::Mutex<Option<u64>>>;
+
+/// 署名鍵ごとに nonce をキャッシュして払い出す
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    slots: Mutex<HashMap<(AccountId, PublicKey), NonceSlot>>,
+}
+
+impl NonceManager {
+    fn slot(&self, signer: &InMemorySigner) -> NonceSlot {
+        let key = (signer.account_id.clone(), signer.public_key());
+        let mut slots = self.slots.lock().unwrap();
+        Arc::clone(slots.entry(key).or_default())
+    }
+
+    /// 次に使う nonce を払い出す
+    ///
+    /// キャッシュが無ければ `fetch` でチェーン上の access key の nonce を取得する。
+    /// 同じ鍵への呼び出しは直列化されるため、並行に呼んでも同じ値は返らない。
+    pub async fn next<F, Fut>(&self, signer: &InMemorySigner, fetch: F) -> Result<u64>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<u64>>,
+    {
+        let slot = self.slot(signer);
+        let mut current = slot.lock().await;
+        let last = match *current {
+            Some(last) => last,
+            None => fetch().await?,
+        };
+        let nonce = last + 1;
+        *current = Some(nonce);
+        Ok(nonce)
+    }
+
+    /// チェーン上の nonce が `ak_nonce` まで進んでいることを反映する
+    ///
+    /// 払い出し済みでまだ取り込まれていない nonce を巻き戻さないよう、大きい方を残す。
+    pub async fn resync(&self, signer: &InMemorySigner, ak_nonce: u64) {
+        let slot = self.slot(signer);
+        let mut current = slot.lock().await;
+        *current = Some(current.map_or(ak_nonce, |last| last.max(ak_nonce)));
+    }
+
+    /// キャッシュを捨て、次回の払い出しでチェーンから取り直させる
+    pub async fn invalidate(&self, signer: &InMemorySigner) {
+        *self.slot(signer).lock().await = None;
+    }
+}
+
+/// 送信が nonce 起因で拒否されたときの立て直し方
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NonceRecovery {
+    /// 使った nonce がチェーン上の値以下だった。`ak_nonce` に合わせて再送する
+    Resync { ak_nonce: u64 },
+    /// nonce が先に進みすぎていた。チェーンから取り直して再送する
+    Refetch,
+}
+
+impl NonceRecovery {
+    pub(crate) fn from_error(err: &JsonRpcError<RpcTransactionError>) -> Option<Self> {
+        let JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcTransactionError::InvalidTransaction { context },
+        )) = err
+        else {
+            return None;
+        };
+        match context {
+            InvalidTxError::InvalidNonce { ak_nonce, .. } => Some(Self::Resync {
+                ak_nonce: *ak_nonce,
+            }),
+            InvalidTxError::NonceTooLarge { .. } => Some(Self::Refetch),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,112 @@
+use super::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn signer(seed: &str) -> InMemorySigner {
+    match InMemorySigner::from_seed(
+        "test.near".parse().unwrap(),
+        near_crypto::KeyType::ED25519,
+        seed,
+    ) {
+        near_crypto::Signer::InMemory(signer) => signer,
+        _ => panic!("Expected InMemorySigner"),
+    }
+}
+
+fn invalid_tx(context: InvalidTxError) -> JsonRpcError<RpcTransactionError> {
+    JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+        RpcTransactionError::InvalidTransaction { context },
+    ))
+}
+
+#[tokio::test]
+async fn test_next_fetches_once_and_increments() {
+    let manager = NonceManager::default();
+    let signer = signer("a");
+    let fetches = AtomicUsize::new(0);
+    let fetch = || async {
+        fetches.fetch_add(1, Ordering::Relaxed);
+        Ok(100)
+    };
+
+    assert_eq!(manager.next(&signer, fetch).await.unwrap(), 101);
+    assert_eq!(manager.next(&signer, fetch).await.unwrap(), 102);
+    assert_eq!(manager.next(&signer, fetch).await.unwrap(), 103);
+    assert_eq!(fetches.load(Ordering::Relaxed), 1);
+}
+
+#[tokio::test]
+async fn test_concurrent_next_hands_out_distinct_nonces() {
+    let manager = NonceManager::default();
+    let signer = signer("a");
+    let fetch = || async {
+        tokio::task::yield_now().await;
+        Ok(0)
+    };
+
+    let mut nonces = futures_util::future::join_all((0..16).map(|_| manager.next(&signer, fetch)))
+        .await
+        .into_iter()
+        .map(|r| r.unwrap())
+        .collect::<Vec<_>>();
+    nonces.sort();
+    assert_eq!(nonces, (1..=16).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_keys_are_independent() {
+    let manager = NonceManager::default();
+    let (a, b) = (signer("a"), signer("b"));
+
+    assert_eq!(manager.next(&a, || async { Ok(10) }).await.unwrap(), 11);
+    assert_eq!(manager.next(&b, || async { Ok(500) }).await.unwrap(), 501);
+    assert_eq!(manager.next(&a, || async { Ok(10) }).await.unwrap(), 12);
+}
+
+#[tokio::test]
+async fn test_resync_never_moves_backwards() {
+    let manager = NonceManager::default();
+    let signer = signer("a");
+    manager.next(&signer, || async { Ok(10) }).await.unwrap();
+
+    // チェーンが先に進んでいれば追従する
+    manager.resync(&signer, 20).await;
+    assert_eq!(manager.next(&signer, || async { Ok(0) }).await.unwrap(), 21);
+
+    // 払い出し済みの値より古い ak_nonce では巻き戻さない
+    manager.resync(&signer, 5).await;
+    assert_eq!(manager.next(&signer, || async { Ok(0) }).await.unwrap(), 22);
+}
+
+#[tokio::test]
+async fn test_invalidate_refetches() {
+    let manager = NonceManager::default();
+    let signer = signer("a");
+    manager.next(&signer, || async { Ok(10) }).await.unwrap();
+    manager.invalidate(&signer).await;
+    assert_eq!(
+        manager.next(&signer, || async { Ok(40) }).await.unwrap(),
+        41
+    );
+}
+
+#[test]
+fn test_recovery_from_error() {
+    assert_eq!(
+        NonceRecovery::from_error(&invalid_tx(InvalidTxError::InvalidNonce {
+            tx_nonce: 5,
+            ak_nonce: 7,
+        })),
+        Some(NonceRecovery::Resync { ak_nonce: 7 })
+    );
+    assert_eq!(
+        NonceRecovery::from_error(&invalid_tx(InvalidTxError::NonceTooLarge {
+            tx_nonce: 1_000_000_000,
+            upper_bound: 7,
+        })),
+        Some(NonceRecovery::Refetch)
+    );
+    assert_eq!(
+        NonceRecovery::from_error(&invalid_tx(InvalidTxError::Expired)),
+        None
+    );
+}
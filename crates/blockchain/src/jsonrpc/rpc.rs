@@ -6,7 +6,8 @@ use near_jsonrpc_client::errors::{
 use near_jsonrpc_client::{JsonRpcClient, MethodCallResult, methods};
 use rand::Rng;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
 
 #[derive(Debug, Clone)]
 pub struct StandardRpcClient {
@@ -74,13 +75,76 @@ impl StandardRpcClient {
         <M as methods::RpcMethod>::Response: Send,
         <M as methods::RpcMethod>::Error: Send,
     {
+        let method_name = method.method_name().to_owned();
         let log = DEFAULT.new(o!(
             "function" => "jsonrpc::Client::call_maybe_retry",
             "server" => endpoint_url.to_owned(),
-            "method" => method.method_name().to_owned(),
+            "method" => method_name.clone(),
         ));
         debug!(log, "calling");
-        let res = client.call(method).await;
+        let span = tracing::info_span!(
+            "rpc",
+            rpc.method = %method_name,
+            rpc.endpoint = endpoint_url,
+            rpc.outcome = tracing::field::Empty,
+        );
+        let started = Instant::now();
+        let res = client.call(method).instrument(span.clone()).await;
+        let result = span.in_scope(|| self.classify(&log, endpoint_url, res));
+        span.record("rpc.outcome", result.outcome());
+        let elapsed = started.elapsed();
+        metrics::rpc::record_request(endpoint_url, &method_name, result.outcome(), elapsed);
+        self.endpoint_pool
+            .record_outcome(endpoint_url, elapsed, !result.is_endpoint_error());
+        result
+    }
+
+    /// 全 endpoint に `status` を問い合わせて head とレイテンシを記録する
+    pub(super) async fn probe_heads(&self) {
+        let log = DEFAULT.new(o!(
+            "function" => "jsonrpc::Client::probe_heads",
+        ));
+        let probes = self.endpoint_pool.endpoints().iter().map(|ep| async {
+            let client = Self::create_client_with_timeout(&ep.url);
+            match self
+                .call_maybe_retry(&client, &ep.url, methods::status::RpcStatusRequest)
+                .await
+            {
+                MaybeRetry::Through(Ok(status)) => {
+                    self.endpoint_pool
+                        .record_head(&ep.url, status.sync_info.latest_block_height);
+                }
+                MaybeRetry::Through(Err(err))
+                | MaybeRetry::Retry { err, .. }
+                | MaybeRetry::SwitchEndpoint { err, .. } => {
+                    debug!(log, "status probe failed"; "endpoint" => &ep.url, "error" => format!("{:?}", err));
+                }
+            }
+        });
+        futures_util::future::join_all(probes).await;
+
+        for health in self.endpoint_pool.snapshot() {
+            trace!(log, "endpoint health";
+                "endpoint" => &health.url,
+                "effective_weight" => health.effective_weight,
+                "error_rate" => health.error_rate,
+                "head_lag" => ?health.head_lag,
+            );
+            metrics::rpc::set_endpoint_health(
+                &health.url,
+                health.effective_weight,
+                health.head_lag,
+            );
+        }
+    }
+
+    /// RPC の結果をリトライ方針に振り分ける
+    fn classify<R, E>(
+        &self,
+        log: &Logger,
+        endpoint_url: &str,
+        res: MethodCallResult<R, E>,
+    ) -> MaybeRetry<MethodCallResult<R, E>, JsonRpcError<E>> {
         match res {
             Ok(res) => {
                 trace!(log, "success");
@@ -268,6 +332,7 @@ impl super::RpcClient for StandardRpcClient {
                             warn!(log, "global retry limit reached";
                                 "reason" => msg,
                             );
+                            metrics::rpc::record_retry_limit_reached(method.method_name());
                             return Err(err);
                         }
 
@@ -297,6 +362,7 @@ impl super::RpcClient for StandardRpcClient {
                             warn!(log, "global retry limit reached";
                                 "reason" => &msg,
                             );
+                            metrics::rpc::record_retry_limit_reached(method.method_name());
                             return Err(err);
                         }
 
@@ -312,6 +378,75 @@ impl super::RpcClient for StandardRpcClient {
             }
         }
     }
+
+    async fn call_hedged<M>(&self, method: M) -> MethodCallResult<M::Response, M::Error>
+    where
+        M: methods::RpcMethod + Send + Sync,
+        <M as methods::RpcMethod>::Response: Send,
+        <M as methods::RpcMethod>::Error: Send,
+    {
+        let Some(primary) = self.endpoint_pool.next_endpoint() else {
+            return super::RpcClient::call(self, method).await;
+        };
+        let Some(backup) = self.endpoint_pool.next_endpoint_excluding(&primary.url) else {
+            return super::RpcClient::call(self, method).await;
+        };
+        let method_name = method.method_name().to_owned();
+        let delay = self.endpoint_pool.hedge_delay(&primary.url);
+
+        let primary_client = Self::create_client_with_timeout(&primary.url);
+        let first = self.call_maybe_retry(&primary_client, &primary.url, &method);
+        tokio::pin!(first);
+        tokio::select! {
+            res = &mut first => {
+                // 待ち時間内に返ってきたら hedge しない。失敗時は通常のリトライ経路へ
+                if let MaybeRetry::Through(res) = res {
+                    return res;
+                }
+                return super::RpcClient::call(self, &method).await;
+            }
+            _ = tokio::time::sleep(delay) => {}
+        }
+
+        let log = DEFAULT.new(o!(
+            "function" => "jsonrpc::Client::call_hedged",
+            "method" => method_name.clone(),
+            "primary" => primary.url.clone(),
+            "backup" => backup.url.clone(),
+        ));
+        debug!(log, "primary is slow, firing hedge request"; "delay" => ?delay);
+        let backup_client = Self::create_client_with_timeout(&backup.url);
+        let second = self.call_maybe_retry(&backup_client, &backup.url, &method);
+        tokio::pin!(second);
+        tokio::select! {
+            res = &mut first => {
+                if let MaybeRetry::Through(res) = res {
+                    metrics::rpc::record_hedge(&method_name, "primary");
+                    return res;
+                }
+                if let MaybeRetry::Through(res) = second.await {
+                    metrics::rpc::record_hedge(&method_name, "hedge");
+                    return res;
+                }
+            }
+            res = &mut second => {
+                if let MaybeRetry::Through(res) = res {
+                    metrics::rpc::record_hedge(&method_name, "hedge");
+                    return res;
+                }
+                if let MaybeRetry::Through(res) = first.await {
+                    metrics::rpc::record_hedge(&method_name, "primary");
+                    return res;
+                }
+            }
+        }
+        metrics::rpc::record_hedge(&method_name, "none");
+        warn!(
+            log,
+            "both hedged requests failed, falling back to retry loop"
+        );
+        super::RpcClient::call(self, &method).await
+    }
 }
 
 enum MaybeRetry<A, B> {
@@ -330,6 +465,23 @@ enum MaybeRetry<A, B> {
     },
 }
 
+impl<T, E, B> MaybeRetry<Result<T, E>, B> {
+    /// メトリクスの `outcome` ラベル
+    fn outcome(&self) -> &'static str {
+        match self {
+            MaybeRetry::Through(Ok(_)) => "ok",
+            MaybeRetry::Through(Err(_)) => "error",
+            MaybeRetry::Retry { .. } => "retry",
+            MaybeRetry::SwitchEndpoint { .. } => "switch_endpoint",
+        }
+    }
+
+    /// endpoint 側の問題による失敗か（健全性スコアに反映する）
+    fn is_endpoint_error(&self) -> bool {
+        !matches!(self, MaybeRetry::Through(_))
+    }
+}
+
 fn calc_retry_duration(upper: Duration, retry_limit: u16, fr: f32) -> impl Fn(u16) -> Duration {
     const N: f32 = 1.0 / std::f32::consts::E;
     move |retry_count: u16| -> Duration {
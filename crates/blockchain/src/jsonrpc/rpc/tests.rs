@@ -396,3 +396,24 @@ fn test_mixed_retry_and_switch_endpoint_responses() {
 
     assert!(tracker.final_result.unwrap().is_ok());
 }
+
+#[test]
+fn test_is_endpoint_error() {
+    // 成功もアプリケーションエラーも endpoint は正常に応答している
+    let ok: MaybeRetry<Result<(), ()>, ()> = MaybeRetry::Through(Ok(()));
+    let app_err: MaybeRetry<Result<(), ()>, ()> = MaybeRetry::Through(Err(()));
+    let retry: MaybeRetry<Result<(), ()>, ()> = MaybeRetry::Retry {
+        err: (),
+        msg: String::new(),
+        min_dur: Duration::ZERO,
+    };
+    let switch: MaybeRetry<Result<(), ()>, ()> = MaybeRetry::SwitchEndpoint {
+        err: (),
+        msg: String::new(),
+        min_dur: Duration::ZERO,
+    };
+    assert!(!ok.is_endpoint_error());
+    assert!(!app_err.is_endpoint_error());
+    assert!(retry.is_endpoint_error());
+    assert!(switch.is_endpoint_error());
+}
@@ -1,6 +1,7 @@
 use crate::jsonrpc::{SentTx, TxInfo};
 use anyhow::{anyhow, bail};
 use logging::*;
+use near_primitives::errors::TxExecutionError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::types::AccountId;
 use near_primitives::views::{
@@ -8,6 +9,42 @@ use near_primitives::views::{
     TxExecutionStatus,
 };
 use std::time::Duration;
+use thiserror::Error;
+
+/// `trade_transactions.tx_id` などに残すトランザクションの識別子
+fn tx_id(account: &AccountId, tx_hash: &CryptoHash) -> String {
+    format!("Tx(account={}, hash={})", account, tx_hash)
+}
+
+/// 署名して送ったものの、ブロードキャストされたか分からないトランザクション
+///
+/// 取り込みを待たない送信では、応答のタイムアウトや通信エラーがブロードキャスト後にも起こる。
+/// 検証で拒否された（確実に未送信の）エラーと区別するために返す。呼び出し側は未送信として
+/// 扱わず、`tx_hash` で結果を確かめること。
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("transaction {tx_hash} may have been broadcast: {reason}")]
+pub struct TxMaybeSent {
+    pub account: AccountId,
+    pub tx_hash: CryptoHash,
+    pub reason: String,
+}
+
+impl TxMaybeSent {
+    /// 送信できた場合の `StandardSentTx` の表示と同じ識別子
+    pub fn tx_id(&self) -> String {
+        tx_id(&self.account, &self.tx_hash)
+    }
+}
+
+/// on-chain で実行に失敗したトランザクション
+///
+/// 失敗してもガスは燃やされるので、呼び出し側が消費量を集計できるよう実行結果も返す。
+#[derive(Error, Debug, Clone)]
+#[error("{error}")]
+pub struct TxFailed {
+    pub view: FinalExecutionOutcomeView,
+    pub error: TxExecutionError,
+}
 
 pub struct StandardSentTx<A> {
     tx_info: A,
@@ -17,7 +54,7 @@ pub struct StandardSentTx<A> {
 
 impl<A> std::fmt::Display for StandardSentTx<A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Tx(account={}, hash={})", self.account, self.tx_hash)
+        f.write_str(&tx_id(&self.account, &self.tx_hash))
     }
 }
 
@@ -32,6 +69,10 @@ impl<A> StandardSentTx<A> {
 }
 
 impl<A: TxInfo> SentTx for StandardSentTx<A> {
+    fn tx_hash(&self) -> CryptoHash {
+        self.tx_hash
+    }
+
     async fn wait_for_executed(&self) -> crate::Result<FinalExecutionOutcomeViewEnum> {
         self.tx_info
             .wait_tx_result(&self.account, &self.tx_hash, TxExecutionStatus::Executed)
@@ -109,9 +150,10 @@ impl<A: TxInfo> SentTx for StandardSentTx<A> {
                         debug!(attempt_log, "transaction still pending"; "status" => format!("{:?}", view.status));
                         // まだ実行中 - 次のポーリングへ
                     }
-                    FinalExecutionStatus::Failure(err) => {
+                    FinalExecutionStatus::Failure(ref err) => {
                         info!(attempt_log, "transaction failed"; "error" => format!("{:?}", err));
-                        return Err(err.into());
+                        let error = err.clone();
+                        return Err(TxFailed { view, error }.into());
                     }
                     FinalExecutionStatus::SuccessValue(_) => {
                         info!(
@@ -1,6 +1,7 @@
 #![deny(warnings)]
 #![allow(async_fn_in_trait)]
 
+pub mod batch;
 pub mod jsonrpc;
 #[cfg(any(test, feature = "mock"))]
 pub mod mock;
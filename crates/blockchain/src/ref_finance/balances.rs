@@ -1,4 +1,5 @@
 use crate::Result;
+use crate::batch::TxBatch;
 use crate::jsonrpc::{AccountInfo, SendTx, SentTx, ViewContract};
 use crate::ref_finance::deposit;
 use crate::ref_finance::history::get_history;
@@ -83,7 +84,7 @@ where
     let required_balance = required_balance.unwrap_or_else(|| {
         let max = get_history()
             .read()
-            .expect("history lock is read-only; poisoning is impossible")
+            .expect("history lock is only held for plain field access; poisoning is impossible")
             .inputs
             .max();
         if max.is_zero() {
@@ -193,15 +194,6 @@ where
             wrapping
         };
 
-        if amount.as_yoctonear() > 0 {
-            trace!(log, "wrapping";
-                "amount" => amount.as_yoctonear(),
-            );
-            deposit::wnear::wrap(client, wallet, amount)
-                .await?
-                .wait_for_success()
-                .await?;
-        }
         amount
     } else {
         NearToken::from_yoctonear(0)
@@ -210,18 +202,43 @@ where
     let total_deposit = wrapped_balance.saturating_add(actual_wrapping);
     if total_deposit.as_yoctonear() > 0 {
         debug!(log, "refilling";
+            "wrapping" => actual_wrapping.as_yoctonear(),
             "amount" => total_deposit.as_yoctonear(),
         );
-        deposit::deposit(client, wallet, &WNEAR_TOKEN, total_deposit)
-            .await?
-            .wait_for_success()
-            .await?;
+        wrap_and_deposit(client, wallet, actual_wrapping, total_deposit).await?;
     } else {
         trace!(log, "no amount to deposit")
     }
     Ok(())
 }
 
+/// NEAR の wrap と REF Finance への wrap.near デポジットを wrap.near 宛ての 1 トランザクションで送る
+///
+/// `near_deposit` が失敗すれば `ft_transfer_call` は実行されないため、wrap だけ済んで
+/// デポジットされない中途半端な状態が往復の合間に生じない。`wrap` が 0 なら積まない。
+async fn wrap_and_deposit<C, W>(
+    client: &C,
+    wallet: &W,
+    wrap: NearToken,
+    amount: NearToken,
+) -> Result<()>
+where
+    C: SendTx,
+    W: Wallet,
+{
+    let mut batch = TxBatch::new(WNEAR_TOKEN.as_account_id().clone());
+    if wrap.as_yoctonear() > 0 {
+        batch.push(deposit::wnear::wrap_call(wrap)?)?;
+    }
+    batch.push(deposit::deposit_call(amount)?)?;
+    batch
+        .send(client, wallet.signer())
+        .await?
+        .wait_for_success()
+        .await?;
+    Ok(())
+}
+
 /// 投資額全額を REF Finance にデポジット（初期ポートフォリオ構築用）
 pub async fn deposit_wrap_near_to_ref<C, W>(
     client: &C,
@@ -265,7 +282,7 @@ where
     trace!(log, "current wrap.near balance"; "wrapped_balance" => wrapped_balance.as_yoctonear());
 
     // 不足分を wrap.near から調達
-    if wrapped_balance < shortage {
+    let actual_wrapping = if wrapped_balance < shortage {
         // さらに NEAR を wrap する必要がある
         let wrapping = shortage.saturating_sub(wrapped_balance);
         let native_balance = client.get_native_amount(account).await?;
@@ -274,7 +291,7 @@ where
 
         let available = native_balance.saturating_sub(minimum_native_balance);
 
-        let actual_wrapping = if available < wrapping {
+        if available < wrapping {
             info!(log, "insufficient balance, wrapping maximum available";
                 "available" => available.as_yoctonear(),
                 "wanted" => wrapping.as_yoctonear(),
@@ -282,19 +299,13 @@ where
             available
         } else {
             wrapping
-        };
-
-        if actual_wrapping.as_yoctonear() > 0 {
-            trace!(log, "wrapping NEAR to wrap.near"; "amount" => actual_wrapping.as_yoctonear());
-            deposit::wnear::wrap(client, wallet, actual_wrapping)
-                .await?
-                .wait_for_success()
-                .await?;
         }
-    }
+    } else {
+        NearToken::from_yoctonear(0)
+    };
 
-    // wrap.near の最終残高を確認して、デポジット可能な量を決定
-    let final_wrapped_balance = deposit::wnear::balance_of(client, account).await?;
+    // wrap 後の wrap.near 残高を見込んで、デポジット可能な量を決定
+    let final_wrapped_balance = wrapped_balance.saturating_add(actual_wrapping);
 
     if final_wrapped_balance.as_yoctonear() == 0 {
         return Err(anyhow::anyhow!("No wrap.near balance available to deposit"));
@@ -309,14 +320,12 @@ where
 
     trace!(log, "depositing wrap.near to REF Finance";
         "shortage" => shortage.as_yoctonear(),
+        "wrapping" => actual_wrapping.as_yoctonear(),
         "available" => final_wrapped_balance.as_yoctonear(),
         "depositing" => deposit_amount.as_yoctonear()
     );
 
-    deposit::deposit(client, wallet, &WNEAR_TOKEN, deposit_amount)
-        .await?
-        .wait_for_success()
-        .await?;
+    wrap_and_deposit(client, wallet, actual_wrapping, deposit_amount).await?;
 
     info!(log, "deposit completed successfully"; "deposited" => deposit_amount.as_yoctonear());
     Ok(())
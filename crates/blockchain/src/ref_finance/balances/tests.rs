@@ -2,6 +2,7 @@ use super::*;
 use anyhow::anyhow;
 use common::config::ConfigResolver;
 use near_crypto::InMemorySigner;
+use near_primitives::hash::CryptoHash;
 use near_primitives::transaction::Action;
 use near_primitives::views::{
     CallResult, FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum,
@@ -192,11 +193,23 @@ impl SendTx for MockClient {
 
     async fn send_tx(
         &self,
-        _signer: &InMemorySigner,
-        _receiver: &AccountId,
-        _actions: Vec<Action>,
+        signer: &InMemorySigner,
+        receiver: &AccountId,
+        actions: Vec<Action>,
     ) -> Result<Self::Output> {
         self.log_operation("send_tx");
+        // バッチは各 FunctionCall を順に exec_contract として再生し、失敗した時点で止める
+        for action in actions {
+            if let Action::FunctionCall(fc) = action {
+                let args: serde_json::Value = serde_json::from_slice(&fc.args)?;
+                let sent = self
+                    .exec_contract(signer, receiver, &fc.method_name, args, fc.deposit)
+                    .await?;
+                if sent.should_fail {
+                    return Ok(sent);
+                }
+            }
+        }
         Ok(MockSentTx { should_fail: false })
     }
 }
@@ -248,6 +261,10 @@ struct MockSentTx {
 }
 
 impl SentTx for MockSentTx {
+    fn tx_hash(&self) -> CryptoHash {
+        CryptoHash::default()
+    }
+
     async fn wait_for_executed(&self) -> Result<FinalExecutionOutcomeViewEnum> {
         unimplemented!()
     }
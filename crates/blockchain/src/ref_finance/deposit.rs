@@ -1,14 +1,22 @@
 use crate::Result;
+use crate::batch::BatchCall;
 use crate::jsonrpc::{SendTx, ViewContract};
 use crate::ref_finance::CONTRACT_ADDRESS;
 use crate::wallet::Wallet;
 use common::types::TokenAccount;
 use logging::*;
+use near_primitives::types::Gas;
 use near_sdk::json_types::U128;
 use near_sdk::{AccountId, NearToken};
 use serde_json::json;
 use std::collections::BTreeMap;
 
+/// バッチに積むときの `register_tokens` の添付ガス（`MAX_REGISTER_PER_CYCLE` 件を登録できる量）
+pub const REGISTER_TOKENS_GAS: Gas = Gas::from_teragas(200);
+/// バッチに積むときの `ft_transfer_call` の添付ガス（受け手の `ft_on_transfer` と
+/// `ft_resolve_transfer` の分を含む）
+pub const FT_TRANSFER_CALL_GAS: Gas = Gas::from_teragas(150);
+
 /// NEP-145 `storage_deposit` の `registration_only` フラグを表す自己説明的な enum。
 ///
 /// ## 背景
@@ -55,14 +63,19 @@ impl DepositMode {
 
 pub mod wnear {
     use crate::Result;
+    use crate::batch::BatchCall;
     use crate::jsonrpc::{SendTx, ViewContract};
     use crate::ref_finance::token_account::WNEAR_TOKEN;
     use crate::wallet::Wallet;
     use logging::*;
+    use near_primitives::types::Gas;
     use near_sdk::json_types::U128;
     use near_sdk::{AccountId, NearToken};
     use serde_json::json;
 
+    /// バッチに積むときの `near_deposit` の添付ガス
+    pub const NEAR_DEPOSIT_GAS: Gas = Gas::from_teragas(10);
+
     pub async fn balance_of<C: ViewContract>(client: &C, account: &AccountId) -> Result<NearToken> {
         let log = DEFAULT.new(o!(
             "function" => "balance_of",
@@ -93,8 +106,6 @@ pub mod wnear {
         ));
         trace!(log, "wrapping native token");
 
-        const METHOD_NAME: &str = "near_deposit";
-
         let args = json!({});
         let signer = wallet.signer();
 
@@ -102,13 +113,20 @@ pub mod wnear {
             .exec_contract(
                 signer,
                 WNEAR_TOKEN.as_account_id(),
-                METHOD_NAME,
+                WRAP_METHOD_NAME,
                 &args,
                 amount,
             )
             .await
     }
 
+    const WRAP_METHOD_NAME: &str = "near_deposit";
+
+    /// `wrap` と同じ呼び出しを wrap.near 宛てのバッチに積む形で作る
+    pub fn wrap_call(amount: NearToken) -> Result<BatchCall> {
+        BatchCall::new(WRAP_METHOD_NAME, &json!({}), amount, NEAR_DEPOSIT_GAS)
+    }
+
     pub async fn unwrap<C: SendTx, W: Wallet>(
         client: &C,
         wallet: &W,
@@ -154,22 +172,40 @@ pub async fn deposit<C: SendTx, W: Wallet>(
     ));
     trace!(log, "entered");
 
-    const METHOD_NAME: &str = "ft_transfer_call";
-
-    let args = json!({
-        "receiver_id": CONTRACT_ADDRESS.clone(),
-        "amount": U128(amount.as_yoctonear()),
-        "msg": "",
-    });
-
     let deposit = NearToken::from_yoctonear(1); // minimum deposit
     let signer = wallet.signer();
 
     client
-        .exec_contract(signer, token.as_account_id(), METHOD_NAME, &args, deposit)
+        .exec_contract(
+            signer,
+            token.as_account_id(),
+            DEPOSIT_METHOD_NAME,
+            &deposit_args(amount),
+            deposit,
+        )
         .await
 }
 
+const DEPOSIT_METHOD_NAME: &str = "ft_transfer_call";
+
+fn deposit_args(amount: NearToken) -> serde_json::Value {
+    json!({
+        "receiver_id": CONTRACT_ADDRESS.clone(),
+        "amount": U128(amount.as_yoctonear()),
+        "msg": "",
+    })
+}
+
+/// `deposit` と同じ呼び出しをトークンコントラクト宛てのバッチに積む形で作る
+pub fn deposit_call(amount: NearToken) -> Result<BatchCall> {
+    BatchCall::new(
+        DEPOSIT_METHOD_NAME,
+        &deposit_args(amount),
+        NearToken::from_yoctonear(1),
+        FT_TRANSFER_CALL_GAS,
+    )
+}
+
 /// REF Finance に登録された account の deposit 一覧を取得する。
 ///
 /// 戻り値は `BTreeMap` で決定的な iteration 順序を保証する。これにより
@@ -199,6 +235,32 @@ pub async fn get_deposits<C: ViewContract>(
     Ok(deposits)
 }
 
+/// wallet（REF Finance の外）に保有している FT 残高（NEP-141 `ft_balance_of`）
+pub async fn wallet_balance_of<C: ViewContract>(
+    client: &C,
+    token: &TokenAccount,
+    account: &AccountId,
+) -> Result<U128> {
+    let log = DEFAULT.new(o!(
+        "function" => "wallet_balance_of",
+        "token" => format!("{}", token),
+        "account" => format!("{}", account),
+    ));
+    trace!(log, "entered");
+
+    const METHOD_NAME: &str = "ft_balance_of";
+    let args = json!({
+        "account_id": account,
+    });
+
+    let result = client
+        .view_contract(token.as_account_id(), METHOD_NAME, &args)
+        .await?;
+    let balance: U128 = serde_json::from_slice(&result.result)?;
+    trace!(log, "balance"; "balance" => balance.0);
+    Ok(balance)
+}
+
 pub async fn withdraw<C: SendTx, W: Wallet>(
     client: &C,
     wallet: &W,
@@ -245,19 +307,34 @@ pub async fn register_tokens<C: SendTx, W: Wallet>(
     ));
     trace!(log, "entered");
 
-    const METHOD_NAME: &str = "register_tokens";
-    let args = json!({
-        "token_ids": tokens
-    });
-
     let deposit = NearToken::from_yoctonear(1); // minimum deposit
     let signer = wallet.signer();
 
     client
-        .exec_contract(signer, &CONTRACT_ADDRESS, METHOD_NAME, &args, deposit)
+        .exec_contract(
+            signer,
+            &CONTRACT_ADDRESS,
+            REGISTER_METHOD_NAME,
+            &json!({ "token_ids": tokens }),
+            deposit,
+        )
         .await
 }
 
+const REGISTER_METHOD_NAME: &str = "register_tokens";
+
+/// `register_tokens` と同じ呼び出しを REF 宛てのバッチに積む形で作る
+///
+/// attached_deposit は `register_tokens` と同じく 1 yocto（cap 会計の前提、上記 NOTE 参照）。
+pub fn register_tokens_call(tokens: &[TokenAccount]) -> Result<BatchCall> {
+    BatchCall::new(
+        REGISTER_METHOD_NAME,
+        &json!({ "token_ids": tokens }),
+        NearToken::from_yoctonear(1),
+        REGISTER_TOKENS_GAS,
+    )
+}
+
 pub async fn unregister_tokens<C: SendTx, W: Wallet>(
     client: &C,
     wallet: &W,
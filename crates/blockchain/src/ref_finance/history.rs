@@ -33,6 +33,10 @@ pub mod statistics {
     }
 
     impl<A: Copy> Statistics<A> {
+        pub fn new(max: A, average: A) -> Self {
+            Statistics { max, average }
+        }
+
         pub fn max(&self) -> A {
             self.max
         }
@@ -1,7 +1,10 @@
+mod by_amount;
 mod by_token;
+mod cycle;
 mod edge;
 pub mod graph;
 pub mod preview;
+pub mod split;
 
 use crate::Result;
 use crate::ref_finance::history;
@@ -20,12 +23,16 @@ use std::hash::Hash;
 use std::ops::{Add, Div, Mul, Sub};
 use std::sync::Arc;
 
+/// `pick_cycle_previews` で取引量を見積もる循環の最大数
+const MAX_CYCLES: usize = 8;
+
 pub async fn swap_path(
     graph: &TokenGraph,
     start: &TokenInAccount,
     goal: &TokenOutAccount,
+    amount: u128,
 ) -> Result<TokenPath> {
-    graph.get_path_with_return(start, goal)
+    graph.get_path_with_return_for_amount(start, goal, amount)
 }
 
 fn rate_average<M: Into<u128>>(min: M, max: M) -> u128 {
@@ -59,7 +66,7 @@ where
     let ave_input = {
         let ave = history::get_history()
             .read()
-            .expect("history lock is read-only; poisoning is impossible")
+            .expect("history lock is only held for plain field access; poisoning is impossible")
             .inputs
             .average();
         if ave.is_zero() {
@@ -92,6 +99,60 @@ where
     Ok(result.map(|a| Arc::into_inner(a).expect("should be unwrapped")))
 }
 
+/// 循環裁定の候補を探し、それぞれ利益が最大になる取引量で見積もる
+///
+/// `pick_previews` の往復では拾えない三角形以上の循環を対象とする。取引量は
+/// `search_best_path` で 1 から `total_amount` の範囲から選び、ガス代を差し引いた
+/// 利益が残るものだけを利益の大きい順に返す。
+pub fn pick_cycle_previews<M>(
+    graph: &TokenGraph,
+    start: &TokenInAccount,
+    total_amount: M,
+    gas_price: GasPrice,
+) -> Result<Vec<(Preview<M>, TokenPath)>>
+where
+    M: Send + Sync + Copy + Hash + Debug,
+    M: Eq + Ord + Zero + One,
+    M: Add<Output = M> + Sub<Output = M> + Mul<Output = M> + Div<Output = M>,
+    M: From<u128> + Into<u128>,
+{
+    let log = DEFAULT.new(o!(
+        "function" => "pick_cycle_previews",
+        "start" => format!("{:?}", start),
+        "total_amount" => format!("{:?}", total_amount),
+        "gas_price" => format!("{:?}", gas_price),
+    ));
+    trace!(log, "start");
+
+    let min_input = one();
+    if total_amount <= min_input {
+        return Ok(vec![]);
+    }
+    let ave_input = rate_average(min_input, total_amount).into();
+    let goal = start.as_out();
+
+    let cycles = graph.list_cycles(start, MAX_CYCLES);
+    debug!(log, "cycles found"; "count" => cycles.len());
+
+    let mut previews = Vec::new();
+    for path in cycles {
+        let calc = |value: M| -> Result<Option<Arc<Preview<M>>>> {
+            let output = path.calc_value(value.into())?;
+            let preview = Preview::new(gas_price, value, goal.clone(), path.len(), output);
+            Ok(Some(Arc::new(preview)))
+        };
+        let best = search_best_path(min_input, ave_input, total_amount, calc, |p| p.gain)?;
+        if let Some(preview) = best.and_then(Arc::into_inner)
+            && preview.gain > 0
+        {
+            previews.push((preview, path));
+        }
+    }
+    previews.sort_by_key(|(preview, _)| std::cmp::Reverse(preview.gain));
+    trace!(log, "finish"; "count" => previews.len());
+    Ok(previews)
+}
+
 fn pick_by_amount<M>(
     graph: &TokenGraph,
     start: &TokenInAccount,
@@ -112,10 +173,9 @@ where
     ));
     trace!(log, "start");
 
-    let list = graph.list_returns(amount.into(), start, goals)?;
+    let list = graph.list_returns_for_amount(amount.into(), start, goals)?;
     let mut goals = vec![];
-    for (goal, output) in list.into_iter().take(limit) {
-        let path = graph.get_path_with_return(start, &goal)?;
+    for (goal, output, path) in list.into_iter().take(limit) {
         let preview = Preview::new(gas_price, amount, goal, path.len(), output);
         let gain = preview.gain;
         if gain > 0 {
@@ -0,0 +1,194 @@
+//! 取引量を考慮した経路探索
+//!
+//! `TokenGraph` のエッジ重みは入力側プール残高の半分をスワップした場合のレートで固定されるため、
+//! 取引量によらず同じ経路が選ばれる。ここでは実際の取引量を 1 ホップずつ `estimate_return` に
+//! 通し、価格インパクト込みの出力が最大になる経路をホップ数の上限内で探す。
+
+use common::types::{TokenAccount, TokenInAccount, TokenOutAccount};
+use dex::{PoolInfoList, TokenPair, TokenPairLike, TokenPath};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// ある取引量で到達したときの経路と出力量
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub output: u128,
+    pub pairs: Vec<TokenPair>,
+}
+
+impl Route {
+    pub fn into_path(self) -> TokenPath {
+        TokenPath(self.pairs)
+    }
+
+    /// 経路がすでにトークンを通過しているか（始点を含む）
+    fn visits(&self, token: &TokenAccount) -> bool {
+        self.pairs
+            .iter()
+            .any(|pair| pair.token_in_id().inner() == token)
+    }
+
+    fn uses_pool(&self, pool_id: u32) -> bool {
+        self.pairs.iter().any(|pair| pair.pool_id() == pool_id)
+    }
+}
+
+/// 入力トークンごとのスワップ可能なペア（プール内の全方向）
+#[derive(Debug)]
+pub struct PairsByToken {
+    by_in: HashMap<TokenAccount, Vec<TokenPair>>,
+}
+
+impl PairsByToken {
+    pub fn new(pools: &PoolInfoList) -> Self {
+        let mut by_in: HashMap<TokenAccount, Vec<TokenPair>> = HashMap::new();
+        for pool in pools.iter().filter(|pool| pool.is_simple()) {
+            for i in 0..pool.len() {
+                for j in (0..pool.len()).filter(|&j| j != i) {
+                    let Ok(pair) = pool.get_pair(i.into(), j.into()) else {
+                        continue;
+                    };
+                    let has_liquidity = matches!(pair.amount_in(), Ok(v) if v > 0)
+                        && matches!(pair.amount_out(), Ok(v) if v > 0);
+                    if has_liquidity {
+                        by_in
+                            .entry(pair.token_in_id().into())
+                            .or_default()
+                            .push(pair);
+                    }
+                }
+            }
+        }
+        Self { by_in }
+    }
+
+    /// `token` を入力とするペア
+    pub(super) fn from(&self, token: &TokenAccount) -> impl Iterator<Item = &TokenPair> {
+        self.by_in.get(token).into_iter().flatten()
+    }
+
+    /// `start` から `amount` を流したとき、各トークンに最も多く届く経路
+    ///
+    /// ホップごとに前段で出力が改善したトークンだけを展開する（ホップ数制限付きの
+    /// Bellman-Ford）。同じトークン・同じプールを二度通る経路は、途中でプール残高が
+    /// 変わり見積もりが成り立たないため除外する。
+    pub fn search(
+        &self,
+        start: &TokenInAccount,
+        amount: u128,
+        max_hops: usize,
+    ) -> HashMap<TokenOutAccount, Route> {
+        self.search_within(start, amount, max_hops, |_, _, _| true)
+    }
+
+    /// `start` から `goal` への最良経路
+    ///
+    /// `goal` までの残りホップ数で届かないトークンは展開しない。`avoid_pools` は
+    /// 直前の取引で残高が変わっていて見積もりに使えないプール（往復の往路など）。
+    pub fn search_to(
+        &self,
+        start: &TokenInAccount,
+        goal: &TokenOutAccount,
+        amount: u128,
+        max_hops: usize,
+        avoid_pools: &[u32],
+    ) -> Option<Route> {
+        let distances = self.hops_to(goal.inner(), max_hops);
+        let mut routes = self.search_within(start, amount, max_hops, |token, pool_id, hops| {
+            !avoid_pools.contains(&pool_id)
+                && distances.get(token).is_some_and(|&d| hops + d <= max_hops)
+        });
+        routes.remove(goal)
+    }
+
+    fn search_within<F>(
+        &self,
+        start: &TokenInAccount,
+        amount: u128,
+        max_hops: usize,
+        allowed: F,
+    ) -> HashMap<TokenOutAccount, Route>
+    where
+        F: Fn(&TokenAccount, u32, usize) -> bool,
+    {
+        if amount == 0 {
+            return HashMap::new();
+        }
+
+        let mut best: HashMap<TokenAccount, Route> = HashMap::new();
+        let origin = start.inner();
+        let mut frontier = vec![(
+            origin.clone(),
+            Route {
+                output: amount,
+                pairs: Vec::new(),
+            },
+        )];
+        for hops in 1..=max_hops {
+            let mut next: HashMap<TokenAccount, Route> = HashMap::new();
+            for (token, route) in &frontier {
+                for pair in self.from(token) {
+                    let token_out: TokenAccount = pair.token_out_id().into();
+                    // 始点への帰着もここで除外される（循環は呼び出し側で往路・復路に分ける）
+                    if route.visits(&token_out)
+                        || route.uses_pool(pair.pool_id())
+                        || !allowed(&token_out, pair.pool_id(), hops)
+                    {
+                        continue;
+                    }
+                    let output = match pair.estimate_return(route.output) {
+                        Ok(output) if output > 0 => output,
+                        _ => continue,
+                    };
+                    let improves = |r: Option<&Route>| r.is_none_or(|r| output > r.output);
+                    if improves(best.get(&token_out)) && improves(next.get(&token_out)) {
+                        let mut pairs = route.pairs.clone();
+                        pairs.push(pair.clone());
+                        next.insert(token_out, Route { output, pairs });
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+            for (token, route) in &next {
+                best.insert(token.clone(), route.clone());
+            }
+            frontier = next.into_iter().collect();
+        }
+
+        best.into_iter()
+            .map(|(token, route)| (token.into(), route))
+            .collect()
+    }
+
+    /// 各トークンから `goal` までの最小ホップ数（`max_hops` 以内のもののみ）
+    fn hops_to(&self, goal: &TokenAccount, max_hops: usize) -> HashMap<TokenAccount, usize> {
+        let mut by_out: HashMap<TokenAccount, HashSet<&TokenAccount>> = HashMap::new();
+        for (token_in, pairs) in &self.by_in {
+            for pair in pairs {
+                by_out
+                    .entry(pair.token_out_id().into())
+                    .or_default()
+                    .insert(token_in);
+            }
+        }
+
+        let mut distances = HashMap::from([(goal.clone(), 0)]);
+        let mut queue = VecDeque::from([(goal.clone(), 0)]);
+        while let Some((token, d)) = queue.pop_front() {
+            if d >= max_hops {
+                continue;
+            }
+            for &prev in by_out.get(&token).into_iter().flatten() {
+                if !distances.contains_key(prev) {
+                    distances.insert(prev.clone(), d + 1);
+                    queue.push_back((prev.clone(), d + 1));
+                }
+            }
+        }
+        distances
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,94 @@
+use super::*;
+use dex::test_helpers::simple_pool;
+
+const A: &str = "token-a.near";
+const B: &str = "token-b.near";
+const C: &str = "token-c.near";
+
+/// A→B の直結プールは良いレートだが浅く、A→C→B は少し悪いレートだが深い
+fn pairs() -> PairsByToken {
+    PairsByToken::new(&PoolInfoList::new(vec![
+        simple_pool(1, [A, B], [10u128.pow(15), 2 * 10u128.pow(15)]),
+        simple_pool(2, [A, C], [10u128.pow(18), 10u128.pow(18)]),
+        simple_pool(3, [C, B], [10u128.pow(18), 19 * 10u128.pow(17)]),
+    ]))
+}
+
+fn token_in(name: &str) -> TokenInAccount {
+    name.parse().unwrap()
+}
+
+fn token_out(name: &str) -> TokenOutAccount {
+    name.parse().unwrap()
+}
+
+fn pool_ids(route: &Route) -> Vec<u32> {
+    route.pairs.iter().map(|p| p.pool_id()).collect()
+}
+
+#[test]
+fn test_small_amount_takes_direct_pool() {
+    let routes = pairs().search(&token_in(A), 10u128.pow(12), 3);
+    let route = &routes[&token_out(B)];
+    assert_eq!(pool_ids(route), vec![1]);
+}
+
+#[test]
+fn test_large_amount_takes_deep_route() {
+    let amount = 10u128.pow(15);
+    let routes = pairs().search(&token_in(A), amount, 3);
+    let route = &routes[&token_out(B)];
+    assert_eq!(pool_ids(route), vec![2, 3]);
+    assert_eq!(
+        route.output,
+        TokenPath(route.pairs.clone()).calc_value(amount).unwrap()
+    );
+}
+
+#[test]
+fn test_max_hops_limits_route() {
+    let routes = pairs().search(&token_in(A), 10u128.pow(15), 1);
+    assert_eq!(pool_ids(&routes[&token_out(B)]), vec![1]);
+}
+
+#[test]
+fn test_search_to_matches_search() {
+    let pairs = pairs();
+    let amount = 10u128.pow(15);
+    let to_b = pairs
+        .search_to(&token_in(A), &token_out(B), amount, 3, &[])
+        .unwrap();
+    let all = pairs.search(&token_in(A), amount, 3);
+    assert_eq!(to_b.output, all[&token_out(B)].output);
+    assert_eq!(pool_ids(&to_b), pool_ids(&all[&token_out(B)]));
+
+    // 未知のトークンや 0 量では経路なし
+    assert!(
+        pairs
+            .search_to(&token_in(A), &token_out("unknown.near"), amount, 3, &[])
+            .is_none()
+    );
+    assert!(pairs.search(&token_in(A), 0, 3).is_empty());
+}
+
+#[test]
+fn test_route_does_not_return_to_start() {
+    let routes = pairs().search(&token_in(A), 10u128.pow(12), 3);
+    assert!(!routes.contains_key(&token_out(A)));
+    for route in routes.values() {
+        let tokens = TokenPath(route.pairs.clone()).all_tokens();
+        assert_eq!(
+            tokens.len(),
+            route.pairs.len() + 1,
+            "tokens must not repeat"
+        );
+    }
+}
+
+#[test]
+fn test_search_to_avoids_pools() {
+    let route = pairs()
+        .search_to(&token_in(A), &token_out(B), 10u128.pow(15), 3, &[2])
+        .unwrap();
+    assert_eq!(pool_ids(&route), vec![1]);
+}
@@ -0,0 +1,117 @@
+//! 負の対数重みによる循環裁定の探索
+//!
+//! 往復（start→goal→start）の評価は最良の片道経路に沿うため、それを通らない三角形以上の
+//! 循環を見落とす。ここではペアの重みを `-ln(スポットレート)` とし、始点から出て始点に戻る
+//! 重みの和が負の閉路（レートの積が 1 を超える循環）をホップ数制限付きの Bellman-Ford で探す。
+//! スポットレートは価格インパクトを含まないので、見つかった循環の取引量は呼び出し側で決める。
+
+use super::by_amount::PairsByToken;
+use common::types::{TokenAccount, TokenInAccount};
+use dex::{TokenPair, TokenPairLike, TokenPath};
+use std::collections::{HashMap, HashSet};
+
+/// 始点から途中のトークンまでの経路と重みの和
+#[derive(Debug, Clone)]
+struct Walk {
+    weight: f64,
+    pairs: Vec<TokenPair>,
+}
+
+impl Walk {
+    fn visits(&self, token: &TokenAccount) -> bool {
+        self.pairs
+            .iter()
+            .any(|pair| pair.token_out_id().inner() == token)
+    }
+
+    fn uses_pool(&self, pool_id: u32) -> bool {
+        self.pairs.iter().any(|pair| pair.pool_id() == pool_id)
+    }
+}
+
+fn weight(pair: &TokenPair) -> Option<f64> {
+    pair.spot_rate()
+        .ok()
+        .filter(|rate| *rate > 0.0)
+        .map(|rate| -rate.ln())
+}
+
+/// `start` を通る負の閉路を重みの小さい（レートの積が大きい）順に最大 `limit` 件返す
+///
+/// ホップ数ごとに各トークンへの重み最小の経路だけを残して展開し、始点へ戻るペアで
+/// 閉じたときに重みの和が負になるものを循環として集める。同じトークン・同じプールを
+/// 二度通る経路は、途中でプール残高が変わり見積もりが成り立たないため除外する。
+pub(super) fn find_cycles(
+    pairs: &PairsByToken,
+    start: &TokenInAccount,
+    max_hops: usize,
+    limit: usize,
+) -> Vec<TokenPath> {
+    let origin = start.inner();
+    let mut cycles: Vec<(f64, Vec<TokenPair>)> = Vec::new();
+    let mut seen: HashSet<Vec<u32>> = HashSet::new();
+
+    let mut frontier: HashMap<TokenAccount, Walk> = HashMap::from([(
+        origin.clone(),
+        Walk {
+            weight: 0.0,
+            pairs: Vec::new(),
+        },
+    )]);
+    for hops in 1..=max_hops {
+        let mut next: HashMap<TokenAccount, Walk> = HashMap::new();
+        for (token, walk) in &frontier {
+            for pair in pairs.from(token) {
+                if walk.uses_pool(pair.pool_id()) {
+                    continue;
+                }
+                let Some(w) = weight(pair) else {
+                    continue;
+                };
+                let total = walk.weight + w;
+                let token_out: TokenAccount = pair.token_out_id().into();
+
+                if &token_out == origin {
+                    if total < 0.0 {
+                        let mut cycle = walk.pairs.clone();
+                        cycle.push(pair.clone());
+                        let pool_ids = cycle.iter().map(|p| p.pool_id()).collect();
+                        if seen.insert(pool_ids) {
+                            cycles.push((total, cycle));
+                        }
+                    }
+                    continue;
+                }
+                // 最終ホップでは始点に戻るペアしか意味がない
+                if hops == max_hops || walk.visits(&token_out) {
+                    continue;
+                }
+                if next.get(&token_out).is_none_or(|w| total < w.weight) {
+                    let mut pairs = walk.pairs.clone();
+                    pairs.push(pair.clone());
+                    next.insert(
+                        token_out,
+                        Walk {
+                            weight: total,
+                            pairs,
+                        },
+                    );
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        frontier = next;
+    }
+
+    cycles.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    cycles
+        .into_iter()
+        .take(limit)
+        .map(|(_, pairs)| TokenPath(pairs))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,101 @@
+use super::*;
+use dex::test_helpers::simple_pool;
+use dex::{PoolInfo, PoolInfoList};
+use std::sync::Arc;
+
+const WNEAR: &str = "wrap.near";
+const A: &str = "token-a.near";
+const B: &str = "token-b.near";
+const C: &str = "token-c.near";
+
+const RESERVE: u128 = 10u128.pow(27);
+
+fn pairs(pools: Vec<Arc<PoolInfo>>) -> PairsByToken {
+    PairsByToken::new(&PoolInfoList::new(pools))
+}
+
+fn start() -> TokenInAccount {
+    WNEAR.parse().unwrap()
+}
+
+fn pool_ids(path: &TokenPath) -> Vec<u32> {
+    path.0.iter().map(|p| p.pool_id()).collect()
+}
+
+/// wrap.near → A → B → wrap.near で B が割安（どの往復でも利益は出ない）
+fn triangle() -> Vec<Arc<PoolInfo>> {
+    vec![
+        simple_pool(1, [WNEAR, A], [RESERVE, RESERVE]),
+        simple_pool(2, [A, B], [RESERVE, RESERVE]),
+        simple_pool(3, [B, WNEAR], [RESERVE, RESERVE * 11 / 10]),
+    ]
+}
+
+#[test]
+fn test_finds_triangular_cycle() {
+    let cycles = find_cycles(&pairs(triangle()), &start(), 10, 8);
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(pool_ids(&cycles[0]), vec![1, 2, 3]);
+    assert_eq!(cycles[0].0[0].token_in_id(), start());
+    assert_eq!(cycles[0].0[2].token_out_id(), start().as_out());
+}
+
+#[test]
+fn test_no_cycle_when_balanced() {
+    let pools = vec![
+        simple_pool(1, [WNEAR, A], [RESERVE, RESERVE]),
+        simple_pool(2, [A, B], [RESERVE, RESERVE]),
+        simple_pool(3, [B, WNEAR], [RESERVE, RESERVE]),
+    ];
+    assert!(find_cycles(&pairs(pools), &start(), 10, 8).is_empty());
+}
+
+#[test]
+fn test_cycle_bounded_by_max_hops() {
+    assert!(find_cycles(&pairs(triangle()), &start(), 2, 8).is_empty());
+    assert_eq!(find_cycles(&pairs(triangle()), &start(), 3, 8).len(), 1);
+}
+
+#[test]
+fn test_cycles_sorted_and_limited() {
+    let mut pools = triangle();
+    // 別々のプールを通る wrap.near → C → wrap.near の方がレートの積が大きい
+    pools.push(simple_pool(4, [WNEAR, C], [RESERVE, RESERVE]));
+    pools.push(simple_pool(5, [C, WNEAR], [RESERVE, RESERVE * 12 / 10]));
+
+    let cycles = find_cycles(&pairs(pools.clone()), &start(), 10, 8);
+    assert_eq!(cycles.len(), 2);
+    assert_eq!(pool_ids(&cycles[0]), vec![4, 5]);
+    assert_eq!(pool_ids(&cycles[1]), vec![1, 2, 3]);
+
+    let cycles = find_cycles(&pairs(pools), &start(), 10, 1);
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(pool_ids(&cycles[0]), vec![4, 5]);
+}
+
+#[test]
+fn test_pick_cycle_previews_sizes_cycle() {
+    use crate::ref_finance::path::graph::TokenGraph;
+    use crate::ref_finance::path::pick_cycle_previews;
+    use crate::types::gas_price::GasPrice;
+    use near_sdk::NearToken;
+
+    let graph = TokenGraph::new(Arc::new(PoolInfoList::new(triangle())));
+    let gas_price = GasPrice::from_balance(NearToken::from_yoctonear(100_000_000));
+    let total = RESERVE / 10;
+
+    let previews = pick_cycle_previews(&graph, &start(), total, gas_price).unwrap();
+
+    assert_eq!(previews.len(), 1);
+    let (preview, path) = &previews[0];
+    assert_eq!(pool_ids(path), vec![1, 2, 3]);
+    assert_eq!(preview.token, start().as_out());
+    assert_eq!(preview.depth, 3);
+    assert!(preview.gain > 0);
+    // 価格インパクトがあるため、全額ではなく途中の取引量で利益が最大になる
+    assert!(preview.input_value < total);
+    assert_eq!(
+        preview.output_value,
+        path.calc_value(preview.input_value).unwrap()
+    );
+}
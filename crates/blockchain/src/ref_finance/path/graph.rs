@@ -1,11 +1,15 @@
 use crate::Result;
+use crate::ref_finance::path::by_amount::{PairsByToken, Route};
 use crate::ref_finance::path::by_token::PoolsByToken;
+use crate::ref_finance::path::cycle;
 use crate::ref_finance::path::edge::EdgeWeight;
+use crate::ref_finance::path::split::{self, SplitRoute};
 use anyhow::anyhow;
 use common::types::TokenAccount;
 use common::types::{TokenInAccount, TokenOutAccount};
 use dex::errors::Error;
-use dex::{PoolInfoList, TokenPath};
+use dex::token_pair::MAX_HOPS;
+use dex::{PoolInfoList, TokenPairLike, TokenPath};
 use logging::*;
 use petgraph::algo;
 use petgraph::graph::NodeIndex;
@@ -21,13 +25,19 @@ use std::sync::{Arc, Mutex, RwLock};
 pub struct TokenGraph {
     pools: Arc<PoolInfoList>,
     graph: CachedPath<TokenInAccount, TokenOutAccount, TokenAccount, EdgeWeight>,
+    pairs: PairsByToken,
 }
 
 impl TokenGraph {
     pub fn new(pools_list: Arc<PoolInfoList>) -> Self {
         let pools = Arc::clone(&pools_list);
+        let pairs = PairsByToken::new(&pools);
         let graph = Self::cached_path(pools_list);
-        Self { pools, graph }
+        Self {
+            pools,
+            graph,
+            pairs,
+        }
     }
 
     fn cached_path(
@@ -265,6 +275,175 @@ impl TokenGraph {
         values.sort_by_key(|(_, value, _)| std::cmp::Reverse(*value));
         Ok(values)
     }
+
+    /// 取引量 `amount` での出力が最大になる経路
+    ///
+    /// `get_path` は半プール量のレートで固定したエッジ重みで経路を選ぶため、取引量に
+    /// よらず同じ経路になる。こちらは各ホップの価格インパクトを含めて `MAX_HOPS` 以内で探す。
+    pub fn get_path_for_amount(
+        &self,
+        start: &TokenInAccount,
+        goal: &TokenOutAccount,
+        amount: u128,
+    ) -> Result<TokenPath> {
+        self.route_for_amount(start, goal, amount, MAX_HOPS, &[])
+            .map(Route::into_path)
+    }
+
+    /// 往路と復路を取引量に応じて選んだ往復パス
+    ///
+    /// 復路は往路の出力量で探索し、往復合わせて `MAX_HOPS` 以内に収める。
+    pub fn get_path_with_return_for_amount(
+        &self,
+        start: &TokenInAccount,
+        goal: &TokenOutAccount,
+        amount: u128,
+    ) -> Result<TokenPath> {
+        let outbound = self.route_for_amount(start, goal, amount, MAX_HOPS - 1, &[])?;
+        self.join_return(start, goal, outbound)
+            .map(Route::into_path)
+    }
+
+    /// 取引量 `amount` をプールを共有しない最大 `max_legs` 本の経路に分割する
+    ///
+    /// 候補はその時点で残っているプールでの最良経路を順に取り出したもので、全経路の
+    /// アクション数の合計は `MAX_HOPS` 以内に収める。分割しても出力が増えない場合は
+    /// 1 本だけの `SplitRoute` になる。
+    pub fn split_route_for_amount(
+        &self,
+        start: &TokenInAccount,
+        goal: &TokenOutAccount,
+        amount: u128,
+        max_legs: usize,
+    ) -> Result<SplitRoute> {
+        let mut candidates = Vec::new();
+        let mut used_pools: Vec<u32> = Vec::new();
+        let mut hops = 0;
+        while candidates.len() < max_legs.max(1) && hops < MAX_HOPS {
+            let Some(route) =
+                self.pairs
+                    .search_to(start, goal, amount, MAX_HOPS - hops, &used_pools)
+            else {
+                break;
+            };
+            hops += route.pairs.len();
+            used_pools.extend(route.pairs.iter().map(|p| p.pool_id()));
+            candidates.push(route.into_path());
+        }
+        if candidates.is_empty() {
+            return Err(anyhow!(
+                "no route for amount {}: {:?} -> {:?}",
+                amount,
+                start,
+                goal
+            ));
+        }
+        split::allocate(candidates, amount)
+    }
+
+    /// `start` から出て `start` に戻る、スポットレートの積が 1 を超える循環
+    ///
+    /// 往復に限らず `MAX_HOPS` 以内の任意の循環を対象とし、レートの積が大きい順に
+    /// 最大 `limit` 件返す。
+    pub fn list_cycles(&self, start: &TokenInAccount, limit: usize) -> Vec<TokenPath> {
+        cycle::find_cycles(&self.pairs, start, MAX_HOPS, limit)
+    }
+
+    /// `list_returns` の取引量考慮版。往復後の出力量と往復パスを出力の大きい順に返す。
+    pub fn list_returns_for_amount(
+        &self,
+        initial: u128,
+        start: &TokenInAccount,
+        goals: &[TokenOutAccount],
+    ) -> Result<Vec<(TokenOutAccount, u128, TokenPath)>> {
+        let log = DEFAULT.new(o!(
+            "function" => "TokenGraph::list_returns_for_amount",
+            "initial" => initial,
+            "start" => format!("{:?}", start),
+        ));
+
+        let mut outbounds = self.pairs.search(start, initial, MAX_HOPS - 1);
+        let mut values = Vec::new();
+        for goal in goals.iter() {
+            let Some(outbound) = outbounds.remove(goal) else {
+                debug!(log, "no outbound route for amount"; "goal" => %goal);
+                continue;
+            };
+            match self.join_return(start, goal, outbound) {
+                Ok(route) => values.push((goal.clone(), route.output, route.into_path())),
+                Err(e) => debug!(log, "no return route for amount";
+                    "goal" => %goal,
+                    "error" => %e,
+                ),
+            }
+        }
+        values.sort_by_key(|(_, value, _)| std::cmp::Reverse(*value));
+        Ok(values)
+    }
+
+    /// `list_values_with_path` の取引量考慮版
+    pub fn list_values_with_path_for_amount(
+        &self,
+        initial: u128,
+        start: &TokenInAccount,
+        goals: &[TokenOutAccount],
+    ) -> Result<Vec<(TokenOutAccount, u128, TokenPath)>> {
+        let log = DEFAULT.new(o!(
+            "function" => "TokenGraph::list_values_with_path_for_amount",
+            "initial" => initial,
+            "start" => format!("{:?}", start),
+        ));
+
+        let mut routes = self.pairs.search(start, initial, MAX_HOPS);
+        let mut values = Vec::new();
+        for goal in goals.iter() {
+            match routes.remove(goal) {
+                Some(route) => values.push((goal.clone(), route.output, route.into_path())),
+                None => debug!(log, "no route for amount"; "goal" => %goal),
+            }
+        }
+        values.sort_by_key(|(_, value, _)| std::cmp::Reverse(*value));
+        Ok(values)
+    }
+
+    fn route_for_amount(
+        &self,
+        start: &TokenInAccount,
+        goal: &TokenOutAccount,
+        amount: u128,
+        max_hops: usize,
+        avoid_pools: &[u32],
+    ) -> Result<Route> {
+        self.pairs
+            .search_to(start, goal, amount, max_hops, avoid_pools)
+            .ok_or_else(|| anyhow!("no route for amount {}: {:?} -> {:?}", amount, start, goal))
+    }
+
+    /// 往路の出力量で復路を探して連結する
+    ///
+    /// 往路で通ったプールは残高が変わるため、復路では使わない。
+    fn join_return(
+        &self,
+        start: &TokenInAccount,
+        goal: &TokenOutAccount,
+        outbound: Route,
+    ) -> Result<Route> {
+        let remaining = MAX_HOPS - outbound.pairs.len();
+        let used: Vec<u32> = outbound.pairs.iter().map(|p| p.pool_id()).collect();
+        let inbound = self.route_for_amount(
+            &goal.as_in(),
+            &start.as_out(),
+            outbound.output,
+            remaining,
+            &used,
+        )?;
+        let mut pairs = outbound.pairs;
+        pairs.extend(inbound.pairs);
+        Ok(Route {
+            output: inbound.output,
+            pairs,
+        })
+    }
 }
 
 type PathToOut<O, N> = HashMap<O, Vec<N>>;
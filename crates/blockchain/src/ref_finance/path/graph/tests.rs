@@ -594,3 +594,48 @@ fn test_update_graph_excludes_one_way_tokens() {
         goal_strs
     );
 }
+
+/// 取引量考慮の往復パスは往路の出力量で復路を選び、出力は calc_value と一致する
+#[test]
+fn test_path_with_return_for_amount() {
+    use dex::test_helpers::simple_pool;
+
+    // B→A の復路は浅い直結プールより深い C 経由の方が大量のときに有利
+    let pools_list = Arc::new(PoolInfoList::new(vec![
+        simple_pool(
+            1,
+            ["token-a.near", "token-b.near"],
+            [10u128.pow(15), 10u128.pow(15)],
+        ),
+        simple_pool(
+            2,
+            ["token-a.near", "token-c.near"],
+            [10u128.pow(18), 10u128.pow(18)],
+        ),
+        simple_pool(
+            3,
+            ["token-c.near", "token-b.near"],
+            [10u128.pow(18), 10u128.pow(18)],
+        ),
+    ]));
+    let graph = TokenGraph::new(pools_list);
+
+    let start: TokenInAccount = "token-a.near".parse().unwrap();
+    let goal: TokenOutAccount = "token-b.near".parse().unwrap();
+
+    let amount = 10u128.pow(15);
+    let path = graph
+        .get_path_with_return_for_amount(&start, &goal, amount)
+        .unwrap();
+    let pools: Vec<u32> = path.0.iter().map(|p| p.pool_id()).collect();
+    assert_eq!(pools, vec![2, 3, 1]);
+    assert_eq!(path.0.last().unwrap().token_out_id(), start.as_out());
+
+    let returns = graph
+        .list_returns_for_amount(amount, &start, std::slice::from_ref(&goal))
+        .unwrap();
+    assert_eq!(returns.len(), 1);
+    let (token, output, listed) = &returns[0];
+    assert_eq!(token, &goal);
+    assert_eq!(*output, listed.calc_value(amount).unwrap());
+}
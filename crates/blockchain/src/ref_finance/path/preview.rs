@@ -7,6 +7,16 @@ use common::types::{TokenInAccount, TokenOutAccount};
 use dex::{TokenPairLike, TokenPath};
 use near_gas::NearGas;
 
+/// スワップ 1 トランザクションの固定ガス（消費見込み）
+pub const HEAD_GAS: NearGas = NearGas::from_ggas(2700);
+/// スワップ action 1 つごとに加算するガス（消費見込み）
+pub const BY_STEP_GAS: NearGas = NearGas::from_ggas(2600);
+
+/// `depth` ホップのスワップ 1 回で消費されるガスの見込み
+pub fn swap_gas(depth: usize) -> NearGas {
+    NearGas::from_gas(HEAD_GAS.as_gas() + BY_STEP_GAS.as_gas() * (depth as u64))
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Preview<M> {
     pub gas_price: GasPrice,
@@ -21,9 +31,6 @@ impl<M> Preview<M>
 where
     M: Into<u128> + Copy,
 {
-    const HEAD: NearGas = NearGas::from_ggas(2700);
-    const BY_STEP: NearGas = NearGas::from_ggas(2600);
-
     pub fn new(
         gas_price: GasPrice,
         input_value: M,
@@ -43,8 +50,7 @@ where
     }
 
     fn cost(gas_price: GasPrice, depth: usize) -> u128 {
-        let gas = Self::HEAD.as_gas() + Self::BY_STEP.as_gas() * (depth as u64);
-        gas as u128 * gas_price.to_balance()
+        gas_price.cost_of(swap_gas(depth))
     }
 
     fn gain(gas_price: GasPrice, depth: usize, input_value: M, output_value: u128) -> u128 {
@@ -82,11 +88,15 @@ impl<M> PreviewList<M> {
         self,
         graph: &TokenGraph,
         start: &TokenInAccount,
-    ) -> Result<(Vec<(Preview<M>, TokenPath)>, Vec<TokenAccount>)> {
+    ) -> Result<(Vec<(Preview<M>, TokenPath)>, Vec<TokenAccount>)>
+    where
+        M: Into<u128> + Copy,
+    {
         let mut tokens = Vec::new();
         let mut pre_path = Vec::new();
         for p in self.list {
-            let path = ref_finance::path::swap_path(graph, start, &p.token).await?;
+            let path =
+                ref_finance::path::swap_path(graph, start, &p.token, p.input_value.into()).await?;
             for pair in path.0.iter() {
                 tokens.push(pair.token_in_id().into());
                 tokens.push(pair.token_out_id().into());
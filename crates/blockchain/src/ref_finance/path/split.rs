@@ -0,0 +1,97 @@
+//! 1 つの注文を複数の経路に分割するルーター
+//!
+//! REF の `swap` は `amount_in` を指定したアクションから新しいチェーンを開始できるため、
+//! プールを共有しない複数の経路に入力を配分して 1 トランザクションで実行できる。
+//! 薄いプールでの価格インパクトを分散し、合計出力を最大化する。
+
+use crate::Result;
+use common::types::TokenAccount;
+use dex::TokenPath;
+
+/// 入力量をこの数に等分し、限界出力の大きい経路へ 1 単位ずつ割り当てる
+const SPLIT_STEPS: u128 = 20;
+
+/// 分割注文の 1 経路分
+pub struct SplitLeg {
+    pub path: TokenPath,
+    pub amount_in: u128,
+    pub estimated_out: u128,
+}
+
+/// 分割注文全体
+pub struct SplitRoute {
+    pub legs: Vec<SplitLeg>,
+}
+
+impl SplitRoute {
+    pub fn total_out(&self) -> u128 {
+        self.legs.iter().map(|leg| leg.estimated_out).sum()
+    }
+
+    /// 全経路のアクション数（ガス消費はこれに比例する）
+    pub fn hops(&self) -> usize {
+        self.legs.iter().map(|leg| leg.path.len()).sum()
+    }
+
+    /// 全経路に含まれるトークン（重複除去済み）
+    pub fn all_tokens(&self) -> Vec<TokenAccount> {
+        let mut tokens: Vec<TokenAccount> = self
+            .legs
+            .iter()
+            .flat_map(|leg| leg.path.all_tokens())
+            .collect();
+        tokens.sort();
+        tokens.dedup();
+        tokens
+    }
+}
+
+/// 候補経路に `amount` を配分する
+///
+/// 経路同士はプールを共有しないため出力は互いに独立で、AMM の出力は入力に対して凹なので、
+/// 等分した単位を限界出力の大きい経路へ順に割り当てる貪欲法でほぼ最適な配分になる。
+/// 割り当てのなかった候補は結果に含めない。
+pub(super) fn allocate(candidates: Vec<TokenPath>, amount: u128) -> Result<SplitRoute> {
+    let step = (amount / SPLIT_STEPS).max(1);
+    let mut allocated = vec![0u128; candidates.len()];
+    let mut outputs = vec![0u128; candidates.len()];
+
+    let mut remaining = amount;
+    while remaining > 0 {
+        // 端数は最後の単位にまとめる
+        let chunk = if remaining < 2 * step {
+            remaining
+        } else {
+            step
+        };
+        let mut best: Option<(usize, u128, u128)> = None;
+        for (i, path) in candidates.iter().enumerate() {
+            let output = path.calc_value(allocated[i] + chunk)?;
+            let gain = output.saturating_sub(outputs[i]);
+            if best.is_none_or(|(_, best_gain, _)| gain > best_gain) {
+                best = Some((i, gain, output));
+            }
+        }
+        let Some((i, _, output)) = best else {
+            break;
+        };
+        allocated[i] += chunk;
+        outputs[i] = output;
+        remaining -= chunk;
+    }
+
+    let legs = candidates
+        .into_iter()
+        .zip(allocated.into_iter().zip(outputs))
+        .filter(|(_, (amount_in, _))| *amount_in > 0)
+        .map(|(path, (amount_in, estimated_out))| SplitLeg {
+            path,
+            amount_in,
+            estimated_out,
+        })
+        .collect();
+    Ok(SplitRoute { legs })
+}
+
+#[cfg(test)]
+mod tests;
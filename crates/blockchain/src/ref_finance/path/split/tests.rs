@@ -0,0 +1,94 @@
+use super::*;
+use crate::ref_finance::path::graph::TokenGraph;
+use common::types::{TokenInAccount, TokenOutAccount};
+use dex::test_helpers::simple_pool;
+use dex::{PoolInfoList, TokenPairLike};
+use std::sync::Arc;
+
+const A: &str = "token-a.near";
+const B: &str = "token-b.near";
+
+/// 同じ A/B ペアの同じ深さのプール 2 つ
+fn twin_pools() -> TokenGraph {
+    TokenGraph::new(Arc::new(PoolInfoList::new(vec![
+        simple_pool(1, [A, B], [10u128.pow(18), 10u128.pow(18)]),
+        simple_pool(2, [A, B], [10u128.pow(18), 10u128.pow(18)]),
+    ])))
+}
+
+fn start() -> TokenInAccount {
+    A.parse().unwrap()
+}
+
+fn goal() -> TokenOutAccount {
+    B.parse().unwrap()
+}
+
+#[test]
+fn test_large_order_is_split_evenly_across_twin_pools() {
+    let graph = twin_pools();
+    let amount = 10u128.pow(17);
+    let route = graph
+        .split_route_for_amount(&start(), &goal(), amount, 3)
+        .unwrap();
+
+    assert_eq!(route.legs.len(), 2);
+    assert_eq!(route.hops(), 2);
+    assert_eq!(route.legs.iter().map(|l| l.amount_in).sum::<u128>(), amount);
+    assert_eq!(route.legs[0].amount_in, route.legs[1].amount_in);
+    for leg in &route.legs {
+        assert_eq!(
+            leg.estimated_out,
+            leg.path.calc_value(leg.amount_in).unwrap()
+        );
+    }
+
+    // 分割の方が 1 プールに流すより出力が多い
+    let single = graph
+        .get_path_for_amount(&start(), &goal(), amount)
+        .unwrap();
+    assert!(route.total_out() > single.calc_value(amount).unwrap());
+    assert_eq!(route.all_tokens().len(), 2);
+}
+
+#[test]
+fn test_single_leg_when_splitting_disabled() {
+    let route = twin_pools()
+        .split_route_for_amount(&start(), &goal(), 10u128.pow(17), 1)
+        .unwrap();
+    assert_eq!(route.legs.len(), 1);
+    assert_eq!(route.legs[0].amount_in, 10u128.pow(17));
+}
+
+#[test]
+fn test_unused_candidate_is_dropped() {
+    // pool 2 は極端に浅く、配分しても出力が増えない
+    let graph = TokenGraph::new(Arc::new(PoolInfoList::new(vec![
+        simple_pool(1, [A, B], [10u128.pow(18), 10u128.pow(18)]),
+        simple_pool(2, [A, B], [10u128.pow(6), 10u128.pow(6)]),
+    ])));
+    let route = graph
+        .split_route_for_amount(&start(), &goal(), 10u128.pow(15), 3)
+        .unwrap();
+    assert_eq!(route.legs.len(), 1);
+    assert_eq!(route.legs[0].path.0[0].pool_id(), 1);
+}
+
+#[test]
+fn test_no_route() {
+    let unknown: TokenOutAccount = "unknown.near".parse().unwrap();
+    assert!(
+        twin_pools()
+            .split_route_for_amount(&start(), &unknown, 1_000, 3)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_allocate_small_amount() {
+    let graph = twin_pools();
+    let path = graph.get_path_for_amount(&start(), &goal(), 100).unwrap();
+    let route = allocate(vec![path], 7).unwrap();
+    assert_eq!(route.legs.len(), 1);
+    assert_eq!(route.legs[0].amount_in, 7);
+}
@@ -65,3 +65,30 @@ pub async fn read_pools_from_node<C: ViewContract>(client: &C) -> Result<Arc<Poo
     info!(log, "finish"; "count" => pools.len());
     Ok(Arc::new(PoolInfoList::new(pools)))
 }
+
+/// 指定した id のプールだけをノードから取得する
+///
+/// 全プールの取得より軽いため、使う直前に一部のプールを最新化するのに使う。
+pub async fn get_pools_by_id<C: ViewContract>(
+    client: &C,
+    pool_ids: &[u32],
+) -> Result<Vec<Arc<PoolInfo>>> {
+    let results = pool_ids.iter().map(|&pool_id| get_pool(client, pool_id));
+    join_all(results).await.into_iter().collect()
+}
+
+pub async fn get_pool<C: ViewContract>(client: &C, pool_id: u32) -> Result<Arc<PoolInfo>> {
+    let log = DEFAULT.new(o!(
+        "function" => "get_pool",
+        "pool_id" => pool_id,
+    ));
+    let args = json!({ "pool_id": pool_id });
+    debug!(log, "requesting");
+    let res = client
+        .view_contract(&CONTRACT_ADDRESS, "get_pool", &args)
+        .await?;
+    let bare: PoolInfoBared =
+        from_slice(&res.result).context(format!("failed to parse pool {pool_id}"))?;
+    let timestamp = chrono::Utc::now().naive_utc();
+    Ok(Arc::new(PoolInfo::new(pool_id, bare, timestamp)))
+}
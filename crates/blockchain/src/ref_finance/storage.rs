@@ -1,10 +1,12 @@
 use crate::Result;
+use crate::batch::{BatchCall, TxBatch};
 use crate::jsonrpc::{SendTx, SentTx, ViewContract};
 use crate::ref_finance::token_account::WNEAR_TOKEN;
 use crate::ref_finance::{CONTRACT_ADDRESS, deposit};
 use crate::wallet::Wallet;
-use common::types::TokenAccount;
+use common::types::{TokenAccount, YoctoAmount};
 use logging::*;
+use near_primitives::types::Gas;
 use near_sdk::json_types::U128;
 use near_sdk::{AccountId, NearToken};
 use serde::{Deserialize, Serialize};
@@ -150,10 +152,6 @@ pub async fn deposit<C: SendTx, W: Wallet>(
     mode: deposit::DepositMode,
 ) -> Result<C::Output> {
     let log = DEFAULT.new(o!("function" => "storage::deposit"));
-    const METHOD_NAME: &str = "storage_deposit";
-    let args = json!({
-        "registration_only": mode.registration_only(),
-    });
     let signer = wallet.signer();
     info!(log, "depositing";
         "value" => value.as_yoctonear(),
@@ -162,10 +160,37 @@ pub async fn deposit<C: SendTx, W: Wallet>(
     );
 
     client
-        .exec_contract(signer, &CONTRACT_ADDRESS, METHOD_NAME, &args, value)
+        .exec_contract(
+            signer,
+            &CONTRACT_ADDRESS,
+            DEPOSIT_METHOD_NAME,
+            &deposit_args(mode),
+            value,
+        )
         .await
 }
 
+const DEPOSIT_METHOD_NAME: &str = "storage_deposit";
+
+/// バッチに積むときの `storage_deposit` の添付ガス
+pub const STORAGE_DEPOSIT_GAS: Gas = Gas::from_teragas(30);
+
+fn deposit_args(mode: deposit::DepositMode) -> serde_json::Value {
+    json!({
+        "registration_only": mode.registration_only(),
+    })
+}
+
+/// `deposit` と同じ呼び出しを REF 宛てのバッチに積む形で作る
+pub fn deposit_call(value: NearToken, mode: deposit::DepositMode) -> Result<BatchCall> {
+    BatchCall::new(
+        DEPOSIT_METHOD_NAME,
+        &deposit_args(mode),
+        value,
+        STORAGE_DEPOSIT_GAS,
+    )
+}
+
 pub async fn balance_of<C: ViewContract>(
     client: &C,
     account: &AccountId,
@@ -199,7 +224,7 @@ pub async fn balance_of<C: ViewContract>(
 /// 3. unregister 後の実際の available で top-up 額を再計算
 /// 4. top-up が上限を超える場合はエラー
 /// 5. 不足があれば storage_deposit で top-up
-/// 6. 未登録の必要トークンを register_tokens
+/// 6. 未登録の必要トークンを register_tokens（5 と同じトランザクションにまとめる）
 ///
 /// # TOCTOU に関する注記
 /// ステップ 2 では unregister 前に deposits を再取得してゼロ残高を再検証するが、
@@ -285,6 +310,7 @@ where
         // NOTE: この strict `>` が `remaining_cap = max_top_up.checked_sub(initial_deposit)`
         // (ステップ 5) の不変条件 `initial_deposit ≤ max_top_up` の根拠。緩和時は併せて検討。
         if amount > max_top_up {
+            metrics::storage::record_cap_breach();
             return Err(anyhow::anyhow!(
                 "initial storage deposit {} yocto exceeds cap {} yocto",
                 amount.as_yoctonear(),
@@ -310,6 +336,7 @@ where
         .wait_for_success()
         .await?;
         info!(log, "initial storage deposit completed"; "amount" => amount.as_yoctonear());
+        metrics::storage::record_initial_deposit(&YoctoAmount::from_u128(amount.as_yoctonear()));
         amount
     };
 
@@ -565,6 +592,7 @@ where
         .checked_sub(initial_deposit)
         .expect("initial_deposit ≤ max_top_up: enforced by initial-deposit cap guard in step 1");
     if actual_top_up > remaining_cap {
+        metrics::storage::record_cap_breach();
         return Err(anyhow::anyhow!(
             "ref storage top-up {} yocto exceeds remaining cap {} yocto \
              (max_top_up={}, initial_deposit={})",
@@ -575,7 +603,13 @@ where
         ));
     }
 
-    // 6. top-up
+    // 6-7. top-up と register_tokens を 1 トランザクションで送る
+    //
+    // 同一レシート内の action は原子的に実行されるため、register_tokens が拒否された
+    // 場合は top-up も巻き戻り（attached deposit は返金される）、「top-up だけ済んで
+    // 登録されていない」半端な状態が残らない。cap 検証（ステップ 5）はバッチを組む前に
+    // 完了しているので、cap 会計は従来どおり actual_top_up だけで閉じる。
+    let mut batch = TxBatch::new(CONTRACT_ADDRESS.clone());
     if !actual_top_up.is_zero() {
         warn!(log, "ref storage top-up";
             "wallet" => %account,
@@ -583,24 +617,26 @@ where
             "available_before" => post_unregister_available,
             "cap" => max_top_up.as_yoctonear(),
         );
-        deposit(
-            client,
-            wallet,
+        batch.push(deposit_call(
             actual_top_up,
             deposit::DepositMode::DepositWithRegistration,
-        )
-        .await?
-        .wait_for_success()
-        .await?;
+        )?)?;
     }
-
-    // 7. register_tokens
     if !to_register.is_empty() {
         info!(log, "registering tokens"; "count" => to_register.len());
-        deposit::register_tokens(client, wallet, &to_register)
+        batch.push(deposit::register_tokens_call(&to_register)?)?;
+    }
+    if !batch.is_empty() {
+        batch
+            .send(client, wallet.signer())
             .await?
             .wait_for_success()
             .await?;
+    }
+    if !actual_top_up.is_zero() {
+        metrics::storage::record_top_up(&YoctoAmount::from_u128(actual_top_up.as_yoctonear()));
+    }
+    if !to_register.is_empty() {
         info!(log, "tokens registered"; "count" => to_register.len());
     }
 
@@ -1,13 +1,20 @@
+use crate::batch::{BatchCall, MAX_TX_GAS, TxBatch};
 use crate::ref_finance::CONTRACT_ADDRESS;
+use crate::ref_finance::deposit;
 use crate::wallet::Wallet;
 use crate::{Result, jsonrpc};
+use common::types::TokenAccount;
 use dex::{TokenPair, TokenPairLike};
 use logging::*;
+use near_primitives::types::Gas;
 use near_primitives::views::{FinalExecutionOutcomeView, FinalExecutionStatus};
 use near_sdk::json_types::U128;
 use near_sdk::{AccountId, NearToken};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use thiserror::Error;
 
 /// Single swap action.
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -88,6 +95,172 @@ where
     Ok((actions, out))
 }
 
+/// 分割スワップの 1 経路分
+///
+/// 各経路は `amount_in` を持つ最初のアクションから独立したチェーンとして実行される。
+pub struct SwapLeg<'a, T> {
+    pub path: &'a [T],
+    pub arg: SwapArg,
+}
+
+/// 複数経路のアクションを 1 回の `swap` 呼び出し用に連結する
+///
+/// 経路ごとに最後のアクションへその経路の `min_out` を付ける。戻り値の出力は全経路の推定合計。
+fn build_split_swap_actions<T>(legs: &[SwapLeg<'_, T>]) -> Result<(Vec<SwapAction>, u128)>
+where
+    T: TokenPairLike,
+{
+    let mut actions = Vec::new();
+    let mut total_out = 0;
+    for leg in legs {
+        let (leg_actions, out) = build_swap_actions(leg.path, leg.arg.clone())?;
+        actions.extend(leg_actions);
+        total_out += out;
+    }
+    Ok((actions, total_out))
+}
+
+/// 送信前の dry run で得た 1 アクション分の on-chain 見積もり
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HopQuote {
+    pub pool_id: u32,
+    pub amount_in: u128,
+    pub amount_out: u128,
+}
+
+/// dry run で送信を見送った理由
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PreflightError {
+    #[error("token is not registered in REF deposits: {0}")]
+    NotRegistered(AccountId),
+    #[error("insufficient REF deposit of {token}: available={available} required={required}")]
+    InsufficientDeposit {
+        token: AccountId,
+        available: u128,
+        required: u128,
+    },
+    #[error("first swap action of a chain has no amount_in (pool {0})")]
+    MissingAmountIn(u32),
+    #[error("E68: slippage error at pool {pool_id}: quote={quote} min_amount_out={min_amount_out}")]
+    Slippage {
+        pool_id: u32,
+        quote: u128,
+        min_amount_out: u128,
+    },
+}
+
+/// アクションが使うトークンがすべて登録済みで、各チェーンの入力分の deposit があるか確認する
+fn check_deposits(
+    deposits: &BTreeMap<TokenAccount, U128>,
+    actions: &[SwapAction],
+) -> std::result::Result<(), PreflightError> {
+    let mut required: BTreeMap<&AccountId, u128> = BTreeMap::new();
+    for action in actions {
+        for token in [&action.token_in, &action.token_out] {
+            if !deposits.contains_key(&TokenAccount::from(token.clone())) {
+                return Err(PreflightError::NotRegistered(token.clone()));
+            }
+        }
+        if let Some(amount_in) = action.amount_in {
+            *required.entry(&action.token_in).or_default() += amount_in.0;
+        }
+    }
+    for (token, required) in required {
+        let available = deposits
+            .get(&TokenAccount::from(token.clone()))
+            .map(|u| u.0)
+            .unwrap_or_default();
+        if available < required {
+            return Err(PreflightError::InsufficientDeposit {
+                token: token.clone(),
+                available,
+                required,
+            });
+        }
+    }
+    Ok(())
+}
+
+async fn get_return<C: jsonrpc::ViewContract>(
+    client: &C,
+    action: &SwapAction,
+    amount_in: u128,
+) -> Result<u128> {
+    let args = json!({
+        "pool_id": action.pool_id,
+        "token_in": action.token_in,
+        "amount_in": U128(amount_in),
+        "token_out": action.token_out,
+    });
+    let result = client
+        .view_contract(&CONTRACT_ADDRESS, "get_return", &args)
+        .await?;
+    let amount: U128 = serde_json::from_slice(&result.result)?;
+    Ok(amount.0)
+}
+
+/// `actions` を送信せずに現在の chain state で試算する
+///
+/// 各アクションのプールに `get_return` を問い合わせ、前のアクションの出力を次の入力として
+/// たどる。トークンの未登録、deposit 不足、`min_amount_out` を下回る見積もり（契約側の
+/// `E68: slippage error` に相当）のいずれかがあれば [`PreflightError`] を返す。
+///
+/// 見積もりはすべて送信前のプール状態に対するもので、同じプールを 2 度通る場合の
+/// 途中の残高変化は反映されない。
+pub async fn preflight<C: jsonrpc::ViewContract>(
+    client: &C,
+    account: &AccountId,
+    actions: &[SwapAction],
+) -> Result<Vec<HopQuote>> {
+    let deposits = deposit::get_deposits(client, account).await?;
+    check_deposits(&deposits, actions)?;
+    quote_actions(client, actions).await
+}
+
+/// アクションのチェーンを `get_return` でたどり、`min_amount_out` を下回る見積もりがあれば拒否する
+async fn quote_actions<C: jsonrpc::ViewContract>(
+    client: &C,
+    actions: &[SwapAction],
+) -> Result<Vec<HopQuote>> {
+    let log = DEFAULT.new(o!(
+        "function" => "quote_actions",
+        "actions" => actions.len(),
+    ));
+
+    let mut quotes = Vec::with_capacity(actions.len());
+    let mut prev_out = None;
+    for action in actions {
+        let pool_id = action.pool_id;
+        let amount_in = match (action.amount_in, prev_out) {
+            (Some(amount_in), _) => amount_in.0,
+            (None, Some(prev_out)) => prev_out,
+            (None, None) => return Err(PreflightError::MissingAmountIn(pool_id).into()),
+        };
+        let quote = get_return(client, action, amount_in).await?;
+        debug!(log, "quoted";
+            "pool_id" => pool_id,
+            "amount_in" => amount_in,
+            "quote" => quote,
+            "min_amount_out" => action.min_amount_out.0,
+        );
+        if quote < action.min_amount_out.0 {
+            return Err(PreflightError::Slippage {
+                pool_id,
+                quote,
+                min_amount_out: action.min_amount_out.0,
+            }
+            .into());
+        }
+        quotes.push(HopQuote {
+            pool_id,
+            amount_in,
+            amount_out: quote,
+        });
+        prev_out = Some(quote);
+    }
+    Ok(quotes)
+}
+
 pub async fn run_swap<A, W>(
     client: &A,
     wallet: &W,
@@ -95,7 +268,7 @@ pub async fn run_swap<A, W>(
     arg: SwapArg,
 ) -> Result<(A::Output, u128)>
 where
-    A: jsonrpc::SendTx,
+    A: jsonrpc::SendTx + jsonrpc::ViewContract,
     W: Wallet,
 {
     let log = DEFAULT.new(o!(
@@ -107,30 +280,188 @@ where
     trace!(log, "entered");
 
     let (actions, out) = build_swap_actions(path, arg)?;
+    preflight(client, wallet.account_id(), &actions).await?;
+    let tx = send_swap(client, wallet, actions).await?;
+    Ok((tx, out))
+}
+
+/// 複数経路に分割したスワップを 1 トランザクションで実行する
+pub async fn run_split_swap<A, W>(
+    client: &A,
+    wallet: &W,
+    legs: &[SwapLeg<'_, TokenPair>],
+) -> Result<(A::Output, u128)>
+where
+    A: jsonrpc::SendTx + jsonrpc::ViewContract,
+    W: Wallet,
+{
+    let log = DEFAULT.new(o!(
+        "function" => "run_split_swap",
+        "legs" => legs.len(),
+        "initial" => legs.iter().map(|leg| leg.arg.initial_in).sum::<u128>(),
+        "min_out" => legs.iter().map(|leg| leg.arg.min_out).sum::<u128>(),
+    ));
+    trace!(log, "entered");
+
+    let (actions, out) = build_split_swap_actions(legs)?;
+    preflight(client, wallet.account_id(), &actions).await?;
+    let tx = send_swap(client, wallet, actions).await?;
+    Ok((tx, out))
+}
 
+async fn send_swap<A, W>(client: &A, wallet: &W, actions: Vec<SwapAction>) -> Result<A::Output>
+where
+    A: jsonrpc::SendTx,
+    W: Wallet,
+{
     let args = json!({
         "actions": actions,
     });
-
-    let deposit = NearToken::from_yoctonear(1);
     let signer = wallet.signer();
 
-    let tx_hash = client
-        .exec_contract(signer, &CONTRACT_ADDRESS, METHOD_NAME, args, deposit)
-        .await?;
+    client
+        .exec_contract(
+            signer,
+            &CONTRACT_ADDRESS,
+            METHOD_NAME,
+            args,
+            swap_deposit(wallet),
+        )
+        .await
+}
+
+/// `swap` に添付する deposit
+///
+/// function-call access key は deposit を添付できない。REF の swap は 1 yocto を
+/// 必須としていないので、その場合は 0 で送る。
+fn swap_deposit<W: Wallet>(wallet: &W) -> NearToken {
+    if wallet.is_function_call_key() {
+        NearToken::from_yoctonear(0)
+    } else {
+        NearToken::from_yoctonear(1)
+    }
+}
+
+/// バッチに積む `swap` 呼び出し 1 回あたりの固定ガス
+pub const SWAP_BASE_GAS: Gas = Gas::from_teragas(10);
+/// バッチに積む `swap` の action 1 つごとに加算するガス
+pub const SWAP_STEP_GAS: Gas = Gas::from_teragas(10);
+
+/// `actions` 個の action を持つ `swap` 呼び出しにバッチ内で添付するガス
+///
+/// `swap` は REF 内で完結し cross-contract 呼び出しを伴わないので、action 数に比例した
+/// ガスで足りる（単発の送信は上限まで添付するが、バッチでは呼び出し同士で分け合う）。
+pub fn swap_call_gas(actions: usize) -> Gas {
+    Gas::from_gas(
+        SWAP_BASE_GAS
+            .as_gas()
+            .saturating_add(SWAP_STEP_GAS.as_gas().saturating_mul(actions as u64)),
+    )
+}
+
+/// スワップを順序を保ったまま REF 宛てのバッチに分ける
+///
+/// `action_counts` は各スワップ（`swap` 呼び出し 1 回）の action 数。戻り値は
+/// `action_counts` の添字の範囲で、添付ガスの合計が [`MAX_TX_GAS`] に収まるように区切る。
+/// function-call access key は複数 action のトランザクションに署名できないため、
+/// その場合は 1 件ずつになる。
+pub fn swap_batches<W: Wallet>(wallet: &W, action_counts: &[usize]) -> Vec<Range<usize>> {
+    let max_calls = if wallet.is_function_call_key() {
+        1
+    } else {
+        usize::MAX
+    };
+    pack_swap_calls(action_counts, max_calls)
+}
 
-    Ok((tx_hash, out))
+fn pack_swap_calls(action_counts: &[usize], max_calls: usize) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut gas = 0u64;
+    for (i, &actions) in action_counts.iter().enumerate() {
+        let call_gas = swap_call_gas(actions).as_gas();
+        let full = i - start >= max_calls || gas.saturating_add(call_gas) > MAX_TX_GAS.as_gas();
+        if i > start && full {
+            batches.push(start..i);
+            start = i;
+            gas = 0;
+        }
+        gas = gas.saturating_add(call_gas);
+    }
+    if start < action_counts.len() {
+        batches.push(start..action_counts.len());
+    }
+    batches
+}
+
+/// 複数のスワップを REF 宛ての 1 トランザクションで実行する
+///
+/// `swaps` の各要素が `swap` 呼び出し 1 回分（分割経路を含む）になる。呼び出しは同じ
+/// レシートで順に実行され、どれかが失敗するとすべて巻き戻る。送信前に全呼び出しを合わせた
+/// deposit と、呼び出しごとの見積もりを [`preflight`] と同じ基準で確認する。見積もりは
+/// 送信前のプール状態に対するもので、先の呼び出しによる価格の変化は反映されない。
+///
+/// 戻り値の 2 つ目は呼び出しごとの推定出力。
+pub async fn run_swap_batch<A, W>(
+    client: &A,
+    wallet: &W,
+    swaps: &[Vec<SwapLeg<'_, TokenPair>>],
+) -> Result<(A::Output, Vec<u128>)>
+where
+    A: jsonrpc::SendTx + jsonrpc::ViewContract,
+    W: Wallet,
+{
+    let log = DEFAULT.new(o!(
+        "function" => "run_swap_batch",
+        "swaps" => swaps.len(),
+    ));
+    trace!(log, "entered");
+
+    let calls = swaps
+        .iter()
+        .map(|legs| build_split_swap_actions(legs))
+        .collect::<Result<Vec<_>>>()?;
+
+    let all_actions: Vec<SwapAction> = calls
+        .iter()
+        .flat_map(|(actions, _)| actions.iter().cloned())
+        .collect();
+    let deposits = deposit::get_deposits(client, wallet.account_id()).await?;
+    check_deposits(&deposits, &all_actions)?;
+    for (actions, _) in &calls {
+        quote_actions(client, actions).await?;
+    }
+
+    let deposit = swap_deposit(wallet);
+    let mut batch = TxBatch::new(CONTRACT_ADDRESS.clone());
+    let mut outs = Vec::with_capacity(calls.len());
+    for (actions, out) in calls {
+        let gas = swap_call_gas(actions.len());
+        batch.push(BatchCall::new(
+            METHOD_NAME,
+            &json!({ "actions": actions }),
+            deposit,
+            gas,
+        )?)?;
+        outs.push(out);
+    }
+    let tx = batch.send(client, wallet.signer()).await?;
+    Ok((tx, outs))
 }
 
 /// Extract the actual output amount from a successful swap transaction outcome.
 ///
 /// # Contract assumption
 ///
-/// REF Finance's `swap()` contract function returns the actual output token amount
-/// as a JSON-encoded `U128` in `FinalExecutionStatus::SuccessValue`, even for
-/// multi-hop swaps. If the contract changes this format (e.g. after an upgrade),
-/// parsing will fail with a `warn` log and the caller should treat the result as
-/// `None` (i.e. `actual_to_amount` = NULL in the database).
+/// REF Finance's `swap()` contract function returns the output of the *last*
+/// action as a JSON-encoded `U128` in `FinalExecutionStatus::SuccessValue`.
+/// For split swaps (several action chains in one call) that is only the last
+/// leg, so the per-action `Swapped {in} {token_in} for {out} {token_out}` logs
+/// are summed for the final output token instead. When no such logs are
+/// present, the `SuccessValue` is used as is. If the contract changes this
+/// format (e.g. after an upgrade), parsing will fail with a `warn` log and the
+/// caller should treat the result as `None` (i.e. `actual_to_amount` = NULL in
+/// the database).
 pub fn extract_actual_output(view: &FinalExecutionOutcomeView) -> Result<u128> {
     let log = DEFAULT.new(o!("function" => "extract_actual_output"));
     match &view.status {
@@ -145,7 +476,7 @@ pub fn extract_actual_output(view: &FinalExecutionOutcomeView) -> Result<u128> {
                 );
                 e
             })?;
-            Ok(amount.0)
+            Ok(sum_swap_logs(view).unwrap_or(amount.0))
         }
         FinalExecutionStatus::Failure(err) => Err(anyhow::anyhow!("Transaction failed: {:?}", err)),
         _ => Err(anyhow::anyhow!(
@@ -155,5 +486,87 @@ pub fn extract_actual_output(view: &FinalExecutionOutcomeView) -> Result<u128> {
     }
 }
 
+/// トランザクション全体で燃やされたガスと NEAR（yocto）を合計する
+///
+/// トランザクション自身の outcome と、そこから派生したすべてのレシートを含む。
+pub fn extract_burnt(view: &FinalExecutionOutcomeView) -> (u64, u128) {
+    std::iter::once(&view.transaction_outcome)
+        .chain(view.receipts_outcome.iter())
+        .fold((0u64, 0u128), |(gas, tokens), o| {
+            (
+                gas.saturating_add(o.outcome.gas_burnt.as_gas()),
+                tokens.saturating_add(o.outcome.tokens_burnt.as_yoctonear()),
+            )
+        })
+}
+
+/// [`run_swap_batch`] で送ったスワップの実際の出力を呼び出しごとに取り出す
+///
+/// `Swapped` ログは action ごとに 1 行出るので、各呼び出しの action 数（`action_counts`）で
+/// ログを区切り、呼び出しごとに最終出力トークンへの出力を合計する。トランザクションが
+/// 成功していない、またはログの数が action の合計と合わず区切れない場合はすべて `None`。
+pub fn extract_batch_outputs(
+    view: &FinalExecutionOutcomeView,
+    action_counts: &[usize],
+) -> Vec<Option<u128>> {
+    let log = DEFAULT.new(o!("function" => "extract_batch_outputs"));
+    let swaps = swap_logs(view);
+    let expected: usize = action_counts.iter().sum();
+    if !matches!(view.status, FinalExecutionStatus::SuccessValue(_)) || swaps.len() != expected {
+        warn!(log, "cannot split swap logs by call";
+            "logs" => swaps.len(),
+            "actions" => expected,
+        );
+        return vec![None; action_counts.len()];
+    }
+    let mut rest = swaps.as_slice();
+    action_counts
+        .iter()
+        .map(|&count| {
+            let (call, tail) = rest.split_at(count);
+            rest = tail;
+            sum_final_outputs(call)
+        })
+        .collect()
+}
+
+/// レシートの `Swapped` ログを順に `(amount_out, token_out)` として取り出す
+fn swap_logs(view: &FinalExecutionOutcomeView) -> Vec<(u128, &str)> {
+    view.receipts_outcome
+        .iter()
+        .flat_map(|receipt| receipt.outcome.logs.iter())
+        .filter_map(|log| parse_swap_log(log))
+        .collect()
+}
+
+/// スワップログから最終出力トークンへの出力量を合計する（ログが無ければ `None`）
+fn sum_swap_logs(view: &FinalExecutionOutcomeView) -> Option<u128> {
+    sum_final_outputs(&swap_logs(view))
+}
+
+/// 最後のログの出力トークンを最終トークンとみなし、その出力を合計する
+///
+/// 各経路は単純路なので、最終トークンが出力になるのは各経路の最後のアクションだけになる。
+fn sum_final_outputs(swaps: &[(u128, &str)]) -> Option<u128> {
+    let &(_, final_token) = swaps.last()?;
+    Some(
+        swaps
+            .iter()
+            .filter(|(_, token)| *token == final_token)
+            .map(|(amount, _)| amount)
+            .sum(),
+    )
+}
+
+/// `Swapped {amount_in} {token_in} for {amount_out} {token_out}[, ...]` から
+/// `(amount_out, token_out)` を取り出す
+fn parse_swap_log(log: &str) -> Option<(u128, &str)> {
+    let (_, out) = log.strip_prefix("Swapped ")?.split_once(" for ")?;
+    let mut words = out.split_whitespace();
+    let amount = words.next()?.parse().ok()?;
+    let token = words.next()?.trim_end_matches(',');
+    Some((amount, token))
+}
+
 #[cfg(test)]
 mod tests;
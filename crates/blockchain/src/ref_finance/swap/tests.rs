@@ -163,6 +163,158 @@ fn test_build_swap_actions_with_very_small_amounts() {
     assert_eq!(actions[0].min_amount_out.0, 5);
 }
 
+#[test]
+fn test_build_split_swap_actions() {
+    // A→B の直結と A→C→B の 2 経路に分割
+    let direct = [MockTokenPair {
+        pool_id: 1,
+        token_in: "token_a".parse().unwrap(),
+        token_out: "token_b".parse().unwrap(),
+        rate: 2.0,
+    }];
+    let via_c = [
+        MockTokenPair {
+            pool_id: 2,
+            token_in: "token_a".parse().unwrap(),
+            token_out: "token_c".parse().unwrap(),
+            rate: 1.0,
+        },
+        MockTokenPair {
+            pool_id: 3,
+            token_in: "token_c".parse().unwrap(),
+            token_out: "token_b".parse().unwrap(),
+            rate: 1.5,
+        },
+    ];
+    let legs = [
+        SwapLeg {
+            path: &direct[..],
+            arg: SwapArg {
+                initial_in: 600,
+                min_out: 1100,
+            },
+        },
+        SwapLeg {
+            path: &via_c[..],
+            arg: SwapArg {
+                initial_in: 400,
+                min_out: 550,
+            },
+        },
+    ];
+
+    let (actions, output) = build_split_swap_actions(&legs).unwrap();
+
+    assert_eq!(actions.len(), 3);
+    // 各経路の先頭アクションだけが amount_in を持ち、新しいチェーンを開始する
+    assert_eq!(actions[0].pool_id, 1);
+    assert_eq!(actions[0].amount_in, Some(U128(600)));
+    assert_eq!(actions[0].min_amount_out.0, 1100);
+    assert_eq!(actions[1].pool_id, 2);
+    assert_eq!(actions[1].amount_in, Some(U128(400)));
+    assert_eq!(actions[1].min_amount_out.0, 0);
+    assert_eq!(actions[2].pool_id, 3);
+    assert_eq!(actions[2].amount_in, None);
+    assert_eq!(actions[2].min_amount_out.0, 550);
+
+    assert_eq!(output, 1200 + 600);
+}
+
+fn outcome_with_logs(success_value: &[u8], logs: &[&str]) -> FinalExecutionOutcomeView {
+    let mut view = dummy_final_outcome(success_value.to_vec());
+    let mut receipt = view.transaction_outcome.clone();
+    receipt.outcome.logs = logs.iter().map(|l| l.to_string()).collect();
+    view.receipts_outcome.push(receipt);
+    view
+}
+
+#[test]
+fn test_extract_actual_output_sums_split_legs() {
+    // SuccessValue は最後の経路の出力のみ
+    let view = outcome_with_logs(
+        b"\"900\"",
+        &[
+            "Swapped 600 token_a for 1200 token_b",
+            "Swapped 400 token_a for 400 token_c",
+            "Swapped 400 token_c for 900 token_b, total fee 12, admin fee 2",
+        ],
+    );
+    assert_eq!(extract_actual_output(&view).unwrap(), 2100);
+}
+
+#[test]
+fn test_extract_actual_output_single_path_logs() {
+    // 往復（A→B→A）では最後の出力だけが最終トークン
+    let view = outcome_with_logs(
+        b"\"1010\"",
+        &[
+            "Swapped 1000 token_a for 2000 token_b",
+            "Exchange ref.near got 3 shares",
+            "Swapped 2000 token_b for 1010 token_a",
+        ],
+    );
+    assert_eq!(extract_actual_output(&view).unwrap(), 1010);
+}
+
+#[test]
+fn test_extract_batch_outputs_splits_logs_by_call() {
+    let view = outcome_with_logs(
+        b"\"70\"",
+        &[
+            // 1 回目: token_a -> wrap.near の 2 経路
+            "Swapped 600 token_a for 30 wrap.near",
+            "Swapped 400 token_a for 20 wrap.near",
+            // 2 回目: token_b -> wrap.near -> token_c
+            "Swapped 100 token_b for 5 wrap.near",
+            "Swapped 5 wrap.near for 70 token_c",
+        ],
+    );
+    assert_eq!(
+        extract_batch_outputs(&view, &[2, 2]),
+        vec![Some(50), Some(70)]
+    );
+    // ログの数が action の合計と合わなければ区切れない
+    assert_eq!(extract_batch_outputs(&view, &[2, 1]), vec![None, None]);
+}
+
+#[test]
+fn test_pack_swap_calls_by_gas() {
+    assert_eq!(swap_call_gas(2), Gas::from_teragas(30));
+    // 1 ホップ（20 Tgas）は 15 件で 300 Tgas
+    let counts = vec![1; 16];
+    assert_eq!(pack_swap_calls(&counts, usize::MAX), vec![0..15, 15..16]);
+    assert_eq!(pack_swap_calls(&[2, 1, 3], 1), vec![0..1, 1..2, 2..3]);
+    assert!(pack_swap_calls(&[], usize::MAX).is_empty());
+}
+
+#[test]
+fn test_extract_burnt_sums_receipts() {
+    let mut view = outcome_with_logs(b"\"1\"", &[]);
+    view.transaction_outcome.outcome.gas_burnt = near_primitives::types::Gas::from_gas(100);
+    view.transaction_outcome.outcome.tokens_burnt = NearToken::from_yoctonear(10);
+    let mut receipt = view.transaction_outcome.clone();
+    receipt.outcome.gas_burnt = near_primitives::types::Gas::from_gas(250);
+    receipt.outcome.tokens_burnt = NearToken::from_yoctonear(25);
+    view.receipts_outcome.push(receipt);
+
+    // 先頭のレシートは outcome_with_logs が作った 0 のもの
+    assert_eq!(extract_burnt(&view), (350, 35));
+}
+
+#[test]
+fn test_parse_swap_log() {
+    assert_eq!(
+        parse_swap_log("Swapped 10 wrap.near for 25 usdt.tether-token.near"),
+        Some((25, "usdt.tether-token.near"))
+    );
+    assert_eq!(
+        parse_swap_log("Swapped 10 a.near for 25 b.near, total fee 3, admin fee 1"),
+        Some((25, "b.near"))
+    );
+    assert_eq!(parse_swap_log("Transfer 10 a.near"), None);
+    assert_eq!(parse_swap_log("Swapped 10 a.near for many b.near"), None);
+}
+
 #[test]
 fn test_extract_actual_output_success() {
     // REF Finance returns U128 as JSON-encoded string: "\"12345\""
@@ -224,3 +376,172 @@ fn test_extract_actual_output_failure() {
         "unexpected error message: {err_msg}"
     );
 }
+
+// --- preflight ---
+
+/// `get_deposits` と、プールごとに固定倍率で返す `get_return` を持つクライアント
+struct QuoteClient {
+    deposits: BTreeMap<TokenAccount, U128>,
+    multipliers: BTreeMap<u32, u128>,
+    quoted: std::sync::Mutex<Vec<(u32, u128)>>,
+}
+
+impl QuoteClient {
+    fn new(deposits: &[(&str, u128)], multipliers: &[(u32, u128)]) -> Self {
+        Self {
+            deposits: deposits
+                .iter()
+                .map(|(t, a)| (t.parse().unwrap(), U128(*a)))
+                .collect(),
+            multipliers: multipliers.iter().copied().collect(),
+            quoted: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl jsonrpc::ViewContract for QuoteClient {
+    async fn view_contract<T>(
+        &self,
+        _receiver: &AccountId,
+        method_name: &str,
+        args: &T,
+    ) -> Result<near_primitives::views::CallResult>
+    where
+        T: ?Sized + serde::Serialize + Sync,
+    {
+        let result = match method_name {
+            "get_deposits" => serde_json::to_vec(&self.deposits)?,
+            "get_return" => {
+                let args = serde_json::to_value(args)?;
+                let pool_id = args["pool_id"].as_u64().unwrap() as u32;
+                let amount_in: U128 = serde_json::from_value(args["amount_in"].clone())?;
+                self.quoted.lock().unwrap().push((pool_id, amount_in.0));
+                serde_json::to_vec(&U128(amount_in.0 * self.multipliers[&pool_id]))?
+            }
+            other => panic!("unexpected view call: {other}"),
+        };
+        Ok(near_primitives::views::CallResult {
+            result,
+            logs: vec![],
+        })
+    }
+}
+
+fn action(
+    pool_id: u32,
+    token_in: &str,
+    token_out: &str,
+    amount_in: Option<u128>,
+    min_out: u128,
+) -> SwapAction {
+    SwapAction {
+        pool_id,
+        token_in: token_in.parse().unwrap(),
+        amount_in: amount_in.map(U128),
+        token_out: token_out.parse().unwrap(),
+        min_amount_out: U128(min_out),
+    }
+}
+
+fn account() -> AccountId {
+    "app.zaciraci.near".parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_preflight_chains_quotes() {
+    let client = QuoteClient::new(
+        &[("wrap.near", 100), ("a.near", 0), ("b.near", 0)],
+        &[(1, 2), (2, 3)],
+    );
+    let actions = vec![
+        action(1, "wrap.near", "a.near", Some(100), 0),
+        action(2, "a.near", "b.near", None, 600),
+    ];
+
+    let quotes = preflight(&client, &account(), &actions).await.unwrap();
+
+    assert_eq!(
+        quotes,
+        vec![
+            HopQuote {
+                pool_id: 1,
+                amount_in: 100,
+                amount_out: 200,
+            },
+            HopQuote {
+                pool_id: 2,
+                amount_in: 200,
+                amount_out: 600,
+            },
+        ]
+    );
+    assert_eq!(*client.quoted.lock().unwrap(), vec![(1, 100), (2, 200)]);
+}
+
+#[tokio::test]
+async fn test_preflight_refuses_below_min_out() {
+    let client = QuoteClient::new(&[("wrap.near", 100), ("a.near", 0)], &[(1, 2)]);
+    let actions = vec![action(1, "wrap.near", "a.near", Some(100), 201)];
+
+    let err = preflight(&client, &account(), &actions).await.unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<PreflightError>(),
+        Some(&PreflightError::Slippage {
+            pool_id: 1,
+            quote: 200,
+            min_amount_out: 201,
+        })
+    );
+    assert!(err.to_string().starts_with("E68: slippage error"));
+}
+
+#[tokio::test]
+async fn test_preflight_checks_deposits_before_quoting() {
+    let client = QuoteClient::new(&[("wrap.near", 100)], &[(1, 2)]);
+    let actions = vec![action(1, "wrap.near", "a.near", Some(100), 0)];
+
+    let err = preflight(&client, &account(), &actions).await.unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<PreflightError>(),
+        Some(&PreflightError::NotRegistered("a.near".parse().unwrap()))
+    );
+    assert!(client.quoted.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_check_deposits_sums_split_legs() {
+    let deposits: BTreeMap<TokenAccount, U128> = [("wrap.near", 150), ("a.near", 0), ("b.near", 0)]
+        .iter()
+        .map(|(t, a)| (t.parse().unwrap(), U128(*a)))
+        .collect();
+    // 2 経路とも wrap.near から始まるので入力の合計が必要
+    let actions = vec![
+        action(1, "wrap.near", "a.near", Some(100), 0),
+        action(2, "wrap.near", "b.near", Some(100), 0),
+    ];
+
+    assert_eq!(
+        check_deposits(&deposits, &actions),
+        Err(PreflightError::InsufficientDeposit {
+            token: "wrap.near".parse().unwrap(),
+            available: 150,
+            required: 200,
+        })
+    );
+    assert_eq!(check_deposits(&deposits, &actions[..1]), Ok(()));
+}
+
+#[tokio::test]
+async fn test_preflight_requires_amount_in_on_first_action() {
+    let client = QuoteClient::new(&[("wrap.near", 100), ("a.near", 0)], &[(1, 2)]);
+    let actions = vec![action(1, "wrap.near", "a.near", None, 0)];
+
+    let err = preflight(&client, &account(), &actions).await.unwrap_err();
+
+    assert_eq!(
+        err.downcast_ref::<PreflightError>(),
+        Some(&PreflightError::MissingAmountIn(1))
+    );
+}
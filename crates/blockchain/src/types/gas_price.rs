@@ -12,4 +12,9 @@ impl GasPrice {
     pub const fn to_balance(self) -> u128 {
         self.0 as u128
     }
+
+    /// Returns the cost of burning `gas` at this price in yoctoNEAR.
+    pub const fn cost_of(self, gas: near_gas::NearGas) -> u128 {
+        gas.as_gas() as u128 * self.to_balance()
+    }
 }
@@ -6,6 +6,10 @@ use near_crypto::SecretKey::ED25519;
 use near_crypto::{ED25519SecretKey, InMemorySigner};
 use near_sdk::AccountId;
 
+mod access_keys;
+
+pub use access_keys::{AccessKeyPool, AccessKeyWallet};
+
 const CURVE: slipped10::Curve = slipped10::Curve::Ed25519;
 const HARDEND: u32 = 1 << 31;
 
@@ -17,6 +21,11 @@ pub fn new_wallet() -> StandardWallet {
 pub trait Wallet {
     fn account_id(&self) -> &AccountId;
     fn signer(&self) -> &InMemorySigner;
+
+    /// function-call access key で署名する場合は true。deposit を添付できない
+    fn is_function_call_key(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone)]
@@ -0,0 +1,170 @@
+//! HD ウォレットから派生した function-call access key
+//!
+//! 同じ鍵で署名するトランザクションは nonce で直列化されるため、独立したタスク
+//! （trade と arbitrage など）が並行に送るには別の鍵を使うのがよい。ここでは root の
+//! HD パスから子鍵を派生させ、REF Finance の `swap` だけを呼べる function-call
+//! access key としてアカウントに登録する。
+//!
+//! function-call access key は deposit を添付できないため、storage の top-up や
+//! `ft_transfer_call` など deposit を伴う呼び出しは従来どおり root の鍵で送る。
+
+use super::{StandardWallet, Wallet};
+use crate::Result;
+use crate::jsonrpc::{AccessKeyInfo, SendTx, SentTx};
+use crate::ref_finance::CONTRACT_ADDRESS;
+use logging::*;
+use near_crypto::InMemorySigner;
+use near_jsonrpc_client::errors::{JsonRpcError, JsonRpcServerError};
+use near_jsonrpc_primitives::types::query::RpcQueryError;
+use near_primitives::account::{AccessKey, AccessKeyPermission, FunctionCallPermission};
+use near_primitives::action::{Action, AddKeyAction};
+use near_sdk::AccountId;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 派生に使う HD パスの先頭 index（既存の派生先と重ならないよう離しておく）
+const BASE_INDEX: i32 = 1000;
+
+/// function-call access key に許可するメソッド
+const ALLOWED_METHODS: &[&str] = &["swap"];
+
+/// function-call access key で署名するウォレット
+#[derive(Clone)]
+pub struct AccessKeyWallet {
+    inner: StandardWallet,
+}
+
+impl AccessKeyWallet {
+    pub fn pub_base58(&self) -> String {
+        self.inner.pub_base58()
+    }
+
+    fn add_key_action(&self) -> Action {
+        Action::AddKey(Box::new(AddKeyAction {
+            public_key: self.inner.signer().public_key(),
+            access_key: AccessKey {
+                nonce: 0,
+                permission: AccessKeyPermission::FunctionCall(FunctionCallPermission {
+                    allowance: None,
+                    receiver_id: CONTRACT_ADDRESS.to_string(),
+                    method_names: ALLOWED_METHODS.iter().map(|m| m.to_string()).collect(),
+                }),
+            },
+        }))
+    }
+}
+
+impl Wallet for AccessKeyWallet {
+    fn account_id(&self) -> &AccountId {
+        self.inner.account_id()
+    }
+
+    fn signer(&self) -> &InMemorySigner {
+        self.inner.signer()
+    }
+
+    fn is_function_call_key(&self) -> bool {
+        true
+    }
+}
+
+/// root ウォレットから派生した function-call access key の集合
+///
+/// `acquire` は鍵を順番に貸し出す。鍵ごとに nonce が独立しているので、
+/// 別々の鍵を使うタスク同士は並行に送信できる。
+pub struct AccessKeyPool {
+    root: StandardWallet,
+    keys: Vec<AccessKeyWallet>,
+    next: AtomicUsize,
+}
+
+impl AccessKeyPool {
+    pub fn derive(root: &StandardWallet, size: u32) -> Result<Self> {
+        let keys = (0..size)
+            .map(|i| {
+                let index = BASE_INDEX + i32::try_from(i)?;
+                Ok(AccessKeyWallet {
+                    inner: root.derive(index)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            root: root.clone(),
+            keys,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// 鍵を持たないプール。`acquire` は常に `None` を返す
+    pub fn empty(root: &StandardWallet) -> Self {
+        Self {
+            root: root.clone(),
+            keys: Vec::new(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn keys(&self) -> &[AccessKeyWallet] {
+        &self.keys
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// 次の鍵を貸し出す。プールが空なら `None`
+    pub fn acquire(&self) -> Option<&AccessKeyWallet> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let i = self.next.fetch_add(1, Ordering::Relaxed) % self.keys.len();
+        Some(&self.keys[i])
+    }
+
+    /// 未登録の鍵を root の鍵で 1 トランザクションにまとめて登録する
+    ///
+    /// 登録した鍵の数を返す。
+    pub async fn provision<C>(&self, client: &C) -> Result<usize>
+    where
+        C: SendTx + AccessKeyInfo,
+    {
+        let log = DEFAULT.new(o!(
+            "function" => "AccessKeyPool::provision",
+            "account_id" => format!("{}", self.root.account_id()),
+            "size" => self.keys.len(),
+        ));
+        let mut actions = Vec::new();
+        for key in &self.keys {
+            match client.get_access_key_info(key.signer()).await {
+                Ok(_) => trace!(log, "access key already registered"; "pubkey" => key.pub_base58()),
+                Err(err) if is_unknown_access_key(&err) => {
+                    debug!(log, "access key not registered"; "pubkey" => key.pub_base58());
+                    actions.push(key.add_key_action());
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if actions.is_empty() {
+            return Ok(0);
+        }
+        let added = actions.len();
+        client
+            .send_tx(self.root.signer(), self.root.account_id(), actions)
+            .await?
+            .wait_for_success()
+            .await?;
+        info!(log, "registered function-call access keys"; "added" => added);
+        Ok(added)
+    }
+}
+
+fn is_unknown_access_key(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<JsonRpcError<RpcQueryError>>(),
+        Some(JsonRpcError::ServerError(JsonRpcServerError::HandlerError(
+            RpcQueryError::UnknownAccessKey { .. }
+        )))
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,201 @@
+use super::*;
+use crate::mock::dummy_final_outcome;
+use near_primitives::hash::CryptoHash;
+use near_primitives::views::{
+    AccessKeyPermissionView, AccessKeyView, FinalExecutionOutcomeView,
+    FinalExecutionOutcomeViewEnum,
+};
+use near_sdk::NearToken;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+const MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+fn root() -> StandardWallet {
+    StandardWallet::new(
+        "root.near".parse().unwrap(),
+        MNEMONIC.parse().unwrap(),
+        "m/44'/397'/0'".parse().unwrap(),
+    )
+    .unwrap()
+}
+
+struct MockSentTx;
+
+impl SentTx for MockSentTx {
+    fn tx_hash(&self) -> CryptoHash {
+        CryptoHash::default()
+    }
+
+    async fn wait_for_executed(&self) -> Result<FinalExecutionOutcomeViewEnum> {
+        unimplemented!()
+    }
+
+    async fn wait_for_success(&self) -> Result<FinalExecutionOutcomeView> {
+        Ok(dummy_final_outcome(vec![]))
+    }
+}
+
+/// `registered` に含まれる公開鍵だけを登録済みとして扱うクライアント
+struct MockClient {
+    registered: HashSet<String>,
+    sent: Mutex<Vec<(AccountId, AccountId, Vec<Action>)>>,
+}
+
+impl MockClient {
+    fn new(registered: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            registered: registered.into_iter().collect(),
+            sent: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl AccessKeyInfo for MockClient {
+    async fn get_access_key_info(&self, signer: &InMemorySigner) -> Result<AccessKeyView> {
+        if self.registered.contains(&signer.public_key().to_string()) {
+            return Ok(AccessKeyView {
+                nonce: 1,
+                permission: AccessKeyPermissionView::FullAccess,
+            });
+        }
+        let err: JsonRpcError<RpcQueryError> = JsonRpcError::ServerError(
+            JsonRpcServerError::HandlerError(RpcQueryError::UnknownAccessKey {
+                public_key: signer.public_key(),
+                block_height: 1,
+                block_hash: CryptoHash::default(),
+            }),
+        );
+        Err(err.into())
+    }
+}
+
+impl SendTx for MockClient {
+    type Output = MockSentTx;
+
+    async fn transfer_native_token(
+        &self,
+        _signer: &InMemorySigner,
+        _receiver: &AccountId,
+        _amount: NearToken,
+    ) -> Result<Self::Output> {
+        unimplemented!()
+    }
+
+    async fn exec_contract<T>(
+        &self,
+        _signer: &InMemorySigner,
+        _receiver: &AccountId,
+        _method_name: &str,
+        _args: T,
+        _deposit: NearToken,
+    ) -> Result<Self::Output>
+    where
+        T: Sized + serde::Serialize,
+    {
+        unimplemented!()
+    }
+
+    async fn send_tx(
+        &self,
+        signer: &InMemorySigner,
+        receiver: &AccountId,
+        actions: Vec<Action>,
+    ) -> Result<Self::Output> {
+        self.sent
+            .lock()
+            .unwrap()
+            .push((signer.account_id.clone(), receiver.clone(), actions));
+        Ok(MockSentTx)
+    }
+}
+
+#[test]
+fn test_derive_distinct_keys_on_same_account() {
+    let root = root();
+    let pool = AccessKeyPool::derive(&root, 3).unwrap();
+
+    let pubkeys: HashSet<_> = pool.keys().iter().map(|k| k.pub_base58()).collect();
+    assert_eq!(pubkeys.len(), 3);
+    assert!(!pubkeys.contains(&root.pub_base58()));
+    assert!(
+        pool.keys()
+            .iter()
+            .all(|k| k.account_id() == root.account_id())
+    );
+    assert!(pool.keys().iter().all(|k| k.is_function_call_key()));
+    assert!(!root.is_function_call_key());
+
+    // 同じ root からは同じ鍵が派生する
+    let again = AccessKeyPool::derive(&root, 3).unwrap();
+    assert_eq!(pool.keys()[1].pub_base58(), again.keys()[1].pub_base58());
+}
+
+#[test]
+fn test_acquire_round_robin() {
+    let root = root();
+    let empty = AccessKeyPool::empty(&root);
+    assert!(empty.acquire().is_none());
+
+    let pool = AccessKeyPool::derive(&root, 2).unwrap();
+    let order: Vec<_> = (0..4)
+        .map(|_| pool.acquire().unwrap().pub_base58())
+        .collect();
+    assert_eq!(order[0], order[2]);
+    assert_eq!(order[1], order[3]);
+    assert_ne!(order[0], order[1]);
+}
+
+#[tokio::test]
+async fn test_provision_adds_only_missing_keys_in_one_tx() {
+    let root = root();
+    let pool = AccessKeyPool::derive(&root, 3).unwrap();
+    let client = MockClient::new([pool.keys()[0].signer().public_key().to_string()]);
+
+    let added = pool.provision(&client).await.unwrap();
+    assert_eq!(added, 2);
+
+    let sent = client.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    let (signer, receiver, actions) = &sent[0];
+    assert_eq!(signer, root.account_id());
+    assert_eq!(receiver, root.account_id());
+    let added_keys: Vec<_> = actions
+        .iter()
+        .map(|action| match action {
+            Action::AddKey(add) => {
+                match &add.access_key.permission {
+                    AccessKeyPermission::FunctionCall(permission) => {
+                        assert_eq!(permission.receiver_id, CONTRACT_ADDRESS.to_string());
+                        assert_eq!(permission.method_names, vec!["swap".to_string()]);
+                    }
+                    other => panic!("unexpected permission {other:?}"),
+                }
+                add.public_key.clone()
+            }
+            other => panic!("unexpected action {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        added_keys,
+        vec![
+            pool.keys()[1].signer().public_key(),
+            pool.keys()[2].signer().public_key(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_provision_is_noop_when_registered() {
+    let root = root();
+    let pool = AccessKeyPool::derive(&root, 2).unwrap();
+    let client = MockClient::new(
+        pool.keys()
+            .iter()
+            .map(|k| k.signer().public_key().to_string()),
+    );
+
+    assert_eq!(pool.provision(&client).await.unwrap(), 0);
+    assert!(client.sent.lock().unwrap().is_empty());
+}
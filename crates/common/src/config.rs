@@ -3,8 +3,9 @@ pub mod store;
 mod typed;
 
 pub use typed::{
-    ConfigAccess, ConfigResolver, ConfigValueType, KEY_DEFINITIONS, KeyDefinition, MockConfig,
-    REF_STORAGE_MAX_TOP_UP_ABSOLUTE_CEILING, ResolvedKeyInfo, resolve_all_without_db, typed,
+    ConfigAccess, ConfigOverlay, ConfigResolver, ConfigValueType, KEY_DEFINITIONS, KeyDefinition,
+    MockConfig, REF_STORAGE_MAX_TOP_UP_ABSOLUTE_CEILING, ResolvedKeyInfo, resolve_all_without_db,
+    typed,
 };
 
 #[cfg(test)]
@@ -46,6 +46,15 @@ pub struct StartupConfig {
     /// ID tokens. Empty when authentication is not configured; in that
     /// case authenticated endpoints reject every request.
     pub google_client_id: String,
+    /// Port of the Prometheus `/metrics` endpoint. `0` disables it.
+    pub metrics_port: u16,
+    /// OTLP (gRPC) collector endpoint for trace export, e.g.
+    /// `http://otel-collector:4317`. Empty disables tracing export.
+    pub otel_exporter_otlp_endpoint: String,
+    /// `service.name` resource attribute attached to exported spans.
+    pub otel_service_name: String,
+    /// Fraction of root traces to sample, in `[0.0, 1.0]`.
+    pub otel_traces_sample_ratio: f64,
 }
 
 impl fmt::Debug for StartupConfig {
@@ -72,6 +81,13 @@ impl fmt::Debug for StartupConfig {
             .field("root_hdpath", &self.root_hdpath)
             .field("instance_id", &self.instance_id)
             .field("google_client_id", &presence(&self.google_client_id))
+            .field("metrics_port", &self.metrics_port)
+            .field(
+                "otel_exporter_otlp_endpoint",
+                &self.otel_exporter_otlp_endpoint,
+            )
+            .field("otel_service_name", &self.otel_service_name)
+            .field("otel_traces_sample_ratio", &self.otel_traces_sample_ratio)
             .finish()
     }
 }
@@ -91,6 +107,12 @@ impl StartupConfig {
             root_hdpath: env_string("ROOT_HDPATH").unwrap_or_else(|| "m/44'/397'/0'".to_string()),
             instance_id: env_string("INSTANCE_ID").unwrap_or_else(|| "*".to_string()),
             google_client_id: env_string("GOOGLE_CLIENT_ID").unwrap_or_default(),
+            metrics_port: env_parse("METRICS_PORT").unwrap_or(9464),
+            otel_exporter_otlp_endpoint: env_string("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .unwrap_or_default(),
+            otel_service_name: env_string("OTEL_SERVICE_NAME")
+                .unwrap_or_else(|| "zaciraci".to_string()),
+            otel_traces_sample_ratio: env_parse("OTEL_TRACES_SAMPLER_ARG").unwrap_or(1.0),
         }
     }
 }
@@ -140,6 +162,10 @@ mod tests {
         assert_eq!(config.rpc_failure_reset_seconds, 300);
         assert_eq!(config.root_hdpath, "m/44'/397'/0'");
         assert_eq!(config.instance_id, "*");
+        assert_eq!(config.metrics_port, 9464);
+        assert!(config.otel_exporter_otlp_endpoint.is_empty());
+        assert_eq!(config.otel_service_name, "zaciraci");
+        assert_eq!(config.otel_traces_sample_ratio, 1.0);
     }
 
     #[test]
@@ -163,6 +189,10 @@ mod tests {
             root_hdpath: "m/44'/397'/0'".to_string(),
             instance_id: "primary".to_string(),
             google_client_id: "123-abc.apps.googleusercontent.com".to_string(),
+            metrics_port: 9464,
+            otel_exporter_otlp_endpoint: "http://collector:4317".to_string(),
+            otel_service_name: "zaciraci".to_string(),
+            otel_traces_sample_ratio: 1.0,
         };
         let formatted = format!("{config:?}");
         assert!(!formatted.contains("supersecret"));
@@ -188,6 +218,10 @@ mod tests {
             root_hdpath: String::new(),
             instance_id: "*".to_string(),
             google_client_id: String::new(),
+            metrics_port: 0,
+            otel_exporter_otlp_endpoint: String::new(),
+            otel_service_name: "zaciraci".to_string(),
+            otel_traces_sample_ratio: 1.0,
         };
         let formatted = format!("{config:?}");
         assert!(formatted.contains("[unset]"));
@@ -61,6 +61,8 @@ pub(crate) trait ConfigResolve: Sized {
     const VALUE_TYPE: ConfigValueType;
     fn resolve(key: &str, default: Self::Default) -> Self;
     fn resolve_without_db(key: &str, default: Self::Default) -> Self;
+    /// Parse a `ConfigOverlay` value (`None` if it does not parse as this type).
+    fn parse_override(raw: &str) -> Option<Self>;
     fn display_string(value: Self) -> std::string::String;
 }
 
@@ -79,6 +81,9 @@ impl ConfigResolve for bool {
             .and_then(|v| v.to_lowercase().parse::<bool>().ok())
             .unwrap_or(default)
     }
+    fn parse_override(raw: &str) -> Option<Self> {
+        raw.to_lowercase().parse::<bool>().ok()
+    }
     fn display_string(value: Self) -> std::string::String {
         value.to_string()
     }
@@ -93,6 +98,9 @@ impl ConfigResolve for String {
     fn resolve_without_db(key: &str, default: &'static str) -> Self {
         crate::config::store::get_excluding_db(key).unwrap_or_else(|_| default.to_string())
     }
+    fn parse_override(raw: &str) -> Option<Self> {
+        Some(raw.to_string())
+    }
     fn display_string(value: Self) -> std::string::String {
         value
     }
@@ -115,6 +123,9 @@ macro_rules! impl_config_resolve_numeric {
                     .and_then(|v| v.parse::<$ty>().ok())
                     .unwrap_or(default)
             }
+            fn parse_override(raw: &str) -> Option<Self> {
+                raw.parse::<$ty>().ok()
+            }
             fn display_string(value: Self) -> std::string::String {
                 value.to_string()
             }
@@ -145,6 +156,9 @@ impl ConfigResolve for Duration {
             .and_then(|v| humantime::parse_duration(&v).ok())
             .unwrap_or(default)
     }
+    fn parse_override(raw: &str) -> Option<Self> {
+        humantime::parse_duration(raw).ok()
+    }
     fn display_string(value: Self) -> std::string::String {
         humantime::format_duration(value).to_string()
     }
@@ -161,6 +175,9 @@ impl ConfigResolve for anyhow::Result<String> {
         crate::config::store::get_excluding_db(key)
             .map_err(|_| anyhow::anyhow!("required config key not found: {}", key))
     }
+    fn parse_override(raw: &str) -> Option<Self> {
+        Some(Ok(raw.to_string()))
+    }
     fn display_string(value: Self) -> std::string::String {
         value.unwrap_or_else(|_| "(未設定)".to_string())
     }
@@ -218,12 +235,46 @@ impl MockStore for anyhow::Result<String> {
     }
 }
 
+// ── ConfigOverlay: per-run overrides ──
+
+/// `ConfigAccess` that layers per-key overrides over `ConfigResolver`
+/// without writing to the global `CONFIG_STORE`.
+///
+/// Values use the same string form as `store::set`. Keys without an override,
+/// or whose override does not parse as the key's type, fall through to
+/// `ConfigResolver`. Lets several configurations run side by side in one
+/// process (e.g. concurrent simulation runs).
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverlay {
+    overrides: std::collections::HashMap<std::string::String, std::string::String>,
+}
+
+impl ConfigOverlay {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, key: &str, value: impl ToString) {
+        self.overrides.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn with(mut self, key: &str, value: impl ToString) -> Self {
+        self.set(key, value);
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.overrides.get(key).map(std::string::String::as_str)
+    }
+}
+
 // ── Main macro ──
 
 /// Declarative macro that generates:
 /// - `ConfigAccess` trait with typed accessor methods
 /// - `ConfigResolver` struct that resolves values via `config::get()` priority chain
 /// - `MockConfig` struct for test isolation (wraps real resolver, overrides per-field)
+/// - `ConfigOverlay` struct for per-run overrides by key, without touching the global store
 /// - `KEY_DEFINITIONS` const with static metadata for all config keys
 /// - `resolve_all_without_db()` function for runtime key resolution excluding DB
 macro_rules! define_typed_config {
@@ -286,6 +337,17 @@ macro_rules! define_typed_config {
             )*
         }
 
+        impl ConfigAccess for ConfigOverlay {
+            $(
+                fn $method(&self) -> $ty {
+                    self.overrides
+                        .get($key)
+                        .and_then(|raw| <$ty as ConfigResolve>::parse_override(raw))
+                        .unwrap_or_else(|| ConfigResolver.$method())
+                }
+            )*
+        }
+
         pub const KEY_DEFINITIONS: &[KeyDefinition] = &[
             $(
                 KeyDefinition {
@@ -342,6 +404,12 @@ define_typed_config! {
         default: 10
     }
 
+    /// Prefix of evaluation period ids; the latest period is looked up among ids with this prefix
+    fn trade_evaluation_period_prefix() -> String {
+        key: "TRADE_EVALUATION_PERIOD_PREFIX",
+        default: "eval_"
+    }
+
     /// Account reserve in NEAR
     fn trade_account_reserve() -> u32 {
         key: "TRADE_ACCOUNT_RESERVE",
@@ -438,6 +506,12 @@ define_typed_config! {
         default: 0.3
     }
 
+    /// Maximum number of pool-disjoint routes a single swap is split across (1 disables splitting)
+    fn trade_swap_max_split_legs() -> u32 {
+        key: "TRADE_SWAP_MAX_SPLIT_LEGS",
+        default: 3
+    }
+
     // ── arbitrage ──
 
     /// Whether arbitrage engine is enabled
@@ -496,6 +570,14 @@ define_typed_config! {
         default: 128
     }
 
+    // ── wallet ──
+
+    /// Number of HD-derived function-call access keys used to sign swaps in parallel (0 disables)
+    fn access_key_pool_size() -> u32 {
+        key: "ACCESS_KEY_POOL_SIZE",
+        default: 0
+    }
+
     // ── rpc ──
 
     /// Max RPC retry attempts
@@ -549,6 +631,37 @@ define_typed_config! {
         default: "0 0 4 * * 7"
     }
 
+    /// Retention period for job run history records in days
+    fn job_runs_retention_days() -> u32 {
+        key: "JOB_RUNS_RETENTION_DAYS",
+        default: 90
+    }
+
+    /// How far back missed scheduled runs are caught up after downtime.
+    /// Scheduled times older than this window are dropped instead of replayed.
+    fn scheduler_catch_up_window() -> Duration {
+        key: "SCHEDULER_CATCH_UP_WINDOW",
+        default: Duration::from_secs(86400)
+    }
+
+    /// Cron schedule for reconciling on-chain holdings against the DB
+    fn holdings_reconcile_cron_schedule() -> String {
+        key: "HOLDINGS_RECONCILE_CRON_SCHEDULE",
+        default: "0 30 * * * *"
+    }
+
+    /// Allowed difference between on-chain and DB-implied holdings in basis points
+    fn holdings_reconcile_tolerance_bps() -> u32 {
+        key: "HOLDINGS_RECONCILE_TOLERANCE_BPS",
+        default: 10
+    }
+
+    /// Whether holdings reconciliation writes corrective adjustment records
+    fn holdings_reconcile_write_adjustments() -> bool {
+        key: "HOLDINGS_RECONCILE_WRITE_ADJUSTMENTS",
+        default: false
+    }
+
     // ── wallet / logging: moved to StartupConfig ──
 
     // ── portfolio/liquidity ──
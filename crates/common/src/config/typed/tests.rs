@@ -160,6 +160,14 @@ fn test_token_rates_retention_days_default() {
     assert_eq!(typed().token_rates_retention_days(), 90);
 }
 
+#[test]
+#[serial]
+fn test_access_key_pool_size_default() {
+    let _env = EnvGuard::remove("ACCESS_KEY_POOL_SIZE");
+    crate::config::store::remove("ACCESS_KEY_POOL_SIZE");
+    assert_eq!(typed().access_key_pool_size(), 0);
+}
+
 #[test]
 #[serial]
 fn test_rpc_max_attempts_default() {
@@ -411,6 +419,36 @@ fn test_mock_config_delegates_to_real() {
     let _ = mock.trade_top_tokens();
 }
 
+// ── ConfigOverlay tests ──
+
+#[test]
+#[serial]
+fn test_config_overlay_overrides_without_global_store() {
+    let _env = EnvGuard::remove("TRADE_TOP_TOKENS");
+    crate::config::store::remove("TRADE_TOP_TOKENS");
+    let overlay = ConfigOverlay::new()
+        .with("TRADE_TOP_TOKENS", 25)
+        .with("TRADE_ENABLED", "TRUE")
+        .with("PORTFOLIO_REBALANCE_THRESHOLD", 0.25);
+
+    assert_eq!(overlay.trade_top_tokens(), 25);
+    assert!(overlay.trade_enabled());
+    assert_eq!(overlay.portfolio_rebalance_threshold(), 0.25);
+    assert_eq!(overlay.get("TRADE_TOP_TOKENS"), Some("25"));
+    assert!(crate::config::store::get("TRADE_TOP_TOKENS").is_err());
+}
+
+#[test]
+#[serial]
+fn test_config_overlay_falls_back_on_missing_or_invalid() {
+    let _env = EnvGuard::remove("TRADE_EVALUATION_DAYS");
+    crate::config::store::remove("TRADE_EVALUATION_DAYS");
+    let overlay = ConfigOverlay::new().with("TRADE_EVALUATION_DAYS", "ten");
+
+    assert_eq!(overlay.trade_evaluation_days(), 10);
+    assert_eq!(overlay.trade_evaluation_period_prefix(), "eval_");
+}
+
 // ── trade_price_history_days ──
 
 #[test]
@@ -560,7 +598,7 @@ fn test_value_type_result_string() {
 #[test]
 fn test_key_definitions_count() {
     // define_typed_config! に定義されたキーの数と一致すること
-    assert_eq!(KEY_DEFINITIONS.len(), 48);
+    assert_eq!(KEY_DEFINITIONS.len(), 58);
 }
 
 #[test]
@@ -2,6 +2,8 @@
 
 pub mod errors;
 pub mod pool_info;
+#[cfg(any(test, feature = "test-helpers"))]
+pub mod test_helpers;
 pub mod token_index;
 pub mod token_pair;
 
@@ -156,6 +156,15 @@ impl PoolInfoList {
             .cloned()
             .ok_or_else(|| Error::OutOfIndexOfPools(index).into())
     }
+
+    /// 同じ id のプールを `pools` で置き換えた新しいリストを返す（無い id は追加される）
+    pub fn with_replaced(&self, pools: Vec<Arc<PoolInfo>>) -> Self {
+        let mut by_id = self.by_id.clone();
+        for pool in pools {
+            by_id.insert(pool.id, pool);
+        }
+        Self::new(by_id.into_values().collect())
+    }
 }
 
 #[cfg(test)]
@@ -252,6 +261,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_pool_info_list_with_replaced() {
+        let pool = |id: u32, amount: u128| {
+            Arc::new(PoolInfo::new(
+                id,
+                PoolInfoBared {
+                    pool_kind: "SIMPLE_POOL".to_string(),
+                    token_account_ids: vec!["a.near".parse().unwrap(), "b.near".parse().unwrap()],
+                    amounts: vec![amount.into(), amount.into()],
+                    total_fee: 30,
+                    shares_total_supply: 0_u128.into(),
+                    amp: 0,
+                },
+                chrono::Utc::now().naive_utc(),
+            ))
+        };
+        let list = PoolInfoList::new(vec![pool(0, 100), pool(1, 100)]);
+
+        let replaced = list.with_replaced(vec![pool(1, 200), pool(2, 300)]);
+        assert_eq!(replaced.list().len(), 3);
+        assert_eq!(replaced.get(0).unwrap().amount(0.into()).unwrap(), 100);
+        assert_eq!(replaced.get(1).unwrap().amount(0.into()).unwrap(), 200);
+        assert_eq!(replaced.get(2).unwrap().amount(0.into()).unwrap(), 300);
+        // 元のリストは変わらない
+        assert_eq!(list.get(1).unwrap().amount(0.into()).unwrap(), 100);
+        assert!(replaced.list().windows(2).all(|w| w[0].id < w[1].id));
+    }
+
     #[test]
     fn test_pool_info_estimate_return() {
         let sample = PoolInfo::new(
@@ -0,0 +1,35 @@
+//! テスト用のプール生成ヘルパー
+
+use crate::pool_info::{PoolInfo, PoolInfoBared};
+use chrono::{NaiveDate, NaiveDateTime};
+use near_sdk::json_types::U128;
+use std::sync::Arc;
+
+/// [`simple_pool`] のスナップショット時刻（2025-01-01 00:00:00）
+pub fn pool_timestamp() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2025, 1, 1)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+/// `tokens` を `amounts` ずつ持つ SIMPLE_POOL（手数料 0.3%、LP シェア 0）
+pub fn simple_pool_bare(tokens: [&str; 2], amounts: [u128; 2]) -> PoolInfoBared {
+    PoolInfoBared {
+        pool_kind: "SIMPLE_POOL".to_string(),
+        token_account_ids: tokens.iter().map(|t| t.parse().unwrap()).collect(),
+        amounts: amounts.into_iter().map(U128).collect(),
+        total_fee: 30,
+        shares_total_supply: U128(0),
+        amp: 0,
+    }
+}
+
+/// [`simple_pool_bare`] をプール `id` として [`pool_timestamp`] に記録したもの
+pub fn simple_pool(id: u32, tokens: [&str; 2], amounts: [u128; 2]) -> Arc<PoolInfo> {
+    Arc::new(PoolInfo::new(
+        id,
+        simple_pool_bare(tokens, amounts),
+        pool_timestamp(),
+    ))
+}
@@ -1,5 +1,5 @@
 use crate::errors::Error;
-use crate::pool_info::PoolInfo;
+use crate::pool_info::{PoolInfo, PoolInfoList};
 use crate::token_index::{TokenIn, TokenOut};
 use anyhow::Result;
 use common::types::{TokenAccount, TokenInAccount, TokenOutAccount};
@@ -69,6 +69,11 @@ impl TokenPair {
         }
     }
 
+    /// プールの手数料（[`FEE_DIVISOR`] 分率）
+    pub fn fee(&self) -> u32 {
+        self.pool.bare.total_fee
+    }
+
     /// 入力側のプールサイズを取得
     pub fn amount_in(&self) -> Result<u128> {
         self.pool.amount(self.token_in.as_index())
@@ -79,6 +84,17 @@ impl TokenPair {
         self.pool.amount(self.token_out.as_index())
     }
 
+    /// 取引量を 0 に近づけたときの交換レート（手数料込み、価格インパクトなし）
+    pub fn spot_rate(&self) -> Result<f64> {
+        let balance_in = self.amount_in()?;
+        let balance_out = self.amount_out()?;
+        if balance_in == 0 || balance_out == 0 {
+            return Err(Error::ZeroAmount.into());
+        }
+        let fee_ratio = (FEE_DIVISOR - self.pool.bare.total_fee) as f64 / FEE_DIVISOR as f64;
+        Ok(balance_out as f64 / balance_in as f64 * fee_ratio)
+    }
+
     pub fn estimate_normal_return(&self) -> Result<(u128, u128)> {
         let balance_in = self.pool.amount(self.token_in.as_index())?;
         if balance_in == 0 {
@@ -141,6 +157,26 @@ impl TokenPath {
         seen
     }
 
+    /// パスが通るプールの id（重複除去済み）
+    pub fn pool_ids(&self) -> Vec<u32> {
+        let mut ids: Vec<u32> = self.0.iter().map(|pair| pair.pool_id()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// 同じペアを `pools` のプール状態で組み直したパスを返す
+    ///
+    /// パスは作成時点のプール残高を保持しているため、残高を更新した後に見積もり直す際に使う。
+    pub fn rebind(&self, pools: &PoolInfoList) -> Result<TokenPath> {
+        let pairs = self
+            .0
+            .iter()
+            .map(|pair| pools.get_pair(pair.pair_id()))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TokenPath(pairs))
+    }
+
     pub fn calc_value(&self, initial: u128) -> Result<u128> {
         if initial == 0 {
             return Ok(0);
@@ -133,6 +133,26 @@ fn test_token_pair_estimate_normal_return() {
     assert!(out_value > 0);
 }
 
+#[test]
+fn test_token_pair_spot_rate() {
+    let pool = make_test_pool(1, 30, vec![1_000_000, 2_000_000]);
+    let pair = pool.get_pair(TokenIn::from(0), TokenOut::from(1)).unwrap();
+    let rate = pair.spot_rate().unwrap();
+    assert!((rate - 2.0 * 0.997).abs() < 1e-12);
+
+    // 少量のスワップのレートはスポットレートに近く、それを超えない
+    let out = pair.estimate_return(1_000).unwrap();
+    assert!(out as f64 <= 1_000.0 * rate);
+    assert!(out as f64 > 1_000.0 * rate * 0.99);
+}
+
+#[test]
+fn test_token_pair_spot_rate_zero_balance() {
+    let pool = make_test_pool(1, 30, vec![0, 2_000_000]);
+    let pair = pool.get_pair(TokenIn::from(0), TokenOut::from(1)).unwrap();
+    assert!(pair.spot_rate().is_err());
+}
+
 #[test]
 fn test_token_pair_estimate_normal_return_zero_balance() {
     let pool = make_test_pool(1, 30, vec![0, 2000]);
@@ -286,6 +306,52 @@ fn test_all_tokens_3hop_4tokens() {
     assert_eq!(tokens[3].as_str(), "d.near");
 }
 
+// --- TokenPath::rebind ---
+
+#[test]
+fn test_pool_ids_dedup() {
+    let pool_ab = make_named_pool(1, &["a.near", "b.near"], vec![1_000_000, 1_000_000]);
+    let pool_bc = make_named_pool(2, &["b.near", "c.near"], vec![1_000_000, 1_000_000]);
+    let path = TokenPath(vec![
+        pool_bc
+            .get_pair(TokenIn::from(0), TokenOut::from(1))
+            .unwrap(),
+        pool_ab
+            .get_pair(TokenIn::from(1), TokenOut::from(0))
+            .unwrap(),
+        pool_ab
+            .get_pair(TokenIn::from(0), TokenOut::from(1))
+            .unwrap(),
+    ]);
+    assert_eq!(path.pool_ids(), vec![1, 2]);
+}
+
+#[test]
+fn test_rebind_uses_new_reserves() {
+    let stale = make_named_pool(1, &["a.near", "b.near"], vec![1_000_000, 1_000_000]);
+    let path = TokenPath(vec![
+        stale.get_pair(TokenIn::from(0), TokenOut::from(1)).unwrap(),
+    ]);
+
+    let fresh = make_named_pool(1, &["a.near", "b.near"], vec![1_000_000, 500_000]);
+    let pools = PoolInfoList::new(vec![fresh]);
+    let rebound = path.rebind(&pools).unwrap();
+
+    assert_eq!(rebound.len(), 1);
+    assert_eq!(rebound.0[0].pair_id(), path.0[0].pair_id());
+    assert!(rebound.calc_value(1_000).unwrap() < path.calc_value(1_000).unwrap());
+}
+
+#[test]
+fn test_rebind_missing_pool() {
+    let pool = make_named_pool(1, &["a.near", "b.near"], vec![1_000_000, 1_000_000]);
+    let path = TokenPath(vec![
+        pool.get_pair(TokenIn::from(0), TokenOut::from(1)).unwrap(),
+    ]);
+    let pools = PoolInfoList::new(vec![]);
+    assert!(path.rebind(&pools).is_err());
+}
+
 // --- TokenPath::validate_length ---
 
 #[test]
@@ -1,5 +1,7 @@
 #![deny(warnings)]
 
+pub mod otel;
+
 pub use slog::*;
 use std::sync::LazyLock;
 
@@ -30,7 +32,7 @@ pub static DEFAULT: LazyLock<Logger> = LazyLock::new(|| {
     };
 
     Logger::root(
-        drain,
+        otel::TraceContext::new(drain),
         o!(
             "version" => env!("CARGO_PKG_VERSION"),
             "commit" => option_env!("GIT_COMMIT_HASH").unwrap_or("unknown"),
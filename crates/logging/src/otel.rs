@@ -0,0 +1,119 @@
+//! OpenTelemetry によるトレース出力と slog への橋渡し
+//!
+//! 処理の区切りは `tracing` の span で表し、`OTEL_EXPORTER_OTLP_ENDPOINT` が設定されていれば
+//! OTLP (gRPC) で collector に送る。slog の各レコードには [`TraceContext`] drain が
+//! 実行中 span の `trace_id` / `span_id` を付与するため、ログ行からトレースを引ける。
+//! warn 以上のレコードは span の event としてもトレースに残る。
+
+use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use slog::{BorrowedKV, Drain, Level, OwnedKVList, Record, RecordStatic, SingleKV};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// トレース出力の終了処理を行うガード
+///
+/// drop 時に未送信の span を flush する。`main` の終わりまで保持すること。
+pub struct TracingGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("failed to shut down tracer provider: {e}");
+        }
+    }
+}
+
+/// `StartupConfig` に従ってトレース出力を初期化する
+///
+/// endpoint が未設定の場合は何もせず `Ok(None)` を返す（span は記録されない）。
+/// tokio runtime 上で呼ぶこと。
+pub fn init() -> Result<Option<TracingGuard>, Box<dyn std::error::Error + Send + Sync>> {
+    let startup = common::config::startup::get();
+    if startup.otel_exporter_otlp_endpoint.is_empty() {
+        return Ok(None);
+    }
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(startup.otel_exporter_otlp_endpoint.clone())
+        .build()?;
+
+    let ratio = startup.otel_traces_sample_ratio.clamp(0.0, 1.0);
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(startup.otel_service_name.clone())
+                .build(),
+        )
+        .build();
+
+    let tracer = provider.tracer("zaciraci");
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()?;
+
+    Ok(Some(TracingGuard { provider }))
+}
+
+/// 実行中 span の (trace_id, span_id)。トレースが無効、または span 外なら `None`。
+pub fn current_ids() -> Option<(String, String)> {
+    let cx = tracing::Span::current().context();
+    let span = cx.span();
+    let sc = span.span_context();
+    sc.is_valid()
+        .then(|| (sc.trace_id().to_string(), sc.span_id().to_string()))
+}
+
+/// slog レコードに実行中 span の `trace_id` / `span_id` を付与する drain
+///
+/// span の情報は thread/task local なので、`slog_async` の手前（ログを出した
+/// スレッド上）に置く必要がある。
+pub struct TraceContext<D> {
+    inner: D,
+}
+
+impl<D> TraceContext<D> {
+    pub fn new(inner: D) -> Self {
+        Self { inner }
+    }
+}
+
+impl<D: Drain> Drain for TraceContext<D> {
+    type Ok = D::Ok;
+    type Err = D::Err;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        let Some((trace_id, span_id)) = current_ids() else {
+            return self.inner.log(record, values);
+        };
+
+        if record.level().is_at_least(Level::Warning) {
+            tracing::warn!(target: "slog", level = record.level().as_str(), "{}", record.msg());
+        }
+
+        let kv = (
+            SingleKV("trace_id", trace_id),
+            (SingleKV("span_id", span_id), record.kv()),
+        );
+        let rs = RecordStatic {
+            location: record.location(),
+            tag: record.tag(),
+            level: record.level(),
+        };
+        self.inner
+            .log(&Record::new(&rs, record.msg(), BorrowedKV(&kv)), values)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,76 @@
+use super::*;
+use slog::{KV, Logger, Serializer, o};
+use std::sync::{Arc, Mutex};
+
+/// 受け取ったレコードのキー一覧を保存する drain
+#[derive(Clone, Default)]
+struct KeyCapture {
+    keys: Arc<Mutex<Vec<String>>>,
+}
+
+struct KeySerializer<'a>(&'a mut Vec<String>);
+
+impl Serializer for KeySerializer<'_> {
+    fn emit_arguments(&mut self, key: slog::Key, _val: &std::fmt::Arguments) -> slog::Result {
+        self.0.push(key.to_string());
+        Ok(())
+    }
+}
+
+impl Drain for KeyCapture {
+    type Ok = ();
+    type Err = slog::Never;
+
+    fn log(&self, record: &Record, _values: &OwnedKVList) -> Result<(), slog::Never> {
+        let mut keys = self.keys.lock().unwrap();
+        record
+            .kv()
+            .serialize(record, &mut KeySerializer(&mut keys))
+            .unwrap();
+        Ok(())
+    }
+}
+
+fn with_otel_subscriber<R>(f: impl FnOnce() -> R) -> R {
+    let provider = SdkTracerProvider::builder().build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    tracing::subscriber::with_default(subscriber, f)
+}
+
+#[test]
+fn test_current_ids_none_outside_span() {
+    assert!(current_ids().is_none());
+    with_otel_subscriber(|| assert!(current_ids().is_none()));
+}
+
+#[test]
+fn test_current_ids_inside_span() {
+    with_otel_subscriber(|| {
+        let span = tracing::info_span!("test_span");
+        let _entered = span.enter();
+        let (trace_id, span_id) = current_ids().expect("span is active");
+        assert_eq!(trace_id.len(), 32);
+        assert_eq!(span_id.len(), 16);
+    });
+}
+
+#[test]
+fn test_trace_context_adds_ids_only_inside_span() {
+    let capture = KeyCapture::default();
+    let log = Logger::root(TraceContext::new(capture.clone()), o!());
+
+    slog::info!(log, "outside"; "k" => 1);
+    assert_eq!(*capture.keys.lock().unwrap(), vec!["k"]);
+    capture.keys.lock().unwrap().clear();
+
+    with_otel_subscriber(|| {
+        let span = tracing::info_span!("test_span");
+        let _entered = span.enter();
+        slog::info!(log, "inside"; "k" => 1);
+    });
+    let keys = capture.keys.lock().unwrap().clone();
+    assert!(keys.contains(&"trace_id".to_string()));
+    assert!(keys.contains(&"span_id".to_string()));
+    assert!(keys.contains(&"k".to_string()));
+}
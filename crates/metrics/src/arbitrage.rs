@@ -0,0 +1,45 @@
+//! 裁定取引のメトリクス（`arbitrage`）
+
+use crate::{register, yocto_to_near_f64};
+use common::types::YoctoAmount;
+use prometheus::{Counter, IntCounterVec, Opts};
+use std::sync::LazyLock;
+
+static SWAPS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "arbitrage_swaps_total",
+                "Arbitrage swap attempts by result (success/failure/stale)",
+            ),
+            &["result"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static GAIN: LazyLock<Counter> = LazyLock::new(|| {
+    register(
+        Counter::with_opts(Opts::new(
+            "arbitrage_gain_near_total",
+            "Estimated gain of successful arbitrage swaps in NEAR",
+        ))
+        .expect("valid metric"),
+    )
+});
+
+/// 成功した裁定取引と見込み利益を記録する
+pub fn record_success(gain: &YoctoAmount) {
+    SWAPS.with_label_values(&["success"]).inc();
+    GAIN.inc_by(yocto_to_near_f64(gain).max(0.0));
+}
+
+/// 失敗した裁定取引を記録する
+pub fn record_failure() {
+    SWAPS.with_label_values(&["failure"]).inc();
+}
+
+/// 実行直前の再見積もりで利益が消えて見送った裁定取引を記録する
+pub fn record_stale() {
+    SWAPS.with_label_values(&["stale"]).inc();
+}
@@ -0,0 +1,57 @@
+//! DB コネクションプールのメトリクス（`persistence::connection_pool`）
+
+use crate::register;
+use prometheus::{Histogram, HistogramOpts, IntGauge, IntGaugeVec, Opts};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+static POOL: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "DB pool connections by state (max/size/available)",
+            ),
+            &["state"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static WAITING: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(
+        IntGauge::with_opts(Opts::new(
+            "db_pool_waiting",
+            "Tasks waiting for a DB connection",
+        ))
+        .expect("valid metric"),
+    )
+});
+
+static WAIT: LazyLock<Histogram> = LazyLock::new(|| {
+    register(
+        Histogram::with_opts(
+            HistogramOpts::new(
+                "db_pool_wait_seconds",
+                "Time spent waiting to check out a DB connection",
+            )
+            .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 30.0]),
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// プールの現在の状態を記録する
+pub fn set_pool_status(max_size: usize, size: usize, available: usize, waiting: usize) {
+    let to_i64 = |v: usize| i64::try_from(v).unwrap_or(i64::MAX);
+    POOL.with_label_values(&["max"]).set(to_i64(max_size));
+    POOL.with_label_values(&["size"]).set(to_i64(size));
+    POOL.with_label_values(&["available"])
+        .set(to_i64(available));
+    WAITING.set(to_i64(waiting));
+}
+
+/// コネクション取得の待ち時間を記録する
+pub fn observe_checkout(elapsed: Duration) {
+    WAIT.observe(elapsed.as_secs_f64());
+}
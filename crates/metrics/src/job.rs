@@ -0,0 +1,41 @@
+//! cron ジョブのメトリクス（`scheduler`）
+
+use crate::register;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+static RUNS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("job_runs_total", "Job runs by job name and final status"),
+            &["job", "status"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new("job_duration_seconds", "Job run duration").buckets(vec![
+                1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0,
+            ]),
+            &["job"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// 実行したジョブの所要時間と結果を記録する
+pub fn record_run(job: &str, status: &str, elapsed: Duration) {
+    RUNS.with_label_values(&[job, status]).inc();
+    DURATION
+        .with_label_values(&[job])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// 実行せずに skip したジョブを記録する
+pub fn record_skipped(job: &str) {
+    RUNS.with_label_values(&[job, "skipped"]).inc();
+}
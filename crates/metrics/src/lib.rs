@@ -0,0 +1,63 @@
+#![deny(warnings)]
+
+//! Prometheus メトリクス
+//!
+//! プロセス共通の [`Registry`] に各レイヤーのメトリクスを登録し、[`encode`] で
+//! text exposition format に変換する。HTTP への公開は `web::serve_metrics` が行う。
+//!
+//! メトリクス名には `zaciraci_` prefix が付く。各メトリクスは初回記録時に登録されるため、
+//! 一度も記録されていないメトリクスは出力に現れない。
+
+pub mod arbitrage;
+pub mod db;
+pub mod job;
+pub mod prediction;
+pub mod rpc;
+pub mod storage;
+pub mod trade;
+
+use bigdecimal::ToPrimitive;
+use common::types::YoctoAmount;
+use prometheus::core::Collector;
+use prometheus::{Encoder, Registry, TextEncoder};
+use std::sync::LazyLock;
+
+const NAMESPACE: &str = "zaciraci";
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(|| {
+    Registry::new_custom(Some(NAMESPACE.to_string()), None)
+        .expect("metrics namespace must be valid")
+});
+
+/// メトリクスを registry に登録して返す
+///
+/// メトリクスは `LazyLock` の初期化で一度だけ登録されるため、名前の重複はコード上の誤り。
+fn register<T>(metric: T) -> T
+where
+    T: Collector + Clone + 'static,
+{
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("metric must be registered only once");
+    metric
+}
+
+/// 登録済みの全メトリクスを text exposition format で返す
+pub fn encode() -> prometheus::Result<String> {
+    let mut buf = Vec::new();
+    TextEncoder::new().encode(&REGISTRY.gather(), &mut buf)?;
+    String::from_utf8(buf).map_err(|e| prometheus::Error::Msg(e.to_string()))
+}
+
+/// text exposition format の Content-Type
+pub fn content_type() -> String {
+    TextEncoder::new().format_type().to_string()
+}
+
+/// yoctoNEAR 金額を NEAR 単位の f64 に変換する（メトリクス用、精度は落ちる）
+fn yocto_to_near_f64(value: &YoctoAmount) -> f64 {
+    value.to_near().as_bigdecimal().to_f64().unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,59 @@
+//! 価格予測のメトリクス（`trade::predict` / `trade::prediction_accuracy`）
+
+use crate::register;
+use prometheus::{Histogram, HistogramOpts, IntCounterVec, Opts};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+static LATENCY: LazyLock<Histogram> = LazyLock::new(|| {
+    register(
+        Histogram::with_opts(
+            HistogramOpts::new(
+                "prediction_duration_seconds",
+                "Latency of a single token price prediction",
+            )
+            .buckets(vec![0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0]),
+        )
+        .expect("valid metric"),
+    )
+});
+
+static PREDICTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "predictions_total",
+                "Token price predictions by result (success/failure)",
+            ),
+            &["result"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static MAPE: LazyLock<Histogram> = LazyLock::new(|| {
+    register(
+        Histogram::with_opts(
+            HistogramOpts::new(
+                "prediction_mape_percent",
+                "MAPE (%) of evaluated predictions against the actual price",
+            )
+            .buckets(vec![0.5, 1.0, 2.0, 3.0, 5.0, 7.5, 10.0, 15.0, 25.0, 50.0]),
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// 1 トークン分の予測にかかった時間と成否を記録する
+pub fn record_prediction(elapsed: Duration, success: bool) {
+    LATENCY.observe(elapsed.as_secs_f64());
+    let result = if success { "success" } else { "failure" };
+    PREDICTIONS.with_label_values(&[result]).inc();
+}
+
+/// 評価済み予測の MAPE を記録する
+pub fn record_mape(mape: f64) {
+    if mape.is_finite() {
+        MAPE.observe(mape);
+    }
+}
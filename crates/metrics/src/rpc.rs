@@ -0,0 +1,132 @@
+//! NEAR RPC 呼び出しのメトリクス（`blockchain::jsonrpc`）
+
+use crate::register;
+use prometheus::{GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+static REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "rpc_requests_total",
+                "RPC requests per endpoint, method and outcome (ok/error/retry/switch_endpoint)",
+            ),
+            &["endpoint", "method", "outcome"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new("rpc_request_duration_seconds", "RPC request latency")
+                .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+            &["endpoint"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static ENDPOINT_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "rpc_endpoint_marked_failed_total",
+                "Times an endpoint was taken out of rotation by EndpointPool",
+            ),
+            &["endpoint"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static RETRY_EXHAUSTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "rpc_retry_limit_reached_total",
+                "RPC calls that gave up after reaching the global retry limit",
+            ),
+            &["method"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static HEDGED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "rpc_hedged_requests_total",
+                "View calls that fired a hedge request, by which side answered (primary/hedge/none)",
+            ),
+            &["method", "winner"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static EFFECTIVE_WEIGHT: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register(
+        GaugeVec::new(
+            Opts::new(
+                "rpc_endpoint_effective_weight",
+                "Selection weight after latency and error-rate adjustment",
+            ),
+            &["endpoint"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static HEAD_LAG: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "rpc_endpoint_head_lag_blocks",
+                "Blocks behind the best head seen across endpoints",
+            ),
+            &["endpoint"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// 1 回の RPC リクエストの結果を記録する
+pub fn record_request(endpoint: &str, method: &str, outcome: &str, elapsed: Duration) {
+    REQUESTS
+        .with_label_values(&[endpoint, method, outcome])
+        .inc();
+    DURATION
+        .with_label_values(&[endpoint])
+        .observe(elapsed.as_secs_f64());
+}
+
+/// endpoint が失敗扱いになったことを記録する
+pub fn record_endpoint_failed(endpoint: &str) {
+    ENDPOINT_FAILURES.with_label_values(&[endpoint]).inc();
+}
+
+/// 全体のリトライ上限に達して諦めたことを記録する
+pub fn record_retry_limit_reached(method: &str) {
+    RETRY_EXHAUSTED.with_label_values(&[method]).inc();
+}
+
+/// hedge を発火した呼び出しと、どちらの応答を採用したかを記録する
+pub fn record_hedge(method: &str, winner: &str) {
+    HEDGED.with_label_values(&[method, winner]).inc();
+}
+
+/// endpoint の適応重みと head の遅れを記録する
+pub fn set_endpoint_health(endpoint: &str, effective_weight: f64, head_lag: Option<u64>) {
+    EFFECTIVE_WEIGHT
+        .with_label_values(&[endpoint])
+        .set(effective_weight);
+    if let Some(lag) = head_lag {
+        HEAD_LAG
+            .with_label_values(&[endpoint])
+            .set(i64::try_from(lag).unwrap_or(i64::MAX));
+    }
+}
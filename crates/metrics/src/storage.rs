@@ -0,0 +1,59 @@
+//! REF Finance storage top-up のメトリクス（`blockchain::ref_finance::storage`）
+//!
+//! README の「Alert 閾値の由来」にある `cumulative_top_up_daily` は
+//! `increase(zaciraci_ref_storage_top_up_near_total[1d])` で求める。
+
+use crate::{register, yocto_to_near_f64};
+use common::types::YoctoAmount;
+use prometheus::{Counter, IntCounter, IntCounterVec, Opts};
+use std::sync::LazyLock;
+
+static TOP_UPS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "ref_storage_top_ups_total",
+                "REF storage deposits by kind (initial/top_up)",
+            ),
+            &["kind"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static TOP_UP_AMOUNT: LazyLock<Counter> = LazyLock::new(|| {
+    register(
+        Counter::with_opts(Opts::new(
+            "ref_storage_top_up_near_total",
+            "NEAR spent on REF storage deposits (initial deposit and top-up)",
+        ))
+        .expect("valid metric"),
+    )
+});
+
+static CAP_BREACHES: LazyLock<IntCounter> = LazyLock::new(|| {
+    register(
+        IntCounter::with_opts(Opts::new(
+            "ref_storage_cap_breaches_total",
+            "Storage deposits rejected because they exceed max_top_up",
+        ))
+        .expect("valid metric"),
+    )
+});
+
+/// 初期 storage deposit を記録する
+pub fn record_initial_deposit(amount: &YoctoAmount) {
+    TOP_UPS.with_label_values(&["initial"]).inc();
+    TOP_UP_AMOUNT.inc_by(yocto_to_near_f64(amount));
+}
+
+/// storage top-up を記録する
+pub fn record_top_up(amount: &YoctoAmount) {
+    TOP_UPS.with_label_values(&["top_up"]).inc();
+    TOP_UP_AMOUNT.inc_by(yocto_to_near_f64(amount));
+}
+
+/// cap 超過による拒否を記録する
+pub fn record_cap_breach() {
+    CAP_BREACHES.inc();
+}
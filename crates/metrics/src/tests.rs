@@ -0,0 +1,45 @@
+use super::*;
+use std::time::Duration;
+
+#[test]
+fn test_encode_includes_recorded_metrics() {
+    rpc::record_request(
+        "https://rpc.example",
+        "query",
+        "ok",
+        Duration::from_millis(120),
+    );
+    trade::record_swap(true);
+    job::record_run("test_job", "succeeded", Duration::from_secs(3));
+
+    let text = encode().unwrap();
+    assert!(text.contains(
+        r#"zaciraci_rpc_requests_total{endpoint="https://rpc.example",method="query",outcome="ok"}"#
+    ));
+    assert!(text.contains(r#"zaciraci_trade_swaps_total{result="success"}"#));
+    assert!(text.contains(r#"zaciraci_job_duration_seconds_count{job="test_job"}"#));
+}
+
+#[test]
+fn test_yocto_to_near_f64() {
+    let one_and_half = YoctoAmount::from_u128(1_500_000_000_000_000_000_000_000);
+    assert!((yocto_to_near_f64(&one_and_half) - 1.5).abs() < 1e-9);
+    assert_eq!(yocto_to_near_f64(&YoctoAmount::zero()), 0.0);
+}
+
+#[test]
+fn test_slippage_ignores_zero_estimate() {
+    // 理論出力 0 は比率が定義できないので panic せずに無視する
+    trade::record_slippage(0, 100);
+    trade::record_slippage(1_000, 990);
+    assert!(
+        encode()
+            .unwrap()
+            .contains("zaciraci_trade_swap_slippage_ratio_count")
+    );
+}
+
+#[test]
+fn test_content_type_is_text_format() {
+    assert!(content_type().starts_with("text/plain"));
+}
@@ -0,0 +1,50 @@
+//! 自動トレードのスワップ実行メトリクス（`trade::swap`）
+
+use crate::register;
+use prometheus::{Histogram, HistogramOpts, IntCounterVec, Opts};
+use std::sync::LazyLock;
+
+static SWAPS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "trade_swaps_total",
+                "Trade swaps by result (success/failure)",
+            ),
+            &["result"],
+        )
+        .expect("valid metric"),
+    )
+});
+
+static SLIPPAGE: LazyLock<Histogram> = LazyLock::new(|| {
+    register(
+        Histogram::with_opts(
+            HistogramOpts::new(
+                "trade_swap_slippage_ratio",
+                "Realised slippage (estimated - actual) / estimated; negative means better than estimated",
+            )
+            .buckets(vec![
+                -0.01, -0.001, 0.0, 0.001, 0.0025, 0.005, 0.01, 0.02, 0.05, 0.1,
+            ]),
+        )
+        .expect("valid metric"),
+    )
+});
+
+/// スワップの成否を記録する
+pub fn record_swap(success: bool) {
+    let result = if success { "success" } else { "failure" };
+    SWAPS.with_label_values(&[result]).inc();
+}
+
+/// AMM 理論出力と実際の出力からスリッページを記録する
+///
+/// 理論出力が 0 の場合は比率が定義できないため記録しない。
+pub fn record_slippage(estimated_output: u128, actual_output: u128) {
+    if estimated_output == 0 {
+        return;
+    }
+    let estimated = estimated_output as f64;
+    SLIPPAGE.observe((estimated - actual_output as f64) / estimated);
+}
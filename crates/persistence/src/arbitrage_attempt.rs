@@ -0,0 +1,380 @@
+//! 裁定取引の損益台帳
+//!
+//! 裁定取引は起点トークンで入って同じトークンで戻るため、量はすべて起点トークン
+//! （wrap.near）の最小単位で記録する。ガス代も NEAR（yocto）なので、そのまま差し引いて
+//! 純利益を求められる。
+
+use crate::connection_pool;
+use crate::schema::arbitrage_attempts;
+use anyhow::{Context, Result};
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use chrono::{NaiveDate, NaiveDateTime};
+use common::types::TokenSmallestUnits;
+use common::types::token_account::TokenAccount;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// 試行の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArbitrageAttemptStatus {
+    Success,
+    Failure,
+}
+
+impl ArbitrageAttemptStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Failure => "failure",
+        }
+    }
+}
+
+impl fmt::Display for ArbitrageAttemptStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// SYNC: accepted values must match the CHECK constraint in
+// migrations/2026-10-21-000000_create_arbitrage_attempts/up.sql
+impl FromStr for ArbitrageAttemptStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "success" => Ok(Self::Success),
+            "failure" => Ok(Self::Failure),
+            other => Err(anyhow::anyhow!(
+                "invalid arbitrage attempt status: {}",
+                other
+            )),
+        }
+    }
+}
+
+/// 経路の 1 ホップ（JSONB 用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArbitrageHop {
+    pub pool_id: u32,
+    pub token_in: TokenAccount,
+    pub token_out: TokenAccount,
+}
+
+/// 試行の結末
+#[derive(Debug, Clone)]
+pub enum ArbitrageOutcome {
+    /// トランザクションが成功した
+    Success {
+        tx_hash: String,
+        /// on-chain の実際の出力（取得できなかった場合は `None`）
+        actual_output: Option<u128>,
+        gas_burnt: u64,
+        /// 燃やされた NEAR（yocto）
+        gas_cost: u128,
+    },
+    /// 送信に失敗した、またはトランザクションが失敗した
+    Failure {
+        tx_hash: Option<String>,
+        /// on-chain で失敗した場合に燃やされたガス（未送信・結果不明なら `None`）
+        gas_burnt: Option<u64>,
+        /// 燃やされた NEAR（yocto）
+        gas_cost: Option<u128>,
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = arbitrage_attempts)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct ArbitrageAttempt {
+    pub id: i32,
+    pub attempted_at: NaiveDateTime,
+    pub start_token: String,
+    pub path: serde_json::Value,
+    #[diesel(deserialize_as = BigDecimal)]
+    pub input_amount: TokenSmallestUnits,
+    #[diesel(deserialize_as = BigDecimal)]
+    pub estimated_output: TokenSmallestUnits,
+    // Nullable カラムでは deserialize_as が Option と互換しないため BigDecimal を直接使用
+    pub actual_output: Option<BigDecimal>,
+    pub gas_burnt: Option<i64>,
+    pub gas_cost: Option<BigDecimal>,
+    pub tx_hash: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = arbitrage_attempts)]
+pub struct NewArbitrageAttempt {
+    attempted_at: NaiveDateTime,
+    start_token: String,
+    path: serde_json::Value,
+    #[diesel(serialize_as = BigDecimal)]
+    input_amount: TokenSmallestUnits,
+    #[diesel(serialize_as = BigDecimal)]
+    estimated_output: TokenSmallestUnits,
+    actual_output: Option<BigDecimal>,
+    gas_burnt: Option<i64>,
+    gas_cost: Option<BigDecimal>,
+    tx_hash: Option<String>,
+    status: String,
+    error: Option<String>,
+}
+
+impl NewArbitrageAttempt {
+    pub fn new(
+        start_token: &TokenAccount,
+        path: &[ArbitrageHop],
+        input_amount: u128,
+        estimated_output: u128,
+        outcome: ArbitrageOutcome,
+    ) -> Result<Self> {
+        let path = serde_json::to_value(path).context("Failed to serialize arbitrage path")?;
+        let (status, tx_hash, actual_output, gas_burnt, gas_cost, error) = match outcome {
+            ArbitrageOutcome::Success {
+                tx_hash,
+                actual_output,
+                gas_burnt,
+                gas_cost,
+            } => (
+                ArbitrageAttemptStatus::Success,
+                Some(tx_hash),
+                actual_output.map(BigDecimal::from),
+                Some(i64::try_from(gas_burnt).unwrap_or(i64::MAX)),
+                Some(BigDecimal::from(gas_cost)),
+                None,
+            ),
+            ArbitrageOutcome::Failure {
+                tx_hash,
+                gas_burnt,
+                gas_cost,
+                error,
+            } => (
+                ArbitrageAttemptStatus::Failure,
+                tx_hash,
+                None,
+                gas_burnt.map(|gas| i64::try_from(gas).unwrap_or(i64::MAX)),
+                gas_cost.map(BigDecimal::from),
+                Some(error),
+            ),
+        };
+        Ok(Self {
+            attempted_at: chrono::Utc::now().naive_utc(),
+            start_token: start_token.to_string(),
+            path,
+            input_amount: TokenSmallestUnits::from_u128(input_amount),
+            estimated_output: TokenSmallestUnits::from_u128(estimated_output),
+            actual_output,
+            gas_burnt,
+            gas_cost,
+            tx_hash,
+            status: status.to_string(),
+            error,
+        })
+    }
+
+    pub fn insert(self, conn: &mut PgConnection) -> QueryResult<ArbitrageAttempt> {
+        diesel::insert_into(arbitrage_attempts::table)
+            .values(self)
+            .returning(ArbitrageAttempt::as_returning())
+            .get_result(conn)
+    }
+
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "arbitrage_attempts", db.operation = "insert")
+    )]
+    pub async fn insert_async(self) -> Result<ArbitrageAttempt> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| self.insert(conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to insert arbitrage attempt")
+    }
+}
+
+impl ArbitrageAttempt {
+    pub fn status(&self) -> Result<ArbitrageAttemptStatus> {
+        self.status.parse()
+    }
+
+    /// path JSONB を ArbitrageHop の Vec にパース
+    pub fn parse_path(&self) -> Result<Vec<ArbitrageHop>> {
+        serde_json::from_value(self.path.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to parse arbitrage path: {}", e))
+    }
+
+    /// ガス代を含まない損益（成功時のみ、実績が無ければ理論出力で代用）
+    pub fn gross_profit(&self) -> BigDecimal {
+        if self.status != ArbitrageAttemptStatus::Success.as_str() {
+            return BigDecimal::zero();
+        }
+        let output = self
+            .actual_output
+            .clone()
+            .unwrap_or_else(|| self.estimated_output.as_bigdecimal().clone());
+        output - self.input_amount.as_bigdecimal()
+    }
+
+    /// 指定期間（`start` 以上 `end` 未満）の試行を古い順に取得
+    pub async fn list_between_async(
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> Result<Vec<ArbitrageAttempt>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                arbitrage_attempts::table
+                    .filter(arbitrage_attempts::attempted_at.ge(start))
+                    .filter(arbitrage_attempts::attempted_at.lt(end))
+                    .order((
+                        arbitrage_attempts::attempted_at.asc(),
+                        arbitrage_attempts::id.asc(),
+                    ))
+                    .select(ArbitrageAttempt::as_select())
+                    .load(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to list arbitrage attempts")
+    }
+
+    /// 指定日（UTC、両端を含む）の日次損益
+    pub async fn daily_summaries_async(
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<DailyArbitrageSummary>> {
+        let attempts = Self::list_between_async(
+            start.and_time(chrono::NaiveTime::MIN),
+            end.succ_opt()
+                .unwrap_or(NaiveDate::MAX)
+                .and_time(chrono::NaiveTime::MIN),
+        )
+        .await?;
+        Ok(summarize_by_day(&attempts))
+    }
+
+    /// 直近 `limit` 件の成功した試行の入力量の統計
+    ///
+    /// 成功した試行が無ければ `None`。
+    pub async fn input_statistics_async(limit: i64) -> Result<Option<InputStatistics>> {
+        let conn = connection_pool::get().await?;
+
+        let inputs = conn
+            .interact(move |conn| {
+                arbitrage_attempts::table
+                    .filter(arbitrage_attempts::status.eq(ArbitrageAttemptStatus::Success.as_str()))
+                    .order(arbitrage_attempts::attempted_at.desc())
+                    .limit(limit)
+                    .select(arbitrage_attempts::input_amount)
+                    .load::<BigDecimal>(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+            .context("Failed to load arbitrage inputs")?;
+
+        Ok(InputStatistics::from_inputs(&inputs))
+    }
+
+    /// 指定期間の試行を削除（テスト専用）
+    #[cfg(any(test, feature = "mock"))]
+    pub async fn delete_between_async(start: NaiveDateTime, end: NaiveDateTime) -> Result<()> {
+        let conn = connection_pool::get().await?;
+
+        conn.interact(move |conn| {
+            diesel::delete(
+                arbitrage_attempts::table
+                    .filter(arbitrage_attempts::attempted_at.ge(start))
+                    .filter(arbitrage_attempts::attempted_at.lt(end)),
+            )
+            .execute(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+        .map_err(|e| anyhow::anyhow!("Failed to delete arbitrage attempts: {}", e))?;
+
+        Ok(())
+    }
+}
+
+/// 成功した試行の入力量（最小単位）の統計
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputStatistics {
+    pub max: u128,
+    pub average: u128,
+}
+
+impl InputStatistics {
+    fn from_inputs(inputs: &[BigDecimal]) -> Option<Self> {
+        let values: Vec<u128> = inputs.iter().filter_map(|v| v.to_u128()).collect();
+        let max = *values.iter().max()?;
+        let sum: BigDecimal = values.iter().map(|&v| BigDecimal::from(v)).sum();
+        let average = (sum / BigDecimal::from(values.len() as u64))
+            .to_u128()
+            .unwrap_or(max);
+        Some(Self { max, average })
+    }
+}
+
+/// 1 日分の裁定取引の損益（量はすべて起点トークンの最小単位）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyArbitrageSummary {
+    pub date: NaiveDate,
+    pub attempts: u32,
+    pub successes: u32,
+    /// 成功した試行の `出力 - 入力` の合計
+    pub gross_profit: BigDecimal,
+    /// ガス代の合計（on-chain で失敗した試行の分も含む）
+    pub gas_cost: BigDecimal,
+    /// `gross_profit - gas_cost`
+    pub net_profit: BigDecimal,
+}
+
+/// 試行を UTC の日付ごとに集計する（日付の昇順）
+pub fn summarize_by_day(attempts: &[ArbitrageAttempt]) -> Vec<DailyArbitrageSummary> {
+    let mut days: BTreeMap<NaiveDate, DailyArbitrageSummary> = BTreeMap::new();
+    for attempt in attempts {
+        let date = attempt.attempted_at.date();
+        let day = days.entry(date).or_insert_with(|| DailyArbitrageSummary {
+            date,
+            attempts: 0,
+            successes: 0,
+            gross_profit: BigDecimal::zero(),
+            gas_cost: BigDecimal::zero(),
+            net_profit: BigDecimal::zero(),
+        });
+        day.attempts += 1;
+        if attempt.status == ArbitrageAttemptStatus::Success.as_str() {
+            day.successes += 1;
+        }
+        day.gross_profit += attempt.gross_profit();
+        if let Some(gas_cost) = &attempt.gas_cost {
+            day.gas_cost += gas_cost;
+        }
+    }
+    days.into_values()
+        .map(|mut day| {
+            day.net_profit = &day.gross_profit - &day.gas_cost;
+            day
+        })
+        .collect()
+}
+
+/// 期間全体の純利益
+pub fn total_net_profit(days: &[DailyArbitrageSummary]) -> BigDecimal {
+    days.iter().map(|day| &day.net_profit).sum()
+}
+
+#[cfg(test)]
+mod tests;
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::Result;
 use deadpool_diesel::postgres::Pool;
@@ -27,5 +27,21 @@ static POOL: LazyLock<Pool> = LazyLock::new(|| {
 });
 
 pub async fn get() -> Result<Client> {
-    Ok(POOL.get().await?)
+    let started = Instant::now();
+    let client = POOL.get().await;
+    metrics::db::observe_checkout(started.elapsed());
+    Ok(client?)
+}
+
+/// プールの現在の状態（サイズ・空き・待ち）をメトリクスに反映する
+///
+/// gauge は scrape 時点の値が意味を持つため、`/metrics` のハンドラから呼ぶ。
+pub fn record_metrics() {
+    let status = POOL.status();
+    metrics::db::set_pool_status(
+        status.max_size,
+        status.size,
+        status.available,
+        status.waiting,
+    );
 }
@@ -35,6 +35,15 @@ pub struct NewEvaluationPeriod {
 
 impl NewEvaluationPeriod {
     pub fn new(initial_value: YoctoAmount, selected_tokens: Vec<String>) -> Self {
+        Self::with_prefix("eval_", initial_value, selected_tokens)
+    }
+
+    /// period_id を `prefix` + UUID で作る（`get_latest_with_prefix` で探せる）
+    pub fn with_prefix(
+        prefix: &str,
+        initial_value: YoctoAmount,
+        selected_tokens: Vec<String>,
+    ) -> Self {
         let selected_tokens_opt: Option<Vec<Option<String>>> = if selected_tokens.is_empty() {
             None
         } else {
@@ -42,7 +51,7 @@ impl NewEvaluationPeriod {
         };
 
         Self {
-            period_id: format!("eval_{}", Uuid::new_v4()),
+            period_id: format!("{}{}", prefix, Uuid::new_v4()),
             start_time: chrono::Utc::now().naive_utc(),
             initial_value,
             selected_tokens: selected_tokens_opt,
@@ -55,7 +64,27 @@ impl NewEvaluationPeriod {
             .get_result(conn)
     }
 
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "evaluation_periods", db.operation = "insert")
+    )]
     pub async fn insert_async(self) -> Result<EvaluationPeriod> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            let period = EvaluationPeriod {
+                id: tables.next_id(),
+                period_id: self.period_id,
+                start_time: self.start_time,
+                initial_value: self.initial_value,
+                selected_tokens: self.selected_tokens,
+                created_at: chrono::Utc::now().naive_utc(),
+            };
+            tables.evaluation_periods.push(period.clone());
+            return Ok(period);
+        }
+
         let conn = connection_pool::get().await?;
 
         let result = conn
@@ -67,6 +96,15 @@ impl NewEvaluationPeriod {
     }
 }
 
+/// `prefix` に前方一致する LIKE パターン（`_` と `%` はリテラルとして扱う）
+fn like_prefix_pattern(prefix: &str) -> String {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('_', "\\_")
+        .replace('%', "\\%");
+    format!("{}%", escaped)
+}
+
 impl EvaluationPeriod {
     /// 最新の評価期間を取得
     pub fn get_latest(conn: &mut PgConnection) -> QueryResult<Option<EvaluationPeriod>> {
@@ -88,6 +126,41 @@ impl EvaluationPeriod {
         result.context("Failed to get latest evaluation period")
     }
 
+    /// period_id が `prefix` で始まる評価期間のうち最新のものを取得
+    pub fn get_latest_with_prefix(
+        conn: &mut PgConnection,
+        prefix: &str,
+    ) -> QueryResult<Option<EvaluationPeriod>> {
+        evaluation_periods::table
+            .filter(evaluation_periods::period_id.like(like_prefix_pattern(prefix)))
+            .order(evaluation_periods::start_time.desc())
+            .first(conn)
+            .optional()
+    }
+
+    /// period_id が `prefix` で始まる評価期間のうち最新のものを非同期で取得
+    pub async fn get_latest_with_prefix_async(prefix: String) -> Result<Option<EvaluationPeriod>> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let tables = store.tables();
+            let latest = tables
+                .evaluation_periods
+                .iter()
+                .filter(|period| period.period_id.starts_with(&prefix))
+                .max_by_key(|period| period.start_time);
+            return Ok(latest.cloned());
+        }
+
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| Self::get_latest_with_prefix(conn, &prefix))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to get latest evaluation period")
+    }
+
     /// period_idで評価期間を取得
     pub fn get_by_period_id(
         conn: &mut PgConnection,
@@ -192,10 +265,29 @@ impl EvaluationPeriod {
     }
 
     /// 選定トークンを非同期で更新
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "evaluation_periods", db.operation = "update")
+    )]
     pub async fn update_selected_tokens_async(
         period_id: String,
         tokens: Vec<String>,
     ) -> Result<EvaluationPeriod> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            let period = tables
+                .evaluation_periods
+                .iter_mut()
+                .find(|period| period.period_id == period_id)
+                .ok_or_else(|| anyhow::anyhow!("evaluation period not found: {}", period_id))
+                .context("Failed to update selected tokens")?;
+            period.selected_tokens =
+                (!tokens.is_empty()).then(|| tokens.into_iter().map(Some).collect());
+            return Ok(period.clone());
+        }
+
         let conn = connection_pool::get().await?;
 
         let result = conn
@@ -256,6 +348,15 @@ pub async fn cleanup_old_records(retention_days: u32) -> Result<()> {
     let cutoff_date =
         chrono::Utc::now().naive_utc() - chrono::TimeDelta::days(i64::from(effective_days));
 
+    #[cfg(any(test, feature = "test-helpers"))]
+    if let Some(store) = crate::memory::current() {
+        let deleted_count = store
+            .tables()
+            .delete_evaluation_periods_created_before(cutoff_date);
+        info!(log, "finish"; "deleted_count" => deleted_count);
+        return Ok(());
+    }
+
     let conn = connection_pool::get().await?;
 
     let deleted_count = conn
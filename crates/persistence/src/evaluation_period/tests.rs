@@ -26,6 +26,22 @@ fn test_new_evaluation_period_with_empty_tokens() {
     assert_eq!(new_period.selected_tokens, None);
 }
 
+#[test]
+fn test_new_evaluation_period_with_prefix() {
+    let initial_value = YoctoAmount::from_u128(1);
+
+    let new_period = NewEvaluationPeriod::with_prefix("sim_1_", initial_value, vec![]);
+
+    assert!(new_period.period_id.starts_with("sim_1_"));
+}
+
+#[test]
+fn test_like_prefix_pattern_escapes_wildcards() {
+    // `_` がワイルドカードのままだと "sim_1_" が "sim_10_..." にも一致してしまう
+    assert_eq!(like_prefix_pattern("eval_"), "eval\\_%");
+    assert_eq!(like_prefix_pattern("a%b\\"), "a\\%b\\\\%");
+}
+
 #[tokio::test]
 async fn test_initial_value_db_roundtrip() {
     // 大きな値で DB ラウンドトリップが正しく YoctoAmount に戻ることを確認
@@ -179,6 +195,7 @@ async fn test_cleanup_cascades_to_child_tables() {
         evaluation_period_id: period_id.clone(),
         timestamp: old_time,
         token_holdings: serde_json::json!([]),
+        token_pnl: None,
     };
     PortfolioHolding::insert_async(holding).await.unwrap();
 
@@ -193,6 +210,8 @@ async fn test_cleanup_cascades_to_child_tables() {
         timestamp: old_time,
         evaluation_period_id: period_id.clone(),
         actual_to_amount: None,
+        gas_burnt: None,
+        gas_cost: None,
     };
     tx.insert_async().await.unwrap();
 
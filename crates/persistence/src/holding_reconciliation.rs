@@ -0,0 +1,296 @@
+//! on-chain 保有量と DB 上の保有量の突合結果、および補正レコード
+
+use crate::connection_pool;
+use crate::schema::{holding_adjustments, holding_reconciliations};
+use anyhow::{Context, Result};
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use common::types::TokenSmallestUnits;
+use common::types::token_account::TokenAccount;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// JSONB 用のトークンごとの突合結果（量はすべて最小単位）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenReconciliation {
+    pub token: TokenAccount,
+    pub decimals: u8,
+    /// 基準スナップショット + 以降の取引 + 補正から導いた保有量（データ不整合時は負もありうる）
+    pub expected: BigDecimal,
+    /// REF Finance の deposit 残高
+    pub ref_deposit: TokenSmallestUnits,
+    /// wallet の FT 残高（参考値。基準スナップショットに含まれないので差分には入れない）
+    pub wallet_balance: TokenSmallestUnits,
+    /// `ref_deposit - expected`
+    pub difference: BigDecimal,
+    /// 許容範囲を超えた差分か
+    pub discrepancy: bool,
+}
+
+impl TokenReconciliation {
+    /// `expected` と比べる on-chain の保有量（REF deposit）
+    pub fn actual(&self) -> BigDecimal {
+        self.ref_deposit.as_bigdecimal().clone()
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = holding_reconciliations)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct DbHoldingReconciliation {
+    pub id: i32,
+    pub evaluation_period_id: String,
+    pub baseline_holding_id: i32,
+    pub checked_at: NaiveDateTime,
+    pub discrepancy_count: i32,
+    pub adjusted: bool,
+    pub tokens: serde_json::Value,
+}
+
+impl DbHoldingReconciliation {
+    /// tokens JSONB を TokenReconciliation の Vec にパース
+    pub fn parse_tokens(&self) -> Result<Vec<TokenReconciliation>> {
+        serde_json::from_value(self.tokens.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to parse reconciliation tokens: {}", e))
+    }
+}
+
+/// 1 回の突合結果
+#[derive(Debug, Clone)]
+pub struct ReconciliationReport {
+    pub evaluation_period_id: String,
+    /// 基準にした `portfolio_holdings` の行
+    pub baseline_holding_id: i32,
+    pub checked_at: NaiveDateTime,
+    pub tokens: Vec<TokenReconciliation>,
+}
+
+impl ReconciliationReport {
+    pub fn discrepancies(&self) -> impl Iterator<Item = &TokenReconciliation> {
+        self.tokens.iter().filter(|t| t.discrepancy)
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = holding_reconciliations)]
+struct NewHoldingReconciliation {
+    evaluation_period_id: String,
+    baseline_holding_id: i32,
+    checked_at: NaiveDateTime,
+    discrepancy_count: i32,
+    adjusted: bool,
+    tokens: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = holding_adjustments)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HoldingAdjustment {
+    pub id: i32,
+    pub reconciliation_id: i32,
+    pub evaluation_period_id: String,
+    pub token: String,
+    pub amount: BigDecimal,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = holding_adjustments)]
+struct NewHoldingAdjustment {
+    reconciliation_id: i32,
+    evaluation_period_id: String,
+    token: String,
+    amount: BigDecimal,
+    created_at: NaiveDateTime,
+}
+
+pub struct HoldingReconciliation;
+
+impl HoldingReconciliation {
+    /// 突合結果を記録する
+    ///
+    /// `write_adjustments` が true なら、差分が許容範囲を超えたトークンについて
+    /// 差分をそのまま補正レコードとして同一トランザクションで書き込む。
+    pub fn insert(
+        report: &ReconciliationReport,
+        write_adjustments: bool,
+        conn: &mut PgConnection,
+    ) -> QueryResult<DbHoldingReconciliation> {
+        let write_adjustments = write_adjustments && report.discrepancies().next().is_some();
+        let tokens = serde_json::to_value(&report.tokens)
+            .map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))?;
+        let record = NewHoldingReconciliation {
+            evaluation_period_id: report.evaluation_period_id.clone(),
+            baseline_holding_id: report.baseline_holding_id,
+            checked_at: report.checked_at,
+            discrepancy_count: report.discrepancies().count() as i32,
+            adjusted: write_adjustments,
+            tokens,
+        };
+
+        conn.transaction(|conn| {
+            let inserted: DbHoldingReconciliation =
+                diesel::insert_into(holding_reconciliations::table)
+                    .values(&record)
+                    .returning(DbHoldingReconciliation::as_returning())
+                    .get_result(conn)?;
+
+            if write_adjustments {
+                let rows: Vec<NewHoldingAdjustment> = report
+                    .discrepancies()
+                    .map(|t| NewHoldingAdjustment {
+                        reconciliation_id: inserted.id,
+                        evaluation_period_id: report.evaluation_period_id.clone(),
+                        token: t.token.to_string(),
+                        amount: t.difference.clone(),
+                        created_at: report.checked_at,
+                    })
+                    .collect();
+                diesel::insert_into(holding_adjustments::table)
+                    .values(&rows)
+                    .execute(conn)?;
+            }
+
+            Ok(inserted)
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "holding_reconciliations", db.operation = "insert")
+    )]
+    pub async fn insert_async(
+        report: ReconciliationReport,
+        write_adjustments: bool,
+    ) -> Result<DbHoldingReconciliation> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| Self::insert(&report, write_adjustments, conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to insert holding reconciliation")
+    }
+
+    /// 突合結果を新しい順に取得
+    pub async fn list_recent_async(
+        page: i64,
+        page_size: i64,
+    ) -> Result<Vec<DbHoldingReconciliation>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                holding_reconciliations::table
+                    .order((
+                        holding_reconciliations::checked_at.desc(),
+                        holding_reconciliations::id.desc(),
+                    ))
+                    .limit(page_size)
+                    .offset(page * page_size)
+                    .select(DbHoldingReconciliation::as_select())
+                    .load(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to list holding reconciliations")
+    }
+
+    /// 突合結果の件数
+    pub async fn count_async() -> Result<i64> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(|conn| holding_reconciliations::table.count().get_result(conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to count holding reconciliations")
+    }
+
+    /// id で取得
+    pub async fn get_by_id_async(id: i32) -> Result<Option<DbHoldingReconciliation>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                holding_reconciliations::table
+                    .filter(holding_reconciliations::id.eq(id))
+                    .select(DbHoldingReconciliation::as_select())
+                    .first(conn)
+                    .optional()
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to get holding reconciliation by id")
+    }
+
+    /// 指定した突合で書き込まれた補正レコード
+    pub async fn get_adjustments_async(reconciliation_id: i32) -> Result<Vec<HoldingAdjustment>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                holding_adjustments::table
+                    .filter(holding_adjustments::reconciliation_id.eq(reconciliation_id))
+                    .order(holding_adjustments::token.asc())
+                    .select(HoldingAdjustment::as_select())
+                    .load(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to get holding adjustments")
+    }
+
+    /// 評価期間内の補正量をトークンごとに合計する
+    pub async fn sum_adjustments_by_period_async(
+        period_id: String,
+    ) -> Result<BTreeMap<String, BigDecimal>> {
+        let conn = connection_pool::get().await?;
+
+        let rows = conn
+            .interact(move |conn| {
+                holding_adjustments::table
+                    .filter(holding_adjustments::evaluation_period_id.eq(&period_id))
+                    .select((holding_adjustments::token, holding_adjustments::amount))
+                    .load::<(String, BigDecimal)>(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+            .context("Failed to sum holding adjustments")?;
+
+        let mut sums: BTreeMap<String, BigDecimal> = BTreeMap::new();
+        for (token, amount) in rows {
+            *sums.entry(token).or_default() += amount;
+        }
+        Ok(sums)
+    }
+
+    /// 指定評価期間の突合結果を削除（テスト専用、補正レコードも cascade で消える）
+    #[cfg(any(test, feature = "mock"))]
+    pub async fn delete_by_period_async(period_id: String) -> Result<()> {
+        let conn = connection_pool::get().await?;
+
+        conn.interact(move |conn| {
+            diesel::delete(
+                holding_reconciliations::table
+                    .filter(holding_reconciliations::evaluation_period_id.eq(&period_id)),
+            )
+            .execute(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+        .map_err(|e| anyhow::anyhow!("Failed to delete holding reconciliations: {}", e))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
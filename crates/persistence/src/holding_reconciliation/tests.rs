@@ -0,0 +1,117 @@
+use super::*;
+use crate::evaluation_period::NewEvaluationPeriod;
+use crate::portfolio_holding::{NewPortfolioHolding, PortfolioHolding};
+use common::types::YoctoAmount;
+use futures::FutureExt;
+use std::panic::AssertUnwindSafe;
+
+async fn create_test_period_with_snapshot() -> (String, i32) {
+    let period_id = NewEvaluationPeriod::new(
+        YoctoAmount::from_u128(100_000_000_000_000_000_000_000_000),
+        vec![],
+    )
+    .insert_async()
+    .await
+    .unwrap()
+    .period_id;
+    PortfolioHolding::insert_async(NewPortfolioHolding {
+        evaluation_period_id: period_id.clone(),
+        timestamp: chrono::Utc::now().naive_utc(),
+        token_holdings: serde_json::json!([]),
+        token_pnl: None,
+    })
+    .await
+    .unwrap();
+    let baseline = PortfolioHolding::get_first_for_period_async(period_id.clone())
+        .await
+        .unwrap()
+        .unwrap();
+    (period_id, baseline.id)
+}
+
+fn token(name: &str, difference: i64, discrepancy: bool) -> TokenReconciliation {
+    TokenReconciliation {
+        token: name.parse().unwrap(),
+        decimals: 18,
+        expected: BigDecimal::from(1_000),
+        ref_deposit: TokenSmallestUnits::from_bigdecimal(BigDecimal::from(1_000 + difference)),
+        wallet_balance: TokenSmallestUnits::zero(),
+        difference: BigDecimal::from(difference),
+        discrepancy,
+    }
+}
+
+#[test]
+fn test_token_reconciliation_actual() {
+    let mut t = token("a.near", 5, true);
+    t.wallet_balance = TokenSmallestUnits::from_u128(10);
+    assert_eq!(t.actual(), BigDecimal::from(1_005));
+}
+
+// --- DB integration tests ---
+
+#[tokio::test]
+async fn test_insert_with_and_without_adjustments() {
+    let (period_id, baseline_id) = create_test_period_with_snapshot().await;
+
+    let result = AssertUnwindSafe(async {
+        let report = ReconciliationReport {
+            evaluation_period_id: period_id.clone(),
+            baseline_holding_id: baseline_id,
+            checked_at: chrono::Utc::now().naive_utc(),
+            tokens: vec![
+                token("a.near", -300, true),
+                token("b.near", 1, false),
+                token("c.near", 200, true),
+            ],
+        };
+
+        // 補正なし
+        let dry = HoldingReconciliation::insert_async(report.clone(), false)
+            .await
+            .unwrap();
+        assert_eq!(dry.discrepancy_count, 2);
+        assert!(!dry.adjusted);
+        assert_eq!(dry.parse_tokens().unwrap(), report.tokens);
+        assert!(
+            HoldingReconciliation::get_adjustments_async(dry.id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // 補正あり: 差分が許容範囲外のトークンだけ補正される
+        let adjusted = HoldingReconciliation::insert_async(report, true)
+            .await
+            .unwrap();
+        assert!(adjusted.adjusted);
+        let adjustments = HoldingReconciliation::get_adjustments_async(adjusted.id)
+            .await
+            .unwrap();
+        assert_eq!(adjustments.len(), 2);
+        assert_eq!(adjustments[0].token, "a.near");
+        assert_eq!(adjustments[0].amount, BigDecimal::from(-300));
+
+        let sums = HoldingReconciliation::sum_adjustments_by_period_async(period_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(sums.get("a.near"), Some(&BigDecimal::from(-300)));
+        assert_eq!(sums.get("c.near"), Some(&BigDecimal::from(200)));
+        assert!(!sums.contains_key("b.near"));
+
+        let found = HoldingReconciliation::get_by_id_async(adjusted.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.evaluation_period_id, period_id);
+    })
+    .catch_unwind()
+    .await;
+
+    let _ = HoldingReconciliation::delete_by_period_async(period_id.clone()).await;
+    let _ = crate::evaluation_period::EvaluationPeriod::delete_by_period_id_async(period_id).await;
+
+    if let Err(e) = result {
+        std::panic::resume_unwind(e);
+    }
+}
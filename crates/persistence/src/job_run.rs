@@ -0,0 +1,388 @@
+use crate::connection_pool;
+use crate::schema::job_runs;
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use logging::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// ジョブ実行のきっかけ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobRunTrigger {
+    /// cron スケジュールどおりの実行
+    Schedule,
+    /// 停止中に取りこぼした予定時刻の追いかけ実行
+    CatchUp,
+    /// gRPC 等からの手動実行
+    Manual,
+}
+
+impl JobRunTrigger {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Schedule => "schedule",
+            Self::CatchUp => "catch_up",
+            Self::Manual => "manual",
+        }
+    }
+}
+
+impl fmt::Display for JobRunTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// SYNC: accepted values must match the CHECK constraint in
+// migrations/2026-10-18-000000_create_job_runs/up.sql
+impl FromStr for JobRunTrigger {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "schedule" => Ok(Self::Schedule),
+            "catch_up" => Ok(Self::CatchUp),
+            "manual" => Ok(Self::Manual),
+            other => Err(anyhow::anyhow!("invalid job run trigger: {}", other)),
+        }
+    }
+}
+
+/// ジョブ実行の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// 前回の実行が終わっていなかったため実行しなかった
+    Skipped,
+    /// `Running` のままプロセスが終了した（起動時に回収される）
+    Abandoned,
+}
+
+impl JobRunStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+            Self::Abandoned => "abandoned",
+        }
+    }
+}
+
+impl fmt::Display for JobRunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// SYNC: accepted values must match the CHECK constraint in
+// migrations/2026-10-18-000000_create_job_runs/up.sql
+impl FromStr for JobRunStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "running" => Ok(Self::Running),
+            "succeeded" => Ok(Self::Succeeded),
+            "failed" => Ok(Self::Failed),
+            "skipped" => Ok(Self::Skipped),
+            "abandoned" => Ok(Self::Abandoned),
+            other => Err(anyhow::anyhow!("invalid job run status: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = job_runs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct JobRun {
+    pub id: i32,
+    pub job_name: String,
+    pub trigger: String,
+    pub scheduled_at: Option<NaiveDateTime>,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+impl JobRun {
+    pub fn trigger(&self) -> Result<JobRunTrigger> {
+        self.trigger.parse()
+    }
+
+    pub fn status(&self) -> Result<JobRunStatus> {
+        self.status.parse()
+    }
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = job_runs)]
+pub struct NewJobRun {
+    pub job_name: String,
+    pub trigger: String,
+    pub scheduled_at: Option<NaiveDateTime>,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    pub status: String,
+    pub error: Option<String>,
+}
+
+impl NewJobRun {
+    /// 実行開始レコード（status = running）
+    pub fn started(
+        job_name: &str,
+        trigger: JobRunTrigger,
+        scheduled_at: Option<NaiveDateTime>,
+    ) -> Self {
+        Self {
+            job_name: job_name.to_string(),
+            trigger: trigger.as_str().to_string(),
+            scheduled_at,
+            started_at: chrono::Utc::now().naive_utc(),
+            finished_at: None,
+            status: JobRunStatus::Running.as_str().to_string(),
+            error: None,
+        }
+    }
+
+    /// 実行せずに終わったレコード（status = skipped）
+    pub fn skipped(
+        job_name: &str,
+        trigger: JobRunTrigger,
+        scheduled_at: Option<NaiveDateTime>,
+        reason: &str,
+    ) -> Self {
+        let now = chrono::Utc::now().naive_utc();
+        Self {
+            job_name: job_name.to_string(),
+            trigger: trigger.as_str().to_string(),
+            scheduled_at,
+            started_at: now,
+            finished_at: Some(now),
+            status: JobRunStatus::Skipped.as_str().to_string(),
+            error: Some(reason.to_string()),
+        }
+    }
+
+    pub async fn insert_async(self) -> Result<JobRun> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                diesel::insert_into(job_runs::table)
+                    .values(&self)
+                    .get_result(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to insert job run")
+    }
+}
+
+impl JobRun {
+    /// 実行終了を記録する。`error` が `Some` なら failed、`None` なら succeeded。
+    pub async fn finish_async(id: i32, error: Option<String>) -> Result<()> {
+        let status = if error.is_some() {
+            JobRunStatus::Failed
+        } else {
+            JobRunStatus::Succeeded
+        };
+        let conn = connection_pool::get().await?;
+
+        conn.interact(move |conn| {
+            diesel::update(job_runs::table.filter(job_runs::id.eq(id)))
+                .set((
+                    job_runs::finished_at.eq(chrono::Utc::now().naive_utc()),
+                    job_runs::status.eq(status.as_str()),
+                    job_runs::error.eq(error),
+                ))
+                .execute(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+        .context("Failed to finish job run")?;
+
+        Ok(())
+    }
+
+    /// `running` のまま残っているレコードを `abandoned` にする
+    ///
+    /// backend は single-process 前提のため、起動時点で running のレコードは
+    /// 前のプロセスが実行中に落ちたものと見なせる。
+    ///
+    /// 戻り値: 更新した件数
+    pub async fn abandon_running_async() -> Result<usize> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(|conn| {
+                diesel::update(
+                    job_runs::table.filter(job_runs::status.eq(JobRunStatus::Running.as_str())),
+                )
+                .set((
+                    job_runs::finished_at.eq(chrono::Utc::now().naive_utc()),
+                    job_runs::status.eq(JobRunStatus::Abandoned.as_str()),
+                    job_runs::error.eq("process exited while running"),
+                ))
+                .execute(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to abandon running job runs")
+    }
+
+    /// 予定時刻ベース（schedule / catch_up）で最後に処理した予定時刻を取得
+    ///
+    /// skipped も含める（overlap で見送った予定時刻は取りこぼしではないため）。
+    pub async fn latest_scheduled_at_async(job_name: String) -> Result<Option<NaiveDateTime>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                job_runs::table
+                    .filter(job_runs::job_name.eq(&job_name))
+                    .filter(job_runs::scheduled_at.is_not_null())
+                    .select(diesel::dsl::max(job_runs::scheduled_at))
+                    .first::<Option<NaiveDateTime>>(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to get latest scheduled_at")
+    }
+
+    /// ジョブ実行履歴を新しい順に取得（`job_name` が `None` なら全ジョブ）
+    pub async fn list_recent_async(
+        job_name: Option<String>,
+        page: i64,
+        page_size: i64,
+    ) -> Result<Vec<JobRun>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                let mut query = job_runs::table.into_boxed();
+                if let Some(name) = job_name {
+                    query = query.filter(job_runs::job_name.eq(name));
+                }
+                query
+                    .order((job_runs::started_at.desc(), job_runs::id.desc()))
+                    .limit(page_size)
+                    .offset(page * page_size)
+                    .select(JobRun::as_select())
+                    .load(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to list job runs")
+    }
+
+    /// ジョブ実行履歴の件数（`job_name` が `None` なら全ジョブ）
+    pub async fn count_async(job_name: Option<String>) -> Result<i64> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                let mut query = job_runs::table.into_boxed();
+                if let Some(name) = job_name {
+                    query = query.filter(job_runs::job_name.eq(name));
+                }
+                query.count().get_result(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to count job runs")
+    }
+
+    /// id で取得
+    pub async fn get_by_id_async(id: i32) -> Result<Option<JobRun>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                job_runs::table
+                    .filter(job_runs::id.eq(id))
+                    .select(JobRun::as_select())
+                    .first(conn)
+                    .optional()
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to get job run by id")
+    }
+
+    /// 指定ジョブの実行履歴を削除（テスト専用）
+    #[cfg(any(test, feature = "mock"))]
+    pub async fn delete_by_job_name_async(job_name: String) -> Result<()> {
+        let conn = connection_pool::get().await?;
+
+        conn.interact(move |conn| {
+            diesel::delete(job_runs::table.filter(job_runs::job_name.eq(&job_name))).execute(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+        .map_err(|e| anyhow::anyhow!("Failed to delete job runs: {}", e))?;
+
+        Ok(())
+    }
+}
+
+/// Minimum retention period to prevent accidental mass deletion
+const MIN_RETENTION_DAYS: u32 = 7;
+
+/// 指定日数より古い実行履歴を削除
+pub async fn cleanup_old_records(retention_days: u32) -> Result<()> {
+    let log = DEFAULT.new(o!(
+        "function" => "job_run::cleanup_old_records",
+        "retention_days" => retention_days,
+    ));
+
+    if retention_days == 0 {
+        warn!(
+            log,
+            "retention_days is 0, skipping cleanup to prevent deleting all records"
+        );
+        return Ok(());
+    }
+
+    let effective_days = retention_days.max(MIN_RETENTION_DAYS);
+    if effective_days != retention_days {
+        warn!(log, "retention_days below minimum, using minimum";
+            "requested" => retention_days, "effective" => effective_days);
+    }
+
+    trace!(log, "start");
+
+    let cutoff_date =
+        chrono::Utc::now().naive_utc() - chrono::TimeDelta::days(i64::from(effective_days));
+
+    let conn = connection_pool::get().await?;
+
+    let deleted_count = conn
+        .interact(move |conn| {
+            diesel::delete(
+                job_runs::table
+                    .filter(job_runs::started_at.lt(cutoff_date))
+                    .filter(job_runs::status.ne(JobRunStatus::Running.as_str())),
+            )
+            .execute(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interaction error: {:?}", e))??;
+
+    info!(log, "finish"; "deleted_count" => deleted_count);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
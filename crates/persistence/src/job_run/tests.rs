@@ -0,0 +1,138 @@
+use super::*;
+use serial_test::serial;
+
+#[test]
+fn test_trigger_round_trip() {
+    for trigger in [
+        JobRunTrigger::Schedule,
+        JobRunTrigger::CatchUp,
+        JobRunTrigger::Manual,
+    ] {
+        assert_eq!(trigger.as_str().parse::<JobRunTrigger>().unwrap(), trigger);
+    }
+    assert!("cron".parse::<JobRunTrigger>().is_err());
+}
+
+#[test]
+fn test_status_round_trip() {
+    for status in [
+        JobRunStatus::Running,
+        JobRunStatus::Succeeded,
+        JobRunStatus::Failed,
+        JobRunStatus::Skipped,
+        JobRunStatus::Abandoned,
+    ] {
+        assert_eq!(status.as_str().parse::<JobRunStatus>().unwrap(), status);
+    }
+    assert!("done".parse::<JobRunStatus>().is_err());
+}
+
+// --- DB integration tests ---
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_start_and_finish() -> Result<()> {
+    let job = "test_job_run_start_and_finish".to_string();
+    JobRun::delete_by_job_name_async(job.clone()).await?;
+
+    let scheduled = chrono::Utc::now().naive_utc();
+    let run = NewJobRun::started(&job, JobRunTrigger::Schedule, Some(scheduled))
+        .insert_async()
+        .await?;
+    assert_eq!(run.status()?, JobRunStatus::Running);
+    assert_eq!(run.trigger()?, JobRunTrigger::Schedule);
+    assert!(run.finished_at.is_none());
+
+    JobRun::finish_async(run.id, Some("boom".to_string())).await?;
+    let finished = JobRun::get_by_id_async(run.id).await?.unwrap();
+    assert_eq!(finished.status()?, JobRunStatus::Failed);
+    assert_eq!(finished.error.as_deref(), Some("boom"));
+    assert!(finished.finished_at.is_some());
+
+    JobRun::delete_by_job_name_async(job).await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_latest_scheduled_at_ignores_manual_runs() -> Result<()> {
+    let job = "test_job_run_latest_scheduled".to_string();
+    JobRun::delete_by_job_name_async(job.clone()).await?;
+
+    assert!(
+        JobRun::latest_scheduled_at_async(job.clone())
+            .await?
+            .is_none()
+    );
+
+    let base = chrono::Utc::now().naive_utc() - chrono::TimeDelta::hours(3);
+    let earlier = base - chrono::TimeDelta::hours(1);
+    NewJobRun::started(&job, JobRunTrigger::Schedule, Some(earlier))
+        .insert_async()
+        .await?;
+    NewJobRun::skipped(&job, JobRunTrigger::Schedule, Some(base), "overlap")
+        .insert_async()
+        .await?;
+    NewJobRun::started(&job, JobRunTrigger::Manual, None)
+        .insert_async()
+        .await?;
+
+    let latest = JobRun::latest_scheduled_at_async(job.clone())
+        .await?
+        .unwrap();
+    assert_eq!(latest.and_utc().timestamp(), base.and_utc().timestamp());
+
+    JobRun::delete_by_job_name_async(job).await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_list_recent_and_count() -> Result<()> {
+    let job = "test_job_run_list_recent".to_string();
+    JobRun::delete_by_job_name_async(job.clone()).await?;
+
+    for _ in 0..3 {
+        NewJobRun::started(&job, JobRunTrigger::Manual, None)
+            .insert_async()
+            .await?;
+    }
+
+    assert_eq!(JobRun::count_async(Some(job.clone())).await?, 3);
+
+    let page0 = JobRun::list_recent_async(Some(job.clone()), 0, 2).await?;
+    let page1 = JobRun::list_recent_async(Some(job.clone()), 1, 2).await?;
+    assert_eq!(page0.len(), 2);
+    assert_eq!(page1.len(), 1);
+    assert!(page0[0].id > page0[1].id);
+    assert!(page0[1].id > page1[0].id);
+
+    JobRun::delete_by_job_name_async(job).await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_abandon_running() -> Result<()> {
+    let job = "test_job_run_abandon".to_string();
+    JobRun::delete_by_job_name_async(job.clone()).await?;
+
+    let running = NewJobRun::started(&job, JobRunTrigger::Schedule, None)
+        .insert_async()
+        .await?;
+    let done = NewJobRun::started(&job, JobRunTrigger::Schedule, None)
+        .insert_async()
+        .await?;
+    JobRun::finish_async(done.id, None).await?;
+
+    let count = JobRun::abandon_running_async().await?;
+    assert!(count >= 1);
+
+    let running = JobRun::get_by_id_async(running.id).await?.unwrap();
+    assert_eq!(running.status()?, JobRunStatus::Abandoned);
+    let done = JobRun::get_by_id_async(done.id).await?.unwrap();
+    assert_eq!(done.status()?, JobRunStatus::Succeeded);
+
+    JobRun::delete_by_job_name_async(job).await?;
+    Ok(())
+}
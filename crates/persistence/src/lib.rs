@@ -1,10 +1,16 @@
 #![deny(warnings)]
 
+pub mod arbitrage_attempt;
 pub mod authorized_users;
 pub mod config_store;
 pub mod connection_pool;
 pub mod evaluation_period;
+pub mod holding_reconciliation;
+pub mod job_run;
 pub mod maintenance;
+#[cfg(any(test, feature = "test-helpers"))]
+pub mod memory;
+pub mod pending_transaction;
 pub mod pool_info;
 pub mod portfolio_holding;
 pub mod prediction_record;
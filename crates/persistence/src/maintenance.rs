@@ -9,6 +9,7 @@ const REINDEX_TARGETS: &[&str] = &[
     "config_store",
     "config_store_history",
     "evaluation_periods",
+    "job_runs",
     "pool_info",
     "portfolio_holdings",
     "prediction_records",
@@ -16,77 +17,42 @@ const REINDEX_TARGETS: &[&str] = &[
     "trade_transactions",
 ];
 
-/// DB メンテナンスの定期実行エントリポイント
-pub async fn run(cfg: impl ConfigAccess + 'static) {
-    let log = DEFAULT.new(o!("function" => "maintenance::run"));
-    info!(log, "starting db maintenance cron job");
-
-    let cron_conf = cfg.db_maintenance_cron_schedule();
-    let schedule: cron::Schedule = match cron_conf.parse() {
-        Ok(s) => {
-            info!(log, "db maintenance schedule configured"; "schedule" => &cron_conf);
-            s
-        }
-        Err(e) => {
-            error!(log, "failed to parse db maintenance schedule, using default";
-                   "error" => ?e, "schedule" => &cron_conf, "default" => DEFAULT_CRON_SCHEDULE);
-            DEFAULT_CRON_SCHEDULE
-                .parse()
-                .expect("hardcoded default cron schedule must be valid")
-        }
-    };
-
-    for next in schedule.upcoming(chrono::Utc) {
-        let now = chrono::Utc::now();
-        if next <= now {
-            continue;
-        }
-
-        let wait = match (next - now).to_std() {
-            Ok(d) => d,
-            Err(_) => continue,
-        };
-
-        debug!(log, "waiting for next maintenance";
-            "next_time" => %next,
-            "wait_seconds" => wait.as_secs()
-        );
-
-        // 1分間隔でスリープ（長時間 sleep を避ける）
-        loop {
-            let now = chrono::Utc::now();
-            if now >= next {
-                break;
+/// DB メンテナンスのデフォルト cron スケジュール
+pub const DEFAULT_CRON_SCHEDULE: &str = "0 0 4 * * 7";
+
+/// DB メンテナンスを1回実行する
+///
+/// スケジューリングは呼び出し側（`scheduler` クレートに登録されたジョブ）が担う。
+/// 個々のテーブルの REINDEX 失敗は他のテーブルの処理を止めないが、
+/// 1件でも失敗すれば実行結果としてエラーを返す。
+pub async fn execute(cfg: &impl ConfigAccess) -> Result<()> {
+    let log = DEFAULT.new(o!("function" => "maintenance::execute"));
+    info!(log, "executing db maintenance");
+
+    let mut failed = Vec::new();
+    for table in REINDEX_TARGETS {
+        match reindex_table(table).await {
+            Ok(()) => info!(log, "reindex completed"; "table" => *table),
+            Err(e) => {
+                error!(log, "reindex failed"; "table" => *table, "error" => %e);
+                failed.push(*table);
             }
-            let remaining = match (next - now).to_std() {
-                Ok(d) => d,
-                Err(_) => break,
-            };
-            let max_sleep = cfg.cron_max_sleep_seconds();
-            let sleep_duration = remaining.min(std::time::Duration::from_secs(max_sleep));
-            tokio::time::sleep(sleep_duration).await;
-        }
-
-        // 実行前に DB 設定をリロード
-        let instance_id = &common::config::startup::get().instance_id;
-        if let Err(e) = crate::config_store::reload_to_config(instance_id).await {
-            warn!(log, "config reload failed, using previous values"; "error" => %e);
         }
+    }
 
-        info!(log, "executing db maintenance");
+    if let Err(e) = crate::job_run::cleanup_old_records(cfg.job_runs_retention_days()).await {
+        warn!(log, "failed to cleanup old job_runs records"; "error" => %e);
+    }
 
-        for table in REINDEX_TARGETS {
-            match reindex_table(table).await {
-                Ok(()) => info!(log, "reindex completed"; "table" => *table),
-                Err(e) => error!(log, "reindex failed"; "table" => *table, "error" => %e),
-            }
-        }
+    if !failed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "reindex failed for tables: {}",
+            failed.join(", ")
+        ));
     }
+    Ok(())
 }
 
-/// REINDEX 対象テーブルのデフォルト cron スケジュール
-const DEFAULT_CRON_SCHEDULE: &str = "0 0 4 * * 7";
-
 /// テーブル名がホワイトリストに含まれるか検証
 fn validate_reindex_target(table_name: &str) -> Result<()> {
     if !REINDEX_TARGETS.contains(&table_name) {
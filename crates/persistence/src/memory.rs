@@ -0,0 +1,156 @@
+//! Postgres の代わりに使うインメモリのストア
+//!
+//! [`MemoryStore::scope`] の中で実行した処理では、戦略サイクルが使う永続化関数
+//! （評価期間・レート・プール・予測・取引記録・journal・保有量）が DB ではなくこのストアを
+//! 読み書きする。各関数は DB と同じ条件・並び順で結果を返す。
+//!
+//! ストアはタスクローカルなので、スコープの外や並行して動く DB テストには影響しない。
+//! `tokio::spawn` した先には引き継がれないため、スコープ内から spawn する処理は
+//! [`propagate`] で包む。
+//!
+//! テスト専用（`test-helpers` feature）で、本番ビルドには含まれない。DB との一致は
+//! `memory/tests.rs` のパリティテストで、同じクエリを両方のバックエンドに投げて確かめる。
+
+use crate::evaluation_period::EvaluationPeriod;
+use crate::pending_transaction::PendingTransaction;
+use crate::portfolio_holding::DbPortfolioHolding;
+use crate::prediction_record::{DbPredictionRecord, NewPredictionRecord};
+use crate::token_rate::TokenRate;
+use crate::trade_transaction::TradeTransaction;
+use chrono::NaiveDateTime;
+use dex::{PoolInfo, PoolInfoList};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+tokio::task_local! {
+    static STORE: Arc<MemoryStore>;
+}
+
+/// 実行中のタスクが使うストア（スコープ外なら `None` で、DB を使う）
+pub(crate) fn current() -> Option<Arc<MemoryStore>> {
+    STORE.try_with(Arc::clone).ok()
+}
+
+/// 呼び出し時点のストアを `future` に引き継ぐ（`tokio::spawn` に渡す前に包む）
+pub fn propagate<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    let store = current();
+    async move {
+        match store {
+            Some(store) => STORE.scope(store, future).await,
+            None => future.await,
+        }
+    }
+}
+
+/// テーブルごとの行
+#[derive(Debug, Default)]
+pub(crate) struct Tables {
+    last_id: i32,
+    pub(crate) evaluation_periods: Vec<EvaluationPeriod>,
+    pub(crate) token_rates: Vec<TokenRate>,
+    pub(crate) pool_infos: Vec<Arc<PoolInfo>>,
+    pub(crate) prediction_records: Vec<DbPredictionRecord>,
+    pub(crate) trade_transactions: Vec<TradeTransaction>,
+    pub(crate) pending_transactions: Vec<PendingTransaction>,
+    pub(crate) portfolio_holdings: Vec<DbPortfolioHolding>,
+}
+
+impl Tables {
+    /// serial カラムの次の値（全テーブル共通の連番）
+    pub(crate) fn next_id(&mut self) -> i32 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    /// `cutoff` より前に作成された評価期間を、子テーブルの行とともに削除する（ON DELETE CASCADE 相当）
+    pub(crate) fn delete_evaluation_periods_created_before(
+        &mut self,
+        cutoff: NaiveDateTime,
+    ) -> usize {
+        let before = self.evaluation_periods.len();
+        self.evaluation_periods
+            .retain(|period| period.created_at >= cutoff);
+        let remaining: HashSet<&str> = self
+            .evaluation_periods
+            .iter()
+            .map(|period| period.period_id.as_str())
+            .collect();
+        self.trade_transactions
+            .retain(|tx| remaining.contains(tx.evaluation_period_id.as_str()));
+        self.pending_transactions
+            .retain(|entry| remaining.contains(entry.evaluation_period_id.as_str()));
+        self.portfolio_holdings
+            .retain(|holding| remaining.contains(holding.evaluation_period_id.as_str()));
+        before - self.evaluation_periods.len()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    tables: Mutex<Tables>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// `future` の中の永続化関数をこのストアに向けて実行する
+    pub async fn scope<F: Future>(self: &Arc<Self>, future: F) -> F::Output {
+        STORE.scope(Arc::clone(self), future).await
+    }
+
+    pub(crate) fn tables(&self) -> MutexGuard<'_, Tables> {
+        self.tables.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// レートを追加する（`TokenRate::batch_insert` 相当）
+    pub fn insert_token_rates(&self, rates: &[TokenRate]) {
+        self.tables().token_rates.extend_from_slice(rates);
+    }
+
+    /// プール一覧を追加する（`pool_info::write_to_db` 相当。各プールの timestamp で記録される）
+    pub fn insert_pools(&self, pools: &PoolInfoList) {
+        self.tables().pool_infos.extend_from_slice(pools.list());
+    }
+
+    /// 予測を `created_at` に作成されたものとして追加し、id を返す
+    pub fn insert_prediction(&self, record: NewPredictionRecord, created_at: NaiveDateTime) -> i32 {
+        let mut tables = self.tables();
+        let id = tables.next_id();
+        tables.prediction_records.push(DbPredictionRecord {
+            id,
+            token: record.token,
+            quote_token: record.quote_token,
+            predicted_price: record.predicted_price,
+            data_cutoff_time: record.data_cutoff_time,
+            target_time: record.target_time,
+            actual_price: None,
+            mape: None,
+            absolute_error: None,
+            evaluated_at: None,
+            created_at,
+        });
+        id
+    }
+
+    pub fn evaluation_periods(&self) -> Vec<EvaluationPeriod> {
+        self.tables().evaluation_periods.clone()
+    }
+
+    pub fn trade_transactions(&self) -> Vec<TradeTransaction> {
+        self.tables().trade_transactions.clone()
+    }
+
+    pub fn pending_transactions(&self) -> Vec<PendingTransaction> {
+        self.tables().pending_transactions.clone()
+    }
+
+    pub fn portfolio_holdings(&self) -> Vec<DbPortfolioHolding> {
+        self.tables().portfolio_holdings.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests;
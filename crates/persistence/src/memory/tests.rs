@@ -0,0 +1,596 @@
+//! インメモリストアと DB のパリティテスト
+//!
+//! 同じデータを両方に入れ、同じクエリを DB とストアのスコープ内で実行して結果を比べる。
+//! ストア側の実装が SQL とずれるとここで落ちる。
+
+use super::*;
+use crate::Result;
+use crate::connection_pool;
+use crate::evaluation_period::NewEvaluationPeriod;
+use crate::pending_transaction::NewPendingTransaction;
+use crate::portfolio_holding::{NewPortfolioHolding, PortfolioHolding};
+use crate::prediction_record::PredictionRecord;
+use crate::schema::{pool_info, prediction_records, token_rates};
+use crate::token_rate::{SwapPath, SwapPoolInfo};
+use bigdecimal::BigDecimal;
+use chrono::{SubsecRound, TimeDelta};
+use common::config::ConfigResolver;
+use common::types::{
+    ExchangeRate, TimeRange, TokenAccount, TokenInAccount, TokenOutAccount, TokenSmallestUnits,
+    YoctoAmount,
+};
+use dex::PoolInfoBared;
+use diesel::prelude::*;
+use near_sdk::json_types::U128;
+use serial_test::serial;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// 同じ処理を DB とストアの両方で実行し、(DB, ストア) の順に結果を返す
+async fn on_both<T, Fut>(store: &Arc<MemoryStore>, run: impl Fn() -> Fut) -> (T, T)
+where
+    Fut: Future<Output = T>,
+{
+    let db = run().await;
+    let memory = store.scope(run()).await;
+    (db, memory)
+}
+
+fn now() -> NaiveDateTime {
+    chrono::Utc::now().naive_utc().trunc_subsecs(0)
+}
+
+fn token(name: &str) -> TokenOutAccount {
+    TokenAccount::from_str(name).unwrap().into()
+}
+
+fn wnear() -> TokenAccount {
+    TokenAccount::from_str("wrap.near").unwrap()
+}
+
+fn rate(
+    base: &TokenOutAccount,
+    quote: &TokenInAccount,
+    raw: &str,
+    decimals: u8,
+    timestamp: NaiveDateTime,
+    swap_path: Option<SwapPath>,
+) -> TokenRate {
+    TokenRate {
+        base: base.clone(),
+        quote: quote.clone(),
+        exchange_rate: ExchangeRate::from_raw_rate(BigDecimal::from_str(raw).unwrap(), decimals),
+        timestamp,
+        rate_calc_near: 10,
+        swap_path,
+    }
+}
+
+type RateKey = (
+    String,
+    String,
+    BigDecimal,
+    u8,
+    NaiveDateTime,
+    Option<SwapPath>,
+);
+
+fn rate_key(rate: &TokenRate) -> RateKey {
+    (
+        rate.base.to_string(),
+        rate.quote.to_string(),
+        rate.exchange_rate.raw_rate().clone(),
+        rate.exchange_rate.decimals(),
+        rate.timestamp,
+        rate.swap_path.clone(),
+    )
+}
+
+/// スポットレートは補正で無限小数になり得るので、桁を揃えて比べる
+fn spot_keys<K: Display>(rates: HashMap<K, ExchangeRate>) -> BTreeMap<String, (BigDecimal, u8)> {
+    rates
+        .into_iter()
+        .map(|(token, rate)| {
+            (
+                token.to_string(),
+                (rate.raw_rate().round(20), rate.decimals()),
+            )
+        })
+        .collect()
+}
+
+fn pool(id: u32, amount: u128, timestamp: NaiveDateTime) -> Arc<PoolInfo> {
+    let bare = PoolInfoBared {
+        pool_kind: "SIMPLE_POOL".to_string(),
+        token_account_ids: vec![
+            wnear(),
+            TokenAccount::from_str(&format!("token{id}.near")).unwrap(),
+        ],
+        amounts: vec![U128(amount), U128(amount * 2)],
+        total_fee: 30,
+        shares_total_supply: U128(0),
+        amp: 0,
+    };
+    Arc::new(PoolInfo::new(id, bare, timestamp))
+}
+
+fn pool_keys(pools: &PoolInfoList) -> Vec<(u32, NaiveDateTime, Vec<U128>)> {
+    pools
+        .list()
+        .iter()
+        .map(|pool| (pool.id, pool.timestamp, pool.bare.amounts.clone()))
+        .collect()
+}
+
+fn prediction_keys(
+    records: &[DbPredictionRecord],
+) -> Vec<(i32, String, BigDecimal, NaiveDateTime, NaiveDateTime)> {
+    records
+        .iter()
+        .map(|r| {
+            (
+                r.id,
+                r.token.clone(),
+                r.predicted_price.clone(),
+                r.data_cutoff_time,
+                r.target_time,
+            )
+        })
+        .collect()
+}
+
+async fn load_predictions() -> Result<Vec<DbPredictionRecord>> {
+    let rows = connection_pool::get()
+        .await?
+        .interact(|conn| {
+            prediction_records::table
+                .select(DbPredictionRecord::as_select())
+                .load(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interaction error: {:?}", e))??;
+    Ok(rows)
+}
+
+#[tokio::test]
+#[serial]
+async fn test_token_rate_queries_match_db() -> Result<()> {
+    connection_pool::get()
+        .await?
+        .interact(|conn| diesel::delete(token_rates::table).execute(conn))
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interaction error: {:?}", e))??;
+
+    let now = now();
+    let at = |hours: i64| now - TimeDelta::hours(hours);
+    let (a, b, c) = (token("a.near"), token("b.near"), token("c.near"));
+    let quote: TokenInAccount = wnear().into();
+    let other_quote: TokenInAccount = TokenAccount::from_str("usdt.tether-token.near")
+        .unwrap()
+        .into();
+    let path = SwapPath {
+        pools: vec![SwapPoolInfo {
+            pool_id: 1,
+            token_in_idx: 0,
+            token_out_idx: 1,
+            amount_in: TokenSmallestUnits::from_u128(10_u128.pow(27)),
+            amount_out: TokenSmallestUnits::from_u128(10_u128.pow(30)),
+        }],
+    };
+    let rates = vec![
+        rate(&a, &quote, "1000", 24, at(6), None),
+        rate(&a, &quote, "1100", 24, at(5), None),
+        rate(&a, &quote, "900", 18, at(4), None),
+        // quote が違うレートは decimals 以外では見えない
+        rate(&a, &other_quote, "5", 6, at(3), None),
+        rate(&b, &quote, "2000", 24, at(6), Some(path.clone())),
+        rate(&b, &quote, "2020", 24, at(5), None),
+        rate(&b, &quote, "1990", 24, at(2), None),
+        // 2 件しかないのでボラティリティの対象外
+        rate(&c, &quote, "300", 24, at(5), None),
+        rate(&c, &quote, "310", 24, at(1), Some(path)),
+    ];
+    TokenRate::batch_insert(&rates, &ConfigResolver).await?;
+    let store = MemoryStore::new();
+    store.insert_token_rates(&rates);
+
+    for base in [&a, &b, &c, &token("missing.near")] {
+        let (db, memory) = on_both(&store, || TokenRate::get_latest(base, &quote)).await;
+        assert_eq!(db?.as_ref().map(rate_key), memory?.as_ref().map(rate_key));
+    }
+
+    let range = TimeRange {
+        start: at(5),
+        end: at(2),
+    };
+    let (db, memory) = on_both(&store, || {
+        TokenRate::get_rates_in_time_range(&range, &a, &quote)
+    })
+    .await;
+    let keys = |rates: Vec<TokenRate>| rates.iter().map(rate_key).collect::<Vec<_>>();
+    assert_eq!(keys(db?), keys(memory?));
+
+    let tokens = [a.clone(), b.clone(), c.clone()];
+    let (db, memory) = on_both(&store, || {
+        TokenRate::get_rates_for_multiple_tokens(&tokens, &quote, &range)
+    })
+    .await;
+    let by_token = |rates: HashMap<TokenOutAccount, Vec<TokenRate>>| {
+        rates
+            .into_iter()
+            .map(|(token, rates)| (token.to_string(), keys(rates)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    assert_eq!(by_token(db?), by_token(memory?));
+
+    let wide = TimeRange {
+        start: at(7),
+        end: now,
+    };
+    let (db, memory) = on_both(&store, || {
+        TokenRate::get_by_volatility_in_time_range(&wide, &quote)
+    })
+    .await;
+    let (db, memory) = (db?, memory?);
+    assert_eq!(db.len(), 2);
+    for (db, memory) in db.iter().zip(&memory) {
+        assert_eq!(db.base, memory.base);
+        assert_eq!(
+            db.coefficient_of_variation.round(10),
+            memory.coefficient_of_variation.round(10)
+        );
+    }
+    assert_eq!(db.len(), memory.len());
+
+    for at_or_before in [at(5), at(3), now] {
+        let (db, memory) = on_both(&store, || {
+            TokenRate::get_spot_rates_at_time(&tokens, &quote, at_or_before)
+        })
+        .await;
+        assert_eq!(spot_keys(db?), spot_keys(memory?));
+    }
+
+    let wnear = wnear();
+    let (db, memory) = on_both(&store, || crate::token_rate::get_all_latest_rates(&wnear)).await;
+    assert_eq!(spot_keys(db?), spot_keys(memory?));
+
+    let (db, memory) = on_both(&store, crate::token_rate::get_all_decimals).await;
+    assert_eq!(
+        db?.into_iter().collect::<BTreeMap<_, _>>(),
+        memory?.into_iter().collect::<BTreeMap<_, _>>()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial(pool_info)]
+async fn test_pool_queries_match_db() -> Result<()> {
+    connection_pool::get()
+        .await?
+        .interact(|conn| diesel::delete(pool_info::table).execute(conn))
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interaction error: {:?}", e))??;
+
+    let now = now();
+    let at = |hours: i64| now - TimeDelta::hours(hours);
+    let snapshots = [
+        PoolInfoList::new(vec![
+            pool(0, 100, at(3)),
+            pool(1, 200, at(3)),
+            pool(2, 300, at(3)),
+        ]),
+        // pool 2 が更新されないスナップショット
+        PoolInfoList::new(vec![pool(0, 110, at(2)), pool(1, 210, at(2))]),
+        PoolInfoList::new(vec![
+            pool(0, 120, at(1)),
+            pool(1, 220, at(1)),
+            pool(2, 320, at(1)),
+        ]),
+    ];
+    let store = MemoryStore::new();
+    for pools in &snapshots {
+        crate::pool_info::write_to_db(pools, &ConfigResolver).await?;
+        store.insert_pools(pools);
+    }
+
+    let half_hour = TimeDelta::minutes(30);
+    for timestamp in [
+        Some(at(4)),
+        Some(at(3)),
+        Some(at(2)),
+        Some(at(2) + half_hour),
+        Some(at(1)),
+        Some(now),
+        None,
+    ] {
+        let (db, memory) = on_both(&store, || crate::pool_info::read_from_db(timestamp)).await;
+        assert_eq!(
+            db.as_deref().map(pool_keys).ok(),
+            memory.as_deref().map(pool_keys).ok(),
+            "timestamp = {timestamp:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_prediction_queries_match_db() -> Result<()> {
+    connection_pool::get()
+        .await?
+        .interact(|conn| diesel::delete(prediction_records::table).execute(conn))
+        .await
+        .map_err(|e| anyhow::anyhow!("Database interaction error: {:?}", e))??;
+
+    let now = now();
+    let later = |hours: i64| now + TimeDelta::hours(hours);
+    let prediction = |token: &str, price: i64, cutoff: NaiveDateTime, target: NaiveDateTime| {
+        NewPredictionRecord {
+            token: token.to_string(),
+            quote_token: "wrap.near".to_string(),
+            predicted_price: BigDecimal::from(price),
+            data_cutoff_time: cutoff,
+            target_time: target,
+        }
+    };
+    PredictionRecord::batch_insert(&[
+        prediction("a.near", 1, now, later(24)),
+        prediction("a.near", 2, now, later(48)),
+        // 対象時刻が同じなら data_cutoff_time が新しい方
+        prediction("b.near", 3, now - TimeDelta::hours(1), later(24)),
+        prediction("b.near", 4, now, later(24)),
+        // 対象時刻が過ぎている
+        prediction(
+            "c.near",
+            5,
+            now - TimeDelta::hours(48),
+            now - TimeDelta::hours(24),
+        ),
+        prediction(
+            "c.near",
+            6,
+            now - TimeDelta::hours(72),
+            now - TimeDelta::hours(48),
+        ),
+    ])
+    .await?;
+
+    // 評価済みの行も作り、DB の行をそのままストアに写す
+    for row in load_predictions()
+        .await?
+        .iter()
+        .filter(|r| r.token == "c.near")
+    {
+        PredictionRecord::update_evaluation(row.id, BigDecimal::from(5), 1.0, BigDecimal::from(1))
+            .await?;
+    }
+    let store = MemoryStore::new();
+    store
+        .tables()
+        .prediction_records
+        .extend(load_predictions().await?);
+
+    let tokens = [token("a.near"), token("b.near"), token("c.near")];
+    for as_of in [
+        now - TimeDelta::hours(1),
+        now + TimeDelta::minutes(1),
+        later(30),
+    ] {
+        let (db, memory) = on_both(&store, || {
+            PredictionRecord::get_latest_fresh_predictions(&tokens, as_of)
+        })
+        .await;
+        assert_eq!(
+            prediction_keys(&db?),
+            prediction_keys(&memory?),
+            "as_of = {as_of}"
+        );
+    }
+
+    for limit in [1, 10] {
+        let (db, memory) = on_both(&store, || {
+            PredictionRecord::get_recent_evaluated_for_tokens(limit, &tokens)
+        })
+        .await;
+        assert_eq!(prediction_keys(&db?), prediction_keys(&memory?));
+    }
+
+    Ok(())
+}
+
+/// 戦略サイクル 1 回分の書き込みと読み出しを比較できる形で記録したもの
+#[derive(Debug, PartialEq)]
+struct CycleRecord {
+    latest_period: Option<String>,
+    selected_tokens: Option<Vec<Option<String>>>,
+    update_missing_fails: bool,
+    sent: (String, Option<String>),
+    resend_fails: bool,
+    completed: String,
+    completed_again: String,
+    duplicate_insert_fails: bool,
+    count: i64,
+    transactions: Vec<(String, BigDecimal, Option<BigDecimal>, Option<i64>)>,
+    gas: (Option<BigDecimal>, Option<BigDecimal>),
+    latest_holding: Option<(NaiveDateTime, serde_json::Value)>,
+}
+
+async fn record_cycle(prefix: String, period_id: String, batch_id: String) -> Result<CycleRecord> {
+    let now = now();
+    NewEvaluationPeriod {
+        period_id: period_id.clone(),
+        start_time: now,
+        initial_value: YoctoAmount::from_u128(100_000_000_000_000_000_000_000_000),
+        selected_tokens: None,
+    }
+    .insert_async()
+    .await?;
+    let latest_period = EvaluationPeriod::get_latest_with_prefix_async(prefix)
+        .await?
+        .map(|period| period.period_id);
+    let selected_tokens =
+        EvaluationPeriod::update_selected_tokens_async(period_id.clone(), vec!["a.near".into()])
+            .await?
+            .selected_tokens;
+    let update_missing_fails =
+        EvaluationPeriod::update_selected_tokens_async(format!("{period_id}_missing"), vec![])
+            .await
+            .is_err();
+
+    let intent = NewPendingTransaction {
+        trade_batch_id: batch_id.clone(),
+        evaluation_period_id: period_id.clone(),
+        sender_account: "test.near".to_string(),
+        from_token: "wrap.near".to_string(),
+        from_amount: TokenSmallestUnits::from_u128(1_000),
+        to_token: "a.near".to_string(),
+        estimated_to_amount: TokenSmallestUnits::from_u128(2_000),
+    };
+    let entry = intent.clone().insert_async().await?;
+    let tx_id = format!("{batch_id}_tx1");
+    let sent =
+        PendingTransaction::mark_sent_async(entry.id, "hash".to_string(), tx_id.clone()).await?;
+    let resend_fails =
+        PendingTransaction::mark_sent_async(entry.id, "hash".to_string(), tx_id.clone())
+            .await
+            .is_err();
+    let transaction = sent
+        .to_trade_transaction(Some(BigDecimal::from(1_990)))?
+        .with_gas(1_000, 100);
+    let completed = PendingTransaction::complete_async(entry.id, transaction.clone()).await?;
+    let completed_again = PendingTransaction::complete_async(entry.id, transaction).await?;
+    let voided = intent.insert_async().await?;
+    PendingTransaction::void_async(voided.id, "failed".to_string()).await?;
+
+    let direct = TradeTransaction {
+        tx_id: format!("{batch_id}_tx2"),
+        trade_batch_id: batch_id.clone(),
+        from_token: "a.near".to_string(),
+        from_amount: TokenSmallestUnits::from_u128(500),
+        to_token: "wrap.near".to_string(),
+        to_amount: TokenSmallestUnits::from_u128(250),
+        timestamp: now + TimeDelta::seconds(1),
+        evaluation_period_id: period_id.clone(),
+        actual_to_amount: None,
+        gas_burnt: None,
+        gas_cost: None,
+    };
+    direct.clone().insert_async().await?;
+    let duplicate_insert_fails = direct.insert_async().await.is_err();
+
+    let count = TradeTransaction::count_by_evaluation_period_async(period_id.clone()).await?;
+    let transactions = TradeTransaction::find_by_evaluation_period_async(period_id.clone())
+        .await?
+        .into_iter()
+        .map(|tx| {
+            (
+                tx.tx_id,
+                tx.from_amount.as_bigdecimal().clone(),
+                tx.actual_to_amount,
+                tx.gas_burnt,
+            )
+        })
+        .collect();
+    let gas = TradeTransaction::gas_by_batch_async(batch_id).await?;
+
+    for (hours_ago, amount) in [(2, "1"), (1, "2")] {
+        PortfolioHolding::insert_async(NewPortfolioHolding {
+            evaluation_period_id: period_id.clone(),
+            timestamp: now - TimeDelta::hours(hours_ago),
+            token_holdings: serde_json::json!([{ "token": "a.near", "balance": amount }]),
+            token_pnl: None,
+        })
+        .await?;
+    }
+    let latest_holding = PortfolioHolding::get_latest_for_period_async(period_id)
+        .await?
+        .map(|holding| (holding.timestamp, holding.token_holdings));
+
+    Ok(CycleRecord {
+        latest_period,
+        selected_tokens,
+        update_missing_fails,
+        sent: (sent.status, sent.tx_id),
+        resend_fails,
+        completed: completed.tx_id,
+        completed_again: completed_again.tx_id,
+        duplicate_insert_fails,
+        count,
+        transactions,
+        gas,
+        latest_holding,
+    })
+}
+
+#[tokio::test]
+#[serial(evaluation_period, portfolio_holding)]
+async fn test_cycle_writes_match_db() -> Result<()> {
+    let prefix = format!("parity_{}_", uuid::Uuid::new_v4().simple());
+    let period_id = format!("{prefix}period");
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let store = MemoryStore::new();
+
+    let (db, memory) = on_both(&store, || {
+        record_cycle(prefix.clone(), period_id.clone(), batch_id.clone())
+    })
+    .await;
+    EvaluationPeriod::delete_by_period_id_async(period_id).await?;
+
+    let db = db?;
+    assert_eq!(db.count, 2);
+    assert_eq!(db, memory?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_propagate_carries_store_into_spawned_task() {
+    let store = MemoryStore::new();
+    store
+        .scope(async {
+            tokio::spawn(propagate(
+                NewEvaluationPeriod::with_prefix("mem_", YoctoAmount::zero(), vec![])
+                    .insert_async(),
+            ))
+            .await
+            .unwrap()
+            .unwrap();
+        })
+        .await;
+    assert_eq!(store.evaluation_periods().len(), 1);
+}
+
+#[test]
+fn test_cleanup_cascades_to_child_rows() {
+    let store = MemoryStore::new();
+    let mut tables = store.tables();
+    let now = chrono::Utc::now().naive_utc();
+    for (period_id, created_at) in [("old", now - TimeDelta::days(60)), ("new", now)] {
+        let id = tables.next_id();
+        tables.evaluation_periods.push(EvaluationPeriod {
+            id,
+            period_id: period_id.to_string(),
+            start_time: created_at,
+            initial_value: YoctoAmount::zero(),
+            selected_tokens: None,
+            created_at,
+        });
+        tables.portfolio_holdings.push(DbPortfolioHolding {
+            id,
+            evaluation_period_id: period_id.to_string(),
+            timestamp: created_at,
+            token_holdings: serde_json::json!([]),
+            created_at,
+            token_pnl: None,
+        });
+    }
+
+    let deleted = tables.delete_evaluation_periods_created_before(now - TimeDelta::days(30));
+
+    assert_eq!(deleted, 1);
+    assert_eq!(tables.evaluation_periods[0].period_id, "new");
+    assert_eq!(tables.portfolio_holdings.len(), 1);
+    assert_eq!(tables.portfolio_holdings[0].evaluation_period_id, "new");
+}
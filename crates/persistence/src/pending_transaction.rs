@@ -0,0 +1,451 @@
+//! スワップの write-ahead journal
+//!
+//! `exec_contract` の前に intent を記録し、ブロードキャスト直後に tx hash を記録する。
+//! `trade_transactions` への記録と journal の完了は [`PendingTransaction::complete_async`] で
+//! 1 トランザクションにまとめて行う。プロセスが途中で落ちた場合は、reconciler が
+//! 未解決の行を on-chain の結果で確定させる。
+
+use crate::connection_pool;
+#[cfg(any(test, feature = "test-helpers"))]
+use crate::memory::Tables;
+use crate::schema::{pending_transactions, trade_transactions};
+use crate::trade_transaction::TradeTransaction;
+use anyhow::{Context, Result};
+use bigdecimal::BigDecimal;
+use chrono::NaiveDateTime;
+use common::types::TokenSmallestUnits;
+use diesel::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// journal エントリの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingTransactionStatus {
+    /// 送信前に記録した意図（tx hash 未取得）
+    Intent,
+    /// ブロードキャスト済み（tx hash 取得済み、結果未確定）
+    Sent,
+    /// `trade_transactions` に記録済み
+    Completed,
+    /// 取引は成立しなかった（または結果を確認できなかった）
+    Voided,
+}
+
+impl PendingTransactionStatus {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Intent => "intent",
+            Self::Sent => "sent",
+            Self::Completed => "completed",
+            Self::Voided => "voided",
+        }
+    }
+
+    /// 未解決の状態
+    pub const UNRESOLVED: [Self; 2] = [Self::Intent, Self::Sent];
+}
+
+impl fmt::Display for PendingTransactionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// SYNC: accepted values must match the CHECK constraint in
+// migrations/2026-10-19-000000_create_pending_transactions/up.sql
+impl FromStr for PendingTransactionStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "intent" => Ok(Self::Intent),
+            "sent" => Ok(Self::Sent),
+            "completed" => Ok(Self::Completed),
+            "voided" => Ok(Self::Voided),
+            other => Err(anyhow::anyhow!(
+                "invalid pending transaction status: {}",
+                other
+            )),
+        }
+    }
+}
+
+fn unresolved_status_strs() -> [&'static str; 2] {
+    PendingTransactionStatus::UNRESOLVED.map(PendingTransactionStatus::as_str)
+}
+
+/// インメモリストアの未解決エントリを `status` で閉じる（解決済みなら何もしない）
+#[cfg(any(test, feature = "test-helpers"))]
+fn resolve_in_memory(
+    tables: &mut Tables,
+    id: i32,
+    status: PendingTransactionStatus,
+    error: Option<String>,
+) {
+    let unresolved = unresolved_status_strs();
+    if let Some(entry) = tables
+        .pending_transactions
+        .iter_mut()
+        .find(|entry| entry.id == id && unresolved.contains(&entry.status.as_str()))
+    {
+        entry.status = status.as_str().to_string();
+        entry.resolved_at = Some(chrono::Utc::now().naive_utc());
+        if error.is_some() {
+            entry.error = error;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = pending_transactions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PendingTransaction {
+    pub id: i32,
+    pub trade_batch_id: String,
+    pub evaluation_period_id: String,
+    pub sender_account: String,
+    pub from_token: String,
+    #[diesel(deserialize_as = BigDecimal)]
+    pub from_amount: TokenSmallestUnits,
+    pub to_token: String,
+    #[diesel(deserialize_as = BigDecimal)]
+    pub estimated_to_amount: TokenSmallestUnits,
+    pub tx_hash: Option<String>,
+    pub tx_id: Option<String>,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub error: Option<String>,
+}
+
+/// 送信前に記録する intent（status = intent で挿入される）
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = pending_transactions)]
+pub struct NewPendingTransaction {
+    pub trade_batch_id: String,
+    pub evaluation_period_id: String,
+    pub sender_account: String,
+    pub from_token: String,
+    #[diesel(serialize_as = BigDecimal)]
+    pub from_amount: TokenSmallestUnits,
+    pub to_token: String,
+    #[diesel(serialize_as = BigDecimal)]
+    pub estimated_to_amount: TokenSmallestUnits,
+}
+
+impl NewPendingTransaction {
+    pub fn insert(self, conn: &mut PgConnection) -> QueryResult<PendingTransaction> {
+        diesel::insert_into(pending_transactions::table)
+            .values((
+                self,
+                pending_transactions::status.eq(PendingTransactionStatus::Intent.as_str()),
+            ))
+            .returning(PendingTransaction::as_returning())
+            .get_result(conn)
+    }
+
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "pending_transactions", db.operation = "insert")
+    )]
+    pub async fn insert_async(self) -> Result<PendingTransaction> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            let entry = PendingTransaction {
+                id: tables.next_id(),
+                trade_batch_id: self.trade_batch_id,
+                evaluation_period_id: self.evaluation_period_id,
+                sender_account: self.sender_account,
+                from_token: self.from_token,
+                from_amount: self.from_amount,
+                to_token: self.to_token,
+                estimated_to_amount: self.estimated_to_amount,
+                tx_hash: None,
+                tx_id: None,
+                status: PendingTransactionStatus::Intent.as_str().to_string(),
+                created_at: chrono::Utc::now().naive_utc(),
+                resolved_at: None,
+                error: None,
+            };
+            tables.pending_transactions.push(entry.clone());
+            return Ok(entry);
+        }
+
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| self.insert(conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to insert pending transaction")
+    }
+}
+
+impl PendingTransaction {
+    pub fn status(&self) -> Result<PendingTransactionStatus> {
+        self.status.parse()
+    }
+
+    /// このエントリに対応する `trade_transactions` の行を作る
+    ///
+    /// `to_amount` には送信時点の理論出力を、`actual_to_amount` には on-chain の実績を入れる。
+    /// tx_id が未記録（intent のまま）の場合はエラー。
+    pub fn to_trade_transaction(
+        &self,
+        actual_to_amount: Option<BigDecimal>,
+    ) -> Result<TradeTransaction> {
+        let tx_id = self
+            .tx_id
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("pending transaction {} has no tx_id", self.id))?;
+        Ok(TradeTransaction {
+            tx_id,
+            trade_batch_id: self.trade_batch_id.clone(),
+            from_token: self.from_token.clone(),
+            from_amount: self.from_amount.clone(),
+            to_token: self.to_token.clone(),
+            to_amount: self.estimated_to_amount.clone(),
+            timestamp: chrono::Utc::now().naive_utc(),
+            evaluation_period_id: self.evaluation_period_id.clone(),
+            actual_to_amount,
+            gas_burnt: None,
+            gas_cost: None,
+        })
+    }
+
+    /// ブロードキャスト結果の tx hash を記録する（intent → sent）
+    ///
+    /// `tx_id` は完了時に `trade_transactions.tx_id` として使う識別子。
+    /// 更新後のエントリを返す。
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "pending_transactions", db.operation = "update")
+    )]
+    pub async fn mark_sent_async(
+        id: i32,
+        tx_hash: String,
+        tx_id: String,
+    ) -> Result<PendingTransaction> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            let entry = tables
+                .pending_transactions
+                .iter_mut()
+                .find(|entry| {
+                    entry.id == id && entry.status == PendingTransactionStatus::Intent.as_str()
+                })
+                .ok_or_else(|| {
+                    anyhow::anyhow!("pending transaction {} is not in intent state", id)
+                })?;
+            entry.tx_hash = Some(tx_hash);
+            entry.tx_id = Some(tx_id);
+            entry.status = PendingTransactionStatus::Sent.as_str().to_string();
+            return Ok(entry.clone());
+        }
+
+        let conn = connection_pool::get().await?;
+
+        let updated = conn
+            .interact(move |conn| {
+                diesel::update(
+                    pending_transactions::table
+                        .filter(pending_transactions::id.eq(id))
+                        .filter(
+                            pending_transactions::status
+                                .eq(PendingTransactionStatus::Intent.as_str()),
+                        ),
+                )
+                .set((
+                    pending_transactions::tx_hash.eq(tx_hash),
+                    pending_transactions::tx_id.eq(tx_id),
+                    pending_transactions::status.eq(PendingTransactionStatus::Sent.as_str()),
+                ))
+                .returning(PendingTransaction::as_returning())
+                .get_result(conn)
+                .optional()
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+            .context("Failed to mark pending transaction as sent")?;
+
+        updated.ok_or_else(|| anyhow::anyhow!("pending transaction {} is not in intent state", id))
+    }
+
+    /// 取引を `trade_transactions` に記録し、エントリを completed にする
+    ///
+    /// 両者は同一 DB トランザクションで行う。同じ tx_id の行が既にある場合は挿入せず
+    /// 既存行を返す（reconciler の再実行に備えた冪等性）。
+    pub fn complete(
+        id: i32,
+        transaction: TradeTransaction,
+        conn: &mut PgConnection,
+    ) -> QueryResult<TradeTransaction> {
+        conn.transaction(|conn| {
+            let tx_id = transaction.tx_id.clone();
+            diesel::insert_into(trade_transactions::table)
+                .values(transaction)
+                .on_conflict(trade_transactions::tx_id)
+                .do_nothing()
+                .execute(conn)?;
+
+            diesel::update(
+                pending_transactions::table
+                    .filter(pending_transactions::id.eq(id))
+                    .filter(pending_transactions::status.eq_any(unresolved_status_strs())),
+            )
+            .set((
+                pending_transactions::status.eq(PendingTransactionStatus::Completed.as_str()),
+                pending_transactions::resolved_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+
+            trade_transactions::table
+                .filter(trade_transactions::tx_id.eq(&tx_id))
+                .first(conn)
+        })
+    }
+
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "pending_transactions", db.operation = "complete")
+    )]
+    pub async fn complete_async(
+        id: i32,
+        transaction: TradeTransaction,
+    ) -> Result<TradeTransaction> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            let recorded = match tables
+                .trade_transactions
+                .iter()
+                .position(|tx| tx.tx_id == transaction.tx_id)
+            {
+                Some(index) => tables.trade_transactions[index].clone(),
+                None => {
+                    tables.trade_transactions.push(transaction.clone());
+                    transaction
+                }
+            };
+            resolve_in_memory(&mut tables, id, PendingTransactionStatus::Completed, None);
+            return Ok(recorded);
+        }
+
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| Self::complete(id, transaction, conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to complete pending transaction")
+    }
+
+    /// 取引が成立しなかったものとしてエントリを閉じる
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "pending_transactions", db.operation = "update")
+    )]
+    pub async fn void_async(id: i32, reason: String) -> Result<()> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            resolve_in_memory(
+                &mut tables,
+                id,
+                PendingTransactionStatus::Voided,
+                Some(reason),
+            );
+            return Ok(());
+        }
+
+        let conn = connection_pool::get().await?;
+
+        conn.interact(move |conn| {
+            diesel::update(
+                pending_transactions::table
+                    .filter(pending_transactions::id.eq(id))
+                    .filter(pending_transactions::status.eq_any(unresolved_status_strs())),
+            )
+            .set((
+                pending_transactions::status.eq(PendingTransactionStatus::Voided.as_str()),
+                pending_transactions::resolved_at.eq(chrono::Utc::now().naive_utc()),
+                pending_transactions::error.eq(reason),
+            ))
+            .execute(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+        .context("Failed to void pending transaction")?;
+
+        Ok(())
+    }
+
+    /// 未解決（intent / sent）のエントリを古い順に取得
+    pub async fn list_unresolved_async() -> Result<Vec<PendingTransaction>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(|conn| {
+                pending_transactions::table
+                    .filter(pending_transactions::status.eq_any(unresolved_status_strs()))
+                    .order((
+                        pending_transactions::created_at.asc(),
+                        pending_transactions::id.asc(),
+                    ))
+                    .select(PendingTransaction::as_select())
+                    .load(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to list unresolved pending transactions")
+    }
+
+    /// id で取得
+    pub async fn get_by_id_async(id: i32) -> Result<Option<PendingTransaction>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                pending_transactions::table
+                    .filter(pending_transactions::id.eq(id))
+                    .select(PendingTransaction::as_select())
+                    .first(conn)
+                    .optional()
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to get pending transaction by id")
+    }
+
+    /// 指定バッチのエントリを削除（テスト専用）
+    #[cfg(any(test, feature = "mock"))]
+    pub async fn delete_by_batch_id_async(batch_id: String) -> Result<()> {
+        let conn = connection_pool::get().await?;
+
+        conn.interact(move |conn| {
+            diesel::delete(
+                pending_transactions::table
+                    .filter(pending_transactions::trade_batch_id.eq(&batch_id)),
+            )
+            .execute(conn)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?
+        .map_err(|e| anyhow::anyhow!("Failed to delete pending transactions: {}", e))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
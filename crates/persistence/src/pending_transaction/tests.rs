@@ -0,0 +1,166 @@
+use super::*;
+use futures::FutureExt;
+use std::panic::AssertUnwindSafe;
+
+async fn create_test_evaluation_period() -> String {
+    use crate::evaluation_period::NewEvaluationPeriod;
+    let new_period = NewEvaluationPeriod::new(
+        common::types::YoctoAmount::from_u128(100_000_000_000_000_000_000_000_000),
+        vec![],
+    );
+    new_period.insert_async().await.unwrap().period_id
+}
+
+async fn delete_test_evaluation_period(period_id: String) {
+    let _ = crate::evaluation_period::EvaluationPeriod::delete_by_period_id_async(period_id).await;
+}
+
+fn new_intent(batch_id: &str, period_id: &str) -> NewPendingTransaction {
+    NewPendingTransaction {
+        trade_batch_id: batch_id.to_string(),
+        evaluation_period_id: period_id.to_string(),
+        sender_account: "test.near".to_string(),
+        from_token: "wrap.near".to_string(),
+        from_amount: TokenSmallestUnits::from_u128(1_000_000_000_000_000_000_000_000),
+        to_token: "akaia.tkn.near".to_string(),
+        estimated_to_amount: TokenSmallestUnits::from_u128(50_000_000_000_000_000_000_000),
+    }
+}
+
+#[test]
+fn test_status_round_trip() {
+    for status in [
+        PendingTransactionStatus::Intent,
+        PendingTransactionStatus::Sent,
+        PendingTransactionStatus::Completed,
+        PendingTransactionStatus::Voided,
+    ] {
+        assert_eq!(
+            status.as_str().parse::<PendingTransactionStatus>().unwrap(),
+            status
+        );
+    }
+    assert!("failed".parse::<PendingTransactionStatus>().is_err());
+}
+
+// --- DB integration tests ---
+
+#[tokio::test]
+async fn test_intent_sent_complete() {
+    let period_id = create_test_evaluation_period().await;
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let tx_id = format!("test_tx_{}", uuid::Uuid::new_v4());
+
+    let result = AssertUnwindSafe(async {
+        let entry = new_intent(&batch_id, &period_id)
+            .insert_async()
+            .await
+            .unwrap();
+        assert_eq!(entry.status().unwrap(), PendingTransactionStatus::Intent);
+        assert!(entry.tx_hash.is_none());
+        // tx_id が無い間は trade_transactions の行を作れない
+        assert!(entry.to_trade_transaction(None).is_err());
+
+        let sent = PendingTransaction::mark_sent_async(entry.id, "hash".to_string(), tx_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(sent.status().unwrap(), PendingTransactionStatus::Sent);
+        assert_eq!(sent.tx_id.as_deref(), Some(tx_id.as_str()));
+        // sent になったエントリを再度 sent にはできない
+        assert!(
+            PendingTransaction::mark_sent_async(entry.id, "hash".to_string(), tx_id.clone())
+                .await
+                .is_err()
+        );
+
+        assert!(
+            PendingTransaction::list_unresolved_async()
+                .await
+                .unwrap()
+                .iter()
+                .any(|p| p.id == entry.id)
+        );
+
+        let actual = BigDecimal::from(49_000_000_000_000_000_000_000_u128);
+        let trade = sent.to_trade_transaction(Some(actual.clone())).unwrap();
+        let recorded = PendingTransaction::complete_async(sent.id, trade.clone())
+            .await
+            .unwrap();
+        assert_eq!(recorded.tx_id, tx_id);
+        assert_eq!(recorded.to_amount, sent.estimated_to_amount);
+        assert_eq!(recorded.actual_to_amount, Some(actual));
+
+        // 再実行しても trade_transactions は重複しない
+        let again = PendingTransaction::complete_async(sent.id, trade)
+            .await
+            .unwrap();
+        assert_eq!(again.tx_id, tx_id);
+
+        let completed = PendingTransaction::get_by_id_async(entry.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            completed.status().unwrap(),
+            PendingTransactionStatus::Completed
+        );
+        assert!(completed.resolved_at.is_some());
+        assert!(
+            !PendingTransaction::list_unresolved_async()
+                .await
+                .unwrap()
+                .iter()
+                .any(|p| p.id == entry.id)
+        );
+    })
+    .catch_unwind()
+    .await;
+
+    let _ = TradeTransaction::delete_by_tx_id_async(tx_id).await;
+    let _ = PendingTransaction::delete_by_batch_id_async(batch_id).await;
+    delete_test_evaluation_period(period_id).await;
+
+    if let Err(e) = result {
+        std::panic::resume_unwind(e);
+    }
+}
+
+#[tokio::test]
+async fn test_void() {
+    let period_id = create_test_evaluation_period().await;
+    let batch_id = uuid::Uuid::new_v4().to_string();
+
+    let result = AssertUnwindSafe(async {
+        let entry = new_intent(&batch_id, &period_id)
+            .insert_async()
+            .await
+            .unwrap();
+        PendingTransaction::void_async(entry.id, "swap failed".to_string())
+            .await
+            .unwrap();
+
+        let voided = PendingTransaction::get_by_id_async(entry.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(voided.status().unwrap(), PendingTransactionStatus::Voided);
+        assert_eq!(voided.error.as_deref(), Some("swap failed"));
+        assert!(voided.resolved_at.is_some());
+
+        // 確定済みのエントリは sent に戻せない
+        assert!(
+            PendingTransaction::mark_sent_async(entry.id, "hash".to_string(), "tx".to_string())
+                .await
+                .is_err()
+        );
+    })
+    .catch_unwind()
+    .await;
+
+    let _ = PendingTransaction::delete_by_batch_id_async(batch_id).await;
+    delete_test_evaluation_period(period_id).await;
+
+    if let Err(e) = result {
+        std::panic::resume_unwind(e);
+    }
+}
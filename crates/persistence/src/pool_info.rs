@@ -9,6 +9,8 @@ use dex::{PoolInfo, PoolInfoBared, PoolInfoList};
 use diesel::prelude::*;
 use logging::*;
 use serde_json::Value as JsonValue;
+#[cfg(any(test, feature = "test-helpers"))]
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
 // データベース用モデル
@@ -78,6 +80,11 @@ fn to_new_db(pool: &PoolInfo) -> Result<NewDbPoolInfo> {
 }
 
 /// 複数レコードを一括挿入
+#[tracing::instrument(
+    name = "db",
+    skip_all,
+    fields(db.table = "pool_info", db.operation = "insert")
+)]
 pub async fn batch_insert(pool_infos: &[Arc<PoolInfo>], cfg: &impl ConfigAccess) -> Result<()> {
     let log = DEFAULT.new(o!(
         "function" => "pool_info::batch_insert",
@@ -246,6 +253,34 @@ pub async fn get_all_unique_between(range: TimeRange) -> Result<Vec<PoolInfo>> {
 
 /// DBからPoolInfoListを読み込む
 pub async fn read_from_db(timestamp: Option<NaiveDateTime>) -> Result<Arc<PoolInfoList>> {
+    #[cfg(any(test, feature = "test-helpers"))]
+    if let Some(store) = crate::memory::current() {
+        let tables = store.tables();
+        let first = tables
+            .pool_infos
+            .iter()
+            .filter(|pool| pool.id == 0 && timestamp.is_none_or(|ts| pool.timestamp < ts))
+            .max_by_key(|pool| pool.timestamp)
+            .ok_or_else(|| anyhow!("no pool found"))?;
+        let end = timestamp.unwrap_or(chrono::Utc::now().naive_utc());
+        let mut latest: BTreeMap<u32, &Arc<PoolInfo>> = BTreeMap::new();
+        for pool in tables
+            .pool_infos
+            .iter()
+            .filter(|pool| pool.timestamp >= first.timestamp && pool.timestamp <= end)
+        {
+            match latest.get(&pool.id) {
+                Some(current) if current.timestamp >= pool.timestamp => {}
+                _ => {
+                    latest.insert(pool.id, pool);
+                }
+            }
+        }
+        return Ok(Arc::new(PoolInfoList::new(
+            latest.into_values().cloned().collect(),
+        )));
+    }
+
     let first = if let Some(timestamp) = timestamp {
         get_latest_before(0, timestamp).await?
     } else {
@@ -1,9 +1,10 @@
 use crate::connection_pool;
 use crate::schema::portfolio_holdings;
 use anyhow::Result;
+use bigdecimal::BigDecimal;
 use chrono::NaiveDateTime;
-use common::types::TokenSmallestUnits;
 use common::types::token_account::TokenAccount;
+use common::types::{TokenSmallestUnits, YoctoValue};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +16,20 @@ pub struct TokenHolding {
     pub decimals: u8,
 }
 
+/// JSONB 用の個別トークン損益（評価期間の取引から平均取得原価法で計算）
+///
+/// 金額はすべて yoctoNEAR。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenPnl {
+    pub token: TokenAccount,
+    /// 保有中の数量の取得原価
+    pub cost_basis: YoctoValue,
+    /// 売却済みの数量の実現損益
+    pub realized_pnl: BigDecimal,
+    /// 保有中の数量の評価額 - 取得原価（レートが無い場合は `None`）
+    pub unrealized_pnl: Option<BigDecimal>,
+}
+
 #[derive(Debug, Clone, Queryable, Selectable)]
 #[diesel(table_name = portfolio_holdings)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -25,6 +40,7 @@ pub struct DbPortfolioHolding {
     pub timestamp: NaiveDateTime,
     pub token_holdings: serde_json::Value,
     pub created_at: NaiveDateTime,
+    pub token_pnl: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -33,6 +49,7 @@ pub struct NewPortfolioHolding {
     pub evaluation_period_id: String,
     pub timestamp: NaiveDateTime,
     pub token_holdings: serde_json::Value,
+    pub token_pnl: Option<serde_json::Value>,
 }
 
 impl DbPortfolioHolding {
@@ -41,13 +58,42 @@ impl DbPortfolioHolding {
         serde_json::from_value(self.token_holdings.clone())
             .map_err(|e| anyhow::anyhow!("Failed to parse token_holdings: {}", e))
     }
+
+    /// token_pnl JSONB を TokenPnl の Vec にパース（未記録なら空）
+    pub fn parse_token_pnl(&self) -> Result<Vec<TokenPnl>> {
+        match &self.token_pnl {
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|e| anyhow::anyhow!("Failed to parse token_pnl: {}", e)),
+            None => Ok(vec![]),
+        }
+    }
 }
 
 pub struct PortfolioHolding;
 
 impl PortfolioHolding {
     /// 1件挿入
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "portfolio_holdings", db.operation = "insert")
+    )]
     pub async fn insert_async(record: NewPortfolioHolding) -> Result<()> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            let holding = DbPortfolioHolding {
+                id: tables.next_id(),
+                evaluation_period_id: record.evaluation_period_id,
+                timestamp: record.timestamp,
+                token_holdings: record.token_holdings,
+                created_at: chrono::Utc::now().naive_utc(),
+                token_pnl: record.token_pnl,
+            };
+            tables.portfolio_holdings.push(holding);
+            return Ok(());
+        }
+
         let conn = connection_pool::get().await?;
 
         conn.interact(move |conn| {
@@ -82,6 +128,17 @@ impl PortfolioHolding {
     pub async fn get_latest_for_period_async(
         period_id: String,
     ) -> Result<Option<DbPortfolioHolding>> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let tables = store.tables();
+            let latest = tables
+                .portfolio_holdings
+                .iter()
+                .filter(|holding| holding.evaluation_period_id == period_id)
+                .max_by_key(|holding| holding.timestamp);
+            return Ok(latest.cloned());
+        }
+
         let conn = connection_pool::get().await?;
 
         let result = conn
@@ -97,6 +154,29 @@ impl PortfolioHolding {
 
         Ok(result)
     }
+
+    /// 期間の最初の1件を取得（期間開始直後の保有量）
+    pub async fn get_first_for_period_async(
+        period_id: String,
+    ) -> Result<Option<DbPortfolioHolding>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| {
+                portfolio_holdings::table
+                    .filter(portfolio_holdings::evaluation_period_id.eq(&period_id))
+                    .order_by((
+                        portfolio_holdings::timestamp.asc(),
+                        portfolio_holdings::id.asc(),
+                    ))
+                    .first::<DbPortfolioHolding>(conn)
+                    .optional()
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Database interaction error: {:?}", e))??;
+
+        Ok(result)
+    }
 }
 
 #[cfg(test)]
@@ -58,6 +58,7 @@ async fn test_insert_and_get_by_period() -> Result<()> {
             evaluation_period_id: period_id.clone(),
             timestamp: now - chrono::TimeDelta::seconds(i),
             token_holdings: holdings_json.clone(),
+            token_pnl: None,
         };
         PortfolioHolding::insert_async(record).await?;
     }
@@ -97,6 +98,7 @@ async fn test_get_latest_for_period() -> Result<()> {
         evaluation_period_id: period_id.clone(),
         timestamp: now - chrono::TimeDelta::seconds(10),
         token_holdings: holdings_json.clone(),
+        token_pnl: None,
     };
     PortfolioHolding::insert_async(older).await?;
 
@@ -104,6 +106,7 @@ async fn test_get_latest_for_period() -> Result<()> {
         evaluation_period_id: period_id.clone(),
         timestamp: now,
         token_holdings: holdings_json,
+        token_pnl: None,
     };
     PortfolioHolding::insert_async(newer).await?;
 
@@ -131,6 +134,7 @@ async fn test_insert_with_invalid_period_id() {
         evaluation_period_id: "non_existent_period_id".to_string(),
         timestamp: now,
         token_holdings: holdings_json,
+        token_pnl: None,
     };
 
     // FK constraint should cause an error
@@ -223,6 +227,7 @@ fn test_parse_holdings_valid_jsonb() {
         timestamp: chrono::Utc::now().naive_utc(),
         token_holdings: json,
         created_at: chrono::Utc::now().naive_utc(),
+        token_pnl: None,
     };
 
     let holdings = record.parse_holdings().unwrap();
@@ -240,6 +245,7 @@ fn test_parse_holdings_empty_array() {
         timestamp: chrono::Utc::now().naive_utc(),
         token_holdings: serde_json::json!([]),
         created_at: chrono::Utc::now().naive_utc(),
+        token_pnl: None,
     };
 
     let holdings = record.parse_holdings().unwrap();
@@ -254,6 +260,7 @@ fn test_parse_holdings_invalid_jsonb() {
         timestamp: chrono::Utc::now().naive_utc(),
         token_holdings: serde_json::json!({"not": "an array"}),
         created_at: chrono::Utc::now().naive_utc(),
+        token_pnl: None,
     };
 
     let result = record.parse_holdings();
@@ -273,6 +280,7 @@ fn test_new_portfolio_holding_construction() {
         evaluation_period_id: "eval_123".to_string(),
         timestamp: chrono::Utc::now().naive_utc(),
         token_holdings: json.clone(),
+        token_pnl: None,
     };
 
     assert_eq!(record.evaluation_period_id, "eval_123");
@@ -308,6 +316,7 @@ fn test_parse_holdings_multiple_tokens() {
         timestamp: chrono::Utc::now().naive_utc(),
         token_holdings: json,
         created_at: chrono::Utc::now().naive_utc(),
+        token_pnl: None,
     };
 
     let holdings = record.parse_holdings().unwrap();
@@ -319,3 +328,79 @@ fn test_parse_holdings_multiple_tokens() {
     assert!(tokens.contains(&"wrap.near"));
     assert!(tokens.contains(&"aurora"));
 }
+
+#[tokio::test]
+#[serial(portfolio_holding)]
+async fn test_get_first_for_period() -> Result<()> {
+    let period_id = create_test_evaluation_period().await;
+    let holdings_json = create_test_holdings_json();
+    let now = chrono::Utc::now().naive_utc();
+    let now = now
+        .with_nanosecond(now.nanosecond() / 1_000 * 1_000)
+        .unwrap();
+
+    for i in 0..3 {
+        let record = NewPortfolioHolding {
+            evaluation_period_id: period_id.clone(),
+            timestamp: now - chrono::TimeDelta::seconds(i),
+            token_holdings: holdings_json.clone(),
+            token_pnl: None,
+        };
+        PortfolioHolding::insert_async(record).await?;
+    }
+
+    let first = PortfolioHolding::get_first_for_period_async(period_id.clone()).await?;
+    assert_eq!(
+        first.unwrap().timestamp,
+        now - chrono::TimeDelta::seconds(2)
+    );
+
+    let none =
+        PortfolioHolding::get_first_for_period_async("non_existent_period".to_string()).await?;
+    assert!(none.is_none());
+
+    cleanup_holdings_for_period(&period_id).await;
+    Ok(())
+}
+
+#[test]
+fn test_parse_token_pnl_missing_is_empty() {
+    let record = DbPortfolioHolding {
+        id: 1,
+        evaluation_period_id: "eval_test".to_string(),
+        timestamp: chrono::Utc::now().naive_utc(),
+        token_holdings: serde_json::json!([]),
+        created_at: chrono::Utc::now().naive_utc(),
+        token_pnl: None,
+    };
+
+    assert!(record.parse_token_pnl().unwrap().is_empty());
+}
+
+#[test]
+fn test_parse_token_pnl_roundtrip() {
+    let pnl = vec![
+        TokenPnl {
+            token: "usdc.near".parse().unwrap(),
+            cost_basis: YoctoValue::from_yocto(BigDecimal::from(1_000_000)),
+            realized_pnl: BigDecimal::from(-250),
+            unrealized_pnl: Some(BigDecimal::from(300)),
+        },
+        TokenPnl {
+            token: "aurora".parse().unwrap(),
+            cost_basis: YoctoValue::zero(),
+            realized_pnl: BigDecimal::from(42),
+            unrealized_pnl: None,
+        },
+    ];
+    let record = DbPortfolioHolding {
+        id: 1,
+        evaluation_period_id: "eval_test".to_string(),
+        timestamp: chrono::Utc::now().naive_utc(),
+        token_holdings: serde_json::json!([]),
+        created_at: chrono::Utc::now().naive_utc(),
+        token_pnl: Some(serde_json::to_value(&pnl).unwrap()),
+    };
+
+    assert_eq!(record.parse_token_pnl().unwrap(), pnl);
+}
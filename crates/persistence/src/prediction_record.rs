@@ -6,6 +6,8 @@ use chrono::NaiveDateTime;
 use common::types::{TokenAccount, TokenOutAccount};
 use diesel::prelude::*;
 use logging::*;
+#[cfg(any(test, feature = "test-helpers"))]
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Queryable, Selectable)]
 #[diesel(table_name = prediction_records)]
@@ -42,6 +44,11 @@ mod tests;
 
 impl PredictionRecord {
     /// 予測バッチ挿入
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "prediction_records", db.operation = "insert")
+    )]
     pub async fn batch_insert(records: &[NewPredictionRecord]) -> Result<()> {
         if records.is_empty() {
             return Ok(());
@@ -114,6 +121,11 @@ impl PredictionRecord {
     }
 
     /// 評価結果で更新
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "prediction_records", db.operation = "update")
+    )]
     pub async fn update_evaluation(
         id: i32,
         actual_price: BigDecimal,
@@ -157,6 +169,25 @@ impl PredictionRecord {
         Ok(results)
     }
 
+    /// `target_time` が [`since`, `until`) の評価済みレコードの MAPE を取得
+    pub async fn get_mapes_between(since: NaiveDateTime, until: NaiveDateTime) -> Result<Vec<f64>> {
+        let conn = connection_pool::get().await?;
+
+        let results = conn
+            .interact(move |conn| {
+                prediction_records::table
+                    .filter(prediction_records::target_time.ge(since))
+                    .filter(prediction_records::target_time.lt(until))
+                    .filter(prediction_records::mape.is_not_null())
+                    .select(prediction_records::mape.assume_not_null())
+                    .load::<f64>(conn)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Database interaction error: {:?}", e))??;
+
+        Ok(results)
+    }
+
     /// 指定トークン群の直近評価済みレコードを一括取得
     pub async fn get_recent_evaluated_for_tokens(
         limit: i64,
@@ -166,6 +197,19 @@ impl PredictionRecord {
             return Ok(Vec::new());
         }
         let tokens: Vec<String> = tokens.iter().map(|t| t.to_string()).collect();
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut results: Vec<DbPredictionRecord> = store
+                .tables()
+                .prediction_records
+                .iter()
+                .filter(|r| r.evaluated_at.is_some() && tokens.contains(&r.token))
+                .cloned()
+                .collect();
+            results.sort_by(|a, b| b.target_time.cmp(&a.target_time));
+            results.truncate(usize::try_from(limit).unwrap_or(0));
+            return Ok(results);
+        }
         let conn = connection_pool::get().await?;
         let results = conn
             .interact(move |conn| {
@@ -229,6 +273,24 @@ impl PredictionRecord {
         }
 
         let tokens: Vec<String> = tokens.iter().map(|t| t.to_string()).collect();
+
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut latest: BTreeMap<String, DbPredictionRecord> = BTreeMap::new();
+            for record in store.tables().prediction_records.iter().filter(|r| {
+                tokens.contains(&r.token) && r.created_at <= as_of && r.target_time > as_of
+            }) {
+                let key = |r: &DbPredictionRecord| (r.target_time, r.data_cutoff_time, r.id);
+                match latest.get(&record.token) {
+                    Some(current) if key(current) >= key(record) => {}
+                    _ => {
+                        latest.insert(record.token.clone(), record.clone());
+                    }
+                }
+            }
+            return Ok(latest.into_values().collect());
+        }
+
         let conn = connection_pool::get().await?;
 
         let results = conn
@@ -1,5 +1,22 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    arbitrage_attempts (id) {
+        id -> Int4,
+        attempted_at -> Timestamp,
+        start_token -> Varchar,
+        path -> Jsonb,
+        input_amount -> Numeric,
+        estimated_output -> Numeric,
+        actual_output -> Nullable<Numeric>,
+        gas_burnt -> Nullable<Int8>,
+        gas_cost -> Nullable<Numeric>,
+        tx_hash -> Nullable<Varchar>,
+        status -> Varchar,
+        error -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     authorized_users (id) {
         id -> Int4,
@@ -43,6 +60,61 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    holding_adjustments (id) {
+        id -> Int4,
+        reconciliation_id -> Int4,
+        evaluation_period_id -> Varchar,
+        token -> Varchar,
+        amount -> Numeric,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    holding_reconciliations (id) {
+        id -> Int4,
+        evaluation_period_id -> Varchar,
+        baseline_holding_id -> Int4,
+        checked_at -> Timestamp,
+        discrepancy_count -> Int4,
+        adjusted -> Bool,
+        tokens -> Jsonb,
+    }
+}
+
+diesel::table! {
+    job_runs (id) {
+        id -> Int4,
+        job_name -> Varchar,
+        trigger -> Varchar,
+        scheduled_at -> Nullable<Timestamp>,
+        started_at -> Timestamp,
+        finished_at -> Nullable<Timestamp>,
+        status -> Varchar,
+        error -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    pending_transactions (id) {
+        id -> Int4,
+        trade_batch_id -> Varchar,
+        evaluation_period_id -> Varchar,
+        sender_account -> Varchar,
+        from_token -> Varchar,
+        from_amount -> Numeric,
+        to_token -> Varchar,
+        estimated_to_amount -> Numeric,
+        tx_hash -> Nullable<Varchar>,
+        tx_id -> Nullable<Varchar>,
+        status -> Varchar,
+        created_at -> Timestamp,
+        resolved_at -> Nullable<Timestamp>,
+        error -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     pool_info (id) {
         id -> Int4,
@@ -64,6 +136,7 @@ diesel::table! {
         timestamp -> Timestamp,
         token_holdings -> Jsonb,
         created_at -> Timestamp,
+        token_pnl -> Nullable<Jsonb>,
     }
 }
 
@@ -107,14 +180,24 @@ diesel::table! {
         timestamp -> Timestamp,
         evaluation_period_id -> Varchar,
         actual_to_amount -> Nullable<Numeric>,
+        gas_burnt -> Nullable<Int8>,
+        gas_cost -> Nullable<Numeric>,
     }
 }
 
+diesel::joinable!(holding_adjustments -> holding_reconciliations (reconciliation_id));
+diesel::joinable!(holding_reconciliations -> portfolio_holdings (baseline_holding_id));
+
 diesel::allow_tables_to_appear_in_same_query!(
+    arbitrage_attempts,
     authorized_users,
     config_store,
     config_store_history,
     evaluation_periods,
+    holding_adjustments,
+    holding_reconciliations,
+    job_runs,
+    pending_transactions,
     pool_info,
     portfolio_holdings,
     prediction_records,
@@ -168,6 +168,11 @@ impl TokenRate {
     }
 
     // 複数レコードを一括挿入
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "token_rates", db.operation = "insert")
+    )]
     pub async fn batch_insert(token_rates: &[TokenRate], cfg: &impl ConfigAccess) -> Result<()> {
         let log = DEFAULT.new(o!(
             "function" => "batch_insert",
@@ -261,6 +266,11 @@ impl TokenRate {
         use diesel::QueryDsl;
         use diesel::dsl::max;
 
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            return Ok(memory_rates(&store, quote, |rate| &rate.base == base).pop());
+        }
+
         let base_str = base.to_string();
         let quote_str = quote.to_string();
         let conn = connection_pool::get().await?;
@@ -310,6 +320,13 @@ impl TokenRate {
     ) -> Result<Vec<TokenRate>> {
         use diesel::QueryDsl;
 
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            return Ok(memory_rates(&store, quote, |rate| {
+                &rate.base == base && rate.timestamp > range.start && rate.timestamp <= range.end
+            }));
+        }
+
         let conn = connection_pool::get().await?;
 
         let start = range.start;
@@ -347,6 +364,20 @@ impl TokenRate {
             return Ok(HashMap::new());
         }
 
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let rates = memory_rates(&store, quote, |rate| {
+                tokens.contains(&rate.base)
+                    && rate.timestamp > range.start
+                    && rate.timestamp <= range.end
+            });
+            let mut map: HashMap<TokenOutAccount, Vec<TokenRate>> = HashMap::new();
+            for rate in rates {
+                map.entry(rate.base.clone()).or_default().push(rate);
+            }
+            return Ok(map);
+        }
+
         let conn = connection_pool::get().await?;
 
         let tokens_vec: Vec<String> = tokens.iter().map(|t| t.to_string()).collect();
@@ -404,6 +435,14 @@ impl TokenRate {
         ));
         trace!(log, "start");
 
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let rates = memory_rates(&store, quote, |rate| {
+                rate.timestamp >= range_start && rate.timestamp <= range_end
+            });
+            return Ok(volatility_of(&rates));
+        }
+
         let conn = connection_pool::get().await?;
 
         // SQLクエリを実装してボラティリティを計算
@@ -614,6 +653,14 @@ impl TokenRate {
             return Ok(HashMap::new());
         }
 
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let rates = memory_rates(&store, quote, |rate| {
+                tokens.contains(&rate.base) && rate.timestamp <= at_or_before
+            });
+            return Ok(latest_spot_rates(rates));
+        }
+
         let conn = connection_pool::get().await?;
 
         let tokens_vec: Vec<String> = tokens.iter().map(|t| t.to_string()).collect();
@@ -683,6 +730,78 @@ struct TokenDecimalsRow {
     decimals: i16,
 }
 
+/// インメモリストアから `quote` 建てのレートを条件で絞り、時刻の昇順で返す
+#[cfg(any(test, feature = "test-helpers"))]
+fn memory_rates(
+    store: &crate::memory::MemoryStore,
+    quote: &TokenInAccount,
+    filter: impl Fn(&TokenRate) -> bool,
+) -> Vec<TokenRate> {
+    let mut rates: Vec<TokenRate> = store
+        .tables()
+        .token_rates
+        .iter()
+        .filter(|rate| &rate.quote == quote && filter(rate))
+        .cloned()
+        .collect();
+    rates.sort_by_key(|rate| rate.timestamp);
+    rates
+}
+
+/// 時刻の昇順に並んだ `rates` から、トークンごとの最新スポットレートを求める
+///
+/// 最新行に swap_path が無ければ、swap_path のある最新行のもので補う（SQL の COALESCE と同じ）。
+#[cfg(any(test, feature = "test-helpers"))]
+fn latest_spot_rates(rates: Vec<TokenRate>) -> HashMap<TokenOutAccount, ExchangeRate> {
+    let mut latest: HashMap<TokenOutAccount, TokenRate> = HashMap::new();
+    let mut latest_paths: HashMap<TokenOutAccount, SwapPath> = HashMap::new();
+    for rate in rates {
+        if let Some(path) = &rate.swap_path {
+            latest_paths.insert(rate.base.clone(), path.clone());
+        }
+        latest.insert(rate.base.clone(), rate);
+    }
+    latest
+        .into_iter()
+        .map(|(token, rate)| {
+            let fallback = latest_paths.get(&token);
+            let spot_rate = rate.to_spot_rate_with_fallback(fallback);
+            (token, spot_rate)
+        })
+        .collect()
+}
+
+/// `get_by_volatility_in_time_range` の SQL と同じ集計（変動係数の降順）
+#[cfg(any(test, feature = "test-helpers"))]
+fn volatility_of(rates: &[TokenRate]) -> Vec<TokenVolatility> {
+    let mut by_token: HashMap<&TokenOutAccount, Vec<&BigDecimal>> = HashMap::new();
+    for rate in rates {
+        by_token
+            .entry(&rate.base)
+            .or_default()
+            .push(rate.exchange_rate.raw_rate());
+    }
+    let mut result: Vec<TokenVolatility> = by_token
+        .into_iter()
+        .filter(|(_, values)| values.len() >= 3 && values.iter().all(|v| **v > BigDecimal::zero()))
+        .filter_map(|(token, values)| {
+            let count = BigDecimal::from(values.len() as u64);
+            let mean = values.iter().copied().sum::<BigDecimal>() / &count;
+            let variance = values
+                .iter()
+                .map(|v| (*v - &mean) * (*v - &mean))
+                .sum::<BigDecimal>()
+                / &count;
+            Some(TokenVolatility {
+                base: token.clone().into(),
+                coefficient_of_variation: variance.sqrt()? / mean,
+            })
+        })
+        .collect();
+    result.sort_by(|a, b| b.coefficient_of_variation.cmp(&a.coefficient_of_variation));
+    result
+}
+
 /// 全トークンの最新スポットレートを一括取得（指定 quote_token 建て）
 ///
 /// LATERAL サブクエリで base_token ごとに最新1行だけを取得し、
@@ -694,6 +813,15 @@ struct TokenDecimalsRow {
 pub async fn get_all_latest_rates(
     quote_token: &TokenAccount,
 ) -> Result<HashMap<TokenAccount, ExchangeRate>> {
+    #[cfg(any(test, feature = "test-helpers"))]
+    if let Some(store) = crate::memory::current() {
+        let rates = memory_rates(&store, &quote_token.clone().into(), |_| true);
+        return Ok(latest_spot_rates(rates)
+            .into_iter()
+            .map(|(token, rate)| (token.into(), rate))
+            .collect());
+    }
+
     let conn = connection_pool::get().await?;
     let quote_str = quote_token.to_string();
 
@@ -754,6 +882,16 @@ pub async fn get_all_latest_rates(
 
 /// token_rates テーブルから全トークンの最新 decimals を一括取得
 pub async fn get_all_decimals() -> Result<HashMap<TokenAccount, u8>> {
+    #[cfg(any(test, feature = "test-helpers"))]
+    if let Some(store) = crate::memory::current() {
+        let mut rates = store.tables().token_rates.clone();
+        rates.sort_by_key(|rate| rate.timestamp);
+        return Ok(rates
+            .into_iter()
+            .map(|rate| (rate.base.into(), rate.exchange_rate.decimals()))
+            .collect());
+    }
+
     let conn = connection_pool::get().await?;
 
     let rows: Vec<TokenDecimalsRow> = conn
@@ -26,16 +26,49 @@ pub struct TradeTransaction {
     // Nullable カラムでは deserialize_as/serialize_as が Option と互換しないため
     // BigDecimal を直接使用。呼び出し側で TokenSmallestUnits との変換を行う。
     pub actual_to_amount: Option<BigDecimal>,
+    /// トランザクション全体で燃やされたガス
+    pub gas_burnt: Option<i64>,
+    /// 燃やされたガスの NEAR 換算（yoctoNEAR）
+    pub gas_cost: Option<BigDecimal>,
 }
 
 impl TradeTransaction {
+    /// on-chain で燃やされたガス（`extract_burnt` の値）を記録する
+    pub fn with_gas(self, gas_burnt: u64, gas_cost: u128) -> Self {
+        Self {
+            gas_burnt: i64::try_from(gas_burnt).ok(),
+            gas_cost: Some(BigDecimal::from(gas_cost)),
+            ..self
+        }
+    }
+
     pub fn insert(self, conn: &mut PgConnection) -> QueryResult<TradeTransaction> {
         diesel::insert_into(trade_transactions::table)
             .values(self)
             .get_result(conn)
     }
 
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "trade_transactions", db.operation = "insert")
+    )]
     pub async fn insert_async(self) -> Result<TradeTransaction> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let mut tables = store.tables();
+            if tables
+                .trade_transactions
+                .iter()
+                .any(|tx| tx.tx_id == self.tx_id)
+            {
+                return Err(anyhow::anyhow!("duplicate tx_id: {}", self.tx_id))
+                    .context("Failed to insert trade transaction");
+            }
+            tables.trade_transactions.push(self.clone());
+            return Ok(self);
+        }
+
         let conn = connection_pool::get().await?;
 
         let result = conn
@@ -55,6 +88,11 @@ impl TradeTransaction {
             .get_results(conn)
     }
 
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "trade_transactions", db.operation = "insert")
+    )]
     pub async fn insert_batch_async(transactions: Vec<Self>) -> Result<Vec<TradeTransaction>> {
         let conn = connection_pool::get().await?;
 
@@ -155,6 +193,13 @@ impl TradeTransaction {
     }
 
     pub async fn count_by_evaluation_period_async(period_id: String) -> Result<i64> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let count =
+                memory_transactions(&store, |tx| tx.evaluation_period_id == period_id).len();
+            return Ok(count as i64);
+        }
+
         let conn = connection_pool::get().await?;
 
         let result = conn
@@ -165,6 +210,53 @@ impl TradeTransaction {
         result.context("Failed to count transactions by evaluation period")
     }
 
+    /// 指定したバッチで燃やされたガスとその NEAR 換算（yoctoNEAR）の合計
+    ///
+    /// ガスが記録されていない取引は含まない。
+    pub fn gas_by_batch(
+        batch_id: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<(Option<BigDecimal>, Option<BigDecimal>)> {
+        use diesel::dsl::sum;
+
+        trade_transactions::table
+            .filter(trade_transactions::trade_batch_id.eq(batch_id))
+            .select((
+                sum(trade_transactions::gas_burnt),
+                sum(trade_transactions::gas_cost),
+            ))
+            .first(conn)
+    }
+
+    #[tracing::instrument(
+        name = "db",
+        skip_all,
+        fields(db.table = "trade_transactions", db.operation = "select")
+    )]
+    pub async fn gas_by_batch_async(
+        batch_id: String,
+    ) -> Result<(Option<BigDecimal>, Option<BigDecimal>)> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            let batch = memory_transactions(&store, |tx| tx.trade_batch_id == batch_id);
+            let gas_burnt = batch
+                .iter()
+                .filter_map(|tx| tx.gas_burnt)
+                .map(BigDecimal::from);
+            let gas_cost = batch.iter().filter_map(|tx| tx.gas_cost.clone());
+            return Ok((sum_nullable(gas_burnt), sum_nullable(gas_cost)));
+        }
+
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| Self::gas_by_batch(&batch_id, conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to sum gas by batch ID")
+    }
+
     /// 指定期間内の全取引を取得
     pub fn find_by_date_range(
         start: NaiveDateTime,
@@ -192,6 +284,134 @@ impl TradeTransaction {
 
         result.context("Failed to find transactions by date range")
     }
+
+    /// `end` より前の取引を (timestamp, tx_id) の昇順で `after` の次から最大 `limit` 件取得
+    ///
+    /// 全履歴を一度に読み込まずにページ単位で走査するためのもの。
+    pub fn find_page_before(
+        end: NaiveDateTime,
+        after: Option<(NaiveDateTime, String)>,
+        limit: i64,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<TradeTransaction>> {
+        let mut query = trade_transactions::table
+            .filter(trade_transactions::timestamp.lt(end))
+            .order((
+                trade_transactions::timestamp.asc(),
+                trade_transactions::tx_id.asc(),
+            ))
+            .limit(limit)
+            .into_boxed();
+        if let Some((timestamp, tx_id)) = after {
+            query = query.filter(
+                trade_transactions::timestamp
+                    .gt(timestamp)
+                    .or(trade_transactions::timestamp
+                        .eq(timestamp)
+                        .and(trade_transactions::tx_id.gt(tx_id))),
+            );
+        }
+        query.get_results(conn)
+    }
+
+    /// `end` より前の取引をページ単位で取得（非同期版）
+    pub async fn find_page_before_async(
+        end: NaiveDateTime,
+        after: Option<(NaiveDateTime, String)>,
+        limit: i64,
+    ) -> Result<Vec<TradeTransaction>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| Self::find_page_before(end, after, limit, conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to find transactions page")
+    }
+
+    /// 指定した評価期間の全取引を取得（古い順）
+    pub fn find_by_evaluation_period(
+        period_id: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<TradeTransaction>> {
+        trade_transactions::table
+            .filter(trade_transactions::evaluation_period_id.eq(period_id))
+            .order(trade_transactions::timestamp.asc())
+            .get_results(conn)
+    }
+
+    /// 指定した評価期間の全取引を取得（非同期版）
+    pub async fn find_by_evaluation_period_async(
+        period_id: String,
+    ) -> Result<Vec<TradeTransaction>> {
+        #[cfg(any(test, feature = "test-helpers"))]
+        if let Some(store) = crate::memory::current() {
+            return Ok(memory_transactions(&store, |tx| {
+                tx.evaluation_period_id == period_id
+            }));
+        }
+
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| Self::find_by_evaluation_period(&period_id, conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to find transactions by evaluation period")
+    }
+
+    /// 指定した評価期間で `after` より後に記録された取引を取得
+    pub fn find_by_period_after(
+        period_id: &str,
+        after: NaiveDateTime,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<TradeTransaction>> {
+        trade_transactions::table
+            .filter(trade_transactions::evaluation_period_id.eq(period_id))
+            .filter(trade_transactions::timestamp.gt(after))
+            .order(trade_transactions::timestamp.asc())
+            .get_results(conn)
+    }
+
+    /// 指定した評価期間で `after` より後に記録された取引を取得（非同期版）
+    pub async fn find_by_period_after_async(
+        period_id: String,
+        after: NaiveDateTime,
+    ) -> Result<Vec<TradeTransaction>> {
+        let conn = connection_pool::get().await?;
+
+        let result = conn
+            .interact(move |conn| Self::find_by_period_after(&period_id, after, conn))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to interact with database: {}", e))?;
+
+        result.context("Failed to find transactions by period")
+    }
+}
+
+/// インメモリストアの取引を条件で絞り、timestamp の昇順で返す
+#[cfg(any(test, feature = "test-helpers"))]
+fn memory_transactions(
+    store: &crate::memory::MemoryStore,
+    filter: impl Fn(&TradeTransaction) -> bool,
+) -> Vec<TradeTransaction> {
+    let mut transactions: Vec<TradeTransaction> = store
+        .tables()
+        .trade_transactions
+        .iter()
+        .filter(|tx| filter(tx))
+        .cloned()
+        .collect();
+    transactions.sort_by_key(|tx| tx.timestamp);
+    transactions
+}
+
+/// SQL の SUM と同じく、値が 1 つも無ければ `None`
+#[cfg(any(test, feature = "test-helpers"))]
+fn sum_nullable(values: impl Iterator<Item = BigDecimal>) -> Option<BigDecimal> {
+    values.reduce(|sum, value| sum + value)
 }
 
 #[cfg(test)]
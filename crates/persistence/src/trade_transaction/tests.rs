@@ -33,6 +33,8 @@ async fn test_trade_transaction_crud() {
         timestamp: chrono::Utc::now().naive_utc(),
         evaluation_period_id: period_id.clone(),
         actual_to_amount: None,
+        gas_burnt: None,
+        gas_cost: None,
     };
 
     let result = AssertUnwindSafe(async {
@@ -99,6 +101,8 @@ async fn test_count_by_evaluation_period() {
             timestamp: chrono::Utc::now().naive_utc(),
             evaluation_period_id: period_id.clone(),
             actual_to_amount: None,
+            gas_burnt: None,
+            gas_cost: None,
         };
 
         transaction.insert_async().await.unwrap();
@@ -157,6 +161,8 @@ async fn test_transaction_with_evaluation_period_id() {
         timestamp: chrono::Utc::now().naive_utc(),
         evaluation_period_id: period_id.clone(),
         actual_to_amount: None,
+        gas_burnt: None,
+        gas_cost: None,
     };
 
     let result = AssertUnwindSafe(async {
@@ -203,6 +209,8 @@ async fn test_actual_to_amount_roundtrip() {
         timestamp: chrono::Utc::now().naive_utc(),
         evaluation_period_id: period_id.clone(),
         actual_to_amount: Some(actual_value.clone()),
+        gas_burnt: None,
+        gas_cost: None,
     };
     tx_with.insert_async().await.unwrap();
 
@@ -217,6 +225,8 @@ async fn test_actual_to_amount_roundtrip() {
         timestamp: chrono::Utc::now().naive_utc(),
         evaluation_period_id: period_id.clone(),
         actual_to_amount: None,
+        gas_burnt: None,
+        gas_cost: None,
     };
     tx_without.insert_async().await.unwrap();
 
@@ -245,6 +255,66 @@ async fn test_actual_to_amount_roundtrip() {
     }
 }
 
+#[tokio::test]
+async fn test_gas_by_batch() {
+    let period_id = create_test_evaluation_period().await;
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let tx_ids: Vec<String> = (0..3)
+        .map(|i| format!("test_tx_gas_{}_{}", i, uuid::Uuid::new_v4()))
+        .collect();
+
+    // 3 件目はガス未記録（reconciler が回収した取引など）
+    let gas = [
+        Some((10_000_000_000_000u64, 1_000u128)),
+        Some((5_000_000_000_000, 500)),
+        None,
+    ];
+    for (tx_id, gas) in tx_ids.iter().zip(gas) {
+        let tx = TradeTransaction {
+            tx_id: tx_id.clone(),
+            trade_batch_id: batch_id.clone(),
+            from_token: "wrap.near".to_string(),
+            from_amount: TokenSmallestUnits::from_u128(1_000_000_000_000_000_000_000_000),
+            to_token: "akaia.tkn.near".to_string(),
+            to_amount: TokenSmallestUnits::from_u128(50_000_000_000_000_000_000_000),
+            timestamp: chrono::Utc::now().naive_utc(),
+            evaluation_period_id: period_id.clone(),
+            actual_to_amount: None,
+            gas_burnt: None,
+            gas_cost: None,
+        };
+        let tx = match gas {
+            Some((burnt, cost)) => tx.with_gas(burnt, cost),
+            None => tx,
+        };
+        tx.insert_async().await.unwrap();
+    }
+
+    let result = AssertUnwindSafe(async {
+        let (burnt, cost) = TradeTransaction::gas_by_batch_async(batch_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(burnt, Some(BigDecimal::from(15_000_000_000_000u64)));
+        assert_eq!(cost, Some(BigDecimal::from(1_500)));
+
+        let (burnt, cost) = TradeTransaction::gas_by_batch_async("no-such-batch".to_string())
+            .await
+            .unwrap();
+        assert_eq!((burnt, cost), (None, None));
+    })
+    .catch_unwind()
+    .await;
+
+    for tx_id in tx_ids {
+        let _ = TradeTransaction::delete_by_tx_id_async(tx_id).await;
+    }
+    delete_test_evaluation_period(period_id).await;
+
+    if let Err(e) = result {
+        std::panic::resume_unwind(e);
+    }
+}
+
 #[tokio::test]
 async fn test_find_by_date_range() {
     let period_id = create_test_evaluation_period().await;
@@ -273,6 +343,8 @@ async fn test_find_by_date_range() {
             timestamp: *ts,
             evaluation_period_id: period_id.clone(),
             actual_to_amount: None,
+            gas_burnt: None,
+            gas_cost: None,
         };
         tx.insert_async().await.unwrap();
     }
@@ -333,3 +405,73 @@ async fn test_find_by_date_range() {
         std::panic::resume_unwind(e);
     }
 }
+
+#[tokio::test]
+async fn test_find_page_before() {
+    let period_id = create_test_evaluation_period().await;
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().naive_utc();
+
+    // 同じ時刻の 2 件と、その 1 日後の 1 件
+    let tx_ids: Vec<String> = (0..3)
+        .map(|i| format!("test_tx_page_{}_{}", i, uuid::Uuid::new_v4()))
+        .collect();
+    let timestamps = [now, now, now + chrono::TimeDelta::days(1)];
+
+    for (tx_id, ts) in tx_ids.iter().zip(timestamps.iter()) {
+        let tx = TradeTransaction {
+            tx_id: tx_id.clone(),
+            trade_batch_id: batch_id.clone(),
+            from_token: "wrap.near".to_string(),
+            from_amount: TokenSmallestUnits::from_u128(1_000_000_000_000_000_000_000_000),
+            to_token: "akaia.tkn.near".to_string(),
+            to_amount: TokenSmallestUnits::from_u128(50_000_000_000_000_000_000_000),
+            timestamp: *ts,
+            evaluation_period_id: period_id.clone(),
+            actual_to_amount: None,
+            gas_burnt: None,
+            gas_cost: None,
+        };
+        tx.insert_async().await.unwrap();
+    }
+
+    let result = AssertUnwindSafe(async {
+        let mut same_time = [tx_ids[0].clone(), tx_ids[1].clone()];
+        same_time.sort();
+
+        // 同時刻の 1 件目の次から → 同時刻の 2 件目は含み、1 日後（end 以降）は含まない
+        let page = TradeTransaction::find_page_before_async(
+            now + chrono::TimeDelta::days(1),
+            Some((now, same_time[0].clone())),
+            100,
+        )
+        .await
+        .unwrap();
+        let ids: Vec<&str> = page.iter().map(|t| t.tx_id.as_str()).collect();
+        assert!(ids.contains(&same_time[1].as_str()));
+        assert!(!ids.contains(&same_time[0].as_str()));
+        assert!(!ids.contains(&tx_ids[2].as_str()));
+
+        // 件数は limit まで
+        let page = TradeTransaction::find_page_before_async(
+            now + chrono::TimeDelta::days(2),
+            Some((now, same_time[0].clone())),
+            1,
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.len(), 1);
+    })
+    .catch_unwind()
+    .await;
+
+    // Cleanup
+    for tx_id in &tx_ids {
+        let _ = TradeTransaction::delete_by_tx_id_async(tx_id.clone()).await;
+    }
+    delete_test_evaluation_period(period_id).await;
+
+    if let Err(e) = result {
+        std::panic::resume_unwind(e);
+    }
+}
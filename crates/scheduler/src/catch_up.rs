@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// 1回の catch-up 判定で走査する予定時刻の上限（異常に細かい cron 式への保険）
+const MAX_SCAN: usize = 10_000;
+
+/// 停止中に取りこぼした予定時刻をどう扱うか
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+    /// 取りこぼした実行は行わない
+    Skip,
+    /// 取りこぼした中で最新の予定時刻のみ1回実行する
+    Latest,
+    /// 取りこぼした予定時刻を古い順に最大 `max_runs` 回実行する（新しい方を優先して残す）
+    All { max_runs: usize },
+}
+
+impl CatchUpPolicy {
+    /// 取りこぼした予定時刻（昇順）から実行対象を選ぶ。戻り値も昇順。
+    pub fn select(self, missed: &[DateTime<Utc>]) -> Vec<DateTime<Utc>> {
+        match self {
+            Self::Skip => Vec::new(),
+            Self::Latest => missed.last().copied().into_iter().collect(),
+            Self::All { max_runs } => {
+                let skip = missed.len().saturating_sub(max_runs);
+                missed[skip..].to_vec()
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CatchUpPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Skip => f.write_str("skip"),
+            Self::Latest => f.write_str("latest"),
+            Self::All { max_runs } => write!(f, "all(max={max_runs})"),
+        }
+    }
+}
+
+/// 最後に処理した予定時刻 `last` より後で、`now` 以前の予定時刻を昇順に列挙する。
+///
+/// `window` より古い予定時刻は対象外。`last` が `None`（一度も実行していない）の場合は
+/// 初回起動とみなして何も返さない。
+pub fn missed_runs(
+    schedule: &cron::Schedule,
+    last: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+    window: Duration,
+) -> Vec<DateTime<Utc>> {
+    let Some(last) = last else {
+        return Vec::new();
+    };
+    let window_start = chrono::TimeDelta::from_std(window)
+        .ok()
+        .and_then(|w| now.checked_sub_signed(w))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+    let from = last.max(window_start);
+
+    schedule
+        .after(&from)
+        .take(MAX_SCAN)
+        .take_while(|t| *t <= now)
+        .filter(|t| *t > last && *t >= window_start)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests;
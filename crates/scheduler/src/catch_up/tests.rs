@@ -0,0 +1,89 @@
+use super::*;
+use chrono::TimeZone;
+
+fn hourly() -> cron::Schedule {
+    "0 0 * * * *".parse().unwrap()
+}
+
+fn at(h: u32, m: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 10, h, m, 0).unwrap()
+}
+
+#[test]
+fn test_missed_runs_none_when_never_ran() {
+    let missed = missed_runs(&hourly(), None, at(12, 30), Duration::from_secs(86400));
+    assert!(missed.is_empty());
+}
+
+#[test]
+fn test_missed_runs_lists_times_after_last() {
+    let missed = missed_runs(
+        &hourly(),
+        Some(at(8, 0)),
+        at(11, 30),
+        Duration::from_secs(86400),
+    );
+    assert_eq!(missed, vec![at(9, 0), at(10, 0), at(11, 0)]);
+}
+
+#[test]
+fn test_missed_runs_includes_now_boundary() {
+    let missed = missed_runs(
+        &hourly(),
+        Some(at(10, 0)),
+        at(11, 0),
+        Duration::from_secs(86400),
+    );
+    assert_eq!(missed, vec![at(11, 0)]);
+}
+
+#[test]
+fn test_missed_runs_respects_window() {
+    // 2時間より古い予定時刻は捨てる
+    let missed = missed_runs(
+        &hourly(),
+        Some(at(1, 0)),
+        at(11, 30),
+        Duration::from_secs(2 * 3600),
+    );
+    assert_eq!(missed, vec![at(10, 0), at(11, 0)]);
+}
+
+#[test]
+fn test_missed_runs_empty_when_up_to_date() {
+    let missed = missed_runs(
+        &hourly(),
+        Some(at(11, 0)),
+        at(11, 59),
+        Duration::from_secs(86400),
+    );
+    assert!(missed.is_empty());
+}
+
+#[test]
+fn test_policy_skip() {
+    let missed = vec![at(9, 0), at(10, 0)];
+    assert!(CatchUpPolicy::Skip.select(&missed).is_empty());
+}
+
+#[test]
+fn test_policy_latest() {
+    let missed = vec![at(9, 0), at(10, 0), at(11, 0)];
+    assert_eq!(CatchUpPolicy::Latest.select(&missed), vec![at(11, 0)]);
+    assert!(CatchUpPolicy::Latest.select(&[]).is_empty());
+}
+
+#[test]
+fn test_policy_all_keeps_newest() {
+    let missed = vec![at(9, 0), at(10, 0), at(11, 0)];
+    assert_eq!(
+        CatchUpPolicy::All { max_runs: 2 }.select(&missed),
+        vec![at(10, 0), at(11, 0)]
+    );
+    assert_eq!(CatchUpPolicy::All { max_runs: 10 }.select(&missed), missed);
+    assert!(
+        CatchUpPolicy::All { max_runs: 0 }
+            .select(&missed)
+            .is_empty()
+    );
+}
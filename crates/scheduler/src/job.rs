@@ -0,0 +1,60 @@
+use crate::catch_up::CatchUpPolicy;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub type JobFuture = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>;
+
+type JobFn = Arc<dyn Fn() -> JobFuture + Send + Sync>;
+
+/// スケジューラに登録するジョブ
+#[derive(Clone)]
+pub struct Job {
+    pub(crate) name: String,
+    pub(crate) schedule: cron::Schedule,
+    pub(crate) catch_up: CatchUpPolicy,
+    pub(crate) task: JobFn,
+}
+
+impl Job {
+    /// catch-up なし（`CatchUpPolicy::Skip`）でジョブを作成する
+    pub fn new<F, Fut>(name: &str, schedule: cron::Schedule, task: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            schedule,
+            catch_up: CatchUpPolicy::Skip,
+            task: Arc::new(move || Box::pin(task())),
+        }
+    }
+
+    pub fn with_catch_up(mut self, policy: CatchUpPolicy) -> Self {
+        self.catch_up = policy;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn schedule(&self) -> &cron::Schedule {
+        &self.schedule
+    }
+
+    pub fn catch_up(&self) -> CatchUpPolicy {
+        self.catch_up
+    }
+}
+
+impl std::fmt::Debug for Job {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Job")
+            .field("name", &self.name)
+            .field("schedule", &self.schedule.to_string())
+            .field("catch_up", &self.catch_up)
+            .finish()
+    }
+}
@@ -0,0 +1,442 @@
+#![deny(warnings)]
+
+//! cron ジョブのスケジューラ
+//!
+//! 各ジョブの実行（開始・終了・結果・エラー）を `job_runs` テーブルに記録する。
+//! 停止中に取りこぼした予定時刻はジョブごとの [`CatchUpPolicy`] に従って起動時に追いかけ実行し、
+//! 同じジョブの実行が重なる場合は後から来た方を `skipped` として記録して実行しない。
+//!
+//! backend は single-process 前提（`backend` の crate doc 参照）のため、overlap guard は
+//! プロセスローカルのフラグで足りる。起動時に `running` のまま残っている `job_runs` は
+//! 前のプロセスが実行中に落ちたものとして `abandoned` にする。
+
+pub mod catch_up;
+mod job;
+
+pub use catch_up::CatchUpPolicy;
+pub use job::{Job, JobFuture};
+
+use chrono::{DateTime, Utc};
+use common::config::ConfigAccess;
+use logging::*;
+use persistence::job_run::{JobRun, JobRunStatus, JobRunTrigger, NewJobRun};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+use tracing::Instrument;
+
+type Result<T> = anyhow::Result<T>;
+
+/// cron スケジュール文字列をパースし、失敗時は default にフォールバック
+pub fn parse_schedule(cron_expr: &str, default: &str) -> cron::Schedule {
+    let log = DEFAULT.new(o!("function" => "scheduler::parse_schedule"));
+
+    match cron_expr.parse() {
+        Ok(s) => {
+            info!(log, "cron schedule configured"; "schedule" => cron_expr);
+            s
+        }
+        Err(e) => {
+            error!(log, "failed to parse cron schedule, using default";
+                   "error" => ?e, "schedule" => cron_expr, "default" => default);
+            default
+                .parse()
+                .expect("hardcoded default cron schedule must be valid")
+        }
+    }
+}
+
+struct JobEntry {
+    job: Job,
+    running: AtomicBool,
+}
+
+/// 実行中フラグを保持するガード。drop で解放されるためタスクが panic しても残らない。
+struct OverlapGuard {
+    entry: Arc<JobEntry>,
+}
+
+impl OverlapGuard {
+    fn try_acquire(entry: &Arc<JobEntry>) -> Option<Self> {
+        entry
+            .running
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| Self {
+                entry: Arc::clone(entry),
+            })
+    }
+}
+
+impl Drop for OverlapGuard {
+    fn drop(&mut self) {
+        self.entry.running.store(false, Ordering::Release);
+    }
+}
+
+/// 登録済みジョブの状態
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub name: String,
+    pub schedule: String,
+    pub catch_up: CatchUpPolicy,
+    pub running: bool,
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+/// 手動実行の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerOutcome {
+    /// 実行を開始した（完了は待たない）
+    Started { run_id: i32 },
+    /// 同じジョブが実行中のため実行しなかった
+    AlreadyRunning,
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: RwLock<BTreeMap<String, Arc<JobEntry>>>,
+}
+
+static GLOBAL: LazyLock<Scheduler> = LazyLock::new(Scheduler::new);
+
+/// プロセス共通のスケジューラ
+///
+/// backend がジョブを登録して [`Scheduler::run`] し、gRPC の JobService が
+/// 同じインスタンスから一覧・手動実行を行う。
+pub fn global() -> &'static Scheduler {
+    &GLOBAL
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ジョブを登録する。[`Scheduler::run`] より前に呼ぶこと。
+    pub fn register(&self, job: Job) -> Result<()> {
+        let mut jobs = self
+            .jobs
+            .write()
+            .map_err(|_| anyhow::anyhow!("scheduler registry lock poisoned"))?;
+        if jobs.contains_key(&job.name) {
+            return Err(anyhow::anyhow!("job already registered: {}", job.name));
+        }
+        jobs.insert(
+            job.name.clone(),
+            Arc::new(JobEntry {
+                job,
+                running: AtomicBool::new(false),
+            }),
+        );
+        Ok(())
+    }
+
+    fn entry(&self, name: &str) -> Option<Arc<JobEntry>> {
+        self.jobs
+            .read()
+            .ok()
+            .and_then(|jobs| jobs.get(name).cloned())
+    }
+
+    fn entries(&self) -> Vec<Arc<JobEntry>> {
+        self.jobs
+            .read()
+            .map(|jobs| jobs.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 登録済みジョブの一覧（名前順）
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        let now = Utc::now();
+        self.entries()
+            .into_iter()
+            .map(|entry| JobInfo {
+                name: entry.job.name.clone(),
+                schedule: entry.job.schedule.to_string(),
+                catch_up: entry.job.catch_up,
+                running: entry.running.load(Ordering::Acquire),
+                next_run: entry.job.schedule.after(&now).next(),
+            })
+            .collect()
+    }
+
+    /// ジョブを手動で実行する
+    ///
+    /// 実行記録を作成した時点で戻り、ジョブ本体はバックグラウンドで実行される。
+    /// 手動実行は呼び出し元に run id を返す必要があるため、記録の作成に失敗した場合は
+    /// 実行せずにエラーを返す。
+    pub async fn trigger(&self, name: &str) -> Result<TriggerOutcome> {
+        let entry = self
+            .entry(name)
+            .ok_or_else(|| anyhow::anyhow!("unknown job: {}", name))?;
+
+        let Some(guard) = OverlapGuard::try_acquire(&entry) else {
+            record_skipped(&entry, JobRunTrigger::Manual, None).await;
+            return Ok(TriggerOutcome::AlreadyRunning);
+        };
+
+        let run = NewJobRun::started(name, JobRunTrigger::Manual, None)
+            .insert_async()
+            .await?;
+        tokio::spawn(execute(guard, Some(run.id)));
+        Ok(TriggerOutcome::Started { run_id: run.id })
+    }
+
+    /// 登録済みの全ジョブのスケジュール実行を開始する（戻らない）
+    pub async fn run<C>(&self, cfg: C)
+    where
+        C: ConfigAccess + Clone + 'static,
+    {
+        let log = DEFAULT.new(o!("function" => "scheduler::run"));
+
+        match JobRun::abandon_running_async().await {
+            Ok(0) => {}
+            Ok(count) => warn!(log, "marked stale running job runs as abandoned"; "count" => count),
+            Err(e) => warn!(log, "failed to abandon stale running job runs"; "error" => %e),
+        }
+
+        let handles: Vec<_> = self
+            .entries()
+            .into_iter()
+            .map(|entry| tokio::spawn(job_loop(entry, cfg.clone())))
+            .collect();
+        info!(log, "scheduler started"; "jobs" => handles.len());
+
+        for handle in handles {
+            if let Err(e) = handle.await {
+                error!(log, "job loop terminated unexpectedly"; "error" => %e);
+            }
+        }
+    }
+}
+
+async fn job_loop<C: ConfigAccess>(entry: Arc<JobEntry>, cfg: C) {
+    let log = DEFAULT.new(o!("function" => "cronjob", "name" => entry.job.name.clone()));
+    info!(log, "starting cron job";
+        "schedule" => %entry.job.schedule,
+        "catch_up" => %entry.job.catch_up,
+    );
+
+    run_catch_up(&entry, &cfg).await;
+
+    for (iteration, next) in entry.job.schedule.upcoming(Utc).enumerate() {
+        let now = Utc::now();
+        debug!(log, "cron iteration"; "iteration" => iteration, "next" => %next, "now" => %now);
+
+        // 実行時刻を過ぎている場合はスキップ
+        if next <= now {
+            warn!(log, "execution time already passed, skipping to next iteration";
+                "next" => %next,
+                "now" => %now,
+                "iteration" => iteration
+            );
+            continue;
+        }
+
+        debug!(log, "waiting for next execution";
+            "wait_seconds" => (next - now).num_seconds(),
+            "next_time" => %next
+        );
+
+        // 長時間sleepを避けるため、1分間隔でチェック
+        loop {
+            let now = Utc::now();
+            if now >= next {
+                break;
+            }
+
+            let remaining = match (next - now).to_std() {
+                Ok(d) => d,
+                Err(_) => break, // 時刻が過去になった場合は即座に実行
+            };
+
+            // 最大sleep秒数（設定可能、デフォルト60秒）
+            let max_sleep = cfg.cron_max_sleep_seconds();
+            let sleep_duration = remaining.min(std::time::Duration::from_secs(max_sleep));
+
+            // 長時間待機の場合は定期的にログを出力
+            let log_threshold = cfg.cron_log_threshold_seconds();
+            if remaining.as_secs() > log_threshold {
+                debug!(log, "still waiting for next execution";
+                    "remaining_seconds" => remaining.as_secs(),
+                    "next_time" => %next
+                );
+            }
+
+            tokio::time::sleep(sleep_duration).await;
+        }
+
+        // 実行はバックグラウンドで行い、ループは次の予定時刻の待機に戻る。
+        // 実行が次の予定時刻を越えて長引いた場合は overlap guard が skipped を記録する。
+        if let Some((guard, run_id)) = begin(&entry, JobRunTrigger::Schedule, Some(next)).await {
+            tokio::spawn(execute(guard, run_id));
+        }
+    }
+}
+
+/// 停止中に取りこぼした予定時刻を `CatchUpPolicy` に従って順に実行する
+async fn run_catch_up(entry: &Arc<JobEntry>, cfg: &impl ConfigAccess) {
+    let policy = entry.job.catch_up;
+    if policy == CatchUpPolicy::Skip {
+        return;
+    }
+
+    let log = DEFAULT.new(o!(
+        "function" => "scheduler::run_catch_up",
+        "name" => entry.job.name.clone(),
+    ));
+
+    let last = match JobRun::latest_scheduled_at_async(entry.job.name.clone()).await {
+        Ok(last) => last.map(|t| t.and_utc()),
+        Err(e) => {
+            warn!(log, "failed to read last scheduled run, skipping catch-up"; "error" => %e);
+            return;
+        }
+    };
+
+    let window = cfg.scheduler_catch_up_window();
+    let missed = catch_up::missed_runs(&entry.job.schedule, last, Utc::now(), window);
+    if missed.is_empty() {
+        debug!(log, "no missed runs"; "last_scheduled_at" => ?last);
+        return;
+    }
+
+    let selected = policy.select(&missed);
+    info!(log, "catching up missed runs";
+        "missed" => missed.len(),
+        "selected" => selected.len(),
+        "policy" => %policy,
+    );
+
+    for scheduled_at in selected {
+        if let Some((guard, run_id)) =
+            begin(entry, JobRunTrigger::CatchUp, Some(scheduled_at)).await
+        {
+            execute(guard, run_id).await;
+        }
+    }
+}
+
+/// overlap guard を取得して実行開始を記録する。実行中なら skipped を記録して `None`。
+///
+/// 予定時刻ベースの実行では記録の失敗で実行自体を止めない（run id は `None` になる）。
+async fn begin(
+    entry: &Arc<JobEntry>,
+    trigger: JobRunTrigger,
+    scheduled_at: Option<DateTime<Utc>>,
+) -> Option<(OverlapGuard, Option<i32>)> {
+    let log = DEFAULT.new(o!(
+        "function" => "scheduler::begin",
+        "name" => entry.job.name.clone(),
+        "trigger" => trigger.as_str(),
+    ));
+
+    let Some(guard) = OverlapGuard::try_acquire(entry) else {
+        warn!(log, "previous run still in progress, skipping"; "scheduled_at" => ?scheduled_at);
+        record_skipped(entry, trigger, scheduled_at).await;
+        return None;
+    };
+
+    let record = NewJobRun::started(
+        &entry.job.name,
+        trigger,
+        scheduled_at.map(|t| t.naive_utc()),
+    );
+    let run_id = match record.insert_async().await {
+        Ok(run) => Some(run.id),
+        Err(e) => {
+            warn!(log, "failed to record job run start, running anyway"; "error" => %e);
+            None
+        }
+    };
+    Some((guard, run_id))
+}
+
+async fn record_skipped(
+    entry: &JobEntry,
+    trigger: JobRunTrigger,
+    scheduled_at: Option<DateTime<Utc>>,
+) {
+    metrics::job::record_skipped(&entry.job.name);
+    let record = NewJobRun::skipped(
+        &entry.job.name,
+        trigger,
+        scheduled_at.map(|t| t.naive_utc()),
+        "previous run still in progress",
+    );
+    if let Err(e) = record.insert_async().await {
+        let log = DEFAULT.new(o!(
+            "function" => "scheduler::record_skipped",
+            "name" => entry.job.name.clone(),
+        ));
+        warn!(log, "failed to record skipped job run"; "error" => %e);
+    }
+}
+
+/// ジョブ本体を実行して結果を記録する。guard は実行完了後に解放される。
+async fn execute(guard: OverlapGuard, run_id: Option<i32>) {
+    let entry = Arc::clone(&guard.entry);
+    let log = DEFAULT.new(o!(
+        "function" => "run",
+        "name" => entry.job.name.clone(),
+        "run_id" => run_id,
+    ));
+
+    // タスク実行前に DB から設定をリロード
+    let instance_id = &common::config::startup::get().instance_id;
+    if let Err(e) = persistence::config_store::reload_to_config(instance_id).await {
+        warn!(log, "config reload failed, using previous values"; "error" => %e);
+    }
+
+    // 1 回の実行を 1 トレースのルートにする（ジョブ配下の RPC・DB 書き込みが子 span になる）
+    let span = tracing::info_span!(
+        parent: None,
+        "job",
+        job.name = %entry.job.name,
+        job.run_id = run_id,
+        job.status = tracing::field::Empty,
+    );
+
+    info!(log, "executing scheduled task");
+    let started = std::time::Instant::now();
+
+    // panic もジョブの失敗として記録するため別タスクで実行する
+    let task = (entry.job.task)().instrument(span.clone());
+    let result = match tokio::spawn(task).await {
+        Ok(result) => result,
+        Err(e) => Err(anyhow::anyhow!("job task aborted: {}", e)),
+    };
+
+    let elapsed = started.elapsed();
+    let status = if result.is_ok() {
+        JobRunStatus::Succeeded
+    } else {
+        JobRunStatus::Failed
+    };
+    metrics::job::record_run(&entry.job.name, status.as_str(), elapsed);
+    span.record("job.status", status.as_str());
+
+    let elapsed_ms = elapsed.as_millis() as u64;
+    let error = match result {
+        Ok(()) => {
+            info!(log, "success"; "elapsed_ms" => elapsed_ms);
+            None
+        }
+        Err(err) => {
+            error!(log, "failure"; "error" => ?err, "elapsed_ms" => elapsed_ms);
+            Some(format!("{err:#}"))
+        }
+    };
+
+    if let Some(id) = run_id
+        && let Err(e) = JobRun::finish_async(id, error).await
+    {
+        warn!(log, "failed to record job run result"; "error" => %e);
+    }
+
+    drop(guard);
+}
+
+#[cfg(test)]
+mod tests;
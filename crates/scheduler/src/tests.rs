@@ -0,0 +1,235 @@
+use super::*;
+use serial_test::serial;
+use std::sync::atomic::AtomicUsize;
+use std::time::Duration;
+
+fn every_minute() -> cron::Schedule {
+    "0 * * * * *".parse().unwrap()
+}
+
+#[test]
+fn test_parse_schedule_valid() {
+    let schedule = parse_schedule("0 */15 * * * *", "0 0 0 * * *");
+    let mut upcoming = schedule.upcoming(Utc);
+    let first = upcoming.next().unwrap();
+    let second = upcoming.next().unwrap();
+    assert_eq!((second - first).num_minutes(), 15);
+}
+
+#[test]
+fn test_parse_schedule_fallback_on_invalid() {
+    let schedule = parse_schedule("invalid cron", "0 */15 * * * *");
+    let mut upcoming = schedule.upcoming(Utc);
+    let first = upcoming.next().unwrap();
+    let second = upcoming.next().unwrap();
+    assert_eq!((second - first).num_minutes(), 15); // デフォルトにフォールバック
+}
+
+#[test]
+fn test_register_rejects_duplicate_name() {
+    let scheduler = Scheduler::new();
+    scheduler
+        .register(Job::new("dup", every_minute(), || async { Ok(()) }))
+        .unwrap();
+    let err = scheduler
+        .register(Job::new("dup", every_minute(), || async { Ok(()) }))
+        .unwrap_err();
+    assert!(err.to_string().contains("already registered"));
+}
+
+#[test]
+fn test_jobs_lists_registered_in_name_order() {
+    let scheduler = Scheduler::new();
+    scheduler
+        .register(
+            Job::new("b_job", every_minute(), || async { Ok(()) })
+                .with_catch_up(CatchUpPolicy::Latest),
+        )
+        .unwrap();
+    scheduler
+        .register(Job::new("a_job", every_minute(), || async { Ok(()) }))
+        .unwrap();
+
+    let jobs = scheduler.jobs();
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].name, "a_job");
+    assert_eq!(jobs[0].catch_up, CatchUpPolicy::Skip);
+    assert_eq!(jobs[1].name, "b_job");
+    assert_eq!(jobs[1].catch_up, CatchUpPolicy::Latest);
+    assert!(jobs.iter().all(|j| !j.running));
+    assert!(jobs.iter().all(|j| j.next_run.is_some()));
+}
+
+#[test]
+fn test_overlap_guard_is_exclusive_and_released_on_drop() {
+    let entry = Arc::new(JobEntry {
+        job: Job::new("guarded", every_minute(), || async { Ok(()) }),
+        running: AtomicBool::new(false),
+    });
+
+    let guard = OverlapGuard::try_acquire(&entry).expect("first acquire succeeds");
+    assert!(OverlapGuard::try_acquire(&entry).is_none());
+    drop(guard);
+    assert!(OverlapGuard::try_acquire(&entry).is_some());
+}
+
+#[tokio::test]
+async fn test_trigger_unknown_job() {
+    let scheduler = Scheduler::new();
+    let err = scheduler.trigger("missing").await.unwrap_err();
+    assert!(err.to_string().contains("unknown job"));
+}
+
+async fn wait_until_finished(run_id: i32) -> JobRun {
+    for _ in 0..100 {
+        let run = JobRun::get_by_id_async(run_id).await.unwrap().unwrap();
+        if run.finished_at.is_some() {
+            return run;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("job run {run_id} did not finish in time");
+}
+
+// --- DB integration tests ---
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_trigger_records_success_and_failure() {
+    let ok_name = "test_scheduler_trigger_ok";
+    let ng_name = "test_scheduler_trigger_ng";
+    JobRun::delete_by_job_name_async(ok_name.to_string())
+        .await
+        .unwrap();
+    JobRun::delete_by_job_name_async(ng_name.to_string())
+        .await
+        .unwrap();
+
+    let scheduler = Scheduler::new();
+    scheduler
+        .register(Job::new(ok_name, every_minute(), || async { Ok(()) }))
+        .unwrap();
+    scheduler
+        .register(Job::new(ng_name, every_minute(), || async {
+            Err(anyhow::anyhow!("expected failure"))
+        }))
+        .unwrap();
+
+    let TriggerOutcome::Started { run_id } = scheduler.trigger(ok_name).await.unwrap() else {
+        panic!("expected job to start");
+    };
+    let run = wait_until_finished(run_id).await;
+    assert_eq!(run.status().unwrap(), JobRunStatus::Succeeded);
+    assert_eq!(run.trigger().unwrap(), JobRunTrigger::Manual);
+
+    let TriggerOutcome::Started { run_id } = scheduler.trigger(ng_name).await.unwrap() else {
+        panic!("expected job to start");
+    };
+    let run = wait_until_finished(run_id).await;
+    assert_eq!(run.status().unwrap(), JobRunStatus::Failed);
+    assert!(run.error.unwrap().contains("expected failure"));
+
+    JobRun::delete_by_job_name_async(ok_name.to_string())
+        .await
+        .unwrap();
+    JobRun::delete_by_job_name_async(ng_name.to_string())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_trigger_skips_while_running() {
+    let name = "test_scheduler_trigger_overlap";
+    JobRun::delete_by_job_name_async(name.to_string())
+        .await
+        .unwrap();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let scheduler = Scheduler::new();
+    {
+        let calls = Arc::clone(&calls);
+        scheduler
+            .register(Job::new(name, every_minute(), move || {
+                let calls = Arc::clone(&calls);
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(300)).await;
+                    Ok(())
+                }
+            }))
+            .unwrap();
+    }
+
+    let TriggerOutcome::Started { run_id } = scheduler.trigger(name).await.unwrap() else {
+        panic!("expected job to start");
+    };
+    assert_eq!(
+        scheduler.trigger(name).await.unwrap(),
+        TriggerOutcome::AlreadyRunning
+    );
+
+    wait_until_finished(run_id).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let runs = JobRun::list_recent_async(Some(name.to_string()), 0, 10)
+        .await
+        .unwrap();
+    let statuses: Vec<_> = runs.iter().map(|r| r.status().unwrap()).collect();
+    assert_eq!(statuses.len(), 2);
+    assert!(statuses.contains(&JobRunStatus::Skipped));
+    assert!(statuses.contains(&JobRunStatus::Succeeded));
+
+    JobRun::delete_by_job_name_async(name.to_string())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_catch_up_runs_latest_missed() {
+    let name = "test_scheduler_catch_up";
+    JobRun::delete_by_job_name_async(name.to_string())
+        .await
+        .unwrap();
+
+    // 5分前の予定時刻まで処理済みという状態を作る
+    let last = Utc::now() - chrono::TimeDelta::minutes(5);
+    NewJobRun::started(name, JobRunTrigger::Schedule, Some(last.naive_utc()))
+        .insert_async()
+        .await
+        .unwrap();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let entry = {
+        let calls = Arc::clone(&calls);
+        Arc::new(JobEntry {
+            job: Job::new(name, every_minute(), move || {
+                let calls = Arc::clone(&calls);
+                async move {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .with_catch_up(CatchUpPolicy::Latest),
+            running: AtomicBool::new(false),
+        })
+    };
+
+    run_catch_up(&entry, &common::config::ConfigResolver).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let runs = JobRun::list_recent_async(Some(name.to_string()), 0, 10)
+        .await
+        .unwrap();
+    let catch_up_runs: Vec<_> = runs
+        .iter()
+        .filter(|r| r.trigger().unwrap() == JobRunTrigger::CatchUp)
+        .collect();
+    assert_eq!(catch_up_runs.len(), 1);
+    assert_eq!(catch_up_runs[0].status().unwrap(), JobRunStatus::Succeeded);
+
+    JobRun::delete_by_job_name_async(name.to_string())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,125 @@
+use crate::cli::ArbitrageReportArgs;
+use anyhow::Result;
+use bigdecimal::BigDecimal;
+use logging::*;
+use num_traits::ToPrimitive;
+use persistence::arbitrage_attempt::{ArbitrageAttempt, DailyArbitrageSummary, total_net_profit};
+use serde::Serialize;
+
+const YOCTO_PER_NEAR: f64 = 1e24;
+
+/// One day of the report (amounts in yoctoNEAR as decimal strings)
+#[derive(Debug, Serialize)]
+pub struct DailyReport {
+    pub date: String,
+    pub attempts: u32,
+    pub successes: u32,
+    pub gross_profit: String,
+    pub gas_cost: String,
+    pub net_profit: String,
+}
+
+/// Net arbitrage profit per day over the requested period
+#[derive(Debug, Serialize)]
+pub struct ArbitrageReport {
+    pub days: Vec<DailyReport>,
+    pub total_attempts: u32,
+    pub total_successes: u32,
+    pub total_net_profit: String,
+}
+
+pub fn build_report(days: &[DailyArbitrageSummary]) -> ArbitrageReport {
+    ArbitrageReport {
+        days: days
+            .iter()
+            .map(|day| DailyReport {
+                date: day.date.format("%Y-%m-%d").to_string(),
+                attempts: day.attempts,
+                successes: day.successes,
+                gross_profit: day.gross_profit.to_string(),
+                gas_cost: day.gas_cost.to_string(),
+                net_profit: day.net_profit.to_string(),
+            })
+            .collect(),
+        total_attempts: days.iter().map(|day| day.attempts).sum(),
+        total_successes: days.iter().map(|day| day.successes).sum(),
+        total_net_profit: total_net_profit(days).to_string(),
+    }
+}
+
+fn to_near(yocto: &BigDecimal) -> f64 {
+    yocto.to_f64().unwrap_or(0.0) / YOCTO_PER_NEAR
+}
+
+fn print_text_report(days: &[DailyArbitrageSummary], start: &str, end: &str) {
+    println!("\n=== Arbitrage P&L Report ===");
+    println!("Period: {} to {}", start, end);
+
+    if days.is_empty() {
+        println!("\nNo arbitrage attempts in this period.");
+        return;
+    }
+
+    println!(
+        "\n{:<10}  {:>8}  {:>9}  {:>14}  {:>12}  {:>14}",
+        "date", "attempts", "successes", "gross (NEAR)", "gas (NEAR)", "net (NEAR)"
+    );
+    for day in days {
+        println!(
+            "{:<10}  {:>8}  {:>9}  {:>14.6}  {:>12.6}  {:>+14.6}",
+            day.date.format("%Y-%m-%d"),
+            day.attempts,
+            day.successes,
+            to_near(&day.gross_profit),
+            to_near(&day.gas_cost),
+            to_near(&day.net_profit),
+        );
+    }
+    println!(
+        "\nTotal net profit: {:+.6} NEAR",
+        to_near(&total_net_profit(days))
+    );
+}
+
+/// Parse and validate date range from ArbitrageReportArgs
+fn parse_date_range(args: &ArbitrageReportArgs) -> Result<(chrono::NaiveDate, chrono::NaiveDate)> {
+    let start_date = args.parse_start_date()?;
+    let end_date = args.parse_end_date()?;
+
+    if start_date > end_date {
+        return Err(anyhow::anyhow!(
+            "start-date must not be after end-date: {} > {}",
+            start_date,
+            end_date
+        ));
+    }
+
+    Ok((start_date, end_date))
+}
+
+pub async fn run_arbitrage_report(args: &ArbitrageReportArgs) -> Result<()> {
+    let log = DEFAULT.new(o!("function" => "run_arbitrage_report"));
+
+    let (start_date, end_date) = parse_date_range(args)?;
+
+    info!(log, "loading arbitrage ledger";
+        "start_date" => %start_date, "end_date" => %end_date
+    );
+
+    let days = ArbitrageAttempt::daily_summaries_async(start_date, end_date).await?;
+
+    match args.format {
+        crate::cli::OutputFormat::Text => {
+            print_text_report(&days, &args.start_date, &args.end_date);
+        }
+        crate::cli::OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&build_report(&days))?;
+            println!("{}", json);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
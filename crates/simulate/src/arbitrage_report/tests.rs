@@ -0,0 +1,59 @@
+use super::*;
+use crate::cli::OutputFormat;
+use chrono::NaiveDate;
+
+fn day(d: u32, attempts: u32, successes: u32, gross: i64, gas: i64) -> DailyArbitrageSummary {
+    DailyArbitrageSummary {
+        date: NaiveDate::from_ymd_opt(2026, 10, d).unwrap(),
+        attempts,
+        successes,
+        gross_profit: BigDecimal::from(gross),
+        gas_cost: BigDecimal::from(gas),
+        net_profit: BigDecimal::from(gross - gas),
+    }
+}
+
+fn make_args(start: &str, end: &str) -> ArbitrageReportArgs {
+    ArbitrageReportArgs {
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        format: OutputFormat::Text,
+    }
+}
+
+#[test]
+fn build_report_totals_days() {
+    let report = build_report(&[day(1, 3, 2, 5_000, 1_000), day(2, 1, 0, 0, 0)]);
+
+    assert_eq!(report.days.len(), 2);
+    assert_eq!(report.days[0].date, "2026-10-01");
+    assert_eq!(report.days[0].net_profit, "4000");
+    assert_eq!(report.total_attempts, 4);
+    assert_eq!(report.total_successes, 2);
+    assert_eq!(report.total_net_profit, "4000");
+}
+
+#[test]
+fn build_report_empty() {
+    let report = build_report(&[]);
+    assert!(report.days.is_empty());
+    assert_eq!(report.total_attempts, 0);
+    assert_eq!(report.total_net_profit, "0");
+}
+
+#[test]
+fn to_near_converts_yocto() {
+    assert_eq!(to_near(&BigDecimal::from(10u128.pow(24))), 1.0);
+    assert_eq!(to_near(&BigDecimal::from(-(10i128.pow(23)))), -0.1);
+}
+
+#[test]
+fn parse_date_range_allows_single_day() {
+    let (start, end) = parse_date_range(&make_args("2026-10-01", "2026-10-01")).unwrap();
+    assert_eq!(start, end);
+}
+
+#[test]
+fn parse_date_range_rejects_reversed() {
+    assert!(parse_date_range(&make_args("2026-10-02", "2026-10-01")).is_err());
+}
@@ -0,0 +1,508 @@
+//! Benchmark equity curves for a simulation run.
+//!
+//! Values are in NEAR like the strategy's own curve, so buy-and-hold NEAR is
+//! the flat curve at the initial capital, and the token baskets show what
+//! passive exposure to the tokens the strategy picked would have returned
+//! over the same snapshot dates. A token joins the baskets only from the
+//! snapshot of its first purchase, so no basket holds a token before the
+//! strategy knew about it.
+
+use crate::cli::RunArgs;
+use crate::output::period_returns;
+use crate::portfolio_state::{DEFAULT_DECIMALS, PortfolioState, RateProvider, to_f64_or_warn};
+use chrono::{DateTime, Utc};
+use common::types::TokenAccount;
+use logging::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tokio::sync::Mutex;
+
+/// Fee the benchmark baskets pay on every swap: REF Finance's standard
+/// SIMPLE_POOL fee, in units of `dex::FEE_DIVISOR` (0.3%).
+const BENCHMARK_POOL_FEE: u32 = 30;
+
+fn fee_rate() -> f64 {
+    f64::from(BENCHMARK_POOL_FEE) / f64::from(dex::FEE_DIVISOR)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkKind {
+    /// Keep the initial capital in NEAR
+    BuyAndHoldNear,
+    /// Equal weights in the tokens bought so far, rebalanced at every snapshot
+    EqualWeight,
+    /// Tokens bought so far weighted by market cap (total supply × price),
+    /// re-weighted whenever a token joins and held in between; constant
+    /// supplies keep the weights at market cap
+    MarketCapWeighted,
+}
+
+impl std::fmt::Display for BenchmarkKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BuyAndHoldNear => write!(f, "buy-and-hold NEAR"),
+            Self::EqualWeight => write!(f, "equal-weight basket"),
+            Self::MarketCapWeighted => write!(f, "market-cap-weighted basket"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkComparison {
+    pub benchmark: BenchmarkKind,
+    /// Tokens the benchmark holds (empty for NEAR)
+    pub tokens: Vec<String>,
+    pub total_return: f64,
+    pub final_balance_near: f64,
+    /// Annualised strategy return not explained by `beta` (risk-free rate = 0)
+    pub alpha: f64,
+    pub beta: f64,
+    /// Annualised std-dev of the per-interval return difference
+    pub tracking_error: f64,
+    /// Annualised mean return difference over `tracking_error`
+    pub information_ratio: f64,
+    /// Benchmark value (NEAR) at each `portfolio_values` timestamp
+    pub values: Vec<f64>,
+}
+
+/// Strategy performance relative to one benchmark
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct RelativeMetrics {
+    pub alpha: f64,
+    pub beta: f64,
+    pub tracking_error: f64,
+    pub information_ratio: f64,
+}
+
+/// Build the benchmark curves over the run's snapshot dates and compare the
+/// strategy against each.
+///
+/// `total_supplies` (whole tokens) drives the market-cap weights; tokens
+/// without a supply are left out of that basket.
+pub(crate) async fn compare_benchmarks(
+    cli: &RunArgs,
+    state: &PortfolioState,
+    rate_provider: &(impl RateProvider + ?Sized),
+    total_supplies: &BTreeMap<TokenAccount, f64>,
+) -> Vec<BenchmarkComparison> {
+    let initial_capital = cli.initial_capital;
+    let interval_days = cli.rebalance_interval_days;
+    let timestamps: Vec<_> = state.snapshots.iter().map(|s| s.timestamp).collect();
+    let strategy: Vec<_> = state.snapshots.iter().map(|s| s.total_value_near).collect();
+    let compare = |benchmark, tokens: &[TokenAccount], values| {
+        comparison(
+            benchmark,
+            tokens,
+            initial_capital,
+            values,
+            &strategy,
+            interval_days,
+        )
+    };
+
+    let mut comparisons = vec![compare(
+        BenchmarkKind::BuyAndHoldNear,
+        &[],
+        vec![initial_capital; strategy.len()],
+    )];
+
+    let purchases = first_purchases(state);
+    let table = PriceTable::fetch(
+        purchases.keys().cloned().collect(),
+        &timestamps,
+        rate_provider,
+    )
+    .await;
+    // Step at which each token joins: the snapshot of its first purchase, or
+    // its first price after that. Tokens joining at the final snapshot, when
+    // the baskets are sold, are never held.
+    let last = timestamps.len().saturating_sub(1);
+    let joins: Vec<usize> = table
+        .tokens
+        .iter()
+        .zip(&table.first_priced)
+        .map(|(token, first_priced)| {
+            let bought = timestamps.partition_point(|at| *at < purchases[token]);
+            bought.max(*first_priced)
+        })
+        .collect();
+    let held: Vec<bool> = joins.iter().map(|join| *join < last).collect();
+    let table = table.only(|i| held[i]);
+    let joins: Vec<usize> = joins.into_iter().filter(|join| *join < last).collect();
+    if table.tokens.is_empty() {
+        return comparisons;
+    }
+
+    let equal = (0..timestamps.len())
+        .map(|step| Some(equal_weights(&joins, step)))
+        .collect::<Vec<_>>();
+    comparisons.push(compare(
+        BenchmarkKind::EqualWeight,
+        &table.tokens,
+        basket_values(initial_capital, &table.prices, &equal),
+    ));
+
+    let capped_columns: Vec<bool> = table
+        .tokens
+        .iter()
+        .map(|token| total_supplies.contains_key(token))
+        .collect();
+    let capped = table.only(|i| capped_columns[i]);
+    let capped_joins: Vec<usize> = joins
+        .iter()
+        .zip(&capped_columns)
+        .filter(|(_, capped)| **capped)
+        .map(|(join, _)| *join)
+        .collect();
+    let supplies: Vec<f64> = capped.tokens.iter().map(|t| total_supplies[t]).collect();
+    let targets: Vec<_> = capped
+        .prices
+        .iter()
+        .enumerate()
+        .map(|(step, row)| {
+            if capped_joins.contains(&step) {
+                cap_weights(&supplies, row, &capped_joins, step)
+            } else {
+                None
+            }
+        })
+        .collect();
+    if targets.iter().any(Option::is_some) {
+        comparisons.push(compare(
+            BenchmarkKind::MarketCapWeighted,
+            &capped.tokens,
+            basket_values(initial_capital, &capped.prices, &targets),
+        ));
+    }
+
+    comparisons
+}
+
+/// Tokens the strategy bought during the run, with the time of the first
+/// purchase of each
+pub(crate) fn first_purchases(state: &PortfolioState) -> BTreeMap<TokenAccount, DateTime<Utc>> {
+    let wnear = &*blockchain::ref_finance::token_account::WNEAR_TOKEN;
+    let mut purchases = BTreeMap::new();
+    for event in state.swap_events.iter().filter(|e| &e.token_out != wnear) {
+        purchases
+            .entry(event.token_out.clone())
+            .and_modify(|at: &mut DateTime<Utc>| *at = (*at).min(event.timestamp))
+            .or_insert(event.timestamp);
+    }
+    purchases
+}
+
+/// Equal weights over the tokens that have joined by `step`
+fn equal_weights(joins: &[usize], step: usize) -> Vec<f64> {
+    let members = joins.iter().filter(|join| **join <= step).count();
+    joins
+        .iter()
+        .map(|join| {
+            if *join <= step {
+                1.0 / members as f64
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Market-cap weights at `step` over the tokens that have joined by then;
+/// `None` when they have no market cap.
+fn cap_weights(supplies: &[f64], row: &[f64], joins: &[usize], step: usize) -> Option<Vec<f64>> {
+    let caps: Vec<f64> = supplies
+        .iter()
+        .zip(row)
+        .zip(joins)
+        .map(
+            |((supply, price), join)| {
+                if *join <= step { supply * price } else { 0.0 }
+            },
+        )
+        .collect();
+    let total: f64 = caps.iter().sum();
+    (total > 0.0).then(|| caps.iter().map(|cap| cap / total).collect())
+}
+
+/// Total supplies (whole tokens) for the market-cap benchmark, shared by the
+/// runs of one invocation.
+///
+/// Supplies are not recorded historically, so the current supply of each
+/// token is read from the chain the first time a run needs it and taken as
+/// constant over the simulated period. Every later run of a sweep or Monte
+/// Carlo batch reuses it, including tokens whose supply could not be read
+/// (left out of the basket), so all runs weight their baskets alike.
+#[derive(Debug, Default)]
+pub struct TotalSupplies {
+    cache: Mutex<BTreeMap<TokenAccount, Option<f64>>>,
+}
+
+impl TotalSupplies {
+    /// Supplies of `tokens`, reading only those not seen by an earlier run.
+    pub(crate) async fn get(&self, tokens: &[TokenAccount]) -> BTreeMap<TokenAccount, f64> {
+        let log = DEFAULT.new(o!("function" => "TotalSupplies::get"));
+        let mut cache = self.cache.lock().await;
+        let missing: Vec<_> = tokens.iter().filter(|t| !cache.contains_key(*t)).collect();
+        if !missing.is_empty() {
+            let client = blockchain::jsonrpc::new_client();
+            for token in missing {
+                let decimals =
+                    trade::token_cache::get_cached_decimals(token).unwrap_or(DEFAULT_DECIMALS);
+                let supply = match trade::market_data::get_token_total_supply(
+                    &client, token, decimals,
+                )
+                .await
+                {
+                    Ok(supply) => Some(to_f64_or_warn(&supply.to_whole(), "total_supply")),
+                    Err(e) => {
+                        warn!(log, "failed to read total supply, excluded from market-cap benchmark";
+                                "token" => %token, "error" => %e);
+                        None
+                    }
+                };
+                cache.insert(token.clone(), supply);
+            }
+        }
+        tokens
+            .iter()
+            .filter_map(|token| Some((token.clone(), cache.get(token).copied().flatten()?)))
+            .collect()
+    }
+}
+
+/// Prices of the basket tokens (NEAR per whole token) at each snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PriceTable {
+    pub tokens: Vec<TokenAccount>,
+    /// `prices[step][token]`
+    pub prices: Vec<Vec<f64>>,
+    /// First step with a recorded price of each token
+    pub first_priced: Vec<usize>,
+}
+
+impl PriceTable {
+    /// Look up every token at every timestamp.
+    ///
+    /// A missing rate repeats the previous one; tokens with no rate at all
+    /// cannot be bought and are dropped.
+    async fn fetch(
+        tokens: Vec<TokenAccount>,
+        timestamps: &[DateTime<Utc>],
+        rate_provider: &(impl RateProvider + ?Sized),
+    ) -> Self {
+        let mut columns: Vec<Vec<Option<f64>>> = Vec::with_capacity(tokens.len());
+        for token in &tokens {
+            let token_out = token.to_out();
+            let mut column = Vec::with_capacity(timestamps.len());
+            for &at in timestamps {
+                let price = rate_provider
+                    .get_rate(&token_out, at)
+                    .await
+                    .map(|rate| to_f64_or_warn(rate.to_price().as_bigdecimal(), "benchmark_price"))
+                    .filter(|price| *price > 0.0);
+                column.push(price);
+            }
+            columns.push(column);
+        }
+        Self::from_columns(tokens, columns)
+    }
+
+    pub(crate) fn from_columns(tokens: Vec<TokenAccount>, columns: Vec<Vec<Option<f64>>>) -> Self {
+        let log = DEFAULT.new(o!("function" => "PriceTable::from_columns"));
+        let steps = columns.first().map_or(0, Vec::len);
+        let mut kept = Vec::new();
+        let mut filled = Vec::new();
+        let mut first_priced = Vec::new();
+        for (token, column) in tokens.into_iter().zip(columns) {
+            let Some((first_step, first)) = column
+                .iter()
+                .enumerate()
+                .find_map(|(step, price)| Some((step, (*price)?)))
+            else {
+                if steps > 0 {
+                    warn!(log, "no price in the run, excluded from benchmarks";
+                        "token" => %token);
+                }
+                continue;
+            };
+            // Steps before the first price are never held; fill them with it
+            let mut last = first;
+            filled.push(
+                column
+                    .into_iter()
+                    .map(|price| {
+                        last = price.unwrap_or(last);
+                        last
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            kept.push(token);
+            first_priced.push(first_step);
+        }
+        let prices = (0..steps)
+            .map(|step| filled.iter().map(|column| column[step]).collect())
+            .collect();
+        Self {
+            tokens: kept,
+            prices,
+            first_priced,
+        }
+    }
+
+    /// The same table restricted to the token columns matching `keep`
+    fn only(&self, keep: impl Fn(usize) -> bool) -> Self {
+        let columns: Vec<usize> = (0..self.tokens.len()).filter(|&i| keep(i)).collect();
+        Self {
+            tokens: columns.iter().map(|&i| self.tokens[i].clone()).collect(),
+            prices: self
+                .prices
+                .iter()
+                .map(|row| columns.iter().map(|&i| row[i]).collect())
+                .collect(),
+            first_priced: columns.iter().map(|&i| self.first_priced[i]).collect(),
+        }
+    }
+}
+
+/// Value of a token basket at each step of `prices`.
+///
+/// The basket starts as `initial_capital` in NEAR. At every step with a
+/// target it trades to those weights, keeping the unallocated share in NEAR
+/// and paying the pool fee on the turnover like the strategy's own trades;
+/// at the last step it sells everything, paying the fee once more.
+pub(crate) fn basket_values(
+    initial_capital: f64,
+    prices: &[Vec<f64>],
+    targets: &[Option<Vec<f64>>],
+) -> Vec<f64> {
+    let fee = fee_rate();
+    let Some(first) = prices.first() else {
+        return Vec::new();
+    };
+    let mut cash = initial_capital;
+    let mut units = vec![0.0; first.len()];
+    let last = prices.len() - 1;
+    prices
+        .iter()
+        .zip(targets)
+        .enumerate()
+        .map(|(step, (row, target))| {
+            let held: f64 = units.iter().zip(row).map(|(q, p)| q * p).sum();
+            if step == last {
+                return cash + held * (1.0 - fee);
+            }
+            let mut value = cash + held;
+            if let Some(weights) = target {
+                let turnover: f64 = units
+                    .iter()
+                    .zip(row)
+                    .zip(weights)
+                    .map(|((q, p), w)| (value * w - q * p).abs())
+                    .sum();
+                value -= turnover * fee;
+                units = weights
+                    .iter()
+                    .zip(row)
+                    .map(|(w, p)| value * w / p)
+                    .collect();
+                cash = value * (1.0 - weights.iter().sum::<f64>());
+            }
+            value
+        })
+        .collect()
+}
+
+/// Alpha, beta, tracking error and information ratio of `strategy` against
+/// `benchmark`, both sampled every `interval_days` from `initial_capital`.
+pub(crate) fn relative_metrics(
+    initial_capital: f64,
+    strategy: &[f64],
+    benchmark: &[f64],
+    interval_days: i64,
+) -> RelativeMetrics {
+    let strategy = period_returns(initial_capital, strategy);
+    let benchmark = period_returns(initial_capital, benchmark);
+    let n = strategy.len().min(benchmark.len());
+    if n < 2 {
+        return RelativeMetrics {
+            alpha: 0.0,
+            beta: 0.0,
+            tracking_error: 0.0,
+            information_ratio: 0.0,
+        };
+    }
+    let (strategy, benchmark) = (&strategy[..n], &benchmark[..n]);
+    let periods_per_year = 365.0 / interval_days as f64;
+
+    let mean = |xs: &[f64]| xs.iter().sum::<f64>() / xs.len() as f64;
+    let mean_s = mean(strategy);
+    let mean_b = mean(benchmark);
+    let covariance = strategy
+        .iter()
+        .zip(benchmark)
+        .map(|(s, b)| (s - mean_s) * (b - mean_b))
+        .sum::<f64>()
+        / (n - 1) as f64;
+    let variance_b = benchmark.iter().map(|b| (b - mean_b).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let beta = if variance_b > 0.0 {
+        covariance / variance_b
+    } else {
+        0.0
+    };
+
+    let active: Vec<f64> = strategy.iter().zip(benchmark).map(|(s, b)| s - b).collect();
+    let mean_active = mean(&active);
+    let active_std = (active
+        .iter()
+        .map(|a| (a - mean_active).powi(2))
+        .sum::<f64>()
+        / (n - 1) as f64)
+        .sqrt();
+
+    RelativeMetrics {
+        alpha: (mean_s - beta * mean_b) * periods_per_year,
+        beta,
+        tracking_error: active_std * periods_per_year.sqrt(),
+        information_ratio: if active_std > 0.0 {
+            mean_active / active_std * periods_per_year.sqrt()
+        } else {
+            0.0
+        },
+    }
+}
+
+fn comparison(
+    benchmark: BenchmarkKind,
+    tokens: &[TokenAccount],
+    initial_capital: f64,
+    values: Vec<f64>,
+    strategy: &[f64],
+    interval_days: i64,
+) -> BenchmarkComparison {
+    let final_balance_near = values.last().copied().unwrap_or(initial_capital);
+    let RelativeMetrics {
+        alpha,
+        beta,
+        tracking_error,
+        information_ratio,
+    } = relative_metrics(initial_capital, strategy, &values, interval_days);
+    BenchmarkComparison {
+        benchmark,
+        tokens: tokens.iter().map(ToString::to_string).collect(),
+        total_return: if initial_capital > 0.0 {
+            final_balance_near / initial_capital - 1.0
+        } else {
+            0.0
+        },
+        final_balance_near,
+        alpha,
+        beta,
+        tracking_error,
+        information_ratio,
+        values,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,402 @@
+use super::*;
+use crate::portfolio_state::{PortfolioSnapshot, SwapEvent, SwapMethod};
+use bigdecimal::BigDecimal;
+use chrono::TimeZone;
+use common::types::{TokenAmount, YoctoValue};
+use std::path::PathBuf;
+use std::sync::Arc;
+use trade::synthetic_market::{
+    PriceModel, SyntheticMarket, SyntheticMarketConfig, SyntheticTokenConfig,
+};
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {expected}, got {actual}"
+    );
+}
+
+fn token(name: &str) -> TokenAccount {
+    name.parse().unwrap()
+}
+
+/// Buy `weights` at the first step and, with `rebalance`, trade back to them
+/// at every step after
+fn targets(weights: &[f64], steps: usize, rebalance: bool) -> Vec<Option<Vec<f64>>> {
+    (0..steps)
+        .map(|step| (step == 0 || rebalance).then(|| weights.to_vec()))
+        .collect()
+}
+
+// --- basket_values ---
+
+#[test]
+fn basket_pays_fee_on_entry_and_exit() {
+    let fee = fee_rate();
+    let prices = vec![vec![2.0], vec![2.0], vec![2.0]];
+    let values = basket_values(100.0, &prices, &targets(&[1.0], 3, false));
+    assert_close(values[0], 100.0 * (1.0 - fee));
+    assert_close(values[1], 100.0 * (1.0 - fee));
+    assert_close(values[2], 100.0 * (1.0 - fee) * (1.0 - fee));
+}
+
+#[test]
+fn basket_follows_price() {
+    let fee = fee_rate();
+    let prices = vec![vec![1.0], vec![2.0], vec![3.0]];
+    let values = basket_values(100.0, &prices, &targets(&[1.0], 3, false));
+    assert_close(values[1], 200.0 * (1.0 - fee));
+}
+
+#[test]
+fn rebalanced_basket_pays_fee_on_turnover() {
+    let fee = fee_rate();
+    // Token a doubles: the basket is 2/3 in a and sells 1/6 of its value
+    // of a to buy b
+    let prices = vec![vec![1.0, 1.0], vec![2.0, 1.0], vec![2.0, 1.0]];
+    let weights = [0.5, 0.5];
+    let held = basket_values(100.0, &prices, &targets(&weights, 3, false));
+    let rebalanced = basket_values(100.0, &prices, &targets(&weights, 3, true));
+
+    let before = 150.0 * (1.0 - fee);
+    assert_close(held[1], before);
+    let turnover = before / 3.0;
+    assert_close(rebalanced[1], before - turnover * fee);
+    assert!(rebalanced[2] < held[2]);
+}
+
+#[test]
+fn rebalanced_basket_without_drift_matches_hold() {
+    let prices = vec![vec![1.0, 4.0], vec![1.5, 6.0], vec![0.5, 2.0]];
+    let weights = [0.25, 0.75];
+    let held = basket_values(100.0, &prices, &targets(&weights, 3, false));
+    let rebalanced = basket_values(100.0, &prices, &targets(&weights, 3, true));
+    for (h, r) in held.iter().zip(&rebalanced) {
+        assert_close(*r, *h);
+    }
+}
+
+#[test]
+fn basket_without_prices_is_empty() {
+    assert!(basket_values(100.0, &[], &[]).is_empty());
+}
+
+#[test]
+fn basket_stays_in_near_until_a_token_joins() {
+    let fee = fee_rate();
+    let prices = vec![vec![2.0], vec![4.0], vec![4.0]];
+    let targets = vec![Some(vec![0.0]), Some(vec![1.0]), None];
+    let values = basket_values(100.0, &prices, &targets);
+    // The price move before the purchase is not captured
+    assert_close(values[0], 100.0);
+    assert_close(values[1], 100.0 * (1.0 - fee));
+    assert_close(values[2], 100.0 * (1.0 - fee) * (1.0 - fee));
+}
+
+#[test]
+fn joining_token_pays_fee_on_turnover() {
+    let fee = fee_rate();
+    let prices = vec![vec![1.0, 1.0], vec![1.0, 2.0], vec![1.0, 4.0]];
+    let targets = vec![Some(vec![1.0, 0.0]), Some(vec![0.5, 0.5]), None];
+    let values = basket_values(100.0, &prices, &targets);
+
+    // Half of the position in a is sold to buy b
+    let entry = 100.0 * (1.0 - fee);
+    assert_close(values[1], entry * (1.0 - fee));
+    // b doubles after joining: 1/2 + 1/2 × 2
+    assert_close(values[2], entry * (1.0 - fee) * 1.5 * (1.0 - fee));
+}
+
+// --- relative_metrics ---
+
+#[test]
+fn relative_metrics_identical_curves() {
+    let values = [101.0, 99.0, 103.0, 104.0];
+    let m = relative_metrics(100.0, &values, &values, 1);
+    assert_close(m.beta, 1.0);
+    assert_close(m.alpha, 0.0);
+    assert_close(m.tracking_error, 0.0);
+    assert_eq!(m.information_ratio, 0.0);
+}
+
+#[test]
+fn relative_metrics_leveraged_strategy() {
+    // Strategy returns are exactly twice the benchmark's
+    let benchmark = [102.0, 99.96, 101.9592];
+    let strategy = [104.0, 99.84, 103.8336];
+    let m = relative_metrics(100.0, &strategy, &benchmark, 1);
+    assert_close(m.beta, 2.0);
+    assert_close(m.alpha, 0.0);
+    assert!(m.tracking_error > 0.0);
+    assert!(m.information_ratio > 0.0);
+}
+
+#[test]
+fn relative_metrics_flat_benchmark() {
+    let strategy = [110.0, 99.0, 108.9];
+    let m = relative_metrics(100.0, &strategy, &[100.0; 3], 1);
+    assert_eq!(m.beta, 0.0);
+    // Mean return 0.1 - 0.1 + 0.1 = 0.1/3 per day, annualised
+    assert_close(m.alpha, 0.1 / 3.0 * 365.0);
+    assert!(m.tracking_error > 0.0);
+}
+
+#[test]
+fn relative_metrics_too_short() {
+    let m = relative_metrics(100.0, &[110.0], &[105.0], 1);
+    assert_eq!(m.beta, 0.0);
+    assert_eq!(m.tracking_error, 0.0);
+}
+
+// --- PriceTable ---
+
+#[test]
+fn price_table_carries_forward_and_drops_unpriced_tokens() {
+    let table = PriceTable::from_columns(
+        vec![
+            token("a.near"),
+            token("b.near"),
+            token("c.near"),
+            token("d.near"),
+        ],
+        vec![
+            vec![Some(1.0), None, Some(3.0)],
+            vec![None, Some(2.0), Some(2.5)],
+            vec![Some(5.0), Some(5.0), None],
+            vec![None, None, None],
+        ],
+    );
+    assert_eq!(
+        table.tokens,
+        vec![token("a.near"), token("b.near"), token("c.near")]
+    );
+    assert_eq!(table.first_priced, vec![0, 1, 0]);
+    // b is filled with its first price before it has one
+    assert_eq!(
+        table.prices,
+        vec![
+            vec![1.0, 2.0, 5.0],
+            vec![1.0, 2.0, 5.0],
+            vec![3.0, 2.5, 5.0]
+        ]
+    );
+}
+
+// --- compare_benchmarks ---
+
+fn market() -> SyntheticMarket {
+    SyntheticMarket::generate(&SyntheticMarketConfig {
+        seed: 3,
+        start: Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap(),
+        steps: 10,
+        step_hours: 24,
+        tokens: vec![
+            SyntheticTokenConfig {
+                account: token("a.near"),
+                decimals: 18,
+                initial_price: 0.5,
+                drift: 0.0,
+                volatility: 0.8,
+                pool_depth_near: 10_000.0,
+            },
+            SyntheticTokenConfig {
+                account: token("b.near"),
+                decimals: 18,
+                initial_price: 2.0,
+                drift: 0.0,
+                volatility: 0.8,
+                pool_depth_near: 10_000.0,
+            },
+        ],
+        correlation: 0.0,
+        model: PriceModel::Gbm,
+        prediction_horizon_steps: 1,
+        prediction_error: 0.0,
+    })
+    .unwrap()
+}
+
+fn purchase(token_out: &str, timestamp: DateTime<Utc>) -> SwapEvent {
+    SwapEvent {
+        timestamp,
+        token_in: blockchain::ref_finance::token_account::WNEAR_TOKEN.clone(),
+        amount_in: TokenAmount::from_smallest_units(BigDecimal::from(2), 24),
+        token_out: token(token_out),
+        amount_out: TokenAmount::from_smallest_units(BigDecimal::from(1), 18),
+        swap_method: SwapMethod::PoolBased,
+        pool_ids: vec![0],
+        slippage: None,
+    }
+}
+
+fn state_on(market: &SyntheticMarket) -> PortfolioState {
+    let mut state = PortfolioState::new(YoctoValue::zero());
+    state
+        .swap_events
+        .push(purchase("a.near", market.times()[0]));
+    state.snapshots = market
+        .times()
+        .iter()
+        .enumerate()
+        .map(|(i, &timestamp)| PortfolioSnapshot {
+            timestamp,
+            total_value_near: 100.0 + i as f64,
+            holdings: BTreeMap::new(),
+            holding_values_near: BTreeMap::new(),
+            cash_balance: YoctoValue::zero(),
+            realized_pnl_near: 0.0,
+        })
+        .collect();
+    state
+}
+
+fn cli() -> RunArgs {
+    RunArgs {
+        start_date: "2025-06-01".to_string(),
+        end_date: "2025-06-10".to_string(),
+        initial_capital: 100.0,
+        top_tokens: 10,
+        price_history_days: 30,
+        rebalance_threshold: 0.1,
+        rebalance_interval_days: 1,
+        output: PathBuf::from("test.json"),
+        sweep: None,
+        generate_predictions: false,
+        impact_half_life_days: None,
+        impact_persistent: false,
+    }
+}
+
+#[tokio::test]
+async fn compare_benchmarks_on_synthetic_market() {
+    let market = Arc::new(market());
+    let state = state_on(&market);
+    let supplies = BTreeMap::from([(token("a.near"), 1_000_000.0)]);
+
+    let comparisons = compare_benchmarks(&cli(), &state, &*market, &supplies).await;
+
+    let kinds: Vec<_> = comparisons.iter().map(|c| c.benchmark).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            BenchmarkKind::BuyAndHoldNear,
+            BenchmarkKind::EqualWeight,
+            BenchmarkKind::MarketCapWeighted
+        ]
+    );
+    let near = &comparisons[0];
+    assert!(near.values.iter().all(|v| *v == 100.0));
+    assert_eq!(near.total_return, 0.0);
+    assert_eq!(near.beta, 0.0);
+
+    // A single-token basket never needs rebalancing, so both baskets agree
+    let (equal, cap) = (&comparisons[1], &comparisons[2]);
+    assert_eq!(equal.tokens, vec!["a.near".to_string()]);
+    assert_eq!(equal.values.len(), 10);
+    for (e, c) in equal.values.iter().zip(&cap.values) {
+        assert_close(*e, *c);
+    }
+    let first_price = market
+        .price_at(&token("a.near"), market.times()[0])
+        .unwrap();
+    let last_price = market
+        .price_at(&token("a.near"), market.times()[9])
+        .unwrap();
+    let growth = to_f64_or_warn(last_price.as_bigdecimal(), "test")
+        / to_f64_or_warn(first_price.as_bigdecimal(), "test");
+    assert_close(
+        equal.final_balance_near,
+        100.0 * growth * (1.0 - fee_rate()).powi(2),
+    );
+}
+
+fn prices_of(market: &SyntheticMarket, name: &str) -> Vec<Vec<f64>> {
+    market
+        .times()
+        .iter()
+        .map(|&at| {
+            let price = market.price_at(&token(name), at).unwrap();
+            vec![to_f64_or_warn(price.as_bigdecimal(), "test")]
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn baskets_hold_tokens_only_from_their_first_purchase() {
+    let market = Arc::new(market());
+    let mut state = state_on(&market);
+    state
+        .swap_events
+        .push(purchase("b.near", market.times()[5]));
+    state
+        .swap_events
+        .push(purchase("a.near", market.times()[7]));
+    let supplies = BTreeMap::from([(token("a.near"), 1_000.0), (token("b.near"), 1_000.0)]);
+
+    let comparisons = compare_benchmarks(&cli(), &state, &*market, &supplies).await;
+
+    assert_eq!(
+        first_purchases(&state),
+        BTreeMap::from([
+            (token("a.near"), market.times()[0]),
+            (token("b.near"), market.times()[5]),
+        ])
+    );
+    let (equal, cap) = (&comparisons[1], &comparisons[2]);
+    assert_eq!(
+        equal.tokens,
+        vec!["a.near".to_string(), "b.near".to_string()]
+    );
+    assert_eq!(cap.tokens, equal.tokens);
+    // Until b is bought both baskets hold only a
+    let only_a = basket_values(
+        100.0,
+        &prices_of(&market, "a.near"),
+        &targets(&[1.0], 10, true),
+    );
+    for step in 0..5 {
+        assert_close(equal.values[step], only_a[step]);
+        assert_close(cap.values[step], only_a[step]);
+    }
+}
+
+#[tokio::test]
+async fn token_bought_at_the_final_snapshot_is_never_held() {
+    let market = market();
+    let mut state = state_on(&market);
+    state.swap_events[0].timestamp = market.times()[9];
+
+    let comparisons = compare_benchmarks(&cli(), &state, &market, &BTreeMap::new()).await;
+    assert_eq!(comparisons.len(), 1);
+}
+
+#[tokio::test]
+async fn compare_benchmarks_without_supplies_or_purchases() {
+    let market = market();
+    let mut state = state_on(&market);
+
+    let comparisons = compare_benchmarks(&cli(), &state, &market, &BTreeMap::new()).await;
+    assert_eq!(comparisons.len(), 2);
+
+    state.swap_events.clear();
+    let comparisons = compare_benchmarks(&cli(), &state, &market, &BTreeMap::new()).await;
+    assert_eq!(comparisons.len(), 1);
+    assert_eq!(comparisons[0].benchmark, BenchmarkKind::BuyAndHoldNear);
+}
+
+// --- TotalSupplies ---
+
+#[tokio::test]
+async fn total_supplies_reuse_cached_reads() {
+    let supplies = TotalSupplies {
+        cache: Mutex::new(BTreeMap::from([
+            (token("a.near"), Some(1_000.0)),
+            (token("b.near"), None),
+        ])),
+    };
+
+    // Both tokens were read before, so nothing goes to the chain
+    let got = supplies.get(&[token("a.near"), token("b.near")]).await;
+
+    assert_eq!(got, BTreeMap::from([(token("a.near"), 1_000.0)]));
+}
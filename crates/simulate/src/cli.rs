@@ -1,5 +1,7 @@
+use crate::market_impact::ImpactDecay;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
+use trade::tax_lot::LotMethod;
 
 #[derive(Parser, Debug)]
 #[command(name = "simulate", about = "Auto trade backtest simulation")]
@@ -14,6 +16,16 @@ pub enum Command {
     Run(RunArgs),
     /// Verify simulation accuracy against real trades
     Verify(VerifyArgs),
+    /// Report net arbitrage profit per day from the arbitrage ledger
+    ArbitrageReport(ArbitrageReportArgs),
+    /// Replay a production evaluation period and diff the decisions
+    Replay(ReplayArgs),
+    /// Bootstrap the backtest returns and re-run it with noisy predictions
+    Montecarlo(MonteCarloArgs),
+    /// Render a run result or sweep summary JSON as a self-contained HTML report
+    Report(ReportArgs),
+    /// Export FIFO or average-cost tax lots of the trade history as CSV
+    TaxLots(TaxLotsArgs),
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -57,6 +69,15 @@ pub struct RunArgs {
     /// Generate and evaluate predictions for the simulation period before running
     #[arg(long)]
     pub generate_predictions: bool,
+
+    /// Half-life (days) over which simulated pool impact decays back to the
+    /// recorded snapshots; unset or 0 resets it every cycle
+    #[arg(long, conflicts_with = "impact_persistent")]
+    pub impact_half_life_days: Option<f64>,
+
+    /// Keep simulated pool impact for the whole run instead of resetting it every cycle
+    #[arg(long)]
+    pub impact_persistent: bool,
 }
 
 #[derive(Parser, Debug, Clone)]
@@ -74,6 +95,101 @@ pub struct VerifyArgs {
     pub format: OutputFormat,
 }
 
+#[derive(Parser, Debug, Clone)]
+pub struct ArbitrageReportArgs {
+    /// Report start date (YYYY-MM-DD, UTC, inclusive)
+    #[arg(long)]
+    pub start_date: String,
+
+    /// Report end date (YYYY-MM-DD, UTC, inclusive)
+    #[arg(long)]
+    pub end_date: String,
+
+    /// Output format
+    #[arg(long, default_value = "text")]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ReplayArgs {
+    /// Evaluation period to replay
+    #[arg(long)]
+    pub period_id: String,
+
+    /// Tolerance for swap amounts (relative %) and holding weights (percentage points)
+    #[arg(long, default_value = "1.0")]
+    pub tolerance_pct: f64,
+
+    /// Output format
+    #[arg(long, default_value = "text")]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct MonteCarloArgs {
+    /// Backtest to analyse (`--output` receives the Monte Carlo result JSON)
+    #[command(flatten)]
+    pub run: RunArgs,
+
+    /// Number of block-bootstrap resamples of the backtest returns
+    #[arg(long, default_value = "1000")]
+    pub samples: usize,
+
+    /// Block length (days) of the block bootstrap
+    #[arg(long, default_value = "5")]
+    pub block_days: i64,
+
+    /// Re-runs with predictions perturbed by noise calibrated from prediction MAPE (0 = off)
+    #[arg(long, default_value = "0")]
+    pub noise_runs: usize,
+
+    /// Number of noise runs simulated at the same time
+    #[arg(long, default_value = "1")]
+    pub concurrency: usize,
+
+    /// Seed of the bootstrap and the prediction noise
+    #[arg(long, default_value = "0")]
+    pub seed: u64,
+
+    /// Output format
+    #[arg(long, default_value = "text")]
+    pub format: OutputFormat,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct ReportArgs {
+    /// Result JSON of `run` or `sweep_summary.json` of a sweep
+    #[arg(long)]
+    pub input: PathBuf,
+
+    /// Output file path for the HTML report
+    #[arg(long, default_value = "simulation_report.html")]
+    pub output: PathBuf,
+}
+
+#[derive(Parser, Debug, Clone)]
+pub struct TaxLotsArgs {
+    /// Export start date (YYYY-MM-DD, UTC, inclusive)
+    #[arg(long)]
+    pub start_date: String,
+
+    /// Export end date (YYYY-MM-DD, UTC, inclusive)
+    #[arg(long)]
+    pub end_date: String,
+
+    /// Lot method: fifo or average_cost
+    #[arg(long, default_value = "fifo")]
+    pub method: LotMethod,
+
+    /// Local CSV of NEAR fiat prices (`date,price` rows); fiat columns stay empty without it
+    #[arg(long)]
+    pub fiat_prices: Option<PathBuf>,
+
+    /// Output file path for the CSV
+    #[arg(long, default_value = "tax_lots.csv")]
+    pub output: PathBuf,
+}
+
 #[derive(Debug, Clone, clap::ValueEnum)]
 pub enum OutputFormat {
     Text,
@@ -93,6 +209,19 @@ impl RunArgs {
     pub fn parse_end_date(&self) -> anyhow::Result<chrono::NaiveDate> {
         parse_date(&self.end_date, "end-date")
     }
+
+    pub fn impact_decay(&self) -> ImpactDecay {
+        if self.impact_persistent {
+            return ImpactDecay::Persistent;
+        }
+        self.impact_half_life_days
+            .map(|days| {
+                ImpactDecay::half_life(chrono::TimeDelta::seconds(
+                    (days.max(0.0) * 86_400.0) as i64,
+                ))
+            })
+            .unwrap_or_default()
+    }
 }
 
 impl VerifyArgs {
@@ -105,6 +234,26 @@ impl VerifyArgs {
     }
 }
 
+impl ArbitrageReportArgs {
+    pub fn parse_start_date(&self) -> anyhow::Result<chrono::NaiveDate> {
+        parse_date(&self.start_date, "start-date")
+    }
+
+    pub fn parse_end_date(&self) -> anyhow::Result<chrono::NaiveDate> {
+        parse_date(&self.end_date, "end-date")
+    }
+}
+
+impl TaxLotsArgs {
+    pub fn parse_start_date(&self) -> anyhow::Result<chrono::NaiveDate> {
+        parse_date(&self.start_date, "start-date")
+    }
+
+    pub fn parse_end_date(&self) -> anyhow::Result<chrono::NaiveDate> {
+        parse_date(&self.end_date, "end-date")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,6 +270,8 @@ mod tests {
             output: PathBuf::from("test.json"),
             sweep: None,
             generate_predictions: false,
+            impact_half_life_days: None,
+            impact_persistent: false,
         }
     }
 
@@ -178,4 +329,90 @@ mod tests {
         let args = make_verify_args("bad", "2025-06-30");
         assert!(args.parse_start_date().is_err());
     }
+
+    #[test]
+    fn montecarlo_flattens_run_args() {
+        let cli = Cli::try_parse_from([
+            "simulate",
+            "montecarlo",
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-06-30",
+            "--top-tokens",
+            "5",
+            "--noise-runs",
+            "20",
+        ])
+        .unwrap();
+        let Command::Montecarlo(args) = cli.command else {
+            panic!("expected montecarlo command");
+        };
+        assert_eq!(args.run.top_tokens, 5);
+        assert_eq!(args.noise_runs, 20);
+        assert_eq!(args.samples, 1000);
+        assert_eq!(args.block_days, 5);
+    }
+
+    #[test]
+    fn tax_lots_parses_lot_method() {
+        let parse = |extra: &[&str]| {
+            let mut argv = vec![
+                "simulate",
+                "tax-lots",
+                "--start-date",
+                "2025-01-01",
+                "--end-date",
+                "2025-12-31",
+            ];
+            argv.extend_from_slice(extra);
+            Cli::try_parse_from(argv).map(|cli| match cli.command {
+                Command::TaxLots(args) => args,
+                _ => panic!("expected tax-lots command"),
+            })
+        };
+        let args = parse(&[]).unwrap();
+        assert_eq!(args.method, LotMethod::Fifo);
+        assert_eq!(args.output, PathBuf::from("tax_lots.csv"));
+        assert!(args.fiat_prices.is_none());
+
+        let args = parse(&["--method", "average_cost"]).unwrap();
+        assert_eq!(args.method, LotMethod::AverageCost);
+        assert!(parse(&["--method", "lifo"]).is_err());
+    }
+
+    #[test]
+    fn impact_resets_every_cycle_unless_opted_in() {
+        let mut args = make_run_args("2025-06-01", "2025-12-31");
+        assert_eq!(args.impact_decay(), ImpactDecay::PerCycle);
+
+        args.impact_half_life_days = Some(0.0);
+        assert_eq!(args.impact_decay(), ImpactDecay::PerCycle);
+
+        args.impact_half_life_days = Some(1.5);
+        assert_eq!(
+            args.impact_decay(),
+            ImpactDecay::HalfLife(chrono::TimeDelta::hours(36))
+        );
+
+        args.impact_half_life_days = None;
+        args.impact_persistent = true;
+        assert_eq!(args.impact_decay(), ImpactDecay::Persistent);
+    }
+
+    #[test]
+    fn impact_persistent_conflicts_with_half_life() {
+        let result = Cli::try_parse_from([
+            "simulate",
+            "run",
+            "--start-date",
+            "2025-06-01",
+            "--end-date",
+            "2025-12-31",
+            "--impact-persistent",
+            "--impact-half-life-days",
+            "2",
+        ]);
+        assert!(result.is_err());
+    }
 }
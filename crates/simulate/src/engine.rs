@@ -1,4 +1,6 @@
+use crate::benchmark::{self, TotalSupplies};
 use crate::cli::RunArgs;
+use crate::market_impact::MarketImpact;
 use crate::mock_client::SimulationClient;
 use crate::mock_wallet::SimulationWallet;
 use crate::output::SimulationResult;
@@ -6,13 +8,33 @@ use crate::portfolio_state::{DbRateProvider, PortfolioState};
 use anyhow::Result;
 use bigdecimal::BigDecimal;
 use chrono::{NaiveTime, TimeZone, Utc};
+use common::config::ConfigOverlay;
 use common::types::YoctoValue;
 use logging::*;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use tokio::sync::Mutex;
+use trade::strategy::PredictionHook;
 
-pub async fn run_simulation(cli: &RunArgs) -> Result<SimulationResult> {
+/// Run a simulation; `supplies` is shared by every run of one invocation so
+/// the market-cap benchmark reads each token's supply at most once.
+pub async fn run_simulation(cli: &RunArgs, supplies: &TotalSupplies) -> Result<SimulationResult> {
+    // CLI parameters apply to this run only (the global config store is untouched)
+    run_simulation_with_config(cli, &config_overlay(cli), None, supplies).await
+}
+
+/// Run a simulation with an explicit per-run config, normally
+/// [`config_overlay`] of `cli` with extra overrides on top.
+///
+/// `prediction_hook` rewrites the predictions each cycle loads (e.g. to add
+/// noise); production cycles never take one.
+pub async fn run_simulation_with_config(
+    cli: &RunArgs,
+    cfg: &ConfigOverlay,
+    prediction_hook: Option<&dyn PredictionHook>,
+    supplies: &TotalSupplies,
+) -> Result<SimulationResult> {
     let log = DEFAULT.new(o!("function" => "run_simulation"));
 
     let start_date = cli.parse_start_date()?;
@@ -25,15 +47,21 @@ pub async fn run_simulation(cli: &RunArgs) -> Result<SimulationResult> {
             end_date
         ));
     }
-
-    // Apply CLI parameters to config
-    apply_config(cli);
+    if !(cli.initial_capital.is_finite()
+        && cli.initial_capital > 0.0
+        && cli.initial_capital <= f64::from(u32::MAX))
+    {
+        return Err(anyhow::anyhow!(
+            "initial-capital must be a positive number of NEAR up to {}: {}",
+            u32::MAX,
+            cli.initial_capital
+        ));
+    }
 
     // Generate predictions if requested
     if cli.generate_predictions {
-        let cfg = common::config::ConfigResolver;
         info!(log, "generating predictions for simulation period");
-        crate::prediction::generate_predictions_for_range(start_date, end_date, &cfg).await?;
+        crate::prediction::generate_predictions_for_range(start_date, end_date, cfg).await?;
     }
 
     // Initialize token decimals cache from DB
@@ -62,7 +90,8 @@ pub async fn run_simulation(cli: &RunArgs) -> Result<SimulationResult> {
         Arc::clone(&portfolio),
         initial_capital_yocto,
         Arc::clone(&sim_day_shared),
-    );
+    )
+    .with_market_impact(MarketImpact::new(cli.impact_decay()));
     let sim_wallet = SimulationWallet::new();
 
     info!(log, "starting simulation";
@@ -110,8 +139,15 @@ pub async fn run_simulation(cli: &RunArgs) -> Result<SimulationResult> {
         info!(log, "simulation day"; "date" => %current_date, "day" => day_count, "sim_day" => %sim_day);
 
         // Execute the full trading cycle via trade::strategy::start
-        let cfg = common::config::ConfigResolver;
-        if let Err(e) = trade::strategy::start(&sim_client, &sim_wallet, sim_day, &cfg).await {
+        if let Err(e) = trade::strategy::start_with_prediction_hook(
+            &sim_client,
+            &sim_wallet,
+            sim_day,
+            cfg,
+            prediction_hook,
+        )
+        .await
+        {
             warn!(log, "trading cycle failed"; "date" => %current_date, "error" => ?e);
         }
 
@@ -145,7 +181,11 @@ pub async fn run_simulation(cli: &RunArgs) -> Result<SimulationResult> {
 
     // Build result
     let state = portfolio.lock().await;
-    let result = SimulationResult::from_state(cli, &state)?;
+    let mut result = SimulationResult::from_state(cli, &state)?;
+    let purchased: Vec<_> = benchmark::first_purchases(&state).into_keys().collect();
+    let total_supplies = supplies.get(&purchased).await;
+    result.benchmarks =
+        benchmark::compare_benchmarks(cli, &state, &DbRateProvider, &total_supplies).await;
 
     info!(log, "simulation completed";
         "days" => day_count,
@@ -156,26 +196,58 @@ pub async fn run_simulation(cli: &RunArgs) -> Result<SimulationResult> {
     Ok(result)
 }
 
-/// Apply CLI parameters to the config system
-pub(crate) fn apply_config(cli: &RunArgs) {
-    common::config::store::set("TRADE_TOP_TOKENS", &cli.top_tokens.to_string());
-    common::config::store::set(
-        "TRADE_PRICE_HISTORY_DAYS",
-        &cli.price_history_days.to_string(),
-    );
-    common::config::store::set(
-        "PORTFOLIO_REBALANCE_THRESHOLD",
-        &cli.rebalance_threshold.to_string(),
-    );
-    common::config::store::set("TRADE_INITIAL_INVESTMENT", &cli.initial_capital.to_string());
-    // Enable trading (mock client prevents real transactions)
-    common::config::store::set("TRADE_ENABLED", "true");
+/// Distinguishes runs started within the same microsecond.
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Evaluation period prefix unique to one simulation run.
+///
+/// Periods created under it never match production's `eval_` prefix or
+/// another run's, even when runs share the database concurrently.
+pub(crate) fn run_prefix() -> String {
+    format!(
+        "sim_{}_{}_",
+        Utc::now().timestamp_micros(),
+        RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// `TRADE_INITIAL_INVESTMENT` (whole NEAR) for `capital` NEAR.
+///
+/// A fractional value would not parse and silently fall back to the default.
+/// Rounding up lets `prepare_funds` invest the whole simulated balance, since
+/// it takes the smaller of this amount and the balance. Walk-forward runs
+/// carry fractional capital between windows, so this can't be left to the CLI.
+fn initial_investment(capital: f64) -> u32 {
+    capital.ceil() as u32
+}
+
+/// Build the per-run config from CLI parameters.
+///
+/// Each run also gets its own evaluation period prefix ([`run_prefix`]).
+pub(crate) fn config_overlay(cli: &RunArgs) -> ConfigOverlay {
+    ConfigOverlay::new()
+        .with("TRADE_TOP_TOKENS", cli.top_tokens)
+        .with("TRADE_PRICE_HISTORY_DAYS", cli.price_history_days)
+        .with("PORTFOLIO_REBALANCE_THRESHOLD", cli.rebalance_threshold)
+        .with(
+            "TRADE_INITIAL_INVESTMENT",
+            initial_investment(cli.initial_capital),
+        )
+        // Enable trading (mock client prevents real transactions)
+        .with("TRADE_ENABLED", true)
+        .with("TRADE_EVALUATION_PERIOD_PREFIX", run_prefix())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::config::ConfigAccess;
+    use persistence::memory::MemoryStore;
+    use persistence::pending_transaction::PendingTransactionStatus;
     use std::path::PathBuf;
+    use trade::synthetic_market::{
+        PriceModel, SyntheticMarket, SyntheticMarketConfig, SyntheticTokenConfig,
+    };
 
     fn make_cli(start: &str, end: &str) -> RunArgs {
         RunArgs {
@@ -189,13 +261,17 @@ mod tests {
             output: PathBuf::from("test.json"),
             sweep: None,
             generate_predictions: false,
+            impact_half_life_days: None,
+            impact_persistent: false,
         }
     }
 
     #[tokio::test]
     async fn run_simulation_rejects_start_after_end() {
         let cli = make_cli("2025-06-15", "2025-06-01");
-        let err = run_simulation(&cli).await.unwrap_err();
+        let err = run_simulation(&cli, &TotalSupplies::default())
+            .await
+            .unwrap_err();
         assert!(
             err.to_string()
                 .contains("start-date must be before end-date"),
@@ -204,7 +280,7 @@ mod tests {
     }
 
     #[test]
-    fn apply_config_sets_expected_values() {
+    fn config_overlay_sets_expected_values() {
         let cli = RunArgs {
             start_date: "2025-01-01".to_string(),
             end_date: "2025-12-31".to_string(),
@@ -216,26 +292,142 @@ mod tests {
             output: PathBuf::from("test.json"),
             sweep: None,
             generate_predictions: false,
+            impact_half_life_days: None,
+            impact_persistent: false,
         };
 
-        apply_config(&cli);
+        let cfg = config_overlay(&cli);
 
-        assert_eq!(
-            common::config::store::get("TRADE_TOP_TOKENS").unwrap(),
-            "20"
-        );
-        assert_eq!(
-            common::config::store::get("TRADE_PRICE_HISTORY_DAYS").unwrap(),
-            "60"
-        );
-        assert_eq!(
-            common::config::store::get("PORTFOLIO_REBALANCE_THRESHOLD").unwrap(),
-            "0.25"
-        );
-        assert_eq!(
-            common::config::store::get("TRADE_INITIAL_INVESTMENT").unwrap(),
-            "500"
+        assert_eq!(cfg.trade_top_tokens(), 20);
+        assert_eq!(cfg.trade_price_history_days(), 60);
+        assert_eq!(cfg.portfolio_rebalance_threshold(), 0.25);
+        assert_eq!(cfg.trade_initial_investment(), 500);
+        assert!(cfg.trade_enabled());
+        assert!(cfg.trade_evaluation_period_prefix().starts_with("sim_"));
+        // Nothing leaks into the global store
+        assert!(common::config::store::get("TRADE_EVALUATION_PERIOD_PREFIX").is_err());
+    }
+
+    #[tokio::test]
+    async fn run_simulation_rejects_non_positive_capital() {
+        for capital in [0.0, -1.0, f64::NAN] {
+            let mut cli = make_cli("2025-06-01", "2025-06-15");
+            cli.initial_capital = capital;
+            let err = run_simulation(&cli, &TotalSupplies::default())
+                .await
+                .unwrap_err();
+            assert!(
+                err.to_string().contains("initial-capital must be"),
+                "unexpected error: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn config_overlay_rounds_fractional_capital_up() {
+        let mut cli = make_cli("2025-01-01", "2025-01-31");
+        cli.initial_capital = 123.4;
+
+        assert_eq!(config_overlay(&cli).trade_initial_investment(), 124);
+    }
+
+    #[test]
+    fn config_overlay_prefix_is_unique_per_run() {
+        let cli = make_cli("2025-01-01", "2025-01-31");
+
+        let first = config_overlay(&cli).trade_evaluation_period_prefix();
+        let second = config_overlay(&cli).trade_evaluation_period_prefix();
+
+        assert_ne!(first, second);
+    }
+
+    fn synthetic_market() -> SyntheticMarket {
+        let token = |account: &str, initial_price: f64| SyntheticTokenConfig {
+            account: account.parse().unwrap(),
+            decimals: 18,
+            initial_price,
+            drift: 0.5,
+            volatility: 0.6,
+            pool_depth_near: 10_000.0,
+        };
+        SyntheticMarket::generate(&SyntheticMarketConfig {
+            seed: 11,
+            start: Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap(),
+            steps: 40,
+            step_hours: 24,
+            tokens: vec![token("token-a.near", 0.5), token("token-b.near", 2.0)],
+            correlation: 0.3,
+            model: PriceModel::Gbm,
+            prediction_horizon_steps: 1,
+            prediction_error: 0.0,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn strategy_cycle_runs_on_synthetic_market_without_db() {
+        let market = Arc::new(synthetic_market());
+        let sim_day = market.times()[31];
+        let store = MemoryStore::new();
+        market.seed(&store, sim_day);
+
+        let capital = YoctoValue::from_yocto(BigDecimal::from(100u128 * 10u128.pow(24)));
+        let portfolio = Arc::new(Mutex::new(PortfolioState::new(capital.clone())));
+        let client = SimulationClient::new(portfolio, capital, Arc::new(Mutex::new(sim_day)))
+            .with_synthetic_market(Arc::clone(&market));
+        let wallet = SimulationWallet::new();
+        let cfg = ConfigOverlay::new()
+            .with("TRADE_ENABLED", true)
+            .with("TRADE_INITIAL_INVESTMENT", 100)
+            .with("TRADE_EVALUATION_PERIOD_PREFIX", run_prefix());
+
+        let report = store
+            .scope(trade::strategy::start(&client, &wallet, sim_day, &cfg))
+            .await
+            .unwrap();
+
+        assert_eq!(report.halted, None);
+        assert!(report.is_new_period);
+        assert!(!report.selected_tokens.is_empty());
+
+        // The period and its selected tokens were written to the store
+        let periods = store.evaluation_periods();
+        assert_eq!(periods.len(), 1);
+        assert_eq!(periods[0].period_id, report.period_id);
+        let selected: Vec<String> = periods[0]
+            .selected_tokens
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .flatten()
+            .collect();
+        let expected: Vec<String> = report
+            .selected_tokens
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        assert_eq!(selected, expected);
+
+        // Every swap went through the journal and was recorded for the period
+        let pending = store.pending_transactions();
+        let completed = pending
+            .iter()
+            .filter(|entry| entry.status().unwrap() == PendingTransactionStatus::Completed)
+            .count();
+        assert!(pending.iter().all(|entry| {
+            !PendingTransactionStatus::UNRESOLVED.contains(&entry.status().unwrap())
+        }));
+        let trades = store.trade_transactions();
+        assert_eq!(trades.len(), completed);
+        assert!(
+            trades
+                .iter()
+                .all(|tx| tx.evaluation_period_id == report.period_id)
         );
-        assert_eq!(common::config::store::get("TRADE_ENABLED").unwrap(), "true");
+
+        let holdings = store.portfolio_holdings();
+        assert_eq!(holdings.len(), 1);
+        assert_eq!(holdings[0].evaluation_period_id, report.period_id);
+        assert_eq!(holdings[0].timestamp, sim_day.naive_utc());
     }
 }
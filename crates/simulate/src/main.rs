@@ -1,13 +1,20 @@
 #![deny(warnings)]
 
+mod arbitrage_report;
+mod benchmark;
 mod cli;
 mod engine;
+mod market_impact;
 mod mock_client;
 mod mock_wallet;
+mod montecarlo;
 mod output;
 mod portfolio_state;
 mod prediction;
+mod replay;
+mod report;
 mod sweep;
+mod tax_lots;
 mod verify;
 
 use clap::Parser;
@@ -23,6 +30,11 @@ async fn main() -> anyhow::Result<()> {
     match cli.command {
         Command::Run(ref args) => run_simulation_command(args, &log).await,
         Command::Verify(ref args) => verify::run_verify(args).await,
+        Command::ArbitrageReport(ref args) => arbitrage_report::run_arbitrage_report(args).await,
+        Command::Replay(ref args) => replay::run_replay(args).await,
+        Command::Montecarlo(ref args) => montecarlo::run_montecarlo(args).await,
+        Command::Report(ref args) => report::run_report(args),
+        Command::TaxLots(ref args) => tax_lots::run_tax_lots(args).await,
     }
 }
 
@@ -32,7 +44,7 @@ async fn run_simulation_command(args: &cli::RunArgs, log: &slog::Logger) -> anyh
         sweep::run_sweep(args, sweep_path).await?;
     } else {
         info!(log, "running single simulation");
-        let result = engine::run_simulation(args).await?;
+        let result = engine::run_simulation(args, &benchmark::TotalSupplies::default()).await?;
         result.write_to_file(&args.output)?;
         info!(log, "results written"; "path" => args.output.display().to_string());
 
@@ -71,6 +83,20 @@ async fn run_simulation_command(args: &cli::RunArgs, log: &slog::Logger) -> anyh
         } else {
             println!("Trades: {}", perf.trade_count);
         }
+        if !result.benchmarks.is_empty() {
+            println!("---");
+            for b in &result.benchmarks {
+                println!(
+                    "vs {}: return {:+.2}%, alpha {:+.3}, beta {:.3}, tracking error {:.3}, IR {:.3}",
+                    b.benchmark,
+                    b.total_return * 100.0,
+                    b.alpha,
+                    b.beta,
+                    b.tracking_error,
+                    b.information_ratio
+                );
+            }
+        }
     }
 
     Ok(())
@@ -0,0 +1,148 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use dex::{PoolInfo, PoolInfoList};
+use near_sdk::json_types::U128;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// One hop of a simulated swap, as priced against a pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolHop {
+    pub pool_id: u32,
+    pub token_in: usize,
+    pub amount_in: u128,
+    pub token_out: usize,
+    pub amount_out: u128,
+}
+
+/// Overlay of the reserve changes caused by our own simulated swaps.
+///
+/// Historical pool snapshots never reflect the simulated trades, so without
+/// this every swap is priced as if it were the first one into the pool. The
+/// overlay accumulates per-pool reserve deltas and applies them on top of
+/// whatever snapshot is read for the current simulation day.
+///
+/// How long the deltas are kept is set by [`ImpactDecay`]; by default they are
+/// dropped as soon as the simulation moves to a later time.
+#[derive(Debug, Default)]
+pub struct MarketImpact {
+    decay: ImpactDecay,
+    as_of: Option<DateTime<Utc>>,
+    /// pool id -> reserve delta per token index
+    deltas: BTreeMap<u32, Vec<i128>>,
+}
+
+/// How the reserve deltas of [`MarketImpact`] fade between cycles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImpactDecay {
+    /// Drop the deltas as soon as simulated time moves on: only swaps within
+    /// one cycle see each other.
+    #[default]
+    PerCycle,
+    /// Decay the deltas exponentially toward the recorded snapshot.
+    HalfLife(TimeDelta),
+    /// Keep the deltas for the whole run.
+    Persistent,
+}
+
+impl ImpactDecay {
+    /// A non-positive half-life is the same as resetting every cycle.
+    pub fn half_life(half_life: TimeDelta) -> Self {
+        if half_life <= TimeDelta::zero() {
+            Self::PerCycle
+        } else {
+            Self::HalfLife(half_life)
+        }
+    }
+}
+
+impl MarketImpact {
+    pub fn new(decay: ImpactDecay) -> Self {
+        Self {
+            decay,
+            ..Default::default()
+        }
+    }
+
+    /// Decay the deltas from the last observed time up to `at`.
+    fn advance_to(&mut self, at: DateTime<Utc>) {
+        let Some(as_of) = self.as_of.replace(at) else {
+            return;
+        };
+        let elapsed = at - as_of;
+        if elapsed <= TimeDelta::zero() {
+            // Time never moves backwards within a run; keep the later mark.
+            self.as_of = Some(as_of.max(at));
+            return;
+        }
+        let half_life = match self.decay {
+            ImpactDecay::Persistent => return,
+            ImpactDecay::HalfLife(half_life) if half_life > TimeDelta::zero() => half_life,
+            ImpactDecay::PerCycle | ImpactDecay::HalfLife(_) => {
+                self.deltas.clear();
+                return;
+            }
+        };
+        let factor = 0.5f64.powf(elapsed.as_seconds_f64() / half_life.as_seconds_f64());
+        self.deltas.retain(|_, deltas| {
+            for delta in deltas.iter_mut() {
+                *delta = (*delta as f64 * factor) as i128;
+            }
+            deltas.iter().any(|d| *d != 0)
+        });
+    }
+
+    /// Return `pools` with the accumulated deltas applied to their reserves.
+    ///
+    /// Reserves are floored at 1 so an overlay built on a larger snapshot can
+    /// never drain a pool read from a later, thinner one.
+    pub fn apply(&mut self, pools: &PoolInfoList, at: DateTime<Utc>) -> PoolInfoList {
+        self.advance_to(at);
+        if self.deltas.is_empty() {
+            return pools.clone();
+        }
+        let adjusted = self
+            .deltas
+            .iter()
+            .filter_map(|(pool_id, deltas)| {
+                let pool = pools.get(*pool_id).ok()?;
+                let mut bare = pool.bare.clone();
+                for (amount, delta) in bare.amounts.iter_mut().zip(deltas) {
+                    let shifted = i128::try_from(amount.0)
+                        .unwrap_or(i128::MAX)
+                        .saturating_add(*delta)
+                        .max(1);
+                    *amount = U128(shifted as u128);
+                }
+                Some(Arc::new(PoolInfo::new(pool.id, bare, pool.timestamp)))
+            })
+            .collect();
+        pools.with_replaced(adjusted)
+    }
+
+    /// Record the reserve changes of an executed swap.
+    ///
+    /// The whole input stays in the pool (fees are paid to LPs), and the
+    /// output leaves it.
+    pub fn record(&mut self, hops: &[PoolHop], at: DateTime<Utc>) {
+        self.advance_to(at);
+        for hop in hops {
+            let deltas = self.deltas.entry(hop.pool_id).or_default();
+            let len = hop.token_in.max(hop.token_out) + 1;
+            if deltas.len() < len {
+                deltas.resize(len, 0);
+            }
+            deltas[hop.token_in] = deltas[hop.token_in]
+                .saturating_add(i128::try_from(hop.amount_in).unwrap_or(i128::MAX));
+            deltas[hop.token_out] = deltas[hop.token_out]
+                .saturating_sub(i128::try_from(hop.amount_out).unwrap_or(i128::MAX));
+        }
+    }
+
+    /// Number of pools currently shifted away from their snapshot.
+    pub fn impacted_pools(&self) -> usize {
+        self.deltas.len()
+    }
+}
+
+#[cfg(test)]
+mod tests;
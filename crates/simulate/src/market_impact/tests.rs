@@ -0,0 +1,135 @@
+use super::*;
+use chrono::TimeZone;
+use dex::test_helpers::simple_pool_bare;
+
+fn day(d: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 6, d, 0, 0, 0).unwrap()
+}
+
+fn pool_list(amount_a: u128, amount_b: u128) -> PoolInfoList {
+    PoolInfoList::new(vec![Arc::new(PoolInfo::new(
+        1,
+        simple_pool_bare(["wrap.near", "token.near"], [amount_a, amount_b]),
+        day(1).naive_utc(),
+    ))])
+}
+
+fn reserves(pools: &PoolInfoList) -> Vec<u128> {
+    pools
+        .get(1)
+        .unwrap()
+        .bare
+        .amounts
+        .iter()
+        .map(|a| a.0)
+        .collect()
+}
+
+fn buy(amount_in: u128, amount_out: u128) -> PoolHop {
+    PoolHop {
+        pool_id: 1,
+        token_in: 0,
+        amount_in,
+        token_out: 1,
+        amount_out,
+    }
+}
+
+#[test]
+fn apply_without_swaps_returns_snapshot() {
+    let mut impact = MarketImpact::default();
+    let pools = pool_list(1_000, 5_000);
+
+    assert_eq!(impact.apply(&pools, day(1)), pools);
+}
+
+#[test]
+fn recorded_swap_shifts_reserves() {
+    let mut impact = MarketImpact::default();
+    impact.record(&[buy(100, 400)], day(1));
+
+    let adjusted = impact.apply(&pool_list(1_000, 5_000), day(1));
+
+    assert_eq!(reserves(&adjusted), vec![1_100, 4_600]);
+    assert_eq!(impact.impacted_pools(), 1);
+}
+
+#[test]
+fn consecutive_swaps_get_worse_prices() {
+    let mut impact = MarketImpact::default();
+    let snapshot = pool_list(1_000_000, 5_000_000);
+    let first = impact
+        .apply(&snapshot, day(1))
+        .get(1)
+        .unwrap()
+        .estimate_return(dex::TokenIn::from(0), 10_000, dex::TokenOut::from(1))
+        .unwrap();
+    impact.record(&[buy(10_000, first)], day(1));
+
+    let second = impact
+        .apply(&snapshot, day(1))
+        .get(1)
+        .unwrap()
+        .estimate_return(dex::TokenIn::from(0), 10_000, dex::TokenOut::from(1))
+        .unwrap();
+
+    assert!(second < first, "second={second} first={first}");
+}
+
+#[test]
+fn default_resets_on_next_cycle() {
+    let mut impact = MarketImpact::default();
+    impact.record(&[buy(100, 400)], day(1));
+
+    let adjusted = impact.apply(&pool_list(1_000, 5_000), day(2));
+
+    assert_eq!(reserves(&adjusted), vec![1_000, 5_000]);
+    assert_eq!(impact.impacted_pools(), 0);
+}
+
+#[test]
+fn persistent_deltas_survive_across_days() {
+    let mut impact = MarketImpact::new(ImpactDecay::Persistent);
+    impact.record(&[buy(100, 400)], day(1));
+
+    let adjusted = impact.apply(&pool_list(1_000, 5_000), day(10));
+
+    assert_eq!(reserves(&adjusted), vec![1_100, 4_600]);
+}
+
+#[test]
+fn half_life_decays_toward_snapshot() {
+    let mut impact = MarketImpact::new(ImpactDecay::half_life(TimeDelta::days(1)));
+    impact.record(&[buy(100, 400)], day(1));
+
+    let adjusted = impact.apply(&pool_list(1_000, 5_000), day(2));
+
+    assert_eq!(reserves(&adjusted), vec![1_050, 4_800]);
+}
+
+#[test]
+fn zero_half_life_resets_on_next_cycle() {
+    assert_eq!(
+        ImpactDecay::half_life(TimeDelta::zero()),
+        ImpactDecay::PerCycle
+    );
+    let mut impact = MarketImpact::new(ImpactDecay::HalfLife(TimeDelta::zero()));
+    impact.record(&[buy(100, 400)], day(1));
+    assert_eq!(impact.impacted_pools(), 1);
+
+    let adjusted = impact.apply(&pool_list(1_000, 5_000), day(2));
+
+    assert_eq!(reserves(&adjusted), vec![1_000, 5_000]);
+    assert_eq!(impact.impacted_pools(), 0);
+}
+
+#[test]
+fn reserves_never_drop_below_one() {
+    let mut impact = MarketImpact::default();
+    impact.record(&[buy(100, 4_000)], day(1));
+
+    // A later, thinner snapshot than the one the swap was priced on
+    let adjusted = impact.apply(&pool_list(1_000, 1_000), day(1));
+
+    assert_eq!(reserves(&adjusted), vec![1_100, 1]);
+}
@@ -1,15 +1,18 @@
+use crate::market_impact::{MarketImpact, PoolHop};
 use crate::portfolio_state::{
-    self, DEFAULT_DECIMALS, PortfolioState, SwapEvent, SwapMethod, SwapResult, to_u128_or_warn,
+    DEFAULT_DECIMALS, DbRateProvider, PortfolioState, RateProvider, SwapEvent, SwapMethod,
+    SwapResult, to_u128_or_warn,
 };
 use bigdecimal::{BigDecimal, RoundingMode};
 use blockchain::jsonrpc::{AccountInfo, GasInfo, SendTx, SentTx, ViewContract};
 use blockchain::ref_finance::swap::SwapAction;
 use blockchain::types::gas_price::GasPrice;
 use chrono::{DateTime, Utc};
-use common::types::{TokenAccount, TokenAmount, YoctoValue};
+use common::types::{ExchangeRate, TokenAccount, TokenAmount, TokenOutAccount, YoctoValue};
 use logging::*;
 use near_crypto::InMemorySigner;
 use near_primitives::action::Action;
+use near_primitives::hash::CryptoHash;
 use near_primitives::types::BlockId;
 use near_primitives::views::{
     CallResult, FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum,
@@ -20,12 +23,14 @@ use serde_json::json;
 use std::collections::BTreeSet;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use trade::synthetic_market::SyntheticMarket;
 
 /// Simulated NEAR blockchain client for backtesting.
 ///
 /// **Lock ordering**: When acquiring multiple locks, always lock `sim_day`
 /// before `portfolio` to avoid deadlocks. This order must be consistent
-/// across all call sites.
+/// across all call sites. `market_impact` is never held together with
+/// either of them.
 pub struct SimulationClient {
     portfolio: Arc<Mutex<PortfolioState>>,
     initial_native: YoctoValue,
@@ -36,6 +41,11 @@ pub struct SimulationClient {
     /// path (which bypasses the storage cap), matching production behavior
     /// on a fresh account.
     registered: Arc<Mutex<BTreeSet<TokenAccount>>>,
+    /// Pool reserve changes caused by earlier simulated swaps.
+    market_impact: Arc<Mutex<MarketImpact>>,
+    /// In-memory market data replacing the DB pool snapshots, rates and
+    /// token decimals. `None` reads everything from the DB.
+    synthetic_market: Option<Arc<SyntheticMarket>>,
 }
 
 /// REF Finance `storage_balance_bounds.min` value used as the per-token
@@ -73,6 +83,25 @@ impl SimulationClient {
             initial_native,
             sim_day,
             registered: Arc::new(Mutex::new(BTreeSet::new())),
+            market_impact: Arc::new(Mutex::new(MarketImpact::default())),
+            synthetic_market: None,
+        }
+    }
+
+    /// Replace the market-impact overlay (default: deltas reset every cycle).
+    pub fn with_market_impact(self, market_impact: MarketImpact) -> Self {
+        Self {
+            market_impact: Arc::new(Mutex::new(market_impact)),
+            ..self
+        }
+    }
+
+    /// Serve pools, rates and decimals from `market` instead of the DB.
+    #[cfg(test)]
+    pub fn with_synthetic_market(self, market: Arc<SyntheticMarket>) -> Self {
+        Self {
+            synthetic_market: Some(market),
+            ..self
         }
     }
 
@@ -108,12 +137,26 @@ fn estimate_swap_via_pools(
     swap_actions: &[SwapAction],
     amount_in: u128,
 ) -> Option<u128> {
+    trace_swap_via_pools(pools, swap_actions, amount_in)?
+        .last()
+        .map(|hop| hop.amount_out)
+        .or(Some(amount_in))
+}
+
+/// Same walk as [`estimate_swap_via_pools`], keeping each hop's amounts so
+/// the swap can be recorded in the market-impact overlay.
+fn trace_swap_via_pools(
+    pools: &dex::PoolInfoList,
+    swap_actions: &[SwapAction],
+    amount_in: u128,
+) -> Option<Vec<PoolHop>> {
     debug_assert!(
         swap_actions
             .array_windows::<2>()
             .all(|[a, b]| a.token_out == b.token_in),
         "swap action chain is not connected"
     );
+    let mut hops = Vec::with_capacity(swap_actions.len());
     let mut current_amount = amount_in;
     for action in swap_actions {
         let pool = pools.get(action.pool_id).ok()?;
@@ -123,15 +166,39 @@ fn estimate_swap_via_pools(
         let out_idx = pool
             .tokens()
             .position(|t| t.as_account_id() == &action.token_out)?;
-        current_amount = pool
+        let amount_out = pool
             .estimate_return(
                 dex::TokenIn::from(in_idx),
                 current_amount,
                 dex::TokenOut::from(out_idx),
             )
             .ok()?;
+        hops.push(PoolHop {
+            pool_id: action.pool_id,
+            token_in: in_idx,
+            amount_in: current_amount,
+            token_out: out_idx,
+            amount_out,
+        });
+        current_amount = amount_out;
+    }
+    Some(hops)
+}
+
+/// Output `hops` would give at the pools' reserve ratios before the swap,
+/// i.e. without fee or price impact (the marginal price of a SIMPLE_POOL).
+fn spot_amount_out(pools: &dex::PoolInfoList, hops: &[PoolHop]) -> Option<f64> {
+    let mut amount = hops.first()?.amount_in as f64;
+    for hop in hops {
+        let pool = pools.get(hop.pool_id).ok()?;
+        let reserve_in = pool.bare.amounts.get(hop.token_in)?.0 as f64;
+        let reserve_out = pool.bare.amounts.get(hop.token_out)?.0 as f64;
+        if reserve_in == 0.0 {
+            return None;
+        }
+        amount *= reserve_out / reserve_in;
     }
-    Some(current_amount)
+    Some(amount)
 }
 
 impl SimulationClient {
@@ -143,14 +210,55 @@ impl SimulationClient {
         amount_in: u128,
         sim_day: DateTime<Utc>,
     ) -> Option<u128> {
-        let log = DEFAULT.new(o!("function" => "calculate_swap_output_via_pools"));
-        let pools = persistence::pool_info::read_from_db(Some(sim_day.naive_utc()))
-            .await
-            .inspect_err(|e| warn!(log, "failed to read pool data from DB"; "error" => %e))
-            .ok()?;
+        let pools = self.pools_with_impact(sim_day).await?;
         estimate_swap_via_pools(&pools, swap_actions, amount_in)
     }
 
+    /// Same as `calculate_swap_output_via_pools`, keeping the per-hop amounts
+    /// and the pools they were computed from.
+    async fn trace_swap_with_impact(
+        &self,
+        swap_actions: &[SwapAction],
+        amount_in: u128,
+        sim_day: DateTime<Utc>,
+    ) -> Option<(dex::PoolInfoList, Vec<PoolHop>)> {
+        let pools = self.pools_with_impact(sim_day).await?;
+        let hops = trace_swap_via_pools(&pools, swap_actions, amount_in)?;
+        Some((pools, hops))
+    }
+
+    /// Pool snapshot for `sim_day` with the market-impact overlay applied.
+    async fn pools_with_impact(&self, sim_day: DateTime<Utc>) -> Option<dex::PoolInfoList> {
+        let log = DEFAULT.new(o!("function" => "pools_with_impact"));
+        let snapshot = match &self.synthetic_market {
+            Some(market) => Arc::new(market.pools_at(sim_day)?),
+            None => persistence::pool_info::read_from_db(Some(sim_day.naive_utc()))
+                .await
+                .inspect_err(|e| warn!(log, "failed to read pool data from DB"; "error" => %e))
+                .ok()?,
+        };
+        Some(self.market_impact.lock().await.apply(&snapshot, sim_day))
+    }
+
+    /// Rate of `token` against wNEAR at `sim_day`.
+    async fn rate_at(
+        &self,
+        token: &TokenOutAccount,
+        sim_day: DateTime<Utc>,
+    ) -> Option<ExchangeRate> {
+        match &self.synthetic_market {
+            Some(market) => market.get_rate(token, sim_day).await,
+            None => DbRateProvider.get_rate(token, sim_day).await,
+        }
+    }
+
+    fn decimals_of(&self, token: &TokenAccount) -> u8 {
+        self.synthetic_market
+            .as_ref()
+            .and_then(|market| market.decimals(token))
+            .unwrap_or_else(|| decimals_for(token))
+    }
+
     /// Fallback: calculate swap output using DB rates (no fee/slippage).
     async fn calculate_swap_output_via_rates(
         &self,
@@ -161,7 +269,6 @@ impl SimulationClient {
     ) -> u128 {
         use common::types::{TokenAmount, YoctoValue};
         let wnear = &*blockchain::ref_finance::token_account::WNEAR_TOKEN;
-        let wnear_in = blockchain::ref_finance::token_account::WNEAR_TOKEN.to_in();
 
         // token_in -> NEAR value
         let near_value = if token_in == wnear {
@@ -169,13 +276,11 @@ impl SimulationClient {
         } else {
             let token_in_out = token_in.to_out();
 
-            let rate =
-                match portfolio_state::get_rate_at_date(&token_in_out, &wnear_in, sim_day).await {
-                    Some(r) => r,
-                    None => return 0,
-                };
+            let Some(rate) = self.rate_at(&token_in_out, sim_day).await else {
+                return 0;
+            };
 
-            let decimals_in = decimals_for(token_in);
+            let decimals_in = self.decimals_of(token_in);
             let token_amount =
                 TokenAmount::from_smallest_units(BigDecimal::from(amount_in), decimals_in);
             &token_amount / &rate
@@ -190,17 +295,50 @@ impl SimulationClient {
         } else {
             let token_out_out = token_out.to_out();
 
-            let rate =
-                match portfolio_state::get_rate_at_date(&token_out_out, &wnear_in, sim_day).await {
-                    Some(r) => r,
-                    None => return 0,
-                };
+            let Some(rate) = self.rate_at(&token_out_out, sim_day).await else {
+                return 0;
+            };
 
             let token_amount = &near_value * &rate;
             to_u128_or_warn(token_amount.smallest_units(), "swap_rate_token_amount")
         }
     }
 
+    /// Quote REF's `get_return` view the same way `handle_swap` prices a
+    /// single action, so the production swap preflight sees the output the
+    /// simulated swap will actually produce.
+    async fn handle_get_return(&self, args_value: serde_json::Value) -> anyhow::Result<u128> {
+        let sim_day = *self.sim_day.lock().await;
+
+        let pool_id = args_value
+            .get("pool_id")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow::anyhow!("get_return: missing pool_id"))?;
+        let amount_in: U128 = serde_json::from_value(args_value["amount_in"].clone())?;
+        let action = SwapAction {
+            pool_id: u32::try_from(pool_id)?,
+            token_in: serde_json::from_value(args_value["token_in"].clone())?,
+            amount_in: Some(amount_in),
+            token_out: serde_json::from_value(args_value["token_out"].clone())?,
+            min_amount_out: U128(0),
+        };
+
+        if let Some(out) = self
+            .calculate_swap_output_via_pools(std::slice::from_ref(&action), amount_in.0, sim_day)
+            .await
+        {
+            return Ok(out);
+        }
+        Ok(self
+            .calculate_swap_output_via_rates(
+                &TokenAccount::from(action.token_in),
+                amount_in.0,
+                &TokenAccount::from(action.token_out),
+                sim_day,
+            )
+            .await)
+    }
+
     async fn handle_swap(&self, args_value: serde_json::Value) -> anyhow::Result<u128> {
         let log = DEFAULT.new(o!("function" => "SimulationClient::handle_swap"));
 
@@ -232,11 +370,11 @@ impl SimulationClient {
         }
 
         // Try pool-based estimate_return first (fee + slippage aware)
-        let (amount_out, swap_method) = match self
-            .calculate_swap_output_via_pools(&swap_actions, amount_in, sim_day)
-            .await
-        {
-            Some(out) => (out, SwapMethod::PoolBased),
+        let traced = self
+            .trace_swap_with_impact(&swap_actions, amount_in, sim_day)
+            .await;
+        let (amount_out, swap_method) = match traced.as_ref().and_then(|(_, hops)| hops.last()) {
+            Some(hop) => (hop.amount_out, SwapMethod::PoolBased),
             None => {
                 // Fallback to DB rate conversion (no fee/slippage)
                 warn!(log, "pool data unavailable, falling back to DB rate";
@@ -264,8 +402,8 @@ impl SimulationClient {
         // Resolve decimals before mutating state so the new holding entry's
         // decimals match the rate provider's view (both read from the same
         // cache populated by `token_cache::load_from_db`).
-        let decimals_in = decimals_for(&token_in_account);
-        let decimals_out = decimals_for(&token_out_account);
+        let decimals_in = self.decimals_of(&token_in_account);
+        let decimals_out = self.decimals_of(&token_out_account);
 
         // sim_day was acquired at the top of handle_swap. Only portfolio
         // needs locking here.
@@ -288,6 +426,29 @@ impl SimulationClient {
             );
             return Ok(0);
         };
+        drop(state);
+
+        // Pool-based swaps move the pools they went through; later swaps in
+        // this cycle (and, until decayed, later cycles) see the new reserves.
+        let mut slippage = None;
+        if let Some((pools, hops)) = traced {
+            let hops = if actual_in == amount_in {
+                Some(hops)
+            } else {
+                trace_swap_via_pools(&pools, &swap_actions, actual_in)
+            };
+            if let Some(hops) = hops {
+                slippage = spot_amount_out(&pools, &hops)
+                    .filter(|spot| *spot > 0.0)
+                    .map(|spot| 1.0 - actual_out as f64 / spot);
+                let mut impact = self.market_impact.lock().await;
+                impact.record(&hops, sim_day);
+                trace!(log, "recorded market impact";
+                    "hops" => hops.len(), "impacted_pools" => impact.impacted_pools());
+            }
+        }
+
+        let mut state = self.portfolio.lock().await;
         state.swap_events.push(SwapEvent {
             timestamp: sim_day,
             token_in: token_in_account.clone(),
@@ -299,6 +460,7 @@ impl SimulationClient {
             ),
             swap_method,
             pool_ids: swap_actions.iter().map(|a| a.pool_id).collect(),
+            slippage,
         });
 
         trace!(log, "simulated swap";
@@ -339,7 +501,7 @@ impl SendTx for SimulationClient {
         _receiver: &AccountId,
         _amount: NearToken,
     ) -> anyhow::Result<Self::Output> {
-        Ok(MockSentTx { output_amount: 0 })
+        Ok(MockSentTx::default())
     }
 
     async fn exec_contract<T>(
@@ -355,8 +517,12 @@ impl SendTx for SimulationClient {
     {
         if method_name == "swap" {
             let args_value = serde_json::to_value(&args)?;
-            let output_amount = self.handle_swap(args_value).await?;
-            return Ok(MockSentTx { output_amount });
+            let output_amount = self.handle_swap(args_value.clone()).await?;
+            let logs = swap_logs(&args_value, output_amount);
+            return Ok(MockSentTx {
+                output_amount,
+                logs,
+            });
         }
 
         // The mock has to model the on-chain "registered tokens" set so that
@@ -401,16 +567,33 @@ impl SendTx for SimulationClient {
             _ => {}
         }
 
-        Ok(MockSentTx { output_amount: 0 })
+        Ok(MockSentTx::default())
     }
 
     async fn send_tx(
         &self,
-        _signer: &InMemorySigner,
-        _receiver: &AccountId,
-        _actions: Vec<Action>,
+        signer: &InMemorySigner,
+        receiver: &AccountId,
+        actions: Vec<Action>,
     ) -> anyhow::Result<Self::Output> {
-        Ok(MockSentTx { output_amount: 0 })
+        // Batched transactions replay each function call in order, exactly
+        // as if they had been sent one by one. The reported output is that
+        // of the last swap in the batch; the swap logs of every call are kept
+        // in order so each call's output can be read back.
+        let mut batch = MockSentTx::default();
+        for action in actions {
+            if let Action::FunctionCall(fc) = action {
+                let args: serde_json::Value = serde_json::from_slice(&fc.args)?;
+                let sent = self
+                    .exec_contract(signer, receiver, &fc.method_name, args, fc.deposit)
+                    .await?;
+                if fc.method_name == "swap" {
+                    batch.output_amount = sent.output_amount;
+                    batch.logs.extend(sent.logs);
+                }
+            }
+        }
+        Ok(batch)
     }
 }
 
@@ -419,12 +602,16 @@ impl ViewContract for SimulationClient {
         &self,
         receiver: &AccountId,
         method_name: &str,
-        _args: &T,
+        args: &T,
     ) -> anyhow::Result<CallResult>
     where
         T: ?Sized + serde::Serialize + Sync,
     {
         let result = match method_name {
+            "get_return" => {
+                let amount = self.handle_get_return(serde_json::to_value(args)?).await?;
+                serde_json::to_vec(&U128(amount))?
+            }
             "get_deposits" => {
                 // Production `get_deposits` returns exactly the tokens the
                 // account is registered for, with their current balance
@@ -455,7 +642,11 @@ impl ViewContract for SimulationClient {
             "ft_metadata" => {
                 // Look up decimals for the specific token (receiver)
                 let receiver_token = TokenAccount::from(receiver.clone());
-                let decimals = trade::token_cache::get_cached_decimals(&receiver_token)
+                let decimals = self
+                    .synthetic_market
+                    .as_ref()
+                    .and_then(|market| market.decimals(&receiver_token))
+                    .or_else(|| trade::token_cache::get_cached_decimals(&receiver_token))
                     .or_else(|| {
                         // Fall back to holdings decimals.
                         // Uses try_lock (not lock) to avoid violating the sim_day→portfolio
@@ -531,8 +722,39 @@ impl ViewContract for SimulationClient {
     }
 }
 
+#[derive(Default)]
 pub struct MockSentTx {
     output_amount: u128,
+    /// `Swapped` logs of the swap calls, in execution order
+    logs: Vec<String>,
+}
+
+/// REF-style `Swapped` logs for a simulated `swap` call, one per action.
+///
+/// The simulation only tracks the call's total output, so every action but
+/// the last reports zero and the last one reports the whole output.
+fn swap_logs(args: &serde_json::Value, output_amount: u128) -> Vec<String> {
+    let Some(actions) = args
+        .get("actions")
+        .and_then(|actions| serde_json::from_value::<Vec<SwapAction>>(actions.clone()).ok())
+    else {
+        return Vec::new();
+    };
+    let last = actions.len().saturating_sub(1);
+    actions
+        .iter()
+        .enumerate()
+        .map(|(i, action)| {
+            let amount_out = if i == last { output_amount } else { 0 };
+            format!(
+                "Swapped {} {} for {} {}",
+                action.amount_in.map_or(0, |amount| amount.0),
+                action.token_in,
+                amount_out,
+                action.token_out
+            )
+        })
+        .collect()
 }
 
 impl std::fmt::Display for MockSentTx {
@@ -542,13 +764,23 @@ impl std::fmt::Display for MockSentTx {
 }
 
 impl SentTx for MockSentTx {
+    fn tx_hash(&self) -> CryptoHash {
+        CryptoHash::default()
+    }
+
     async fn wait_for_executed(&self) -> anyhow::Result<FinalExecutionOutcomeViewEnum> {
         unimplemented!("SimulationClient does not execute real transactions")
     }
 
     async fn wait_for_success(&self) -> anyhow::Result<FinalExecutionOutcomeView> {
         let value_json = serde_json::to_vec(&U128(self.output_amount))?;
-        Ok(blockchain::mock::dummy_final_outcome(value_json))
+        let mut view = blockchain::mock::dummy_final_outcome(value_json);
+        if !self.logs.is_empty() {
+            let mut receipt = view.transaction_outcome.clone();
+            receipt.outcome.logs = self.logs.clone();
+            view.receipts_outcome.push(receipt);
+        }
+        Ok(view)
     }
 }
 
@@ -379,6 +379,26 @@ async fn view_contract_unknown_method_returns_empty() {
     assert_eq!(balance.0, 0);
 }
 
+#[tokio::test]
+async fn view_contract_get_return_rejects_malformed_args() {
+    let client = make_client(0).await;
+
+    let receiver: AccountId = "v2.ref-finance.near".parse().unwrap();
+    let result = client
+        .view_contract(
+            &receiver,
+            "get_return",
+            &serde_json::json!({
+                "token_in": wnear_str(),
+                "amount_in": U128(1),
+                "token_out": "token-a.near",
+            }),
+        )
+        .await;
+
+    assert!(result.is_err());
+}
+
 #[tokio::test]
 async fn get_native_amount_returns_initial_capital() {
     let initial = 100_000_000_000_000_000_000_000_000u128; // 100 NEAR
@@ -824,11 +844,12 @@ fn estimate_swap_zero_amount_in() {
 
 #[tokio::test]
 async fn mock_sent_tx_display() {
-    let tx = MockSentTx { output_amount: 0 };
+    let tx = MockSentTx::default();
     assert_eq!(format!("{tx}"), "MockSentTx(sim, output=0)");
 
     let tx = MockSentTx {
         output_amount: 12345,
+        ..Default::default()
     };
     assert_eq!(format!("{tx}"), "MockSentTx(sim, output=12345)");
 }
@@ -838,6 +859,7 @@ async fn mock_sent_tx_wait_for_success_returns_ok() {
     use blockchain::jsonrpc::SentTx;
     let tx = MockSentTx {
         output_amount: 42000,
+        ..Default::default()
     };
     let result = tx.wait_for_success().await;
     assert!(result.is_ok());
@@ -852,6 +874,35 @@ async fn mock_sent_tx_wait_for_success_returns_ok() {
     }
 }
 
+#[tokio::test]
+async fn mock_sent_tx_logs_give_each_batched_call_its_output() {
+    use blockchain::jsonrpc::SentTx;
+    let call = |token_in: &str, token_out: &str, output| {
+        swap_logs(
+            &json!({ "actions": [
+                { "pool_id": 1, "token_in": token_in, "amount_in": "100",
+                  "token_out": "wrap.near", "min_amount_out": "0" },
+                { "pool_id": 2, "token_in": "wrap.near",
+                  "token_out": token_out, "min_amount_out": "0" },
+            ]}),
+            output,
+        )
+    };
+    let mut logs = call("a.near", "b.near", 70);
+    logs.extend(call("c.near", "d.near", 30));
+    let tx = MockSentTx {
+        output_amount: 30,
+        logs,
+    };
+
+    let outcome = tx.wait_for_success().await.unwrap();
+
+    assert_eq!(
+        blockchain::ref_finance::swap::extract_batch_outputs(&outcome, &[2, 2]),
+        vec![Some(70), Some(30)]
+    );
+}
+
 // ---------------------------------------------------------------------------
 // estimate_swap_via_pools: multi-hop mid-failure
 // ---------------------------------------------------------------------------
@@ -893,3 +944,135 @@ fn estimate_swap_multi_hop_second_pool_missing_returns_none() {
         "should return None when second hop pool is missing"
     );
 }
+
+// ---------------------------------------------------------------------------
+// trace_swap_via_pools (per-hop amounts for the market-impact overlay)
+// ---------------------------------------------------------------------------
+
+#[test]
+fn trace_swap_records_each_hop() {
+    let pool1 = make_simple_pool(
+        1,
+        "wrap.near",
+        "token-a.near",
+        1_000_000_000_000_000_000_000_000_000,
+        10_000_000_000_000_000_000_000_000_000,
+        30,
+    );
+    let pool2 = make_simple_pool(
+        2,
+        "token-b.near",
+        "token-a.near",
+        5_000_000_000_000_000_000_000_000_000,
+        10_000_000_000_000_000_000_000_000_000,
+        30,
+    );
+    let pools = dex::PoolInfoList::new(vec![pool1, pool2]);
+    let actions = vec![
+        SwapAction {
+            pool_id: 1,
+            token_in: "wrap.near".parse().unwrap(),
+            amount_in: Some(U128(1_000_000_000_000_000_000_000_000)),
+            token_out: "token-a.near".parse().unwrap(),
+            min_amount_out: U128(0),
+        },
+        SwapAction {
+            pool_id: 2,
+            token_in: "token-a.near".parse().unwrap(),
+            amount_in: None,
+            token_out: "token-b.near".parse().unwrap(),
+            min_amount_out: U128(0),
+        },
+    ];
+
+    let hops = trace_swap_via_pools(&pools, &actions, 1_000_000_000_000_000_000_000_000).unwrap();
+
+    assert_eq!(hops.len(), 2);
+    assert_eq!((hops[0].token_in, hops[0].token_out), (0, 1));
+    assert_eq!((hops[1].token_in, hops[1].token_out), (1, 0));
+    assert_eq!(hops[1].amount_in, hops[0].amount_out);
+    assert_eq!(
+        Some(hops[1].amount_out),
+        estimate_swap_via_pools(&pools, &actions, 1_000_000_000_000_000_000_000_000)
+    );
+}
+
+fn synthetic_market() -> Arc<SyntheticMarket> {
+    use trade::synthetic_market::{PriceModel, SyntheticMarketConfig, SyntheticTokenConfig};
+    let config = SyntheticMarketConfig {
+        seed: 7,
+        start: Utc.with_ymd_and_hms(2025, 6, 15, 0, 0, 0).unwrap(),
+        steps: 5,
+        step_hours: 24,
+        tokens: vec![SyntheticTokenConfig {
+            account: "token-a.near".parse().unwrap(),
+            decimals: 6,
+            initial_price: 0.5,
+            drift: 0.0,
+            volatility: 0.0,
+            pool_depth_near: 10_000.0,
+        }],
+        correlation: 0.0,
+        model: PriceModel::Gbm,
+        prediction_horizon_steps: 1,
+        prediction_error: 0.0,
+    };
+    Arc::new(SyntheticMarket::generate(&config).unwrap())
+}
+
+#[tokio::test]
+async fn synthetic_market_swap_and_valuation_without_db() {
+    let market = synthetic_market();
+    let cash = 100_000_000_000_000_000_000_000_000u128; // 100 NEAR
+    let amount_in = 10_000_000_000_000_000_000_000_000u128; // 10 NEAR
+    let portfolio = Arc::new(Mutex::new(PortfolioState::new(YoctoValue::from_yocto(
+        BigDecimal::from(cash),
+    ))));
+    let client = make_client_with_portfolio(Arc::clone(&portfolio))
+        .with_synthetic_market(Arc::clone(&market));
+    let sim_day = *client.sim_day.lock().await;
+    let receiver: AccountId = "v2.ref-finance.near".parse().unwrap();
+
+    let args = serde_json::json!({
+        "actions": [{
+            "pool_id": 0,
+            "token_in": wnear_str(),
+            "amount_in": U128(amount_in),
+            "token_out": "token-a.near",
+            "min_amount_out": U128(0)
+        }]
+    });
+    let tx = client
+        .exec_contract(
+            &test_signer(),
+            &receiver,
+            "swap",
+            args,
+            NearToken::from_yoctonear(1),
+        )
+        .await
+        .unwrap();
+
+    // The first swap sees the untouched synthetic pool
+    let expected = market
+        .pools_at(sim_day)
+        .unwrap()
+        .get(0)
+        .unwrap()
+        .estimate_return(dex::TokenIn::from(0), amount_in, dex::TokenOut::from(1))
+        .unwrap();
+    assert_eq!(tx.output_amount, expected);
+
+    let state = portfolio.lock().await;
+    assert_eq!(state.swap_events.len(), 1);
+    assert_eq!(state.swap_events[0].swap_method, SwapMethod::PoolBased);
+    let holding = &state.holdings[&"token-a.near".parse::<TokenAccount>().unwrap()];
+    assert_eq!(holding.decimals(), 6);
+
+    // 90 NEAR cash + ~20 tokens at 0.5 NEAR, minus the 0.3% fee and slippage
+    let total = state
+        .calculate_total_value_near(sim_day, &*market)
+        .await
+        .unwrap();
+    assert!(total < 100.0 && total > 99.9, "total = {total}");
+}
@@ -0,0 +1,415 @@
+use crate::benchmark::TotalSupplies;
+use crate::cli::{MonteCarloArgs, OutputFormat};
+use crate::engine::{config_overlay, run_simulation, run_simulation_with_config};
+use crate::output::{PerformanceMetrics, ValueMetrics, period_returns, value_metrics};
+use anyhow::Result;
+use bigdecimal::{BigDecimal, FromPrimitive};
+use common::types::{TokenOutAccount, TokenPrice};
+use futures::stream::{self, StreamExt};
+use logging::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use trade::strategy::PredictionHook;
+
+#[derive(Debug, Serialize)]
+pub struct MonteCarloResult {
+    pub config: MonteCarloConfig,
+    /// Metrics of the backtest the bootstrap resamples
+    pub base: MetricSample,
+    /// Block bootstrap of the base run's per-interval returns
+    pub bootstrap: MetricDistributions,
+    /// Re-runs with predictions perturbed by MAPE-calibrated noise
+    pub prediction_noise: Option<PredictionNoiseResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MonteCarloConfig {
+    pub start_date: String,
+    pub end_date: String,
+    pub initial_capital: f64,
+    pub samples: usize,
+    pub block_days: i64,
+    pub noise_runs: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MetricSample {
+    pub total_return: f64,
+    pub max_drawdown: f64,
+    pub sharpe_ratio: f64,
+}
+
+impl MetricSample {
+    fn from_performance(performance: &PerformanceMetrics) -> Self {
+        Self {
+            total_return: performance.total_return,
+            max_drawdown: performance.max_drawdown,
+            sharpe_ratio: performance.sharpe_ratio,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricDistributions {
+    pub samples: usize,
+    pub total_return: Distribution,
+    pub max_drawdown: Distribution,
+    pub sharpe_ratio: Distribution,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub percentiles: Percentiles,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Percentiles {
+    pub p5: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PredictionNoiseResult {
+    /// Mean MAPE (%) of the evaluated predictions in the simulation period
+    pub mean_mape_pct: f64,
+    pub mape_records: usize,
+    /// Std-dev of the log-normal noise applied to each prediction
+    pub noise_stddev: f64,
+    pub distributions: MetricDistributions,
+}
+
+/// Circular block bootstrap: a series as long as `returns`, built from
+/// blocks of `block_len` consecutive returns starting at random offsets.
+///
+/// Blocks keep the short-range autocorrelation (e.g. momentum after a
+/// rebalance) that resampling single days would destroy.
+fn block_bootstrap(returns: &[f64], block_len: usize, rng: &mut impl Rng) -> Vec<f64> {
+    let n = returns.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let block_len = block_len.clamp(1, n);
+    let mut resampled = Vec::with_capacity(n);
+    while resampled.len() < n {
+        let start = rng.random_range(0..n);
+        let take = block_len.min(n - resampled.len());
+        resampled.extend((0..take).map(|k| returns[(start + k) % n]));
+    }
+    resampled
+}
+
+/// Portfolio values obtained by compounding `returns` from `initial_capital`.
+fn compound(initial_capital: f64, returns: &[f64]) -> Vec<f64> {
+    let mut value = initial_capital;
+    returns
+        .iter()
+        .map(|r| {
+            value *= 1.0 + r;
+            value
+        })
+        .collect()
+}
+
+fn bootstrap_samples(
+    initial_capital: f64,
+    values: &[f64],
+    interval_days: i64,
+    samples: usize,
+    block_len: usize,
+    seed: u64,
+) -> Vec<MetricSample> {
+    let returns = period_returns(initial_capital, values);
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..samples)
+        .map(|_| {
+            let path = compound(
+                initial_capital,
+                &block_bootstrap(&returns, block_len, &mut rng),
+            );
+            let ValueMetrics {
+                total_return,
+                max_drawdown,
+                sharpe_ratio,
+                ..
+            } = value_metrics(initial_capital, &path, interval_days);
+            MetricSample {
+                total_return,
+                max_drawdown,
+                sharpe_ratio,
+            }
+        })
+        .collect()
+}
+
+/// Linearly interpolated percentile (`p` in 0..=100) of sorted values.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    match sorted {
+        [] => 0.0,
+        [only] => *only,
+        _ => {
+            let rank = p / 100.0 * (sorted.len() - 1) as f64;
+            let lower = rank.floor() as usize;
+            let upper = rank.ceil() as usize;
+            sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+        }
+    }
+}
+
+fn distribution(values: impl Iterator<Item = f64>) -> Distribution {
+    let mut sorted: Vec<f64> = values.filter(|v| v.is_finite()).collect();
+    if sorted.is_empty() {
+        return Distribution::default();
+    }
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+    let mean = sorted.iter().sum::<f64>() / n;
+    let std_dev = if sorted.len() > 1 {
+        (sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    Distribution {
+        mean,
+        std_dev,
+        min: sorted[0],
+        max: sorted[sorted.len() - 1],
+        percentiles: Percentiles {
+            p5: percentile(&sorted, 5.0),
+            p25: percentile(&sorted, 25.0),
+            p50: percentile(&sorted, 50.0),
+            p75: percentile(&sorted, 75.0),
+            p95: percentile(&sorted, 95.0),
+        },
+    }
+}
+
+fn distributions(samples: &[MetricSample]) -> MetricDistributions {
+    MetricDistributions {
+        samples: samples.len(),
+        total_return: distribution(samples.iter().map(|s| s.total_return)),
+        max_drawdown: distribution(samples.iter().map(|s| s.max_drawdown)),
+        sharpe_ratio: distribution(samples.iter().map(|s| s.sharpe_ratio)),
+    }
+}
+
+/// Std-dev of a zero-mean normal error whose mean absolute value is `mape_pct` %.
+///
+/// `E|X| = σ·√(2/π)` for `X ~ N(0, σ²)`; the log-normal noise applied to
+/// predictions behaves the same for the small σ seen in practice.
+fn noise_stddev_from_mape(mape_pct: f64) -> f64 {
+    mape_pct / 100.0 * (std::f64::consts::PI / 2.0).sqrt()
+}
+
+/// Multiplies each loaded prediction by log-normal noise `exp(σz)`.
+///
+/// The draw is keyed by `seed`, token and cycle time, so a run with the same
+/// seed sees the same noise.
+#[derive(Debug, Clone, Copy)]
+struct PredictionNoise {
+    stddev: f64,
+    seed: u64,
+}
+
+impl PredictionHook for PredictionNoise {
+    fn adjust(
+        &self,
+        predictions: &mut BTreeMap<TokenOutAccount, TokenPrice>,
+        as_of: chrono::DateTime<chrono::Utc>,
+    ) {
+        for (token, price) in predictions.iter_mut() {
+            let mut hasher = DefaultHasher::new();
+            (self.seed, token.to_string(), as_of.timestamp()).hash(&mut hasher);
+            let mut rng = StdRng::seed_from_u64(hasher.finish());
+            // Box-Muller (u1 in (0, 1])
+            let u1 = 1.0 - rng.random::<f64>();
+            let u2 = rng.random::<f64>();
+            let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+            if let Some(factor) = BigDecimal::from_f64((self.stddev * z).exp()) {
+                *price = price.clone() * factor;
+            }
+        }
+    }
+}
+
+async fn run_prediction_noise(
+    args: &MonteCarloArgs,
+    supplies: &TotalSupplies,
+) -> Result<PredictionNoiseResult> {
+    let log = DEFAULT.new(o!("function" => "montecarlo::run_prediction_noise"));
+
+    let since = args.run.parse_start_date()?.and_hms_opt(0, 0, 0).unwrap();
+    let until = (args.run.parse_end_date()? + chrono::TimeDelta::days(1))
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let mapes: Vec<f64> =
+        persistence::prediction_record::PredictionRecord::get_mapes_between(since, until)
+            .await?
+            .into_iter()
+            .filter(|m| m.is_finite())
+            .collect();
+    if mapes.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no evaluated predictions between {} and {} to calibrate the noise",
+            since,
+            until
+        ));
+    }
+    let mean_mape_pct = mapes.iter().sum::<f64>() / mapes.len() as f64;
+    let noise_stddev = noise_stddev_from_mape(mean_mape_pct);
+    info!(log, "calibrated prediction noise";
+        "mean_mape_pct" => mean_mape_pct, "records" => mapes.len(),
+        "noise_stddev" => noise_stddev, "runs" => args.noise_runs);
+
+    let runs: Vec<MetricSample> = stream::iter(0..args.noise_runs)
+        .map(|i| {
+            let log = log.clone();
+            async move {
+                let noise = PredictionNoise {
+                    stddev: noise_stddev,
+                    seed: args.seed.wrapping_add(i as u64),
+                };
+                let cfg = config_overlay(&args.run);
+                match run_simulation_with_config(&args.run, &cfg, Some(&noise), supplies).await {
+                    Ok(result) => Some(MetricSample::from_performance(&result.performance)),
+                    Err(e) => {
+                        warn!(log, "noise run failed"; "run" => i + 1, "error" => ?e);
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(args.concurrency.max(1))
+        .filter_map(|sample| async move { sample })
+        .collect()
+        .await;
+
+    Ok(PredictionNoiseResult {
+        mean_mape_pct,
+        mape_records: mapes.len(),
+        noise_stddev,
+        distributions: distributions(&runs),
+    })
+}
+
+pub async fn run_montecarlo(args: &MonteCarloArgs) -> Result<()> {
+    let log = DEFAULT.new(o!("function" => "run_montecarlo"));
+
+    if args.run.sweep.is_some() {
+        return Err(anyhow::anyhow!(
+            "--sweep cannot be combined with montecarlo"
+        ));
+    }
+
+    // The base run and every noise run share the benchmark token supplies
+    let supplies = TotalSupplies::default();
+    let base = run_simulation(&args.run, &supplies).await?;
+    let values: Vec<f64> = base
+        .portfolio_values
+        .iter()
+        .map(|v| v.total_value)
+        .collect();
+    let interval_days = args.run.rebalance_interval_days;
+    let block_len = (args.block_days / interval_days.max(1)).max(1) as usize;
+    info!(log, "bootstrapping returns";
+        "intervals" => values.len(), "block_len" => block_len, "samples" => args.samples);
+    let bootstrap = bootstrap_samples(
+        args.run.initial_capital,
+        &values,
+        interval_days,
+        args.samples,
+        block_len,
+        args.seed,
+    );
+
+    let prediction_noise = if args.noise_runs > 0 {
+        Some(run_prediction_noise(args, &supplies).await?)
+    } else {
+        None
+    };
+
+    let result = MonteCarloResult {
+        config: MonteCarloConfig {
+            start_date: args.run.start_date.clone(),
+            end_date: args.run.end_date.clone(),
+            initial_capital: args.run.initial_capital,
+            samples: args.samples,
+            block_days: args.block_days,
+            noise_runs: args.noise_runs,
+            seed: args.seed,
+        },
+        base: MetricSample::from_performance(&base.performance),
+        bootstrap: distributions(&bootstrap),
+        prediction_noise,
+    };
+
+    std::fs::write(&args.run.output, serde_json::to_string_pretty(&result)?)?;
+    info!(log, "results written"; "path" => args.run.output.display().to_string());
+
+    match args.format {
+        OutputFormat::Text => print_text_report(&result),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&result)?),
+    }
+    Ok(())
+}
+
+fn print_distributions(label: &str, d: &MetricDistributions) {
+    println!("\n{} ({} samples)", label, d.samples);
+    println!(
+        "{:<14} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        "", "P5", "P25", "P50", "P75", "P95"
+    );
+    for (name, dist, scale) in [
+        ("Return%", &d.total_return, 100.0),
+        ("MaxDD%", &d.max_drawdown, 100.0),
+        ("Sharpe", &d.sharpe_ratio, 1.0),
+    ] {
+        let p = &dist.percentiles;
+        println!(
+            "{:<14} {:>9.2} {:>9.2} {:>9.2} {:>9.2} {:>9.2}",
+            name,
+            p.p5 * scale,
+            p.p25 * scale,
+            p.p50 * scale,
+            p.p75 * scale,
+            p.p95 * scale
+        );
+    }
+}
+
+fn print_text_report(result: &MonteCarloResult) {
+    println!("\n=== Monte Carlo Results ===");
+    println!(
+        "Period: {} to {}",
+        result.config.start_date, result.config.end_date
+    );
+    println!(
+        "Backtest: return {:.2}%, max drawdown {:.2}%, Sharpe {:.3}",
+        result.base.total_return * 100.0,
+        result.base.max_drawdown * 100.0,
+        result.base.sharpe_ratio
+    );
+    print_distributions("Block bootstrap", &result.bootstrap);
+    if let Some(noise) = &result.prediction_noise {
+        print_distributions(
+            &format!(
+                "Prediction noise (MAPE {:.2}%, stddev {:.4})",
+                noise.mean_mape_pct, noise.noise_stddev
+            ),
+            &noise.distributions,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,164 @@
+use super::*;
+
+// --- period_returns / compound ---
+
+#[test]
+fn returns_roundtrip_through_compound() {
+    let values = vec![110.0, 99.0, 121.0];
+
+    let returns = period_returns(100.0, &values);
+
+    assert!((returns[0] - 0.1).abs() < 1e-12);
+    assert!((returns[1] + 0.1).abs() < 1e-12);
+    let rebuilt = compound(100.0, &returns);
+    for (a, b) in rebuilt.iter().zip(&values) {
+        assert!((a - b).abs() < 1e-9, "{a} vs {b}");
+    }
+}
+
+#[test]
+fn returns_after_zero_value_are_zero() {
+    assert_eq!(period_returns(100.0, &[0.0, 50.0]), vec![-1.0, 0.0]);
+}
+
+// --- block_bootstrap ---
+
+#[test]
+fn bootstrap_keeps_length_and_values() {
+    let returns = vec![0.01, 0.02, 0.03, 0.04, 0.05, 0.06, 0.07];
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let resampled = block_bootstrap(&returns, 3, &mut rng);
+
+    assert_eq!(resampled.len(), returns.len());
+    assert!(resampled.iter().all(|r| returns.contains(r)));
+}
+
+#[test]
+fn bootstrap_blocks_are_consecutive() {
+    // Returns are their own indices, so consecutive elements differ by 1 (mod n)
+    let returns: Vec<f64> = (0..10).map(f64::from).collect();
+    let mut rng = StdRng::seed_from_u64(2);
+
+    let resampled = block_bootstrap(&returns, 5, &mut rng);
+
+    for block in resampled.chunks(5) {
+        for pair in block.windows(2) {
+            assert_eq!((pair[0] as usize + 1) % 10, pair[1] as usize);
+        }
+    }
+}
+
+#[test]
+fn bootstrap_of_empty_series_is_empty() {
+    let mut rng = StdRng::seed_from_u64(3);
+    assert!(block_bootstrap(&[], 5, &mut rng).is_empty());
+}
+
+#[test]
+fn bootstrap_samples_are_reproducible() {
+    let values = vec![101.0, 99.0, 104.0, 103.0, 108.0, 107.0];
+
+    let first = bootstrap_samples(100.0, &values, 1, 50, 2, 42);
+    let second = bootstrap_samples(100.0, &values, 1, 50, 2, 42);
+
+    assert_eq!(first.len(), 50);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn bootstrap_with_full_block_reproduces_a_rotation() {
+    // A single block covering the whole series is a rotation: same total return
+    let values = vec![110.0, 99.0, 121.0];
+
+    let samples = bootstrap_samples(100.0, &values, 1, 20, 3, 7);
+
+    for sample in samples {
+        assert!((sample.total_return - 0.21).abs() < 1e-9);
+    }
+}
+
+// --- distribution ---
+
+#[test]
+fn percentile_interpolates() {
+    let sorted = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(percentile(&sorted, 0.0), 1.0);
+    assert_eq!(percentile(&sorted, 50.0), 3.0);
+    assert_eq!(percentile(&sorted, 100.0), 5.0);
+    assert!((percentile(&sorted, 5.0) - 1.2).abs() < 1e-12);
+}
+
+#[test]
+fn distribution_summarises_values() {
+    let dist = distribution([5.0, 1.0, 3.0, f64::NAN, 2.0, 4.0].into_iter());
+
+    assert_eq!(dist.mean, 3.0);
+    assert_eq!(dist.min, 1.0);
+    assert_eq!(dist.max, 5.0);
+    assert_eq!(dist.percentiles.p50, 3.0);
+    assert!((dist.std_dev - 2.5f64.sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn distribution_of_nothing_is_default() {
+    assert_eq!(distribution(std::iter::empty()), Distribution::default());
+}
+
+// --- noise calibration ---
+
+#[test]
+fn noise_stddev_matches_mean_absolute_error() {
+    // For N(0, σ²), E|X| = σ·√(2/π)
+    let stddev = noise_stddev_from_mape(10.0);
+    let mean_abs = stddev * (2.0 / std::f64::consts::PI).sqrt();
+    assert!((mean_abs - 0.1).abs() < 1e-12);
+}
+
+// --- PredictionNoise ---
+
+fn predictions_of(tokens: &[&str]) -> BTreeMap<TokenOutAccount, TokenPrice> {
+    tokens
+        .iter()
+        .map(|t| {
+            (
+                t.parse::<common::types::TokenAccount>().unwrap().into(),
+                TokenPrice::from_near_per_token(BigDecimal::from(2)),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn prediction_noise_is_reproducible_per_seed() {
+    let as_of = chrono::Utc::now();
+    let mut first = predictions_of(&["a.near", "b.near"]);
+    let mut second = first.clone();
+    let mut other_seed = first.clone();
+
+    let noise = |seed| PredictionNoise { stddev: 0.1, seed };
+    noise(7).adjust(&mut first, as_of);
+    noise(7).adjust(&mut second, as_of);
+    noise(8).adjust(&mut other_seed, as_of);
+
+    assert_eq!(first, second);
+    assert_ne!(first, other_seed);
+    // Each token draws its own noise
+    let values: Vec<_> = first.values().collect();
+    assert_ne!(values[0], values[1]);
+}
+
+#[test]
+fn prediction_noise_keeps_prices_positive() {
+    let mut predictions = predictions_of(&["a.near", "b.near", "c.near", "d.near"]);
+
+    PredictionNoise {
+        stddev: 2.0,
+        seed: 1,
+    }
+    .adjust(&mut predictions, chrono::Utc::now());
+
+    for price in predictions.values() {
+        assert!(price.as_bigdecimal() > &BigDecimal::from(0));
+    }
+}
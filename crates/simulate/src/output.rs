@@ -1,3 +1,4 @@
+use crate::benchmark::BenchmarkComparison;
 use crate::cli::RunArgs;
 use crate::portfolio_state::{
     PortfolioState, SwapEvent, SwapMethod, TradeAction, pnl_to_near, to_f64_or_warn,
@@ -15,6 +16,12 @@ pub struct SimulationResult {
     pub trades: Vec<TradeEntry>,
     pub swap_events: Vec<SwapEventEntry>,
     pub portfolio_values: Vec<PortfolioValueEntry>,
+    /// Strategy compared against passive benchmarks over the same snapshots
+    #[serde(default)]
+    pub benchmarks: Vec<BenchmarkComparison>,
+    /// Realized and unrealized P&L per token at the end of the run
+    #[serde(default)]
+    pub pnl_by_token: BTreeMap<String, TokenPnlEntry>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -79,6 +86,18 @@ impl SwapStats {
             fallback_rate,
         }
     }
+
+    /// Add another run's counts and recompute the fallback rate.
+    pub fn merge(&mut self, other: &SwapStats) {
+        self.total_swaps += other.total_swaps;
+        self.pool_based_swaps += other.pool_based_swaps;
+        self.fallback_swaps += other.fallback_swaps;
+        self.fallback_rate = if self.total_swaps > 0 {
+            self.fallback_swaps as f64 / self.total_swaps as f64
+        } else {
+            0.0
+        };
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -102,6 +121,9 @@ pub struct SwapEventEntry {
     pub amount_out_raw: u128,
     pub swap_method: SwapMethod,
     pub pool_ids: Vec<u32>,
+    /// Share of the spot output lost to fee and price impact (pool-based swaps only)
+    #[serde(default)]
+    pub slippage: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -109,12 +131,22 @@ pub struct PortfolioValueEntry {
     pub timestamp: String,
     pub total_value: f64,
     pub holdings: BTreeMap<String, u128>,
+    /// NEAR value of each holding
+    #[serde(default)]
+    pub holding_values_near: BTreeMap<String, f64>,
     pub cash_balance: f64,
     pub daily_pnl_near: f64,
     pub daily_pnl_pct: f64,
     pub cumulative_realized_pnl_near: f64,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TokenPnlEntry {
+    pub realized_near: f64,
+    /// Value in the last snapshot minus the remaining cost basis
+    pub unrealized_near: f64,
+}
+
 impl SimulationResult {
     pub fn from_state(cli: &RunArgs, state: &PortfolioState) -> Result<Self> {
         let config = SimulationConfig {
@@ -167,6 +199,11 @@ impl SimulationResult {
                     timestamp: s.timestamp.to_rfc3339(),
                     total_value: s.total_value_near,
                     holdings: holdings_map,
+                    holding_values_near: s
+                        .holding_values_near
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), *v))
+                        .collect(),
                     cash_balance: to_f64_or_warn(
                         s.cash_balance.as_bigdecimal(),
                         "snapshot_cash_balance",
@@ -199,9 +236,12 @@ impl SimulationResult {
                 ),
                 swap_method: e.swap_method,
                 pool_ids: e.pool_ids.clone(),
+                slippage: e.slippage,
             })
             .collect();
 
+        let pnl_by_token = token_pnl(state);
+
         let swap_stats = SwapStats::from_events(&state.swap_events);
 
         let trade_count = state
@@ -231,6 +271,8 @@ impl SimulationResult {
             trades,
             swap_events,
             portfolio_values,
+            benchmarks: Vec::new(),
+            pnl_by_token,
         })
     }
 
@@ -241,6 +283,35 @@ impl SimulationResult {
     }
 }
 
+/// Realized P&L of every token traded, plus unrealized P&L of the tokens
+/// still held in the last snapshot.
+fn token_pnl(state: &PortfolioState) -> BTreeMap<String, TokenPnlEntry> {
+    let mut pnl: BTreeMap<String, TokenPnlEntry> = state
+        .realized_pnl_by_token
+        .iter()
+        .map(|(token, realized)| {
+            (
+                token.to_string(),
+                TokenPnlEntry {
+                    realized_near: pnl_to_near(*realized),
+                    unrealized_near: 0.0,
+                },
+            )
+        })
+        .collect();
+    if let Some(last) = state.snapshots.last() {
+        for (token, value) in &last.holding_values_near {
+            let cost = state
+                .cost_basis
+                .get(token)
+                .map(|c| to_f64_or_warn(c.as_bigdecimal(), "cost_basis") / 1e24)
+                .unwrap_or(0.0);
+            pnl.entry(token.to_string()).or_default().unrealized_near = value - cost;
+        }
+    }
+    pnl
+}
+
 struct PerformanceInput<'a> {
     initial_capital: f64,
     snapshots: &'a [crate::portfolio_state::PortfolioSnapshot],
@@ -277,6 +348,47 @@ fn calculate_performance(input: PerformanceInput<'_>) -> PerformanceMetrics {
         };
     };
     let final_value = last.total_value_near;
+    let values: Vec<f64> = snapshots.iter().map(|s| s.total_value_near).collect();
+    let ValueMetrics {
+        total_return,
+        sharpe_ratio,
+        sortino_ratio,
+        max_drawdown,
+        win_rate,
+    } = value_metrics(initial_capital, &values, rebalance_interval_days);
+
+    PerformanceMetrics {
+        total_return,
+        sharpe_ratio,
+        sortino_ratio,
+        max_drawdown,
+        win_rate,
+        final_balance_near: final_value,
+        total_realized_pnl_near: pnl_to_near(realized_pnl),
+        trade_count,
+        liquidation_count,
+        swap_stats,
+    }
+}
+
+/// Return and risk metrics of a portfolio value series (NEAR)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ValueMetrics {
+    pub total_return: f64,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub max_drawdown: f64,
+    pub win_rate: f64,
+}
+
+/// Compute [`ValueMetrics`] for `values` sampled every `interval_days`,
+/// starting from `initial_capital`.
+pub(crate) fn value_metrics(
+    initial_capital: f64,
+    values: &[f64],
+    interval_days: i64,
+) -> ValueMetrics {
+    let final_value = values.last().copied().unwrap_or(initial_capital);
     let total_return = if initial_capital > 0.0 {
         (final_value - initial_capital) / initial_capital
     } else {
@@ -286,23 +398,14 @@ fn calculate_performance(input: PerformanceInput<'_>) -> PerformanceMetrics {
     // Calculate per-period returns
     let mut period_returns: Vec<f64> = Vec::new();
     let mut prev_value = initial_capital;
-    for snapshot in snapshots {
+    for &value in values {
         if prev_value > 0.0 {
-            let ret = (snapshot.total_value_near - prev_value) / prev_value;
+            let ret = (value - prev_value) / prev_value;
             period_returns.push(ret);
         }
-        prev_value = snapshot.total_value_near;
+        prev_value = value;
     }
 
-    // Sharpe ratio (assuming risk-free rate = 0)
-    let sharpe_ratio = calculate_sharpe_ratio(&period_returns, rebalance_interval_days);
-
-    // Sortino ratio
-    let sortino_ratio = calculate_sortino_ratio(&period_returns, rebalance_interval_days);
-
-    // Max drawdown
-    let max_drawdown = calculate_max_drawdown(snapshots);
-
     // Win rate
     let winning_periods = period_returns.iter().filter(|&&r| r > 0.0).count();
     let win_rate = if period_returns.is_empty() {
@@ -311,20 +414,29 @@ fn calculate_performance(input: PerformanceInput<'_>) -> PerformanceMetrics {
         winning_periods as f64 / period_returns.len() as f64
     };
 
-    PerformanceMetrics {
+    ValueMetrics {
         total_return,
-        sharpe_ratio,
-        sortino_ratio,
-        max_drawdown,
+        // Sharpe ratio (assuming risk-free rate = 0)
+        sharpe_ratio: calculate_sharpe_ratio(&period_returns, interval_days),
+        sortino_ratio: calculate_sortino_ratio(&period_returns, interval_days),
+        max_drawdown: calculate_max_drawdown(values),
         win_rate,
-        final_balance_near: final_value,
-        total_realized_pnl_near: pnl_to_near(realized_pnl),
-        trade_count,
-        liquidation_count,
-        swap_stats,
     }
 }
 
+/// Per-interval returns of `values`, the first one relative to `initial_capital`.
+pub(crate) fn period_returns(initial_capital: f64, values: &[f64]) -> Vec<f64> {
+    let mut prev = initial_capital;
+    values
+        .iter()
+        .map(|&value| {
+            let ret = if prev > 0.0 { value / prev - 1.0 } else { 0.0 };
+            prev = value;
+            ret
+        })
+        .collect()
+}
+
 fn calculate_sharpe_ratio(returns: &[f64], interval_days: i64) -> f64 {
     if returns.len() < 2 {
         return 0.0;
@@ -366,20 +478,20 @@ fn calculate_sortino_ratio(returns: &[f64], interval_days: i64) -> f64 {
     }
 }
 
-fn calculate_max_drawdown(snapshots: &[crate::portfolio_state::PortfolioSnapshot]) -> f64 {
-    if snapshots.is_empty() {
+fn calculate_max_drawdown(values: &[f64]) -> f64 {
+    let Some(&first) = values.first() else {
         return 0.0;
-    }
+    };
 
-    let mut peak = snapshots[0].total_value_near;
+    let mut peak = first;
     let mut max_dd = 0.0;
 
-    for snapshot in snapshots {
-        if snapshot.total_value_near > peak {
-            peak = snapshot.total_value_near;
+    for &value in values {
+        if value > peak {
+            peak = value;
         }
         if peak > 0.0 {
-            let drawdown = (peak - snapshot.total_value_near) / peak;
+            let drawdown = (peak - value) / peak;
             if drawdown > max_dd {
                 max_dd = drawdown;
             }
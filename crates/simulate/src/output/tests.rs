@@ -14,6 +14,7 @@ fn make_snapshot(total_value_near: f64) -> PortfolioSnapshot {
         timestamp: Utc::now(),
         total_value_near,
         holdings: BTreeMap::new(),
+        holding_values_near: BTreeMap::new(),
         cash_balance: YoctoValue::zero(),
         realized_pnl_near: 0.0,
     }
@@ -120,47 +121,35 @@ fn max_drawdown_empty() {
 
 #[test]
 fn max_drawdown_monotonic_increase() {
-    let snapshots = vec![
-        make_snapshot(100.0),
-        make_snapshot(110.0),
-        make_snapshot(120.0),
-    ];
-    assert_eq!(calculate_max_drawdown(&snapshots), 0.0);
+    let values = vec![100.0, 110.0, 120.0];
+    assert_eq!(calculate_max_drawdown(&values), 0.0);
 }
 
 #[test]
 fn max_drawdown_monotonic_decrease() {
-    let snapshots = vec![
-        make_snapshot(100.0),
-        make_snapshot(80.0),
-        make_snapshot(60.0),
-    ];
-    let dd = calculate_max_drawdown(&snapshots);
+    let values = vec![100.0, 80.0, 60.0];
+    let dd = calculate_max_drawdown(&values);
     assert!((dd - 0.4).abs() < 1e-10, "expected 40% drawdown, got {dd}");
 }
 
 #[test]
 fn max_drawdown_peak_then_recovery() {
-    let snapshots = vec![
-        make_snapshot(100.0),
-        make_snapshot(120.0),
-        make_snapshot(90.0), // 25% drawdown from 120
-        make_snapshot(130.0),
+    let values = vec![
+        100.0, 120.0, 90.0, // 25% drawdown from 120
+        130.0,
     ];
-    let dd = calculate_max_drawdown(&snapshots);
+    let dd = calculate_max_drawdown(&values);
     assert!((dd - 0.25).abs() < 1e-10, "expected 25% drawdown, got {dd}");
 }
 
 #[test]
 fn max_drawdown_multiple_drawdowns() {
-    let snapshots = vec![
-        make_snapshot(100.0),
-        make_snapshot(90.0), // 10% dd from 100
-        make_snapshot(110.0),
-        make_snapshot(77.0), // 30% dd from 110
-        make_snapshot(120.0),
+    let values = vec![
+        100.0, 90.0, // 10% dd from 100
+        110.0, 77.0, // 30% dd from 110
+        120.0,
     ];
-    let dd = calculate_max_drawdown(&snapshots);
+    let dd = calculate_max_drawdown(&values);
     assert!((dd - 0.3).abs() < 1e-10, "expected 30% drawdown, got {dd}");
 }
 
@@ -275,6 +264,8 @@ fn make_cli(start: &str, end: &str) -> RunArgs {
         output: PathBuf::from("test.json"),
         sweep: None,
         generate_predictions: false,
+        impact_half_life_days: None,
+        impact_persistent: false,
     }
 }
 
@@ -332,6 +323,7 @@ fn from_state_maps_snapshots_to_portfolio_values() {
         timestamp: ts,
         total_value_near: 105.0,
         holdings: holdings.clone(),
+        holding_values_near: BTreeMap::new(),
         cash_balance: yocto(cash_yocto),
         realized_pnl_near: 0.0,
     });
@@ -356,6 +348,8 @@ fn from_state_config_reflects_cli_params() {
         output: PathBuf::from("out.json"),
         sweep: None,
         generate_predictions: false,
+        impact_half_life_days: None,
+        impact_persistent: false,
     };
     let state = PortfolioState::new(yocto(200_000_000_000_000_000_000_000_000));
 
@@ -394,6 +388,7 @@ fn portfolio_value_entry_daily_pnl() {
         timestamp: ts1,
         total_value_near: 105.0,
         holdings: BTreeMap::new(),
+        holding_values_near: BTreeMap::new(),
         cash_balance: YoctoValue::zero(),
         realized_pnl_near: 0.0,
     });
@@ -401,6 +396,7 @@ fn portfolio_value_entry_daily_pnl() {
         timestamp: ts2,
         total_value_near: 110.0,
         holdings: BTreeMap::new(),
+        holding_values_near: BTreeMap::new(),
         cash_balance: YoctoValue::zero(),
         realized_pnl_near: 2.5,
     });
@@ -493,6 +489,7 @@ fn make_swap_event(method: SwapMethod, pool_ids: Vec<u32>) -> SwapEvent {
         amount_out: TokenAmount::from_smallest_units(BigDecimal::from(500_000u64), 24),
         swap_method: method,
         pool_ids,
+        slippage: None,
     }
 }
 
@@ -588,6 +585,7 @@ fn from_state_swap_event_entry_mapping() {
         amount_out: TokenAmount::from_smallest_units(BigDecimal::from(500_000u64), 24),
         swap_method: SwapMethod::PoolBased,
         pool_ids: vec![42, 99],
+        slippage: None,
     });
 
     let result = SimulationResult::from_state(&cli, &state).unwrap();
@@ -7,6 +7,8 @@ use persistence::token_rate::TokenRate;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::mem;
+use trade::lot::Lot;
+use trade::synthetic_market::SyntheticMarket;
 
 /// 非負の BigDecimal を u128 に変換する。
 ///
@@ -98,11 +100,25 @@ impl RateProvider for DbRateProvider {
     }
 }
 
+/// In-memory RateProvider backed by a generated synthetic market (no DB).
+impl RateProvider for SyntheticMarket {
+    async fn get_rate(
+        &self,
+        token: &TokenOutAccount,
+        sim_day: DateTime<Utc>,
+    ) -> Option<ExchangeRate> {
+        self.rate_at(token.inner(), sim_day)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortfolioSnapshot {
     pub timestamp: DateTime<Utc>,
     pub total_value_near: f64,
     pub holdings: BTreeMap<TokenAccount, TokenAmount>,
+    /// NEAR value of each holding at `timestamp`
+    #[serde(default)]
+    pub holding_values_near: BTreeMap<TokenAccount, f64>,
     pub cash_balance: YoctoValue,
     pub realized_pnl_near: f64,
 }
@@ -170,6 +186,9 @@ pub(crate) struct SwapEvent {
     pub(crate) amount_out: TokenAmount,
     pub(crate) swap_method: SwapMethod,
     pub(crate) pool_ids: Vec<u32>,
+    /// Share of the pre-swap spot output lost to fee and price impact
+    /// (pool-based swaps only)
+    pub(crate) slippage: Option<f64>,
 }
 
 pub struct PortfolioState {
@@ -357,14 +376,14 @@ impl PortfolioState {
         sim_day: DateTime<Utc>,
         rate_provider: &(impl RateProvider + ?Sized),
     ) -> Result<()> {
-        let total_value = self
-            .calculate_total_value_near(sim_day, rate_provider)
-            .await?;
+        let holding_values = self.holding_values_near(sim_day, rate_provider).await;
+        let total_value = self.total_from(&holding_values);
 
         self.snapshots.push(PortfolioSnapshot {
             timestamp: sim_day,
             total_value_near: total_value,
             holdings: self.holdings.clone(),
+            holding_values_near: holding_values,
             cash_balance: self.cash_balance.clone(),
             realized_pnl_near: pnl_to_near(self.realized_pnl),
         });
@@ -378,13 +397,18 @@ impl PortfolioState {
         sim_day: DateTime<Utc>,
         rate_provider: &(impl RateProvider + ?Sized),
     ) -> Result<f64> {
-        let yocto_per_near: f64 = 1e24;
-
-        // Cash portion (wrap.near)
-        let mut total =
-            to_f64_or_warn(self.cash_balance.as_bigdecimal(), "cash_balance") / yocto_per_near;
+        let holding_values = self.holding_values_near(sim_day, rate_provider).await;
+        Ok(self.total_from(&holding_values))
+    }
 
-        // Holdings
+    /// NEAR value of each non-zero holding. Tokens without a rate are left
+    /// out (they count as zero in the total).
+    pub async fn holding_values_near(
+        &self,
+        sim_day: DateTime<Utc>,
+        rate_provider: &(impl RateProvider + ?Sized),
+    ) -> BTreeMap<TokenAccount, f64> {
+        let mut values = BTreeMap::new();
         for (token_account, token_amount) in &self.holdings {
             if token_amount.is_zero() {
                 continue;
@@ -392,27 +416,33 @@ impl PortfolioState {
 
             let token_out: TokenOutAccount = token_account.to_out();
 
-            match rate_provider.get_rate(&token_out, sim_day).await {
-                Some(rate) => {
-                    let near_value = token_amount / &rate;
-                    let near_f64: f64 =
-                        to_f64_or_warn(near_value.as_bigdecimal(), "token_near_value");
-                    total += near_f64;
-                }
-                None => {
-                    // No rate available, skip this token's value
-                }
+            if let Some(rate) = rate_provider.get_rate(&token_out, sim_day).await {
+                let near_value = token_amount / &rate;
+                values.insert(
+                    token_account.clone(),
+                    to_f64_or_warn(near_value.as_bigdecimal(), "token_near_value"),
+                );
             }
         }
+        values
+    }
 
-        Ok(total)
+    /// Cash (wrap.near) plus the given holding values, in NEAR
+    fn total_from(&self, holding_values: &BTreeMap<TokenAccount, f64>) -> f64 {
+        let yocto_per_near: f64 = 1e24;
+        let mut total =
+            to_f64_or_warn(self.cash_balance.as_bigdecimal(), "cash_balance") / yocto_per_near;
+        for value in holding_values.values() {
+            total += value;
+        }
+        total
     }
 
     /// Compute the cost of the sold portion using average cost basis method.
     ///
     /// `total_holding` is the holding amount *before* the sell (including `sell_amount`).
-    /// Uses `BigDecimal` internally to avoid overflow and precision loss from integer
-    /// division that the previous `u128`-based implementation suffered from.
+    /// The holding is treated as a single merged lot so the split matches
+    /// `trade::pnl` and `trade::tax_lot` (`trade::lot::Lot::split_off`).
     fn average_cost_of_sold(
         &self,
         token: &TokenAccount,
@@ -435,8 +465,13 @@ impl PortfolioState {
             // slightly underestimates the cost of the sold portion and slightly
             // overestimates realized P&L. The error is sub-yoctoNEAR and
             // self-corrects on final sell (early return above).
-            let result = (total_cost.as_bigdecimal() * BigDecimal::from(sell_amount))
-                / BigDecimal::from(total_holding);
+            let mut lot = Lot::new(
+                None,
+                BigDecimal::from(total_holding),
+                Some(total_cost.as_bigdecimal().clone()),
+            );
+            let sold = lot.split_off(&BigDecimal::from(sell_amount));
+            let result = sold.cost.unwrap_or_default();
             YoctoValue::from_yocto(result.with_scale_round(0, bigdecimal::RoundingMode::Floor))
         } else {
             YoctoValue::zero()
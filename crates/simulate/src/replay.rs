@@ -0,0 +1,692 @@
+use crate::cli::ReplayArgs;
+use crate::market_impact::{ImpactDecay, MarketImpact};
+use crate::mock_client::SimulationClient;
+use crate::mock_wallet::SimulationWallet;
+use crate::portfolio_state::{
+    DbRateProvider, PortfolioState, RateProvider, to_f64_or_warn, yocto_to_near,
+};
+use anyhow::Result;
+use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use common::algorithm::types::TradingAction;
+use common::config::ConfigOverlay;
+use common::types::{TokenAccount, TokenAmount};
+use logging::*;
+use num_traits::ToPrimitive;
+use persistence::evaluation_period::{EvaluationPeriod, NewEvaluationPeriod};
+use persistence::portfolio_holding::PortfolioHolding;
+use persistence::trade_transaction::TradeTransaction;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use trade::strategy::CycleReport;
+
+/// Production cycles run once a day by default; trades later than this after
+/// the last recorded cycle belong to the next period's liquidation.
+const LAST_CYCLE_SPAN: chrono::TimeDelta = chrono::TimeDelta::days(1);
+
+/// (from_token, to_token)
+type SwapPair = (String, String);
+
+/// What one trading cycle did, on either side of the replay
+#[derive(Debug, Clone, Default)]
+pub struct CycleRecord {
+    pub time: DateTime<Utc>,
+    /// Input amount (smallest units) swapped per token pair
+    pub swaps: BTreeMap<SwapPair, BigDecimal>,
+    /// Value share of each holding after the cycle, in percent (wrap.near included)
+    pub weights: BTreeMap<String, f64>,
+}
+
+/// A replayed cycle together with the decision `trade::strategy` made
+#[derive(Debug)]
+pub struct SimulatedCycle {
+    pub record: CycleRecord,
+    /// The strategy's decision, or the error that aborted the cycle
+    pub decision: std::result::Result<CycleReport, String>,
+}
+
+impl SimulatedCycle {
+    fn halt_reason(&self) -> Option<String> {
+        match &self.decision {
+            Ok(report) => report.halted.map(str::to_string),
+            Err(e) => Some(format!("cycle failed: {e}")),
+        }
+    }
+
+    fn selected(&self, token: &str) -> bool {
+        self.decision.as_ref().is_ok_and(|report| {
+            report
+                .selected_tokens
+                .iter()
+                .any(|t| t.to_string() == token)
+        })
+    }
+
+    fn holds_only(&self) -> bool {
+        self.decision.as_ref().is_ok_and(|report| {
+            report
+                .actions
+                .iter()
+                .all(|a| matches!(a, TradingAction::Hold))
+        })
+    }
+
+    /// Target weights (percent) the strategy asked for in this cycle
+    fn target_weights(&self) -> BTreeMap<String, f64> {
+        let Ok(report) = &self.decision else {
+            return BTreeMap::new();
+        };
+        let pct = |w: &BigDecimal| w.to_f64().unwrap_or(0.0) * 100.0;
+        let mut targets = BTreeMap::new();
+        for action in &report.actions {
+            match action {
+                TradingAction::Rebalance { target_weights } => {
+                    for (token, weight) in target_weights {
+                        targets.insert(token.to_string(), pct(weight));
+                    }
+                }
+                TradingAction::AddPosition { token, weight } => {
+                    targets.insert(token.to_string(), pct(weight));
+                }
+                _ => {}
+            }
+        }
+        targets
+    }
+}
+
+/// How simulation and production differed in one cycle
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Divergence {
+    /// Token selected for the period by only one side
+    TokenSelection { token: String, in_production: bool },
+    /// Production traded but the simulated cycle stopped before trading
+    SimulationHalted,
+    /// Swap recorded in production with no simulated counterpart
+    MissingSwap { pair: String, production: String },
+    /// Simulated swap with no production counterpart
+    ExtraSwap { pair: String, simulation: String },
+    /// Both sides swapped the pair, by different amounts
+    SwapAmount {
+        pair: String,
+        production: String,
+        simulation: String,
+        diff_pct: f64,
+    },
+    /// Holding weight after the cycle differs
+    Weight {
+        token: String,
+        production_pct: f64,
+        simulation_pct: f64,
+        simulation_target_pct: Option<f64>,
+    },
+}
+
+/// A divergence located in the replayed period, with the likely cause
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DivergencePoint {
+    pub cycle: usize,
+    pub time: DateTime<Utc>,
+    #[serde(flatten)]
+    pub divergence: Divergence,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayReport {
+    pub period_id: String,
+    pub cycles: usize,
+    /// Production trades outside every recorded cycle (e.g. the next period's liquidation)
+    pub unattributed_production_trades: usize,
+    pub divergences: Vec<DivergencePoint>,
+}
+
+fn pair_label((from, to): &SwapPair) -> String {
+    format!("{} -> {}", from, to)
+}
+
+/// The non-wrap.near side of a swap (the token whose position the swap changes)
+fn position_token((from, to): &SwapPair) -> &str {
+    let wnear = blockchain::ref_finance::token_account::WNEAR_TOKEN.to_string();
+    if *to == wnear { from } else { to }
+}
+
+/// Group production trades into the cycles recorded by `portfolio_holdings`.
+///
+/// Cycle `i` owns the trades from its start until the next cycle starts; the
+/// last cycle owns [`LAST_CYCLE_SPAN`]. Returns per-cycle swaps and the number
+/// of trades that fall outside every cycle.
+pub fn assign_trades(
+    cycle_times: &[NaiveDateTime],
+    trades: &[TradeTransaction],
+) -> (Vec<BTreeMap<SwapPair, BigDecimal>>, usize) {
+    let mut swaps = vec![BTreeMap::new(); cycle_times.len()];
+    let mut unattributed = 0;
+    for trade in trades {
+        let cycle = cycle_times
+            .iter()
+            .rposition(|start| *start <= trade.timestamp)
+            .filter(|&i| {
+                let end = cycle_times
+                    .get(i + 1)
+                    .copied()
+                    .unwrap_or(cycle_times[i] + LAST_CYCLE_SPAN);
+                trade.timestamp < end
+            });
+        match cycle {
+            Some(i) => {
+                let pair = (trade.from_token.clone(), trade.to_token.clone());
+                let amount: &mut BigDecimal = swaps[i].entry(pair).or_default();
+                *amount += trade.from_amount.as_bigdecimal();
+            }
+            None => unattributed += 1,
+        }
+    }
+    (swaps, unattributed)
+}
+
+/// Normalize per-token NEAR values into percentages of the total
+pub fn weights_from_values(values: BTreeMap<String, f64>) -> BTreeMap<String, f64> {
+    let total: f64 = values.values().sum();
+    if total <= 0.0 {
+        return BTreeMap::new();
+    }
+    values
+        .into_iter()
+        .map(|(token, value)| (token, value / total * 100.0))
+        .collect()
+}
+
+async fn holding_weights(
+    holdings: &BTreeMap<TokenAccount, TokenAmount>,
+    at: DateTime<Utc>,
+    rate_provider: &impl RateProvider,
+) -> BTreeMap<String, f64> {
+    let wnear = &*blockchain::ref_finance::token_account::WNEAR_TOKEN;
+    let mut values = BTreeMap::new();
+    for (token, amount) in holdings {
+        if amount.is_zero() {
+            continue;
+        }
+        let value = if token == wnear {
+            to_f64_or_warn(&amount.to_whole(), "replay_wnear_value")
+        } else {
+            match rate_provider.get_rate(&token.to_out(), at).await {
+                Some(rate) => {
+                    let near_value = amount / &rate;
+                    to_f64_or_warn(near_value.as_bigdecimal(), "replay_token_value")
+                }
+                None => continue,
+            }
+        };
+        values.insert(token.to_string(), value);
+    }
+    weights_from_values(values)
+}
+
+fn relative_diff_pct(production: &BigDecimal, simulation: &BigDecimal) -> f64 {
+    if production.is_zero() {
+        return if simulation.is_zero() { 0.0 } else { 100.0 };
+    }
+    ((simulation - production) / production * BigDecimal::from(100))
+        .to_f64()
+        .unwrap_or(f64::INFINITY)
+}
+
+const ALREADY_DIVERGED: &str = "holdings had already diverged in an earlier cycle";
+
+fn explain_missing_swap(pair: &SwapPair, sim: &SimulatedCycle, prior_diverged: bool) -> String {
+    let token = position_token(pair);
+    if let Some(reason) = sim.halt_reason() {
+        format!("simulation halted: {reason}")
+    } else if !sim.selected(token) {
+        format!("simulation did not select {token}")
+    } else if sim.holds_only() {
+        "simulation decided to hold".to_string()
+    } else if prior_diverged {
+        ALREADY_DIVERGED.to_string()
+    } else {
+        match sim.target_weights().get(token) {
+            Some(target) => {
+                format!("simulation targeted {token} at {target:.2}% and did not need this swap")
+            }
+            None => format!("simulation produced no action for {token}"),
+        }
+    }
+}
+
+fn explain_extra_swap(pair: &SwapPair, sim: &SimulatedCycle, prior_diverged: bool) -> String {
+    let token = position_token(pair);
+    if prior_diverged {
+        ALREADY_DIVERGED.to_string()
+    } else {
+        match sim.target_weights().get(token) {
+            Some(target) => format!(
+                "simulation targeted {token} at {target:.2}%; production recorded no such trade \
+                 (it may have failed, been skipped, or been routed differently)"
+            ),
+            None => "production recorded no such trade \
+                     (it may have failed, been skipped, or been routed differently)"
+                .to_string(),
+        }
+    }
+}
+
+fn explain_amount(
+    pair: &SwapPair,
+    prod: &CycleRecord,
+    sim: &SimulatedCycle,
+    prior_diverged: bool,
+    tolerance_pct: f64,
+) -> String {
+    if prior_diverged {
+        return ALREADY_DIVERGED.to_string();
+    }
+    let token = position_token(pair);
+    let production = prod.weights.get(token).copied().unwrap_or(0.0);
+    match sim.target_weights().get(token) {
+        Some(&target) if (target - production).abs() > tolerance_pct => format!(
+            "simulation targeted {token} at {target:.2}%, production ended at {production:.2}%"
+        ),
+        _ => "same target, different valuation (rates or pool state at replay time)".to_string(),
+    }
+}
+
+fn explain_weight(
+    target: Option<f64>,
+    production: f64,
+    prior_diverged: bool,
+    tolerance_pct: f64,
+) -> String {
+    match target {
+        Some(target) if (target - production).abs() <= tolerance_pct => {
+            "simulation targeted the production weight; execution (slippage or pricing fallback) \
+             moved it"
+                .to_string()
+        }
+        Some(target) => format!("simulation targeted {target:.2}%"),
+        None if prior_diverged => ALREADY_DIVERGED.to_string(),
+        None => "simulation set no target for this token".to_string(),
+    }
+}
+
+/// Compare production and simulated cycles and explain every divergence.
+///
+/// `tolerance_pct` is the relative tolerance for swap amounts and the
+/// absolute tolerance (percentage points) for holding weights.
+pub fn diff_cycles(
+    production: &[CycleRecord],
+    simulation: &[SimulatedCycle],
+    production_selection: &[String],
+    tolerance_pct: f64,
+) -> Vec<DivergencePoint> {
+    let mut points = Vec::new();
+
+    if let Some(first) = simulation.first()
+        && let Ok(report) = &first.decision
+        && !report.selected_tokens.is_empty()
+        && !production_selection.is_empty()
+    {
+        let simulated: BTreeSet<String> = report
+            .selected_tokens
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let produced: BTreeSet<String> = production_selection.iter().cloned().collect();
+        for token in produced.symmetric_difference(&simulated) {
+            let in_production = produced.contains(token);
+            points.push(DivergencePoint {
+                cycle: 0,
+                time: first.record.time,
+                divergence: Divergence::TokenSelection {
+                    token: token.clone(),
+                    in_production,
+                },
+                reason: if in_production {
+                    "simulation ranked other tokens higher at period start".to_string()
+                } else {
+                    "production ranked other tokens higher at period start".to_string()
+                },
+            });
+        }
+    }
+
+    let mut prior_diverged = false;
+    for (cycle, (prod, sim)) in production.iter().zip(simulation).enumerate() {
+        let time = prod.time;
+        let mut push = |divergence, reason| {
+            points.push(DivergencePoint {
+                cycle,
+                time,
+                divergence,
+                reason,
+            })
+        };
+
+        if !prod.swaps.is_empty()
+            && sim.record.swaps.is_empty()
+            && let Some(reason) = sim.halt_reason()
+        {
+            push(Divergence::SimulationHalted, reason);
+        }
+
+        let pairs: BTreeSet<&SwapPair> = prod.swaps.keys().chain(sim.record.swaps.keys()).collect();
+        for pair in pairs {
+            let divergence = match (prod.swaps.get(pair), sim.record.swaps.get(pair)) {
+                (Some(p), None) => (
+                    Divergence::MissingSwap {
+                        pair: pair_label(pair),
+                        production: p.to_string(),
+                    },
+                    explain_missing_swap(pair, sim, prior_diverged),
+                ),
+                (None, Some(s)) => (
+                    Divergence::ExtraSwap {
+                        pair: pair_label(pair),
+                        simulation: s.to_string(),
+                    },
+                    explain_extra_swap(pair, sim, prior_diverged),
+                ),
+                (Some(p), Some(s)) => {
+                    let diff_pct = relative_diff_pct(p, s);
+                    if diff_pct.abs() <= tolerance_pct {
+                        continue;
+                    }
+                    (
+                        Divergence::SwapAmount {
+                            pair: pair_label(pair),
+                            production: p.to_string(),
+                            simulation: s.to_string(),
+                            diff_pct,
+                        },
+                        explain_amount(pair, prod, sim, prior_diverged, tolerance_pct),
+                    )
+                }
+                (None, None) => continue,
+            };
+            push(divergence.0, divergence.1);
+        }
+
+        let targets = sim.target_weights();
+        let tokens: BTreeSet<&String> = prod
+            .weights
+            .keys()
+            .chain(sim.record.weights.keys())
+            .collect();
+        let mut weights_diverged = false;
+        for token in tokens {
+            let production_pct = prod.weights.get(token).copied().unwrap_or(0.0);
+            let simulation_pct = sim.record.weights.get(token).copied().unwrap_or(0.0);
+            if (production_pct - simulation_pct).abs() <= tolerance_pct {
+                continue;
+            }
+            weights_diverged = true;
+            let target = targets.get(token).copied();
+            push(
+                Divergence::Weight {
+                    token: token.clone(),
+                    production_pct,
+                    simulation_pct,
+                    simulation_target_pct: target,
+                },
+                explain_weight(target, production_pct, prior_diverged, tolerance_pct),
+            );
+        }
+        prior_diverged |= weights_diverged;
+    }
+
+    points
+}
+
+/// Load the cycles production ran in `period` from `portfolio_holdings` and `trade_transactions`
+async fn load_production_cycles(period: &EvaluationPeriod) -> Result<(Vec<CycleRecord>, usize)> {
+    let mut holdings = PortfolioHolding::get_by_period_async(period.period_id.clone()).await?;
+    holdings.reverse();
+
+    let trades = TradeTransaction::find_by_period_after_async(
+        period.period_id.clone(),
+        period.start_time - chrono::TimeDelta::days(1),
+    )
+    .await?;
+    let cycle_times: Vec<NaiveDateTime> = holdings.iter().map(|h| h.timestamp).collect();
+    let (swaps, unattributed) = assign_trades(&cycle_times, &trades);
+
+    let mut cycles = Vec::with_capacity(holdings.len());
+    for (holding, swaps) in holdings.iter().zip(swaps) {
+        let time = Utc.from_utc_datetime(&holding.timestamp);
+        let balances: BTreeMap<TokenAccount, TokenAmount> = holding
+            .parse_holdings()?
+            .into_iter()
+            .map(|h| {
+                let amount =
+                    TokenAmount::from_smallest_units(h.balance.as_bigdecimal().clone(), h.decimals);
+                (h.token, amount)
+            })
+            .collect();
+        cycles.push(CycleRecord {
+            time,
+            swaps,
+            weights: holding_weights(&balances, time, &DbRateProvider).await,
+        });
+    }
+    Ok((cycles, unattributed))
+}
+
+/// Run `trade::strategy::replay_cycle` at each production cycle time.
+///
+/// `replay_cycle` is the part of `trade::strategy::start` that runs once the
+/// evaluation period is known. It skips `start`'s period management, which
+/// looks up the latest period, liquidates and harvests when a period ends,
+/// and honours `TRADE_ENABLED`. A replay covers a single production period,
+/// so none of those steps would run inside it. Pinning the period also
+/// avoids `start` comparing simulated times with the period's wall-clock
+/// `start_time`.
+///
+/// The simulated trades and holdings are recorded under a temporary
+/// evaluation period with its own `sim_` prefix, which is deleted afterwards.
+async fn replay_cycles(
+    period: &EvaluationPeriod,
+    times: &[DateTime<Utc>],
+) -> Result<Vec<SimulatedCycle>> {
+    let log = DEFAULT.new(o!(
+        "function" => "replay_cycles",
+        "period_id" => period.period_id.clone(),
+    ));
+
+    let initial_value = period.initial_value.to_value();
+    let period_prefix = crate::engine::run_prefix();
+    // TRADE_INITIAL_INVESTMENT is whole NEAR; a fractional value would not parse
+    let initial_investment = yocto_to_near(period.initial_value.to_u128()).round() as u32;
+    let cfg = ConfigOverlay::new()
+        .with("TRADE_INITIAL_INVESTMENT", initial_investment)
+        .with("TRADE_ENABLED", true)
+        .with("TRADE_EVALUATION_PERIOD_PREFIX", &period_prefix);
+
+    if let Err(e) = trade::token_cache::load_from_db().await {
+        warn!(log, "failed to load token decimals cache"; "error" => ?e);
+    }
+
+    let portfolio = Arc::new(Mutex::new(PortfolioState::new(initial_value.clone())));
+    let sim_day = Arc::new(Mutex::new(times.first().copied().unwrap_or_else(Utc::now)));
+    // Recorded pool snapshots already contain production's own impact from
+    // earlier cycles, so simulated impact only carries within a cycle.
+    let client = SimulationClient::new(Arc::clone(&portfolio), initial_value, Arc::clone(&sim_day))
+        .with_market_impact(MarketImpact::new(ImpactDecay::PerCycle));
+    let wallet = SimulationWallet::new();
+
+    let replay_period =
+        NewEvaluationPeriod::with_prefix(&period_prefix, period.initial_value.clone(), vec![])
+            .insert_async()
+            .await?;
+    info!(log, "created replay period"; "replay_period_id" => %replay_period.period_id);
+
+    let wnear = blockchain::ref_finance::token_account::WNEAR_TOKEN.clone();
+    let mut cycles = Vec::with_capacity(times.len());
+    let mut selected: Vec<TokenAccount> = Vec::new();
+    for &at in times {
+        *sim_day.lock().await = at;
+        let decision = trade::strategy::replay_cycle(
+            &client,
+            &wallet,
+            at,
+            replay_period.period_id.clone(),
+            selected.is_empty(),
+            selected.clone(),
+            &cfg,
+        )
+        .await;
+        if let Ok(report) = &decision
+            && !report.selected_tokens.is_empty()
+        {
+            selected = report.selected_tokens.clone();
+        }
+
+        let (swaps, holdings) = {
+            let state = portfolio.lock().await;
+            let mut swaps: BTreeMap<SwapPair, BigDecimal> = BTreeMap::new();
+            for event in state.swap_events.iter().filter(|e| e.timestamp == at) {
+                let pair = (event.token_in.to_string(), event.token_out.to_string());
+                *swaps.entry(pair).or_default() += event.amount_in.smallest_units();
+            }
+            let mut holdings = state.holdings.clone();
+            holdings.insert(
+                wnear.clone(),
+                TokenAmount::from_smallest_units(state.cash_balance.as_bigdecimal().clone(), 24),
+            );
+            (swaps, holdings)
+        };
+
+        debug!(log, "replayed cycle"; "time" => %at, "swaps" => swaps.len());
+        cycles.push(SimulatedCycle {
+            record: CycleRecord {
+                time: at,
+                swaps,
+                weights: holding_weights(&holdings, at, &DbRateProvider).await,
+            },
+            decision: decision.map_err(|e| format!("{e:#}")),
+        });
+    }
+
+    if let Err(e) = EvaluationPeriod::delete_by_period_id_async(replay_period.period_id).await {
+        warn!(log, "failed to delete replay period"; "error" => %e);
+    }
+    Ok(cycles)
+}
+
+fn print_text_report(report: &ReplayReport) {
+    println!("\n=== Replay Report ===");
+    println!("Period: {}", report.period_id);
+    println!("Cycles replayed: {}", report.cycles);
+    if report.unattributed_production_trades > 0 {
+        println!(
+            "Production trades outside recorded cycles: {}",
+            report.unattributed_production_trades
+        );
+    }
+
+    if report.divergences.is_empty() {
+        println!("\nSimulation matched production in every cycle.");
+        return;
+    }
+
+    println!("\nDivergences: {}", report.divergences.len());
+    for point in &report.divergences {
+        let what = match &point.divergence {
+            Divergence::TokenSelection {
+                token,
+                in_production,
+            } => format!(
+                "{} selected only in {}",
+                token,
+                if *in_production {
+                    "production"
+                } else {
+                    "simulation"
+                }
+            ),
+            Divergence::SimulationHalted => "simulation did not trade".to_string(),
+            Divergence::MissingSwap { pair, production } => {
+                format!("{pair}: production swapped {production}, simulation did not")
+            }
+            Divergence::ExtraSwap { pair, simulation } => {
+                format!("{pair}: simulation swapped {simulation}, production did not")
+            }
+            Divergence::SwapAmount {
+                pair,
+                production,
+                simulation,
+                diff_pct,
+            } => format!(
+                "{pair}: production {production}, simulation {simulation} ({diff_pct:+.2}%)"
+            ),
+            Divergence::Weight {
+                token,
+                production_pct,
+                simulation_pct,
+                ..
+            } => format!(
+                "{token}: weight production {production_pct:.2}%, simulation {simulation_pct:.2}%"
+            ),
+        };
+        println!(
+            "  [cycle {} {}] {}\n      why: {}",
+            point.cycle,
+            point.time.format("%Y-%m-%d %H:%M"),
+            what,
+            point.reason
+        );
+    }
+}
+
+pub async fn run_replay(args: &ReplayArgs) -> Result<()> {
+    let log = DEFAULT.new(o!("function" => "run_replay", "period_id" => args.period_id.clone()));
+
+    let period = EvaluationPeriod::get_by_period_id_async(args.period_id.clone())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("evaluation period not found: {}", args.period_id))?;
+
+    let (production, unattributed) = load_production_cycles(&period).await?;
+    if production.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no portfolio_holdings recorded for period {}",
+            args.period_id
+        ));
+    }
+    info!(log, "loaded production cycles";
+        "cycles" => production.len(), "unattributed_trades" => unattributed);
+
+    let times: Vec<DateTime<Utc>> = production.iter().map(|c| c.time).collect();
+    let simulation = replay_cycles(&period, &times).await?;
+
+    let production_selection: Vec<String> = period
+        .selected_tokens
+        .iter()
+        .flatten()
+        .flatten()
+        .cloned()
+        .collect();
+    let report = ReplayReport {
+        period_id: period.period_id.clone(),
+        cycles: production.len(),
+        unattributed_production_trades: unattributed,
+        divergences: diff_cycles(
+            &production,
+            &simulation,
+            &production_selection,
+            args.tolerance_pct,
+        ),
+    };
+
+    match args.format {
+        crate::cli::OutputFormat::Text => print_text_report(&report),
+        crate::cli::OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
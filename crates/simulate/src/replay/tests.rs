@@ -0,0 +1,227 @@
+use super::*;
+use common::types::{TokenOutAccount, TokenSmallestUnits};
+use std::str::FromStr;
+
+const WNEAR: &str = "wrap.near";
+const TOKEN: &str = "token.near";
+
+fn at(hour: u32) -> NaiveDateTime {
+    chrono::NaiveDate::from_ymd_opt(2026, 1, 1)
+        .unwrap()
+        .and_hms_opt(hour, 0, 0)
+        .unwrap()
+}
+
+fn make_tx(from: &str, to: &str, amount: u64, timestamp: NaiveDateTime) -> TradeTransaction {
+    TradeTransaction {
+        tx_id: format!("tx_{from}_{to}_{timestamp}"),
+        trade_batch_id: "batch_1".to_string(),
+        from_token: from.to_string(),
+        from_amount: TokenSmallestUnits::from(BigDecimal::from(amount)),
+        to_token: to.to_string(),
+        to_amount: TokenSmallestUnits::from(BigDecimal::from(amount)),
+        timestamp,
+        evaluation_period_id: "eval_test".to_string(),
+        actual_to_amount: None,
+        gas_burnt: None,
+        gas_cost: None,
+    }
+}
+
+fn pair(from: &str, to: &str) -> SwapPair {
+    (from.to_string(), to.to_string())
+}
+
+fn record(hour: u32, swaps: &[(&str, &str, u64)], weights: &[(&str, f64)]) -> CycleRecord {
+    CycleRecord {
+        time: Utc.from_utc_datetime(&at(hour)),
+        swaps: swaps
+            .iter()
+            .map(|(from, to, amount)| (pair(from, to), BigDecimal::from(*amount)))
+            .collect(),
+        weights: weights.iter().map(|(t, w)| (t.to_string(), *w)).collect(),
+    }
+}
+
+fn rebalance(weights: &[(&str, &str)]) -> TradingAction {
+    TradingAction::Rebalance {
+        target_weights: weights
+            .iter()
+            .map(|(token, weight)| {
+                (
+                    TokenOutAccount::from_str(token).unwrap(),
+                    BigDecimal::from_str(weight).unwrap(),
+                )
+            })
+            .collect(),
+    }
+}
+
+fn decided(record: CycleRecord, actions: Vec<TradingAction>) -> SimulatedCycle {
+    SimulatedCycle {
+        record,
+        decision: Ok(CycleReport {
+            selected_tokens: vec![TokenAccount::from_str(TOKEN).unwrap()],
+            actions,
+            ..Default::default()
+        }),
+    }
+}
+
+#[test]
+fn assign_trades_groups_by_cycle_window() {
+    let times = [at(0), at(6)];
+    let trades = [
+        make_tx(WNEAR, TOKEN, 100, at(0)),
+        make_tx(WNEAR, TOKEN, 50, at(1)),
+        make_tx(TOKEN, WNEAR, 30, at(7)),
+    ];
+
+    let (swaps, unattributed) = assign_trades(&times, &trades);
+
+    assert_eq!(unattributed, 0);
+    assert_eq!(swaps[0][&pair(WNEAR, TOKEN)], BigDecimal::from(150));
+    assert_eq!(swaps[1][&pair(TOKEN, WNEAR)], BigDecimal::from(30));
+}
+
+#[test]
+fn assign_trades_counts_trades_outside_cycles() {
+    let times = [at(6)];
+    let before = make_tx(WNEAR, TOKEN, 1, at(5));
+    let after = make_tx(
+        TOKEN,
+        WNEAR,
+        1,
+        at(6) + LAST_CYCLE_SPAN + chrono::TimeDelta::minutes(1),
+    );
+
+    let (swaps, unattributed) = assign_trades(&times, &[before, after]);
+
+    assert!(swaps[0].is_empty());
+    assert_eq!(unattributed, 2);
+}
+
+#[test]
+fn weights_from_values_normalizes_to_percent() {
+    let values = BTreeMap::from([(WNEAR.to_string(), 1.0), (TOKEN.to_string(), 3.0)]);
+
+    let weights = weights_from_values(values);
+
+    assert!((weights[WNEAR] - 25.0).abs() < 1e-9);
+    assert!((weights[TOKEN] - 75.0).abs() < 1e-9);
+    assert!(weights_from_values(BTreeMap::new()).is_empty());
+}
+
+#[test]
+fn diff_cycles_matching_cycles_have_no_divergence() {
+    let prod = vec![record(
+        0,
+        &[(WNEAR, TOKEN, 1000)],
+        &[(WNEAR, 50.0), (TOKEN, 50.0)],
+    )];
+    let sim = vec![decided(
+        record(0, &[(WNEAR, TOKEN, 1005)], &[(WNEAR, 50.4), (TOKEN, 49.6)]),
+        vec![rebalance(&[(TOKEN, "0.5")])],
+    )];
+
+    let points = diff_cycles(&prod, &sim, &[TOKEN.to_string()], 1.0);
+
+    assert!(points.is_empty(), "{points:?}");
+}
+
+#[test]
+fn diff_cycles_reports_token_selection() {
+    let prod = vec![record(0, &[], &[])];
+    let sim = vec![decided(record(0, &[], &[]), vec![TradingAction::Hold])];
+
+    let points = diff_cycles(&prod, &sim, &["other.near".to_string()], 1.0);
+
+    assert_eq!(points.len(), 2);
+    assert!(points.iter().any(|p| p.divergence
+        == Divergence::TokenSelection {
+            token: "other.near".to_string(),
+            in_production: true,
+        }));
+    assert!(points.iter().any(|p| p.divergence
+        == Divergence::TokenSelection {
+            token: TOKEN.to_string(),
+            in_production: false,
+        }));
+}
+
+#[test]
+fn diff_cycles_explains_halted_simulation() {
+    let prod = vec![record(0, &[(WNEAR, TOKEN, 1000)], &[])];
+    let sim = vec![SimulatedCycle {
+        record: record(0, &[], &[]),
+        decision: Ok(CycleReport {
+            halted: Some("no funds available for trading"),
+            ..Default::default()
+        }),
+    }];
+
+    let points = diff_cycles(&prod, &sim, &[], 1.0);
+
+    assert_eq!(points[0].divergence, Divergence::SimulationHalted);
+    assert_eq!(points[0].reason, "no funds available for trading");
+    assert!(matches!(
+        points[1].divergence,
+        Divergence::MissingSwap { .. }
+    ));
+    assert!(points[1].reason.starts_with("simulation halted"));
+}
+
+#[test]
+fn diff_cycles_attributes_amount_to_target_weight() {
+    let prod = vec![record(
+        0,
+        &[(WNEAR, TOKEN, 1000)],
+        &[(WNEAR, 50.0), (TOKEN, 50.0)],
+    )];
+    let sim = vec![decided(
+        record(0, &[(WNEAR, TOKEN, 1600)], &[(WNEAR, 20.0), (TOKEN, 80.0)]),
+        vec![rebalance(&[(TOKEN, "0.8")])],
+    )];
+
+    let points = diff_cycles(&prod, &sim, &[], 1.0);
+
+    let amount = points
+        .iter()
+        .find(|p| matches!(p.divergence, Divergence::SwapAmount { .. }))
+        .unwrap();
+    assert!(amount.reason.contains("targeted token.near at 80.00%"));
+    let weight = points
+        .iter()
+        .find(|p| matches!(&p.divergence, Divergence::Weight { token, .. } if token == TOKEN))
+        .unwrap();
+    assert_eq!(
+        weight.divergence,
+        Divergence::Weight {
+            token: TOKEN.to_string(),
+            production_pct: 50.0,
+            simulation_pct: 80.0,
+            simulation_target_pct: Some(80.0),
+        }
+    );
+}
+
+#[test]
+fn diff_cycles_marks_later_cycles_as_already_diverged() {
+    let prod = vec![
+        record(0, &[], &[(WNEAR, 50.0), (TOKEN, 50.0)]),
+        record(6, &[(TOKEN, WNEAR, 10)], &[(WNEAR, 60.0), (TOKEN, 40.0)]),
+    ];
+    let sim = vec![
+        decided(record(0, &[], &[(WNEAR, 70.0), (TOKEN, 30.0)]), vec![]),
+        decided(
+            record(6, &[(TOKEN, WNEAR, 30)], &[(WNEAR, 60.0), (TOKEN, 40.0)]),
+            vec![],
+        ),
+    ];
+
+    let points = diff_cycles(&prod, &sim, &[], 1.0);
+
+    let later: Vec<_> = points.iter().filter(|p| p.cycle == 1).collect();
+    assert_eq!(later.len(), 1);
+    assert_eq!(later[0].reason, ALREADY_DIVERGED);
+}
@@ -0,0 +1,789 @@
+use crate::cli::ReportArgs;
+use crate::output::{PortfolioValueEntry, SimulationResult};
+use crate::portfolio_state::{SwapMethod, TradeAction};
+use crate::sweep::{SweepEntry, SweepParameters, SweepResult};
+use anyhow::{Context, Result};
+use logging::*;
+use serde::Deserialize;
+use std::fmt::{self, Write};
+
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 260.0;
+const MARGIN_LEFT: f64 = 70.0;
+const MARGIN_RIGHT: f64 = 10.0;
+const MARGIN_TOP: f64 = 10.0;
+const MARGIN_BOTTOM: f64 = 30.0;
+
+const PALETTE: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+];
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:right}\
+th{background:#f0f0f0}td.l,th.l{text-align:left}\
+.legend span{display:inline-block;margin-right:1em}\
+.swatch{display:inline-block;width:10px;height:10px;margin-right:4px}\
+svg{background:#fafafa;margin-bottom:0.5em}";
+
+/// JSON files `simulate report` accepts.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ReportInput {
+    Simulation(Box<SimulationResult>),
+    Sweep(SweepResult),
+}
+
+impl ReportInput {
+    pub(crate) fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .context("input is neither a simulation result nor a sweep summary JSON")
+    }
+
+    pub(crate) fn render(&self) -> Result<String> {
+        let mut out = String::new();
+        match self {
+            Self::Simulation(result) => write_simulation(&mut out, result)?,
+            Self::Sweep(sweep) => write_sweep(&mut out, sweep)?,
+        }
+        Ok(out)
+    }
+}
+
+pub fn run_report(args: &ReportArgs) -> Result<()> {
+    let log = DEFAULT.new(o!("function" => "run_report"));
+    let json = std::fs::read_to_string(&args.input)
+        .with_context(|| format!("failed to read {}", args.input.display()))?;
+    let html = ReportInput::parse(&json)?.render()?;
+    std::fs::write(&args.output, html)
+        .with_context(|| format!("failed to write {}", args.output.display()))?;
+    info!(log, "report written"; "path" => args.output.display().to_string());
+    Ok(())
+}
+
+/// Escape `s` for use in HTML text and attribute values.
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn pct(value: f64) -> String {
+    format!("{:+.2}%", value * 100.0)
+}
+
+/// Date part of an RFC 3339 timestamp.
+fn date_of(timestamp: &str) -> &str {
+    timestamp.get(..10).unwrap_or(timestamp)
+}
+
+fn write_header(out: &mut String, title: &str) -> fmt::Result {
+    write!(
+        out,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title>\
+         <style>{STYLE}</style></head><body><h1>{}</h1>",
+        escape(title),
+        escape(title)
+    )
+}
+
+fn write_footer(out: &mut String) -> fmt::Result {
+    out.write_str("</body></html>")
+}
+
+fn write_table(out: &mut String, headers: &[&str], rows: &[Vec<String>]) -> fmt::Result {
+    out.write_str("<table><tr>")?;
+    for (i, header) in headers.iter().enumerate() {
+        let class = if i == 0 { " class=\"l\"" } else { "" };
+        write!(out, "<th{class}>{}</th>", escape(header))?;
+    }
+    out.write_str("</tr>")?;
+    for row in rows {
+        out.write_str("<tr>")?;
+        for (i, cell) in row.iter().enumerate() {
+            let class = if i == 0 { " class=\"l\"" } else { "" };
+            write!(out, "<td{class}>{}</td>", escape(cell))?;
+        }
+        out.write_str("</tr>")?;
+    }
+    out.write_str("</table>")
+}
+
+// --- charts ---
+
+struct Series<'a> {
+    label: String,
+    values: &'a [f64],
+}
+
+/// Maps indices and values onto the plot area of a chart.
+struct Plot {
+    len: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Plot {
+    fn new(len: usize, values: impl Iterator<Item = f64>) -> Self {
+        let (mut min, mut max) = values
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        if min > max {
+            (min, max) = (0.0, 1.0);
+        } else if min == max {
+            let pad = if min == 0.0 { 1.0 } else { min.abs() * 0.05 };
+            (min, max) = (min - pad, max + pad);
+        }
+        Self { len, min, max }
+    }
+
+    fn x(&self, i: usize) -> f64 {
+        let width = CHART_WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+        MARGIN_LEFT + width * i as f64 / self.len.saturating_sub(1).max(1) as f64
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        let height = CHART_HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+        MARGIN_TOP + height * (self.max - value) / (self.max - self.min)
+    }
+
+    fn write_axes(
+        &self,
+        out: &mut String,
+        labels: &[String],
+        format_y: fn(f64) -> String,
+    ) -> fmt::Result {
+        let bottom = CHART_HEIGHT - MARGIN_BOTTOM;
+        write!(
+            out,
+            "<line x1=\"{MARGIN_LEFT}\" y1=\"{MARGIN_TOP}\" x2=\"{MARGIN_LEFT}\" y2=\"{bottom}\" stroke=\"#999\"/>\
+             <line x1=\"{MARGIN_LEFT}\" y1=\"{bottom}\" x2=\"{}\" y2=\"{bottom}\" stroke=\"#999\"/>",
+            CHART_WIDTH - MARGIN_RIGHT
+        )?;
+        for value in [self.max, self.min] {
+            write!(
+                out,
+                "<text x=\"{}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"end\">{}</text>",
+                MARGIN_LEFT - 4.0,
+                self.y(value) + 4.0,
+                escape(&format_y(value))
+            )?;
+        }
+        if let (Some(first), Some(last)) = (labels.first(), labels.last()) {
+            let y = CHART_HEIGHT - 10.0;
+            write!(
+                out,
+                "<text x=\"{MARGIN_LEFT}\" y=\"{y}\" font-size=\"11\">{}</text>\
+                 <text x=\"{}\" y=\"{y}\" font-size=\"11\" text-anchor=\"end\">{}</text>",
+                escape(first),
+                CHART_WIDTH - MARGIN_RIGHT,
+                escape(last)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn write_legend(out: &mut String, labels: &[String]) -> fmt::Result {
+    out.write_str("<div class=\"legend\">")?;
+    for (i, label) in labels.iter().enumerate() {
+        write!(
+            out,
+            "<span><span class=\"swatch\" style=\"background:{}\"></span>{}</span>",
+            PALETTE[i % PALETTE.len()],
+            escape(label)
+        )?;
+    }
+    out.write_str("</div>")
+}
+
+/// Line chart of `series` over `labels`; hovering a point shows its value.
+fn write_line_chart(
+    out: &mut String,
+    labels: &[String],
+    series: &[Series<'_>],
+    format_y: fn(f64) -> String,
+) -> fmt::Result {
+    let plot = Plot::new(
+        labels.len(),
+        series.iter().flat_map(|s| s.values.iter().copied()),
+    );
+    write!(
+        out,
+        "<svg width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" xmlns=\"http://www.w3.org/2000/svg\">"
+    )?;
+    plot.write_axes(out, labels, format_y)?;
+    for (i, s) in series.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let points: Vec<String> = s
+            .values
+            .iter()
+            .enumerate()
+            .map(|(j, v)| format!("{:.1},{:.1}", plot.x(j), plot.y(*v)))
+            .collect();
+        write!(
+            out,
+            "<polyline fill=\"none\" stroke=\"{color}\" stroke-width=\"1.5\" points=\"{}\"/>",
+            points.join(" ")
+        )?;
+        for (j, (v, label)) in s.values.iter().zip(labels).enumerate() {
+            write!(
+                out,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2.5\" fill=\"{color}\"><title>{} {}: {}</title></circle>",
+                plot.x(j),
+                plot.y(*v),
+                escape(&s.label),
+                escape(label),
+                escape(&format_y(*v))
+            )?;
+        }
+    }
+    out.write_str("</svg>")?;
+    let names: Vec<String> = series.iter().map(|s| s.label.clone()).collect();
+    write_legend(out, &names)
+}
+
+/// Stacked area chart of `layers` (fractions per label, summing to 1).
+fn write_stacked_chart(
+    out: &mut String,
+    labels: &[String],
+    layers: &[(String, Vec<f64>)],
+) -> fmt::Result {
+    let plot = Plot::new(labels.len(), [0.0, 1.0].into_iter());
+    write!(
+        out,
+        "<svg width=\"{CHART_WIDTH}\" height=\"{CHART_HEIGHT}\" xmlns=\"http://www.w3.org/2000/svg\">"
+    )?;
+    plot.write_axes(out, labels, pct_axis)?;
+    let mut base = vec![0.0; labels.len()];
+    for (i, (name, fractions)) in layers.iter().enumerate() {
+        let top: Vec<f64> = base.iter().zip(fractions).map(|(b, f)| b + f).collect();
+        let mut points: Vec<String> = top
+            .iter()
+            .enumerate()
+            .map(|(j, v)| format!("{:.1},{:.1}", plot.x(j), plot.y(*v)))
+            .collect();
+        points.extend(
+            base.iter()
+                .enumerate()
+                .rev()
+                .map(|(j, v)| format!("{:.1},{:.1}", plot.x(j), plot.y(*v))),
+        );
+        let last = fractions.last().copied().unwrap_or(0.0);
+        write!(
+            out,
+            "<polygon fill=\"{}\" fill-opacity=\"0.8\" points=\"{}\"><title>{}: {:.1}% at end</title></polygon>",
+            PALETTE[i % PALETTE.len()],
+            points.join(" "),
+            escape(name),
+            last * 100.0
+        )?;
+        base = top;
+    }
+    out.write_str("</svg>")?;
+    let names: Vec<String> = layers.iter().map(|(name, _)| name.clone()).collect();
+    write_legend(out, &names)
+}
+
+/// Horizontal bars, green for positive and red for negative values.
+fn write_bar_chart(out: &mut String, bars: &[(String, f64)]) -> fmt::Result {
+    let row_height = 22.0;
+    let height = row_height * bars.len() as f64 + MARGIN_TOP * 2.0;
+    let label_width = 180.0;
+    let half = (CHART_WIDTH - label_width - MARGIN_RIGHT) / 2.0;
+    let zero = label_width + half;
+    let scale = bars.iter().map(|(_, v)| v.abs()).fold(0.0, f64::max);
+    write!(
+        out,
+        "<svg width=\"{CHART_WIDTH}\" height=\"{height}\" xmlns=\"http://www.w3.org/2000/svg\">\
+         <line x1=\"{zero}\" y1=\"0\" x2=\"{zero}\" y2=\"{height}\" stroke=\"#999\"/>"
+    )?;
+    for (i, (name, value)) in bars.iter().enumerate() {
+        let y = MARGIN_TOP + row_height * i as f64;
+        let width = if scale > 0.0 {
+            half * value.abs() / scale
+        } else {
+            0.0
+        };
+        let (x, color) = if *value >= 0.0 {
+            (zero, "#2ca02c")
+        } else {
+            (zero - width, "#d62728")
+        };
+        write!(
+            out,
+            "<text x=\"{}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"end\">{}</text>\
+             <rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{width:.1}\" height=\"{:.1}\" fill=\"{color}\">\
+             <title>{}: {:+.4} NEAR</title></rect>",
+            label_width - 6.0,
+            y + 14.0,
+            escape(name),
+            row_height - 6.0,
+            escape(name),
+            value
+        )?;
+    }
+    out.write_str("</svg>")
+}
+
+fn near_axis(value: f64) -> String {
+    format!("{value:.2} NEAR")
+}
+
+fn pct_axis(value: f64) -> String {
+    format!("{:.1}%", value * 100.0)
+}
+
+// --- simulation report ---
+
+/// Drawdown from the running peak at each point (0 or negative).
+pub(crate) fn drawdowns(values: &[f64]) -> Vec<f64> {
+    let mut peak = f64::NEG_INFINITY;
+    values
+        .iter()
+        .map(|&value| {
+            peak = peak.max(value);
+            if peak > 0.0 { value / peak - 1.0 } else { 0.0 }
+        })
+        .collect()
+}
+
+/// Share of cash and of each token in the portfolio value at every point,
+/// cash first and tokens in name order.
+pub(crate) fn allocation_layers(values: &[PortfolioValueEntry]) -> Vec<(String, Vec<f64>)> {
+    let mut tokens: Vec<&String> = values
+        .iter()
+        .flat_map(|v| v.holding_values_near.keys())
+        .collect();
+    tokens.sort();
+    tokens.dedup();
+
+    let totals: Vec<f64> = values
+        .iter()
+        .map(|v| v.cash_balance + v.holding_values_near.values().sum::<f64>())
+        .collect();
+    let share = |value: f64, total: f64| if total > 0.0 { value / total } else { 0.0 };
+
+    let mut layers = vec![(
+        "cash".to_string(),
+        values
+            .iter()
+            .zip(&totals)
+            .map(|(v, &total)| share(v.cash_balance, total))
+            .collect(),
+    )];
+    for token in tokens {
+        layers.push((
+            token.clone(),
+            values
+                .iter()
+                .zip(&totals)
+                .map(|(v, &total)| {
+                    share(
+                        v.holding_values_near.get(token).copied().unwrap_or(0.0),
+                        total,
+                    )
+                })
+                .collect(),
+        ));
+    }
+    layers
+}
+
+fn write_simulation(out: &mut String, result: &SimulationResult) -> fmt::Result {
+    let config = &result.config;
+    let perf = &result.performance;
+    let params = &config.parameters;
+    write_header(
+        out,
+        &format!("Simulation {} to {}", config.start_date, config.end_date),
+    )?;
+
+    out.write_str("<h2>Summary</h2>")?;
+    let summary = [
+        ("Initial capital", near_axis(config.initial_capital)),
+        ("Final balance", near_axis(perf.final_balance_near)),
+        ("Total return", pct(perf.total_return)),
+        ("Sharpe ratio", format!("{:.3}", perf.sharpe_ratio)),
+        ("Sortino ratio", format!("{:.3}", perf.sortino_ratio)),
+        ("Max drawdown", format!("{:.2}%", perf.max_drawdown * 100.0)),
+        ("Win rate", format!("{:.1}%", perf.win_rate * 100.0)),
+        (
+            "Realized P&L",
+            format!("{:+.4} NEAR", perf.total_realized_pnl_near),
+        ),
+        ("Trades", perf.trade_count.to_string()),
+        ("Liquidations", perf.liquidation_count.to_string()),
+        ("Swaps", perf.swap_stats.total_swaps.to_string()),
+        (
+            "DB-rate fallback",
+            format!("{:.1}%", perf.swap_stats.fallback_rate * 100.0),
+        ),
+        ("Top tokens", params.top_tokens.to_string()),
+        ("Price history days", params.price_history_days.to_string()),
+        (
+            "Rebalance threshold",
+            params.rebalance_threshold.to_string(),
+        ),
+        (
+            "Rebalance interval days",
+            params.rebalance_interval_days.to_string(),
+        ),
+    ];
+    let rows: Vec<Vec<String>> = summary
+        .into_iter()
+        .map(|(name, value)| vec![name.to_string(), value])
+        .collect();
+    write_table(out, &["Metric", "Value"], &rows)?;
+
+    if !result.benchmarks.is_empty() {
+        out.write_str("<h2>Benchmarks</h2>")?;
+        let rows: Vec<Vec<String>> = result
+            .benchmarks
+            .iter()
+            .map(|b| {
+                vec![
+                    b.benchmark.to_string(),
+                    b.tokens.join(", "),
+                    pct(b.total_return),
+                    format!("{:.4}", b.final_balance_near),
+                    pct(b.alpha),
+                    format!("{:.3}", b.beta),
+                    pct(b.tracking_error),
+                    format!("{:.3}", b.information_ratio),
+                ]
+            })
+            .collect();
+        write_table(
+            out,
+            &[
+                "Benchmark",
+                "Tokens",
+                "Return",
+                "Final NEAR",
+                "Alpha",
+                "Beta",
+                "Tracking error",
+                "Information ratio",
+            ],
+            &rows,
+        )?;
+    }
+
+    let values = &result.portfolio_values;
+    let labels: Vec<String> = values
+        .iter()
+        .map(|v| date_of(&v.timestamp).to_string())
+        .collect();
+    let totals: Vec<f64> = values.iter().map(|v| v.total_value).collect();
+
+    out.write_str("<h2>Equity curve</h2>")?;
+    let mut series = vec![Series {
+        label: "strategy".to_string(),
+        values: &totals,
+    }];
+    series.extend(
+        result
+            .benchmarks
+            .iter()
+            .filter(|b| b.values.len() == totals.len())
+            .map(|b| Series {
+                label: b.benchmark.to_string(),
+                values: &b.values,
+            }),
+    );
+    write_line_chart(out, &labels, &series, near_axis)?;
+
+    out.write_str("<h2>Drawdown</h2>")?;
+    let drawdown = drawdowns(&totals);
+    write_line_chart(
+        out,
+        &labels,
+        &[Series {
+            label: "drawdown".to_string(),
+            values: &drawdown,
+        }],
+        pct_axis,
+    )?;
+
+    out.write_str("<h2>Allocation</h2>")?;
+    write_stacked_chart(out, &labels, &allocation_layers(values))?;
+
+    out.write_str("<h2>P&amp;L by token</h2>")?;
+    let bars: Vec<(String, f64)> = result
+        .pnl_by_token
+        .iter()
+        .map(|(token, pnl)| (token.clone(), pnl.realized_near + pnl.unrealized_near))
+        .collect();
+    write_bar_chart(out, &bars)?;
+    let rows: Vec<Vec<String>> = result
+        .pnl_by_token
+        .iter()
+        .map(|(token, pnl)| {
+            vec![
+                token.clone(),
+                format!("{:+.4}", pnl.realized_near),
+                format!("{:+.4}", pnl.unrealized_near),
+                format!("{:+.4}", pnl.realized_near + pnl.unrealized_near),
+            ]
+        })
+        .collect();
+    write_table(
+        out,
+        &["Token", "Realized NEAR", "Unrealized NEAR", "Total NEAR"],
+        &rows,
+    )?;
+
+    out.write_str("<h2>Swaps</h2>")?;
+    let rows: Vec<Vec<String>> = result
+        .swap_events
+        .iter()
+        .map(|e| {
+            vec![
+                e.timestamp.clone(),
+                e.token_in.clone(),
+                e.amount_in.clone(),
+                e.token_out.clone(),
+                e.amount_out.clone(),
+                match e.swap_method {
+                    SwapMethod::PoolBased => "pool".to_string(),
+                    SwapMethod::DbRate => "db rate".to_string(),
+                },
+                e.slippage
+                    .map(|s| format!("{:.3}%", s * 100.0))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    write_table(
+        out,
+        &[
+            "Time",
+            "Token in",
+            "Amount in",
+            "Token out",
+            "Amount out",
+            "Method",
+            "Slippage",
+        ],
+        &rows,
+    )?;
+
+    out.write_str("<h2>Liquidations</h2>")?;
+    let rows: Vec<Vec<String>> = result
+        .trades
+        .iter()
+        .filter(|t| t.action == TradeAction::Liquidation)
+        .map(|t| {
+            vec![
+                t.timestamp.clone(),
+                t.token.clone(),
+                format!("{:.4}", t.price),
+                t.realized_pnl
+                    .map(|p| format!("{p:+.4}"))
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+    write_table(
+        out,
+        &["Time", "Token", "Proceeds NEAR", "Realized P&L NEAR"],
+        &rows,
+    )?;
+
+    write_footer(out)
+}
+
+// --- sweep report ---
+
+const PARAMETER_NAMES: [&str; 4] = [
+    "top_tokens",
+    "price_history_days",
+    "rebalance_threshold",
+    "rebalance_interval_days",
+];
+
+/// Values of `p` in `PARAMETER_NAMES` order.
+fn parameter_values(p: &SweepParameters) -> [f64; 4] {
+    [
+        p.top_tokens as f64,
+        p.price_history_days as f64,
+        p.rebalance_threshold,
+        p.rebalance_interval_days as f64,
+    ]
+}
+
+/// Best metric value for each pair of values of the two parameters that vary most.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Heatmap {
+    pub x_name: &'static str,
+    pub y_name: &'static str,
+    pub xs: Vec<f64>,
+    pub ys: Vec<f64>,
+    /// `cells[y][x]`, `None` where no entry has that combination
+    pub cells: Vec<Vec<Option<f64>>>,
+}
+
+pub(crate) fn heatmap(entries: &[SweepEntry], metric: fn(&SweepEntry) -> f64) -> Heatmap {
+    let mut axes: Vec<(usize, Vec<f64>)> = (0..PARAMETER_NAMES.len())
+        .map(|i| {
+            let mut values: Vec<f64> = entries
+                .iter()
+                .map(|e| parameter_values(&e.parameters)[i])
+                .collect();
+            values.sort_by(f64::total_cmp);
+            values.dedup();
+            (i, values)
+        })
+        .collect();
+    // Stable sort: ties keep the `PARAMETER_NAMES` order
+    axes.sort_by_key(|(_, values)| std::cmp::Reverse(values.len()));
+    let (xi, xs) = axes[0].clone();
+    let (yi, ys) = axes[1].clone();
+
+    let mut cells = vec![vec![None; xs.len()]; ys.len()];
+    for entry in entries {
+        let values = parameter_values(&entry.parameters);
+        let col = xs.iter().position(|x| *x == values[xi]);
+        let row = ys.iter().position(|y| *y == values[yi]);
+        if let (Some(col), Some(row)) = (col, row) {
+            let value = metric(entry);
+            let cell: &mut Option<f64> = &mut cells[row][col];
+            *cell = Some(cell.map_or(value, |best| best.max(value)));
+        }
+    }
+    Heatmap {
+        x_name: PARAMETER_NAMES[xi],
+        y_name: PARAMETER_NAMES[yi],
+        xs,
+        ys,
+        cells,
+    }
+}
+
+/// Red (0) through yellow (0.5) to green (1).
+fn scale_color(t: f64) -> String {
+    let t = if t.is_finite() {
+        t.clamp(0.0, 1.0)
+    } else {
+        0.5
+    };
+    let lerp = |a: f64, b: f64, u: f64| (a + (b - a) * u).round() as u8;
+    let (from, to, u) = if t < 0.5 {
+        ((215.0, 48.0, 39.0), (254.0, 224.0, 139.0), t * 2.0)
+    } else {
+        ((254.0, 224.0, 139.0), (26.0, 152.0, 80.0), t * 2.0 - 1.0)
+    };
+    format!(
+        "rgb({},{},{})",
+        lerp(from.0, to.0, u),
+        lerp(from.1, to.1, u),
+        lerp(from.2, to.2, u)
+    )
+}
+
+fn write_heatmap(out: &mut String, map: &Heatmap, format_value: fn(f64) -> String) -> fmt::Result {
+    let filled = map.cells.iter().flatten().flatten().copied();
+    let (lo, hi) = filled.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    });
+    write!(
+        out,
+        "<table><tr><th class=\"l\">{} \\ {}</th>",
+        escape(map.y_name),
+        escape(map.x_name)
+    )?;
+    for x in &map.xs {
+        write!(out, "<th>{x}</th>")?;
+    }
+    out.write_str("</tr>")?;
+    for (y, row) in map.ys.iter().zip(&map.cells) {
+        write!(out, "<tr><th class=\"l\">{y}</th>")?;
+        for cell in row {
+            match cell {
+                Some(value) => {
+                    let t = if hi > lo {
+                        (value - lo) / (hi - lo)
+                    } else {
+                        0.5
+                    };
+                    write!(
+                        out,
+                        "<td style=\"background:{}\">{}</td>",
+                        scale_color(t),
+                        escape(&format_value(*value))
+                    )?;
+                }
+                None => out.write_str("<td></td>")?,
+            }
+        }
+        out.write_str("</tr>")?;
+    }
+    out.write_str("</table>")
+}
+
+fn write_sweep(out: &mut String, sweep: &SweepResult) -> fmt::Result {
+    write_header(
+        out,
+        &format!("Parameter sweep ({} runs)", sweep.results.len()),
+    )?;
+    if !sweep.results.is_empty() {
+        let sharpe = heatmap(&sweep.results, |e| e.sharpe_ratio);
+        out.write_str("<h2>Sharpe ratio</h2><p>Best value over the other parameters.</p>")?;
+        write_heatmap(out, &sharpe, |v| format!("{v:.3}"))?;
+        out.write_str("<h2>Total return</h2><p>Best value over the other parameters.</p>")?;
+        write_heatmap(out, &heatmap(&sweep.results, |e| e.total_return), pct)?;
+    }
+
+    out.write_str("<h2>Results</h2>")?;
+    let rows: Vec<Vec<String>> = sweep
+        .results
+        .iter()
+        .map(|e| {
+            let p = &e.parameters;
+            vec![
+                p.top_tokens.to_string(),
+                p.price_history_days.to_string(),
+                p.rebalance_threshold.to_string(),
+                p.rebalance_interval_days.to_string(),
+                pct(e.total_return),
+                format!("{:.3}", e.sharpe_ratio),
+                format!("{:.3}", e.sortino_ratio),
+                format!("{:.2}%", e.max_drawdown * 100.0),
+                format!("{:.4}", e.final_balance_near),
+                format!("{:+.4}", e.realized_pnl_near),
+            ]
+        })
+        .collect();
+    write_table(
+        out,
+        &[
+            "Top tokens",
+            "History days",
+            "Threshold",
+            "Interval days",
+            "Return",
+            "Sharpe",
+            "Sortino",
+            "Max drawdown",
+            "Final NEAR",
+            "Realized P&L",
+        ],
+        &rows,
+    )?;
+    write_footer(out)
+}
+
+#[cfg(test)]
+mod tests;
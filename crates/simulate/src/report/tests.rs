@@ -0,0 +1,212 @@
+use super::*;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+fn simulation_json() -> serde_json::Value {
+    json!({
+        "config": {
+            "start_date": "2025-01-01",
+            "end_date": "2025-01-03",
+            "initial_capital": 100.0,
+            "parameters": {
+                "top_tokens": 3,
+                "price_history_days": 30,
+                "rebalance_threshold": 0.1,
+                "rebalance_interval_days": 1
+            }
+        },
+        "performance": {
+            "total_return": 0.05,
+            "sharpe_ratio": 1.2,
+            "sortino_ratio": 1.5,
+            "max_drawdown": 0.02,
+            "win_rate": 0.5,
+            "final_balance_near": 105.0,
+            "total_realized_pnl_near": 1.0,
+            "trade_count": 0,
+            "liquidation_count": 1,
+            "total_swaps": 1,
+            "pool_based_swaps": 1,
+            "fallback_swaps": 0,
+            "fallback_rate": 0.0
+        },
+        "trades": [{
+            "timestamp": "2025-01-03T00:00:00+00:00",
+            "action": "liquidation",
+            "token": "a.near",
+            "amount": 10,
+            "price": 12.5,
+            "realized_pnl": 1.0
+        }],
+        "swap_events": [{
+            "timestamp": "2025-01-01T00:00:00+00:00",
+            "token_in": "wrap.near",
+            "amount_in": "50 NEAR",
+            "amount_in_raw": 50,
+            "token_out": "<b>.near",
+            "amount_out": "10",
+            "amount_out_raw": 10,
+            "swap_method": "pool_based",
+            "pool_ids": [0],
+            "slippage": 0.0042
+        }],
+        "portfolio_values": [
+            value_json("2025-01-01T00:00:00+00:00", 100.0, 50.0, 50.0),
+            value_json("2025-01-02T00:00:00+00:00", 98.0, 50.0, 48.0),
+            value_json("2025-01-03T00:00:00+00:00", 105.0, 105.0, 0.0)
+        ],
+        "pnl_by_token": {
+            "a.near": { "realized_near": 1.0, "unrealized_near": 0.0 },
+            "<b>.near": { "realized_near": 0.0, "unrealized_near": -2.0 }
+        }
+    })
+}
+
+fn value_json(timestamp: &str, total: f64, cash: f64, held: f64) -> serde_json::Value {
+    json!({
+        "timestamp": timestamp,
+        "total_value": total,
+        "holdings": {},
+        "holding_values_near": { "a.near": held },
+        "cash_balance": cash,
+        "daily_pnl_near": 0.0,
+        "daily_pnl_pct": 0.0,
+        "cumulative_realized_pnl_near": 0.0
+    })
+}
+
+fn sweep_entry(top_tokens: usize, threshold: f64, days: i64, sharpe: f64) -> SweepEntry {
+    SweepEntry {
+        parameters: SweepParameters {
+            top_tokens,
+            price_history_days: days,
+            rebalance_threshold: threshold,
+            rebalance_interval_days: 1,
+        },
+        total_return: sharpe / 10.0,
+        sharpe_ratio: sharpe,
+        sortino_ratio: 0.0,
+        max_drawdown: 0.1,
+        final_balance_near: 100.0,
+        realized_pnl_near: 0.0,
+    }
+}
+
+#[test]
+fn escape_replaces_markup_characters() {
+    assert_eq!(
+        escape("<a href=\"x\">Tom & 'Jerry'</a>"),
+        "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+    );
+    assert_eq!(escape("plain.near"), "plain.near");
+}
+
+#[test]
+fn parse_detects_simulation_and_sweep() {
+    let simulation = ReportInput::parse(&simulation_json().to_string()).unwrap();
+    assert!(matches!(simulation, ReportInput::Simulation(_)));
+
+    let sweep = SweepResult {
+        results: vec![sweep_entry(3, 0.1, 30, 1.0)],
+    };
+    let parsed = ReportInput::parse(&serde_json::to_string(&sweep).unwrap()).unwrap();
+    assert!(matches!(parsed, ReportInput::Sweep(s) if s.results.len() == 1));
+}
+
+#[test]
+fn parse_rejects_other_json() {
+    let err = ReportInput::parse(r#"{"days": []}"#).unwrap_err();
+    assert!(err.to_string().contains("neither"));
+}
+
+#[test]
+fn drawdowns_follow_running_peak() {
+    let drawdown = drawdowns(&[100.0, 120.0, 90.0, 130.0]);
+    assert_eq!(drawdown[0], 0.0);
+    assert_eq!(drawdown[1], 0.0);
+    assert!((drawdown[2] + 0.25).abs() < 1e-12);
+    assert_eq!(drawdown[3], 0.0);
+}
+
+#[test]
+fn allocation_layers_are_shares_of_value() {
+    let entry = |cash: f64, values: &[(&str, f64)]| PortfolioValueEntry {
+        timestamp: String::new(),
+        total_value: 0.0,
+        holdings: BTreeMap::new(),
+        holding_values_near: values.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        cash_balance: cash,
+        daily_pnl_near: 0.0,
+        daily_pnl_pct: 0.0,
+        cumulative_realized_pnl_near: 0.0,
+    };
+    let layers = allocation_layers(&[
+        entry(50.0, &[("b.near", 50.0)]),
+        entry(0.0, &[("a.near", 30.0), ("b.near", 10.0)]),
+        entry(0.0, &[]),
+    ]);
+    let names: Vec<&str> = layers.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["cash", "a.near", "b.near"]);
+    assert_eq!(layers[0].1, [0.5, 0.0, 0.0]);
+    assert_eq!(layers[1].1, [0.0, 0.75, 0.0]);
+    assert_eq!(layers[2].1, [0.5, 0.25, 0.0]);
+}
+
+#[test]
+fn heatmap_uses_most_varied_parameters_and_best_value() {
+    let entries = [
+        sweep_entry(3, 0.1, 30, 1.0),
+        sweep_entry(3, 0.1, 60, 2.0),
+        sweep_entry(5, 0.1, 30, -1.0),
+        sweep_entry(3, 0.2, 30, 0.5),
+        sweep_entry(5, 0.3, 60, 0.7),
+    ];
+    let map = heatmap(&entries, |e| e.sharpe_ratio);
+    assert_eq!(map.x_name, "rebalance_threshold");
+    assert_eq!(map.y_name, "top_tokens");
+    assert_eq!(map.xs, [0.1, 0.2, 0.3]);
+    assert_eq!(map.ys, [3.0, 5.0]);
+    assert_eq!(
+        map.cells,
+        [
+            vec![Some(2.0), Some(0.5), None],
+            vec![Some(-1.0), None, Some(0.7)]
+        ]
+    );
+}
+
+#[test]
+fn simulation_report_has_all_sections() {
+    let html = ReportInput::parse(&simulation_json().to_string())
+        .unwrap()
+        .render()
+        .unwrap();
+    for section in [
+        "<h2>Summary</h2>",
+        "<h2>Equity curve</h2>",
+        "<h2>Drawdown</h2>",
+        "<h2>Allocation</h2>",
+        "<h2>P&amp;L by token</h2>",
+        "<h2>Swaps</h2>",
+        "<h2>Liquidations</h2>",
+    ] {
+        assert!(html.contains(section), "missing {section}");
+    }
+    assert!(html.contains("0.420%"));
+    assert!(html.contains("&lt;b&gt;.near"));
+    assert!(!html.contains("<b>.near"));
+    assert!(!html.contains("<script"));
+}
+
+#[test]
+fn sweep_report_has_heatmaps_and_table() {
+    let sweep = ReportInput::Sweep(SweepResult {
+        results: vec![sweep_entry(3, 0.1, 30, 1.0), sweep_entry(5, 0.2, 30, 2.0)],
+    });
+    let html = sweep.render().unwrap();
+    assert!(html.contains("<h2>Sharpe ratio</h2>"));
+    assert!(html.contains("<h2>Total return</h2>"));
+    assert!(html.contains("<h2>Results</h2>"));
+    // Both vary twice: the earlier parameter is the x axis
+    assert!(html.contains("rebalance_threshold \\ top_tokens"));
+}
@@ -1,9 +1,17 @@
+mod walk_forward;
+
+use crate::benchmark::TotalSupplies;
 use crate::cli::RunArgs;
 use crate::engine::run_simulation;
+use crate::output::SimulationResult;
 use anyhow::Result;
+use futures::stream::{self, StreamExt};
 use logging::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::Path;
+use walk_forward::WalkForwardConfig;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SweepConfig {
@@ -15,6 +23,12 @@ pub struct SweepConfig {
     pub rebalance_threshold: Vec<f64>,
     #[serde(default = "default_rebalance_interval_days")]
     pub rebalance_interval_days: Vec<i64>,
+    /// Run a walk-forward optimisation instead of a single full-range grid
+    #[serde(default)]
+    pub walk_forward: Option<WalkForwardConfig>,
+    /// Number of combinations simulated at the same time
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
 }
 
 fn default_top_tokens() -> Vec<usize> {
@@ -29,13 +43,19 @@ fn default_rebalance_threshold() -> Vec<f64> {
 fn default_rebalance_interval_days() -> Vec<i64> {
     vec![1]
 }
+fn default_concurrency() -> usize {
+    1
+}
+
+/// File in the output directory that records each finished combination.
+const CHECKPOINT_FILE: &str = "sweep_checkpoint.jsonl";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SweepResult {
     pub results: Vec<SweepEntry>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SweepEntry {
     pub parameters: SweepParameters,
     pub total_return: f64,
@@ -46,7 +66,7 @@ pub struct SweepEntry {
     pub realized_pnl_near: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SweepParameters {
     pub top_tokens: usize,
     pub price_history_days: i64,
@@ -54,49 +74,118 @@ pub struct SweepParameters {
     pub rebalance_interval_days: i64,
 }
 
+impl SweepEntry {
+    fn new(parameters: SweepParameters, result: &SimulationResult) -> Self {
+        Self {
+            parameters,
+            total_return: result.performance.total_return,
+            sharpe_ratio: result.performance.sharpe_ratio,
+            sortino_ratio: result.performance.sortino_ratio,
+            max_drawdown: result.performance.max_drawdown,
+            final_balance_near: result.performance.final_balance_near,
+            realized_pnl_near: result.performance.total_realized_pnl_near,
+        }
+    }
+}
+
+/// One line of the sweep checkpoint: a finished combination and its index in
+/// the generated combination list.
+#[derive(Debug, Serialize, Deserialize)]
+struct CheckpointLine {
+    index: usize,
+    entry: SweepEntry,
+}
+
+/// Load finished combinations from a checkpoint file, keyed by index.
+///
+/// Lines that do not parse (e.g. one cut short by an interrupted write) and
+/// lines whose parameters no longer match the combination at that index
+/// (the sweep config changed) are ignored, so those combinations run again.
+fn load_checkpoint(path: &Path, combinations: &[SweepParameters]) -> BTreeMap<usize, SweepEntry> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return BTreeMap::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<CheckpointLine>(line).ok())
+        .filter(|line| combinations.get(line.index) == Some(&line.entry.parameters))
+        .map(|line| (line.index, line.entry))
+        .collect()
+}
+
+fn append_checkpoint(path: &Path, index: usize, entry: &SweepEntry) -> Result<()> {
+    let line = serde_json::to_string(&CheckpointLine {
+        index,
+        entry: entry.clone(),
+    })?;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
 pub async fn run_sweep(base_cli: &RunArgs, sweep_config_path: &Path) -> Result<()> {
     let log = DEFAULT.new(o!("function" => "run_sweep"));
 
     let config_str = std::fs::read_to_string(sweep_config_path)?;
     let sweep_config: SweepConfig = serde_json::from_str(&config_str)?;
+    // Every combination reads the same token supplies for its benchmarks
+    let supplies = TotalSupplies::default();
 
-    let combinations = generate_combinations(&sweep_config);
-    info!(log, "starting parameter sweep"; "combinations" => combinations.len());
+    if let Some(walk_forward) = &sweep_config.walk_forward {
+        let result =
+            walk_forward::run_walk_forward(base_cli, &sweep_config, walk_forward, &supplies)
+                .await?;
+        let summary_path = base_cli.output.join("walk_forward_summary.json");
+        walk_forward::write_walk_forward(&result, &summary_path)?;
+        info!(log, "walk-forward completed";
+            "windows" => result.windows.len(), "path" => summary_path.display().to_string());
+        walk_forward::print_walk_forward_table(&result);
+        return Ok(());
+    }
 
-    let mut results = Vec::new();
+    let combinations = generate_combinations(&sweep_config);
+    let concurrency = sweep_config.concurrency.max(1);
+    std::fs::create_dir_all(&base_cli.output)?;
 
-    for (i, params) in combinations.iter().enumerate() {
-        info!(log, "running combination"; "index" => i + 1, "total" => combinations.len());
+    // Resume from the checkpoint of an earlier, interrupted sweep
+    let checkpoint_path = base_cli.output.join(CHECKPOINT_FILE);
+    let mut completed = load_checkpoint(&checkpoint_path, &combinations);
+    info!(log, "starting parameter sweep";
+        "combinations" => combinations.len(),
+        "already_completed" => completed.len(),
+        "concurrency" => concurrency);
 
-        let mut cli = base_cli.clone();
-        cli.top_tokens = params.top_tokens;
-        cli.price_history_days = params.price_history_days;
-        cli.rebalance_threshold = params.rebalance_threshold;
-        cli.rebalance_interval_days = params.rebalance_interval_days;
+    let pending: Vec<(usize, &SweepParameters)> = combinations
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !completed.contains_key(i))
+        .collect();
+    let total = combinations.len();
+    let mut runs = stream::iter(pending)
+        .map(|(i, params)| {
+            let log = log.clone();
+            let supplies = &supplies;
+            async move {
+                info!(log, "running combination"; "index" => i + 1, "total" => total);
+                let result = run_simulation(&with_parameters(base_cli, params), supplies).await;
+                (i, params, result)
+            }
+        })
+        .buffer_unordered(concurrency);
 
-        match run_simulation(&cli).await {
+    while let Some((i, params, result)) = runs.next().await {
+        match result {
             Ok(result) => {
-                results.push(SweepEntry {
-                    parameters: SweepParameters {
-                        top_tokens: params.top_tokens,
-                        price_history_days: params.price_history_days,
-                        rebalance_threshold: params.rebalance_threshold,
-                        rebalance_interval_days: params.rebalance_interval_days,
-                    },
-                    total_return: result.performance.total_return,
-                    sharpe_ratio: result.performance.sharpe_ratio,
-                    sortino_ratio: result.performance.sortino_ratio,
-                    max_drawdown: result.performance.max_drawdown,
-                    final_balance_near: result.performance.final_balance_near,
-                    realized_pnl_near: result.performance.total_realized_pnl_near,
-                });
-
                 // Write individual result
                 let individual_path = base_cli.output.join(format!("result_{:03}.json", i + 1));
-                if let Some(parent) = individual_path.parent() {
-                    std::fs::create_dir_all(parent)?;
-                }
                 result.write_to_file(&individual_path)?;
+
+                let entry = SweepEntry::new(params.clone(), &result);
+                append_checkpoint(&checkpoint_path, i, &entry)?;
+                completed.insert(i, entry);
             }
             Err(e) => {
                 warn!(log, "combination failed"; "index" => i + 1, "error" => ?e);
@@ -104,6 +193,8 @@ pub async fn run_sweep(base_cli: &RunArgs, sweep_config_path: &Path) -> Result<(
         }
     }
 
+    let mut results: Vec<SweepEntry> = completed.into_values().collect();
+
     // Sort by Sharpe ratio descending
     results.sort_by(|a, b| {
         b.sharpe_ratio
@@ -114,9 +205,6 @@ pub async fn run_sweep(base_cli: &RunArgs, sweep_config_path: &Path) -> Result<(
     // Write sweep summary
     let sweep_result = SweepResult { results };
     let summary_path = base_cli.output.join("sweep_summary.json");
-    if let Some(parent) = summary_path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
     let json = serde_json::to_string_pretty(&sweep_result)?;
     std::fs::write(&summary_path, json)?;
 
@@ -128,6 +216,15 @@ pub async fn run_sweep(base_cli: &RunArgs, sweep_config_path: &Path) -> Result<(
     Ok(())
 }
 
+fn with_parameters(base_cli: &RunArgs, params: &SweepParameters) -> RunArgs {
+    let mut cli = base_cli.clone();
+    cli.top_tokens = params.top_tokens;
+    cli.price_history_days = params.price_history_days;
+    cli.rebalance_threshold = params.rebalance_threshold;
+    cli.rebalance_interval_days = params.rebalance_interval_days;
+    cli
+}
+
 fn generate_combinations(config: &SweepConfig) -> Vec<SweepParameters> {
     let mut combinations = Vec::new();
 
@@ -9,6 +9,8 @@ fn combinations_single_values() {
         price_history_days: vec![30],
         rebalance_threshold: vec![0.1],
         rebalance_interval_days: vec![1],
+        walk_forward: None,
+        concurrency: 1,
     };
     let combos = generate_combinations(&config);
     assert_eq!(combos.len(), 1);
@@ -25,6 +27,8 @@ fn combinations_cartesian_product() {
         price_history_days: vec![30],
         rebalance_threshold: vec![0.05, 0.1, 0.2],
         rebalance_interval_days: vec![1],
+        walk_forward: None,
+        concurrency: 1,
     };
     let combos = generate_combinations(&config);
     // 2 * 1 * 3 * 1 = 6
@@ -38,6 +42,8 @@ fn combinations_empty_dimension() {
         price_history_days: vec![30],
         rebalance_threshold: vec![0.1],
         rebalance_interval_days: vec![1],
+        walk_forward: None,
+        concurrency: 1,
     };
     let combos = generate_combinations(&config);
     assert_eq!(combos.len(), 0);
@@ -50,6 +56,8 @@ fn combinations_preserves_all_values() {
         price_history_days: vec![30],
         rebalance_threshold: vec![0.1],
         rebalance_interval_days: vec![1],
+        walk_forward: None,
+        concurrency: 1,
     };
     let combos = generate_combinations(&config);
     assert_eq!(combos.len(), 2);
@@ -67,6 +75,7 @@ fn sweep_config_defaults() {
     assert_eq!(config.price_history_days, vec![30]);
     assert_eq!(config.rebalance_threshold, vec![0.1]);
     assert_eq!(config.rebalance_interval_days, vec![1]);
+    assert_eq!(config.concurrency, 1);
 }
 
 #[test]
@@ -88,3 +97,81 @@ fn sweep_config_invalid_json() {
     let json = "not json";
     assert!(serde_json::from_str::<SweepConfig>(json).is_err());
 }
+
+// --- checkpoint ---
+
+fn params(top_tokens: usize) -> SweepParameters {
+    SweepParameters {
+        top_tokens,
+        price_history_days: 30,
+        rebalance_threshold: 0.1,
+        rebalance_interval_days: 1,
+    }
+}
+
+fn entry(top_tokens: usize, sharpe_ratio: f64) -> SweepEntry {
+    SweepEntry {
+        parameters: params(top_tokens),
+        total_return: 0.1,
+        sharpe_ratio,
+        sortino_ratio: 0.0,
+        max_drawdown: 0.0,
+        final_balance_near: 110.0,
+        realized_pnl_near: 1.0,
+    }
+}
+
+fn checkpoint_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "simulate_sweep_{}_{}_{}",
+        name,
+        std::process::id(),
+        CHECKPOINT_FILE
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn checkpoint_roundtrip() {
+    let path = checkpoint_path("roundtrip");
+    let combinations = vec![params(5), params(10), params(20)];
+
+    append_checkpoint(&path, 0, &entry(5, 1.0)).unwrap();
+    append_checkpoint(&path, 2, &entry(20, 2.0)).unwrap();
+    let loaded = load_checkpoint(&path, &combinations);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.keys().copied().collect::<Vec<_>>(), vec![0, 2]);
+    assert_eq!(loaded[&2].sharpe_ratio, 2.0);
+}
+
+#[test]
+fn checkpoint_missing_file_is_empty() {
+    let path = checkpoint_path("missing");
+    assert!(load_checkpoint(&path, &[params(5)]).is_empty());
+}
+
+#[test]
+fn checkpoint_skips_truncated_and_stale_lines() {
+    let path = checkpoint_path("stale");
+    let combinations = vec![params(5), params(10)];
+
+    append_checkpoint(&path, 0, &entry(5, 1.0)).unwrap();
+    // Parameters at index 1 changed since this line was written
+    append_checkpoint(&path, 1, &entry(20, 2.0)).unwrap();
+    // Index beyond the current combination list
+    append_checkpoint(&path, 5, &entry(5, 3.0)).unwrap();
+    // Interrupted write
+    {
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(file, r#"{{"index": 1, "entry": {{"parame"#).unwrap();
+    }
+    let loaded = load_checkpoint(&path, &combinations);
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(loaded.keys().copied().collect::<Vec<_>>(), vec![0]);
+}
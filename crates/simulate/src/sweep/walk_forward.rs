@@ -0,0 +1,460 @@
+use super::{SweepParameters, generate_combinations, with_parameters};
+use crate::benchmark::TotalSupplies;
+use crate::cli::RunArgs;
+use crate::engine::run_simulation;
+use crate::output::{
+    PerformanceMetrics, PortfolioValueEntry, SimulationResult, SwapStats, ValueMetrics,
+    value_metrics,
+};
+use anyhow::Result;
+use chrono::{NaiveDate, TimeDelta};
+use futures::stream::{self, StreamExt};
+use logging::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Walk-forward settings in the sweep config (`"walk_forward": {...}`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardConfig {
+    /// Length of each in-sample (optimisation) window
+    pub in_sample_days: i64,
+    /// Length of each out-of-sample window; windows roll forward by this much
+    pub out_of_sample_days: i64,
+    #[serde(default)]
+    pub objective: Objective,
+}
+
+/// What the in-sample grid is ranked by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Objective {
+    #[default]
+    Sharpe,
+    Sortino,
+    /// Annualised return over max drawdown
+    Calmar,
+}
+
+impl Objective {
+    /// Score a run over a window of `days` days (higher is better).
+    pub fn score(self, performance: &PerformanceMetrics, days: i64) -> f64 {
+        match self {
+            Self::Sharpe => performance.sharpe_ratio,
+            Self::Sortino => performance.sortino_ratio,
+            Self::Calmar => calmar_ratio(performance.total_return, performance.max_drawdown, days),
+        }
+    }
+}
+
+/// Annualised return divided by max drawdown. Without any drawdown the
+/// annualised return itself is used, so flat-but-positive runs still rank.
+fn calmar_ratio(total_return: f64, max_drawdown: f64, days: i64) -> f64 {
+    if days <= 0 || total_return <= -1.0 {
+        return 0.0;
+    }
+    let annualised = (1.0 + total_return).powf(365.0 / days as f64) - 1.0;
+    if max_drawdown > 0.0 {
+        annualised / max_drawdown
+    } else {
+        annualised
+    }
+}
+
+/// One in-sample / out-of-sample split (all dates inclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub in_sample_start: NaiveDate,
+    pub in_sample_end: NaiveDate,
+    pub out_of_sample_start: NaiveDate,
+    pub out_of_sample_end: NaiveDate,
+}
+
+/// Split `[start, end]` into rolling windows whose out-of-sample parts are
+/// contiguous. The last out-of-sample window is truncated at `end`; a window
+/// shorter than two days is dropped because a simulation needs start < end.
+pub fn windows(start: NaiveDate, end: NaiveDate, config: &WalkForwardConfig) -> Vec<Window> {
+    let mut windows = Vec::new();
+    if config.in_sample_days < 2 || config.out_of_sample_days < 2 {
+        return windows;
+    }
+    let mut in_sample_start = start;
+    loop {
+        let in_sample_end = in_sample_start + TimeDelta::days(config.in_sample_days - 1);
+        let out_of_sample_start = in_sample_end + TimeDelta::days(1);
+        if out_of_sample_start >= end {
+            break;
+        }
+        let out_of_sample_end =
+            (out_of_sample_start + TimeDelta::days(config.out_of_sample_days - 1)).min(end);
+        windows.push(Window {
+            in_sample_start,
+            in_sample_end,
+            out_of_sample_start,
+            out_of_sample_end,
+        });
+        in_sample_start += TimeDelta::days(config.out_of_sample_days);
+    }
+    windows
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalkForwardResult {
+    pub config: WalkForwardRunConfig,
+    /// Metrics of the stitched out-of-sample equity curve
+    pub performance: PerformanceMetrics,
+    pub windows: Vec<WindowResult>,
+    pub parameter_stability: ParameterStability,
+    /// Stitched out-of-sample portfolio values
+    pub portfolio_values: Vec<PortfolioValueEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalkForwardRunConfig {
+    pub start_date: String,
+    pub end_date: String,
+    pub initial_capital: f64,
+    pub in_sample_days: i64,
+    pub out_of_sample_days: i64,
+    pub objective: Objective,
+    pub combinations: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WindowResult {
+    pub in_sample_start: String,
+    pub in_sample_end: String,
+    pub out_of_sample_start: String,
+    pub out_of_sample_end: String,
+    pub parameters: SweepParameters,
+    pub in_sample_score: f64,
+    pub out_of_sample_score: f64,
+    pub out_of_sample_return: f64,
+    pub out_of_sample_final_balance_near: f64,
+}
+
+/// How much the chosen parameters moved between windows.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ParameterStability {
+    pub top_tokens: ParameterSummary,
+    pub price_history_days: ParameterSummary,
+    pub rebalance_threshold: ParameterSummary,
+    pub rebalance_interval_days: ParameterSummary,
+    /// Windows whose parameters were identical to the previous window's
+    pub unchanged_windows: usize,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterSummary {
+    pub distinct_values: usize,
+    /// Most frequently chosen value (earliest wins ties)
+    pub most_common: f64,
+    /// Share of windows that chose `most_common`
+    pub most_common_share: f64,
+    /// Number of window-to-window changes
+    pub changes: usize,
+}
+
+fn summarize(values: &[f64]) -> ParameterSummary {
+    let Some(&first) = values.first() else {
+        return ParameterSummary::default();
+    };
+    let mut distinct: Vec<(f64, usize)> = Vec::new();
+    for &value in values {
+        match distinct.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, count)) => *count += 1,
+            None => distinct.push((value, 1)),
+        }
+    }
+    let (most_common, count) =
+        distinct.iter().copied().fold(
+            (first, 0),
+            |best, (v, c)| if c > best.1 { (v, c) } else { best },
+        );
+    ParameterSummary {
+        distinct_values: distinct.len(),
+        most_common,
+        most_common_share: count as f64 / values.len() as f64,
+        changes: values.windows(2).filter(|w| w[0] != w[1]).count(),
+    }
+}
+
+pub fn parameter_stability(chosen: &[SweepParameters]) -> ParameterStability {
+    let column = |f: fn(&SweepParameters) -> f64| chosen.iter().map(f).collect::<Vec<_>>();
+    ParameterStability {
+        top_tokens: summarize(&column(|p| p.top_tokens as f64)),
+        price_history_days: summarize(&column(|p| p.price_history_days as f64)),
+        rebalance_threshold: summarize(&column(|p| p.rebalance_threshold)),
+        rebalance_interval_days: summarize(&column(|p| p.rebalance_interval_days as f64)),
+        unchanged_windows: chosen.windows(2).filter(|w| w[0] == w[1]).count(),
+    }
+}
+
+fn window_days(start: NaiveDate, end: NaiveDate) -> i64 {
+    (end - start).num_days() + 1
+}
+
+fn dated(base: &RunArgs, start: NaiveDate, end: NaiveDate) -> RunArgs {
+    let mut cli = base.clone();
+    cli.start_date = start.format("%Y-%m-%d").to_string();
+    cli.end_date = end.format("%Y-%m-%d").to_string();
+    cli
+}
+
+/// Run every combination on the in-sample window and return the best one.
+///
+/// Up to `concurrency` combinations run at once; results are taken in
+/// combination order so ties resolve the same way as a sequential run.
+async fn optimise(
+    base_cli: &RunArgs,
+    combinations: &[SweepParameters],
+    window: &Window,
+    objective: Objective,
+    concurrency: usize,
+    supplies: &TotalSupplies,
+) -> Option<(SweepParameters, f64)> {
+    let log = DEFAULT.new(o!(
+        "function" => "walk_forward::optimise",
+        "in_sample_start" => window.in_sample_start.to_string(),
+    ));
+    let days = window_days(window.in_sample_start, window.in_sample_end);
+    let cli = dated(base_cli, window.in_sample_start, window.in_sample_end);
+
+    let cli = &cli;
+    let mut runs = stream::iter(combinations)
+        .map(|params| async move {
+            let result = run_simulation(&with_parameters(cli, params), supplies).await;
+            (params, result)
+        })
+        .buffered(concurrency.max(1));
+
+    let mut best: Option<(SweepParameters, f64)> = None;
+    while let Some((params, result)) = runs.next().await {
+        match result {
+            Ok(result) => {
+                let score = objective.score(&result.performance, days);
+                if best.as_ref().is_none_or(|(_, s)| score > *s) {
+                    best = Some((params.clone(), score));
+                }
+            }
+            Err(e) => warn!(log, "in-sample combination failed"; "error" => ?e),
+        }
+    }
+    best
+}
+
+/// Stitch out-of-sample runs into a single equity curve. Each run started
+/// from the previous run's final balance, so the values chain directly;
+/// realised P&L is offset by the earlier windows' totals.
+fn stitch(
+    initial_capital: f64,
+    runs: Vec<SimulationResult>,
+    interval_days: i64,
+) -> (PerformanceMetrics, Vec<PortfolioValueEntry>) {
+    let mut portfolio_values = Vec::new();
+    let mut realized_offset = 0.0;
+    let mut trade_count = 0;
+    let mut liquidation_count = 0;
+    let mut swap_stats = SwapStats::default();
+    for run in runs {
+        for mut entry in run.portfolio_values {
+            entry.cumulative_realized_pnl_near += realized_offset;
+            portfolio_values.push(entry);
+        }
+        realized_offset += run.performance.total_realized_pnl_near;
+        trade_count += run.performance.trade_count;
+        liquidation_count += run.performance.liquidation_count;
+        swap_stats.merge(&run.performance.swap_stats);
+    }
+
+    let values: Vec<f64> = portfolio_values.iter().map(|v| v.total_value).collect();
+    let ValueMetrics {
+        total_return,
+        sharpe_ratio,
+        sortino_ratio,
+        max_drawdown,
+        win_rate,
+    } = value_metrics(initial_capital, &values, interval_days);
+    let performance = PerformanceMetrics {
+        total_return,
+        sharpe_ratio,
+        sortino_ratio,
+        max_drawdown,
+        win_rate,
+        final_balance_near: values.last().copied().unwrap_or(initial_capital),
+        total_realized_pnl_near: realized_offset,
+        trade_count,
+        liquidation_count,
+        swap_stats,
+    };
+    (performance, portfolio_values)
+}
+
+pub async fn run_walk_forward(
+    base_cli: &RunArgs,
+    combinations_config: &super::SweepConfig,
+    config: &WalkForwardConfig,
+    supplies: &TotalSupplies,
+) -> Result<WalkForwardResult> {
+    let log = DEFAULT.new(o!("function" => "run_walk_forward"));
+
+    let start = base_cli.parse_start_date()?;
+    let end = base_cli.parse_end_date()?;
+    let windows = windows(start, end, config);
+    if windows.is_empty() {
+        return Err(anyhow::anyhow!(
+            "no walk-forward window fits {} .. {} (in-sample {} days, out-of-sample {} days, both >= 2)",
+            start,
+            end,
+            config.in_sample_days,
+            config.out_of_sample_days
+        ));
+    }
+    let combinations = generate_combinations(combinations_config);
+    info!(log, "starting walk-forward optimisation";
+        "windows" => windows.len(), "combinations" => combinations.len(),
+        "objective" => ?config.objective);
+
+    let mut capital = base_cli.initial_capital;
+    let mut window_results = Vec::with_capacity(windows.len());
+    let mut chosen = Vec::with_capacity(windows.len());
+    let mut runs = Vec::with_capacity(windows.len());
+    for (i, window) in windows.iter().enumerate() {
+        let Some((params, in_sample_score)) = optimise(
+            base_cli,
+            &combinations,
+            window,
+            config.objective,
+            combinations_config.concurrency,
+            supplies,
+        )
+        .await
+        else {
+            warn!(log, "no in-sample run succeeded, skipping window"; "window" => i + 1);
+            continue;
+        };
+
+        let mut cli = with_parameters(
+            &dated(
+                base_cli,
+                window.out_of_sample_start,
+                window.out_of_sample_end,
+            ),
+            &params,
+        );
+        cli.initial_capital = capital;
+        let result = match run_simulation(&cli, supplies).await {
+            Ok(result) => result,
+            Err(e) => {
+                warn!(log, "out-of-sample run failed, skipping window";
+                    "window" => i + 1, "error" => ?e);
+                continue;
+            }
+        };
+        info!(log, "window completed";
+            "window" => i + 1, "in_sample_score" => in_sample_score,
+            "out_of_sample_return" => result.performance.total_return);
+
+        let oos_path = base_cli
+            .output
+            .join(format!("walk_forward_oos_{:03}.json", i + 1));
+        if let Some(parent) = oos_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        result.write_to_file(&oos_path)?;
+
+        capital = result.performance.final_balance_near;
+        window_results.push(WindowResult {
+            in_sample_start: window.in_sample_start.to_string(),
+            in_sample_end: window.in_sample_end.to_string(),
+            out_of_sample_start: window.out_of_sample_start.to_string(),
+            out_of_sample_end: window.out_of_sample_end.to_string(),
+            parameters: params.clone(),
+            in_sample_score,
+            out_of_sample_score: config.objective.score(
+                &result.performance,
+                window_days(window.out_of_sample_start, window.out_of_sample_end),
+            ),
+            out_of_sample_return: result.performance.total_return,
+            out_of_sample_final_balance_near: result.performance.final_balance_near,
+        });
+        chosen.push(params);
+        runs.push(result);
+    }
+
+    // Annualise the stitched curve at the interval most windows ran with.
+    let parameter_stability = parameter_stability(&chosen);
+    let interval_days = if chosen.is_empty() {
+        base_cli.rebalance_interval_days
+    } else {
+        parameter_stability.rebalance_interval_days.most_common as i64
+    };
+    let (performance, portfolio_values) = stitch(base_cli.initial_capital, runs, interval_days);
+    Ok(WalkForwardResult {
+        config: WalkForwardRunConfig {
+            start_date: base_cli.start_date.clone(),
+            end_date: base_cli.end_date.clone(),
+            initial_capital: base_cli.initial_capital,
+            in_sample_days: config.in_sample_days,
+            out_of_sample_days: config.out_of_sample_days,
+            objective: config.objective,
+            combinations: combinations.len(),
+        },
+        performance,
+        windows: window_results,
+        parameter_stability,
+        portfolio_values,
+    })
+}
+
+pub fn write_walk_forward(result: &WalkForwardResult, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(result)?)?;
+    Ok(())
+}
+
+pub fn print_walk_forward_table(result: &WalkForwardResult) {
+    println!(
+        "\n{:<23} {:<23} {:<8} {:<8} {:<10} {:<8} {:>10} {:>10}",
+        "InSample",
+        "OutOfSample",
+        "TopTok",
+        "HistDays",
+        "RebThresh",
+        "RebIntv",
+        "IS score",
+        "OOS ret%"
+    );
+    println!("{}", "-".repeat(106));
+    for w in &result.windows {
+        println!(
+            "{:<23} {:<23} {:<8} {:<8} {:<10.2} {:<8} {:>10.3} {:>10.2}",
+            format!("{}..{}", w.in_sample_start, w.in_sample_end),
+            format!("{}..{}", w.out_of_sample_start, w.out_of_sample_end),
+            w.parameters.top_tokens,
+            w.parameters.price_history_days,
+            w.parameters.rebalance_threshold,
+            w.parameters.rebalance_interval_days,
+            w.in_sample_score,
+            w.out_of_sample_return * 100.0,
+        );
+    }
+    let perf = &result.performance;
+    println!(
+        "\nOut-of-sample: return {:.2}%, Sharpe {:.3}, Sortino {:.3}, MaxDD {:.2}%, final {:.4} NEAR",
+        perf.total_return * 100.0,
+        perf.sharpe_ratio,
+        perf.sortino_ratio,
+        perf.max_drawdown * 100.0,
+        perf.final_balance_near
+    );
+    println!(
+        "Parameter changes between windows: {} of {} windows unchanged",
+        result.parameter_stability.unchanged_windows,
+        result.windows.len().saturating_sub(1)
+    );
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,241 @@
+use super::*;
+use crate::output::{SimulationConfig, SimulationParameters};
+use std::collections::BTreeMap;
+
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+fn config(in_sample_days: i64, out_of_sample_days: i64) -> WalkForwardConfig {
+    WalkForwardConfig {
+        in_sample_days,
+        out_of_sample_days,
+        objective: Objective::Sharpe,
+    }
+}
+
+fn params(top_tokens: usize, rebalance_threshold: f64) -> SweepParameters {
+    SweepParameters {
+        top_tokens,
+        price_history_days: 30,
+        rebalance_threshold,
+        rebalance_interval_days: 1,
+    }
+}
+
+fn performance(total_return: f64, max_drawdown: f64) -> PerformanceMetrics {
+    PerformanceMetrics {
+        total_return,
+        sharpe_ratio: 1.5,
+        sortino_ratio: 2.5,
+        max_drawdown,
+        win_rate: 0.5,
+        final_balance_near: 100.0 * (1.0 + total_return),
+        total_realized_pnl_near: 1.0,
+        trade_count: 2,
+        liquidation_count: 0,
+        swap_stats: SwapStats {
+            total_swaps: 2,
+            pool_based_swaps: 1,
+            fallback_swaps: 1,
+            fallback_rate: 0.5,
+        },
+    }
+}
+
+fn run(values: &[f64]) -> SimulationResult {
+    SimulationResult {
+        config: SimulationConfig {
+            start_date: "2026-01-01".to_string(),
+            end_date: "2026-01-10".to_string(),
+            initial_capital: 100.0,
+            parameters: SimulationParameters {
+                top_tokens: 10,
+                price_history_days: 30,
+                rebalance_threshold: 0.1,
+                rebalance_interval_days: 1,
+            },
+        },
+        performance: performance(0.0, 0.0),
+        trades: vec![],
+        swap_events: vec![],
+        portfolio_values: values
+            .iter()
+            .map(|&total_value| PortfolioValueEntry {
+                timestamp: String::new(),
+                total_value,
+                holdings: BTreeMap::new(),
+                holding_values_near: BTreeMap::new(),
+                cash_balance: 0.0,
+                daily_pnl_near: 0.0,
+                daily_pnl_pct: 0.0,
+                cumulative_realized_pnl_near: 1.0,
+            })
+            .collect(),
+        benchmarks: vec![],
+        pnl_by_token: BTreeMap::new(),
+    }
+}
+
+// --- windows ---
+
+#[test]
+fn windows_roll_by_out_of_sample_length() {
+    let windows = windows(date("2026-01-01"), date("2026-01-31"), &config(10, 7));
+
+    assert_eq!(windows.len(), 3);
+    assert_eq!(
+        windows[0],
+        Window {
+            in_sample_start: date("2026-01-01"),
+            in_sample_end: date("2026-01-10"),
+            out_of_sample_start: date("2026-01-11"),
+            out_of_sample_end: date("2026-01-17"),
+        }
+    );
+    assert_eq!(windows[1].in_sample_start, date("2026-01-08"));
+    assert_eq!(windows[1].out_of_sample_start, date("2026-01-18"));
+    // Last out-of-sample window is truncated at the end date
+    assert_eq!(windows[2].out_of_sample_start, date("2026-01-25"));
+    assert_eq!(windows[2].out_of_sample_end, date("2026-01-31"));
+}
+
+#[test]
+fn windows_out_of_sample_parts_are_contiguous() {
+    let windows = windows(date("2026-01-01"), date("2026-03-01"), &config(14, 5));
+
+    for pair in windows.windows(2) {
+        assert_eq!(
+            pair[1].out_of_sample_start,
+            pair[0].out_of_sample_end + TimeDelta::days(1)
+        );
+    }
+}
+
+#[test]
+fn windows_drops_single_day_tail() {
+    // In-sample 01..10, out-of-sample would start on the end date itself
+    let windows = windows(date("2026-01-01"), date("2026-01-11"), &config(10, 7));
+    assert!(windows.is_empty());
+}
+
+#[test]
+fn windows_rejects_too_short_lengths() {
+    assert!(windows(date("2026-01-01"), date("2026-12-31"), &config(1, 7)).is_empty());
+    assert!(windows(date("2026-01-01"), date("2026-12-31"), &config(10, 1)).is_empty());
+}
+
+// --- objective ---
+
+#[test]
+fn objective_selects_metric() {
+    let perf = performance(0.1, 0.05);
+    assert_eq!(Objective::Sharpe.score(&perf, 30), 1.5);
+    assert_eq!(Objective::Sortino.score(&perf, 30), 2.5);
+}
+
+#[test]
+fn calmar_annualises_return_over_drawdown() {
+    // 10% over 365 days with a 5% drawdown
+    let score = Objective::Calmar.score(&performance(0.1, 0.05), 365);
+    assert!((score - 2.0).abs() < 1e-9, "got {score}");
+}
+
+#[test]
+fn calmar_without_drawdown_is_annualised_return() {
+    let score = Objective::Calmar.score(&performance(0.1, 0.0), 365);
+    assert!((score - 0.1).abs() < 1e-9, "got {score}");
+}
+
+#[test]
+fn calmar_total_loss_is_zero() {
+    assert_eq!(Objective::Calmar.score(&performance(-1.0, 1.0), 30), 0.0);
+}
+
+#[test]
+fn objective_deserializes_lowercase() {
+    let config: WalkForwardConfig = serde_json::from_str(
+        r#"{"in_sample_days": 30, "out_of_sample_days": 7, "objective": "calmar"}"#,
+    )
+    .unwrap();
+    assert_eq!(config.objective, Objective::Calmar);
+
+    let config: WalkForwardConfig =
+        serde_json::from_str(r#"{"in_sample_days": 30, "out_of_sample_days": 7}"#).unwrap();
+    assert_eq!(config.objective, Objective::Sharpe);
+}
+
+// --- parameter_stability ---
+
+#[test]
+fn stability_counts_changes_and_mode() {
+    let chosen = vec![
+        params(10, 0.1),
+        params(10, 0.2),
+        params(5, 0.2),
+        params(10, 0.2),
+    ];
+
+    let stability = parameter_stability(&chosen);
+
+    assert_eq!(
+        stability.top_tokens,
+        ParameterSummary {
+            distinct_values: 2,
+            most_common: 10.0,
+            most_common_share: 0.75,
+            changes: 2,
+        }
+    );
+    assert_eq!(stability.rebalance_threshold.most_common, 0.2);
+    assert_eq!(stability.rebalance_threshold.changes, 1);
+    assert_eq!(stability.price_history_days.distinct_values, 1);
+    assert_eq!(stability.price_history_days.changes, 0);
+    assert_eq!(stability.unchanged_windows, 0);
+}
+
+#[test]
+fn stability_of_identical_windows() {
+    let chosen = vec![params(10, 0.1); 3];
+
+    let stability = parameter_stability(&chosen);
+
+    assert_eq!(stability.unchanged_windows, 2);
+    assert_eq!(stability.top_tokens.most_common_share, 1.0);
+}
+
+#[test]
+fn stability_empty() {
+    let stability = parameter_stability(&[]);
+    assert_eq!(stability.top_tokens, ParameterSummary::default());
+    assert_eq!(stability.unchanged_windows, 0);
+}
+
+// --- stitch ---
+
+#[test]
+fn stitch_chains_out_of_sample_runs() {
+    let runs = vec![run(&[105.0, 110.0]), run(&[99.0, 121.0])];
+
+    let (perf, values) = stitch(100.0, runs, 1);
+
+    assert_eq!(values.len(), 4);
+    assert!((perf.total_return - 0.21).abs() < 1e-9);
+    assert_eq!(perf.final_balance_near, 121.0);
+    assert!((perf.max_drawdown - 0.1).abs() < 1e-9);
+    assert_eq!(perf.trade_count, 4);
+    assert_eq!(perf.swap_stats.total_swaps, 4);
+    assert_eq!(perf.swap_stats.fallback_rate, 0.5);
+    // Realised P&L of the second window is offset by the first window's total
+    assert_eq!(values[1].cumulative_realized_pnl_near, 1.0);
+    assert_eq!(values[2].cumulative_realized_pnl_near, 2.0);
+    assert_eq!(perf.total_realized_pnl_near, 2.0);
+}
+
+#[test]
+fn stitch_without_runs_keeps_capital() {
+    let (perf, values) = stitch(100.0, vec![], 1);
+    assert!(values.is_empty());
+    assert_eq!(perf.final_balance_near, 100.0);
+    assert_eq!(perf.total_return, 0.0);
+}
@@ -0,0 +1,43 @@
+use crate::cli::TaxLotsArgs;
+use anyhow::{Context, Result};
+use blockchain::ref_finance::token_account::WNEAR_TOKEN;
+use logging::*;
+use trade::tax_lot::{
+    CsvFiatPrices, FiatPriceSource, NoFiatPrices, lot_events_between_async, to_csv,
+};
+
+pub async fn run_tax_lots(args: &TaxLotsArgs) -> Result<()> {
+    let log = DEFAULT.new(o!("function" => "run_tax_lots"));
+
+    let start_date = args.parse_start_date()?;
+    let end_date = args.parse_end_date()?;
+    if start_date > end_date {
+        return Err(anyhow::anyhow!(
+            "start-date must not be after end-date: {} > {}",
+            start_date,
+            end_date
+        ));
+    }
+
+    let prices: Box<dyn FiatPriceSource> = match &args.fiat_prices {
+        Some(path) => Box::new(CsvFiatPrices::load(path)?),
+        None => Box::new(NoFiatPrices),
+    };
+
+    info!(log, "computing tax lots";
+        "start_date" => %start_date,
+        "end_date" => %end_date,
+        "method" => %args.method,
+        "fiat_prices" => args.fiat_prices.is_some()
+    );
+
+    let events =
+        lot_events_between_async(start_date, end_date, &WNEAR_TOKEN, args.method, &*prices).await?;
+    std::fs::write(&args.output, to_csv(&events))
+        .with_context(|| format!("failed to write {}", args.output.display()))?;
+    info!(log, "tax lots written";
+        "rows" => events.len(),
+        "path" => args.output.display().to_string()
+    );
+    Ok(())
+}
@@ -16,6 +16,8 @@ fn make_tx(from: &str, to: &str, estimated: u128, actual: Option<u128>) -> Trade
             .naive_utc(),
         evaluation_period_id: "eval_test".to_string(),
         actual_to_amount: actual.map(BigDecimal::from),
+        gas_burnt: None,
+        gas_cost: None,
     }
 }
 
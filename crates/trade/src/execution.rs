@@ -6,8 +6,9 @@
 mod matching;
 
 use crate::Result;
+use crate::gas::{self, GasEstimate};
 use crate::slippage::{ExpectedReturn, SlippagePolicy};
-use crate::swap::SwapParams;
+use crate::swap::{SwapOutcome, SwapParams};
 use crate::{recorder::TradeRecorder, swap};
 use bigdecimal::{BigDecimal, ToPrimitive, Zero};
 use blockchain::jsonrpc::{AccountInfo, GasInfo, SendTx, SentTx, ViewContract};
@@ -24,6 +25,7 @@ use num_bigint::ToBigInt;
 use persistence::evaluation_period::{EvaluationPeriod, NewEvaluationPeriod};
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
+use tracing::Instrument;
 
 /// TokenAmount を u128 に変換する。
 ///
@@ -57,6 +59,8 @@ fn token_amount_to_u128(amount: &TokenAmount) -> Result<u128> {
 struct PhaseCounters {
     success: usize,
     failed: usize,
+    /// 期待利益がガス代と手数料に届かず見送った件数
+    skipped: usize,
 }
 
 /// harvest reserve のデフォルト値（1 NEAR in yoctoNEAR）
@@ -77,6 +81,8 @@ fn buy_policy(
 pub struct ExecutionSummary {
     pub success_count: usize,
     pub failed_count: usize,
+    /// 期待利益がガス代と手数料に届かず見送ったアクション数
+    pub skipped_count: usize,
 }
 
 /// AddPosition の swap 金額を weight に基づいて按分計算する。
@@ -194,6 +200,7 @@ where
     let mut summary = ExecutionSummary {
         success_count: 0,
         failed_count: 0,
+        skipped_count: 0,
     };
 
     // TradeRecorderを作成（バッチIDで関連取引をグループ化）
@@ -202,6 +209,8 @@ where
         "batch_id" => recorder.get_batch_id(),
         "period_id" => %period_id
     );
+    // 呼び出し元の `trade.cycle` span に batch id を紐付ける（フィールドが無い span では無視される）
+    tracing::Span::current().record("trade.batch_id", recorder.get_batch_id());
 
     // AddPosition の swap 金額を事前に一括計算
     let add_position_amounts = precompute_add_position_amounts(client, wallet, actions).await?;
@@ -213,11 +222,20 @@ where
             evaluation_period_id: &period_id,
             expected_returns,
         };
-        match execute_single_action(client, wallet, action, &ctx, cfg).await {
-            Ok(_) => {
+        let span =
+            tracing::info_span!("trade.action", trade.action_index = idx, trade.action = ?action);
+        match execute_single_action(client, wallet, action, &ctx, cfg)
+            .instrument(span)
+            .await
+        {
+            Ok(SwapOutcome::Executed) => {
                 info!(log, "action executed successfully"; "action" => ?action);
                 summary.success_count += 1;
             }
+            Ok(SwapOutcome::Unprofitable) => {
+                info!(log, "action skipped: expected gain does not cover gas and fees"; "action" => ?action);
+                summary.skipped_count += 1;
+            }
             Err(e) => {
                 error!(log, "action execution failed"; "action" => ?action, "error" => ?e);
                 summary.failed_count += 1;
@@ -225,6 +243,20 @@ where
         }
     }
 
+    // バッチ全体で燃やしたガスを記録（失敗しても取引結果には影響しない）
+    match persistence::trade_transaction::TradeTransaction::gas_by_batch_async(
+        recorder.get_batch_id().to_string(),
+    )
+    .await
+    {
+        Ok((gas_burnt, gas_cost)) => info!(log, "batch gas spent";
+            "batch_id" => recorder.get_batch_id(),
+            "gas_burnt" => gas_burnt.map(|g| g.to_string()).unwrap_or_else(|| "0".to_string()),
+            "gas_cost_yocto" => gas_cost.map(|c| c.to_string()).unwrap_or_else(|| "0".to_string()),
+        ),
+        Err(e) => warn!(log, "failed to sum batch gas"; "error" => %e),
+    }
+
     Ok(summary)
 }
 
@@ -237,13 +269,16 @@ struct ActionContext<'a> {
 }
 
 /// 単一の取引アクションを実行
+///
+/// 購入スワップ（リバランスでは計画全体）が採算に合わず見送られた場合は
+/// [`SwapOutcome::Unprofitable`] を返す。
 async fn execute_single_action<C, W>(
     client: &C,
     wallet: &W,
     action: &TradingAction,
     ctx: &ActionContext<'_>,
     cfg: &impl ConfigAccess,
-) -> Result<()>
+) -> Result<SwapOutcome>
 where
     C: blockchain::jsonrpc::AccountInfo
         + blockchain::jsonrpc::SendTx
@@ -260,7 +295,7 @@ where
         TradingAction::Hold => {
             // HODLなので何もしない
             trace!(log, "holding position");
-            Ok(())
+            Ok(SwapOutcome::Executed)
         }
         TradingAction::Sell { token, target } => {
             // token を売却して target を購入
@@ -293,7 +328,7 @@ where
             // Step 2: wrap.near → target（購入フェーズ: スリッページ保護あり）
             if target.inner() != wrap_near {
                 let target_policy = buy_policy(target, expected_returns);
-                swap::execute_direct_swap(
+                let outcome = swap::execute_direct_swap(
                     client,
                     wallet,
                     &SwapParams {
@@ -306,10 +341,15 @@ where
                     cfg,
                 )
                 .await?;
+                if outcome == SwapOutcome::Unprofitable {
+                    // 売却済みの分は wrap.near のまま残る
+                    info!(log, "sell completed without buying target"; "from" => %token, "to" => %target);
+                    return Ok(outcome);
+                }
             }
 
             debug!(log, "sell completed"; "from" => %token, "to" => %target);
-            Ok(())
+            Ok(SwapOutcome::Executed)
         }
         TradingAction::Switch { from, to } => {
             // from から to へ切り替え（直接スワップ）
@@ -318,7 +358,7 @@ where
 
             let from_token = from.as_in();
             let to_policy = buy_policy(to, expected_returns);
-            swap::execute_direct_swap(
+            let outcome = swap::execute_direct_swap(
                 client,
                 wallet,
                 &SwapParams {
@@ -332,8 +372,15 @@ where
             )
             .await?;
 
-            debug!(log, "switch completed"; "from" => %from, "to" => %to);
-            Ok(())
+            match outcome {
+                SwapOutcome::Executed => {
+                    debug!(log, "switch completed"; "from" => %from, "to" => %to)
+                }
+                SwapOutcome::Unprofitable => {
+                    info!(log, "switch skipped"; "from" => %from, "to" => %to)
+                }
+            }
+            Ok(outcome)
         }
         TradingAction::Rebalance { target_weights } => {
             execute_rebalance(
@@ -372,7 +419,7 @@ where
 
                 let wrap_near_in: TokenInAccount = wrap_near.to_in();
                 let token_policy = buy_policy(token, expected_returns);
-                swap::execute_direct_swap(
+                let outcome = swap::execute_direct_swap(
                     client,
                     wallet,
                     &SwapParams {
@@ -385,10 +432,14 @@ where
                     cfg,
                 )
                 .await?;
+                if outcome == SwapOutcome::Unprofitable {
+                    info!(log, "position not added"; "token" => %token, "weight" => %weight);
+                    return Ok(outcome);
+                }
             }
 
             debug!(log, "position added"; "token" => %token, "weight" => %weight);
-            Ok(())
+            Ok(SwapOutcome::Executed)
         }
         TradingAction::ReducePosition { token, weight } => {
             // ポジション削減
@@ -416,7 +467,7 @@ where
             }
 
             debug!(log, "position reduced"; "token" => %token, "weight" => %weight);
-            Ok(())
+            Ok(SwapOutcome::Executed)
         }
     }
 }
@@ -429,8 +480,14 @@ where
 /// 3. 残余売却: マッチングされなかった売却を wNEAR 経由で実行
 /// 4. 残余購入: マッチングされなかった購入を wNEAR 経由で実行
 ///
+/// 直接スワップ・残余売却・残余購入の各フェーズ内のスワップは
+/// [`swap::execute_swap_batch`] でトランザクションにまとめて送る。
+///
 /// DB から取得するスポットレートの鮮度は外部（token_rate の更新サイクル）に依存する。
 /// レート乖離が大きい場合はスリッページ保護により失敗し、次サイクルで再試行される。
+///
+/// 計画全体の期待利益が見積もりガス代に届かない場合は何も送らず
+/// [`SwapOutcome::Unprofitable`] を返す。
 async fn execute_rebalance<C, W>(
     client: &C,
     wallet: &W,
@@ -439,7 +496,7 @@ async fn execute_rebalance<C, W>(
     recorder: &TradeRecorder,
     cfg: &impl ConfigAccess,
     expected_returns: &BTreeMap<TokenOutAccount, f64>,
-) -> Result<()>
+) -> Result<SwapOutcome>
 where
     C: blockchain::jsonrpc::AccountInfo
         + blockchain::jsonrpc::SendTx
@@ -584,6 +641,58 @@ where
         "remaining_buys" => remaining_buys.len()
     );
 
+    // 計画全体のガスを見積もる。経路は実行時に決まるため、直接スワップは
+    // token → wNEAR → token の 2 ホップ、残余売買は 1 ホップとして概算する。
+    // 保有していないトークンの購入には storage の登録が伴う。
+    let new_tokens = remaining_buys
+        .iter()
+        .map(|op| &op.token)
+        .chain(direct_swaps.iter().map(|ds| &ds.buy_token))
+        .filter(|token| current_balances.get(*token).is_none_or(|a| a.is_zero()))
+        .count();
+    let plan_gas = GasEstimate::default()
+        .with_swaps(direct_swaps.len(), 2)
+        .with_swaps(remaining_sells.len() + remaining_buys.len(), 1)
+        .with_storage_actions(new_tokens);
+    match client.get_gas_price(None).await {
+        Ok(gas_price) => {
+            let plan_cost = plan_gas.cost(gas_price);
+            info!(log, "estimated plan gas";
+                "swaps" => plan_gas.swaps(),
+                "storage_actions" => new_tokens,
+                "tgas" => plan_gas.gas().as_tgas(),
+                "cost" => %plan_cost
+            );
+            let moves: Vec<_> = direct_swaps
+                .iter()
+                .map(|ds| (&ds.sell_token, &ds.buy_token, &ds.near_value))
+                .chain(
+                    remaining_sells
+                        .iter()
+                        .map(|op| (&op.token, wnear, &op.near_value)),
+                )
+                .chain(
+                    remaining_buys
+                        .iter()
+                        .map(|op| (wnear, &op.token, &op.near_value)),
+                )
+                .collect();
+            if let Some(gain) = plan_expected_gain(&moves, expected_returns)
+                && !gas::covers_cost(gain, plan_cost.as_yoctonear(), 0)
+            {
+                info!(log, "skipping rebalance: expected gain does not cover plan gas";
+                    "expected_gain" => gain,
+                    "cost" => %plan_cost
+                );
+                return Ok(SwapOutcome::Unprofitable);
+            }
+        }
+        Err(e) => warn!(log, "failed to get gas price for plan estimate"; "error" => %e),
+    }
+
+    // 各フェーズのスワップは互いに独立なので、フェーズごとに REF 宛てのトランザクションに
+    // まとめて送る（`swap::execute_swap_batch`）。フェーズの間は順に待つ。
+    //
     // 1. 直接スワップ実行（near_value 降順 — match_rebalance_operations がソート済み）
     // 失敗した直接スワップは remaining に fallback し、wNEAR 経由で再試行される。
     // NOTE: at-least-once セマンティクス — RPC タイムアウト等でトランザクションが
@@ -591,7 +700,19 @@ where
     // 再実行される可能性がある。次回リバランスサイクルで超過分は自然修正される。
     let mut fallback_sells = Vec::new();
     let mut fallback_buys = Vec::new();
+    let mut fall_back = |ds: &matching::DirectSwap| {
+        fallback_sells.push(SellOperation {
+            token: ds.sell_token.clone(),
+            near_value: ds.near_value.clone(),
+            exchange_rate: ds.sell_exchange_rate.clone(),
+        });
+        fallback_buys.push(BuyOperation {
+            token: ds.buy_token.clone(),
+            near_value: ds.near_value.clone(),
+        });
+    };
 
+    let mut planned_direct = Vec::with_capacity(direct_swaps.len());
     for ds in &direct_swaps {
         let token_amount: TokenAmount = &ds.near_value * &ds.sell_exchange_rate;
         let token_amount_u128 = match token_amount_to_u128(&token_amount) {
@@ -599,15 +720,7 @@ where
             Err(e) => {
                 error!(log, "token amount conversion failed"; "error" => %e);
                 direct_swap_counters.failed += 1;
-                fallback_sells.push(SellOperation {
-                    token: ds.sell_token.clone(),
-                    near_value: ds.near_value.clone(),
-                    exchange_rate: ds.sell_exchange_rate.clone(),
-                });
-                fallback_buys.push(BuyOperation {
-                    token: ds.buy_token.clone(),
-                    near_value: ds.near_value.clone(),
-                });
+                fall_back(ds);
                 continue;
             }
         };
@@ -616,15 +729,7 @@ where
             warn!(log, "token amount truncated to zero, skipping direct swap";
                 "sell_token" => %ds.sell_token, "buy_token" => %ds.buy_token);
             direct_swap_counters.failed += 1;
-            fallback_sells.push(SellOperation {
-                token: ds.sell_token.clone(),
-                near_value: ds.near_value.clone(),
-                exchange_rate: ds.sell_exchange_rate.clone(),
-            });
-            fallback_buys.push(BuyOperation {
-                token: ds.buy_token.clone(),
-                near_value: ds.near_value.clone(),
-            });
+            fall_back(ds);
             continue;
         }
 
@@ -634,23 +739,27 @@ where
             "near_value" => %ds.near_value,
             "token_amount" => token_amount_u128
         );
+        planned_direct.push((
+            ds,
+            TokenInAccount::from(ds.sell_token.clone()),
+            TokenOutAccount::from(ds.buy_token.clone()),
+            token_amount_u128,
+        ));
+    }
 
-        let from_token: TokenInAccount = ds.sell_token.clone().into();
-        let to_token: TokenOutAccount = ds.buy_token.clone().into();
-        match swap::execute_direct_swap(
-            client,
-            wallet,
-            &SwapParams {
-                from_token: &from_token,
-                to_token: &to_token,
-                swap_amount: Some(token_amount_u128),
-                recorder,
-                policy: &SlippagePolicy::Unprotected,
-            },
-            cfg,
-        )
-        .await
-        {
+    let direct_params: Vec<SwapParams<'_>> = planned_direct
+        .iter()
+        .map(|(_, from_token, to_token, amount)| SwapParams {
+            from_token,
+            to_token,
+            swap_amount: Some(*amount),
+            recorder,
+            policy: &SlippagePolicy::Unprotected,
+        })
+        .collect();
+    let direct_results = swap::execute_swap_batch(client, wallet, &direct_params, cfg).await;
+    for ((ds, ..), result) in planned_direct.iter().zip(direct_results) {
+        match result {
             Ok(_) => {
                 info!(log, "direct swap completed";
                     "sell_token" => %ds.sell_token, "buy_token" => %ds.buy_token);
@@ -665,15 +774,7 @@ where
                     "buy_token" => %ds.buy_token,
                     "near_value" => %ds.near_value);
                 direct_swap_counters.failed += 1;
-                fallback_sells.push(SellOperation {
-                    token: ds.sell_token.clone(),
-                    near_value: ds.near_value.clone(),
-                    exchange_rate: ds.sell_exchange_rate.clone(),
-                });
-                fallback_buys.push(BuyOperation {
-                    token: ds.buy_token.clone(),
-                    near_value: ds.near_value.clone(),
-                });
+                fall_back(ds);
             }
         }
     }
@@ -682,6 +783,7 @@ where
     remaining_buys.extend(fallback_buys);
 
     // 2. 残余売却実行（token → wNEAR）
+    let mut planned_sells = Vec::with_capacity(remaining_sells.len());
     for sell in &remaining_sells {
         let token_amount: TokenAmount = &sell.near_value * &sell.exchange_rate;
         let token_amount_u128 = match token_amount_to_u128(&token_amount) {
@@ -702,21 +804,26 @@ where
 
         trace!(log, "executing remainder sell";
             "token" => %sell.token, "near_value" => %sell.near_value);
-        let from_token: TokenInAccount = sell.token.clone().into();
-        match swap::execute_direct_swap(
-            client,
-            wallet,
-            &SwapParams {
-                from_token: &from_token,
-                to_token: &wrap_near_out,
-                swap_amount: Some(token_amount_u128),
-                recorder,
-                policy: &SlippagePolicy::Unprotected,
-            },
-            cfg,
-        )
-        .await
-        {
+        planned_sells.push((
+            sell,
+            TokenInAccount::from(sell.token.clone()),
+            token_amount_u128,
+        ));
+    }
+
+    let sell_params: Vec<SwapParams<'_>> = planned_sells
+        .iter()
+        .map(|(_, from_token, amount)| SwapParams {
+            from_token,
+            to_token: &wrap_near_out,
+            swap_amount: Some(*amount),
+            recorder,
+            policy: &SlippagePolicy::Unprotected,
+        })
+        .collect();
+    let sell_results = swap::execute_swap_batch(client, wallet, &sell_params, cfg).await;
+    for ((sell, ..), result) in planned_sells.iter().zip(sell_results) {
+        match result {
             Ok(_) => {
                 info!(log, "remainder sell completed"; "token" => %sell.token);
                 remainder_sell.success += 1;
@@ -779,6 +886,7 @@ where
             // 意図的に事前按分としている。次サイクルで自然修正される。
             let mut allocated_sum: u128 = 0;
             let buy_count = remaining_buys.len();
+            let mut planned_buys = Vec::with_capacity(buy_count);
             for (i, buy) in remaining_buys.iter().enumerate() {
                 let is_last = i == buy_count - 1;
                 let wrap_near_amount_u128 = if is_last && ratio.is_some() {
@@ -812,25 +920,30 @@ where
                     "original_value" => %buy.near_value,
                     "wrap_near_amount" => wrap_near_amount_u128);
                 let to_token: TokenOutAccount = buy.token.clone().into();
-                let buy_token_policy = buy_policy(&to_token, expected_returns);
-                match swap::execute_direct_swap(
-                    client,
-                    wallet,
-                    &SwapParams {
-                        from_token: &wrap_near_in,
-                        to_token: &to_token,
-                        swap_amount: Some(wrap_near_amount_u128),
-                        recorder,
-                        policy: &buy_token_policy,
-                    },
-                    cfg,
-                )
-                .await
-                {
-                    Ok(_) => {
+                let policy = buy_policy(&to_token, expected_returns);
+                planned_buys.push((buy, to_token, policy, wrap_near_amount_u128));
+            }
+
+            let buy_params: Vec<SwapParams<'_>> = planned_buys
+                .iter()
+                .map(|(_, to_token, policy, amount)| SwapParams {
+                    from_token: &wrap_near_in,
+                    to_token,
+                    swap_amount: Some(*amount),
+                    recorder,
+                    policy,
+                })
+                .collect();
+            let buy_results = swap::execute_swap_batch(client, wallet, &buy_params, cfg).await;
+            for ((buy, ..), result) in planned_buys.iter().zip(buy_results) {
+                match result {
+                    Ok(SwapOutcome::Executed) => {
                         info!(log, "remainder buy completed"; "token" => %buy.token);
                         remainder_buy.success += 1;
                     }
+                    Ok(SwapOutcome::Unprofitable) => {
+                        remainder_buy.skipped += 1;
+                    }
                     Err(e) => {
                         error!(log, "remainder buy failed"; "token" => %buy.token, "error" => %e);
                         remainder_buy.failed += 1;
@@ -864,7 +977,8 @@ where
         "remainder_sell_success" => remainder_sell.success,
         "remainder_sell_failed" => remainder_sell.failed,
         "remainder_buy_success" => remainder_buy.success,
-        "remainder_buy_failed" => remainder_buy.failed
+        "remainder_buy_failed" => remainder_buy.failed,
+        "remainder_buy_skipped" => remainder_buy.skipped
     );
 
     // 全操作失敗時のみエラーを返す。部分失敗は次回リバランスサイクルで自然修正。
@@ -874,8 +988,37 @@ where
             total_failed
         ));
     }
+    if total_success == 0 && remainder_buy.skipped > 0 {
+        return Ok(SwapOutcome::Unprofitable);
+    }
 
-    Ok(())
+    Ok(SwapOutcome::Executed)
+}
+
+/// リバランス計画全体の期待利益（yoctoNEAR）
+///
+/// `moves` は (売却トークン, 購入トークン, wrap.near 換算額)。各移動の利益は
+/// 購入側と売却側の期待リターンの差で見込み、wrap.near のリターンは 0 とする。
+/// 期待リターンの無いトークンを含む計画は判定できないため `None` を返す。
+fn plan_expected_gain(
+    moves: &[(&TokenAccount, &TokenAccount, &NearValue)],
+    expected_returns: &BTreeMap<TokenOutAccount, f64>,
+) -> Option<u128> {
+    let wnear = &*blockchain::ref_finance::token_account::WNEAR_TOKEN;
+    let expected_return = |token: &TokenAccount| {
+        if token == wnear {
+            Some(0.0)
+        } else {
+            expected_returns.get(&token.to_out()).copied()
+        }
+    };
+    moves
+        .iter()
+        .try_fold(0u128, |total, (sell, buy, near_value)| {
+            let diff = expected_return(buy)? - expected_return(sell)?;
+            let amount = near_value.to_yocto().to_amount().to_u128();
+            Some(total.saturating_add(gas::expected_gain(amount, diff)))
+        })
 }
 
 /// 評価期間のチェックと管理
@@ -899,12 +1042,34 @@ pub(crate) struct LiquidationResult {
 }
 
 fn spawn_cleanup_old_evaluation_periods(retention_days: u32) {
-    tokio::spawn(async move {
+    let cleanup = async move {
         if let Err(e) = persistence::evaluation_period::cleanup_old_records(retention_days).await {
             let log = DEFAULT.new(o!("function" => "cleanup_old_evaluation_periods"));
             warn!(log, "failed to cleanup old evaluation periods"; "error" => %e);
         }
-    });
+    };
+    // インメモリストアで実行中ならその中の評価期間を掃除する
+    #[cfg(feature = "test-helpers")]
+    let cleanup = persistence::memory::propagate(cleanup);
+    tokio::spawn(cleanup);
+}
+
+/// 評価期間のトークン別実現損益をログ出力（取得できなければ警告のみ）
+async fn log_period_realized_pnl(log: &slog::Logger, period_id: &str) {
+    use persistence::trade_transaction::TradeTransaction;
+    match TradeTransaction::find_by_evaluation_period_async(period_id.to_string()).await {
+        Ok(transactions) => {
+            let book = crate::pnl::CostBasisBook::from_transactions(&transactions);
+            for (token, realized) in book.realized() {
+                info!(log, "evaluation period token pnl";
+                    "period_id" => %period_id,
+                    "token" => %token,
+                    "realized_pnl" => %realized
+                );
+            }
+        }
+        Err(e) => warn!(log, "failed to load transactions for token pnl"; "error" => %e),
+    }
 }
 
 pub(crate) async fn manage_evaluation_period<C, W>(
@@ -926,8 +1091,10 @@ where
 
     info!(log, "evaluation period configuration"; "days" => evaluation_period_days);
 
-    // 最新の評価期間を取得
-    let latest_period = EvaluationPeriod::get_latest_async().await?;
+    // 最新の評価期間を取得（period_id の接頭辞が一致するものに限る）
+    let period_prefix = cfg.trade_evaluation_period_prefix();
+    let latest_period =
+        EvaluationPeriod::get_latest_with_prefix_async(period_prefix.clone()).await?;
 
     match latest_period {
         Some(period) => {
@@ -974,6 +1141,9 @@ where
                     "change_percentage" => %format!("{:.2}%", change_percentage)
                 );
 
+                // 清算後なので期間中に取得した分はすべて実現済み
+                log_period_realized_pnl(&log, &period_id).await;
+
                 // ハーベスト判定: 旧 period の initial_value と清算後の final_value で比較
                 // 新 period 作成前に実行することで、正しい initial_value で判定できる
                 let harvested_amount = crate::harvest::check_and_execute_harvest(
@@ -1032,7 +1202,11 @@ where
                 }
 
                 // 新規評価期間を作成（ハーベスト後の残高を initial_value とする）
-                let new_period = NewEvaluationPeriod::new(post_harvest_value.to_amount(), vec![]);
+                let new_period = NewEvaluationPeriod::with_prefix(
+                    &period_prefix,
+                    post_harvest_value.to_amount(),
+                    vec![],
+                );
                 let created_period = new_period.insert_async().await?;
 
                 info!(log, "created new evaluation period";
@@ -1108,7 +1282,8 @@ where
             // 初回起動: 新規評価期間を作成
             info!(log, "no evaluation period found, creating first period");
 
-            let new_period = NewEvaluationPeriod::new(available_funds.clone(), vec![]);
+            let new_period =
+                NewEvaluationPeriod::with_prefix(&period_prefix, available_funds.clone(), vec![]);
             let created_period = new_period.insert_async().await?;
 
             info!(log, "created first evaluation period";
@@ -1165,8 +1340,10 @@ where
 {
     let log = DEFAULT.new(o!("function" => "liquidate_all_positions"));
 
-    // 最新の評価期間を取得
-    let latest_period = EvaluationPeriod::get_latest_async().await?;
+    // 最新の評価期間を取得（period_id の接頭辞が一致するものに限る）
+    let latest_period =
+        EvaluationPeriod::get_latest_with_prefix_async(cfg.trade_evaluation_period_prefix())
+            .await?;
     let period_id = match latest_period {
         Some(period) => {
             // selected_tokensは履歴として記録（実際の清算には使用しない）
@@ -338,3 +338,42 @@ fn test_rounding_remainder_indivisible_yocto() {
         "total must equal available even for small indivisible values"
     );
 }
+
+fn near(value: i64) -> NearValue {
+    NearValue::from_near(BigDecimal::from(value))
+}
+
+#[test]
+fn test_plan_expected_gain_uses_return_difference() {
+    use super::super::plan_expected_gain;
+    use super::helpers::token_account;
+
+    let wnear = &*blockchain::ref_finance::token_account::WNEAR_TOKEN;
+    let a = token_account("a.near");
+    let b = token_account("b.near");
+    let mut expected_returns = std::collections::BTreeMap::new();
+    expected_returns.insert(a.to_out(), 0.5);
+    expected_returns.insert(b.to_out(), -0.25);
+
+    let (ten, twenty) = (near(10), near(20));
+    // b → a: 10 × 0.75 = 7.5 NEAR、a 購入: 20 × 0.5 = 10 NEAR、b 売却: 10 × 0.25 = 2.5 NEAR
+    let gain = plan_expected_gain(
+        &[(&b, &a, &ten), (wnear, &a, &twenty), (&b, wnear, &ten)],
+        &expected_returns,
+    )
+    .unwrap();
+    // f64 経由のため yocto 単位の誤差は許容する
+    assert!(gain.abs_diff(20 * 10u128.pow(24)) < 10u128.pow(12));
+
+    // 下落予測のトークンへの移動は利益 0
+    assert_eq!(
+        plan_expected_gain(&[(&a, &b, &ten)], &expected_returns),
+        Some(0)
+    );
+    // 期待リターンの無いトークンを含む計画は判定しない
+    let c = token_account("c.near");
+    assert_eq!(
+        plan_expected_gain(&[(wnear, &c, &ten)], &expected_returns),
+        None
+    );
+}
@@ -0,0 +1,97 @@
+//! 取引計画のガス見積もりと損益分岐の判定
+//!
+//! スワップ 1 回のガスは `preview::swap_gas`（固定分 + ホップ数 × `BY_STEP_GAS`）、
+//! storage の top-up / トークン登録は 1 action あたり [`STORAGE_ACTION_GAS`] で見積もり、
+//! 送信時点のガス価格を掛けて NEAR に換算する。
+//!
+//! 期待利益がガス代とプール手数料の合計に届かない取引は送らない。
+
+use blockchain::ref_finance::path::preview::{BY_STEP_GAS, HEAD_GAS};
+use blockchain::ref_finance::path::split::SplitRoute;
+use blockchain::types::gas_price::GasPrice;
+use dex::FEE_DIVISOR;
+use near_gas::NearGas;
+use near_sdk::NearToken;
+
+/// storage の top-up / トークン登録 1 action あたりの消費ガスの見込み
+pub const STORAGE_ACTION_GAS: NearGas = NearGas::from_tgas(5);
+
+/// 取引計画全体の消費ガスの見込み
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GasEstimate {
+    swaps: u64,
+    hops: u64,
+    storage_actions: u64,
+}
+
+impl GasEstimate {
+    /// `hops` ホップのスワップを 1 回追加する
+    pub fn with_swap(self, hops: usize) -> Self {
+        self.with_swaps(1, hops)
+    }
+
+    /// それぞれ `hops` ホップのスワップを `count` 回追加する
+    pub fn with_swaps(mut self, count: usize, hops: usize) -> Self {
+        self.swaps += count as u64;
+        self.hops += (count * hops) as u64;
+        self
+    }
+
+    /// storage action を `count` 個追加する
+    pub fn with_storage_actions(mut self, count: usize) -> Self {
+        self.storage_actions += count as u64;
+        self
+    }
+
+    pub fn swaps(&self) -> u64 {
+        self.swaps
+    }
+
+    pub fn gas(&self) -> NearGas {
+        NearGas::from_gas(
+            HEAD_GAS.as_gas() * self.swaps
+                + BY_STEP_GAS.as_gas() * self.hops
+                + STORAGE_ACTION_GAS.as_gas() * self.storage_actions,
+        )
+    }
+
+    /// `gas_price` で換算したガス代
+    pub fn cost(&self, gas_price: GasPrice) -> NearToken {
+        NearToken::from_yoctonear(gas_price.cost_of(self.gas()))
+    }
+}
+
+/// 経路全体で差し引かれるプール手数料（入力トークンの最小単位）
+///
+/// 各 leg の入力に対して、経路上のプール手数料を複利で適用した分を合計する。
+pub fn route_fee(route: &SplitRoute) -> u128 {
+    route
+        .legs
+        .iter()
+        .map(|leg| path_fee(leg.amount_in, leg.path.0.iter().map(|pair| pair.fee())))
+        .sum()
+}
+
+fn path_fee(amount_in: u128, fees: impl IntoIterator<Item = u32>) -> u128 {
+    let remaining = fees.into_iter().fold(amount_in, |remaining, fee| {
+        remaining * u128::from(FEE_DIVISOR - fee) / u128::from(FEE_DIVISOR)
+    });
+    amount_in - remaining
+}
+
+/// 期待リターンから見込まれる利益（入力トークンの最小単位）
+///
+/// 下落予測（負のリターン）の利益は 0 とする。
+pub fn expected_gain(amount_in: u128, expected_return: f64) -> u128 {
+    (amount_in as f64 * expected_return.max(0.0)) as u128
+}
+
+/// 期待利益がガス代と手数料の合計を上回るか
+///
+/// いずれも入力トークンの最小単位で渡す。
+pub fn covers_cost(expected_gain: u128, gas_cost: u128, fee: u128) -> bool {
+    expected_gain > gas_cost.saturating_add(fee)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,54 @@
+use super::*;
+
+#[test]
+fn test_gas_estimate_sums_swaps_and_storage() {
+    let estimate = GasEstimate::default()
+        .with_swap(2)
+        .with_swaps(0, 3)
+        .with_swap(1)
+        .with_storage_actions(1);
+    assert_eq!(estimate.swaps(), 2);
+    // 2 × 2.7 + 3 × 2.6 + 1 × 5 = 18.2 Tgas
+    assert_eq!(estimate.gas(), NearGas::from_ggas(18_200));
+
+    let gas_price = GasPrice::from_balance(NearToken::from_yoctonear(100_000_000));
+    assert_eq!(
+        estimate.cost(gas_price).as_yoctonear(),
+        18_200_000_000_000 * 100_000_000
+    );
+}
+
+#[test]
+fn test_gas_estimate_empty_plan_is_free() {
+    let estimate = GasEstimate::default();
+    assert_eq!(estimate.gas(), NearGas::from_gas(0));
+    assert_eq!(
+        estimate.cost(GasPrice::from_balance(NearToken::from_yoctonear(
+            100_000_000
+        ))),
+        NearToken::from_yoctonear(0)
+    );
+}
+
+#[test]
+fn test_path_fee_compounds_per_hop() {
+    assert_eq!(path_fee(1_000_000, []), 0);
+    // 0.3%
+    assert_eq!(path_fee(1_000_000, [30]), 3_000);
+    // 1 - 0.997 × 0.998 = 0.4994%
+    assert_eq!(path_fee(1_000_000, [30, 20]), 4_994);
+}
+
+#[test]
+fn test_expected_gain_ignores_negative_return() {
+    assert_eq!(expected_gain(1_000_000, 0.05), 50_000);
+    assert_eq!(expected_gain(1_000_000, -0.05), 0);
+}
+
+#[test]
+fn test_covers_cost() {
+    let gas_cost = 1_000;
+    assert!(covers_cost(1_501, gas_cost, 500));
+    assert!(!covers_cost(1_500, gas_cost, 500));
+    assert!(!covers_cost(0, gas_cost, 0));
+}
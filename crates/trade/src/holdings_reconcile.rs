@@ -0,0 +1,222 @@
+//! on-chain 保有量と DB 上の保有量の突合
+//!
+//! 現在の評価期間の最初の `portfolio_holdings` スナップショットを基準に、それ以降の
+//! `trade_transactions` と補正レコードを積み上げた「DB 上あるはずの保有量」を求め、
+//! REF Finance の deposit と比較する。スナップショットは REF deposit だけを数えているので、
+//! 比較も REF deposit 同士で行い、wallet の FT 残高は（REF から出金されたかを見る）参考値として
+//! 並べて記録する。
+//! 清算失敗・手動の出金・ハーベスト漏れなど、取引記録で説明できない残高変化は
+//! 許容範囲を超えた差分（discrepancy）として `holding_reconciliations` に記録される。
+//! 残高の取得に失敗した場合は何も記録せずエラーを返す（一時的な RPC 失敗を補正にしない）。
+
+use crate::Result;
+use anyhow::Context;
+use bigdecimal::{BigDecimal, Signed, Zero};
+use blockchain::jsonrpc::ViewContract;
+use blockchain::ref_finance::token_account::NEAR_TOKEN;
+use blockchain::wallet::Wallet;
+use common::config::ConfigAccess;
+use common::types::{TokenAccount, TokenSmallestUnits};
+use logging::*;
+use persistence::evaluation_period::EvaluationPeriod;
+use persistence::holding_reconciliation::{
+    DbHoldingReconciliation, HoldingReconciliation, ReconciliationReport, TokenReconciliation,
+};
+use persistence::portfolio_holding::{PortfolioHolding, TokenHolding};
+use persistence::trade_transaction::TradeTransaction;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// holdings 突合ジョブ本体
+pub async fn run(cfg: &impl ConfigAccess) -> Result<()> {
+    // 取引中の残高は途中経過なので、トレードサイクルとは重ならないようにする
+    let _cycle = crate::CYCLE_LOCK.lock().await;
+    let client = blockchain::jsonrpc::new_client();
+    // 結果未確認のスワップを記録してからでないと、その分が差分に見える
+    crate::reconcile::reconcile_pending_transactions(&client)
+        .await
+        .context("failed to reconcile pending transactions")?;
+    let wallet = blockchain::wallet::new_wallet();
+    reconcile_holdings(
+        &client,
+        &wallet,
+        cfg.holdings_reconcile_write_adjustments(),
+        cfg,
+    )
+    .await?;
+    Ok(())
+}
+
+/// 現在の評価期間について突合を行い、結果を記録する
+///
+/// 評価期間または基準スナップショットがまだ無い場合は何もせず `None` を返す。
+/// `write_adjustments` が true なら、差分を補正レコードとして書き込み、次回以降の
+/// 突合ではその差分を説明済みとして扱う。
+pub async fn reconcile_holdings<C, W>(
+    client: &C,
+    wallet: &W,
+    write_adjustments: bool,
+    cfg: &impl ConfigAccess,
+) -> Result<Option<DbHoldingReconciliation>>
+where
+    C: ViewContract,
+    W: Wallet,
+{
+    let log = DEFAULT.new(o!("function" => "reconcile_holdings"));
+
+    let Some(period) = EvaluationPeriod::get_latest_async().await? else {
+        info!(log, "no evaluation period, skipping reconciliation");
+        return Ok(None);
+    };
+    let period_id = period.period_id;
+    let Some(baseline) = PortfolioHolding::get_first_for_period_async(period_id.clone()).await?
+    else {
+        info!(log, "no holdings snapshot in current period, skipping reconciliation";
+            "period_id" => %period_id);
+        return Ok(None);
+    };
+
+    // スナップショットは取引後に書き込まれるため、created_at より後の取引だけを積み上げる
+    let trades =
+        TradeTransaction::find_by_period_after_async(period_id.clone(), baseline.created_at)
+            .await?;
+    let adjustments =
+        HoldingReconciliation::sum_adjustments_by_period_async(period_id.clone()).await?;
+    let baseline_holdings = baseline.parse_holdings()?;
+    let expected = expected_holdings(&baseline_holdings, &trades, &adjustments);
+
+    let account = wallet.account_id();
+    let deposits = blockchain::ref_finance::deposit::get_deposits(client, account).await?;
+
+    let tokens: BTreeSet<TokenAccount> = expected
+        .keys()
+        .cloned()
+        .chain(
+            deposits
+                .iter()
+                .filter(|(_, amount)| amount.0 > 0)
+                .map(|(token, _)| token.clone()),
+        )
+        .collect();
+
+    let tolerance_bps = cfg.holdings_reconcile_tolerance_bps();
+    let mut results = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let decimals = crate::token_cache::get_token_decimals_cached(client, &token).await?;
+        let wallet_balance =
+            blockchain::ref_finance::deposit::wallet_balance_of(client, &token, account)
+                .await
+                .with_context(|| format!("failed to get wallet balance of {token}"))?
+                .0;
+        let ref_deposit = deposits.get(&token).map(|u| u.0).unwrap_or_default();
+        let token_expected = expected.get(&token).cloned().unwrap_or_default();
+
+        let result = compare(
+            token,
+            decimals,
+            token_expected,
+            ref_deposit,
+            wallet_balance,
+            tolerance_bps,
+        );
+        if result.discrepancy {
+            warn!(log, "holding discrepancy";
+                "token" => %result.token,
+                "expected" => %result.expected,
+                "ref_deposit" => %result.ref_deposit,
+                "wallet_balance" => %result.wallet_balance,
+                "difference" => %result.difference,
+            );
+        }
+        results.push(result);
+    }
+
+    let report = ReconciliationReport {
+        evaluation_period_id: period_id,
+        baseline_holding_id: baseline.id,
+        checked_at: chrono::Utc::now().naive_utc(),
+        tokens: results,
+    };
+    let token_count = report.tokens.len();
+    let recorded = HoldingReconciliation::insert_async(report, write_adjustments).await?;
+
+    info!(log, "holdings reconciled";
+        "reconciliation_id" => recorded.id,
+        "token_count" => token_count,
+        "discrepancies" => recorded.discrepancy_count,
+        "adjusted" => recorded.adjusted,
+    );
+    Ok(Some(recorded))
+}
+
+/// 基準スナップショットに取引と補正を積み上げた、DB 上あるはずの保有量（最小単位）
+///
+/// ネイティブ NEAR（ハーベストの送金先）は REF deposit / FT 残高の対象外なので除く。
+fn expected_holdings(
+    baseline: &[TokenHolding],
+    trades: &[TradeTransaction],
+    adjustments: &BTreeMap<String, BigDecimal>,
+) -> BTreeMap<TokenAccount, BigDecimal> {
+    let mut expected: BTreeMap<TokenAccount, BigDecimal> = BTreeMap::new();
+    for h in baseline {
+        *expected.entry(h.token.clone()).or_default() += h.balance.as_bigdecimal();
+    }
+
+    let mut add = |token: &str, amount: &BigDecimal| {
+        if let Ok(token) = token.parse::<TokenAccount>() {
+            *expected.entry(token).or_default() += amount;
+        }
+    };
+    for trade in trades {
+        add(
+            &trade.from_token,
+            &-trade.from_amount.as_bigdecimal().clone(),
+        );
+        // 約定実績があればそれを、無ければ送信時の推定出力を使う
+        let received = trade
+            .actual_to_amount
+            .clone()
+            .unwrap_or_else(|| trade.to_amount.as_bigdecimal().clone());
+        add(&trade.to_token, &received);
+    }
+    for (token, amount) in adjustments {
+        add(token, amount);
+    }
+
+    expected.remove(&*NEAR_TOKEN);
+    expected
+}
+
+/// 1 トークン分の突合結果を作る
+///
+/// 差分は REF deposit と `expected` の差で、wallet の残高は含めない。
+/// 差分が `max(|expected|, ref_deposit)` の `tolerance_bps` を超えたら discrepancy とする。
+fn compare(
+    token: TokenAccount,
+    decimals: u8,
+    expected: BigDecimal,
+    ref_deposit: u128,
+    wallet_balance: u128,
+    tolerance_bps: u32,
+) -> TokenReconciliation {
+    let ref_deposit = TokenSmallestUnits::from_u128(ref_deposit);
+    let wallet_balance = TokenSmallestUnits::from_u128(wallet_balance);
+    let actual = ref_deposit.as_bigdecimal().clone();
+    let difference = &actual - &expected;
+
+    let scale = expected.abs().max(actual);
+    let tolerance = scale * BigDecimal::from(tolerance_bps) / BigDecimal::from(10_000);
+    let discrepancy = !difference.is_zero() && difference.abs() > tolerance;
+
+    TokenReconciliation {
+        token,
+        decimals,
+        expected,
+        ref_deposit,
+        wallet_balance,
+        difference,
+        discrepancy,
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -1,25 +1,33 @@
 #![deny(warnings)]
 
 pub mod execution;
+pub mod gas;
 pub mod harvest;
+pub mod holdings_reconcile;
+pub mod lot;
 pub mod market_data;
+pub mod pnl;
 pub mod predict;
 pub mod prediction_accuracy;
+pub mod reconcile;
 pub mod recorder;
 pub mod slippage;
 pub mod snapshot;
 pub mod strategy;
 pub mod swap;
+#[cfg(any(test, feature = "synthetic-market"))]
+pub mod synthetic_market;
+pub mod tax_lot;
 pub mod token_cache;
 pub mod valuation;
 
 type Result<T> = anyhow::Result<T>;
 
+use anyhow::Context;
 use bigdecimal::BigDecimal;
 use blockchain::jsonrpc;
 use blockchain::ref_finance;
 use blockchain::ref_finance::token_account::WNEAR_TOKEN;
-use chrono::Utc as TZ;
 use common::config::{ConfigAccess, ConfigResolver};
 use common::types::NearAmount;
 use common::types::TokenAmount;
@@ -27,70 +35,100 @@ use common::types::TokenInAccount;
 use common::types::TokenOutAccount;
 use logging::*;
 use persistence::token_rate::TokenRate;
+use scheduler::{CatchUpPolicy, Job, Scheduler, parse_schedule};
 use std::collections::BTreeMap;
-use std::future::Future;
 use std::sync::Arc;
+use tracing::Instrument;
 
-pub async fn run(cfg: ConfigResolver) {
+/// record_rates のデフォルト cron スケジュール
+const RECORD_RATES_DEFAULT_CRON: &str = "0 */15 * * * *";
+
+/// auto_trade のデフォルト cron スケジュール
+const TRADE_DEFAULT_CRON: &str = "0 0 0 * * *";
+
+/// holdings_reconcile のデフォルト cron スケジュール
+const HOLDINGS_RECONCILE_DEFAULT_CRON: &str = "0 30 * * * *";
+
+/// トレードサイクル実行中に保持するロック
+///
+/// 取引途中の残高を突合しないよう、`holdings_reconcile` はこれを取ってから実行する。
+static CYCLE_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// トークンキャッシュを初期化し、trade の定期ジョブをスケジューラに登録する
+///
+/// - `record_rates`: 取りこぼした回は追いかけない（レートは実行時点の値しか記録できないため）
+/// - `auto_trade`: 停止中に予定時刻を過ぎていたら起動時に最新の1回だけ実行する
+/// - `holdings_reconcile`: on-chain 保有量と DB の突合。取りこぼした回は追いかけない
+///
+/// `auto_trade` と `holdings_reconcile` は、結果を確認できなかったスワップを最初に journal から
+/// 回収する（古い保有量を前提に取引・突合しないように）。
+pub async fn register_jobs(scheduler: &Scheduler, cfg: ConfigResolver) -> Result<()> {
     // DB からトークン decimals キャッシュを初期化
     if let Err(e) = token_cache::load_from_db().await {
-        let log = DEFAULT.new(o!("function" => "run"));
+        let log = DEFAULT.new(o!("function" => "register_jobs"));
         error!(log, "failed to load token decimals cache from DB"; "error" => ?e);
     }
 
-    tokio::spawn(run_record_rates(cfg));
-    tokio::spawn(run_trade(cfg));
-}
+    scheduler.register(Job::new(
+        "record_rates",
+        parse_schedule(&cfg.record_rates_cron_schedule(), RECORD_RATES_DEFAULT_CRON),
+        move || async move { record_rates(&cfg).await },
+    ))?;
 
-/// cron スケジュール文字列をパースし、失敗時は default にフォールバック
-fn parse_cron_schedule(cron_expr: &str, default: &str) -> cron::Schedule {
-    let log = DEFAULT.new(o!("function" => "parse_cron_schedule"));
+    let log = DEFAULT.new(o!("function" => "register_jobs"));
+    info!(log, "initializing auto trade cron job");
+    scheduler.register(
+        Job::new(
+            "auto_trade",
+            parse_schedule(&cfg.trade_cron_schedule(), TRADE_DEFAULT_CRON),
+            move || async move { run_trade(&cfg).await },
+        )
+        .with_catch_up(CatchUpPolicy::Latest),
+    )?;
+
+    scheduler.register(Job::new(
+        "holdings_reconcile",
+        parse_schedule(
+            &cfg.holdings_reconcile_cron_schedule(),
+            HOLDINGS_RECONCILE_DEFAULT_CRON,
+        ),
+        move || async move { holdings_reconcile::run(&cfg).await },
+    ))?;
 
-    match cron_expr.parse() {
-        Ok(s) => {
-            info!(log, "cron schedule configured"; "schedule" => cron_expr);
-            s
-        }
-        Err(e) => {
-            error!(log, "failed to parse cron schedule, using default";
-                   "error" => ?e, "schedule" => cron_expr, "default" => default);
-            default
-                .parse()
-                .expect("hardcoded default cron schedule must be valid")
-        }
-    }
+    Ok(())
 }
 
-async fn run_record_rates(cfg: ConfigResolver) {
-    const DEFAULT_CRON: &str = "0 */15 * * * *";
-    let schedule = parse_cron_schedule(&cfg.record_rates_cron_schedule(), DEFAULT_CRON);
-    cronjob(schedule, || record_rates(&cfg), "record_rates", &cfg).await;
-}
+/// 予測フェーズと取引フェーズを1サイクル実行する
+///
+/// サイクル全体を `trade.cycle` span で囲む。評価期間 id と `TradeRecorder` の batch id は
+/// 判明した時点でこの span に記録される（`strategy::start` / `execution` 参照）。
+async fn run_trade(cfg: &impl ConfigAccess) -> Result<()> {
+    let span = tracing::info_span!(
+        "trade.cycle",
+        trade.period_id = tracing::field::Empty,
+        trade.batch_id = tracing::field::Empty,
+    );
+    async {
+        let _cycle = CYCLE_LOCK.lock().await;
 
-async fn run_trade(cfg: ConfigResolver) {
-    const DEFAULT_CRON: &str = "0 0 0 * * *";
-    let log = DEFAULT.new(o!("function" => "run_trade"));
-    info!(log, "initializing auto trade cron job");
+        // 前回までに結果を確認できなかったスワップを確定させてから保有量を読む
+        let client = blockchain::jsonrpc::new_client();
+        reconcile::reconcile_pending_transactions(&client)
+            .await
+            .context("failed to reconcile pending transactions, skipping trade cycle")?;
 
-    let schedule = parse_cron_schedule(&cfg.trade_cron_schedule(), DEFAULT_CRON);
-    cronjob(
-        schedule,
-        || async {
-            // 予測フェーズ（失敗 → 今回のサイクルをスキップ）
-            if let Err(e) = run_predictions(&cfg).await {
-                let log = DEFAULT.new(o!("function" => "run_trade"));
-                error!(log, "prediction phase failed, skipping trade cycle"; "error" => %e);
-                return Ok(());
-            }
+        // 予測フェーズ（失敗 → 今回のサイクルをスキップし、ジョブの失敗として記録）
+        run_predictions(cfg)
+            .instrument(tracing::info_span!("trade.prediction"))
+            .await
+            .context("prediction phase failed, skipping trade cycle")?;
 
-            let client = blockchain::jsonrpc::new_client();
-            let wallet = blockchain::wallet::new_wallet();
-            strategy::start(&client, &wallet, chrono::Utc::now(), &cfg).await
-        },
-        "auto_trade",
-        &cfg,
-    )
-    .await;
+        let wallet = blockchain::wallet::new_wallet();
+        strategy::start(&client, &wallet, chrono::Utc::now(), cfg).await?;
+        Ok(())
+    }
+    .instrument(span)
+    .await
 }
 
 /// 全対象トークンの価格予測を実行して prediction_records に保存する（本番 cron 用）
@@ -203,89 +241,6 @@ pub async fn run_prediction_cycle(
     Ok(prediction_entries.len())
 }
 
-async fn cronjob<F, Fut>(schedule: cron::Schedule, func: F, name: &str, cfg: &impl ConfigAccess)
-where
-    F: Fn() -> Fut,
-    Fut: Future<Output = Result<()>>,
-{
-    let log = DEFAULT.new(o!("function" => "cronjob", "name" => name.to_owned()));
-    info!(log, "starting cron job");
-
-    for (iteration, next) in schedule.upcoming(TZ).enumerate() {
-        let now = TZ::now();
-        debug!(log, "cron iteration"; "iteration" => iteration, "next" => %next, "now" => %now);
-
-        // 実行時刻を過ぎている場合はスキップ
-        if next <= now {
-            warn!(log, "execution time already passed, skipping to next iteration";
-                "next" => %next,
-                "now" => %now,
-                "iteration" => iteration
-            );
-            continue;
-        }
-
-        match (next - now).to_std() {
-            Ok(wait) => {
-                debug!(log, "waiting for next execution";
-                    "wait_seconds" => wait.as_secs(),
-                    "next_time" => %next
-                );
-
-                // 長時間sleepを避けるため、1分間隔でチェック
-                loop {
-                    let now = TZ::now();
-                    if now >= next {
-                        break;
-                    }
-
-                    let remaining = match (next - now).to_std() {
-                        Ok(d) => d,
-                        Err(_) => break, // 時刻が過去になった場合は即座に実行
-                    };
-
-                    // 最大sleep秒数（設定可能、デフォルト60秒）
-                    let max_sleep = cfg.cron_max_sleep_seconds();
-                    let sleep_duration = remaining.min(std::time::Duration::from_secs(max_sleep));
-
-                    // 長時間待機の場合は定期的にログを出力
-                    let log_threshold = cfg.cron_log_threshold_seconds();
-                    if remaining.as_secs() > log_threshold {
-                        debug!(log, "still waiting for next execution";
-                            "remaining_seconds" => remaining.as_secs(),
-                            "next_time" => %next
-                        );
-                    }
-
-                    tokio::time::sleep(sleep_duration).await;
-                }
-
-                // タスク実行前に DB から設定をリロード
-                let instance_id = &common::config::startup::get().instance_id;
-                persistence::config_store::reload_to_config(instance_id)
-                    .await
-                    .ok();
-
-                let exec_log = DEFAULT.new(o!("function" => "run", "name" => name.to_owned()));
-                info!(exec_log, "executing scheduled task");
-
-                match func().await {
-                    Ok(_) => info!(exec_log, "success"),
-                    Err(err) => error!(exec_log, "failure"; "error" => ?err),
-                }
-            }
-            Err(e) => {
-                error!(log, "failed to calculate wait duration";
-                    "error" => ?e,
-                    "next" => %next,
-                    "now" => %now,
-                    "iteration" => iteration
-                );
-            }
-        }
-    }
-}
-
 fn get_quote_token() -> TokenInAccount {
     WNEAR_TOKEN.to_in()
 }
@@ -322,9 +277,9 @@ async fn record_rates(cfg: &impl ConfigAccess) -> Result<()> {
     let graph = ref_finance::path::graph::TokenGraph::new(Arc::clone(&pools));
     let goals = graph.update_graph(quote_token)?;
     trace!(log, "found targets"; "goals" => %goals.len());
-    // NearAmount → YoctoAmount → u128 に変換して list_values_with_path に渡す
+    // NearAmount → YoctoAmount → u128 に変換し、実際の量で経路を選んで評価する
     let initial_yocto = initial_value.to_yocto().to_u128();
-    let values = graph.list_values_with_path(initial_yocto, quote_token, &goals)?;
+    let values = graph.list_values_with_path_for_amount(initial_yocto, quote_token, &goals)?;
 
     let log = log.new(o!(
         "num_values" => values.len().to_string(),
@@ -402,24 +357,6 @@ mod tests {
     use super::*;
     use serial_test::serial;
 
-    #[test]
-    fn test_parse_cron_schedule_valid() {
-        let schedule = parse_cron_schedule("0 */15 * * * *", "0 0 0 * * *");
-        let mut upcoming = schedule.upcoming(TZ);
-        let first = upcoming.next().unwrap();
-        let second = upcoming.next().unwrap();
-        assert_eq!((second - first).num_minutes(), 15);
-    }
-
-    #[test]
-    fn test_parse_cron_schedule_fallback_on_invalid() {
-        let schedule = parse_cron_schedule("invalid cron", "0 */15 * * * *");
-        let mut upcoming = schedule.upcoming(TZ);
-        let first = upcoming.next().unwrap();
-        let second = upcoming.next().unwrap();
-        assert_eq!((second - first).num_minutes(), 15); // デフォルトにフォールバック
-    }
-
     #[test]
     #[serial]
     fn test_get_initial_value_default() {
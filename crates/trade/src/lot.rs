@@ -0,0 +1,187 @@
+//! トークン別の取得ロット
+//!
+//! 取得原価の計算（`pnl` の平均取得原価、`tax_lot` の税務ロット、シミュレーションの損益）が
+//! 共通で使うロットの積み上げと払い出し。取得原価の単位は呼び出し側が決める。
+
+use bigdecimal::{BigDecimal, Zero};
+use chrono::NaiveDateTime;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+
+/// ロットの払い出し方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LotMethod {
+    /// 古いロットから払い出す
+    #[default]
+    Fifo,
+    /// トークンごとに 1 つのロットへまとめ、平均取得原価で払い出す
+    AverageCost,
+}
+
+impl LotMethod {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Fifo => "fifo",
+            Self::AverageCost => "average_cost",
+        }
+    }
+}
+
+impl fmt::Display for LotMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LotMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s {
+            "fifo" => Ok(Self::Fifo),
+            "average_cost" | "average-cost" => Ok(Self::AverageCost),
+            other => Err(anyhow::anyhow!(
+                "invalid lot method: {} (expected fifo or average_cost)",
+                other
+            )),
+        }
+    }
+}
+
+/// 未払い出しの取得ロット
+///
+/// 取得原価が分からない値は `None`（履歴で取得していない数量など）。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lot {
+    /// 平均取得原価でまとめたロットと、履歴に無い数量は `None`
+    pub acquired_at: Option<NaiveDateTime>,
+    pub quantity: BigDecimal,
+    pub cost: Option<BigDecimal>,
+    /// 法定通貨建ての取得原価
+    pub cost_fiat: Option<BigDecimal>,
+}
+
+fn add_opt(a: Option<&BigDecimal>, b: Option<&BigDecimal>) -> Option<BigDecimal> {
+    Some(a? + b?)
+}
+
+fn sub_opt(a: Option<&BigDecimal>, b: Option<&BigDecimal>) -> Option<BigDecimal> {
+    Some(a? - b?)
+}
+
+impl Lot {
+    pub fn new(
+        acquired_at: Option<NaiveDateTime>,
+        quantity: BigDecimal,
+        cost: Option<BigDecimal>,
+    ) -> Self {
+        Self {
+            acquired_at,
+            quantity,
+            cost,
+            cost_fiat: None,
+        }
+    }
+
+    /// 取得原価の分からないロット
+    pub fn untracked(quantity: BigDecimal) -> Self {
+        Self::new(None, quantity, None)
+    }
+
+    /// `quantity` 分を按分した取得原価で切り出し、残りを `self` に残す
+    pub fn split_off(&mut self, quantity: &BigDecimal) -> Lot {
+        let share = |cost: &BigDecimal| cost * quantity / &self.quantity;
+        let part = Lot {
+            acquired_at: self.acquired_at,
+            quantity: quantity.clone(),
+            cost: self.cost.as_ref().map(share),
+            cost_fiat: self.cost_fiat.as_ref().map(share),
+        };
+        self.quantity -= quantity;
+        self.cost = sub_opt(self.cost.as_ref(), part.cost.as_ref());
+        self.cost_fiat = sub_opt(self.cost_fiat.as_ref(), part.cost_fiat.as_ref());
+        part
+    }
+}
+
+/// トークン（`K`）ごとの未払い出しロット
+#[derive(Debug, Clone, PartialEq)]
+pub struct LotBook<K> {
+    method: LotMethod,
+    lots: BTreeMap<K, VecDeque<Lot>>,
+}
+
+impl<K: Ord> LotBook<K> {
+    pub fn new(method: LotMethod) -> Self {
+        Self {
+            method,
+            lots: BTreeMap::new(),
+        }
+    }
+
+    pub fn method(&self) -> LotMethod {
+        self.method
+    }
+
+    /// ロットを積む（平均取得原価法では既存のロットへまとめる）
+    pub fn acquire(&mut self, token: K, lot: Lot) {
+        let lots = self.lots.entry(token).or_default();
+        match self.method {
+            LotMethod::Fifo => lots.push_back(lot),
+            LotMethod::AverageCost => {
+                if let Some(merged) = lots.front_mut() {
+                    merged.quantity += &lot.quantity;
+                    merged.cost = add_opt(merged.cost.as_ref(), lot.cost.as_ref());
+                    merged.cost_fiat = add_opt(merged.cost_fiat.as_ref(), lot.cost_fiat.as_ref());
+                } else {
+                    lots.push_back(Lot {
+                        acquired_at: None,
+                        ..lot
+                    });
+                }
+            }
+        }
+    }
+
+    /// `quantity` 分のロットを払い出す（足りない分は取得原価不明のロットにする）
+    pub fn take(&mut self, token: &K, quantity: &BigDecimal) -> Vec<Lot> {
+        let mut remaining = quantity.clone();
+        let mut taken = Vec::new();
+        if let Some(lots) = self.lots.get_mut(token) {
+            while remaining > BigDecimal::zero() {
+                let Some(front) = lots.front_mut() else {
+                    break;
+                };
+                if front.quantity <= remaining {
+                    remaining -= &front.quantity;
+                    taken.extend(lots.pop_front());
+                } else {
+                    taken.push(front.split_off(&remaining));
+                    remaining = BigDecimal::zero();
+                }
+            }
+        }
+        if remaining > BigDecimal::zero() {
+            taken.push(Lot::untracked(remaining));
+        }
+        taken
+    }
+
+    /// 未払い出しの数量と取得原価の合計（取得原価が分からないロットがあれば `None`）
+    pub fn position(&self, token: &K) -> (BigDecimal, Option<BigDecimal>) {
+        let lots = self.lots.get(token).into_iter().flatten();
+        lots.fold(
+            (BigDecimal::zero(), Some(BigDecimal::zero())),
+            |(quantity, cost), lot| {
+                (
+                    quantity + &lot.quantity,
+                    add_opt(cost.as_ref(), lot.cost.as_ref()),
+                )
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,93 @@
+use super::*;
+
+fn time(day: u32) -> NaiveDateTime {
+    chrono::NaiveDate::from_ymd_opt(2025, 1, day)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+fn lot(day: u32, quantity: u32, cost: u32) -> Lot {
+    Lot::new(
+        Some(time(day)),
+        BigDecimal::from(quantity),
+        Some(BigDecimal::from(cost)),
+    )
+}
+
+fn quantities(lots: &[Lot]) -> Vec<BigDecimal> {
+    lots.iter().map(|lot| lot.quantity.clone()).collect()
+}
+
+#[test]
+fn test_lot_method_round_trip() {
+    for method in [LotMethod::Fifo, LotMethod::AverageCost] {
+        assert_eq!(method.to_string().parse::<LotMethod>().unwrap(), method);
+    }
+    assert_eq!(
+        "average-cost".parse::<LotMethod>().unwrap(),
+        LotMethod::AverageCost
+    );
+    assert!("lifo".parse::<LotMethod>().is_err());
+}
+
+#[test]
+fn test_fifo_takes_oldest_lots_and_splits_the_last() {
+    let mut book = LotBook::new(LotMethod::Fifo);
+    book.acquire("a", lot(1, 10, 100));
+    book.acquire("a", lot(2, 10, 300));
+
+    let taken = book.take(&"a", &BigDecimal::from(15));
+    assert_eq!(
+        quantities(&taken),
+        [BigDecimal::from(10), BigDecimal::from(5)]
+    );
+    assert_eq!(taken[0].acquired_at, Some(time(1)));
+    assert_eq!(taken[1].acquired_at, Some(time(2)));
+    assert_eq!(taken[1].cost, Some(BigDecimal::from(150)));
+
+    // 2 つ目のロットの残り
+    assert_eq!(
+        book.position(&"a"),
+        (BigDecimal::from(5), Some(BigDecimal::from(150)))
+    );
+}
+
+#[test]
+fn test_average_cost_merges_lots() {
+    let mut book = LotBook::new(LotMethod::AverageCost);
+    book.acquire("a", lot(1, 10, 100));
+    book.acquire("a", lot(2, 10, 300));
+
+    let taken = book.take(&"a", &BigDecimal::from(5));
+    assert_eq!(taken.len(), 1);
+    assert_eq!(taken[0].acquired_at, None);
+    assert_eq!(taken[0].cost, Some(BigDecimal::from(100)));
+    assert_eq!(
+        book.position(&"a"),
+        (BigDecimal::from(15), Some(BigDecimal::from(300)))
+    );
+}
+
+#[test]
+fn test_shortfall_becomes_untracked_lot() {
+    let mut book = LotBook::new(LotMethod::Fifo);
+    book.acquire("a", lot(1, 10, 100));
+
+    let taken = book.take(&"a", &BigDecimal::from(25));
+    assert_eq!(
+        quantities(&taken),
+        [BigDecimal::from(10), BigDecimal::from(15)]
+    );
+    assert_eq!(taken[1], Lot::untracked(BigDecimal::from(15)));
+    assert_eq!(
+        book.position(&"a"),
+        (BigDecimal::zero(), Some(BigDecimal::zero()))
+    );
+
+    // 取得していないトークンは全量が取得原価不明
+    assert_eq!(
+        book.take(&"b", &BigDecimal::from(3)),
+        [Lot::untracked(BigDecimal::from(3))]
+    );
+}
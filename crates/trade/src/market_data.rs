@@ -344,7 +344,7 @@ where
 ///
 /// # Returns
 /// * `TokenAmount` - 総発行量（smallest_units + decimals）
-pub(crate) async fn get_token_total_supply<C>(
+pub async fn get_token_total_supply<C>(
     client: &C,
     token_id: &TokenAccount,
     decimals: u8,
@@ -0,0 +1,203 @@
+//! トークン別の取得原価と損益
+//!
+//! 評価期間の `trade_transactions` を古い順に積み上げ、平均取得原価法で
+//! トークンごとの取得原価・実現損益を求める（`simulate::portfolio_state` と同じ会計）。
+//! ロットの積み上げと払い出しは `lot::LotBook` を使う。
+//!
+//! - wrap.near での購入は支払った wrap.near を取得原価に加える
+//! - wrap.near への売却は受け取った wrap.near と売却分の取得原価の差を実現損益とする
+//! - トークン同士のスワップは売却分の取得原価を購入側へ移し、損益は実現しない
+//! - 受取量は約定実績（`actual_to_amount`）を優先し、無ければ送信時の推定出力を使う
+//! - wrap.near とネイティブ NEAR の間の移動（ハーベスト）は損益の対象外
+//!
+//! 期間の取引で取得していない数量（前期間の清算失敗の持ち越しなど）は取得原価が分からないため、
+//! その売却分は損益に含めない。
+
+use crate::Result;
+use crate::lot::{Lot, LotBook, LotMethod};
+use bigdecimal::{BigDecimal, Zero};
+use blockchain::ref_finance::token_account::{NEAR_TOKEN, WNEAR_TOKEN};
+use chrono::NaiveDateTime;
+use common::types::{ExchangeRate, TokenAccount, TokenAmount, TokenOutAccount, YoctoValue};
+use persistence::portfolio_holding::TokenPnl;
+use persistence::token_rate::TokenRate;
+use persistence::trade_transaction::TradeTransaction;
+use std::collections::{BTreeMap, HashMap};
+
+/// 取引から積み上げたトークン別の取得原価と実現損益（数量は最小単位、金額は yoctoNEAR）
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostBasisBook {
+    lots: LotBook<TokenAccount>,
+    /// 建玉を持ったことのあるトークンごとの実現損益
+    realized: BTreeMap<TokenAccount, BigDecimal>,
+}
+
+impl Default for CostBasisBook {
+    fn default() -> Self {
+        Self {
+            lots: LotBook::new(LotMethod::AverageCost),
+            realized: BTreeMap::new(),
+        }
+    }
+}
+
+fn is_near(token: &TokenAccount) -> bool {
+    token == &*WNEAR_TOKEN || token == &*NEAR_TOKEN
+}
+
+impl CostBasisBook {
+    /// 取引を古い順に積み上げる
+    pub fn from_transactions(transactions: &[TradeTransaction]) -> Self {
+        let mut book = Self::default();
+        for tx in transactions {
+            book.apply(tx);
+        }
+        book
+    }
+
+    /// 1 件の取引を反映する（トークン名が不正な取引は無視）
+    pub fn apply(&mut self, tx: &TradeTransaction) {
+        let (Ok(from), Ok(to)) = (
+            tx.from_token.parse::<TokenAccount>(),
+            tx.to_token.parse::<TokenAccount>(),
+        ) else {
+            return;
+        };
+        let sold = tx.from_amount.as_bigdecimal();
+        let received = tx
+            .actual_to_amount
+            .clone()
+            .unwrap_or_else(|| tx.to_amount.as_bigdecimal().clone());
+
+        match (is_near(&from), is_near(&to)) {
+            (true, true) => {}
+            (true, false) => self.acquire(to, tx.timestamp, received, sold.clone()),
+            (false, to_near) => {
+                let (cost, covered) = self.dispose(&from, sold);
+                if covered.is_zero() {
+                    return;
+                }
+                if to_near {
+                    let proceeds = received * &covered / sold;
+                    *self.realized.entry(from).or_default() += proceeds - cost;
+                } else {
+                    self.acquire(to, tx.timestamp, received * &covered / sold, cost);
+                }
+            }
+        }
+    }
+
+    fn acquire(
+        &mut self,
+        token: TokenAccount,
+        at: NaiveDateTime,
+        quantity: BigDecimal,
+        cost: BigDecimal,
+    ) {
+        self.realized.entry(token.clone()).or_default();
+        self.lots
+            .acquire(token, Lot::new(Some(at), quantity, Some(cost)));
+    }
+
+    /// `sold` のうち建玉で賄える数量を払い出し、（取り崩した取得原価, 賄えた数量）を返す
+    fn dispose(&mut self, token: &TokenAccount, sold: &BigDecimal) -> (BigDecimal, BigDecimal) {
+        let lots = self.lots.take(token, sold);
+        lots.iter()
+            .filter_map(|lot| lot.cost.as_ref().map(|cost| (cost, &lot.quantity)))
+            .fold(
+                (BigDecimal::zero(), BigDecimal::zero()),
+                |(cost, covered), (lot_cost, quantity)| (cost + lot_cost, covered + quantity),
+            )
+    }
+
+    /// 建玉（数量, 取得原価）
+    fn position(&self, token: &TokenAccount) -> (BigDecimal, BigDecimal) {
+        let (quantity, cost) = self.lots.position(token);
+        (quantity, cost.unwrap_or_default())
+    }
+
+    /// 建玉が残っているトークン
+    pub fn open_tokens(&self) -> Vec<TokenAccount> {
+        self.realized
+            .keys()
+            .filter(|token| self.position(token).0 > BigDecimal::zero())
+            .cloned()
+            .collect()
+    }
+
+    /// トークン別の実現損益（yoctoNEAR、整数に丸める）
+    pub fn realized(&self) -> BTreeMap<TokenAccount, BigDecimal> {
+        self.realized
+            .iter()
+            .map(|(token, realized)| (token.clone(), realized.round(0)))
+            .collect()
+    }
+
+    /// 現在の保有量とレートでトークン別の損益を求める
+    ///
+    /// 含み損益は建玉と実際の保有量の少ない方を対象にする（保有していなければ 0）。
+    pub fn token_pnl(
+        &self,
+        holdings: &BTreeMap<TokenAccount, TokenAmount>,
+        rates: &HashMap<TokenOutAccount, ExchangeRate>,
+    ) -> Vec<TokenPnl> {
+        self.realized
+            .iter()
+            .map(|(token, realized)| {
+                let (quantity, position_cost) = self.position(token);
+                let held = holdings.get(token);
+                let tracked = match held {
+                    Some(amount) => amount.smallest_units().min(&quantity).clone(),
+                    None => BigDecimal::zero(),
+                };
+                let cost = if tracked > BigDecimal::zero() {
+                    position_cost * &tracked / &quantity
+                } else {
+                    BigDecimal::zero()
+                };
+                let unrealized = match held {
+                    Some(amount) if tracked > BigDecimal::zero() => {
+                        rates.get(&token.to_out()).map(|rate| {
+                            let tracked = TokenAmount::from_smallest_units(
+                                tracked.clone(),
+                                amount.decimals(),
+                            );
+                            let value = (tracked / rate).to_yocto();
+                            value.as_bigdecimal() - &cost
+                        })
+                    }
+                    _ => Some(BigDecimal::zero()),
+                };
+                TokenPnl {
+                    token: token.clone(),
+                    cost_basis: YoctoValue::from_yocto(cost.round(0)),
+                    realized_pnl: realized.round(0),
+                    unrealized_pnl: unrealized.map(|u| u.round(0)),
+                }
+            })
+            .collect()
+    }
+}
+
+/// 評価期間の取引と `at` 時点のレートから、`holdings` を保有している時点のトークン別損益を求める
+pub async fn period_token_pnl(
+    period_id: &str,
+    holdings: &BTreeMap<TokenAccount, TokenAmount>,
+    at: NaiveDateTime,
+) -> Result<Vec<TokenPnl>> {
+    let transactions =
+        TradeTransaction::find_by_evaluation_period_async(period_id.to_string()).await?;
+    let book = CostBasisBook::from_transactions(&transactions);
+
+    let tokens: Vec<TokenOutAccount> = book
+        .open_tokens()
+        .iter()
+        .filter(|token| holdings.contains_key(*token))
+        .map(TokenAccount::to_out)
+        .collect();
+    let rates = TokenRate::get_spot_rates_at_time(&tokens, &WNEAR_TOKEN.to_in(), at).await?;
+    Ok(book.token_pnl(holdings, &rates))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,157 @@
+use super::*;
+use common::types::TokenSmallestUnits;
+
+fn trade(
+    from: &str,
+    from_amount: u128,
+    to: &str,
+    to_amount: u128,
+    actual: Option<u128>,
+) -> TradeTransaction {
+    TradeTransaction {
+        tx_id: "tx".to_string(),
+        trade_batch_id: "batch".to_string(),
+        from_token: from.to_string(),
+        from_amount: TokenSmallestUnits::from_u128(from_amount),
+        to_token: to.to_string(),
+        to_amount: TokenSmallestUnits::from_u128(to_amount),
+        timestamp: chrono::Utc::now().naive_utc(),
+        evaluation_period_id: "period".to_string(),
+        actual_to_amount: actual.map(BigDecimal::from),
+        gas_burnt: None,
+        gas_cost: None,
+    }
+}
+
+fn wnear() -> String {
+    WNEAR_TOKEN.to_string()
+}
+
+fn account(name: &str) -> TokenAccount {
+    name.parse().unwrap()
+}
+
+fn holdings(entries: &[(&str, u128)]) -> BTreeMap<TokenAccount, TokenAmount> {
+    entries
+        .iter()
+        .map(|(token, amount)| {
+            (
+                account(token),
+                TokenAmount::from_smallest_units(BigDecimal::from(*amount), 6),
+            )
+        })
+        .collect()
+}
+
+/// 1 NEAR（10^24 yocto）あたり `per_near` 最小単位のレート
+fn rate(per_near: u128) -> ExchangeRate {
+    ExchangeRate::from_raw_rate(BigDecimal::from(per_near), 6)
+}
+
+fn pnl_of<'a>(pnl: &'a [TokenPnl], token: &str) -> &'a TokenPnl {
+    pnl.iter().find(|p| p.token == account(token)).unwrap()
+}
+
+#[test]
+fn test_sell_realizes_against_average_cost() {
+    let book = CostBasisBook::from_transactions(&[
+        trade(&wnear(), 100, "a.near", 10, Some(10)),
+        trade(&wnear(), 200, "a.near", 10, Some(10)),
+        // 平均原価 15/単位で 10 単位を 180 で売却 → +30
+        trade("a.near", 10, &wnear(), 170, Some(180)),
+    ]);
+    assert_eq!(book.realized()[&account("a.near")], BigDecimal::from(30));
+    assert!(book.open_tokens().is_empty());
+
+    let pnl = book.token_pnl(&holdings(&[]), &HashMap::new());
+    // 保有していなければ含み損益は 0、残りの取得原価も 0
+    assert_eq!(
+        pnl_of(&pnl, "a.near").unrealized_pnl,
+        Some(BigDecimal::zero())
+    );
+    assert!(pnl_of(&pnl, "a.near").cost_basis.as_bigdecimal().is_zero());
+}
+
+#[test]
+fn test_actual_amount_preferred_over_estimate() {
+    let book = CostBasisBook::from_transactions(&[
+        trade(&wnear(), 100, "a.near", 10, Some(8)),
+        trade("a.near", 8, &wnear(), 150, None),
+    ]);
+    // 全量（8）を推定出力 150 で売却
+    assert_eq!(book.realized()[&account("a.near")], BigDecimal::from(50));
+}
+
+#[test]
+fn test_token_to_token_swap_transfers_cost() {
+    let book = CostBasisBook::from_transactions(&[
+        trade(&wnear(), 100, "a.near", 10, Some(10)),
+        trade("a.near", 5, "b.near", 20, Some(20)),
+        trade("b.near", 20, &wnear(), 80, Some(80)),
+    ]);
+    let realized = book.realized();
+    assert!(realized[&account("a.near")].is_zero());
+    // 移された原価 50 に対して 80 で売却
+    assert_eq!(realized[&account("b.near")], BigDecimal::from(30));
+}
+
+#[test]
+fn test_untracked_quantity_is_excluded() {
+    let book = CostBasisBook::from_transactions(&[
+        trade(&wnear(), 100, "a.near", 10, Some(10)),
+        // 20 単位のうち期間内に取得した 10 単位分（受取の半分）だけを損益に含める
+        trade("a.near", 20, &wnear(), 300, Some(300)),
+        // 取得していないトークンの売却は無視
+        trade("c.near", 5, &wnear(), 50, Some(50)),
+    ]);
+    let realized = book.realized();
+    assert_eq!(realized[&account("a.near")], BigDecimal::from(50));
+    assert!(!realized.contains_key(&account("c.near")));
+}
+
+#[test]
+fn test_harvest_and_invalid_tokens_are_ignored() {
+    let book = CostBasisBook::from_transactions(&[
+        trade(&wnear(), 1_000, "near", 1_000, Some(1_000)),
+        trade(&wnear(), 100, "Invalid Token", 10, Some(10)),
+    ]);
+    assert_eq!(book, CostBasisBook::default());
+}
+
+#[test]
+fn test_unrealized_uses_rate_for_tracked_quantity() {
+    let book = CostBasisBook::from_transactions(&[
+        trade(
+            &wnear(),
+            10u128.pow(24),
+            "a.near",
+            1_000_000,
+            Some(1_000_000),
+        ),
+        trade(
+            "a.near",
+            500_000,
+            &wnear(),
+            6 * 10u128.pow(23),
+            Some(6 * 10u128.pow(23)),
+        ),
+    ]);
+    // 残り 500_000 を 1 NEAR = 400_000 のレートで評価 → 1.25 NEAR、原価 0.5 NEAR
+    let mut rates = HashMap::new();
+    rates.insert(account("a.near").to_out(), rate(400_000));
+    let pnl = book.token_pnl(&holdings(&[("a.near", 500_000)]), &rates);
+    let a = pnl_of(&pnl, "a.near");
+    assert_eq!(a.realized_pnl, BigDecimal::from(10u128.pow(23)));
+    assert_eq!(
+        a.cost_basis.as_bigdecimal(),
+        &BigDecimal::from(5 * 10u128.pow(23))
+    );
+    assert_eq!(
+        a.unrealized_pnl,
+        Some(BigDecimal::from(75 * 10u128.pow(22)))
+    );
+
+    // レートが無ければ含み損益は不明
+    let pnl = book.token_pnl(&holdings(&[("a.near", 500_000)]), &HashMap::new());
+    assert_eq!(pnl_of(&pnl, "a.near").unrealized_pnl, None);
+}
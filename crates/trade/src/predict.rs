@@ -125,11 +125,10 @@ impl PredictionService {
         );
 
         // ライブラリを直接呼び出し
-        let chronos_response = self
-            .predictor
-            .predict_price(data, forecast_until)
-            .await
-            .context("Failed to execute prediction")?;
+        let started = std::time::Instant::now();
+        let chronos_response = self.predictor.predict_price(data, forecast_until).await;
+        metrics::prediction::record_prediction(started.elapsed(), chronos_response.is_ok());
+        let chronos_response = chronos_response.context("Failed to execute prediction")?;
 
         debug!(log, "Prediction completed";
             "model" => &chronos_response.model_name,
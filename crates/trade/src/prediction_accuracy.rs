@@ -267,6 +267,7 @@ pub async fn evaluate_predictions_as_of(
             continue;
         }
 
+        metrics::prediction::record_mape(mape);
         evaluated_count += 1;
     }
 
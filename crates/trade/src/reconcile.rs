@@ -0,0 +1,174 @@
+//! pending transaction journal の回収
+//!
+//! `swap::execute_direct_swap` は送信前に intent、送信直後に tx hash を journal
+//! (`pending_transactions`) に記録する。プロセスが結果の確認前に落ちたり、送信の応答が
+//! 返らなかったりすると journal が未解決のまま残るため、トレードサイクルと holdings 突合の
+//! 開始時（`CYCLE_LOCK` を取った後）にここで on-chain の結果を確認して確定させる。
+
+use crate::Result;
+use bigdecimal::BigDecimal;
+use blockchain::jsonrpc::TxInfo;
+use chrono::{NaiveDateTime, TimeDelta};
+use logging::*;
+use near_jsonrpc_primitives::types::transactions::RpcTransactionResponse;
+use near_primitives::hash::CryptoHash;
+use near_primitives::views::{
+    FinalExecutionOutcomeViewEnum, FinalExecutionStatus, TxExecutionStatus,
+};
+use near_sdk::AccountId;
+use persistence::pending_transaction::{PendingTransaction, PendingTransactionStatus};
+
+/// RPC が tx を知らない場合に、送信からこの時間が経過していれば破棄されたと見なす
+///
+/// ブロードキャスト直後に再起動した場合、tx がまだチェーンに取り込まれていないことがある。
+const UNKNOWN_TX_GRACE: TimeDelta = TimeDelta::minutes(10);
+
+/// 回収結果の件数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReconcileSummary {
+    pub completed: usize,
+    pub voided: usize,
+    /// 結果が確定せず、次回の回収に持ち越したもの
+    pub unresolved: usize,
+}
+
+/// 1 エントリの確認結果
+#[derive(Debug, PartialEq, Eq)]
+enum Resolution {
+    /// 成功が確定（実際の出力量、取得できなければ `None`）
+    Complete(Option<u128>),
+    /// 取引は成立していない
+    Void(String),
+    /// まだ判断できない
+    Keep(String),
+}
+
+/// 未解決の journal エントリを on-chain の結果で確定させる
+pub async fn reconcile_pending_transactions<C>(client: &C) -> Result<ReconcileSummary>
+where
+    C: TxInfo,
+{
+    let log = DEFAULT.new(o!("function" => "reconcile_pending_transactions"));
+    let entries = PendingTransaction::list_unresolved_async().await?;
+    if entries.is_empty() {
+        trace!(log, "no pending transactions");
+        return Ok(ReconcileSummary::default());
+    }
+    info!(log, "reconciling pending transactions"; "count" => entries.len());
+
+    let now = chrono::Utc::now().naive_utc();
+    let mut summary = ReconcileSummary::default();
+    for entry in entries {
+        let entry_log = log.new(o!(
+            "pending_id" => entry.id,
+            "batch_id" => entry.trade_batch_id.clone(),
+            "from" => entry.from_token.clone(),
+            "to" => entry.to_token.clone(),
+        ));
+
+        let resolution = resolve(client, &entry, now).await;
+        match resolution {
+            Resolution::Complete(actual) => {
+                let transaction = entry.to_trade_transaction(actual.map(BigDecimal::from))?;
+                PendingTransaction::complete_async(entry.id, transaction).await?;
+                info!(entry_log, "recovered swap recorded"; "actual_output" => ?actual);
+                summary.completed += 1;
+            }
+            Resolution::Void(reason) => {
+                warn!(entry_log, "voiding pending transaction"; "reason" => %reason);
+                PendingTransaction::void_async(entry.id, reason).await?;
+                summary.voided += 1;
+            }
+            Resolution::Keep(reason) => {
+                warn!(entry_log, "pending transaction left unresolved"; "reason" => %reason);
+                summary.unresolved += 1;
+            }
+        }
+    }
+
+    info!(log, "finished reconciling pending transactions";
+        "completed" => summary.completed,
+        "voided" => summary.voided,
+        "unresolved" => summary.unresolved,
+    );
+    Ok(summary)
+}
+
+async fn resolve<C: TxInfo>(
+    client: &C,
+    entry: &PendingTransaction,
+    now: NaiveDateTime,
+) -> Resolution {
+    let status = match entry.status() {
+        Ok(status) => status,
+        Err(e) => return Resolution::Keep(e.to_string()),
+    };
+    let tx_hash = match (status, entry.tx_hash.as_deref()) {
+        (PendingTransactionStatus::Sent, Some(hash)) => hash,
+        // ブロードキャスト結果を記録する前に落ちたもの。tx hash が無いため結果を追跡できない。
+        // 送信されていた可能性はあるが、保有量は次サイクルで on-chain から取得し直される。
+        _ => {
+            return Resolution::Void(
+                "no tx hash recorded; process exited before the broadcast result".to_string(),
+            );
+        }
+    };
+
+    let sender: AccountId = match entry.sender_account.parse() {
+        Ok(sender) => sender,
+        Err(e) => return Resolution::Void(format!("invalid sender account: {e}")),
+    };
+    let tx_hash: CryptoHash = match tx_hash.parse() {
+        Ok(hash) => hash,
+        Err(e) => return Resolution::Void(format!("invalid tx hash: {e}")),
+    };
+
+    let result = client
+        .wait_tx_result(&sender, &tx_hash, TxExecutionStatus::Final)
+        .await;
+    classify(result, now - entry.created_at)
+}
+
+/// `wait_tx_result` の結果から journal エントリの扱いを決める
+fn classify(result: Result<RpcTransactionResponse>, age: TimeDelta) -> Resolution {
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            let err_msg = format!("{e:?}");
+            if err_msg.contains("doesn't exist") || err_msg.contains("UNKNOWN_TRANSACTION") {
+                if age >= UNKNOWN_TX_GRACE {
+                    return Resolution::Void("transaction not found on chain".to_string());
+                }
+                return Resolution::Keep("transaction not yet known to RPC".to_string());
+            }
+            return Resolution::Keep(format!("failed to query transaction: {e}"));
+        }
+    };
+
+    if !matches!(response.final_execution_status, TxExecutionStatus::Final) {
+        return Resolution::Keep(format!(
+            "transaction not finalized: {:?}",
+            response.final_execution_status
+        ));
+    }
+    let Some(outcome) = response.final_execution_outcome else {
+        return Resolution::Keep("no execution outcome".to_string());
+    };
+    let view = match outcome {
+        FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(view) => view,
+        FinalExecutionOutcomeViewEnum::FinalExecutionOutcomeWithReceipt(view) => view.final_outcome,
+    };
+
+    match &view.status {
+        FinalExecutionStatus::SuccessValue(_) => {
+            Resolution::Complete(blockchain::ref_finance::swap::extract_actual_output(&view).ok())
+        }
+        FinalExecutionStatus::Failure(err) => Resolution::Void(format!("{err:?}")),
+        FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => {
+            Resolution::Keep(format!("transaction still pending: {:?}", view.status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
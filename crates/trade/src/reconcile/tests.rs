@@ -0,0 +1,95 @@
+use super::*;
+use near_primitives::errors::{InvalidTxError, TxExecutionError};
+
+fn response(status: FinalExecutionStatus, wait: TxExecutionStatus) -> RpcTransactionResponse {
+    let mut view = blockchain::mock::dummy_final_outcome(vec![]);
+    view.status = status;
+    RpcTransactionResponse {
+        final_execution_outcome: Some(FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(view)),
+        final_execution_status: wait,
+    }
+}
+
+#[test]
+fn test_classify_success_with_output() {
+    let res = response(
+        FinalExecutionStatus::SuccessValue(b"\"12345\"".to_vec()),
+        TxExecutionStatus::Final,
+    );
+    assert_eq!(
+        classify(Ok(res), TimeDelta::zero()),
+        Resolution::Complete(Some(12345))
+    );
+}
+
+#[test]
+fn test_classify_success_without_parsable_output() {
+    // 成功は確定しているので、出力量が読めなくても記録する
+    let res = response(
+        FinalExecutionStatus::SuccessValue(b"not json".to_vec()),
+        TxExecutionStatus::Final,
+    );
+    assert_eq!(
+        classify(Ok(res), TimeDelta::zero()),
+        Resolution::Complete(None)
+    );
+}
+
+#[test]
+fn test_classify_failure_is_voided() {
+    let res = response(
+        FinalExecutionStatus::Failure(TxExecutionError::InvalidTxError(InvalidTxError::Expired)),
+        TxExecutionStatus::Final,
+    );
+    assert!(matches!(
+        classify(Ok(res), TimeDelta::zero()),
+        Resolution::Void(_)
+    ));
+}
+
+#[test]
+fn test_classify_not_final_is_kept() {
+    let res = response(
+        FinalExecutionStatus::SuccessValue(b"\"1\"".to_vec()),
+        TxExecutionStatus::Executed,
+    );
+    assert!(matches!(
+        classify(Ok(res), TimeDelta::zero()),
+        Resolution::Keep(_)
+    ));
+
+    let res = RpcTransactionResponse {
+        final_execution_outcome: None,
+        final_execution_status: TxExecutionStatus::Final,
+    };
+    assert!(matches!(
+        classify(Ok(res), TimeDelta::zero()),
+        Resolution::Keep(_)
+    ));
+}
+
+#[test]
+fn test_classify_unknown_transaction_depends_on_age() {
+    let unknown = || Err(anyhow::anyhow!("UNKNOWN_TRANSACTION"));
+
+    assert!(matches!(
+        classify(unknown(), TimeDelta::minutes(1)),
+        Resolution::Keep(_)
+    ));
+    assert!(matches!(
+        classify(unknown(), UNKNOWN_TX_GRACE),
+        Resolution::Void(_)
+    ));
+}
+
+#[test]
+fn test_classify_rpc_error_is_kept() {
+    // RPC 障害では結果が分からないため、古いエントリでも破棄しない
+    assert!(matches!(
+        classify(
+            Err(anyhow::anyhow!("connection refused")),
+            TimeDelta::days(1)
+        ),
+        Resolution::Keep(_)
+    ));
+}
@@ -1,11 +1,13 @@
 use anyhow::{Context, Result};
 use bigdecimal::BigDecimal;
 use common::types::TokenAmount;
-use common::types::{TokenInAccount, TokenOutAccount};
+use common::types::{TokenInAccount, TokenOutAccount, TokenSmallestUnits};
 use logging::*;
+use near_sdk::AccountId;
 use num_traits::Zero;
 use uuid::Uuid;
 
+use persistence::pending_transaction::{NewPendingTransaction, PendingTransaction};
 use persistence::trade_transaction::TradeTransaction;
 
 pub struct TradeRecorder {
@@ -50,25 +52,7 @@ impl TradeRecorder {
             actual_to_amount.map(|a| a.into_smallest_units().into());
 
         if let Some(ref actual_bd) = actual_to_smallest {
-            let estimated_bd = to_smallest.as_bigdecimal();
-            if estimated_bd.is_zero() {
-                warn!(log, "skipping slippage calculation: estimated amount is zero";
-                    "actual" => %actual_bd,
-                    "to_token" => %to_token
-                );
-            } else {
-                // diff_pct > 0: actual > estimated (有利な約定)
-                // diff_pct < 0: actual < estimated (不利な約定 = スリッページ損)
-                let diff = actual_bd - estimated_bd;
-                let diff_pct = (&diff / estimated_bd * BigDecimal::from(100))
-                    .with_scale_round(4, bigdecimal::RoundingMode::HalfUp);
-                debug!(log, "swap slippage";
-                    "estimated" => %estimated_bd,
-                    "actual" => %actual_bd,
-                    "diff_pct" => %diff_pct,
-                    "to_token" => %to_token
-                );
-            }
+            log_slippage(&log, &to_smallest, actual_bd, to_token);
         }
 
         debug!(log, "recording trade details";
@@ -89,6 +73,8 @@ impl TradeRecorder {
             timestamp: chrono::Utc::now().naive_utc(),
             evaluation_period_id: self.evaluation_period_id.clone(),
             actual_to_amount: actual_to_smallest,
+            gas_burnt: None,
+            gas_cost: None,
         };
 
         let result = transaction
@@ -98,6 +84,94 @@ impl TradeRecorder {
 
         Ok(result)
     }
+
+    /// スワップ送信前に journal へ intent を記録する
+    ///
+    /// 戻り値のエントリは、送信後に `PendingTransaction::mark_sent_async` で tx hash を
+    /// 記録し、成功時は [`Self::complete_trade`]、送られていないか on-chain で失敗が確定した
+    /// ときは `void_async` で閉じる。
+    pub async fn record_intent(
+        &self,
+        sender: &AccountId,
+        from_token: &TokenInAccount,
+        from_amount: &TokenAmount,
+        to_token: &TokenOutAccount,
+        estimated_to_amount: &TokenAmount,
+    ) -> Result<PendingTransaction> {
+        let intent = NewPendingTransaction {
+            trade_batch_id: self.batch_id.clone(),
+            evaluation_period_id: self.evaluation_period_id.clone(),
+            sender_account: sender.to_string(),
+            from_token: from_token.to_string(),
+            from_amount: from_amount.to_smallest_units(),
+            to_token: to_token.to_string(),
+            estimated_to_amount: estimated_to_amount.to_smallest_units(),
+        };
+
+        intent
+            .insert_async()
+            .await
+            .context("Failed to record swap intent")
+    }
+
+    /// journal エントリを完了させ、`trade_transactions` に記録する
+    ///
+    /// `burnt` は `extract_burnt` で得た (燃やされたガス, yoctoNEAR)。
+    /// 記録と完了は同一 DB トランザクションで行われる。
+    pub async fn complete_trade(
+        &self,
+        entry: &PendingTransaction,
+        actual_to_amount: Option<TokenAmount>,
+        burnt: (u64, u128),
+    ) -> Result<TradeTransaction> {
+        let log = DEFAULT.new(o!(
+            "function" => "complete_trade",
+            "pending_id" => entry.id,
+        ));
+
+        let actual_to_smallest: Option<BigDecimal> =
+            actual_to_amount.map(|a| a.into_smallest_units().into());
+        if let Some(ref actual_bd) = actual_to_smallest {
+            log_slippage(&log, &entry.estimated_to_amount, actual_bd, &entry.to_token);
+        }
+
+        let (gas_burnt, gas_cost) = burnt;
+        let transaction = entry
+            .to_trade_transaction(actual_to_smallest)?
+            .with_gas(gas_burnt, gas_cost);
+        debug!(log, "recording trade"; "tx_id" => %transaction.tx_id, "batch_id" => %self.batch_id);
+
+        PendingTransaction::complete_async(entry.id, transaction)
+            .await
+            .with_context(|| format!("Failed to complete pending transaction: {}", entry.id))
+    }
+}
+
+fn log_slippage(
+    log: &Logger,
+    estimated: &TokenSmallestUnits,
+    actual_bd: &BigDecimal,
+    to_token: &dyn std::fmt::Display,
+) {
+    let estimated_bd = estimated.as_bigdecimal();
+    if estimated_bd.is_zero() {
+        warn!(log, "skipping slippage calculation: estimated amount is zero";
+            "actual" => %actual_bd,
+            "to_token" => %to_token
+        );
+    } else {
+        // diff_pct > 0: actual > estimated (有利な約定)
+        // diff_pct < 0: actual < estimated (不利な約定 = スリッページ損)
+        let diff = actual_bd - estimated_bd;
+        let diff_pct = (&diff / estimated_bd * BigDecimal::from(100))
+            .with_scale_round(4, bigdecimal::RoundingMode::HalfUp);
+        debug!(log, "swap slippage";
+            "estimated" => %estimated_bd,
+            "actual" => %actual_bd,
+            "diff_pct" => %diff_pct,
+            "to_token" => %to_token
+        );
+    }
 }
 
 #[cfg(test)]
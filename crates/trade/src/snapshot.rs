@@ -12,7 +12,7 @@ pub fn ensure_wnear_included(tokens: &mut Vec<TokenAccount>) {
     }
 }
 
-/// トレード後のポートフォリオ保有量を DB に記録
+/// トレード後のポートフォリオ保有量とトークン別損益を DB に記録
 pub async fn record_portfolio_holdings<C, W>(
     client: &C,
     wallet: &W,
@@ -45,10 +45,21 @@ where
 
     let token_holdings = serde_json::to_value(&holdings)?;
 
+    // トークン別損益は記録できなくても保有量の記録は続ける
+    let token_pnl =
+        match crate::pnl::period_token_pnl(period_id, &balances, current_time.naive_utc()).await {
+            Ok(pnl) => Some(serde_json::to_value(&pnl)?),
+            Err(e) => {
+                warn!(log, "failed to calculate token pnl"; "error" => %e);
+                None
+            }
+        };
+
     let record = NewPortfolioHolding {
         evaluation_period_id: period_id.to_string(),
         timestamp: current_time.naive_utc(),
         token_holdings,
+        token_pnl,
     };
 
     PortfolioHolding::insert_async(record).await?;
@@ -44,21 +44,77 @@ use persistence::evaluation_period::EvaluationPeriod;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
 use std::sync::Arc;
+use tracing::Instrument;
 
 use super::execution::{
-    execute_trading_actions, liquidate_all_positions, manage_evaluation_period,
+    EvaluationPeriodResult, execute_trading_actions, liquidate_all_positions,
+    manage_evaluation_period,
 };
 use super::market_data::{
     calculate_enhanced_liquidity_score, calculate_volatility_from_history,
     estimate_market_cap_async,
 };
 
+/// 1 サイクルで戦略が下した判断
+///
+/// `simulate replay` が本番の `trade_transactions` / `portfolio_holdings` と突き合わせるために返す。
+#[derive(Debug, Clone, Default)]
+pub struct CycleReport {
+    pub period_id: String,
+    pub is_new_period: bool,
+    /// 取引対象として選んだトークン
+    pub selected_tokens: Vec<TokenAccount>,
+    pub actions: Vec<TradingAction>,
+    pub expected_returns: BTreeMap<TokenOutAccount, f64>,
+    /// 取引に進まずにサイクルを終えた理由
+    pub halted: Option<&'static str>,
+}
+
+/// DB から読んだ予測価格を、サイクルで使う前に書き換えるフック
+///
+/// 本番の [`start`] は使わない。`simulate montecarlo` が予測にノイズを掛けて
+/// 戦略の頑健性を測るために [`start_with_prediction_hook`] へ渡す。
+pub trait PredictionHook: Send + Sync {
+    fn adjust(
+        &self,
+        predictions: &mut BTreeMap<TokenOutAccount, TokenPrice>,
+        as_of: chrono::DateTime<chrono::Utc>,
+    );
+}
+
+impl CycleReport {
+    fn halted(period_id: &str, is_new_period: bool, reason: &'static str) -> Self {
+        Self {
+            period_id: period_id.to_string(),
+            is_new_period,
+            halted: Some(reason),
+            ..Self::default()
+        }
+    }
+}
+
 pub async fn start<C, W>(
     client: &C,
     wallet: &W,
     current_time: chrono::DateTime<chrono::Utc>,
     cfg: &impl ConfigAccess,
-) -> Result<()>
+) -> Result<CycleReport>
+where
+    C: AccountInfo + SendTx + ViewContract + GasInfo,
+    <C as SendTx>::Output: Display + blockchain::jsonrpc::SentTx,
+    W: Wallet,
+{
+    start_with_prediction_hook(client, wallet, current_time, cfg, None).await
+}
+
+/// [`start`] と同じサイクルを、読み込んだ予測に `prediction_hook` を掛けて実行する
+pub async fn start_with_prediction_hook<C, W>(
+    client: &C,
+    wallet: &W,
+    current_time: chrono::DateTime<chrono::Utc>,
+    cfg: &impl ConfigAccess,
+    prediction_hook: Option<&dyn PredictionHook>,
+) -> Result<CycleReport>
 where
     C: AccountInfo + SendTx + ViewContract + GasInfo,
     <C as SendTx>::Output: Display + blockchain::jsonrpc::SentTx,
@@ -73,8 +129,10 @@ where
 
     // Step 1: 評価期間のチェックと管理（清算が必要な場合は先に実行）
     // 初回起動時は available_funds=0 で呼び出し、後で prepare_funds() で資金準備
-    let result =
-        manage_evaluation_period(client, wallet, current_time, YoctoAmount::zero(), cfg).await?;
+    let result = manage_evaluation_period(client, wallet, current_time, YoctoAmount::zero(), cfg)
+        .instrument(tracing::info_span!("trade.evaluation_period"))
+        .await?;
+    tracing::Span::current().record("trade.period_id", result.period_id.as_str());
     info!(log, "evaluation period status";
         "period_id" => %result.period_id,
         "is_new_period" => result.is_new_period,
@@ -86,22 +144,76 @@ where
     // period_id が空の場合は清算のみで終了（manage_evaluation_period で停止された）
     if result.period_id.is_empty() {
         info!(log, "trade stopped after liquidation (TRADE_ENABLED=false)");
-        return Ok(());
+        return Ok(CycleReport::halted(
+            "",
+            false,
+            "trade stopped after liquidation",
+        ));
     }
 
     // 取引が無効化されている場合
     if !trade_enabled {
         if result.is_new_period {
             info!(log, "trade disabled, skipping new period");
-            return Ok(());
         } else {
             // 評価期間中: 清算して終了
             info!(log, "trade disabled, liquidating positions");
             let _ = liquidate_all_positions(client, wallet, cfg).await?;
-            return Ok(());
         }
+        return Ok(CycleReport::halted(
+            &result.period_id,
+            result.is_new_period,
+            "trade disabled",
+        ));
     }
 
+    run_cycle(client, wallet, current_time, result, cfg, prediction_hook).await
+}
+
+/// 評価期間を固定して 1 サイクルを実行する（`simulate replay` 用）
+///
+/// `manage_evaluation_period` を通さず、`period_id` の開始（`is_new_period`）または継続として扱う。
+/// 期間の切り替えや清算は行わない。`period_id` の評価期間は DB に存在している必要がある。
+pub async fn replay_cycle<C, W>(
+    client: &C,
+    wallet: &W,
+    current_time: chrono::DateTime<chrono::Utc>,
+    period_id: String,
+    is_new_period: bool,
+    existing_tokens: Vec<TokenAccount>,
+    cfg: &impl ConfigAccess,
+) -> Result<CycleReport>
+where
+    C: AccountInfo + SendTx + ViewContract + GasInfo,
+    <C as SendTx>::Output: Display + blockchain::jsonrpc::SentTx,
+    W: Wallet,
+{
+    let period = EvaluationPeriodResult {
+        period_id,
+        is_new_period,
+        existing_tokens,
+        liquidated_balance: None,
+        failed_liquidations: vec![],
+    };
+    run_cycle(client, wallet, current_time, period, cfg, None).await
+}
+
+/// 評価期間が決まった後の 1 サイクル（資金準備・トークン選定・最適化・取引・保有量記録）
+async fn run_cycle<C, W>(
+    client: &C,
+    wallet: &W,
+    current_time: chrono::DateTime<chrono::Utc>,
+    result: EvaluationPeriodResult,
+    cfg: &impl ConfigAccess,
+    prediction_hook: Option<&dyn PredictionHook>,
+) -> Result<CycleReport>
+where
+    C: AccountInfo + SendTx + ViewContract + GasInfo,
+    <C as SendTx>::Output: Display + blockchain::jsonrpc::SentTx,
+    W: Wallet,
+{
+    let log = DEFAULT.new(o!("function" => "trade::run_cycle"));
+
     // Step 2: 資金準備（新規期間で清算がなかった場合のみ）
     let available_funds: YoctoAmount = if result.is_new_period {
         if let Some(balance) = result.liquidated_balance {
@@ -109,7 +221,11 @@ where
             debug!(log, "Using liquidated balance for new period"; "available_funds" => %balance);
             if balance.is_zero() {
                 info!(log, "no funds available after liquidation");
-                return Ok(());
+                return Ok(CycleReport::halted(
+                    &result.period_id,
+                    true,
+                    "no funds available after liquidation",
+                ));
             }
             balance
         } else {
@@ -119,7 +235,11 @@ where
 
             if funds.is_zero() {
                 info!(log, "no funds available for trading");
-                return Ok(());
+                return Ok(CycleReport::halted(
+                    &result.period_id,
+                    true,
+                    "no funds available for trading",
+                ));
             }
 
             funds
@@ -176,7 +296,11 @@ where
 
     if selected_tokens.is_empty() {
         info!(log, "no tokens selected for trading");
-        return Ok(());
+        return Ok(CycleReport::halted(
+            &period_id,
+            is_new_period,
+            "no tokens selected for trading",
+        ));
     }
 
     // Step 4.5: REF Finance のストレージセットアップを確認・実行
@@ -197,6 +321,7 @@ where
         &keep,
         max_top_up,
     )
+    .instrument(tracing::info_span!("trade.storage_setup"))
     .await?;
     debug!(log, "REF Finance storage setup completed");
 
@@ -228,15 +353,18 @@ where
         period_id: &period_id,
         end_date: current_time,
         cfg,
+        prediction_hook,
+    };
+    let (actions, expected_returns) = match execute_portfolio_strategy(&params, client, wallet)
+        .instrument(tracing::info_span!("trade.optimize"))
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            error!(log, "failed to execute portfolio strategy"; "error" => ?e);
+            return Err(e);
+        }
     };
-    let (actions, expected_returns) =
-        match execute_portfolio_strategy(&params, client, wallet).await {
-            Ok(result) => result,
-            Err(e) => {
-                error!(log, "failed to execute portfolio strategy"; "error" => ?e);
-                return Err(e);
-            }
-        };
 
     info!(log, "portfolio optimization completed";
         "action_count" => actions.len()
@@ -252,7 +380,11 @@ where
         &expected_returns,
     )
     .await?;
-    info!(log, "trades executed"; "success" => executed_actions.success_count, "failed" => executed_actions.failed_count);
+    info!(log, "trades executed";
+        "success" => executed_actions.success_count,
+        "failed" => executed_actions.failed_count,
+        "skipped" => executed_actions.skipped_count
+    );
 
     // ポートフォリオ保有量を記録
     if let Err(e) = super::snapshot::record_portfolio_holdings(
@@ -262,6 +394,7 @@ where
         &token_accounts,
         current_time,
     )
+    .instrument(tracing::info_span!("trade.snapshot"))
     .await
     {
         warn!(log, "failed to record portfolio holdings"; "error" => ?e);
@@ -271,7 +404,14 @@ where
     // 自動実行される。旧 period の initial_value と清算額で正しく比較するため。
 
     info!(log, "success");
-    Ok(())
+    Ok(CycleReport {
+        period_id,
+        is_new_period,
+        selected_tokens: token_accounts,
+        actions,
+        expected_returns,
+        halted: None,
+    })
 }
 
 /// 資金準備 (NEAR -> wrap.near 変換)
@@ -412,6 +552,7 @@ pub(crate) struct PortfolioStrategyParams<'a, Cfg: ConfigAccess> {
     pub(crate) period_id: &'a str,
     pub(crate) end_date: chrono::DateTime<chrono::Utc>,
     pub(crate) cfg: &'a Cfg,
+    pub(crate) prediction_hook: Option<&'a dyn PredictionHook>,
 }
 
 /// ポートフォリオ戦略の実行
@@ -478,6 +619,11 @@ where
         warn!(log, "skipped predictions with unparseable tokens"; "count" => parse_failures);
     }
 
+    if let Some(hook) = params.prediction_hook {
+        hook.adjust(&mut batch_predictions, end_date);
+        debug!(log, "predictions adjusted by hook");
+    }
+
     if batch_predictions.is_empty() {
         warn!(log, "no fresh predictions available for any token";
             "token_count" => token_out_list.len(),
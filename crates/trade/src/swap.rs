@@ -1,11 +1,16 @@
 use crate::Result;
+use crate::gas::{self, GasEstimate};
 use crate::recorder::TradeRecorder;
 use crate::slippage::{self, SlippagePolicy};
-use bigdecimal::BigDecimal;
-use blockchain::jsonrpc::SentTx;
-use common::types::{NearValue, TokenAccount, TokenAmount};
+use bigdecimal::{BigDecimal, ToPrimitive};
+use blockchain::jsonrpc::{GasInfo, SentTx, TxFailed, TxMaybeSent};
+use blockchain::ref_finance::path::split::SplitRoute;
+use blockchain::ref_finance::swap::{SwapArg, SwapLeg};
+use common::types::{NearValue, TokenAccount, TokenAmount, YoctoValue};
+use dex::TokenPair;
 use logging::*;
 use near_sdk::NearToken;
+use persistence::pending_transaction::PendingTransaction;
 use std::collections::BTreeMap;
 
 /// ポートフォリオ全体の現在残高を取得（TokenAmount: smallest_units + decimals）
@@ -66,17 +71,31 @@ pub struct SwapParams<'a> {
     pub policy: &'a SlippagePolicy,
 }
 
+/// execute_direct_swap の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapOutcome {
+    Executed,
+    /// 期待利益がガス代と手数料に届かないため送信しなかった
+    Unprofitable,
+}
+
 /// 2つのトークン間で直接スワップを実行（シンプルなパス探索を使用）
 ///
 /// `params.policy` でスリッページ保護の方針を指定する:
 /// - `FromExpectedReturn`: 予測リターンに基づく min_out を設定
 /// - `Unprotected`: min_out = 0（清算・売却フェーズ用）
+///
+/// `FromExpectedReturn` のスワップは、期待利益が経路のガス代と
+/// プール手数料を下回る場合は送信せず [`SwapOutcome::Unprofitable`] を返す。
+/// ガス代は wNEAR 以外の起点トークンでは最新レートで換算する。
+///
+/// 成否と実現スリッページは `metrics::trade` に記録する。
 pub async fn execute_direct_swap<C, W>(
     client: &C,
     wallet: &W,
     params: &SwapParams<'_>,
     cfg: &impl common::config::ConfigAccess,
-) -> Result<()>
+) -> Result<SwapOutcome>
 where
     C: blockchain::jsonrpc::AccountInfo
         + blockchain::jsonrpc::SendTx
@@ -84,18 +103,117 @@ where
         + blockchain::jsonrpc::GasInfo,
     <C as blockchain::jsonrpc::SendTx>::Output: std::fmt::Display + blockchain::jsonrpc::SentTx,
     W: blockchain::wallet::Wallet,
+{
+    let result = direct_swap(client, wallet, params, cfg).await;
+    if !matches!(result, Ok(SwapOutcome::Unprofitable)) {
+        metrics::trade::record_swap(result.is_ok());
+    }
+    result
+}
+
+/// ガス代を `token` の最小単位に換算する
+///
+/// wNEAR はそのまま、その他のトークンは DB の最新レートで換算する。
+/// レートが無い場合は `None`。
+async fn gas_cost_in_token(token: &TokenAccount, gas_cost: NearToken) -> Result<Option<u128>> {
+    let wnear = &*blockchain::ref_finance::token_account::WNEAR_TOKEN;
+    if token == wnear {
+        return Ok(Some(gas_cost.as_yoctonear()));
+    }
+    let Some(rate) =
+        persistence::token_rate::TokenRate::get_latest(&token.to_out(), &wnear.to_in()).await?
+    else {
+        return Ok(None);
+    };
+    let spot = rate.to_spot_rate();
+    if spot.is_effectively_zero() {
+        return Ok(None);
+    }
+    let near = YoctoValue::from_yocto(BigDecimal::from(gas_cost.as_yoctonear())).to_near();
+    Ok((&near * &spot).smallest_units().to_u128())
+}
+
+/// 送信の準備ができたスワップ（journal に intent を記録済み）
+struct PreparedSwap {
+    entry: PendingTransaction,
+    route: SplitRoute,
+    /// 経路ごとの min_out
+    min_outs: Vec<u128>,
+    swap_amount: u128,
+    estimated_output: u128,
+    to_decimals: u8,
+}
+
+impl PreparedSwap {
+    fn legs(&self) -> Vec<SwapLeg<'_, TokenPair>> {
+        self.route
+            .legs
+            .iter()
+            .zip(&self.min_outs)
+            .map(|(leg, &min_out)| SwapLeg {
+                path: &leg.path.0,
+                arg: SwapArg {
+                    initial_in: leg.amount_in,
+                    min_out,
+                },
+            })
+            .collect()
+    }
+}
+
+enum Prepared {
+    Ready(PreparedSwap),
+    Unprofitable,
+}
+
+fn swap_logger(params: &SwapParams<'_>) -> Logger {
+    DEFAULT.new(o!(
+        "function" => "execute_direct_swap",
+        "from" => format!("{}", params.from_token),
+        "to" => format!("{}", params.to_token)
+    ))
+}
+
+async fn direct_swap<C, W>(
+    client: &C,
+    wallet: &W,
+    params: &SwapParams<'_>,
+    cfg: &impl common::config::ConfigAccess,
+) -> Result<SwapOutcome>
+where
+    C: blockchain::jsonrpc::AccountInfo
+        + blockchain::jsonrpc::SendTx
+        + blockchain::jsonrpc::ViewContract
+        + blockchain::jsonrpc::GasInfo,
+    <C as blockchain::jsonrpc::SendTx>::Output: std::fmt::Display + blockchain::jsonrpc::SentTx,
+    W: blockchain::wallet::Wallet,
+{
+    match prepare_swap(client, wallet, params, cfg).await? {
+        Prepared::Ready(prepared) => send_prepared(client, wallet, params, &prepared).await,
+        Prepared::Unprofitable => Ok(SwapOutcome::Unprofitable),
+    }
+}
+
+/// 送信直前までの準備（残高確認・経路探索・利益判定・storage 登録・journal への intent 記録）
+async fn prepare_swap<C, W>(
+    client: &C,
+    wallet: &W,
+    params: &SwapParams<'_>,
+    cfg: &impl common::config::ConfigAccess,
+) -> Result<Prepared>
+where
+    C: blockchain::jsonrpc::AccountInfo
+        + blockchain::jsonrpc::SendTx
+        + blockchain::jsonrpc::ViewContract
+        + blockchain::jsonrpc::GasInfo,
+    W: blockchain::wallet::Wallet,
 {
     let from_token = params.from_token;
     let to_token = params.to_token;
     let policy = params.policy;
 
-    let log = DEFAULT.new(o!(
-        "function" => "execute_direct_swap",
-        "from" => format!("{}", from_token),
-        "to" => format!("{}", to_token)
-    ));
+    let log = swap_logger(params);
     debug!(log, "starting direct swap");
-
     // 型安全な TokenAccount に変換
     let from_token_account: common::types::TokenAccount = from_token.inner().clone();
     let to_token_account: common::types::TokenAccount = to_token.inner().clone();
@@ -142,17 +260,51 @@ where
     let start: common::types::TokenInAccount = from_token_account.clone().into();
     let goal: common::types::TokenOutAccount = to_token_account.clone().into();
 
-    // from_tokenを起点としてグラフを更新（流動性のあるトークンのみ含める）
-    graph
-        .update_graph(&start)
-        .map_err(|e| anyhow::anyhow!("Failed to update graph from {}: {}", from_token, e))?;
+    // 実際のスワップ量で価格インパクトを考慮した経路を選び、有利なら複数経路に分割する
+    let max_legs = cfg.trade_swap_max_split_legs() as usize;
+    let route = graph
+        .split_route_for_amount(&start, &goal, swap_amount, max_legs)
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to find route from {} to {}: {}",
+                from_token,
+                to_token,
+                e
+            )
+        })?;
+    for leg in &route.legs {
+        leg.path.validate_length()?;
+    }
 
-    // シンプルなパス探索（利益を考慮しない）
-    let path = graph.get_path(&start, &goal)?;
-    path.validate_length()?;
+    // 期待利益がガス代と手数料に届かなければ送らない
+    if let SlippagePolicy::FromExpectedReturn(expected) = policy {
+        let gas_price = client.get_gas_price(None).await?;
+        let gas_cost_near = GasEstimate::default()
+            .with_swap(route.hops())
+            .cost(gas_price);
+        match gas_cost_in_token(&from_token_account, gas_cost_near).await? {
+            Some(gas_cost) => {
+                let fee = gas::route_fee(&route);
+                let gain = gas::expected_gain(swap_amount, expected.as_ratio());
+                if !gas::covers_cost(gain, gas_cost, fee) {
+                    info!(log, "skipping swap: expected gain does not cover gas and fees";
+                        "input" => swap_amount,
+                        "expected_gain" => gain,
+                        "gas_cost" => gas_cost,
+                        "fee" => fee,
+                        "hops" => route.hops(),
+                    );
+                    return Ok(Prepared::Unprofitable);
+                }
+            }
+            None => warn!(log, "no rate to convert gas cost, sending without profitability check";
+                "gas_cost_yocto" => gas_cost_near.as_yoctonear(),
+            ),
+        }
+    }
 
-    // パスに含まれるすべてのトークン（中継トークン含む）のストレージデポジットを確認
-    let tokens = path.all_tokens();
+    // 経路に含まれるすべてのトークン（中継トークン含む）のストレージデポジットを確認
+    let tokens = route.all_tokens();
     // keep: 単発スワップでは基軸通貨の WNEAR のみ保持
     let keep = blockchain::ref_finance::storage::keep_wnear_only();
     let max_top_up = blockchain::ref_finance::storage::max_top_up_from_config(cfg);
@@ -161,82 +313,388 @@ where
     )
     .await?;
 
-    // AMM 理論出力を事前計算し、スリッページポリシーに基づいて min_out を算出
-    let estimated_output = path.calc_value(swap_amount)?;
-    let min_out = slippage::calculate_min_out(estimated_output, policy)?;
+    // AMM 理論出力を経路ごとに事前計算し、スリッページポリシーに基づいて経路ごとの min_out を算出
+    let min_outs = route
+        .legs
+        .iter()
+        .map(|leg| slippage::calculate_min_out(leg.estimated_out, policy))
+        .collect::<Result<Vec<_>>>()?;
+    let estimated_output = route.total_out();
 
     debug!(log, "slippage protection";
         "policy" => %policy,
+        "legs" => route.legs.len(),
+        "hops" => route.hops(),
         "estimated_output" => estimated_output,
-        "min_out" => min_out,
+        "min_out" => min_outs.iter().sum::<u128>(),
     );
 
-    let arg = blockchain::ref_finance::swap::SwapArg {
-        initial_in: swap_amount,
-        min_out,
-    };
+    // トークンの decimals を取得して TokenAmount を作成
+    let from_decimals =
+        crate::token_cache::get_token_decimals_cached(client, from_token.inner()).await?;
+    let to_decimals =
+        crate::token_cache::get_token_decimals_cached(client, to_token.inner()).await?;
+    let from_amount =
+        TokenAmount::from_smallest_units(BigDecimal::from(swap_amount), from_decimals);
+    let estimated_amount =
+        TokenAmount::from_smallest_units(BigDecimal::from(estimated_output), to_decimals);
+
+    // 送信前に journal へ intent を記録（送信後にプロセスが落ちても reconciler が回収できるように）
+    let entry = params
+        .recorder
+        .record_intent(
+            wallet.account_id(),
+            from_token,
+            &from_amount,
+            to_token,
+            &estimated_amount,
+        )
+        .await?;
+
+    Ok(Prepared::Ready(PreparedSwap {
+        entry,
+        route,
+        min_outs,
+        swap_amount,
+        estimated_output,
+        to_decimals,
+    }))
+}
+
+/// 準備したスワップを単独のトランザクションで送り、結果を記録する
+async fn send_prepared<C, W>(
+    client: &C,
+    wallet: &W,
+    params: &SwapParams<'_>,
+    prepared: &PreparedSwap,
+) -> Result<SwapOutcome>
+where
+    C: blockchain::jsonrpc::SendTx + blockchain::jsonrpc::ViewContract,
+    <C as blockchain::jsonrpc::SendTx>::Output: std::fmt::Display + blockchain::jsonrpc::SentTx,
+    W: blockchain::wallet::Wallet,
+{
+    let log = swap_logger(params);
+    let entry = &prepared.entry;
 
     // スワップを実行
-    let (sent_tx, out) =
-        blockchain::ref_finance::swap::run_swap(client, wallet, &path.0, arg).await?;
+    let (sent_tx, _) =
+        match blockchain::ref_finance::swap::run_split_swap(client, wallet, &prepared.legs()).await
+        {
+            Ok(sent) => sent,
+            Err(e) => {
+                settle_unsent_entry(&log, entry.id, &e).await;
+                return Err(e);
+            }
+        };
+
+    let entry = PendingTransaction::mark_sent_async(
+        entry.id,
+        sent_tx.tx_hash().to_string(),
+        sent_tx.to_string(),
+    )
+    .await?;
 
     let outcome = match sent_tx.wait_for_success().await {
         Ok(outcome) => outcome,
         Err(e) => {
             error!(log, "swap transaction failed"; "error" => %e);
+            // on-chain で失敗が確定した場合のみ閉じる。タイムアウト等で結果が不明なものは
+            // sent のまま残し、reconciler に任せる。
+            if e.downcast_ref::<TxFailed>().is_some() {
+                void_entry(&log, entry.id, &e).await;
+            } else {
+                warn!(log, "swap result unknown, leaving journal entry for reconciler";
+                    "pending_id" => entry.id);
+            }
             return Err(anyhow::anyhow!("Swap transaction failed: {}", e));
         }
     };
 
-    // トークンの decimals を取得して TokenAmount を作成
-    let from_decimals =
-        crate::token_cache::get_token_decimals_cached(client, from_token.inner()).await?;
-    let to_decimals =
-        crate::token_cache::get_token_decimals_cached(client, to_token.inner()).await?;
-
     // 実績値を抽出
-    let actual_to_amount = match blockchain::ref_finance::swap::extract_actual_output(&outcome) {
-        Ok(actual) => {
-            if actual == 0 {
-                warn!(log, "swap returned zero output amount";
-                    "from" => %from_token, "to" => %to_token);
-            }
-            Some(TokenAmount::from_smallest_units(
-                BigDecimal::from(actual),
-                to_decimals,
-            ))
-        }
-        Err(e) => {
-            warn!(log, "failed to extract actual output"; "error" => %e);
-            None
+    let actual = blockchain::ref_finance::swap::extract_actual_output(&outcome)
+        .map_err(|e| warn!(log, "failed to extract actual output"; "error" => %e))
+        .ok();
+    complete_swap(
+        &log,
+        params,
+        prepared,
+        &entry,
+        actual,
+        blockchain::ref_finance::swap::extract_burnt(&outcome),
+    )
+    .await
+}
+
+/// 約定したスワップの実績を記録する
+async fn complete_swap(
+    log: &Logger,
+    params: &SwapParams<'_>,
+    prepared: &PreparedSwap,
+    entry: &PendingTransaction,
+    actual: Option<u128>,
+    burnt: (u64, u128),
+) -> Result<SwapOutcome> {
+    let from_token = params.from_token;
+    let to_token = params.to_token;
+    let actual_to_amount = actual.map(|actual| {
+        metrics::trade::record_slippage(prepared.estimated_output, actual);
+        if actual == 0 {
+            warn!(log, "swap returned zero output amount";
+                "from" => %from_token, "to" => %to_token);
         }
-    };
+        TokenAmount::from_smallest_units(BigDecimal::from(actual), prepared.to_decimals)
+    });
 
     info!(log, "swap successful";
         "from" => %from_token,
         "to" => %to_token,
-        "input" => swap_amount,
-        "estimated_output" => out,
+        "input" => prepared.swap_amount,
+        "estimated_output" => prepared.estimated_output,
         "actual_output" => actual_to_amount.as_ref().map(|a| a.to_string()).unwrap_or_else(|| "N/A".to_string()),
     );
 
-    let from_amount =
-        TokenAmount::from_smallest_units(BigDecimal::from(swap_amount), from_decimals);
-    let to_amount = TokenAmount::from_smallest_units(BigDecimal::from(out), to_decimals);
-
     params
         .recorder
-        .record_trade(
-            sent_tx.to_string(),
-            from_token,
-            from_amount,
-            to_token,
-            to_amount,
-            actual_to_amount,
-        )
+        .complete_trade(entry, actual_to_amount, burnt)
         .await?;
 
-    Ok(())
+    Ok(SwapOutcome::Executed)
+}
+
+/// 複数のスワップを REF 宛てのトランザクションにまとめて実行する
+///
+/// 各スワップの準備（残高確認・経路探索・利益判定・storage 登録・journal への intent 記録）は
+/// [`execute_direct_swap`] と同じで、送信だけを `swap_batches` の区切りでまとめる。
+/// 同じバッチのスワップは 1 つのレシートで実行され、どれかが失敗するとすべて巻き戻る。
+/// 送られなかった、または on-chain で失敗したバッチは 1 件ずつ送り直す。結果が分からない
+/// バッチは journal を reconciler に任せ、各スワップを失敗とする。
+///
+/// 戻り値は `params` と同じ順の結果。
+pub async fn execute_swap_batch<C, W>(
+    client: &C,
+    wallet: &W,
+    params: &[SwapParams<'_>],
+    cfg: &impl common::config::ConfigAccess,
+) -> Vec<Result<SwapOutcome>>
+where
+    C: blockchain::jsonrpc::AccountInfo
+        + blockchain::jsonrpc::SendTx
+        + blockchain::jsonrpc::ViewContract
+        + blockchain::jsonrpc::GasInfo,
+    <C as blockchain::jsonrpc::SendTx>::Output: std::fmt::Display + blockchain::jsonrpc::SentTx,
+    W: blockchain::wallet::Wallet,
+{
+    let results = swap_batch(client, wallet, params, cfg).await;
+    for result in &results {
+        if !matches!(result, Ok(SwapOutcome::Unprofitable)) {
+            metrics::trade::record_swap(result.is_ok());
+        }
+    }
+    results
+}
+
+async fn swap_batch<C, W>(
+    client: &C,
+    wallet: &W,
+    params: &[SwapParams<'_>],
+    cfg: &impl common::config::ConfigAccess,
+) -> Vec<Result<SwapOutcome>>
+where
+    C: blockchain::jsonrpc::AccountInfo
+        + blockchain::jsonrpc::SendTx
+        + blockchain::jsonrpc::ViewContract
+        + blockchain::jsonrpc::GasInfo,
+    <C as blockchain::jsonrpc::SendTx>::Output: std::fmt::Display + blockchain::jsonrpc::SentTx,
+    W: blockchain::wallet::Wallet,
+{
+    let log = DEFAULT.new(o!(
+        "function" => "execute_swap_batch",
+        "swaps" => params.len(),
+    ));
+
+    let mut results: Vec<Option<Result<SwapOutcome>>> = params.iter().map(|_| None).collect();
+    let mut ready: Vec<(usize, PreparedSwap)> = Vec::new();
+    for (i, swap) in params.iter().enumerate() {
+        match prepare_swap(client, wallet, swap, cfg).await {
+            Ok(Prepared::Ready(prepared)) => ready.push((i, prepared)),
+            Ok(Prepared::Unprofitable) => results[i] = Some(Ok(SwapOutcome::Unprofitable)),
+            Err(e) => {
+                error!(log, "failed to prepare swap";
+                    "from" => %swap.from_token, "to" => %swap.to_token, "error" => %e);
+                results[i] = Some(Err(e));
+            }
+        }
+    }
+
+    let action_counts: Vec<usize> = ready.iter().map(|(_, p)| p.route.hops()).collect();
+    for range in blockchain::ref_finance::swap::swap_batches(wallet, &action_counts) {
+        let batch = &ready[range];
+        let batch_results = if let [(i, prepared)] = batch {
+            vec![(
+                *i,
+                send_prepared(client, wallet, &params[*i], prepared).await,
+            )]
+        } else {
+            send_batch(&log, client, wallet, params, batch, cfg).await
+        };
+        for (i, result) in batch_results {
+            results[i] = Some(result);
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Err(anyhow::anyhow!("swap was not executed"))))
+        .collect()
+}
+
+/// 準備した 2 件以上のスワップを 1 トランザクションで送り、結果を記録する
+async fn send_batch<C, W>(
+    log: &Logger,
+    client: &C,
+    wallet: &W,
+    params: &[SwapParams<'_>],
+    batch: &[(usize, PreparedSwap)],
+    cfg: &impl common::config::ConfigAccess,
+) -> Vec<(usize, Result<SwapOutcome>)>
+where
+    C: blockchain::jsonrpc::AccountInfo
+        + blockchain::jsonrpc::SendTx
+        + blockchain::jsonrpc::ViewContract
+        + blockchain::jsonrpc::GasInfo,
+    <C as blockchain::jsonrpc::SendTx>::Output: std::fmt::Display + blockchain::jsonrpc::SentTx,
+    W: blockchain::wallet::Wallet,
+{
+    let legs: Vec<_> = batch.iter().map(|(_, prepared)| prepared.legs()).collect();
+    let sent_tx = match blockchain::ref_finance::swap::run_swap_batch(client, wallet, &legs).await {
+        Ok((sent_tx, _)) => sent_tx,
+        Err(e) if e.downcast_ref::<TxMaybeSent>().is_some() => {
+            for (_, prepared) in batch {
+                settle_unsent_entry(log, prepared.entry.id, &e).await;
+            }
+            return batch
+                .iter()
+                .map(|(i, _)| {
+                    (
+                        *i,
+                        Err(anyhow::anyhow!("Swap batch may have been sent: {}", e)),
+                    )
+                })
+                .collect();
+        }
+        Err(e) => {
+            // 送られていないので、準備済みのまま 1 件ずつ送る
+            warn!(log, "swap batch not sent, sending swaps one by one"; "error" => %e);
+            let mut results = Vec::with_capacity(batch.len());
+            for (i, prepared) in batch {
+                results.push((
+                    *i,
+                    send_prepared(client, wallet, &params[*i], prepared).await,
+                ));
+            }
+            return results;
+        }
+    };
+
+    let mut entries = Vec::with_capacity(batch.len());
+    for (_, prepared) in batch {
+        entries.push(
+            PendingTransaction::mark_sent_async(
+                prepared.entry.id,
+                sent_tx.tx_hash().to_string(),
+                sent_tx.to_string(),
+            )
+            .await,
+        );
+    }
+
+    let outcome = match sent_tx.wait_for_success().await {
+        Ok(outcome) => outcome,
+        Err(e) if e.downcast_ref::<TxFailed>().is_some() => {
+            // レシートごと巻き戻っているので、1 件ずつ準備からやり直す
+            warn!(log, "swap batch failed on chain, sending swaps one by one"; "error" => %e);
+            let mut results = Vec::with_capacity(batch.len());
+            for (i, prepared) in batch {
+                void_entry(log, prepared.entry.id, &e).await;
+                results.push((*i, direct_swap(client, wallet, &params[*i], cfg).await));
+            }
+            return results;
+        }
+        Err(e) => {
+            warn!(log, "swap batch result unknown, leaving journal entries for reconciler";
+                "tx" => %sent_tx, "error" => %e);
+            return batch
+                .iter()
+                .map(|(i, _)| {
+                    (
+                        *i,
+                        Err(anyhow::anyhow!("Swap batch transaction failed: {}", e)),
+                    )
+                })
+                .collect();
+        }
+    };
+
+    let action_counts: Vec<usize> = batch.iter().map(|(_, p)| p.route.hops()).collect();
+    let actuals = blockchain::ref_finance::swap::extract_batch_outputs(&outcome, &action_counts);
+    let burnt = split_burnt(
+        blockchain::ref_finance::swap::extract_burnt(&outcome),
+        batch.len(),
+    );
+    let mut results = Vec::with_capacity(batch.len());
+    for ((((i, prepared), entry), actual), burnt) in
+        batch.iter().zip(entries).zip(actuals).zip(burnt)
+    {
+        let swap = &params[*i];
+        let result = match entry {
+            Ok(entry) => {
+                complete_swap(&swap_logger(swap), swap, prepared, &entry, actual, burnt).await
+            }
+            Err(e) => Err(e),
+        };
+        results.push((*i, result));
+    }
+    results
+}
+
+/// バッチ全体で燃やしたガスと NEAR をスワップの件数で等分する（端数は先頭に寄せる）
+fn split_burnt((gas, cost): (u64, u128), count: usize) -> Vec<(u64, u128)> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let n = count as u64;
+    let mut shares = vec![(gas / n, cost / u128::from(n)); count];
+    shares[0].0 += gas % n;
+    shares[0].1 += cost % u128::from(n);
+    shares
+}
+
+/// 送信がエラーになった journal エントリを片付ける
+///
+/// 送られていないことが確かなら失敗として閉じる。ブロードキャストされた可能性がある
+/// （[`TxMaybeSent`]）場合は tx hash 付きで sent にして残し、reconciler に任せる。
+async fn settle_unsent_entry(log: &Logger, id: i32, cause: &anyhow::Error) {
+    let Some(maybe_sent) = cause.downcast_ref::<TxMaybeSent>() else {
+        void_entry(log, id, cause).await;
+        return;
+    };
+    warn!(log, "swap may have been broadcast, leaving journal entry for reconciler";
+        "pending_id" => id,
+        "tx_hash" => %maybe_sent.tx_hash,
+    );
+    if let Err(e) =
+        PendingTransaction::mark_sent_async(id, maybe_sent.tx_hash.to_string(), maybe_sent.tx_id())
+            .await
+    {
+        warn!(log, "failed to mark journal entry as sent"; "pending_id" => id, "error" => %e);
+    }
+}
+
+/// journal エントリを失敗として閉じる（DB エラーはログのみ。未解決のまま残れば reconciler が扱う）
+async fn void_entry(log: &Logger, id: i32, cause: &anyhow::Error) {
+    if let Err(e) = PendingTransaction::void_async(id, cause.to_string()).await {
+        warn!(log, "failed to void journal entry"; "pending_id" => id, "error" => %e);
+    }
 }
 
 #[cfg(test)]
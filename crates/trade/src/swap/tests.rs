@@ -99,3 +99,14 @@ fn test_bigdecimal_zero_handling() {
         "Zero BigDecimal should be identified as zero"
     );
 }
+
+#[test]
+fn test_split_burnt_shares_batch_gas() {
+    // 端数は先頭のスワップに寄せ、合計は変わらない
+    assert_eq!(
+        super::split_burnt((10, 101), 3),
+        vec![(4, 35), (3, 33), (3, 33)]
+    );
+    assert_eq!(super::split_burnt((10, 100), 1), vec![(10, 100)]);
+    assert!(super::split_burnt((10, 100), 0).is_empty());
+}
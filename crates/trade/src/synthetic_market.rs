@@ -0,0 +1,403 @@
+//! シードから再現可能な合成市場データの生成
+//!
+//! DB なしでシミュレーションやテストを回すため、トークン価格（GBM またはレジーム切り替え、
+//! トークン間の相関つき）と、それに整合するプール残高・価格予測をまとめて生成する。
+//! 同じ [`SyntheticMarketConfig`] からは常に同じ市場が生成される。
+//!
+//! `test-helpers` feature では `SyntheticMarket::seed` で `persistence::memory::MemoryStore` に
+//! 流し込み、戦略サイクル全体を DB なしで実行できる。
+
+use crate::Result;
+use bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive};
+use blockchain::ref_finance::token_account::WNEAR_TOKEN;
+use chrono::{DateTime, TimeDelta, Utc};
+use common::types::{ExchangeRate, TokenAccount, TokenPrice};
+use dex::{PoolInfo, PoolInfoBared, PoolInfoList};
+use near_sdk::json_types::U128;
+#[cfg(feature = "test-helpers")]
+use persistence::memory::MemoryStore;
+#[cfg(feature = "test-helpers")]
+use persistence::prediction_record::NewPredictionRecord;
+#[cfg(feature = "test-helpers")]
+use persistence::token_rate::TokenRate;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// ドリフト・ボラティリティは年率で指定する
+const HOURS_PER_YEAR: f64 = 24.0 * 365.0;
+
+/// 合成プールの種別と手数料（REF の SIMPLE_POOL で一般的な 0.3%）
+const POOL_KIND_SIMPLE: &str = "SIMPLE_POOL";
+const POOL_TOTAL_FEE: u32 = 30;
+
+/// wNEAR の decimals
+const NEAR_DECIMALS: u8 = 24;
+
+fn default_step_hours() -> u32 {
+    24
+}
+
+fn default_prediction_horizon_steps() -> usize {
+    1
+}
+
+/// 合成市場の生成パラメータ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticMarketConfig {
+    /// 乱数シード
+    pub seed: u64,
+    /// 最初の時点
+    pub start: DateTime<Utc>,
+    /// 生成する時点の数（最初の時点を含む）
+    pub steps: usize,
+    /// 時点の間隔（時間）
+    #[serde(default = "default_step_hours")]
+    pub step_hours: u32,
+    pub tokens: Vec<SyntheticTokenConfig>,
+    /// トークン間の相関（共通ファクター 1 つで表すため 0〜1）
+    #[serde(default)]
+    pub correlation: f64,
+    #[serde(default)]
+    pub model: PriceModel,
+    /// 予測の対象時刻が何ステップ先か
+    #[serde(default = "default_prediction_horizon_steps")]
+    pub prediction_horizon_steps: usize,
+    /// 予測誤差（対数価格の標準偏差）。0 なら予測は対象時刻の真の価格と一致する
+    #[serde(default)]
+    pub prediction_error: f64,
+}
+
+/// 合成市場に含めるトークン 1 つ分の設定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyntheticTokenConfig {
+    pub account: TokenAccount,
+    pub decimals: u8,
+    /// 初期価格（NEAR/token）
+    pub initial_price: f64,
+    /// 年率ドリフト
+    #[serde(default)]
+    pub drift: f64,
+    /// 年率ボラティリティ
+    pub volatility: f64,
+    /// wNEAR とのプールの wNEAR 側残高（NEAR）
+    pub pool_depth_near: f64,
+}
+
+/// 価格過程のモデル
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PriceModel {
+    /// 幾何ブラウン運動
+    #[default]
+    Gbm,
+    /// 平常・ストレスの 2 状態を確率的に行き来する GBM
+    RegimeSwitching {
+        /// ストレス時のボラティリティ倍率
+        stressed_volatility_multiplier: f64,
+        /// ストレス時の年率ドリフト（トークン個別のドリフトを置き換える）
+        stressed_drift: f64,
+        /// 1 ステップごとに状態が切り替わる確率
+        switch_probability: f64,
+    },
+}
+
+/// ある時点で見えている予測
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntheticPrediction {
+    pub predicted_price: TokenPrice,
+    pub target_time: DateTime<Utc>,
+}
+
+/// 生成済みの合成市場
+///
+/// 時刻を指定した問い合わせは、その時刻以前で最も新しい時点の値を返す
+/// （DB のレート・プール取得と同じ扱い）。最初の時点より前は `None`。
+#[derive(Debug, Clone)]
+pub struct SyntheticMarket {
+    tokens: Vec<SyntheticTokenConfig>,
+    times: Vec<DateTime<Utc>>,
+    /// `prices[step][token]`（NEAR/token）
+    prices: Vec<Vec<f64>>,
+    stressed: Vec<bool>,
+    /// `predictions[step][token]`。対象時点が範囲外になる末尾のステップには無い
+    predictions: Vec<Vec<f64>>,
+    horizon: usize,
+}
+
+impl SyntheticMarket {
+    /// 設定から市場を生成する
+    pub fn generate(config: &SyntheticMarketConfig) -> Result<Self> {
+        validate(config)?;
+
+        let step = TimeDelta::hours(i64::from(config.step_hours));
+        let times: Vec<_> = (0..config.steps)
+            .map(|i| config.start + step * i as i32)
+            .collect();
+
+        let dt = f64::from(config.step_hours) / HOURS_PER_YEAR;
+        let common_weight = config.correlation.sqrt();
+        let own_weight = (1.0 - config.correlation).sqrt();
+
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut current: Vec<f64> = config.tokens.iter().map(|t| t.initial_price).collect();
+        let mut is_stressed = false;
+        let mut prices = vec![current.clone()];
+        let mut stressed = vec![is_stressed];
+        for _ in 1..config.steps {
+            if let PriceModel::RegimeSwitching {
+                switch_probability, ..
+            } = config.model
+                && rng.random::<f64>() < switch_probability
+            {
+                is_stressed = !is_stressed;
+            }
+            let factor = standard_normal(&mut rng);
+            for (price, token) in current.iter_mut().zip(&config.tokens) {
+                let (drift, volatility) = match config.model {
+                    PriceModel::RegimeSwitching {
+                        stressed_volatility_multiplier,
+                        stressed_drift,
+                        ..
+                    } if is_stressed => (
+                        stressed_drift,
+                        token.volatility * stressed_volatility_multiplier,
+                    ),
+                    _ => (token.drift, token.volatility),
+                };
+                let z = common_weight * factor + own_weight * standard_normal(&mut rng);
+                *price *= ((drift - volatility * volatility / 2.0) * dt
+                    + volatility * dt.sqrt() * z)
+                    .exp();
+            }
+            prices.push(current.clone());
+            stressed.push(is_stressed);
+        }
+
+        // 予測誤差は価格とは別系列の乱数にし、誤差の設定を変えても価格が変わらないようにする
+        let horizon = config.prediction_horizon_steps;
+        let mut prediction_rng = StdRng::seed_from_u64(config.seed.wrapping_add(1));
+        let predictions = prices
+            .get(horizon..)
+            .unwrap_or_default()
+            .iter()
+            .map(|target| {
+                target
+                    .iter()
+                    .map(|price| {
+                        price
+                            * (config.prediction_error * standard_normal(&mut prediction_rng)).exp()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            tokens: config.tokens.clone(),
+            times,
+            prices,
+            stressed,
+            predictions,
+            horizon,
+        })
+    }
+
+    /// 生成した時点の一覧
+    pub fn times(&self) -> &[DateTime<Utc>] {
+        &self.times
+    }
+
+    /// 市場に含まれるトークン（wNEAR は含まない）
+    pub fn tokens(&self) -> impl Iterator<Item = &TokenAccount> {
+        self.tokens.iter().map(|t| &t.account)
+    }
+
+    pub fn decimals(&self, token: &TokenAccount) -> Option<u8> {
+        self.token_index(token).map(|i| self.tokens[i].decimals)
+    }
+
+    /// `at` 以前で最も新しい時点の番号
+    pub fn step_at(&self, at: DateTime<Utc>) -> Option<usize> {
+        self.times.partition_point(|t| *t <= at).checked_sub(1)
+    }
+
+    /// `at` の時点がストレス状態か（GBM では常に `false`）
+    pub fn is_stressed_at(&self, at: DateTime<Utc>) -> Option<bool> {
+        self.step_at(at).map(|step| self.stressed[step])
+    }
+
+    pub fn price_at(&self, token: &TokenAccount, at: DateTime<Utc>) -> Option<TokenPrice> {
+        let price = self.prices[self.step_at(at)?][self.token_index(token)?];
+        BigDecimal::from_f64(price).map(TokenPrice::from_near_per_token)
+    }
+
+    pub fn rate_at(&self, token: &TokenAccount, at: DateTime<Utc>) -> Option<ExchangeRate> {
+        let price = self.price_at(token, at)?;
+        Some(ExchangeRate::from_price(&price, self.decimals(token)?))
+    }
+
+    /// `at` 時点のプール一覧
+    ///
+    /// トークンごとに wNEAR との SIMPLE_POOL を 1 つ作り、プール id はトークンの並び順
+    /// （0 から）とする。残高の比はその時点の価格と一致する。
+    pub fn pools_at(&self, at: DateTime<Utc>) -> Option<PoolInfoList> {
+        let step = self.step_at(at)?;
+        let timestamp = self.times[step].naive_utc();
+        let pools = self
+            .tokens
+            .iter()
+            .zip(&self.prices[step])
+            .enumerate()
+            .map(|(id, (token, &price))| {
+                let depth = BigDecimal::from_f64(token.pool_depth_near)?;
+                let price = BigDecimal::from_f64(price)?;
+                let wnear_amount = (&depth * pow10(NEAR_DECIMALS)).to_u128()?;
+                let token_amount = (&depth / &price * pow10(token.decimals)).to_u128()?;
+                let bare = PoolInfoBared {
+                    pool_kind: POOL_KIND_SIMPLE.to_string(),
+                    token_account_ids: vec![WNEAR_TOKEN.clone(), token.account.clone()],
+                    amounts: vec![U128(wnear_amount), U128(token_amount)],
+                    total_fee: POOL_TOTAL_FEE,
+                    shares_total_supply: U128(0),
+                    amp: 0,
+                };
+                Some(Arc::new(PoolInfo::new(id as u32, bare, timestamp)))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(PoolInfoList::new(pools))
+    }
+
+    /// `at` 時点で見えている予測（対象時刻は `prediction_horizon_steps` 先）
+    pub fn prediction_at(
+        &self,
+        token: &TokenAccount,
+        at: DateTime<Utc>,
+    ) -> Option<SyntheticPrediction> {
+        let step = self.step_at(at)?;
+        let predicted = *self.predictions.get(step)?.get(self.token_index(token)?)?;
+        Some(SyntheticPrediction {
+            predicted_price: TokenPrice::from_near_per_token(BigDecimal::from_f64(predicted)?),
+            target_time: self.times[step + self.horizon],
+        })
+    }
+
+    /// `until` 以前の全時点のレート・プール・予測を `store` に入れる
+    ///
+    /// 予測はその時点に作成されたものとして入れるため、各時点の戦略サイクルには
+    /// その時点で見えていた予測だけが渡る。
+    #[cfg(feature = "test-helpers")]
+    pub fn seed(&self, store: &MemoryStore, until: DateTime<Utc>) {
+        let quote = WNEAR_TOKEN.to_in();
+        for &at in self.times.iter().take_while(|t| **t <= until) {
+            let timestamp = at.naive_utc();
+            let rates: Vec<TokenRate> = self
+                .tokens()
+                .filter_map(|token| {
+                    Some(TokenRate {
+                        base: token.clone().into(),
+                        quote: quote.clone(),
+                        exchange_rate: self.rate_at(token, at)?,
+                        timestamp,
+                        rate_calc_near: 0,
+                        swap_path: None,
+                    })
+                })
+                .collect();
+            store.insert_token_rates(&rates);
+            if let Some(pools) = self.pools_at(at) {
+                store.insert_pools(&pools);
+            }
+            for token in self.tokens() {
+                let Some(prediction) = self.prediction_at(token, at) else {
+                    continue;
+                };
+                let record = NewPredictionRecord {
+                    token: token.to_string(),
+                    quote_token: quote.to_string(),
+                    predicted_price: prediction.predicted_price.as_bigdecimal().clone(),
+                    data_cutoff_time: timestamp,
+                    target_time: prediction.target_time.naive_utc(),
+                };
+                store.insert_prediction(record, timestamp);
+            }
+        }
+    }
+
+    fn token_index(&self, token: &TokenAccount) -> Option<usize> {
+        self.tokens.iter().position(|t| &t.account == token)
+    }
+}
+
+fn validate(config: &SyntheticMarketConfig) -> Result<()> {
+    anyhow::ensure!(config.steps > 0, "steps must be positive");
+    anyhow::ensure!(config.step_hours > 0, "step_hours must be positive");
+    anyhow::ensure!(
+        (0.0..=1.0).contains(&config.correlation),
+        "correlation must be within [0, 1]: {}",
+        config.correlation
+    );
+    anyhow::ensure!(
+        config.prediction_horizon_steps > 0,
+        "prediction_horizon_steps must be positive"
+    );
+    anyhow::ensure!(
+        config.prediction_error >= 0.0,
+        "prediction_error must not be negative: {}",
+        config.prediction_error
+    );
+    if let PriceModel::RegimeSwitching {
+        stressed_volatility_multiplier,
+        switch_probability,
+        ..
+    } = config.model
+    {
+        anyhow::ensure!(
+            stressed_volatility_multiplier >= 0.0,
+            "stressed_volatility_multiplier must not be negative: {stressed_volatility_multiplier}"
+        );
+        anyhow::ensure!(
+            (0.0..=1.0).contains(&switch_probability),
+            "switch_probability must be within [0, 1]: {switch_probability}"
+        );
+    }
+    for (i, token) in config.tokens.iter().enumerate() {
+        anyhow::ensure!(
+            token.account != *WNEAR_TOKEN,
+            "{} is the quote token and cannot be a synthetic token",
+            token.account
+        );
+        anyhow::ensure!(
+            config.tokens[..i]
+                .iter()
+                .all(|t| t.account != token.account),
+            "duplicate synthetic token: {}",
+            token.account
+        );
+        anyhow::ensure!(
+            token.initial_price > 0.0 && token.pool_depth_near > 0.0,
+            "initial_price and pool_depth_near must be positive: {}",
+            token.account
+        );
+        anyhow::ensure!(
+            token.volatility >= 0.0,
+            "volatility must not be negative: {}",
+            token.account
+        );
+    }
+    Ok(())
+}
+
+/// 標準正規乱数（Box-Muller、u1 は (0, 1]）
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1 = 1.0 - rng.random::<f64>();
+    let u2 = rng.random::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+}
+
+fn pow10(exp: u8) -> BigDecimal {
+    BigDecimal::new(1.into(), -i64::from(exp))
+}
+
+#[cfg(test)]
+mod tests;
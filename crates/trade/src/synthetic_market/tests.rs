@@ -0,0 +1,240 @@
+use super::*;
+use chrono::TimeZone;
+
+fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap()
+}
+
+fn token(name: &str, initial_price: f64, volatility: f64) -> SyntheticTokenConfig {
+    SyntheticTokenConfig {
+        account: name.parse().unwrap(),
+        decimals: 18,
+        initial_price,
+        drift: 0.0,
+        volatility,
+        pool_depth_near: 10_000.0,
+    }
+}
+
+fn config(tokens: Vec<SyntheticTokenConfig>) -> SyntheticMarketConfig {
+    SyntheticMarketConfig {
+        seed: 42,
+        start: start(),
+        steps: 30,
+        step_hours: 24,
+        tokens,
+        correlation: 0.0,
+        model: PriceModel::Gbm,
+        prediction_horizon_steps: 1,
+        prediction_error: 0.0,
+    }
+}
+
+fn account(name: &str) -> TokenAccount {
+    name.parse().unwrap()
+}
+
+fn prices_of(market: &SyntheticMarket, name: &str) -> Vec<TokenPrice> {
+    market
+        .times()
+        .iter()
+        .map(|t| market.price_at(&account(name), *t).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_generate_is_deterministic_per_seed() {
+    let cfg = config(vec![token("a.near", 0.5, 0.8), token("b.near", 2.0, 0.4)]);
+    let first = SyntheticMarket::generate(&cfg).unwrap();
+    let second = SyntheticMarket::generate(&cfg).unwrap();
+    assert_eq!(prices_of(&first, "a.near"), prices_of(&second, "a.near"));
+    assert_eq!(prices_of(&first, "b.near"), prices_of(&second, "b.near"));
+
+    let other = SyntheticMarket::generate(&SyntheticMarketConfig { seed: 43, ..cfg }).unwrap();
+    assert_ne!(prices_of(&first, "a.near"), prices_of(&other, "a.near"));
+}
+
+#[test]
+fn test_zero_volatility_keeps_price_constant() {
+    let market = SyntheticMarket::generate(&config(vec![token("a.near", 0.5, 0.0)])).unwrap();
+    let expected = TokenPrice::from_near_per_token(BigDecimal::from_f64(0.5).unwrap());
+    assert!(prices_of(&market, "a.near").iter().all(|p| *p == expected));
+}
+
+#[test]
+fn test_full_correlation_moves_identical_tokens_together() {
+    let cfg = SyntheticMarketConfig {
+        correlation: 1.0,
+        ..config(vec![token("a.near", 1.0, 0.6), token("b.near", 1.0, 0.6)])
+    };
+    let market = SyntheticMarket::generate(&cfg).unwrap();
+    let a = prices_of(&market, "a.near");
+    assert_eq!(a, prices_of(&market, "b.near"));
+    // 変動はしている
+    assert!(a.iter().any(|p| *p != a[0]));
+}
+
+#[test]
+fn test_regime_switching_with_certain_switch_alternates() {
+    let cfg = SyntheticMarketConfig {
+        model: PriceModel::RegimeSwitching {
+            stressed_volatility_multiplier: 3.0,
+            stressed_drift: -1.0,
+            switch_probability: 1.0,
+        },
+        ..config(vec![token("a.near", 1.0, 0.5)])
+    };
+    let market = SyntheticMarket::generate(&cfg).unwrap();
+    for (i, t) in market.times().iter().enumerate() {
+        assert_eq!(market.is_stressed_at(*t), Some(i % 2 == 1), "step {i}");
+    }
+}
+
+#[test]
+fn test_step_at_uses_latest_step_not_after() {
+    let market = SyntheticMarket::generate(&config(vec![token("a.near", 1.0, 0.5)])).unwrap();
+    assert_eq!(market.step_at(start() - TimeDelta::seconds(1)), None);
+    assert_eq!(market.step_at(start()), Some(0));
+    assert_eq!(market.step_at(start() + TimeDelta::hours(36)), Some(1));
+    // 最後の時点より後は最後の値のまま
+    assert_eq!(market.step_at(start() + TimeDelta::days(365)), Some(29));
+    assert!(
+        market
+            .price_at(&account("a.near"), start() - TimeDelta::seconds(1))
+            .is_none()
+    );
+    assert!(market.price_at(&account("unknown.near"), start()).is_none());
+}
+
+#[test]
+fn test_rate_matches_price_and_decimals() {
+    let market = SyntheticMarket::generate(&config(vec![token("a.near", 0.5, 0.5)])).unwrap();
+    let at = start() + TimeDelta::days(3);
+    let price = market.price_at(&account("a.near"), at).unwrap();
+    assert_eq!(
+        market.rate_at(&account("a.near"), at),
+        Some(ExchangeRate::from_price(&price, 18))
+    );
+}
+
+#[test]
+fn test_pools_reserves_follow_price() {
+    let market = SyntheticMarket::generate(&config(vec![
+        token("a.near", 0.5, 0.5),
+        token("b.near", 4.0, 0.5),
+    ]))
+    .unwrap();
+    let at = start() + TimeDelta::days(5);
+    let pools = market.pools_at(at).unwrap();
+    assert_eq!(pools.list().len(), 2);
+
+    let pool = pools.get(1).unwrap();
+    assert_eq!(pool.bare.token_account_ids[0], *WNEAR_TOKEN);
+    assert_eq!(pool.bare.token_account_ids[1], account("b.near"));
+    assert_eq!(pool.timestamp, market.times()[5].naive_utc());
+
+    // 残高比（NEAR/token）が価格と一致する
+    let wnear = BigDecimal::from(pool.bare.amounts[0].0) / pow10(NEAR_DECIMALS);
+    let tokens = BigDecimal::from(pool.bare.amounts[1].0) / pow10(18);
+    let implied = (wnear / tokens).to_f64().unwrap();
+    let price = market
+        .price_at(&account("b.near"), at)
+        .unwrap()
+        .as_bigdecimal()
+        .to_f64()
+        .unwrap();
+    assert!(
+        (implied - price).abs() / price < 1e-9,
+        "{implied} vs {price}"
+    );
+    assert!(market.pools_at(start() - TimeDelta::days(1)).is_none());
+}
+
+#[test]
+fn test_prediction_without_error_is_future_price() {
+    let cfg = SyntheticMarketConfig {
+        prediction_horizon_steps: 2,
+        ..config(vec![token("a.near", 1.0, 0.5)])
+    };
+    let market = SyntheticMarket::generate(&cfg).unwrap();
+    let times = market.times();
+
+    let prediction = market.prediction_at(&account("a.near"), times[3]).unwrap();
+    assert_eq!(prediction.target_time, times[5]);
+    assert_eq!(
+        prediction.predicted_price,
+        market.price_at(&account("a.near"), times[5]).unwrap()
+    );
+    // 対象時点が範囲外になる末尾には予測が無い
+    assert!(
+        market
+            .prediction_at(&account("a.near"), times[28])
+            .is_none()
+    );
+}
+
+#[test]
+fn test_prediction_error_does_not_change_prices() {
+    let cfg = config(vec![token("a.near", 1.0, 0.5)]);
+    let exact = SyntheticMarket::generate(&cfg).unwrap();
+    let noisy = SyntheticMarket::generate(&SyntheticMarketConfig {
+        prediction_error: 0.2,
+        ..cfg
+    })
+    .unwrap();
+    assert_eq!(prices_of(&exact, "a.near"), prices_of(&noisy, "a.near"));
+
+    let at = exact.times()[0];
+    assert_ne!(
+        exact.prediction_at(&account("a.near"), at),
+        noisy.prediction_at(&account("a.near"), at)
+    );
+}
+
+#[test]
+fn test_generate_rejects_invalid_config() {
+    let invalid = [
+        SyntheticMarketConfig {
+            correlation: 1.5,
+            ..config(vec![token("a.near", 1.0, 0.5)])
+        },
+        SyntheticMarketConfig {
+            steps: 0,
+            ..config(vec![token("a.near", 1.0, 0.5)])
+        },
+        config(vec![token("a.near", 0.0, 0.5)]),
+        config(vec![token("a.near", 1.0, 0.5), token("a.near", 2.0, 0.5)]),
+        config(vec![token(&WNEAR_TOKEN.to_string(), 1.0, 0.5)]),
+    ];
+    for cfg in invalid {
+        assert!(SyntheticMarket::generate(&cfg).is_err(), "{cfg:?}");
+    }
+}
+
+#[test]
+fn test_config_deserializes_with_defaults() {
+    let cfg: SyntheticMarketConfig = serde_json::from_value(serde_json::json!({
+        "seed": 7,
+        "start": "2025-06-01T00:00:00Z",
+        "steps": 10,
+        "tokens": [{
+            "account": "a.near",
+            "decimals": 6,
+            "initial_price": 0.2,
+            "volatility": 0.5,
+            "pool_depth_near": 1000.0
+        }],
+        "model": {
+            "kind": "regime_switching",
+            "stressed_volatility_multiplier": 2.0,
+            "stressed_drift": -0.5,
+            "switch_probability": 0.1
+        }
+    }))
+    .unwrap();
+    assert_eq!(cfg.step_hours, 24);
+    assert_eq!(cfg.prediction_horizon_steps, 1);
+    assert_eq!(cfg.correlation, 0.0);
+    assert_eq!(cfg.tokens[0].drift, 0.0);
+    assert!(matches!(cfg.model, PriceModel::RegimeSwitching { .. }));
+}
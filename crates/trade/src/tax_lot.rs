@@ -0,0 +1,429 @@
+//! 取引履歴の税務ロット
+//!
+//! `trade_transactions` を古い順に積み上げてトークンごとの取得ロットを作り、処分のたびに
+//! FIFO または平均取得原価法でロットを払い出して、NEAR 建てと法定通貨建ての売却額・取得原価・
+//! 損益を求める。会計士向けの CSV はこの結果をそのまま書き出す。
+//!
+//! - wrap.near でのトークン購入は取得。支払った wrap.near が取得原価
+//! - トークンの wrap.near への売却は処分。評価期間終了時の清算も同じ記録なので区別しない
+//! - トークン同士のスワップは処分として記録するが、売却額は払い出したロットの取得原価とし
+//!   （損益 0）、その取得原価を購入側の新しいロット（取得日時はスワップ時点）へ引き継ぐ
+//! - ハーベスト（`harvest_account_id` への wrap.near → ネイティブ NEAR の送金）は NEAR の処分
+//! - 受取量は約定実績（`actual_to_amount`）を優先し、無ければ送信時の推定出力を使う
+//! - 履歴で取得していない数量の処分は取得原価が分からないため、取得原価・損益を空にする
+//!
+//! `trade_transactions` にはトークンの decimals が無いため、数量は最小単位で出力する。
+
+use crate::Result;
+use crate::lot::{Lot, LotBook};
+use anyhow::Context;
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use chrono::{DateTime, Days, NaiveDate, NaiveDateTime};
+use common::types::token_account::TokenAccount;
+use persistence::trade_transaction::TradeTransaction;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+pub use crate::lot::LotMethod;
+
+/// ハーベストの送金先として記録されるネイティブ NEAR
+const NATIVE_NEAR: &str = "near";
+
+/// CSV に出す NEAR 建て金額の小数桁（yoctoNEAR まで）
+const NEAR_SCALE: i64 = 24;
+
+/// CSV に出す法定通貨建て金額の小数桁
+const FIAT_SCALE: i64 = 8;
+
+/// 履歴を DB から 1 回に読む取引の件数
+const HISTORY_PAGE_SIZE: i64 = 1_000;
+
+/// CSV の列
+pub const CSV_HEADER: &str = "timestamp,tx_id,kind,token,quantity,acquired_at,\
+proceeds_near,cost_basis_near,gain_near,near_fiat_price,proceeds_fiat,cost_basis_fiat,gain_fiat";
+
+/// 法定通貨建ての NEAR 価格の取得元
+pub trait FiatPriceSource: Send + Sync {
+    /// `at` 時点の 1 NEAR の価格（分からなければ `None`）
+    fn near_price(&self, at: NaiveDateTime) -> Option<BigDecimal>;
+}
+
+/// 法定通貨建ての金額を出さない
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoFiatPrices;
+
+impl FiatPriceSource for NoFiatPrices {
+    fn near_price(&self, _at: NaiveDateTime) -> Option<BigDecimal> {
+        None
+    }
+}
+
+/// ローカルの CSV（`日時,価格` の行）から読んだ NEAR 価格
+///
+/// 日時は `YYYY-MM-DD`、`YYYY-MM-DD HH:MM:SS` または RFC 3339（UTC に変換）。
+/// 1 行目が日時として読めなければヘッダとして読み飛ばす。
+/// `at` 以前で最も新しい価格を使い、それより前の価格が無ければ `None`。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CsvFiatPrices {
+    prices: Vec<(NaiveDateTime, BigDecimal)>,
+}
+
+fn parse_price_time(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.naive_utc());
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(dt);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+impl CsvFiatPrices {
+    pub fn parse(csv: &str) -> Result<Self> {
+        let mut prices = Vec::new();
+        for (index, line) in csv.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (time, price) = line
+                .split_once(',')
+                .with_context(|| format!("line {}: expected `time,price`", index + 1))?;
+            let Some(at) = parse_price_time(time.trim()) else {
+                if index == 0 {
+                    continue;
+                }
+                anyhow::bail!("line {}: invalid time: {}", index + 1, time.trim());
+            };
+            let price = BigDecimal::from_str(price.trim())
+                .with_context(|| format!("line {}: invalid price: {}", index + 1, price.trim()))?;
+            prices.push((at, price));
+        }
+        prices.sort_by_key(|(at, _)| *at);
+        Ok(Self { prices })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let csv = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read fiat prices: {}", path.display()))?;
+        Self::parse(&csv)
+    }
+}
+
+impl FiatPriceSource for CsvFiatPrices {
+    fn near_price(&self, at: NaiveDateTime) -> Option<BigDecimal> {
+        let index = self.prices.partition_point(|(time, _)| *time <= at);
+        index.checked_sub(1).map(|i| self.prices[i].1.clone())
+    }
+}
+
+/// 記録の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LotEventKind {
+    Acquisition,
+    Disposal,
+    Harvest,
+}
+
+impl LotEventKind {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Acquisition => "acquisition",
+            Self::Disposal => "disposal",
+            Self::Harvest => "harvest",
+        }
+    }
+}
+
+impl fmt::Display for LotEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 取得 1 件、または処分で払い出したロット 1 つ分の記録
+///
+/// NEAR 建ての金額は NEAR、法定通貨建ての金額は価格 CSV の通貨。分からない値は `None`。
+#[derive(Debug, Clone, PartialEq)]
+pub struct LotEvent {
+    pub timestamp: NaiveDateTime,
+    pub tx_id: String,
+    pub kind: LotEventKind,
+    pub token: String,
+    /// 最小単位（ハーベストは yoctoNEAR）
+    pub quantity: BigDecimal,
+    /// 払い出したロットの取得日時（FIFO の処分のみ）
+    pub acquired_at: Option<NaiveDateTime>,
+    pub proceeds_near: Option<BigDecimal>,
+    pub cost_basis_near: Option<BigDecimal>,
+    pub gain_near: Option<BigDecimal>,
+    /// `timestamp` 時点の 1 NEAR の法定通貨建て価格
+    pub near_fiat_price: Option<BigDecimal>,
+    pub proceeds_fiat: Option<BigDecimal>,
+    pub cost_basis_fiat: Option<BigDecimal>,
+    pub gain_fiat: Option<BigDecimal>,
+}
+
+fn sub_opt(a: Option<&BigDecimal>, b: Option<&BigDecimal>) -> Option<BigDecimal> {
+    Some(a? - b?)
+}
+
+fn mul_opt(a: Option<&BigDecimal>, b: Option<&BigDecimal>) -> Option<BigDecimal> {
+    Some(a? * b?)
+}
+
+fn yocto_to_near(yocto: &BigDecimal) -> BigDecimal {
+    yocto / BigDecimal::from(10u128.pow(24))
+}
+
+/// ロット（取得原価は NEAR）を積み上げながら取得・処分を記録する
+struct LotReport<'a> {
+    wnear: String,
+    prices: &'a dyn FiatPriceSource,
+    lots: LotBook<String>,
+    events: Vec<LotEvent>,
+}
+
+impl<'a> LotReport<'a> {
+    fn new(wnear: &TokenAccount, method: LotMethod, prices: &'a dyn FiatPriceSource) -> Self {
+        Self {
+            wnear: wnear.to_string(),
+            prices,
+            lots: LotBook::new(method),
+            events: Vec::new(),
+        }
+    }
+
+    fn event(&self, tx: &TradeTransaction, kind: LotEventKind, token: &str) -> LotEvent {
+        LotEvent {
+            timestamp: tx.timestamp,
+            tx_id: tx.tx_id.clone(),
+            kind,
+            token: token.to_string(),
+            quantity: BigDecimal::zero(),
+            acquired_at: None,
+            proceeds_near: None,
+            cost_basis_near: None,
+            gain_near: None,
+            near_fiat_price: self.prices.near_price(tx.timestamp),
+            proceeds_fiat: None,
+            cost_basis_fiat: None,
+            gain_fiat: None,
+        }
+    }
+
+    /// 払い出したロットごとに処分を記録する
+    fn dispose(
+        &mut self,
+        tx: &TradeTransaction,
+        lots: Vec<Lot>,
+        proceeds: impl Fn(&Lot) -> (Option<BigDecimal>, Option<BigDecimal>),
+    ) {
+        for lot in lots {
+            let (proceeds_near, proceeds_fiat) = proceeds(&lot);
+            let mut event = self.event(tx, LotEventKind::Disposal, &tx.from_token);
+            event.acquired_at = match self.lots.method() {
+                LotMethod::Fifo => lot.acquired_at,
+                LotMethod::AverageCost => None,
+            };
+            event.gain_near = sub_opt(proceeds_near.as_ref(), lot.cost.as_ref());
+            event.gain_fiat = sub_opt(proceeds_fiat.as_ref(), lot.cost_fiat.as_ref());
+            event.quantity = lot.quantity;
+            event.proceeds_near = proceeds_near;
+            event.cost_basis_near = lot.cost;
+            event.proceeds_fiat = proceeds_fiat;
+            event.cost_basis_fiat = lot.cost_fiat;
+            self.events.push(event);
+        }
+    }
+
+    fn apply(&mut self, tx: &TradeTransaction) {
+        let wnear = self.wnear.as_str();
+        let sold = tx.from_amount.as_bigdecimal().clone();
+        let received = tx
+            .actual_to_amount
+            .clone()
+            .unwrap_or_else(|| tx.to_amount.as_bigdecimal().clone());
+        if sold <= BigDecimal::zero() {
+            return;
+        }
+        let price = self.prices.near_price(tx.timestamp);
+        let is_near = |token: &str| token == wnear || token == NATIVE_NEAR;
+
+        match (is_near(&tx.from_token), is_near(&tx.to_token)) {
+            (true, true) => {
+                if tx.from_token == wnear && tx.to_token == NATIVE_NEAR {
+                    let amount = yocto_to_near(&sold);
+                    let mut event = self.event(tx, LotEventKind::Harvest, NATIVE_NEAR);
+                    event.quantity = sold;
+                    event.proceeds_fiat = mul_opt(Some(&amount), price.as_ref());
+                    event.cost_basis_near = Some(amount.clone());
+                    event.gain_near = Some(BigDecimal::zero());
+                    event.proceeds_near = Some(amount);
+                    self.events.push(event);
+                }
+            }
+            (true, false) => {
+                let cost = yocto_to_near(&sold);
+                let cost_fiat = mul_opt(Some(&cost), price.as_ref());
+                let mut event = self.event(tx, LotEventKind::Acquisition, &tx.to_token);
+                event.quantity = received.clone();
+                event.cost_basis_near = Some(cost.clone());
+                event.cost_basis_fiat = cost_fiat.clone();
+                self.events.push(event);
+                self.lots.acquire(
+                    tx.to_token.clone(),
+                    Lot {
+                        cost_fiat,
+                        ..Lot::new(Some(tx.timestamp), received, Some(cost))
+                    },
+                );
+            }
+            (false, true) => {
+                let lots = self.lots.take(&tx.from_token, &sold);
+                let total = yocto_to_near(&received);
+                self.dispose(tx, lots, |lot| {
+                    let near = &total * &lot.quantity / &sold;
+                    let fiat = mul_opt(Some(&near), price.as_ref());
+                    (Some(near), fiat)
+                });
+            }
+            (false, false) => {
+                let lots = self.lots.take(&tx.from_token, &sold);
+                let cost_near = lots.iter().try_fold(BigDecimal::zero(), |sum, lot| {
+                    lot.cost.as_ref().map(|c| sum + c)
+                });
+                let cost_fiat = lots.iter().try_fold(BigDecimal::zero(), |sum, lot| {
+                    lot.cost_fiat.as_ref().map(|c| sum + c)
+                });
+                self.dispose(tx, lots, |lot| (lot.cost.clone(), lot.cost_fiat.clone()));
+                let mut event = self.event(tx, LotEventKind::Acquisition, &tx.to_token);
+                event.quantity = received.clone();
+                event.cost_basis_near = cost_near.clone();
+                event.cost_basis_fiat = cost_fiat.clone();
+                self.events.push(event);
+                self.lots.acquire(
+                    tx.to_token.clone(),
+                    Lot {
+                        cost_fiat,
+                        ..Lot::new(Some(tx.timestamp), received, cost_near)
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// 取引（古い順）から取得・処分の記録を求める
+pub fn compute_lot_events(
+    transactions: &[TradeTransaction],
+    wnear: &TokenAccount,
+    method: LotMethod,
+    prices: &dyn FiatPriceSource,
+) -> Vec<LotEvent> {
+    let mut report = LotReport::new(wnear, method, prices);
+    for tx in transactions {
+        report.apply(tx);
+    }
+    report.events
+}
+
+/// `start`〜`end`（UTC、両端の日を含む）の取得・処分を求める
+///
+/// 期間より前に取得したロットも払い出せるよう、ロットは最初の取引から積み上げる。
+/// ロットの状態は保存しないため、呼び出しのたびに `end` までの全履歴を DB から読む。
+/// 読み込みは `HISTORY_PAGE_SIZE` 件ずつで、期間より前の記録はページごとに捨てるので、
+/// メモリは未払い出しのロットと期間内の記録の分で済む。
+pub async fn lot_events_between_async(
+    start: NaiveDate,
+    end: NaiveDate,
+    wnear: &TokenAccount,
+    method: LotMethod,
+    prices: &dyn FiatPriceSource,
+) -> Result<Vec<LotEvent>> {
+    let from = start.and_time(chrono::NaiveTime::MIN);
+    let until = end
+        .checked_add_days(Days::new(1))
+        .context("end date out of range")?
+        .and_time(chrono::NaiveTime::MIN);
+    let mut report = LotReport::new(wnear, method, prices);
+    let mut after = None;
+    loop {
+        let page =
+            TradeTransaction::find_page_before_async(until, after, HISTORY_PAGE_SIZE).await?;
+        for tx in &page {
+            report.apply(tx);
+        }
+        report.events.retain(|event| event.timestamp >= from);
+        if page.len() < HISTORY_PAGE_SIZE as usize {
+            break;
+        }
+        after = page.last().map(|tx| (tx.timestamp, tx.tx_id.clone()));
+    }
+    Ok(report.events)
+}
+
+/// 小数点以下 `scale` 桁に丸め、指数表記を使わずに書く（末尾の 0 は省く）
+fn plain(value: &BigDecimal, scale: i64) -> String {
+    let (digits, _) = value
+        .with_scale_round(scale, RoundingMode::HalfEven)
+        .as_bigint_and_exponent();
+    let digits = digits.to_string();
+    let (sign, digits) = match digits.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", digits.as_str()),
+    };
+    let scale = scale as usize;
+    let digits = format!("{:0>width$}", digits, width = scale + 1);
+    let (int, frac) = digits.split_at(digits.len() - scale);
+    let frac = frac.trim_end_matches('0');
+    if frac.is_empty() {
+        format!("{sign}{int}")
+    } else {
+        format!("{sign}{int}.{frac}")
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// 記録を CSV（ヘッダ付き）にする。分からない値は空欄
+pub fn to_csv(events: &[LotEvent]) -> String {
+    let time = |t: &NaiveDateTime| t.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let near = |v: &Option<BigDecimal>| v.as_ref().map_or(String::new(), |v| plain(v, NEAR_SCALE));
+    let fiat = |v: &Option<BigDecimal>| v.as_ref().map_or(String::new(), |v| plain(v, FIAT_SCALE));
+
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for event in events {
+        let fields = [
+            time(&event.timestamp),
+            csv_field(&event.tx_id),
+            event.kind.to_string(),
+            csv_field(&event.token),
+            plain(&event.quantity, 0),
+            event.acquired_at.as_ref().map_or(String::new(), time),
+            near(&event.proceeds_near),
+            near(&event.cost_basis_near),
+            near(&event.gain_near),
+            fiat(&event.near_fiat_price),
+            fiat(&event.proceeds_fiat),
+            fiat(&event.cost_basis_fiat),
+            fiat(&event.gain_fiat),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,236 @@
+use super::*;
+use common::types::TokenSmallestUnits;
+
+const ONE_NEAR: u128 = 10u128.pow(24);
+
+fn wnear() -> TokenAccount {
+    "wrap.near".parse().unwrap()
+}
+
+fn time(at: &str) -> NaiveDateTime {
+    NaiveDateTime::parse_from_str(at, "%Y-%m-%d %H:%M:%S").unwrap()
+}
+
+fn trade(at: &str, from: &str, from_amount: u128, to: &str, received: u128) -> TradeTransaction {
+    TradeTransaction {
+        tx_id: format!("tx-{at}"),
+        trade_batch_id: "batch".to_string(),
+        from_token: from.to_string(),
+        from_amount: TokenSmallestUnits::from_u128(from_amount),
+        to_token: to.to_string(),
+        to_amount: TokenSmallestUnits::from_u128(received),
+        timestamp: time(at),
+        evaluation_period_id: "period".to_string(),
+        actual_to_amount: Some(BigDecimal::from(received)),
+        gas_burnt: None,
+        gas_cost: None,
+    }
+}
+
+fn near(value: u128) -> Option<BigDecimal> {
+    Some(BigDecimal::from(value))
+}
+
+/// 1 NEAR = 2 の固定価格
+struct FixedPrice;
+
+impl FiatPriceSource for FixedPrice {
+    fn near_price(&self, _at: NaiveDateTime) -> Option<BigDecimal> {
+        Some(BigDecimal::from(2))
+    }
+}
+
+fn history() -> Vec<TradeTransaction> {
+    vec![
+        trade("2025-01-01 00:00:00", "wrap.near", ONE_NEAR, "a.near", 10),
+        trade(
+            "2025-01-02 00:00:00",
+            "wrap.near",
+            3 * ONE_NEAR,
+            "a.near",
+            10,
+        ),
+        // 15 単位を 6 NEAR で売却
+        trade(
+            "2025-01-03 00:00:00",
+            "a.near",
+            15,
+            "wrap.near",
+            6 * ONE_NEAR,
+        ),
+    ]
+}
+
+#[test]
+fn test_fifo_disposes_oldest_lots_first() {
+    let events = compute_lot_events(&history(), &wnear(), LotMethod::Fifo, &FixedPrice);
+    assert_eq!(events.len(), 4);
+    assert_eq!(events[0].kind, LotEventKind::Acquisition);
+    assert_eq!(events[0].cost_basis_near, near(1));
+    assert_eq!(events[0].cost_basis_fiat, near(2));
+
+    // 1 つ目のロット全量（10 単位、原価 1 NEAR）と 2 つ目の半分（5 単位、原価 1.5 NEAR）
+    let (first, second) = (&events[2], &events[3]);
+    assert_eq!(first.kind, LotEventKind::Disposal);
+    assert_eq!(first.quantity, BigDecimal::from(10));
+    assert_eq!(first.acquired_at, Some(time("2025-01-01 00:00:00")));
+    assert_eq!(first.proceeds_near, near(4));
+    assert_eq!(first.gain_near, near(3));
+    assert_eq!(first.gain_fiat, near(6));
+    assert_eq!(second.quantity, BigDecimal::from(5));
+    assert_eq!(second.acquired_at, Some(time("2025-01-02 00:00:00")));
+    assert_eq!(
+        second.cost_basis_near,
+        Some(BigDecimal::from_str("1.5").unwrap())
+    );
+    assert_eq!(second.gain_near, Some(BigDecimal::from_str("0.5").unwrap()));
+}
+
+#[test]
+fn test_average_cost_merges_lots() {
+    let events = compute_lot_events(&history(), &wnear(), LotMethod::AverageCost, &NoFiatPrices);
+    assert_eq!(events.len(), 3);
+    // 平均原価 0.2 NEAR/単位で 15 単位 → 原価 3 NEAR
+    let disposal = &events[2];
+    assert_eq!(disposal.quantity, BigDecimal::from(15));
+    assert_eq!(disposal.acquired_at, None);
+    assert_eq!(disposal.cost_basis_near, near(3));
+    assert_eq!(disposal.gain_near, near(3));
+    assert_eq!(disposal.near_fiat_price, None);
+    assert_eq!(disposal.gain_fiat, None);
+}
+
+#[test]
+fn test_token_swap_carries_cost_over() {
+    let events = compute_lot_events(
+        &[
+            trade(
+                "2025-01-01 00:00:00",
+                "wrap.near",
+                2 * ONE_NEAR,
+                "a.near",
+                10,
+            ),
+            trade("2025-01-02 00:00:00", "a.near", 10, "b.near", 40),
+            trade(
+                "2025-01-03 00:00:00",
+                "b.near",
+                40,
+                "wrap.near",
+                5 * ONE_NEAR,
+            ),
+        ],
+        &wnear(),
+        LotMethod::Fifo,
+        &NoFiatPrices,
+    );
+    let kinds: Vec<_> = events.iter().map(|e| (e.kind, e.token.as_str())).collect();
+    assert_eq!(
+        kinds,
+        [
+            (LotEventKind::Acquisition, "a.near"),
+            (LotEventKind::Disposal, "a.near"),
+            (LotEventKind::Acquisition, "b.near"),
+            (LotEventKind::Disposal, "b.near"),
+        ]
+    );
+    assert_eq!(events[1].gain_near, near(0));
+    assert_eq!(events[2].cost_basis_near, near(2));
+    assert_eq!(events[3].acquired_at, Some(time("2025-01-02 00:00:00")));
+    assert_eq!(events[3].gain_near, near(3));
+}
+
+#[test]
+fn test_untracked_quantity_has_unknown_cost() {
+    let events = compute_lot_events(
+        &[
+            trade("2025-01-01 00:00:00", "wrap.near", ONE_NEAR, "a.near", 10),
+            trade(
+                "2025-01-02 00:00:00",
+                "a.near",
+                20,
+                "wrap.near",
+                4 * ONE_NEAR,
+            ),
+        ],
+        &wnear(),
+        LotMethod::Fifo,
+        &NoFiatPrices,
+    );
+    let unknown = &events[2];
+    assert_eq!(unknown.quantity, BigDecimal::from(10));
+    assert_eq!(unknown.proceeds_near, near(2));
+    assert_eq!(unknown.cost_basis_near, None);
+    assert_eq!(unknown.gain_near, None);
+}
+
+#[test]
+fn test_harvest_is_near_disposal() {
+    let events = compute_lot_events(
+        &[trade(
+            "2025-01-01 00:00:00",
+            "wrap.near",
+            3 * ONE_NEAR,
+            "near",
+            3 * ONE_NEAR,
+        )],
+        &wnear(),
+        LotMethod::Fifo,
+        &FixedPrice,
+    );
+    assert_eq!(events.len(), 1);
+    let harvest = &events[0];
+    assert_eq!(harvest.kind, LotEventKind::Harvest);
+    assert_eq!(harvest.token, "near");
+    assert_eq!(harvest.proceeds_near, near(3));
+    assert_eq!(harvest.gain_near, near(0));
+    assert_eq!(harvest.proceeds_fiat, near(6));
+    assert_eq!(harvest.cost_basis_fiat, None);
+}
+
+#[test]
+fn test_csv_prices_use_latest_before() {
+    let prices =
+        CsvFiatPrices::parse("date,usd\n2025-01-02,4.5\n\n2025-01-01T12:00:00Z,3\n").unwrap();
+    assert_eq!(prices.near_price(time("2025-01-01 00:00:00")), None);
+    assert_eq!(prices.near_price(time("2025-01-01 18:00:00")), near(3));
+    assert_eq!(
+        prices.near_price(time("2025-01-05 00:00:00")),
+        Some(BigDecimal::from_str("4.5").unwrap())
+    );
+}
+
+#[test]
+fn test_csv_prices_reject_invalid_rows() {
+    assert!(CsvFiatPrices::parse("2025-01-01,1\nyesterday,2\n").is_err());
+    assert!(CsvFiatPrices::parse("2025-01-01,abc\n").is_err());
+    assert!(CsvFiatPrices::parse("2025-01-01\n").is_err());
+}
+
+#[test]
+fn test_plain_never_uses_exponent() {
+    assert_eq!(
+        plain(&BigDecimal::from(ONE_NEAR), 0),
+        "1000000000000000000000000"
+    );
+    assert_eq!(
+        plain(&yocto_to_near(&BigDecimal::from(1)), NEAR_SCALE),
+        "0.000000000000000000000001"
+    );
+    assert_eq!(plain(&BigDecimal::from_str("-1.50").unwrap(), 8), "-1.5");
+    assert_eq!(plain(&BigDecimal::from_str("0.125").unwrap(), 2), "0.12");
+}
+
+#[test]
+fn test_to_csv_leaves_unknown_values_empty() {
+    let mut events = compute_lot_events(&history(), &wnear(), LotMethod::Fifo, &NoFiatPrices);
+    events[0].tx_id = "a,b".to_string();
+    let csv = to_csv(&events[..1]);
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some(CSV_HEADER));
+    assert_eq!(
+        lines.next(),
+        Some("2025-01-01T00:00:00Z,\"a,b\",acquisition,a.near,10,,,1,,,,,")
+    );
+    assert_eq!(lines.next(), None);
+}
@@ -2,7 +2,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let protos = &[
         "proto/zaciraci/v1/health.proto",
         "proto/zaciraci/v1/config.proto",
+        "proto/zaciraci/v1/job.proto",
         "proto/zaciraci/v1/portfolio.proto",
+        "proto/zaciraci/v1/reconciliation.proto",
+        "proto/zaciraci/v1/arbitrage.proto",
     ];
 
     tonic_prost_build::configure()
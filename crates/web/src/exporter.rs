@@ -0,0 +1,46 @@
+//! Prometheus の scrape 用 HTTP エンドポイント（`GET /metrics`）
+
+use axum::Router;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use logging::*;
+
+pub(crate) fn router() -> Router {
+    Router::new().route("/metrics", get(metrics_handler))
+}
+
+async fn metrics_handler() -> Response {
+    persistence::connection_pool::record_metrics();
+
+    match metrics::encode() {
+        Ok(body) => ([(header::CONTENT_TYPE, metrics::content_type())], body).into_response(),
+        Err(e) => {
+            let log = DEFAULT.new(o!("function" => "metrics_handler"));
+            warn!(log, "failed to encode metrics"; "error" => %e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metrics_handler_returns_text_format() {
+        metrics::trade::record_swap(true);
+
+        let response = metrics_handler().await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+        assert!(content_type.starts_with("text/plain"));
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("zaciraci_trade_swaps_total"));
+        assert!(body.contains("zaciraci_db_pool_connections"));
+    }
+}
@@ -1,5 +1,6 @@
 #![deny(warnings)]
 
+mod exporter;
 mod services;
 
 pub mod proto {
@@ -13,12 +14,18 @@ use anyhow::Context;
 use google_auth::GoogleAuthenticator;
 use grpc_auth::AuthInterceptor;
 use logging::*;
+use proto::arbitrage_service_server::ArbitrageServiceServer;
 use proto::config_service_server::ConfigServiceServer;
 use proto::health_service_server::HealthServiceServer;
+use proto::job_service_server::JobServiceServer;
 use proto::portfolio_service_server::PortfolioServiceServer;
+use proto::reconciliation_service_server::ReconciliationServiceServer;
+use services::arbitrage::ArbitrageServiceImpl;
 use services::config::ConfigServiceImpl;
 use services::health::HealthServiceImpl;
+use services::job::JobServiceImpl;
 use services::portfolio::PortfolioServiceImpl;
+use services::reconciliation::ReconciliationServiceImpl;
 use tonic::service::interceptor::InterceptedService;
 
 /// Start the gRPC / grpc-web server.
@@ -98,6 +105,18 @@ pub async fn serve(port: u16) -> anyhow::Result<()> {
     );
     let portfolio_svc = InterceptedService::new(
         PortfolioServiceServer::new(PortfolioServiceImpl),
+        auth_interceptor.clone(),
+    );
+    let job_svc = InterceptedService::new(
+        JobServiceServer::new(JobServiceImpl::new(scheduler::global())),
+        auth_interceptor.clone(),
+    );
+    let reconciliation_svc = InterceptedService::new(
+        ReconciliationServiceServer::new(ReconciliationServiceImpl),
+        auth_interceptor.clone(),
+    );
+    let arbitrage_svc = InterceptedService::new(
+        ArbitrageServiceServer::new(ArbitrageServiceImpl),
         auth_interceptor,
     );
 
@@ -109,9 +128,34 @@ pub async fn serve(port: u16) -> anyhow::Result<()> {
         .add_service(health_svc)
         .add_service(config_svc)
         .add_service(portfolio_svc)
+        .add_service(job_svc)
+        .add_service(reconciliation_svc)
+        .add_service(arbitrage_svc)
         .serve(addr)
         .await
         .context("gRPC server failed")?;
 
     Ok(())
 }
+
+/// Start the Prometheus metrics endpoint (`GET /metrics`).
+///
+/// Served on its own port, separate from the gRPC server, and without
+/// authentication: the port must only be reachable by the scraper (private
+/// network / fly.io internal), never through the public TLS proxy.
+pub async fn serve_metrics(port: u16) -> anyhow::Result<()> {
+    let log = DEFAULT.new(o!("module" => "web::metrics"));
+
+    let addr = SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 0], port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind metrics endpoint on {addr}"))?;
+
+    info!(log, "metrics endpoint starting"; "addr" => %addr);
+
+    axum::serve(listener, exporter::router())
+        .await
+        .context("metrics endpoint failed")?;
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
+pub(crate) mod arbitrage;
 pub(crate) mod auth;
 pub(crate) mod config;
+pub(crate) mod dates;
 pub(crate) mod health;
+pub(crate) mod job;
 pub(crate) mod portfolio;
+pub(crate) mod reconciliation;
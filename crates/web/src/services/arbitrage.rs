@@ -0,0 +1,59 @@
+use crate::proto::arbitrage_service_server::ArbitrageService;
+use crate::proto::{GetDailyReportRequest, GetDailyReportResponse};
+use crate::services::auth::require_reader;
+use crate::services::dates::parse_date_range;
+use chrono::NaiveDate;
+use logging::{DEFAULT, o, warn};
+use persistence::arbitrage_attempt::{ArbitrageAttempt, DailyArbitrageSummary, total_net_profit};
+use tonic::{Request, Response, Status};
+
+/// 1 回の問い合わせで集計する最大日数
+const MAX_REPORT_DAYS: i64 = 366;
+
+fn parse_range(req: &GetDailyReportRequest) -> Result<(NaiveDate, NaiveDate), Status> {
+    parse_date_range(&req.start_date, &req.end_date, MAX_REPORT_DAYS)
+}
+
+fn day_to_proto(day: &DailyArbitrageSummary) -> crate::proto::DailyArbitrageReport {
+    crate::proto::DailyArbitrageReport {
+        date: day.date.format("%Y-%m-%d").to_string(),
+        attempts: day.attempts,
+        successes: day.successes,
+        gross_profit: day.gross_profit.to_string(),
+        gas_cost: day.gas_cost.to_string(),
+        net_profit: day.net_profit.to_string(),
+    }
+}
+
+pub struct ArbitrageServiceImpl;
+
+#[cfg(test)]
+mod tests;
+
+#[tonic::async_trait]
+impl ArbitrageService for ArbitrageServiceImpl {
+    async fn get_daily_report(
+        &self,
+        request: Request<GetDailyReportRequest>,
+    ) -> Result<Response<GetDailyReportResponse>, Status> {
+        require_reader(&request)?;
+        let (start, end) = parse_range(request.get_ref())?;
+        let log = DEFAULT.new(o!(
+            "function" => "get_daily_report",
+            "start" => format!("{start}"),
+            "end" => format!("{end}"),
+        ));
+
+        let days = ArbitrageAttempt::daily_summaries_async(start, end)
+            .await
+            .map_err(|e| {
+                warn!(log, "failed to get arbitrage summaries"; "error" => %e);
+                Status::internal("internal error")
+            })?;
+
+        Ok(Response::new(GetDailyReportResponse {
+            total_net_profit: total_net_profit(&days).to_string(),
+            days: days.iter().map(day_to_proto).collect(),
+        }))
+    }
+}
@@ -0,0 +1,78 @@
+use super::*;
+use bigdecimal::BigDecimal;
+use common::types::{Email, Role};
+use grpc_auth::AuthenticatedUser;
+
+fn request_as<T>(body: T, role: Role) -> Request<T> {
+    let mut req = Request::new(body);
+    req.extensions_mut().insert(AuthenticatedUser::new(
+        Email::new("tester@example.com").unwrap(),
+        role,
+    ));
+    req
+}
+
+fn range(start: &str, end: &str) -> GetDailyReportRequest {
+    GetDailyReportRequest {
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+    }
+}
+
+#[test]
+fn test_parse_range_accepts_inclusive_dates() {
+    let (start, end) = parse_range(&range("2026-10-01", "2026-10-01")).unwrap();
+    assert_eq!(start, end);
+    assert_eq!(start, NaiveDate::from_ymd_opt(2026, 10, 1).unwrap());
+}
+
+#[test]
+fn test_parse_range_rejects_invalid_input() {
+    for (start, end) in [
+        ("2026/10/01", "2026-10-02"),
+        ("2026-10-01", ""),
+        ("2026-10-02", "2026-10-01"),
+        ("2025-01-01", "2026-01-01"),
+    ] {
+        let err = parse_range(&range(start, end)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument, "{start}..{end}");
+    }
+}
+
+#[test]
+fn test_day_to_proto_formats_amounts() {
+    let day = DailyArbitrageSummary {
+        date: NaiveDate::from_ymd_opt(2026, 10, 3).unwrap(),
+        attempts: 4,
+        successes: 3,
+        gross_profit: BigDecimal::from(1_500),
+        gas_cost: BigDecimal::from(2_000),
+        net_profit: BigDecimal::from(-500),
+    };
+
+    let proto = day_to_proto(&day);
+    assert_eq!(proto.date, "2026-10-03");
+    assert_eq!(proto.attempts, 4);
+    assert_eq!(proto.successes, 3);
+    assert_eq!(proto.gross_profit, "1500");
+    assert_eq!(proto.gas_cost, "2000");
+    assert_eq!(proto.net_profit, "-500");
+}
+
+#[tokio::test]
+async fn test_get_daily_report_requires_auth() {
+    let err = ArbitrageServiceImpl
+        .get_daily_report(Request::new(range("2026-10-01", "2026-10-02")))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_get_daily_report_validates_before_querying() {
+    let err = ArbitrageServiceImpl
+        .get_daily_report(request_as(range("2026-10-02", "2026-10-01"), Role::Reader))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+}
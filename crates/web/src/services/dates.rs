@@ -0,0 +1,34 @@
+//! リクエストの日付パラメータの検証
+
+use chrono::NaiveDate;
+use tonic::Status;
+
+/// `YYYY-MM-DD` を解釈する。不正なら `field` を示す `InvalidArgument`
+pub(crate) fn parse_date(field: &str, value: &str) -> Result<NaiveDate, Status> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| Status::invalid_argument(format!("{field} must be YYYY-MM-DD: {value}")))
+}
+
+/// `start_date` / `end_date`（両端を含む）を解釈し、`max_days` 日未満の範囲に限る
+pub(crate) fn parse_date_range(
+    start_date: &str,
+    end_date: &str,
+    max_days: i64,
+) -> Result<(NaiveDate, NaiveDate), Status> {
+    let start = parse_date("start_date", start_date)?;
+    let end = parse_date("end_date", end_date)?;
+    if end < start {
+        return Err(Status::invalid_argument(
+            "end_date must not be before start_date",
+        ));
+    }
+    if (end - start).num_days() >= max_days {
+        return Err(Status::invalid_argument(format!(
+            "date range must not exceed {max_days} days"
+        )));
+    }
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,20 @@
+use super::*;
+use tonic::Code;
+
+#[test]
+fn test_parse_date_names_field() {
+    assert_eq!(
+        parse_date("start_date", "2026-10-01").unwrap(),
+        NaiveDate::from_ymd_opt(2026, 10, 1).unwrap()
+    );
+    let err = parse_date("end_date", "2026/10/01").unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert!(err.message().contains("end_date"));
+}
+
+#[test]
+fn test_parse_date_range_limits_days() {
+    assert!(parse_date_range("2026-01-01", "2026-01-02", 2).is_ok());
+    assert!(parse_date_range("2026-01-01", "2026-01-03", 2).is_err());
+    assert!(parse_date_range("2026-01-02", "2026-01-01", 2).is_err());
+}
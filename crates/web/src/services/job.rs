@@ -0,0 +1,143 @@
+use crate::proto::job_service_server::JobService;
+use crate::proto::{
+    ListJobRunsRequest, ListJobRunsResponse, ListJobsRequest, ListJobsResponse, TriggerJobRequest,
+    TriggerJobResponse,
+};
+use crate::services::auth::{require_reader, require_writer};
+use logging::{DEFAULT, info, o, warn};
+use persistence::job_run::JobRun;
+use scheduler::{Scheduler, TriggerOutcome};
+use tonic::{Request, Response, Status};
+
+fn naive_to_timestamp(dt: chrono::NaiveDateTime) -> prost_types::Timestamp {
+    let utc = dt.and_utc();
+    prost_types::Timestamp {
+        seconds: utc.timestamp(),
+        nanos: 0,
+    }
+}
+
+fn job_info_to_proto(info: scheduler::JobInfo) -> crate::proto::Job {
+    crate::proto::Job {
+        name: info.name,
+        schedule: info.schedule,
+        catch_up: info.catch_up.to_string(),
+        running: info.running,
+        next_run: info.next_run.map(|t| naive_to_timestamp(t.naive_utc())),
+    }
+}
+
+fn job_run_to_proto(run: JobRun) -> crate::proto::JobRun {
+    crate::proto::JobRun {
+        id: run.id,
+        job_name: run.job_name,
+        trigger: run.trigger,
+        scheduled_at: run.scheduled_at.map(naive_to_timestamp),
+        started_at: Some(naive_to_timestamp(run.started_at)),
+        finished_at: run.finished_at.map(naive_to_timestamp),
+        status: run.status,
+        error: run.error,
+    }
+}
+
+pub struct JobServiceImpl {
+    scheduler: &'static Scheduler,
+}
+
+impl JobServiceImpl {
+    pub fn new(scheduler: &'static Scheduler) -> Self {
+        Self { scheduler }
+    }
+}
+
+#[cfg(test)]
+mod tests;
+
+#[tonic::async_trait]
+impl JobService for JobServiceImpl {
+    async fn list_jobs(
+        &self,
+        request: Request<ListJobsRequest>,
+    ) -> Result<Response<ListJobsResponse>, Status> {
+        require_reader(&request)?;
+
+        let jobs = self
+            .scheduler
+            .jobs()
+            .into_iter()
+            .map(job_info_to_proto)
+            .collect();
+
+        Ok(Response::new(ListJobsResponse { jobs }))
+    }
+
+    async fn list_job_runs(
+        &self,
+        request: Request<ListJobRunsRequest>,
+    ) -> Result<Response<ListJobRunsResponse>, Status> {
+        require_reader(&request)?;
+        let req = request.get_ref();
+        let page = i64::from(req.page.max(0));
+        let page_size = i64::from(req.page_size.clamp(1, 200));
+        let job_name = (!req.job_name.is_empty()).then(|| req.job_name.clone());
+
+        let (runs, total_count) = tokio::try_join!(
+            JobRun::list_recent_async(job_name.clone(), page, page_size),
+            JobRun::count_async(job_name),
+        )
+        .map_err(|e| {
+            let log = DEFAULT.new(o!("function" => "list_job_runs"));
+            warn!(log, "failed to get job runs"; "error" => %e);
+            Status::internal("internal error")
+        })?;
+
+        let runs = runs.into_iter().map(job_run_to_proto).collect();
+
+        Ok(Response::new(ListJobRunsResponse { runs, total_count }))
+    }
+
+    async fn trigger_job(
+        &self,
+        request: Request<TriggerJobRequest>,
+    ) -> Result<Response<TriggerJobResponse>, Status> {
+        let user = require_writer(&request)?;
+        let job_name = &request.get_ref().job_name;
+
+        if job_name.is_empty() {
+            return Err(Status::invalid_argument("job_name must not be empty"));
+        }
+        if !self.scheduler.jobs().iter().any(|j| &j.name == job_name) {
+            return Err(Status::not_found(format!("unknown job: {job_name}")));
+        }
+
+        let log = DEFAULT.new(o!(
+            "function" => "trigger_job",
+            "job_name" => job_name.clone(),
+            "user" => user.masked_email(),
+        ));
+
+        let outcome = self.scheduler.trigger(job_name).await.map_err(|e| {
+            warn!(log, "failed to trigger job"; "error" => %e);
+            Status::internal("internal error")
+        })?;
+
+        let response = match outcome {
+            TriggerOutcome::Started { run_id } => {
+                info!(log, "job triggered manually"; "run_id" => run_id);
+                TriggerJobResponse {
+                    run_id: Some(run_id),
+                    already_running: false,
+                }
+            }
+            TriggerOutcome::AlreadyRunning => {
+                info!(log, "manual trigger skipped, job already running");
+                TriggerJobResponse {
+                    run_id: None,
+                    already_running: true,
+                }
+            }
+        };
+
+        Ok(Response::new(response))
+    }
+}
@@ -0,0 +1,157 @@
+use super::*;
+use common::types::{Email, Role};
+use grpc_auth::AuthenticatedUser;
+use scheduler::Job;
+use serial_test::serial;
+
+fn request_as<T>(body: T, role: Role) -> Request<T> {
+    let mut req = Request::new(body);
+    req.extensions_mut().insert(AuthenticatedUser::new(
+        Email::new("tester@example.com").unwrap(),
+        role,
+    ));
+    req
+}
+
+fn test_scheduler(job_name: &str) -> &'static Scheduler {
+    let scheduler = Box::leak(Box::new(Scheduler::new()));
+    scheduler
+        .register(Job::new(
+            job_name,
+            "0 0 0 * * *".parse().unwrap(),
+            || async { Ok(()) },
+        ))
+        .unwrap();
+    scheduler
+}
+
+#[test]
+fn test_job_run_to_proto_maps_optional_fields() {
+    let started = chrono::Utc::now().naive_utc();
+    let run = JobRun {
+        id: 7,
+        job_name: "auto_trade".to_string(),
+        trigger: "manual".to_string(),
+        scheduled_at: None,
+        started_at: started,
+        finished_at: None,
+        status: "running".to_string(),
+        error: None,
+    };
+    let proto = job_run_to_proto(run);
+    assert_eq!(proto.id, 7);
+    assert_eq!(proto.trigger, "manual");
+    assert!(proto.scheduled_at.is_none());
+    assert_eq!(
+        proto.started_at.unwrap().seconds,
+        started.and_utc().timestamp()
+    );
+    assert!(proto.finished_at.is_none());
+    assert_eq!(proto.status, "running");
+}
+
+#[tokio::test]
+async fn test_list_jobs_requires_auth() {
+    let svc = JobServiceImpl::new(test_scheduler("web_test_list_auth"));
+    let err = svc
+        .list_jobs(Request::new(ListJobsRequest {}))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_list_jobs_returns_registered_jobs() {
+    let svc = JobServiceImpl::new(test_scheduler("web_test_list"));
+    let resp = svc
+        .list_jobs(request_as(ListJobsRequest {}, Role::Reader))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(resp.jobs.len(), 1);
+    assert_eq!(resp.jobs[0].name, "web_test_list");
+    assert_eq!(resp.jobs[0].catch_up, "skip");
+    assert!(!resp.jobs[0].running);
+    assert!(resp.jobs[0].next_run.is_some());
+}
+
+#[tokio::test]
+async fn test_trigger_job_requires_writer() {
+    let svc = JobServiceImpl::new(test_scheduler("web_test_trigger_reader"));
+    let err = svc
+        .trigger_job(request_as(
+            TriggerJobRequest {
+                job_name: "web_test_trigger_reader".to_string(),
+            },
+            Role::Reader,
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn test_trigger_job_unknown_name() {
+    let svc = JobServiceImpl::new(test_scheduler("web_test_trigger_unknown"));
+    let err = svc
+        .trigger_job(request_as(
+            TriggerJobRequest {
+                job_name: "no_such_job".to_string(),
+            },
+            Role::Writer,
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+#[serial(job_run)]
+async fn test_trigger_job_and_list_runs() {
+    let name = "web_test_trigger_and_list";
+    JobRun::delete_by_job_name_async(name.to_string())
+        .await
+        .unwrap();
+    let svc = JobServiceImpl::new(test_scheduler(name));
+
+    let resp = svc
+        .trigger_job(request_as(
+            TriggerJobRequest {
+                job_name: name.to_string(),
+            },
+            Role::Writer,
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!resp.already_running);
+    let run_id = resp.run_id.expect("run id should be returned");
+
+    let runs = svc
+        .list_job_runs(request_as(
+            ListJobRunsRequest {
+                job_name: name.to_string(),
+                page: 0,
+                page_size: 10,
+            },
+            Role::Reader,
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(runs.total_count, 1);
+    assert_eq!(runs.runs[0].id, run_id);
+    assert_eq!(runs.runs[0].trigger, "manual");
+
+    // 実行完了を待ってから後始末する
+    for _ in 0..100 {
+        let run = JobRun::get_by_id_async(run_id).await.unwrap().unwrap();
+        if run.finished_at.is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    JobRun::delete_by_job_name_async(name.to_string())
+        .await
+        .unwrap();
+}
@@ -1,18 +1,26 @@
 use crate::proto::portfolio_service_server::PortfolioService;
 use crate::proto::{
-    GetEvaluationPeriodsRequest, GetEvaluationPeriodsResponse, GetPortfolioHoldingsRequest,
-    GetPortfolioHoldingsResponse,
+    ExportTaxLotsRequest, ExportTaxLotsResponse, GetEvaluationPeriodsRequest,
+    GetEvaluationPeriodsResponse, GetPortfolioHoldingsRequest, GetPortfolioHoldingsResponse,
 };
 use crate::services::auth::require_reader;
+use crate::services::dates::parse_date_range;
+use chrono::NaiveDate;
 use common::types::near_units::YoctoValue;
 use common::types::token_account::{TokenAccount, TokenInAccount, TokenOutAccount};
 use common::types::token_types::ExchangeRate;
 use logging::{DEFAULT, o, warn};
 use persistence::evaluation_period::EvaluationPeriod;
-use persistence::portfolio_holding::{DbPortfolioHolding, PortfolioHolding};
+use persistence::portfolio_holding::{DbPortfolioHolding, PortfolioHolding, TokenPnl};
 use persistence::token_rate::TokenRate;
 use std::collections::HashMap;
 use tonic::{Request, Response, Status};
+use trade::tax_lot::{
+    CsvFiatPrices, FiatPriceSource, LotMethod, NoFiatPrices, lot_events_between_async, to_csv,
+};
+
+/// 1 回の税務ロット出力で対象にする最大日数
+const MAX_TAX_LOT_DAYS: i64 = 366;
 
 fn naive_to_timestamp(dt: chrono::NaiveDateTime) -> prost_types::Timestamp {
     let utc = dt.and_utc();
@@ -90,13 +98,34 @@ fn db_holding_to_proto(
         total_yocto = total_yocto + yocto;
     }
 
+    let token_pnl = holding
+        .parse_token_pnl()
+        .map_err(|e| {
+            let log = DEFAULT.new(o!("function" => "db_holding_to_proto"));
+            warn!(log, "failed to parse token pnl"; "error" => %e);
+            Status::internal("internal error")
+        })?
+        .into_iter()
+        .map(token_pnl_to_proto)
+        .collect();
+
     Ok(crate::proto::PortfolioHolding {
         timestamp: Some(naive_to_timestamp(ts)),
         token_holdings,
         total_value_wnear: total_yocto.to_string(),
+        token_pnl,
     })
 }
 
+fn token_pnl_to_proto(pnl: TokenPnl) -> crate::proto::TokenPnl {
+    crate::proto::TokenPnl {
+        token: pnl.token.to_string(),
+        cost_basis: pnl.cost_basis.to_string(),
+        realized_pnl: pnl.realized_pnl.to_string(),
+        unrealized_pnl: pnl.unrealized_pnl.map(|u| u.to_string()),
+    }
+}
+
 /// holding をパースしてレートを取得
 async fn parse_and_fetch_rates(
     holding: &DbPortfolioHolding,
@@ -132,6 +161,30 @@ async fn parse_and_fetch_rates(
     Ok((parsed, rates))
 }
 
+fn parse_tax_lot_range(req: &ExportTaxLotsRequest) -> Result<(NaiveDate, NaiveDate), Status> {
+    parse_date_range(&req.start_date, &req.end_date, MAX_TAX_LOT_DAYS)
+}
+
+/// 未指定なら FIFO
+fn parse_lot_method(method: &str) -> Result<LotMethod, Status> {
+    if method.is_empty() {
+        return Ok(LotMethod::default());
+    }
+    method
+        .parse()
+        .map_err(|e: anyhow::Error| Status::invalid_argument(e.to_string()))
+}
+
+/// 未指定なら法定通貨建ての列を出さない
+fn parse_fiat_prices(csv: &str) -> Result<Box<dyn FiatPriceSource>, Status> {
+    if csv.trim().is_empty() {
+        return Ok(Box::new(NoFiatPrices));
+    }
+    let prices = CsvFiatPrices::parse(csv)
+        .map_err(|e| Status::invalid_argument(format!("invalid fiat_prices_csv: {e}")))?;
+    Ok(Box::new(prices))
+}
+
 pub struct PortfolioServiceImpl;
 
 #[cfg(test)]
@@ -198,4 +251,29 @@ impl PortfolioService for PortfolioServiceImpl {
 
         Ok(Response::new(GetPortfolioHoldingsResponse { holdings }))
     }
+
+    async fn export_tax_lots(
+        &self,
+        request: Request<ExportTaxLotsRequest>,
+    ) -> Result<Response<ExportTaxLotsResponse>, Status> {
+        require_reader(&request)?;
+        let req = request.get_ref();
+        let (start, end) = parse_tax_lot_range(req)?;
+        let method = parse_lot_method(&req.method)?;
+        let prices = parse_fiat_prices(&req.fiat_prices_csv)?;
+        let wnear = wnear_token();
+
+        let events = lot_events_between_async(start, end, &wnear, method, &*prices)
+            .await
+            .map_err(|e| {
+                let log = DEFAULT.new(o!("function" => "export_tax_lots"));
+                warn!(log, "failed to compute tax lots"; "error" => %e);
+                Status::internal("internal error")
+            })?;
+
+        Ok(Response::new(ExportTaxLotsResponse {
+            csv: to_csv(&events),
+            row_count: u32::try_from(events.len()).unwrap_or(u32::MAX),
+        }))
+    }
 }
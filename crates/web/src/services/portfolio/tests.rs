@@ -93,6 +93,97 @@ fn test_evaluation_period_to_proto_tokens_with_none() {
     assert_eq!(proto.selected_tokens, vec!["a", "b"]);
 }
 
+#[test]
+fn test_token_pnl_to_proto() {
+    use bigdecimal::BigDecimal;
+
+    let proto = token_pnl_to_proto(TokenPnl {
+        token: "usdc.near".parse().unwrap(),
+        cost_basis: YoctoValue::from_yocto(BigDecimal::from(1_000)),
+        realized_pnl: BigDecimal::from(-25),
+        unrealized_pnl: None,
+    });
+    assert_eq!(proto.token, "usdc.near");
+    assert_eq!(proto.cost_basis, "1000");
+    assert_eq!(proto.realized_pnl, "-25");
+    assert_eq!(proto.unrealized_pnl, None);
+}
+
+fn tax_lots_request(start: &str, end: &str, method: &str, prices: &str) -> ExportTaxLotsRequest {
+    ExportTaxLotsRequest {
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        method: method.to_string(),
+        fiat_prices_csv: prices.to_string(),
+    }
+}
+
+#[test]
+fn test_parse_tax_lot_range() {
+    let ok = parse_tax_lot_range(&tax_lots_request("2025-01-01", "2025-12-31", "", ""));
+    assert!(ok.is_ok());
+    for (start, end) in [
+        ("2025-12-31", "2025-01-01"),
+        ("2025-01-01", "2026-01-02"),
+        ("2025/01/01", "2025-12-31"),
+    ] {
+        let err = parse_tax_lot_range(&tax_lots_request(start, end, "", "")).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument, "{start}..{end}");
+    }
+}
+
+#[test]
+fn test_parse_lot_method_and_fiat_prices() {
+    assert_eq!(parse_lot_method("").unwrap(), LotMethod::Fifo);
+    assert_eq!(
+        parse_lot_method("average_cost").unwrap(),
+        LotMethod::AverageCost
+    );
+    assert_eq!(
+        parse_lot_method("lifo").unwrap_err().code(),
+        tonic::Code::InvalidArgument
+    );
+
+    let at = NaiveDateTime::parse_from_str("2025-01-02 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+    assert_eq!(parse_fiat_prices("  ").unwrap().near_price(at), None);
+    assert_eq!(
+        parse_fiat_prices("2025-01-01,3.5").unwrap().near_price(at),
+        Some("3.5".parse().unwrap())
+    );
+    assert_eq!(
+        parse_fiat_prices("2025-01-01,abc").err().unwrap().code(),
+        tonic::Code::InvalidArgument
+    );
+}
+
+#[tokio::test]
+async fn test_export_tax_lots_rejects_missing_auth() {
+    let svc = PortfolioServiceImpl;
+    let result = svc
+        .export_tax_lots(Request::new(tax_lots_request(
+            "2025-01-01",
+            "2025-12-31",
+            "",
+            "",
+        )))
+        .await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_export_tax_lots_validates_before_querying() {
+    let svc = PortfolioServiceImpl;
+    let result = svc
+        .export_tax_lots(reader_request(tax_lots_request(
+            "2025-01-01",
+            "2025-12-31",
+            "lifo",
+            "",
+        )))
+        .await;
+    assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+}
+
 #[tokio::test]
 async fn test_get_portfolio_holdings_rejects_missing_auth() {
     let svc = PortfolioServiceImpl;
@@ -0,0 +1,138 @@
+use crate::proto::reconciliation_service_server::ReconciliationService;
+use crate::proto::{
+    GetReconciliationRequest, GetReconciliationResponse, ListReconciliationsRequest,
+    ListReconciliationsResponse,
+};
+use crate::services::auth::require_reader;
+use logging::{DEFAULT, o, warn};
+use persistence::holding_reconciliation::{
+    DbHoldingReconciliation, HoldingAdjustment, HoldingReconciliation, TokenReconciliation,
+};
+use tonic::{Request, Response, Status};
+
+fn naive_to_timestamp(dt: chrono::NaiveDateTime) -> prost_types::Timestamp {
+    let utc = dt.and_utc();
+    prost_types::Timestamp {
+        seconds: utc.timestamp(),
+        nanos: 0,
+    }
+}
+
+fn token_to_proto(token: TokenReconciliation) -> crate::proto::TokenReconciliation {
+    crate::proto::TokenReconciliation {
+        token: token.token.to_string(),
+        decimals: u32::from(token.decimals),
+        expected: token.expected.to_string(),
+        ref_deposit: token.ref_deposit.to_string(),
+        wallet_balance: token.wallet_balance.to_string(),
+        difference: token.difference.to_string(),
+        discrepancy: token.discrepancy,
+    }
+}
+
+fn reconciliation_to_proto(
+    record: DbHoldingReconciliation,
+) -> anyhow::Result<crate::proto::Reconciliation> {
+    let tokens = record
+        .parse_tokens()?
+        .into_iter()
+        .map(token_to_proto)
+        .collect();
+
+    Ok(crate::proto::Reconciliation {
+        id: record.id,
+        evaluation_period_id: record.evaluation_period_id,
+        baseline_holding_id: record.baseline_holding_id,
+        checked_at: Some(naive_to_timestamp(record.checked_at)),
+        discrepancy_count: record.discrepancy_count,
+        adjusted: record.adjusted,
+        tokens,
+    })
+}
+
+fn adjustment_to_proto(adjustment: HoldingAdjustment) -> crate::proto::HoldingAdjustment {
+    crate::proto::HoldingAdjustment {
+        token: adjustment.token,
+        amount: adjustment.amount.to_string(),
+        created_at: Some(naive_to_timestamp(adjustment.created_at)),
+    }
+}
+
+pub struct ReconciliationServiceImpl;
+
+#[cfg(test)]
+mod tests;
+
+#[tonic::async_trait]
+impl ReconciliationService for ReconciliationServiceImpl {
+    async fn list_reconciliations(
+        &self,
+        request: Request<ListReconciliationsRequest>,
+    ) -> Result<Response<ListReconciliationsResponse>, Status> {
+        require_reader(&request)?;
+        let req = request.get_ref();
+        let page = i64::from(req.page.max(0));
+        let page_size = i64::from(req.page_size.clamp(1, 200));
+        let log = DEFAULT.new(o!("function" => "list_reconciliations"));
+
+        let (records, total_count) = tokio::try_join!(
+            HoldingReconciliation::list_recent_async(page, page_size),
+            HoldingReconciliation::count_async(),
+        )
+        .map_err(|e| {
+            warn!(log, "failed to get reconciliations"; "error" => %e);
+            Status::internal("internal error")
+        })?;
+
+        let reconciliations = records
+            .into_iter()
+            .map(reconciliation_to_proto)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|e| {
+                warn!(log, "failed to convert reconciliation"; "error" => %e);
+                Status::internal("internal error")
+            })?;
+
+        Ok(Response::new(ListReconciliationsResponse {
+            reconciliations,
+            total_count,
+        }))
+    }
+
+    async fn get_reconciliation(
+        &self,
+        request: Request<GetReconciliationRequest>,
+    ) -> Result<Response<GetReconciliationResponse>, Status> {
+        require_reader(&request)?;
+        let id = request.get_ref().id;
+        let log = DEFAULT.new(o!("function" => "get_reconciliation", "id" => id));
+
+        let record = HoldingReconciliation::get_by_id_async(id)
+            .await
+            .map_err(|e| {
+                warn!(log, "failed to get reconciliation"; "error" => %e);
+                Status::internal("internal error")
+            })?
+            .ok_or_else(|| Status::not_found(format!("reconciliation not found: {id}")))?;
+
+        let adjustments = HoldingReconciliation::get_adjustments_async(id)
+            .await
+            .map_err(|e| {
+                warn!(log, "failed to get adjustments"; "error" => %e);
+                Status::internal("internal error")
+            })?
+            .into_iter()
+            .map(adjustment_to_proto)
+            .collect();
+
+        let reconciliation = reconciliation_to_proto(record).map_err(|e| {
+            warn!(log, "failed to convert reconciliation"; "error" => %e);
+            Status::internal("internal error")
+        })?;
+
+        Ok(Response::new(GetReconciliationResponse {
+            reconciliation: Some(reconciliation),
+            adjustments,
+        }))
+    }
+}
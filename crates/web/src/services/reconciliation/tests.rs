@@ -0,0 +1,91 @@
+use super::*;
+use bigdecimal::BigDecimal;
+use common::types::{Email, Role, TokenSmallestUnits};
+use grpc_auth::AuthenticatedUser;
+
+fn request_as<T>(body: T, role: Role) -> Request<T> {
+    let mut req = Request::new(body);
+    req.extensions_mut().insert(AuthenticatedUser::new(
+        Email::new("tester@example.com").unwrap(),
+        role,
+    ));
+    req
+}
+
+#[test]
+fn test_reconciliation_to_proto_formats_amounts() {
+    let checked_at = chrono::Utc::now().naive_utc();
+    let tokens = vec![TokenReconciliation {
+        token: "usdt.tether-token.near".parse().unwrap(),
+        decimals: 6,
+        expected: BigDecimal::from(1_000_000),
+        ref_deposit: TokenSmallestUnits::from_u128(900_000),
+        wallet_balance: TokenSmallestUnits::from_u128(50_000),
+        difference: BigDecimal::from(-50_000),
+        discrepancy: true,
+    }];
+    let record = DbHoldingReconciliation {
+        id: 3,
+        evaluation_period_id: "eval_test".to_string(),
+        baseline_holding_id: 11,
+        checked_at,
+        discrepancy_count: 1,
+        adjusted: false,
+        tokens: serde_json::to_value(&tokens).unwrap(),
+    };
+
+    let proto = reconciliation_to_proto(record).unwrap();
+    assert_eq!(proto.id, 3);
+    assert_eq!(proto.baseline_holding_id, 11);
+    assert_eq!(
+        proto.checked_at.unwrap().seconds,
+        checked_at.and_utc().timestamp()
+    );
+    assert_eq!(proto.tokens.len(), 1);
+    let token = &proto.tokens[0];
+    assert_eq!(token.token, "usdt.tether-token.near");
+    assert_eq!(token.decimals, 6);
+    assert_eq!(token.expected, "1000000");
+    assert_eq!(token.ref_deposit, "900000");
+    assert_eq!(token.wallet_balance, "50000");
+    assert_eq!(token.difference, "-50000");
+    assert!(token.discrepancy);
+}
+
+#[test]
+fn test_reconciliation_to_proto_rejects_malformed_tokens() {
+    let record = DbHoldingReconciliation {
+        id: 1,
+        evaluation_period_id: "eval_test".to_string(),
+        baseline_holding_id: 1,
+        checked_at: chrono::Utc::now().naive_utc(),
+        discrepancy_count: 0,
+        adjusted: false,
+        tokens: serde_json::json!({"not": "a list"}),
+    };
+    assert!(reconciliation_to_proto(record).is_err());
+}
+
+#[tokio::test]
+async fn test_list_reconciliations_requires_auth() {
+    let err = ReconciliationServiceImpl
+        .list_reconciliations(Request::new(ListReconciliationsRequest {
+            page: 0,
+            page_size: 10,
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_get_reconciliation_not_found() {
+    let err = ReconciliationServiceImpl
+        .get_reconciliation(request_as(
+            GetReconciliationRequest { id: -1 },
+            Role::Reader,
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::NotFound);
+}
@@ -0,0 +1,25 @@
+CREATE TABLE job_runs (
+    id           SERIAL      PRIMARY KEY,
+    job_name     VARCHAR     NOT NULL,
+    -- SYNC: allowed values must match JobRunTrigger in
+    -- crates/persistence/src/job_run.rs
+    trigger      VARCHAR     NOT NULL
+        CHECK (trigger IN ('schedule', 'catch_up', 'manual')),
+    -- cron 上の予定時刻。manual 実行では NULL
+    scheduled_at TIMESTAMP,
+    started_at   TIMESTAMP   NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at  TIMESTAMP,
+    -- SYNC: allowed values must match JobRunStatus in
+    -- crates/persistence/src/job_run.rs
+    status       VARCHAR     NOT NULL
+        CHECK (status IN ('running', 'succeeded', 'failed', 'skipped', 'abandoned')),
+    error        TEXT
+);
+
+CREATE INDEX idx_job_runs_job_started
+    ON job_runs (job_name, started_at DESC);
+
+-- catch-up 判定で「最後に予定時刻ベースで実行した run」を引くためのインデックス
+CREATE INDEX idx_job_runs_job_scheduled
+    ON job_runs (job_name, scheduled_at DESC)
+    WHERE scheduled_at IS NOT NULL;
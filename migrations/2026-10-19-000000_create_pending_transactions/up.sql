@@ -0,0 +1,36 @@
+-- スワップの write-ahead journal
+-- exec_contract の前に intent を記録し、ブロードキャスト直後に tx hash を記録する。
+-- trade_transactions への記録と同時に completed になる。起動時の reconciler が
+-- 未解決（intent / sent）の行を on-chain の結果で completed / voided に確定させる。
+CREATE TABLE pending_transactions (
+    id                   SERIAL          PRIMARY KEY,
+    trade_batch_id       VARCHAR         NOT NULL,
+    evaluation_period_id VARCHAR         NOT NULL
+        REFERENCES evaluation_periods(period_id) ON DELETE CASCADE,
+    sender_account       VARCHAR         NOT NULL,
+    from_token           VARCHAR         NOT NULL,
+    from_amount          NUMERIC(39, 0)  NOT NULL,
+    to_token             VARCHAR         NOT NULL,
+    -- 送信時点の AMM 理論出力（trade_transactions.to_amount になる）
+    estimated_to_amount  NUMERIC(39, 0)  NOT NULL,
+    -- ブロードキャスト後に設定される
+    tx_hash              VARCHAR,
+    -- trade_transactions.tx_id として使う識別子
+    tx_id                VARCHAR,
+    -- SYNC: allowed values must match PendingTransactionStatus in
+    -- crates/persistence/src/pending_transaction.rs
+    status               VARCHAR         NOT NULL
+        CHECK (status IN ('intent', 'sent', 'completed', 'voided')),
+    created_at           TIMESTAMP       NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolved_at          TIMESTAMP,
+    error                TEXT,
+    CHECK (status = 'intent' OR status = 'voided' OR tx_hash IS NOT NULL)
+);
+
+-- reconciler が未解決の行だけを引くための部分インデックス
+CREATE INDEX idx_pending_transactions_unresolved
+    ON pending_transactions (created_at)
+    WHERE status IN ('intent', 'sent');
+
+CREATE INDEX idx_pending_transactions_batch_id
+    ON pending_transactions (trade_batch_id);
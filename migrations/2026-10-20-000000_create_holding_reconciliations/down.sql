@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS holding_adjustments;
+DROP TABLE IF EXISTS holding_reconciliations;
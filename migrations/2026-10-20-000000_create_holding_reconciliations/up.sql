@@ -0,0 +1,34 @@
+-- on-chain 保有量（REF deposit + wallet FT 残高）と DB から導いた保有量の突合結果
+CREATE TABLE holding_reconciliations (
+    id                   SERIAL      PRIMARY KEY,
+    evaluation_period_id VARCHAR     NOT NULL
+        REFERENCES evaluation_periods(period_id) ON DELETE CASCADE,
+    -- 基準にした portfolio_holdings の行
+    baseline_holding_id  INTEGER     NOT NULL
+        REFERENCES portfolio_holdings(id) ON DELETE CASCADE,
+    checked_at           TIMESTAMP   NOT NULL,
+    discrepancy_count    INTEGER     NOT NULL,
+    -- 補正レコード（holding_adjustments）を書き込んだか
+    adjusted             BOOLEAN     NOT NULL DEFAULT FALSE,
+    -- トークンごとの突合結果
+    tokens               JSONB       NOT NULL
+);
+
+CREATE INDEX idx_holding_reconciliations_checked_at
+    ON holding_reconciliations (checked_at DESC);
+
+-- 説明のつかない残高変化を DB 側に取り込むための補正レコード
+CREATE TABLE holding_adjustments (
+    id                   SERIAL          PRIMARY KEY,
+    reconciliation_id    INTEGER         NOT NULL
+        REFERENCES holding_reconciliations(id) ON DELETE CASCADE,
+    evaluation_period_id VARCHAR         NOT NULL
+        REFERENCES evaluation_periods(period_id) ON DELETE CASCADE,
+    token                VARCHAR         NOT NULL,
+    -- 最小単位での増減（負なら DB 上の想定より on-chain が少ない）
+    amount               NUMERIC(40, 0)  NOT NULL,
+    created_at           TIMESTAMP       NOT NULL
+);
+
+CREATE INDEX idx_holding_adjustments_period
+    ON holding_adjustments (evaluation_period_id, created_at);
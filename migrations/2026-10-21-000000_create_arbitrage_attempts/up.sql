@@ -0,0 +1,27 @@
+-- 裁定取引の試行ごとの損益台帳
+-- 起点トークン（wrap.near）で入って同じトークンで戻るため、量はすべて起点トークンの最小単位。
+CREATE TABLE arbitrage_attempts (
+    id               SERIAL          PRIMARY KEY,
+    attempted_at     TIMESTAMP       NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    start_token      VARCHAR         NOT NULL,
+    -- [{"pool_id": 1, "token_in": "...", "token_out": "..."}, ...]
+    path             JSONB           NOT NULL,
+    input_amount     NUMERIC(39, 0)  NOT NULL,
+    -- 送信時点の AMM 理論出力
+    estimated_output NUMERIC(39, 0)  NOT NULL,
+    -- on-chain の実績（取得できなかった場合は NULL）
+    actual_output    NUMERIC(39, 0),
+    -- トランザクションと全 receipt で消費したガス
+    gas_burnt        BIGINT,
+    -- ガスの対価として燃やされた NEAR（yocto）
+    gas_cost         NUMERIC(39, 0),
+    tx_hash          VARCHAR,
+    -- SYNC: allowed values must match ArbitrageAttemptStatus in
+    -- crates/persistence/src/arbitrage_attempt.rs
+    status           VARCHAR         NOT NULL
+        CHECK (status IN ('success', 'failure')),
+    error            TEXT
+);
+
+CREATE INDEX idx_arbitrage_attempts_attempted_at
+    ON arbitrage_attempts (attempted_at);
@@ -0,0 +1,2 @@
+ALTER TABLE trade_transactions DROP COLUMN gas_cost;
+ALTER TABLE trade_transactions DROP COLUMN gas_burnt;
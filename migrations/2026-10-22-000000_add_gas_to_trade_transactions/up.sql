@@ -0,0 +1,3 @@
+-- スワップごとに燃やされたガスと、その NEAR 換算（yoctoNEAR）
+ALTER TABLE trade_transactions ADD COLUMN gas_burnt BIGINT;
+ALTER TABLE trade_transactions ADD COLUMN gas_cost NUMERIC;
@@ -0,0 +1 @@
+ALTER TABLE portfolio_holdings DROP COLUMN token_pnl;
@@ -0,0 +1,2 @@
+-- トークン別の取得原価と実現・含み損益（trade::pnl が保有量の記録時に計算する）
+ALTER TABLE portfolio_holdings ADD COLUMN token_pnl JSONB;